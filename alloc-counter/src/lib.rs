@@ -0,0 +1,84 @@
+//! A global allocator that counts the allocations made by each thread, for
+//! the tests and benches that guard against extra allocations.
+//!
+//! Install it in the test or bench binary with
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static GLOBAL: alloc_counter::CountingAlloc = alloc_counter::CountingAlloc;
+//! ```
+//!
+//! then measure a closure with [`counting`]. Counts are kept per thread, so
+//! tests running in parallel don't affect each other.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// Allocations and reallocations made while running a closure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub allocs: usize,
+    pub reallocs: usize,
+}
+
+impl Counts {
+    /// Allocations and reallocations together.
+    pub fn total(&self) -> usize {
+        self.allocs + self.reallocs
+    }
+}
+
+/// Counts allocations and reallocations, passing them on to the system
+/// allocator.
+pub struct CountingAlloc;
+
+thread_local! {
+    static COUNTS: Cell<Counts> = const {
+        Cell::new(Counts {
+            allocs: 0,
+            reallocs: 0,
+        })
+    };
+}
+
+fn count(f: impl FnOnce(&mut Counts)) {
+    // The thread local is unavailable while its thread is being torn down.
+    let _ = COUNTS.try_with(|counts| {
+        let mut c = counts.get();
+        f(&mut c);
+        counts.set(c);
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(|c| c.allocs += 1);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(|c| c.reallocs += 1);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// The allocations made by the current thread while running `f`, along with
+/// what `f` returned. Only meaningful when [`CountingAlloc`] is the global
+/// allocator.
+pub fn counting<T>(f: impl FnOnce() -> T) -> (Counts, T) {
+    let before = COUNTS.with(Cell::get);
+    let value = f();
+    let after = COUNTS.with(Cell::get);
+
+    let counts = Counts {
+        allocs: after.allocs - before.allocs,
+        reallocs: after.reallocs - before.reallocs,
+    };
+    (counts, value)
+}
@@ -1,8 +1,23 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use fastanvil::JavaChunk;
+use fastanvil::{Chunk, ChunkPalette, JavaChunk, LazyJavaChunk};
+use fastnbt::{borrow::BorrowedValue, Value};
+use serde::Deserialize;
 
 const CHUNK_RAW: &[u8] = include_bytes!("../resources/chunk.nbt");
 
+// A 1.18 chunk, where every section has a block and biome palette full of
+// namespaced names and property strings.
+const PALETTE_CHUNK_RAW: &[u8] = include_bytes!("../resources/21w44a-test1.nbt");
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct StatusOnly<'a> {
+    #[serde(rename = "DataVersion")]
+    data_version: i32,
+    #[serde(rename = "Status")]
+    status: &'a str,
+}
+
 pub fn fastnbt_benchmark(c: &mut Criterion) {
     c.bench_function("chunk", |b| {
         b.iter(|| {
@@ -10,7 +25,112 @@ pub fn fastnbt_benchmark(c: &mut Criterion) {
             black_box(chunk);
         });
     });
+
+    c.bench_function("palette heavy chunk", |b| {
+        b.iter(|| {
+            let chunk = JavaChunk::from_bytes(PALETTE_CHUNK_RAW).unwrap();
+            black_box(chunk);
+        });
+    });
+
+    // Only the palettes, as used to search for blocks.
+    c.bench_function("chunk palette only", |b| {
+        b.iter(|| {
+            let palette = ChunkPalette::from_bytes(CHUNK_RAW).unwrap();
+            black_box(palette);
+        });
+    });
+
+    c.bench_function("palette heavy chunk palette only", |b| {
+        b.iter(|| {
+            let palette = ChunkPalette::from_bytes(PALETTE_CHUNK_RAW).unwrap();
+            black_box(palette);
+        });
+    });
+
+    // Only a couple of fields, as when scanning a world. Everything else in
+    // the chunk is skipped over.
+    c.bench_function("chunk status only", |b| {
+        b.iter(|| {
+            let chunk: StatusOnly = fastnbt::from_bytes(PALETTE_CHUNK_RAW).unwrap();
+            black_box(chunk);
+        });
+    });
+
+    // Compare runs with and without the `fast-hash` feature to see the effect
+    // of the hasher used for compounds.
+    c.bench_function("chunk to value", |b| {
+        b.iter(|| {
+            let chunk: Value = fastnbt::from_bytes(CHUNK_RAW).unwrap();
+            black_box(chunk);
+        });
+    });
+
+    c.bench_function("palette heavy chunk to value", |b| {
+        b.iter(|| {
+            let chunk: Value = fastnbt::from_bytes(PALETTE_CHUNK_RAW).unwrap();
+            black_box(chunk);
+        });
+    });
+
+    c.bench_function("chunk to borrowed value", |b| {
+        b.iter(|| {
+            let chunk: BorrowedValue = fastnbt::from_bytes(CHUNK_RAW).unwrap();
+            black_box(chunk);
+        });
+    });
+
+    c.bench_function("palette heavy chunk to borrowed value", |b| {
+        b.iter(|| {
+            let chunk: BorrowedValue = fastnbt::from_bytes(PALETTE_CHUNK_RAW).unwrap();
+            black_box(chunk);
+        });
+    });
+}
+
+// Lazy chunks only decode the sections that are looked at, so should win
+// easily when a single block is wanted, and lose a little when every block is.
+pub fn lazy_benchmark(c: &mut Criterion) {
+    c.bench_function("single block", |b| {
+        b.iter(|| {
+            let chunk = JavaChunk::from_bytes(PALETTE_CHUNK_RAW).unwrap();
+            black_box(chunk.block(8, 64, 8).map(|b| b.name().len()));
+        });
+    });
+
+    c.bench_function("lazy single block", |b| {
+        b.iter(|| {
+            let chunk = LazyJavaChunk::from_bytes(PALETTE_CHUNK_RAW).unwrap();
+            black_box(chunk.block(8, 64, 8).map(|b| b.name().len()));
+        });
+    });
+
+    c.bench_function("every block", |b| {
+        b.iter(|| {
+            let chunk = JavaChunk::from_bytes(PALETTE_CHUNK_RAW).unwrap();
+            black_box(count_blocks(&chunk));
+        });
+    });
+
+    c.bench_function("lazy every block", |b| {
+        b.iter(|| {
+            let chunk = LazyJavaChunk::from_bytes(PALETTE_CHUNK_RAW).unwrap();
+            black_box(count_blocks(&chunk));
+        });
+    });
+}
+
+fn count_blocks(chunk: &impl Chunk) -> usize {
+    let mut count = 0;
+    for y in chunk.y_range() {
+        for z in 0..16 {
+            for x in 0..16 {
+                count += chunk.block(x, y, z).is_some() as usize;
+            }
+        }
+    }
+    count
 }
 
-criterion_group!(benches, fastnbt_benchmark);
+criterion_group!(benches, fastnbt_benchmark, lazy_benchmark);
 criterion_main!(benches);
@@ -0,0 +1,30 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fastnbt::Value;
+
+const CHUNK_RAW: &[u8] = include_bytes!("../resources/chunk.nbt");
+
+// A 1.18 chunk, where every section has a block and biome palette full of
+// namespaced names and property strings. Nearly all of the strings written are
+// compound keys and ASCII values.
+const PALETTE_CHUNK_RAW: &[u8] = include_bytes!("../resources/21w44a-test1.nbt");
+
+pub fn serialize_benchmark(c: &mut Criterion) {
+    let chunk: Value = fastnbt::from_bytes(CHUNK_RAW).unwrap();
+    c.bench_function("chunk value to bytes", |b| {
+        b.iter(|| {
+            let bytes = fastnbt::to_bytes(&chunk).unwrap();
+            black_box(bytes);
+        });
+    });
+
+    let chunk: Value = fastnbt::from_bytes(PALETTE_CHUNK_RAW).unwrap();
+    c.bench_function("palette heavy chunk value to bytes", |b| {
+        b.iter(|| {
+            let bytes = fastnbt::to_bytes(&chunk).unwrap();
+            black_box(bytes);
+        });
+    });
+}
+
+criterion_group!(benches, serialize_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,195 @@
+//! Guards against performance regressions in chunk parsing. Unlike the other
+//! benches this measures nothing for comparison, it fails if parsing gets
+//! slower than a floor or allocates more than a ceiling. It only runs when
+//! `FASTANVIL_PERF_GUARD` is set, and never in an unoptimised build, so
+//! `cargo test --all-targets` skips it. Run it with
+//!
+//! ```sh
+//! FASTANVIL_PERF_GUARD=1 cargo bench -p fastanvil --bench perf_guard
+//! ```
+//!
+//! The guards are below, in `GUARDS`, so changing them shows up in review.
+//! Throughput floors are around a third of what a typical desktop manages, so
+//! that only real regressions fail. Slow machines, eg shared CI runners, can
+//! scale every floor by setting `FASTANVIL_PERF_FLOOR_SCALE`, eg to `0.5`.
+//! Allocation ceilings do not depend on the machine, so are never scaled.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use alloc_counter::{counting, CountingAlloc};
+use fastanvil::{ChunkPalette, JavaChunk};
+use fastnbt::borrow::LongArray;
+use serde::Deserialize;
+
+// A 1.18 chunk, where every section has a block and biome palette.
+const CHUNK: &[u8] = include_bytes!("../resources/21w44a-test1.nbt");
+
+/// How many times each guard parses the chunk.
+const ITERATIONS: u32 = 2000;
+
+struct Guard {
+    name: &'static str,
+    /// The fewest chunks a second the parse must manage.
+    min_per_sec: f64,
+    /// The most allocations and reallocations a single parse may make, if
+    /// checked.
+    max_allocs: Option<usize>,
+    parse: fn(&[u8]),
+}
+
+const GUARDS: &[Guard] = &[
+    Guard {
+        name: "borrowed chunk",
+        min_per_sec: 4_000.0,
+        // The vecs of sections and palettes, and nothing else.
+        max_allocs: Some(27),
+        parse: |data| {
+            let chunk: BorrowedChunk = fastnbt::from_bytes(data).unwrap();
+            black_box(chunk.palette_len());
+        },
+    },
+    Guard {
+        name: "chunk palette",
+        min_per_sec: 2_500.0,
+        max_allocs: None,
+        parse: |data| {
+            black_box(ChunkPalette::from_bytes(data).unwrap());
+        },
+    },
+    Guard {
+        name: "java chunk",
+        min_per_sec: 2_000.0,
+        max_allocs: None,
+        parse: |data| {
+            black_box(JavaChunk::from_bytes(data).unwrap());
+        },
+    },
+];
+
+/// The sections of a 1.18 chunk, borrowing everything it can from the input
+/// rather than copying it.
+#[derive(Deserialize)]
+struct BorrowedChunk<'a> {
+    #[serde(borrow)]
+    sections: Vec<BorrowedSection<'a>>,
+}
+
+impl BorrowedChunk<'_> {
+    fn palette_len(&self) -> usize {
+        self.sections
+            .iter()
+            .filter_map(|s| s.block_states.as_ref())
+            .map(|s| s.palette.len())
+            .sum()
+    }
+}
+
+#[derive(Deserialize)]
+struct BorrowedSection<'a> {
+    #[serde(rename = "Y")]
+    _y: i8,
+    #[serde(borrow)]
+    block_states: Option<BorrowedStates<'a>>,
+}
+
+#[derive(Deserialize)]
+struct BorrowedStates<'a> {
+    #[serde(borrow)]
+    palette: Vec<BorrowedBlock<'a>>,
+    #[serde(rename = "data", borrow)]
+    _data: Option<LongArray<'a>>,
+}
+
+#[derive(Deserialize)]
+struct BorrowedBlock<'a> {
+    #[serde(rename = "Name")]
+    _name: &'a str,
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// The allocations made by a single call of `f`.
+fn count_allocs(f: impl FnOnce()) -> usize {
+    counting(f).0.total()
+}
+
+/// The time taken by each call of `f`, the best of a few runs of
+/// `ITERATIONS` calls to smooth out noise from the rest of the machine.
+fn time_per_call(f: impl Fn()) -> Duration {
+    // Warm up caches and the allocator.
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                f();
+            }
+            start.elapsed() / ITERATIONS
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    if std::env::var_os("FASTANVIL_PERF_GUARD").is_none() {
+        println!("perf_guard: skipped, set FASTANVIL_PERF_GUARD to run it");
+        return;
+    }
+    if cfg!(debug_assertions) {
+        // Floors are for optimised code, debug builds are far slower.
+        println!("perf_guard: skipped in an unoptimised build");
+        return;
+    }
+
+    // `cargo bench` passes `--bench`, and a filter if given one. Guards not
+    // matching the filter are skipped.
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with('-'));
+    let scale = match std::env::var("FASTANVIL_PERF_FLOOR_SCALE") {
+        Ok(scale) => scale
+            .parse::<f64>()
+            .expect("FASTANVIL_PERF_FLOOR_SCALE should be a number"),
+        Err(_) => 1.0,
+    };
+
+    let mut failures = vec![];
+
+    for guard in GUARDS {
+        if filter
+            .as_ref()
+            .is_some_and(|f| !guard.name.contains(f.as_str()))
+        {
+            continue;
+        }
+
+        let per_call = time_per_call(|| (guard.parse)(CHUNK));
+        let per_sec = 1.0 / per_call.as_secs_f64();
+        let floor = guard.min_per_sec * scale;
+        println!("{}: {per_sec:.0} chunks/s (floor {floor:.0})", guard.name);
+        if per_sec < floor {
+            failures.push(format!(
+                "{}: {per_sec:.0} chunks/s is below the floor of {floor:.0}",
+                guard.name
+            ));
+        }
+
+        if let Some(max) = guard.max_allocs {
+            let allocs = count_allocs(|| (guard.parse)(CHUNK));
+            println!("{}: {allocs} allocations (ceiling {max})", guard.name);
+            if allocs > max {
+                failures.push(format!(
+                    "{}: {allocs} allocations is above the ceiling of {max}",
+                    guard.name
+                ));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,142 @@
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fastanvil::{MmapRegionLoader, RCoord, Region, RegionFileLoader, RegionLoader};
+use serde::Deserialize;
+
+const CHUNKS: &[&[u8]] = &[
+    include_bytes!("../resources/chunk.nbt"),
+    include_bytes!("../resources/1.17.1.chunk"),
+    include_bytes!("../resources/21w44a-test1.nbt"),
+    include_bytes!("../resources/etho.chunk"),
+];
+
+/// Write a region full of chunks to a temporary directory.
+fn region_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fastanvil-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(dir.join("r.0.0.mca"))
+        .unwrap();
+
+    let mut region = Region::new(file).unwrap();
+    for z in 0..32 {
+        for x in 0..32 {
+            region
+                .write_chunk(x, z, CHUNKS[(x + z) % CHUNKS.len()])
+                .unwrap();
+        }
+    }
+
+    dir
+}
+
+/// What a scan of a world wants from each chunk. These are at the root of
+/// chunks in the 1.18 layout. Older chunks have them in `Level` instead, so
+/// for those the whole chunk is walked whichever way it is read.
+const SCAN_PATHS: &[&str] = &["Status", "xPos", "zPos"];
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Scan<'a> {
+    #[serde(rename = "Status")]
+    status: Option<&'a str>,
+    #[serde(rename = "xPos")]
+    x: Option<i32>,
+    #[serde(rename = "zPos")]
+    z: Option<i32>,
+}
+
+/// Chunk coordinates visited in a fixed scattered order, as a tile server
+/// might request them.
+fn scattered() -> Vec<(usize, usize)> {
+    (0..1024)
+        .map(|i| ((i * 7) % 32, (i * 13 / 32) % 32))
+        .collect()
+}
+
+pub fn region_benchmark(c: &mut Criterion) {
+    let dir = region_dir();
+    let order = scattered();
+
+    let file_loader = RegionFileLoader::new(dir.clone());
+    c.bench_function("region read chunks file new buffers", |b| {
+        b.iter(|| {
+            let mut region = file_loader.region(RCoord(0), RCoord(0)).unwrap();
+            for &(x, z) in &order {
+                black_box(region.read_chunk(x, z).unwrap());
+            }
+        });
+    });
+
+    c.bench_function("region read chunks file", |b| {
+        let mut buf = vec![];
+        b.iter(|| {
+            let mut region = file_loader.region(RCoord(0), RCoord(0)).unwrap();
+            for &(x, z) in &order {
+                region.read_chunk_into(x, z, &mut buf).unwrap();
+                black_box(&buf);
+            }
+        });
+    });
+
+    // SAFETY: Nothing modifies the region while the benchmark runs.
+    let mmap_loader = unsafe { MmapRegionLoader::new(dir.clone()) };
+    c.bench_function("region read chunks mmap", |b| {
+        let mut buf = vec![];
+        b.iter(|| {
+            let mut region = mmap_loader.region(RCoord(0), RCoord(0)).unwrap();
+            for &(x, z) in &order {
+                region.read_chunk_into(x, z, &mut buf).unwrap();
+                black_box(&buf);
+            }
+        });
+    });
+
+    c.bench_function("region decompress chunks mmap", |b| {
+        let mut buf = vec![];
+        b.iter(|| {
+            let region = mmap_loader.region(RCoord(0), RCoord(0)).unwrap();
+            for &(x, z) in &order {
+                region.decompress_chunk_into(x, z, &mut buf).unwrap();
+                black_box(&buf);
+            }
+        });
+    });
+
+    // A few values from every chunk, as a struct and with `extract`. The
+    // chunks are decompressed up front so that only the parsing is measured.
+    let mut region = file_loader.region(RCoord(0), RCoord(0)).unwrap();
+    let chunks: Vec<_> = order
+        .iter()
+        .map(|&(x, z)| region.read_chunk(x, z).unwrap().unwrap())
+        .collect();
+
+    c.bench_function("region scan struct", |b| {
+        b.iter(|| {
+            for chunk in &chunks {
+                let scan: Scan = fastnbt::from_bytes(chunk).unwrap();
+                black_box(scan);
+            }
+        });
+    });
+
+    c.bench_function("region scan extract", |b| {
+        b.iter(|| {
+            for chunk in &chunks {
+                black_box(fastnbt::extract(chunk, SCAN_PATHS).unwrap());
+            }
+        });
+    });
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+criterion_group!(benches, region_benchmark);
+criterion_main!(benches);
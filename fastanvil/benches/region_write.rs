@@ -0,0 +1,57 @@
+use std::io::Cursor;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fastanvil::{CompressionLevel, CompressionPolicy, CompressionScheme, Region};
+
+const CHUNKS: &[&[u8]] = &[
+    include_bytes!("../resources/chunk.nbt"),
+    include_bytes!("../resources/1.17.1.chunk"),
+    include_bytes!("../resources/21w44a-test1.nbt"),
+    include_bytes!("../resources/etho.chunk"),
+];
+
+/// Write a region full of chunks to memory, returning its size.
+fn write_region(policy: CompressionPolicy) -> usize {
+    let mut region = Region::new(Cursor::new(vec![]))
+        .unwrap()
+        .with_compression(policy);
+
+    for z in 0..32 {
+        for x in 0..32 {
+            region
+                .write_chunk(x, z, CHUNKS[(x + z) % CHUNKS.len()])
+                .unwrap();
+        }
+    }
+
+    region.into_inner().unwrap().into_inner().len()
+}
+
+pub fn region_write_benchmark(c: &mut Criterion) {
+    let policies = [
+        ("fastest", CompressionPolicy::default()),
+        (
+            "default",
+            CompressionPolicy::new(CompressionScheme::Zlib, CompressionLevel::Default),
+        ),
+        (
+            "best",
+            CompressionPolicy::new(CompressionScheme::Zlib, CompressionLevel::Best),
+        ),
+        (
+            "gzip",
+            CompressionPolicy::new(CompressionScheme::Gzip, CompressionLevel::Fastest),
+        ),
+        ("auto", CompressionPolicy::auto(CompressionLevel::Fastest)),
+        ("uncompressed", CompressionPolicy::uncompressed()),
+    ];
+
+    for (name, policy) in policies {
+        c.bench_function(&format!("region write {name}"), |b| {
+            b.iter(|| black_box(write_region(policy)));
+        });
+    }
+}
+
+criterion_group!(benches, region_write_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,207 @@
+use std::{collections::HashMap, path::Path};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fastanvil::tex::{assets_key, Blockstate, Model, Render, Renderer};
+
+const STAIRS_BLOCKSTATE: &str = include_str!("../resources/assets/blockstates/acacia_stairs.json");
+
+// Enough of the vanilla model hierarchy for stairs to resolve a top texture.
+const MODELS: &[(&str, &str)] = &[
+    ("minecraft:block/block", r#"{}"#),
+    (
+        "minecraft:block/stairs",
+        r##"{
+            "parent": "block/block",
+            "elements": [{
+                "from": [0, 0, 0],
+                "to": [16, 8, 16],
+                "faces": { "up": { "texture": "#top" } }
+            }]
+        }"##,
+    ),
+    (
+        "minecraft:block/inner_stairs",
+        r#"{ "parent": "minecraft:block/stairs" }"#,
+    ),
+    (
+        "minecraft:block/outer_stairs",
+        r#"{ "parent": "minecraft:block/stairs" }"#,
+    ),
+    (
+        "minecraft:block/acacia_stairs",
+        r#"{ "parent": "minecraft:block/stairs", "textures": { "top": "minecraft:block/acacia_planks" } }"#,
+    ),
+    (
+        "minecraft:block/acacia_stairs_inner",
+        r#"{ "parent": "minecraft:block/inner_stairs", "textures": { "top": "minecraft:block/acacia_planks" } }"#,
+    ),
+    (
+        "minecraft:block/acacia_stairs_outer",
+        r#"{ "parent": "minecraft:block/outer_stairs", "textures": { "top": "minecraft:block/acacia_planks" } }"#,
+    ),
+];
+
+fn renderer() -> Renderer {
+    let blockstates = [(
+        "minecraft:acacia_stairs".to_owned(),
+        serde_json::from_str::<Blockstate>(STAIRS_BLOCKSTATE).unwrap(),
+    )]
+    .into_iter()
+    .collect();
+
+    let models: HashMap<String, Model> = MODELS
+        .iter()
+        .map(|(name, json)| (name.to_string(), serde_json::from_str(json).unwrap()))
+        .collect();
+
+    let textures = [(
+        "minecraft:block/acacia_planks".to_owned(),
+        vec![0u8; 16 * 16 * 4],
+    )]
+    .into_iter()
+    .collect();
+
+    Renderer::new(blockstates, models, textures)
+}
+
+fn states() -> Vec<String> {
+    match serde_json::from_str(STAIRS_BLOCKSTATE).unwrap() {
+        Blockstate::Variants(variants) => variants.into_keys().collect(),
+        Blockstate::Multipart(_) => unreachable!(),
+    }
+}
+
+pub fn render_benchmark(c: &mut Criterion) {
+    let states = states();
+
+    // Every state looked up many times, as happens when rendering a world.
+    let mut uncached = renderer().with_cache_capacity(0);
+    c.bench_function("get_top uncached", |b| {
+        b.iter(|| {
+            for props in &states {
+                let tex = uncached.get_top("minecraft:acacia_stairs", props).unwrap();
+                black_box(tex);
+            }
+        });
+    });
+
+    let mut cached = renderer();
+    c.bench_function("get_top cached", |b| {
+        b.iter(|| {
+            for props in &states {
+                let tex = cached.top("minecraft:acacia_stairs", props).unwrap();
+                black_box(tex);
+            }
+        });
+    });
+}
+
+const CUBE_MODELS: &[(&str, &str)] = &[
+    ("minecraft:block/block", r#"{}"#),
+    (
+        "minecraft:block/cube",
+        r##"{
+            "parent": "block/block",
+            "elements": [{
+                "from": [0, 0, 0],
+                "to": [16, 16, 16],
+                "faces": { "up": { "texture": "#up" }, "down": { "texture": "#down" } }
+            }]
+        }"##,
+    ),
+    (
+        "minecraft:block/cube_all",
+        r##"{ "parent": "block/cube", "textures": { "up": "#all", "down": "#all" } }"##,
+    ),
+];
+
+/// Write the assets of a thousand cube blocks to files, as JSON like the
+/// vanilla assets. Textures are raw RGBA rather than PNG.
+fn write_many_blocks(dir: &Path) {
+    for sub in ["blockstates", "models", "textures"] {
+        std::fs::create_dir_all(dir.join(sub)).unwrap();
+    }
+
+    for (name, json) in CUBE_MODELS {
+        let file = name.strip_prefix("minecraft:block/").unwrap();
+        std::fs::write(dir.join("models").join(file), json).unwrap();
+    }
+
+    for i in 0..1000 {
+        let blockstate = format!(r#"{{"variants": {{"": {{"model": "minecraft:block/b{i}"}}}}}}"#);
+        let model = format!(
+            r#"{{"parent": "minecraft:block/cube_all", "textures": {{"all": "minecraft:block/b{i}"}}}}"#
+        );
+        let name = format!("b{i}");
+        std::fs::write(dir.join("blockstates").join(&name), blockstate).unwrap();
+        std::fs::write(dir.join("models").join(&name), model).unwrap();
+        std::fs::write(dir.join("textures").join(&name), vec![i as u8; 16 * 16 * 4]).unwrap();
+    }
+}
+
+/// Read every file in a directory, keyed by name with a prefix.
+fn read_dir<T>(dir: &Path, prefix: &str, parse: impl Fn(Vec<u8>) -> T) -> HashMap<String, T> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            (
+                format!("{prefix}{name}"),
+                parse(std::fs::read(&path).unwrap()),
+            )
+        })
+        .collect()
+}
+
+fn many_blocks_from_files(dir: &Path) -> Renderer {
+    fn json<T: serde::de::DeserializeOwned>(data: Vec<u8>) -> T {
+        serde_json::from_slice(&data).unwrap()
+    }
+
+    let blockstates: HashMap<String, Blockstate> =
+        read_dir(&dir.join("blockstates"), "minecraft:", json);
+    let models: HashMap<String, Model> = read_dir(&dir.join("models"), "minecraft:block/", json);
+    let textures = read_dir(&dir.join("textures"), "minecraft:block/", |data| data);
+
+    Renderer::new(blockstates, models, textures)
+}
+
+fn render_many_blocks(renderer: &mut Renderer) {
+    for i in 0..1000 {
+        black_box(renderer.get_top(&format!("minecraft:b{i}"), "").unwrap());
+    }
+}
+
+// Startup until every block has been rendered once, from the asset files and
+// from a bundle.
+pub fn startup_benchmark(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join(format!("fastanvil-bench-{}", std::process::id()));
+    write_many_blocks(&dir);
+
+    c.bench_function("many blocks from files", |b| {
+        b.iter(|| {
+            let mut renderer = many_blocks_from_files(&dir);
+            render_many_blocks(&mut renderer);
+            black_box(renderer)
+        });
+    });
+
+    let bundle = dir.join("assets.bundle");
+    let key = assets_key(&dir).unwrap();
+    many_blocks_from_files(&dir)
+        .save_bundle(&bundle, key)
+        .unwrap();
+    c.bench_function("many blocks from bundle", |b| {
+        b.iter(|| {
+            let mut renderer = Renderer::load_bundle(&bundle, key).unwrap();
+            render_many_blocks(&mut renderer);
+            black_box(renderer)
+        });
+    });
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+criterion_group!(benches, render_benchmark, startup_benchmark);
+criterion_main!(benches);
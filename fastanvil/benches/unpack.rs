@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fastanvil::{PackedBits, PackingMode};
+use fastnbt::LongArray;
+
+/// The indices of a section of 4096 blocks, packed `bits` wide.
+fn section(bits: usize, packing: PackingMode) -> PackedBits {
+    let indices: Vec<u16> = (0..4096u32)
+        .map(|i| (i.wrapping_mul(2654435761) >> 7) as u16)
+        .collect();
+    PackedBits(LongArray::new(PackedBits::from_indices(
+        &indices, bits, packing,
+    )))
+}
+
+pub fn unpack_benchmark(c: &mut Criterion) {
+    for bits in [4, 5, 9, 15] {
+        for (name, packing) in [
+            ("padded", PackingMode::Padded),
+            ("spanning", PackingMode::Spanning),
+        ] {
+            let packed = section(bits, packing);
+            let mut out = [0; 4096];
+
+            c.bench_function(&format!("unpack {bits} bits {name}"), |b| {
+                b.iter(|| {
+                    packed.unpack_into(bits, packing, &mut out);
+                    black_box(&out);
+                })
+            });
+
+            // Reading each index on its own, for comparison.
+            c.bench_function(&format!("get {bits} bits {name}"), |b| {
+                b.iter(|| {
+                    for (i, value) in out.iter_mut().enumerate() {
+                        *value = packed.get(i, bits, packing);
+                    }
+                    black_box(&out);
+                })
+            });
+        }
+    }
+}
+
+criterion_group!(benches, unpack_benchmark);
+criterion_main!(benches);
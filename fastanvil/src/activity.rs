@@ -0,0 +1,121 @@
+use std::io::{Read, Seek};
+
+use serde::Deserialize;
+
+use crate::{CCoord, Region, Result};
+
+/// How long players have spent near a chunk and when it was last saved, from
+/// [`chunk_activity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkActivity {
+    /// The chunk's coordinates in the world, not within the region.
+    pub x: CCoord,
+    pub z: CCoord,
+
+    /// See [`Chunk::inhabited_time`][crate::Chunk::inhabited_time]. Zero if
+    /// the chunk does not record it, as Minecraft treats it.
+    pub inhabited_time: i64,
+
+    /// See [`Chunk::last_update`][crate::Chunk::last_update]. Zero if the
+    /// chunk does not record it.
+    pub last_update: i64,
+}
+
+/// The [`ChunkActivity`] of every chunk in a region, eg to draw a heatmap of
+/// where players have been or to find chunks to trim that were barely
+/// visited.
+///
+/// Only the chunk's coordinates and these two values are read. Sections and
+/// everything else are skipped over without being deserialized, so this is
+/// quick enough to scan a whole world. Works with chunks of every version
+/// since Anvil replaced McRegion. Chunks that cannot be read or parsed are
+/// skipped.
+///
+/// ```no_run
+/// # use fastanvil::{chunk_activity, Region};
+/// # let file = std::fs::File::open("r.0.0.mca").unwrap();
+/// let mut region = Region::from_stream(file).unwrap();
+///
+/// // An hour at 20 ticks per second.
+/// for chunk in chunk_activity(&mut region).unwrap() {
+///     if chunk.inhabited_time < 20 * 60 * 60 {
+///         println!("chunk {}, {} is barely visited", chunk.x.0, chunk.z.0);
+///     }
+/// }
+/// ```
+pub fn chunk_activity<S: Read + Seek>(region: &mut Region<S>) -> Result<Vec<ChunkActivity>> {
+    let mut activity = vec![];
+
+    region.scan_chunks(|_, _, data| -> Result<()> {
+        let Some(chunk) = data
+            .ok()
+            .and_then(|data| fastnbt::from_bytes::<ActivityChunk>(data).ok())
+        else {
+            return Ok(());
+        };
+
+        // Before 1.18 everything is in a Level compound.
+        let fields = match chunk.level {
+            Some(level) => level,
+            None => chunk.root(),
+        };
+        if let (Some(x), Some(z)) = (fields.x_pos, fields.z_pos) {
+            activity.push(ChunkActivity {
+                x: CCoord(x as isize),
+                z: CCoord(z as isize),
+                inhabited_time: fields.inhabited_time.unwrap_or(0),
+                last_update: fields.last_update.unwrap_or(0),
+            });
+        }
+        Ok(())
+    })?;
+
+    Ok(activity)
+}
+
+// Just the fields wanted, for both chunk layouts. Flattening the root fields
+// would buffer the rest of the chunk, so they are repeated instead.
+
+#[derive(Deserialize)]
+struct ActivityChunk {
+    #[serde(rename = "Level")]
+    level: Option<ActivityFields>,
+
+    #[serde(rename = "xPos")]
+    x_pos: Option<i32>,
+
+    #[serde(rename = "zPos")]
+    z_pos: Option<i32>,
+
+    #[serde(rename = "InhabitedTime")]
+    inhabited_time: Option<i64>,
+
+    #[serde(rename = "LastUpdate")]
+    last_update: Option<i64>,
+}
+
+impl ActivityChunk {
+    fn root(self) -> ActivityFields {
+        ActivityFields {
+            x_pos: self.x_pos,
+            z_pos: self.z_pos,
+            inhabited_time: self.inhabited_time,
+            last_update: self.last_update,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ActivityFields {
+    #[serde(rename = "xPos")]
+    x_pos: Option<i32>,
+
+    #[serde(rename = "zPos")]
+    z_pos: Option<i32>,
+
+    #[serde(rename = "InhabitedTime")]
+    inhabited_time: Option<i64>,
+
+    #[serde(rename = "LastUpdate")]
+    last_update: Option<i64>,
+}
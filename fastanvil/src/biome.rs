@@ -200,6 +200,92 @@ pub struct Climate {
 }
 
 impl Biome {
+    /// The name of the biome from 1.18, when biomes started being saved by
+    /// name. Biomes removed in 1.18 are named after the biome the game
+    /// replaced them with, and [`Biome::Unknown`] becomes plains as unknown
+    /// biomes do when the game upgrades a chunk.
+    pub(crate) fn name_1_18(self) -> &'static str {
+        use Biome::*;
+
+        match self {
+            Ocean => "minecraft:ocean",
+            Plains | Unknown => "minecraft:plains",
+            Desert | DesertHills | DesertLakes => "minecraft:desert",
+            Mountains | MountainEdge | WindsweptHills => "minecraft:windswept_hills",
+            Forest | WoodedHills => "minecraft:forest",
+            Taiga | TaigaHills | TaigaMountains => "minecraft:taiga",
+            Swamp | SwampHills => "minecraft:swamp",
+            River => "minecraft:river",
+            Nether | NetherWastes => "minecraft:nether_wastes",
+            TheEnd => "minecraft:the_end",
+            FrozenOcean => "minecraft:frozen_ocean",
+            FrozenRiver => "minecraft:frozen_river",
+            SnowyTundra | SnowyMountains | SnowyPlains => "minecraft:snowy_plains",
+            MushroomFields | MushroomFieldShore => "minecraft:mushroom_fields",
+            Beach => "minecraft:beach",
+            Jungle | JungleHills | ModifiedJungle => "minecraft:jungle",
+            JungleEdge | ModifiedJungleEdge | SparseJungle => "minecraft:sparse_jungle",
+            DeepOcean => "minecraft:deep_ocean",
+            StoneShore | StonyShore => "minecraft:stony_shore",
+            SnowyBeach => "minecraft:snowy_beach",
+            BirchForest | BirchForestHills => "minecraft:birch_forest",
+            DarkForest | DarkForestHills => "minecraft:dark_forest",
+            SnowyTaiga | SnowyTaigaHills | SnowyTaigaMountains => "minecraft:snowy_taiga",
+            GiantTreeTaiga | GiantTreeTaigaHills | OldGrowthPineTaiga => {
+                "minecraft:old_growth_pine_taiga"
+            }
+            WoodedMountains | WindsweptForest => "minecraft:windswept_forest",
+            Savanna => "minecraft:savanna",
+            SavannaPlateau => "minecraft:savanna_plateau",
+            Badlands | BadlandsPlateau | ModifiedBadlandsPlateau => "minecraft:badlands",
+            WoodedBadlandsPlateau | ModifiedWoodedBadlandsPlateau | WoodedBadlands => {
+                "minecraft:wooded_badlands"
+            }
+            SmallEndIslands => "minecraft:small_end_islands",
+            EndMidlands => "minecraft:end_midlands",
+            EndHighlands => "minecraft:end_highlands",
+            EndBarrens => "minecraft:end_barrens",
+            WarmOcean | DeepWarmOcean => "minecraft:warm_ocean",
+            LukewarmOcean => "minecraft:lukewarm_ocean",
+            ColdOcean => "minecraft:cold_ocean",
+            DeepLukewarmOcean => "minecraft:deep_lukewarm_ocean",
+            DeepColdOcean => "minecraft:deep_cold_ocean",
+            DeepFrozenOcean => "minecraft:deep_frozen_ocean",
+            TheVoid => "minecraft:the_void",
+            SunflowerPlains => "minecraft:sunflower_plains",
+            GravellyMountains | ModifiedGravellyMountains | WindsweptGravellyHills => {
+                "minecraft:windswept_gravelly_hills"
+            }
+            FlowerForest => "minecraft:flower_forest",
+            IceSpikes => "minecraft:ice_spikes",
+            TallBirchForest | TallBirchHills | OldGrowthBirchForest => {
+                "minecraft:old_growth_birch_forest"
+            }
+            GiantSpruceTaiga | GiantSpruceTaigaHills | OldGrowthSpruceTaiga => {
+                "minecraft:old_growth_spruce_taiga"
+            }
+            ShatteredSavanna | ShatteredSavannaPlateau | WindsweptSavanna => {
+                "minecraft:windswept_savanna"
+            }
+            ErodedBadlands => "minecraft:eroded_badlands",
+            BambooJungle | BambooJungleHills => "minecraft:bamboo_jungle",
+            SoulSandValley => "minecraft:soul_sand_valley",
+            CrimsonForest => "minecraft:crimson_forest",
+            WarpedForest => "minecraft:warped_forest",
+            BasaltDeltas => "minecraft:basalt_deltas",
+            DripstoneCaves => "minecraft:dripstone_caves",
+            FrozenPeaks => "minecraft:frozen_peaks",
+            Grove => "minecraft:grove",
+            JaggedPeaks => "minecraft:jagged_peaks",
+            LushCaves => "minecraft:lush_caves",
+            Meadow => "minecraft:meadow",
+            SnowySlopes => "minecraft:snowy_slopes",
+            StonyPeaks => "minecraft:stony_peaks",
+            MangroveSwamp => "minecraft:mangrove_swamp",
+            DeepDark => "minecraft:deep_dark",
+        }
+    }
+
     // Values from https://github.com/erich666/Mineways/blob/master/Win/biomes.cpp
     pub fn climate(self) -> Climate {
         let climate = |t, r| Climate {
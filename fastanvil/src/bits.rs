@@ -1,6 +1,5 @@
 //! functionality around bit manipulations specific to the Anvil file format.
 
-use bit_field::{BitArray, BitField};
 use fastnbt::LongArray;
 use serde::Deserialize;
 
@@ -11,8 +10,9 @@ const SNAPSHOT_21W44A: i32 = 2845;
 
 /// PackedBits can be used in place of blockstates in chunks to avoid
 /// allocating memory for them when they might not be needed. This object by
-/// default just retains a reference to the data in the input, and `unpack_into`
-/// can be used to get the unpacked version when needed.
+/// default just retains a reference to the data in the input, and
+/// [`unpack_into`][Self::unpack_into] can be used to get the unpacked version
+/// when needed.
 #[derive(Deserialize, Debug)]
 pub struct PackedBits(pub LongArray);
 
@@ -32,35 +32,149 @@ impl PackedBits {
     }
 
     fn unpack_1_16(&self, bits_per_item: usize, buf: &mut [u16]) {
-        let data = &*self.0;
-
-        let mut buf_i = 0;
-        let values_per_64bits = 64 / bits_per_item;
-
-        for datum in data {
-            let datum = *datum as u64;
-            for i in 0..values_per_64bits {
-                let v = datum.get_bits(i * bits_per_item..(i + 1) * bits_per_item);
-                buf[buf_i] = v as u16;
-                buf_i += 1;
-                if buf_i >= buf.len() {
-                    break;
-                }
+        self.unpack_into(bits_per_item, PackingMode::Padded, buf)
+    }
+
+    fn unpack_1_15(&self, bits_per_item: usize, buf: &mut [u16]) {
+        self.unpack_into(bits_per_item, PackingMode::Spanning, buf)
+    }
+
+    /// Unpack indices `bits` wide into `out`, filling it. Indices past the end
+    /// of the data are 0.
+    ///
+    /// Whole longs are unpacked at a time, with the shifts for each width up
+    /// to 16 worked out at compile time. This is the same unpacking that
+    /// sections use for their block states. Wider indices, from sections with
+    /// huge palettes, are unpacked one at a time and cut down to their low 16
+    /// bits. A width of 0 or over 64 unpacks to all zeros.
+    ///
+    /// ```
+    /// # use fastanvil::{PackedBits, PackingMode};
+    /// # use fastnbt::LongArray;
+    /// let packed = PackedBits(LongArray::new(vec![0x321]));
+    /// let mut indices = [0; 4];
+    /// packed.unpack_into(4, PackingMode::Padded, &mut indices);
+    /// assert_eq!(indices, [1, 2, 3, 0]);
+    /// ```
+    pub fn unpack_into(&self, bits: usize, packing: PackingMode, out: &mut [u16]) {
+        unpack(&self.0, bits, packing, out)
+    }
+}
+
+/// How indices are packed into the longs of a [`PackedBits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackingMode {
+    /// The 1.16 and later layout, where each long holds as many whole indices
+    /// as fit and the bits left over at the top are padding. No index spans
+    /// two longs.
+    Padded,
+    /// The layout before 1.16, where indices are packed tightly with no
+    /// padding, so an index can start in one long and end in the next.
+    Spanning,
+}
+
+impl PackingMode {
+    /// The number of longs needed to hold `len` indices of `bits` each.
+    pub fn packed_len(self, len: usize, bits: usize) -> usize {
+        match self {
+            PackingMode::Padded => crate::unstable_div_ceil(len, 64 / bits),
+            PackingMode::Spanning => crate::unstable_div_ceil(len * bits, 64),
+        }
+    }
+
+    /// The long an index starts in, and the bit within it.
+    fn position(self, index: usize, bits: usize) -> (usize, usize) {
+        match self {
+            PackingMode::Padded => {
+                let per_long = 64 / bits;
+                (index / per_long, index % per_long * bits)
             }
+            PackingMode::Spanning => (index * bits / 64, index * bits % 64),
         }
     }
+}
 
-    fn unpack_1_15(&self, bits_per_item: usize, buf: &mut [u16]) {
-        // TODO: Get around having to allocate here.
-        let mut v: Vec<u64> = vec![];
-        for datum in &*self.0 {
-            v.push(*datum as u64);
+impl PackedBits {
+    /// Pack indices into longs, the reverse of unpacking them. Each index is
+    /// `bits` wide, from 1 to 16, and any higher bits of an index are
+    /// dropped.
+    ///
+    /// Block states in a section use at least 4 bits, whatever the size of
+    /// the palette, so use [`bits_per_block`] to pick the width for them.
+    ///
+    /// ```
+    /// # use fastanvil::{PackedBits, PackingMode};
+    /// let longs = PackedBits::from_indices(&[1, 2, 3], 4, PackingMode::Padded);
+    /// assert_eq!(longs, [0x321]);
+    /// ```
+    pub fn from_indices(indices: &[u16], bits: usize, packing: PackingMode) -> Vec<i64> {
+        assert!((1..=16).contains(&bits), "bits must be from 1 to 16");
+
+        let mut longs = vec![0u64; packing.packed_len(indices.len(), bits)];
+        let mask = (1u64 << bits) - 1;
+
+        for (i, &index) in indices.iter().enumerate() {
+            let (long, offset) = packing.position(i, bits);
+            let index = index as u64 & mask;
+            longs[long] |= index << offset;
+            if offset + bits > 64 {
+                longs[long + 1] |= index >> (64 - offset);
+            }
         }
 
-        for (i, val) in buf.iter_mut().enumerate() {
-            let begin = i * bits_per_item;
-            let end = begin + bits_per_item;
-            *val = v.get_bits(begin..end) as u16;
+        longs.into_iter().map(|l| l as i64).collect()
+    }
+
+    /// Get the index at position `i`, for indices `bits` wide, from 1 to 16.
+    ///
+    /// # Panics
+    ///
+    /// If `i` is past the end of the data, or `bits` is not from 1 to 16.
+    pub fn get(&self, i: usize, bits: usize, packing: PackingMode) -> u16 {
+        assert!((1..=16).contains(&bits), "bits must be from 1 to 16");
+
+        let (long, offset) = packing.position(i, bits);
+        let mask = (1u64 << bits) - 1;
+
+        let mut v = self.0[long] as u64 >> offset;
+        if offset + bits > 64 {
+            v |= (self.0[long + 1] as u64) << (64 - offset);
+        }
+        (v & mask) as u16
+    }
+
+    /// Replace the index at position `i` in place, leaving the others as they
+    /// are. This only works while the width of the indices stays the same;
+    /// if a palette grows past what `bits` can index, unpack the indices and
+    /// pack them again with [`from_indices`][Self::from_indices].
+    ///
+    /// ```
+    /// # use fastanvil::{PackedBits, PackingMode};
+    /// # use fastnbt::LongArray;
+    /// let longs = PackedBits::from_indices(&[0; 4096], 4, PackingMode::Padded);
+    /// let mut packed = PackedBits(LongArray::new(longs));
+    /// packed.set(100, 7, 4, PackingMode::Padded);
+    /// assert_eq!(packed.get(100, 4, PackingMode::Padded), 7);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `i` is past the end of the data, or `bits` is not from 1 to 16.
+    pub fn set(&mut self, i: usize, value: u16, bits: usize, packing: PackingMode) {
+        assert!((1..=16).contains(&bits), "bits must be from 1 to 16");
+
+        let (long, offset) = packing.position(i, bits);
+        let mask = (1u64 << bits) - 1;
+        let value = value as u64 & mask;
+
+        let low = &mut self.0[long];
+        *low = ((*low as u64 & !(mask << offset)) | value << offset) as i64;
+
+        if offset + bits > 64 {
+            let spilled = offset + bits - 64;
+            let high = &mut self.0[long + 1];
+            let high_mask = (1u64 << spilled) - 1;
+            *high = ((*high as u64 & !high_mask) | value >> (64 - offset)) as i64;
         }
     }
 }
@@ -145,36 +259,111 @@ pub fn expand_heightmap(data: &[i64], y_min: isize, data_version: i32) -> Vec<i1
 }
 
 /// Expand generic bit-packed data in the 1.16 format, ie with padding bits.
+/// A width of 0 gives no values.
 pub fn expand_generic_1_16(data: &[i64], bits: usize) -> Vec<u16> {
-    let values_per_64bits = 64 / bits;
-    let mut result: Vec<u16> = Vec::with_capacity(values_per_64bits * data.len());
-
-    for datum in data {
-        for i in 0..values_per_64bits {
-            let datum = *datum as u64;
-            let v = datum.get_bits(i * bits..(i + 1) * bits);
-            result.push(v as u16);
-        }
-    }
-
+    let mut result = vec![0; 64usize.checked_div(bits).unwrap_or(0) * data.len()];
+    unpack(data, bits, PackingMode::Padded, &mut result);
     result
 }
 
 /// Expand generic bit-packed data in the 1.15 format, ie data potentially existing across two 64-bit ints.
+/// A width of 0 gives no values.
 pub fn expand_generic_1_15(data: &[i64], bits: usize) -> Vec<u16> {
-    let mut result: Vec<u16> = vec![0; (data.len() * 64) / bits];
+    let mut result = vec![0; (data.len() * 64).checked_div(bits).unwrap_or(0)];
+    unpack(data, bits, PackingMode::Spanning, &mut result);
+    result
+}
 
-    // Unfortunely make a copy here in order to treat the data as u64 rather than i64.
-    // At some point we will change the parser to let us take the data as u64 rather than i64.
-    let copy: Vec<_> = data.iter().map(|i| *i as u64).collect();
+/// Unpack the palette indices of a section into `out`, ordered by x, then z,
+/// then y. Handles both the 1.16+ format where indices are padded so none
+/// span two longs, and the tightly packed format before it, telling them
+/// apart by the length of the data. Indices past the end of the data are 0.
+pub(crate) fn unpack_section_indices(data: &[i64], bits: usize, out: &mut [u16; 4096]) {
+    let packing = if data.len() == PackingMode::Padded.packed_len(out.len(), bits) {
+        PackingMode::Padded
+    } else {
+        PackingMode::Spanning
+    };
+    unpack(data, bits, packing, out)
+}
 
-    for (i, val) in result.iter_mut().enumerate() {
-        let begin = i * bits;
-        let end = begin + bits;
-        *val = copy.get_bits(begin..end) as u16;
+/// Unpack indices `bits` wide into `out`, with zeros past the end of the data.
+/// Each width gets its own copy of the loops, so the compiler knows how many
+/// indices are in a long and can unroll and vectorise unpacking them.
+fn unpack(data: &[i64], bits: usize, packing: PackingMode, out: &mut [u16]) {
+    macro_rules! widths {
+        ($($bits:literal)*) => {
+            match (packing, bits) {
+                $(
+                    (PackingMode::Padded, $bits) => unpack_padded::<$bits>(data, out),
+                    (PackingMode::Spanning, $bits) => unpack_spanning::<$bits>(data, out),
+                )*
+                _ => unpack_generic(data, bits, packing, out),
+            }
+        };
     }
 
-    result
+    widths!(1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16)
+}
+
+/// Unpack indices of any width an index at a time, for the widths that do not
+/// have their own copy of the loops. Only the low 16 bits of each index are
+/// kept.
+fn unpack_generic(data: &[i64], bits: usize, packing: PackingMode, out: &mut [u16]) {
+    if bits == 0 || bits > 64 {
+        out.fill(0);
+        return;
+    }
+
+    let long = |i: usize| data.get(i).map_or(0, |&l| l as u64);
+    for (i, value) in out.iter_mut().enumerate() {
+        let (start, offset) = packing.position(i, bits);
+        let mut index = long(start) >> offset;
+        if offset + bits > 64 {
+            index |= long(start + 1) << (64 - offset);
+        }
+        *value = index as u16;
+    }
+}
+
+fn unpack_padded<const BITS: usize>(data: &[i64], out: &mut [u16]) {
+    let per_long = 64 / BITS;
+    let mut longs = data.iter().map(|&l| l as u64);
+
+    let mut chunks = out.chunks_exact_mut(per_long);
+    for values in &mut chunks {
+        unpack_long::<BITS>(longs.next().unwrap_or(0), values);
+    }
+    unpack_long::<BITS>(longs.next().unwrap_or(0), chunks.into_remainder());
+}
+
+#[inline(always)]
+fn unpack_long<const BITS: usize>(long: u64, values: &mut [u16]) {
+    let mask = (1u64 << BITS) - 1;
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = (long >> (i * BITS) & mask) as u16;
+    }
+}
+
+fn unpack_spanning<const BITS: usize>(data: &[i64], out: &mut [u16]) {
+    let mask = (1u64 << BITS) - 1;
+    let mut longs = data.iter().map(|&l| l as u64);
+
+    // Bits read from the data but not yet unpacked, lowest first. Topping it
+    // up a long at a time saves working out where each index starts.
+    let mut buffer = 0u128;
+    let mut buffered = 0;
+
+    for value in out {
+        if buffered < BITS {
+            buffer |= (longs.next().unwrap_or(0) as u128) << buffered;
+            buffered += 64;
+        }
+
+        *value = (buffer as u64 & mask) as u16;
+        buffer >>= BITS;
+        buffered -= BITS;
+    }
 }
 
 /// Get the number of bits that will be used in `Blockstates` per block.
@@ -189,7 +378,7 @@ pub fn bits_per_block(palette_len: usize) -> usize {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use byteorder::{BigEndian, ReadBytesExt};
 
@@ -316,6 +505,189 @@ mod tests {
         assert_eq!(10, bits_per_block(1 << 10));
     }
 
+    /// Longs of random bits, the same on every run. The other random test
+    /// data is made from these.
+    pub(crate) fn random_longs(len: usize, seed: u64) -> Vec<i64> {
+        let mut state = seed | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as i64
+            })
+            .collect()
+    }
+
+    /// Pseudo-random indices that fit in `bits`, the same on every run.
+    fn indices(len: usize, bits: usize, seed: u64) -> Vec<u16> {
+        random_longs(len, seed)
+            .into_iter()
+            .map(|l| (l as u64 & ((1 << bits) - 1)) as u16)
+            .collect()
+    }
+
+    #[test]
+    fn from_indices_round_trips_padded() {
+        for bits in 1..=16 {
+            for len in [1, 63, 64, 256, 4096] {
+                let indices = indices(len, bits, (bits * len) as u64);
+                let longs = PackedBits::from_indices(&indices, bits, PackingMode::Padded);
+                assert_eq!(longs.len(), crate::unstable_div_ceil(len, 64 / bits));
+
+                let mut unpacked = expand_generic_1_16(&longs, bits);
+                unpacked.truncate(len);
+                assert_eq!(unpacked, indices, "bits {bits}, len {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn from_indices_round_trips_spanning() {
+        for bits in 1..=16 {
+            // Whole numbers of longs, as the decoder expects.
+            let len = 4096;
+            let indices = indices(len, bits, bits as u64);
+            let longs = PackedBits::from_indices(&indices, bits, PackingMode::Spanning);
+            assert_eq!(longs.len(), len * bits / 64);
+
+            assert_eq!(expand_generic_1_15(&longs, bits), indices, "bits {bits}");
+
+            let packed = PackedBits(LongArray::new(longs));
+            let mut buf = vec![0; len];
+            packed.unpack_1_15(bits, &mut buf);
+            assert_eq!(buf, indices, "bits {bits}");
+        }
+    }
+
+    #[test]
+    fn from_indices_round_trips_block_states() {
+        for palette_len in [1, 2, 15, 16, 17, 32, 33, 100, 4096] {
+            let bits = bits_per_block(palette_len);
+            let indices: Vec<u16> = indices(4096, bits, palette_len as u64)
+                .into_iter()
+                .map(|i| i % palette_len as u16)
+                .collect();
+
+            for packing in [PackingMode::Padded, PackingMode::Spanning] {
+                let longs = PackedBits::from_indices(&indices, bits, packing);
+                let mut expanded = expand_blockstates(&longs, palette_len);
+                expanded.truncate(4096);
+                assert_eq!(expanded, indices, "palette {palette_len}, {packing:?}");
+
+                let mut section = [0; 4096];
+                unpack_section_indices(&longs, bits, &mut section);
+                assert_eq!(section[..], indices[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn unpack_matches_reading_each_index() {
+        for seed in 0..20 {
+            for bits in 1..=16 {
+                for packing in [PackingMode::Padded, PackingMode::Spanning] {
+                    let longs = 1 + (seed as usize * 7 + bits) % 300;
+                    let packed =
+                        PackedBits(LongArray::new(random_longs(longs, seed * 16 + bits as u64)));
+
+                    // Every index the data holds, and a few past the end.
+                    let len = match packing {
+                        PackingMode::Padded => longs * (64 / bits),
+                        PackingMode::Spanning => longs * 64 / bits,
+                    };
+                    let mut out = vec![u16::MAX; len + 5];
+                    packed.unpack_into(bits, packing, &mut out);
+
+                    let expected: Vec<_> = (0..len).map(|i| packed.get(i, bits, packing)).collect();
+                    assert_eq!(
+                        out[..len],
+                        expected,
+                        "bits {bits}, {packing:?}, seed {seed}"
+                    );
+                    // Spanning data can end part way through an index, which
+                    // keeps the bits it has.
+                    assert_eq!(out[len + 1..], [0; 4]);
+
+                    // Unpacking fewer indices than the data holds.
+                    let mut short = vec![0; len / 3];
+                    packed.unpack_into(bits, packing, &mut short);
+                    assert_eq!(short, expected[..len / 3]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unpack_wide_indices_keeps_low_bits() {
+        for bits in 17..=64 {
+            for packing in [PackingMode::Padded, PackingMode::Spanning] {
+                let longs = random_longs(40, bits as u64);
+                let packed = PackedBits(LongArray::new(longs.clone()));
+
+                let mut out = vec![0; 64];
+                packed.unpack_into(bits, packing, &mut out);
+
+                // Read each index from a bit at a time.
+                let bit = |i: usize| longs.get(i / 64).map_or(0, |&l| (l as u64 >> (i % 64)) & 1);
+                for (i, &value) in out.iter().enumerate() {
+                    let (long, offset) = packing.position(i, bits);
+                    let start = long * 64 + offset;
+                    let expected = (0..16).fold(0, |v, b| v | bit(start + b) << b) as u16;
+                    assert_eq!(value, expected, "bits {bits}, {packing:?}, index {i}");
+                }
+            }
+        }
+
+        for bits in [0, 65] {
+            let mut out = vec![u16::MAX; 8];
+            PackedBits(LongArray::new(vec![-1; 4])).unpack_into(
+                bits,
+                PackingMode::Padded,
+                &mut out,
+            );
+            assert_eq!(out, [0; 8]);
+        }
+        assert!(expand_generic_1_16(&[1, 2], 0).is_empty());
+        assert!(expand_generic_1_15(&[1, 2], 0).is_empty());
+    }
+
+    #[test]
+    fn set_changes_only_one_index() {
+        for bits in 1..=16 {
+            for packing in [PackingMode::Padded, PackingMode::Spanning] {
+                let mut indices = indices(4096, bits, bits as u64 + 1);
+                let longs = PackedBits::from_indices(&indices, bits, packing);
+                let mut packed = PackedBits(LongArray::new(longs));
+
+                let max = ((1u32 << bits) - 1) as u16;
+                for (i, value) in [(0, max), (1, 0), (4095, max), (2000, 1), (63, max)] {
+                    packed.set(i, value, bits, packing);
+                    indices[i] = value;
+                }
+
+                let unpacked: Vec<_> = (0..4096).map(|i| packed.get(i, bits, packing)).collect();
+                assert_eq!(unpacked, indices, "bits {bits}, {packing:?}");
+                assert_eq!(
+                    *packed.0,
+                    PackedBits::from_indices(&indices, bits, packing)[..]
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "bits must be from 1 to 16")]
+    fn get_with_zero_bits_panics() {
+        PackedBits(LongArray::new(vec![0; 4])).get(0, 0, PackingMode::Padded);
+    }
+
+    #[test]
+    #[should_panic(expected = "bits must be from 1 to 16")]
+    fn set_with_too_many_bits_panics() {
+        PackedBits(LongArray::new(vec![0; 4])).set(0, 1, 64, PackingMode::Spanning);
+    }
+
     #[test]
     fn unpack_1_15_heightmap() {
         let height_data = vec![
@@ -0,0 +1,120 @@
+use serde::Deserialize;
+
+/// The text of a sign block entity, from any version.
+///
+/// Before 1.20 a sign only has text on its front, in `Text1` to `Text4`. From
+/// 1.20 it has text on both sides, in the `messages` of its `front_text` and
+/// `back_text`. Either deserializes to this, so the lines are read the same
+/// way whichever version saved the sign. Signs from before 1.20 have blank
+/// lines on the back.
+///
+/// Each line is a JSON text component, eg `{"text":"Hello"}`. With the
+/// `serde_json` feature `SignSide::plain_lines` gives the plain text.
+///
+/// ```
+/// # use fastanvil::SignText;
+/// # use fastnbt::nbt;
+/// let sign: SignText = fastnbt::from_value(&nbt!({
+///     "id": "minecraft:sign",
+///     "Text1": r#"{"text":"Hello"}"#,
+///     "Text2": r#"{"text":""}"#,
+///     "Text3": r#"{"text":""}"#,
+///     "Text4": r#"{"text":""}"#,
+/// }))
+/// .unwrap();
+/// assert_eq!(sign.front.messages[0], r#"{"text":"Hello"}"#);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "SignRepr")]
+pub struct SignText {
+    pub front: SignSide,
+    pub back: SignSide,
+
+    /// Whether the sign has been waxed so it can't be edited. Only 1.20 and
+    /// later signs have wax.
+    pub waxed: Option<bool>,
+}
+
+/// The text on one side of a [`SignText`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignSide {
+    /// The four lines, as JSON text components.
+    pub messages: [String; 4],
+
+    /// The dye colour of the text, eg `black`.
+    pub color: Option<String>,
+
+    /// Whether the text has been made to glow with a glow ink sac.
+    pub glowing: Option<bool>,
+}
+
+#[cfg(feature = "serde_json")]
+impl SignSide {
+    /// The plain text of each line, without formatting. See
+    /// [`to_plain`][crate::text_component::to_plain].
+    pub fn plain_lines(&self) -> [String; 4] {
+        self.messages
+            .each_ref()
+            .map(|line| crate::text_component::to_plain(line))
+    }
+}
+
+/// Every field of both layouts, since a sign only ever has one of them.
+#[derive(Deserialize)]
+struct SignRepr {
+    front_text: Option<SideRepr>,
+    back_text: Option<SideRepr>,
+    is_waxed: Option<bool>,
+
+    #[serde(rename = "Text1", default)]
+    text1: String,
+    #[serde(rename = "Text2", default)]
+    text2: String,
+    #[serde(rename = "Text3", default)]
+    text3: String,
+    #[serde(rename = "Text4", default)]
+    text4: String,
+    #[serde(rename = "Color")]
+    color: Option<String>,
+    #[serde(rename = "GlowingText")]
+    glowing_text: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct SideRepr {
+    #[serde(default)]
+    messages: [String; 4],
+    color: Option<String>,
+    has_glowing_text: Option<bool>,
+}
+
+impl From<SideRepr> for SignSide {
+    fn from(side: SideRepr) -> Self {
+        Self {
+            messages: side.messages,
+            color: side.color,
+            glowing: side.has_glowing_text,
+        }
+    }
+}
+
+impl From<SignRepr> for SignText {
+    fn from(sign: SignRepr) -> Self {
+        match sign.front_text {
+            Some(front) => Self {
+                front: front.into(),
+                back: sign.back_text.map(Into::into).unwrap_or_default(),
+                waxed: sign.is_waxed,
+            },
+            None => Self {
+                front: SignSide {
+                    messages: [sign.text1, sign.text2, sign.text3, sign.text4],
+                    color: sign.color,
+                    glowing: sign.glowing_text,
+                },
+                back: SignSide::default(),
+                waxed: None,
+            },
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::io::Read;
+
+use flate2::{
+    read::{GzEncoder, ZlibEncoder},
+    Compression,
+};
+
+use crate::{sector_count_for_len, CompressionScheme, Result};
+
+/// How hard to compress chunks, trading the time spent writing them for the
+/// space they take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompressionLevel {
+    /// The fastest compression, and the biggest files. Suits bulk exports.
+    #[default]
+    Fastest,
+    /// The compression level zlib uses by default.
+    Default,
+    /// The slowest compression, and the smallest files.
+    Best,
+    /// A level from 0, no compression, to 9, the same as `Best`. Higher
+    /// levels are treated as 9.
+    Custom(u32),
+}
+
+impl CompressionLevel {
+    fn flate2(self) -> Compression {
+        match self {
+            CompressionLevel::Fastest => Compression::fast(),
+            CompressionLevel::Default => Compression::default(),
+            CompressionLevel::Best => Compression::best(),
+            CompressionLevel::Custom(level) => Compression::new(level.min(9)),
+        }
+    }
+}
+
+/// How [`Region::write_chunk`][crate::Region::write_chunk] compresses chunks.
+/// See [`Region::with_compression`][crate::Region::with_compression].
+///
+/// The default compresses with zlib at [`CompressionLevel::Fastest`], which
+/// every version of Minecraft can read.
+///
+/// ```
+/// # use fastanvil::{CompressionLevel, CompressionPolicy, CompressionScheme};
+/// let policy = CompressionPolicy::auto(CompressionLevel::Best);
+///
+/// // Too small to be worth compressing.
+/// let (scheme, data) = policy.compress(&[1, 2, 3]).unwrap();
+/// assert_eq!(scheme, CompressionScheme::Uncompressed);
+/// assert_eq!(data, [1, 2, 3]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionPolicy {
+    pub scheme: CompressionScheme,
+    pub level: CompressionLevel,
+
+    /// Store a chunk uncompressed when compressing it would not make it take
+    /// fewer sectors, eg for tiny chunks where the compression overhead
+    /// outweighs what it saves. Uncompressed chunks are quicker to read, but
+    /// older versions of Minecraft cannot read them.
+    pub auto: bool,
+}
+
+impl CompressionPolicy {
+    /// Compress with the given scheme at the given level.
+    pub fn new(scheme: CompressionScheme, level: CompressionLevel) -> Self {
+        Self {
+            scheme,
+            level,
+            auto: false,
+        }
+    }
+
+    /// Compress with zlib at the given level, unless storing the chunk
+    /// uncompressed takes no more space. See the `auto` field.
+    pub fn auto(level: CompressionLevel) -> Self {
+        Self {
+            scheme: CompressionScheme::Zlib,
+            level,
+            auto: true,
+        }
+    }
+
+    /// Never compress chunks.
+    pub fn uncompressed() -> Self {
+        Self::new(CompressionScheme::Uncompressed, CompressionLevel::default())
+    }
+
+    /// Compress a chunk according to the policy, giving the scheme it was
+    /// compressed with and the compressed data.
+    pub fn compress(&self, uncompressed: &[u8]) -> Result<(CompressionScheme, Vec<u8>)> {
+        // Nothing can be smaller than a single sector, so don't bother trying.
+        if self.auto && sector_count_for_len(uncompressed.len()) == 1 {
+            return Ok((CompressionScheme::Uncompressed, uncompressed.to_vec()));
+        }
+
+        let mut buf = vec![];
+        match self.scheme {
+            CompressionScheme::Zlib => {
+                ZlibEncoder::new(uncompressed, self.level.flate2()).read_to_end(&mut buf)?;
+            }
+            CompressionScheme::Gzip => {
+                GzEncoder::new(uncompressed, self.level.flate2()).read_to_end(&mut buf)?;
+            }
+            CompressionScheme::Uncompressed => buf.extend_from_slice(uncompressed),
+        }
+
+        if self.auto && sector_count_for_len(uncompressed.len()) <= sector_count_for_len(buf.len())
+        {
+            buf.clear();
+            buf.extend_from_slice(uncompressed);
+            return Ok((CompressionScheme::Uncompressed, buf));
+        }
+
+        Ok((self.scheme, buf))
+    }
+}
+
+impl Default for CompressionPolicy {
+    fn default() -> Self {
+        Self::new(CompressionScheme::Zlib, CompressionLevel::Fastest)
+    }
+}
+
+/// What writing a chunk to a region did, eg for logging how much space chunks
+/// take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkWrite {
+    /// The scheme the chunk was stored with.
+    pub scheme: CompressionScheme,
+    /// The length of the chunk's data as stored, ie after compression.
+    pub compressed_len: usize,
+    /// The number of sectors the chunk takes up in the region.
+    pub sectors: usize,
+}
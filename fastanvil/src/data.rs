@@ -0,0 +1,200 @@
+//! Readers for the files in a world's `data` directory, such as the chunks
+//! forced to stay loaded and the scoreboard.
+//!
+//! These files are gzipped NBT, with everything of interest in a `data`
+//! compound. Each type can be read from the file with `from_path`, or from
+//! NBT that has already been decompressed with `from_bytes`.
+//!
+//! ```no_run
+//! # use std::path::Path;
+//! # use fastanvil::data::ForcedChunks;
+//! let forced = ForcedChunks::from_path(Path::new("world/data/chunks.dat")).unwrap();
+//! for (x, z) in forced.chunks {
+//!     println!("chunk {}, {} is force loaded", x.0, z.0);
+//! }
+//! ```
+
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+use fastnbt::{error::Result, LongArray, Value};
+use flate2::read::GzDecoder;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::CCoord;
+
+/// Split a chunk position packed into a long, as in forced chunks and the
+/// structure references of chunks. The x coordinate is the low 32 bits and
+/// the z coordinate the high 32 bits.
+pub fn unpack_chunk_pos(packed: i64) -> (CCoord, CCoord) {
+    let x = packed as i32;
+    let z = (packed >> 32) as i32;
+    (CCoord(x as isize), CCoord(z as isize))
+}
+
+/// Pack a chunk position into a long, the reverse of [`unpack_chunk_pos`].
+/// Coordinates are truncated to 32 bits.
+pub fn pack_chunk_pos(x: CCoord, z: CCoord) -> i64 {
+    (x.0 as i32 as u32 as i64) | ((z.0 as i32 as i64) << 32)
+}
+
+/// The chunks forced to stay loaded, eg with `/forceload`, from
+/// `data/chunks.dat`. Each dimension has its own file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForcedChunks {
+    pub data_version: Option<i32>,
+    pub chunks: Vec<(CCoord, CCoord)>,
+}
+
+impl ForcedChunks {
+    /// Read a gzipped `chunks.dat` file.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        Self::from_bytes(&read_gzip(path)?)
+    }
+
+    /// Read the uncompressed NBT of a `chunks.dat` file.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct Forced {
+            #[serde(rename = "Forced")]
+            forced: Option<LongArray>,
+        }
+
+        let file: DataFile<Forced> = fastnbt::from_bytes(data)?;
+        let chunks = match file.data.forced {
+            Some(forced) => forced.iter().map(|&c| unpack_chunk_pos(c)).collect(),
+            None => vec![],
+        };
+
+        Ok(Self {
+            data_version: file.data_version,
+            chunks,
+        })
+    }
+}
+
+/// The objectives and scores of a world, from `data/scoreboard.dat`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Scoreboard {
+    #[serde(skip)]
+    pub data_version: Option<i32>,
+
+    #[serde(rename = "Objectives", default)]
+    pub objectives: Vec<Objective>,
+
+    #[serde(rename = "PlayerScores", default)]
+    pub scores: Vec<Score>,
+
+    /// The objective shown in each display slot, eg `slot_1` for the
+    /// sidebar.
+    #[serde(rename = "DisplaySlots", default)]
+    pub display_slots: HashMap<String, String>,
+}
+
+/// An objective of a [`Scoreboard`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Objective {
+    #[serde(rename = "Name")]
+    pub name: String,
+
+    /// What the score counts, eg `dummy` or `deathCount`.
+    #[serde(rename = "CriteriaName")]
+    pub criteria: String,
+
+    /// The text component shown for the objective. This is a JSON string in
+    /// older versions and NBT in newer ones.
+    #[serde(rename = "DisplayName")]
+    pub display_name: Option<Value>,
+
+    /// How the score is shown, `integer` or `hearts`.
+    #[serde(rename = "RenderType")]
+    pub render_type: Option<String>,
+}
+
+/// The score of a player or entity for an objective of a [`Scoreboard`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Score {
+    /// The player name, or the UUID of an entity.
+    #[serde(rename = "Name")]
+    pub holder: String,
+
+    #[serde(rename = "Objective")]
+    pub objective: String,
+
+    #[serde(rename = "Score")]
+    pub score: i32,
+
+    /// Whether the score can't be changed with `/trigger`.
+    #[serde(rename = "Locked", default)]
+    pub locked: bool,
+}
+
+impl Scoreboard {
+    /// Read a gzipped `scoreboard.dat` file.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        Self::from_bytes(&read_gzip(path)?)
+    }
+
+    /// Read the uncompressed NBT of a `scoreboard.dat` file.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let file: DataFile<Scoreboard> = fastnbt::from_bytes(data)?;
+        Ok(Self {
+            data_version: file.data_version,
+            ..file.data
+        })
+    }
+
+    /// The objective with the given name.
+    pub fn objective(&self, name: &str) -> Option<&Objective> {
+        self.objectives.iter().find(|o| o.name == name)
+    }
+
+    /// The scores for an objective.
+    pub fn scores_for<'a>(&'a self, objective: &'a str) -> impl Iterator<Item = &'a Score> + 'a {
+        self.scores.iter().filter(move |s| s.objective == objective)
+    }
+}
+
+/// The next ids given out for maps and the like, from `data/idcounts.dat`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdCounts {
+    pub data_version: Option<i32>,
+
+    /// The id of the most recently created map, if any have been.
+    pub map: Option<i32>,
+}
+
+impl IdCounts {
+    /// Read a gzipped `idcounts.dat` file.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        Self::from_bytes(&read_gzip(path)?)
+    }
+
+    /// Read the uncompressed NBT of an `idcounts.dat` file.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct Counts {
+            map: Option<i32>,
+        }
+
+        let file: DataFile<Counts> = fastnbt::from_bytes(data)?;
+        Ok(Self {
+            data_version: file.data_version,
+            map: file.data.map,
+        })
+    }
+}
+
+/// The layout shared by the files.
+#[derive(Deserialize)]
+#[serde(bound = "T: DeserializeOwned")]
+struct DataFile<T> {
+    #[serde(rename = "DataVersion")]
+    data_version: Option<i32>,
+    data: T,
+}
+
+pub(crate) fn read_gzip(path: &Path) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    GzDecoder::new(File::open(path)?).read_to_end(&mut data)?;
+    Ok(data)
+}
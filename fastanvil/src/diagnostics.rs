@@ -0,0 +1,95 @@
+use std::{error::Error, fmt::Display};
+
+use crate::CCoord;
+
+/// Something unexpected found while reading a chunk that was worked around
+/// rather than treated as an error. Collected by the `_with_diagnostics`
+/// variants of parsing and rendering functions, eg
+/// [`JavaChunk::from_bytes_with_diagnostics`][crate::JavaChunk::from_bytes_with_diagnostics]
+/// and [`render_region_with_diagnostics`][crate::render_region_with_diagnostics].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+
+    /// The world coordinates of the chunk, if known. Parsing a chunk on its
+    /// own does not know where it is, but rendering a region does.
+    pub chunk: Option<(CCoord, CCoord)>,
+}
+
+/// What a [`Diagnostic`] is about, and what was done about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// The chunk could not be read from its region, eg its data is corrupt.
+    /// It is left out.
+    UnreadableChunk(String),
+
+    /// The chunk could not be deserialized, eg it is from a version this
+    /// crate does not support. It is left out.
+    UnparsableChunk(String),
+
+    /// Loading or rendering the chunk panicked, and the panic was caught, see
+    /// [`ChunkErrorPolicy::catch_panics`][crate::ChunkErrorPolicy::catch_panics].
+    /// It is left out.
+    ChunkPanicked(String),
+
+    /// The chunk's `DataVersion` is outside of the versions this crate knows
+    /// about. It was parsed with whichever layout matched.
+    UnknownDataVersion(i32),
+
+    /// The chunk has no motion blocking heightmap, so surface heights are
+    /// calculated from its blocks with
+    /// [`recompute_heightmap`][crate::recompute_heightmap].
+    MissingHeightmap,
+
+    /// The block states of a section don't match its palette, eg it has
+    /// states but no palette. The section is treated as air.
+    InvalidBlockStates { section_y: i8 },
+
+    /// A section has biomes this crate does not know. They are given as
+    /// [`Biome::Unknown`][crate::biome::Biome::Unknown] or `None`.
+    UnknownBiome { section_y: i8 },
+}
+
+impl Diagnostic {
+    pub(crate) fn new(kind: DiagnosticKind) -> Self {
+        Self { kind, chunk: None }
+    }
+}
+
+impl DiagnosticKind {
+    /// Whether the chunk was left out because of this.
+    #[cfg(feature = "render")]
+    pub(crate) fn is_chunk_failure(&self) -> bool {
+        matches!(
+            self,
+            DiagnosticKind::UnreadableChunk(_)
+                | DiagnosticKind::UnparsableChunk(_)
+                | DiagnosticKind::ChunkPanicked(_)
+        )
+    }
+}
+
+impl Error for Diagnostic {}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((x, z)) = self.chunk {
+            write!(f, "chunk {}, {}: ", x.0, z.0)?;
+        }
+
+        match &self.kind {
+            DiagnosticKind::UnreadableChunk(e) => write!(f, "could not read chunk: {e}"),
+            DiagnosticKind::UnparsableChunk(e) => write!(f, "could not parse chunk: {e}"),
+            DiagnosticKind::ChunkPanicked(e) => write!(f, "chunk panicked: {e}"),
+            DiagnosticKind::UnknownDataVersion(v) => write!(f, "unknown data version {v}"),
+            DiagnosticKind::MissingHeightmap => f.write_str("missing heightmap, recalculated"),
+            DiagnosticKind::InvalidBlockStates { section_y } => write!(
+                f,
+                "block states of section {section_y} do not match its palette, treated as air"
+            ),
+            DiagnosticKind::UnknownBiome { section_y } => {
+                write!(f, "unknown biome in section {section_y}")
+            }
+        }
+    }
+}
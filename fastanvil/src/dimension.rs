@@ -1,8 +1,11 @@
-use std::io::{Read, Seek, Write};
+use std::io::{self, Read, Seek};
+use std::path::Path;
 use std::{error::Error, fmt::Display, ops::Range};
 
-use crate::Region;
-use crate::{biome::Biome, Block};
+use serde::de::DeserializeOwned;
+
+use crate::{biome::Biome, Block, BlockArchetype};
+use crate::{ChunkBitmap, JavaChunk, Region};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct RCoord(pub isize);
@@ -16,6 +19,24 @@ pub enum HeightMode {
     Calculate, // calculate height maps manually, much slower.
 }
 
+/// The blocks, biomes and heights of a chunk, whatever version it is from.
+///
+/// The trait is object safe, so code can work with a `&dyn Chunk` or a
+/// `Box<dyn Chunk>` from [`JavaChunk::boxed`][crate::JavaChunk::boxed] rather
+/// than being generic over the chunk type. Rendering accepts either.
+///
+/// Chunks that are still generating, and some in void worlds, can have no
+/// sections at all, or sections without their block data. Implementations
+/// treat these the same way, so none of the methods panic for them:
+///
+/// * A chunk without sections has an empty [`y_range`][Chunk::y_range], at
+///   the bottom of the world if the chunk records where that is. Every block
+///   and biome is `None`, and every column's surface is at the bottom of the
+///   range, whatever a stored heightmap says.
+/// * A section missing from the middle of a chunk has `None` for its blocks,
+///   biomes, fill and counts. Missing sections are air in the game.
+/// * A section with a palette of one block but no data is filled with that
+///   block. A section with no usable block data at all is air.
 pub trait Chunk: Send + Sync {
     // Status of the chunk. Typically anything except 'full' means the chunk
     // hasn't been fully generated yet. We use this to skip chunks on map edges
@@ -24,6 +45,10 @@ pub trait Chunk: Send + Sync {
 
     /// Get the height of the first air-like block above something not air-like.
     /// Will panic if given x/z coordinates outside of 0..16.
+    ///
+    /// Heights are world heights, so are below zero for surfaces low in a
+    /// 1.18 world. A column with no blocks is at the bottom of
+    /// [`y_range`][Chunk::y_range].
     fn surface_height(&self, x: usize, z: usize, mode: HeightMode) -> isize;
 
     /// Get the biome of the given coordinate. A biome may not exist if the
@@ -36,20 +61,124 @@ pub trait Chunk: Send + Sync {
     /// trying to access the block at height 1234 would return None.
     fn block(&self, x: usize, y: isize, z: usize) -> Option<&Block>;
 
-    /// Get the range of Y values that are valid for this chunk.
+    /// Get the range of Y values that are valid for this chunk. Y values
+    /// are world heights, eg from -64 in 1.18, for every method taking one.
     fn y_range(&self) -> Range<isize>;
+
+    /// If the section containing the given y is made entirely of airy blocks
+    /// or entirely of non-airy blocks, say which. This lets renderers skip
+    /// whole sections when scanning down a column. Returning `None` is always
+    /// correct, just slower.
+    fn section_fill(&self, _y: isize) -> Option<SectionFill> {
+        None
+    }
+
+    /// Count how many of each block there are in the section containing the
+    /// given y, without looking at each block. The same block may appear
+    /// more than once. Used by [`count_blocks`][crate::count_blocks].
+    /// Returning `None` is always correct, just slower.
+    fn section_block_counts(&self, _y: isize) -> Option<Vec<(&Block, usize)>> {
+        None
+    }
+
+    /// The total number of ticks players have spent near the chunk, which
+    /// Minecraft uses to raise the local difficulty. `None` if the chunk does
+    /// not record it.
+    fn inhabited_time(&self) -> Option<i64> {
+        None
+    }
+
+    /// The game tick the chunk was last saved at. `None` if the chunk does
+    /// not record it.
+    fn last_update(&self) -> Option<i64> {
+        None
+    }
+}
+
+/// What a 16x16x16 section of a chunk is made of, when every block in it is
+/// the same kind. See [`Chunk::section_fill`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionFill {
+    /// Every block is airy, eg air or cave air.
+    Air,
+    /// No block is airy.
+    Solid,
+}
+
+impl SectionFill {
+    /// Work out the fill of a section from its palette. An empty palette is
+    /// taken to be an empty section, ie air.
+    pub fn from_palette(palette: &[Block]) -> Option<Self> {
+        let airy = palette
+            .iter()
+            .filter(|b| b.archetype == BlockArchetype::Airy)
+            .count();
+
+        if airy == palette.len() {
+            Some(SectionFill::Air)
+        } else if airy == 0 {
+            Some(SectionFill::Solid)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Debug)]
-pub struct LoaderError(pub(crate) String);
+pub struct LoaderError {
+    msg: String,
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+}
 
 pub type LoaderResult<T> = std::result::Result<T, LoaderError>;
 
-impl Error for LoaderError {}
+impl LoaderError {
+    /// A directory that could not be found, even ignoring case. The source is
+    /// an IO error of kind [`NotFound`][io::ErrorKind::NotFound].
+    pub(crate) fn missing_dir(dir: &Path) -> Self {
+        Self {
+            msg: format!("directory not found: {}", dir.display()),
+            source: Some(Box::new(io::Error::from(io::ErrorKind::NotFound))),
+        }
+    }
+}
+
+impl Error for LoaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_ref().map(|e| e.as_ref() as _)
+    }
+}
 
 impl Display for LoaderError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.0)
+        f.write_str(&self.msg)
+    }
+}
+
+impl From<std::io::Error> for LoaderError {
+    fn from(err: std::io::Error) -> Self {
+        Self {
+            msg: err.to_string(),
+            source: Some(Box::new(err)),
+        }
+    }
+}
+
+impl From<crate::Error> for LoaderError {
+    fn from(err: crate::Error) -> Self {
+        Self {
+            msg: err.to_string(),
+            source: Some(Box::new(err)),
+        }
+    }
+}
+
+impl From<fastnbt::error::Error> for LoaderError {
+    fn from(err: fastnbt::error::Error) -> Self {
+        Self {
+            msg: format!("could not parse chunk: {}", err),
+            source: Some(Box::new(err)),
+        }
     }
 }
 
@@ -60,7 +189,7 @@ impl Display for LoaderError {
 /// or perhaps a WASM version loading from a file buffer in the browser.
 pub trait RegionLoader<S>
 where
-    S: Seek + Read + Write,
+    S: Seek + Read,
 {
     /// Get a particular region. Returns None if region does not exist.
     fn region(&self, x: RCoord, z: RCoord) -> Option<Region<S>>;
@@ -68,4 +197,116 @@ where
     /// List the regions that this loader can return. Implmentations need to
     /// provide this so that callers can efficiently find regions to process.
     fn list(&self) -> LoaderResult<Vec<(RCoord, RCoord)>>;
+
+    /// The chunks present in each region, from the headers of the regions
+    /// alone. Nothing is decompressed, so this takes seconds even for worlds
+    /// of many gigabytes. Regions are in the order of [`list`][Self::list],
+    /// and any that cannot be opened are left out.
+    ///
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use fastanvil::{Dimension, RegionLoader, World};
+    /// let world = World::open(Path::new("world")).unwrap();
+    /// let loader = world.region_loader(&Dimension::Overworld).unwrap();
+    ///
+    /// let coverage = loader.coverage().unwrap();
+    /// let chunks: usize = coverage.iter().map(|(_, _, chunks)| chunks.len()).sum();
+    /// println!("{chunks} chunks generated in {} regions", coverage.len());
+    /// ```
+    fn coverage(&self) -> LoaderResult<Vec<(RCoord, RCoord, ChunkBitmap)>> {
+        let mut coverage = vec![];
+        for (x, z) in self.list()? {
+            if let Some(mut region) = self.region(x, z) {
+                coverage.push((x, z, region.chunk_bitmap()?));
+            }
+        }
+        Ok(coverage)
+    }
+
+    /// Read the uncompressed NBT of the chunk at the given chunk coordinates.
+    /// Unlike [`Region::read_chunk`] these are world chunk coordinates, not
+    /// coordinates within a region. Returns None if the chunk, or the region
+    /// containing it, does not exist.
+    ///
+    /// This does not choose a type for the chunk, so the NBT can be
+    /// deserialized into your own types with `fastnbt::from_bytes`.
+    fn read_chunk(&self, x: CCoord, z: CCoord) -> crate::Result<Option<Vec<u8>>> {
+        let mut buf = vec![];
+        Ok(self.read_chunk_into(x, z, &mut buf)?.then_some(buf))
+    }
+
+    /// Like [`read_chunk`][Self::read_chunk], but reads the chunk into `buf`,
+    /// replacing its contents. Reusing the same buffer when scanning many
+    /// chunks avoids an allocation for each chunk.
+    ///
+    /// Returns a bool indicating if the chunk was found. If it was not, `buf`
+    /// is left empty.
+    fn read_chunk_into(&self, x: CCoord, z: CCoord, buf: &mut Vec<u8>) -> crate::Result<bool> {
+        buf.clear();
+
+        let (rx, rz) = (RCoord(x.0.div_euclid(32)), RCoord(z.0.div_euclid(32)));
+        match self.region(rx, rz) {
+            Some(mut region) => {
+                let (x, z) = (x.0.rem_euclid(32) as usize, z.0.rem_euclid(32) as usize);
+                region.read_chunk_into(x, z, buf)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Read and parse the chunk at the given world chunk coordinates. Returns
+    /// None if the chunk, or the region containing it, does not exist.
+    ///
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use fastanvil::{CCoord, Chunk, RegionFileLoader, RegionLoader};
+    /// let loader = RegionFileLoader::new(Path::new("world/region").to_owned());
+    /// if let Some(chunk) = loader.chunk(CCoord(-3), CCoord(40)).unwrap() {
+    ///     println!("inhabited for {:?} ticks", chunk.inhabited_time());
+    /// }
+    /// ```
+    fn chunk(&self, x: CCoord, z: CCoord) -> LoaderResult<Option<JavaChunk>> {
+        let mut buf = vec![];
+        if !self.read_chunk_into(x, z, &mut buf)? {
+            return Ok(None);
+        }
+        Ok(Some(JavaChunk::from_bytes(&buf)?))
+    }
+
+    /// Like [`chunk`][Self::chunk], but deserializes the chunk into your own
+    /// type. A type with only the fields you need is much quicker to
+    /// deserialize than a whole [`JavaChunk`]. Being generic, this cannot be
+    /// called through a `dyn RegionLoader`.
+    ///
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use fastanvil::{CCoord, RegionFileLoader, RegionLoader};
+    /// # use fastnbt::LongArray;
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct HeightsOnly {
+    ///     #[serde(rename = "Heightmaps")]
+    ///     heightmaps: Heightmaps,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Heightmaps {
+    ///     #[serde(rename = "WORLD_SURFACE")]
+    ///     world_surface: Option<LongArray>,
+    /// }
+    ///
+    /// let loader = RegionFileLoader::new(Path::new("world/region").to_owned());
+    /// let chunk = loader.chunk_as::<HeightsOnly>(CCoord(0), CCoord(0)).unwrap();
+    /// ```
+    fn chunk_as<T>(&self, x: CCoord, z: CCoord) -> LoaderResult<Option<T>>
+    where
+        T: DeserializeOwned,
+        Self: Sized,
+    {
+        let mut buf = vec![];
+        if !self.read_chunk_into(x, z, &mut buf)? {
+            return Ok(None);
+        }
+        Ok(Some(fastnbt::from_bytes(&buf)?))
+    }
 }
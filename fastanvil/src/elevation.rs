@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+
+use crate::{
+    is_rendered, load_chunk, Block, Chunk, Palette, RCoord, RegionLoader, TopShadeRenderer,
+    CHUNKS_PER_REGION_SIDE,
+};
+
+/// The number of blocks along each side of a region.
+const REGION_LEN: usize = CHUNKS_PER_REGION_SIDE * 16;
+
+/// The surface heights of a region from [`render_region_elevation`], eg for
+/// exporting to GIS tools.
+///
+/// The grids are `512 * 512` long, one entry per block column, row by row
+/// from the northwest corner. The column at block `x, z` within the region
+/// is at `z * 512 + x`.
+#[derive(Debug, Clone)]
+pub struct ElevationMap {
+    pub x: RCoord,
+    pub z: RCoord,
+
+    /// The height of the air above the surface of each column, as used to
+    /// shade the region by [`TopShadeRenderer`]. [`i16::MIN`] for columns
+    /// that are not rendered, eg in missing chunks.
+    pub heights: Vec<i16>,
+
+    /// The block at the surface of each column, just below its height, as an
+    /// index into [`blocks`][Self::blocks]. [`u32::MAX`] for columns without
+    /// one. Only present if asked for.
+    pub top_blocks: Option<Vec<u32>>,
+
+    /// The blocks indexed by [`top_blocks`][Self::top_blocks], each once.
+    pub blocks: Vec<Block>,
+}
+
+impl ElevationMap {
+    /// The height of the column at block `x, z` within the region, or `None`
+    /// if it was not rendered.
+    pub fn height(&self, x: usize, z: usize) -> Option<i16> {
+        Some(self.heights[z * REGION_LEN + x]).filter(|&h| h != i16::MIN)
+    }
+
+    /// The block at the surface of the column at block `x, z` within the
+    /// region, if top blocks were asked for and the column has one.
+    pub fn top_block(&self, x: usize, z: usize) -> Option<&Block> {
+        let i = self.top_blocks.as_ref()?[z * REGION_LEN + x];
+        self.blocks.get(i as usize)
+    }
+}
+
+/// Work out the surface height of every column of a region, the same heights
+/// that [`render_region`][crate::render_region] shades the region by. The
+/// renderer's height mode and dimension options are used, but its palette and
+/// render mode are not.
+///
+/// With `top_blocks`, the block at the surface of each column is recorded as
+/// well. Chunks that are missing, cannot be loaded or would not be rendered
+/// are left as [`i16::MIN`].
+///
+/// ```no_run
+/// # use std::path::PathBuf;
+/// # use fastanvil::{render_region_elevation, HeightMode, RCoord, RegionFileLoader, TopShadeRenderer};
+/// # fn run(palette: &impl fastanvil::Palette) {
+/// let loader = RegionFileLoader::new(PathBuf::from("world/region"));
+/// let renderer = TopShadeRenderer::new(palette, HeightMode::Calculate);
+///
+/// let map = render_region_elevation(RCoord(0), RCoord(0), &loader, renderer, false);
+/// println!("highest column: {:?}", map.heights.iter().max());
+/// # }
+/// ```
+pub fn render_region_elevation<P: Palette + ?Sized, S>(
+    x: RCoord,
+    z: RCoord,
+    loader: &dyn RegionLoader<S>,
+    renderer: TopShadeRenderer<P>,
+    top_blocks: bool,
+) -> ElevationMap
+where
+    S: Seek + Read,
+{
+    let mut map = ElevationMap {
+        x,
+        z,
+        heights: vec![i16::MIN; REGION_LEN * REGION_LEN],
+        top_blocks: top_blocks.then(|| vec![u32::MAX; REGION_LEN * REGION_LEN]),
+        blocks: vec![],
+    };
+
+    let Some(mut region) = loader.region(x, z) else {
+        return map;
+    };
+
+    // Indices of blocks already in `map.blocks`, by encoded description.
+    let mut indices: HashMap<String, u32> = HashMap::new();
+
+    for cz in 0..CHUNKS_PER_REGION_SIDE {
+        for cx in 0..CHUNKS_PER_REGION_SIDE {
+            let Some(chunk) = load_chunk(&mut region, cx, cz, &mut vec![]) else {
+                continue;
+            };
+            if !is_rendered(&chunk) {
+                continue;
+            }
+
+            let heights = renderer.surface_heights(&chunk);
+
+            for bz in 0..16 {
+                for bx in 0..16 {
+                    let height = heights[bz * 16 + bx];
+                    let i = (cz * 16 + bz) * REGION_LEN + cx * 16 + bx;
+                    map.heights[i] = height as i16;
+
+                    let Some(top_blocks) = map.top_blocks.as_mut() else {
+                        continue;
+                    };
+                    let Some(block) = chunk.block(bx, height - 1, bz) else {
+                        continue;
+                    };
+
+                    let index = match indices.get(block.encoded_description()) {
+                        Some(&index) => index,
+                        None => {
+                            let index = map.blocks.len() as u32;
+                            indices.insert(block.encoded_description().to_owned(), index);
+                            map.blocks.push(block.clone());
+                            index
+                        }
+                    };
+                    top_blocks[i] = index;
+                }
+            }
+        }
+    }
+
+    map
+}
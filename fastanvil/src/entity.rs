@@ -0,0 +1,313 @@
+//! Typed entities, for the common fields of every entity and the data of a
+//! few types that are most often wanted, such as dropped items and item
+//! frames.
+//!
+//! From 1.17 entities are saved in their own regions, in the `entities`
+//! directory beside `region`. Each chunk of those regions is an
+//! [`EntityChunk`]. Before 1.17 entities are in the `Entities` of the chunk
+//! itself, and each can be read as a [`TypedEntity`] all the same.
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use fastanvil::Region;
+//! # use fastanvil::entity::{EntityChunk, TypedEntity};
+//! let file = File::open("world/entities/r.0.0.mca").unwrap();
+//! let mut region = Region::from_stream(file).unwrap();
+//! let data = region.read_chunk(0, 0).unwrap().unwrap();
+//!
+//! for entity in EntityChunk::from_bytes(&data).unwrap().entities {
+//!     match entity {
+//!         TypedEntity::Item(item) => println!("{:?} on the ground", item.item),
+//!         TypedEntity::Villager(villager) => println!("{:?}", villager.data),
+//!         other => println!("{:?}", other.id()),
+//!     }
+//! }
+//! ```
+//!
+//! Anything not modelled by the typed structs is kept, in the `other` of the
+//! [`Entity`] or [`ItemStack`], or as the whole [`Value`] of a
+//! [`TypedEntity::Other`].
+
+use fastnbt::{error::Result, serde_helpers::uuid_from_int_array, IntArray, Map, Value};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+use crate::CCoord;
+
+type Compound = Map<String, Value>;
+
+/// The fields every entity has. Those that are missing are left as their
+/// default.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(try_from = "Compound")]
+pub struct Entity {
+    /// The type of the entity, eg `minecraft:item`.
+    pub id: String,
+
+    /// The position as x, y and z.
+    pub pos: [f64; 3],
+
+    /// The velocity as x, y and z, in blocks per tick.
+    pub motion: [f64; 3],
+
+    /// The yaw then the pitch, in degrees.
+    pub rotation: [f32; 2],
+
+    /// The UUID, from `UUID` or from `UUIDMost` and `UUIDLeast` before 1.16.
+    pub uuid: Option<u128>,
+
+    /// The name given to the entity, eg with a name tag. This is a JSON text
+    /// component in older versions and NBT in newer ones.
+    pub custom_name: Option<Value>,
+
+    /// Every other field of the entity.
+    pub other: Compound,
+}
+
+impl TryFrom<Compound> for Entity {
+    type Error = fastnbt::error::Error;
+
+    fn try_from(mut nbt: Compound) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct Uuid(#[serde(with = "uuid_from_int_array")] u128);
+
+        let uuid = match take::<Uuid>(&mut nbt, "UUID") {
+            Ok(Some(Uuid(uuid))) => Some(uuid),
+            _ => match (
+                take::<i64>(&mut nbt, "UUIDMost")?,
+                take::<i64>(&mut nbt, "UUIDLeast")?,
+            ) {
+                (Some(most), Some(least)) => {
+                    Some((most as u64 as u128) << 64 | least as u64 as u128)
+                }
+                _ => None,
+            },
+        };
+
+        Ok(Self {
+            id: take(&mut nbt, "id")?.unwrap_or_default(),
+            pos: take(&mut nbt, "Pos")?.unwrap_or_default(),
+            motion: take(&mut nbt, "Motion")?.unwrap_or_default(),
+            rotation: take(&mut nbt, "Rotation")?.unwrap_or_default(),
+            uuid,
+            custom_name: nbt.remove("CustomName"),
+            other: nbt,
+        })
+    }
+}
+
+/// A stack of items, eg held in an item frame or lying on the ground.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(try_from = "Compound")]
+pub struct ItemStack {
+    /// The item, eg `minecraft:diamond`.
+    pub id: String,
+
+    /// How many items are in the stack, from `Count` before 1.20.5 and
+    /// `count` after. Stacks saved without a count hold one item.
+    pub count: i32,
+
+    /// Every other field of the stack, such as the `tag` or `components`
+    /// holding enchantments and names.
+    pub other: Compound,
+}
+
+impl TryFrom<Compound> for ItemStack {
+    type Error = fastnbt::error::Error;
+
+    fn try_from(mut nbt: Compound) -> Result<Self> {
+        let count = match take::<i32>(&mut nbt, "count")? {
+            Some(count) => count,
+            None => take::<i8>(&mut nbt, "Count")?.map_or(1, i32::from),
+        };
+
+        Ok(Self {
+            id: take(&mut nbt, "id")?.unwrap_or_default(),
+            count,
+            other: nbt,
+        })
+    }
+}
+
+/// A dropped item, `minecraft:item`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemEntity {
+    pub entity: Entity,
+    pub item: Option<ItemStack>,
+}
+
+/// An item frame, `minecraft:item_frame` or `minecraft:glow_item_frame`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemFrame {
+    pub entity: Entity,
+
+    /// The item in the frame, if any.
+    pub item: Option<ItemStack>,
+
+    /// The direction the frame faces: 0 is down, 1 up, 2 north, 3 south, 4
+    /// west and 5 east.
+    pub facing: Option<i8>,
+}
+
+/// An armor stand, `minecraft:armor_stand`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArmorStand {
+    pub entity: Entity,
+    pub pose: ArmorStandPose,
+}
+
+/// The rotation of each part of an [`ArmorStand`] as x, y and z, in degrees.
+/// Parts that have not been posed are `None`, and have their default pose.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ArmorStandPose {
+    #[serde(rename = "Head")]
+    pub head: Option<[f32; 3]>,
+
+    #[serde(rename = "Body")]
+    pub body: Option<[f32; 3]>,
+
+    #[serde(rename = "LeftArm")]
+    pub left_arm: Option<[f32; 3]>,
+
+    #[serde(rename = "RightArm")]
+    pub right_arm: Option<[f32; 3]>,
+
+    #[serde(rename = "LeftLeg")]
+    pub left_leg: Option<[f32; 3]>,
+
+    #[serde(rename = "RightLeg")]
+    pub right_leg: Option<[f32; 3]>,
+}
+
+/// A villager, `minecraft:villager`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Villager {
+    pub entity: Entity,
+
+    /// The job of the villager. Villagers from before 1.14 have none.
+    pub data: Option<VillagerData>,
+}
+
+/// The job of a [`Villager`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct VillagerData {
+    /// The profession, eg `minecraft:librarian` or `minecraft:none`.
+    pub profession: String,
+
+    /// The level of the villager, from 1 for a novice to 5 for a master.
+    pub level: i32,
+
+    /// The biome the villager comes from, eg `minecraft:plains`.
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// An entity, typed by its `id`. Entities of other types are kept as a
+/// [`Value`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "Compound")]
+pub enum TypedEntity {
+    Item(ItemEntity),
+    ItemFrame(ItemFrame),
+    ArmorStand(ArmorStand),
+    Villager(Villager),
+    Other(Value),
+}
+
+impl TypedEntity {
+    /// The type of the entity, eg `minecraft:item`. `None` only for an
+    /// [`Other`][TypedEntity::Other] without an `id`.
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            TypedEntity::Item(item) => Some(&item.entity.id),
+            TypedEntity::ItemFrame(frame) => Some(&frame.entity.id),
+            TypedEntity::ArmorStand(stand) => Some(&stand.entity.id),
+            TypedEntity::Villager(villager) => Some(&villager.entity.id),
+            TypedEntity::Other(value) => value.pointer("/id")?.as_str(),
+        }
+    }
+
+    /// The common fields of the entity, unless it is an
+    /// [`Other`][TypedEntity::Other].
+    pub fn entity(&self) -> Option<&Entity> {
+        match self {
+            TypedEntity::Item(item) => Some(&item.entity),
+            TypedEntity::ItemFrame(frame) => Some(&frame.entity),
+            TypedEntity::ArmorStand(stand) => Some(&stand.entity),
+            TypedEntity::Villager(villager) => Some(&villager.entity),
+            TypedEntity::Other(_) => None,
+        }
+    }
+}
+
+impl TryFrom<Compound> for TypedEntity {
+    type Error = fastnbt::error::Error;
+
+    fn try_from(mut nbt: Compound) -> Result<Self> {
+        let id = nbt.get("id").and_then(Value::as_str).unwrap_or_default();
+
+        Ok(match id {
+            "minecraft:item" => TypedEntity::Item(ItemEntity {
+                item: take(&mut nbt, "Item")?,
+                entity: nbt.try_into()?,
+            }),
+            "minecraft:item_frame" | "minecraft:glow_item_frame" => {
+                TypedEntity::ItemFrame(ItemFrame {
+                    item: take(&mut nbt, "Item")?,
+                    facing: take(&mut nbt, "Facing")?,
+                    entity: nbt.try_into()?,
+                })
+            }
+            "minecraft:armor_stand" => TypedEntity::ArmorStand(ArmorStand {
+                pose: take(&mut nbt, "Pose")?.unwrap_or_default(),
+                entity: nbt.try_into()?,
+            }),
+            "minecraft:villager" => TypedEntity::Villager(Villager {
+                data: take(&mut nbt, "VillagerData")?,
+                entity: nbt.try_into()?,
+            }),
+            _ => TypedEntity::Other(Value::Compound(nbt)),
+        })
+    }
+}
+
+/// A chunk of an entity region, from the `entities` directory of 1.17 and
+/// later worlds.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EntityChunk {
+    #[serde(rename = "DataVersion")]
+    pub data_version: i32,
+
+    /// The chunk the entities are in.
+    #[serde(rename = "Position", deserialize_with = "chunk_position")]
+    pub position: (CCoord, CCoord),
+
+    #[serde(rename = "Entities", default)]
+    pub entities: Vec<TypedEntity>,
+}
+
+impl EntityChunk {
+    /// Read the uncompressed NBT of a chunk, eg from [`Region::read_chunk`][crate::Region::read_chunk].
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        fastnbt::from_bytes(data)
+    }
+}
+
+fn chunk_position<'de, D: Deserializer<'de>>(
+    d: D,
+) -> std::result::Result<(CCoord, CCoord), D::Error> {
+    let pos = IntArray::deserialize(d)?;
+    match *pos {
+        [x, z] => Ok((CCoord(x as isize), CCoord(z as isize))),
+        _ => Err(serde::de::Error::invalid_length(
+            pos.len(),
+            &"a position of 2 ints",
+        )),
+    }
+}
+
+/// Remove a field, deserializing it if it is there.
+fn take<T: DeserializeOwned>(nbt: &mut Compound, key: &str) -> Result<Option<T>> {
+    nbt.remove(key)
+        .map(|value| fastnbt::from_value(&value))
+        .transpose()
+}
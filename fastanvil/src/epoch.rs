@@ -0,0 +1,121 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A point in time that can be converted to and from milliseconds since the
+/// Unix epoch. Used by the [`epoch_seconds`] and [`epoch_millis`] serde
+/// adapters and [`Region::last_saved`][crate::Region::last_saved].
+///
+/// Implemented for [`SystemTime`], and for `time::OffsetDateTime` when the
+/// `time` feature is enabled.
+pub trait EpochTime: Sized {
+    /// Returns `None` if the time cannot be represented by this type.
+    fn from_epoch_millis(millis: i64) -> Option<Self>;
+
+    /// Returns `None` if the time does not fit in an `i64` of milliseconds.
+    fn to_epoch_millis(&self) -> Option<i64>;
+}
+
+impl EpochTime for SystemTime {
+    fn from_epoch_millis(millis: i64) -> Option<Self> {
+        let offset = Duration::from_millis(millis.unsigned_abs());
+        if millis >= 0 {
+            UNIX_EPOCH.checked_add(offset)
+        } else {
+            UNIX_EPOCH.checked_sub(offset)
+        }
+    }
+
+    fn to_epoch_millis(&self) -> Option<i64> {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => after.as_millis().try_into().ok(),
+            Err(before) => {
+                // Round towards negative infinity, like the seconds adapter.
+                let before = before.duration();
+                let millis =
+                    before.as_millis() + u128::from(before.subsec_nanos() % 1_000_000 != 0);
+                i64::try_from(millis).ok().map(|m| -m)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl EpochTime for time::OffsetDateTime {
+    fn from_epoch_millis(millis: i64) -> Option<Self> {
+        Self::from_unix_timestamp_nanos(millis as i128 * 1_000_000).ok()
+    }
+
+    fn to_epoch_millis(&self) -> Option<i64> {
+        self.unix_timestamp_nanos()
+            .div_euclid(1_000_000)
+            .try_into()
+            .ok()
+    }
+}
+
+/// Serde adapter for times stored as seconds since the Unix epoch, for use
+/// with `#[serde(with = "fastanvil::epoch_seconds")]` on an [`EpochTime`]
+/// field. Times are serialized as a Long, and any integer is accepted when
+/// deserializing. Sub-second precision is lost when serializing.
+pub mod epoch_seconds {
+    use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serializer};
+
+    use super::EpochTime;
+
+    pub fn serialize<T, S>(time: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: EpochTime,
+        S: Serializer,
+    {
+        let millis = time
+            .to_epoch_millis()
+            .ok_or_else(|| S::Error::custom("time out of range"))?;
+
+        serializer.serialize_i64(millis.div_euclid(1000))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: EpochTime,
+        D: Deserializer<'de>,
+    {
+        let secs = i64::deserialize(deserializer)?;
+
+        secs.checked_mul(1000)
+            .and_then(T::from_epoch_millis)
+            .ok_or_else(|| D::Error::custom(format!("time out of range: {} seconds", secs)))
+    }
+}
+
+/// Serde adapter for times stored as milliseconds since the Unix epoch, such
+/// as `LastPlayed` in level.dat. For use with
+/// `#[serde(with = "fastanvil::epoch_millis")]` on an [`EpochTime`] field.
+/// Times are serialized as a Long, and any integer is accepted when
+/// deserializing.
+pub mod epoch_millis {
+    use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serializer};
+
+    use super::EpochTime;
+
+    pub fn serialize<T, S>(time: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: EpochTime,
+        S: Serializer,
+    {
+        let millis = time
+            .to_epoch_millis()
+            .ok_or_else(|| S::Error::custom("time out of range"))?;
+
+        serializer.serialize_i64(millis)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: EpochTime,
+        D: Deserializer<'de>,
+    {
+        let millis = i64::deserialize(deserializer)?;
+
+        T::from_epoch_millis(millis)
+            .ok_or_else(|| D::Error::custom(format!("time out of range: {} milliseconds", millis)))
+    }
+}
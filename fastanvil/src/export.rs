@@ -0,0 +1,508 @@
+//! Export a table with a row for each chunk of a world, eg to analyse it with
+//! pandas or a spreadsheet.
+//!
+//! The columns are chosen with [`ColumnSpec`]. [`chunks_table`] writes the
+//! table as CSV, and with the `arrow` feature `chunks_record_batch` builds an
+//! Arrow record batch and `chunks_parquet` writes a Parquet file.
+//!
+//! ```no_run
+//! # use std::path::Path;
+//! # use fastanvil::{export::{chunks_table, ColumnSpec}, RegionFileLoader};
+//! let loader = RegionFileLoader::new(Path::new("world/region").to_owned());
+//! let columns = [
+//!     ColumnSpec::X,
+//!     ColumnSpec::Z,
+//!     ColumnSpec::InhabitedTime,
+//!     ColumnSpec::BlockCount("minecraft:*diamond_ore".to_owned()),
+//! ];
+//! let file = std::fs::File::create("chunks.csv").unwrap();
+//! chunks_table(&loader, &columns, std::io::BufWriter::new(file)).unwrap();
+//! ```
+//!
+//! Each value is read in the cheapest way there is. Columns that are not
+//! about blocks or biomes only need a few fields of each chunk, so the
+//! sections are skipped over without being deserialized. Block counts first
+//! check the chunk's palettes with [`ChunkPalette`], and only parse the whole
+//! chunk if a block being counted is in them.
+
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+    io::{self, Read, Seek, Write},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    count_blocks, version::DataVersion, BlockFilter, CCoord, Chunk, ChunkPalette, JavaChunk,
+    LoaderError, RegionLoader, CHUNKS_PER_REGION_SIDE,
+};
+
+/// A column of the table exported by [`chunks_table`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnSpec {
+    /// The chunk's x coordinate in the world, in chunks. Named `x`.
+    X,
+    /// The chunk's z coordinate in the world, in chunks. Named `z`.
+    Z,
+    /// The `DataVersion` of the game that saved the chunk. Named
+    /// `data_version`, and empty for chunks from before 1.9.
+    DataVersion,
+    /// See [`Chunk::inhabited_time`]. Named `inhabited_time`.
+    InhabitedTime,
+    /// See [`Chunk::last_update`]. Named `last_update`.
+    LastUpdate,
+    /// How far the chunk is through generation, eg `minecraft:full`. Named
+    /// `status`.
+    Status,
+    /// The number of blocks in the chunk whose name matches, which can
+    /// contain `*` as for [`BlockFilter::name`]. Named after the name given,
+    /// eg `minecraft:*_ore`. Empty for chunks from before 1.13, which have
+    /// numeric block ids.
+    BlockCount(String),
+    /// How many of each biome the chunk has, as a JSON object of biome id to
+    /// count, eg `{"minecraft:plains":48,"minecraft:river":16}`. Biomes are
+    /// counted for each 4x4x4 cell, and biomes from before 1.18 are counted as
+    /// the biome that replaced them. Named `biomes`.
+    Biomes,
+}
+
+impl ColumnSpec {
+    /// The name of the column, as used in the header of the table.
+    pub fn name(&self) -> &str {
+        match self {
+            ColumnSpec::X => "x",
+            ColumnSpec::Z => "z",
+            ColumnSpec::DataVersion => "data_version",
+            ColumnSpec::InhabitedTime => "inhabited_time",
+            ColumnSpec::LastUpdate => "last_update",
+            ColumnSpec::Status => "status",
+            ColumnSpec::BlockCount(name) => name,
+            ColumnSpec::Biomes => "biomes",
+        }
+    }
+}
+
+/// Error from exporting a table.
+#[derive(Debug)]
+pub enum ExportError {
+    /// The regions could not be listed.
+    Loader(LoaderError),
+    /// A region could not be read.
+    Region(crate::Error),
+    /// The table could not be written.
+    IO(std::io::Error),
+    #[cfg(feature = "arrow")]
+    Arrow(arrow_schema::ArrowError),
+    #[cfg(feature = "arrow")]
+    Parquet(parquet::errors::ParquetError),
+}
+
+pub type ExportResult<T> = std::result::Result<T, ExportError>;
+
+impl From<LoaderError> for ExportError {
+    fn from(err: LoaderError) -> Self {
+        ExportError::Loader(err)
+    }
+}
+
+impl From<crate::Error> for ExportError {
+    fn from(err: crate::Error) -> Self {
+        ExportError::Region(err)
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(err: std::io::Error) -> Self {
+        ExportError::IO(err)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<arrow_schema::ArrowError> for ExportError {
+    fn from(err: arrow_schema::ArrowError) -> Self {
+        ExportError::Arrow(err)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<parquet::errors::ParquetError> for ExportError {
+    fn from(err: parquet::errors::ParquetError) -> Self {
+        ExportError::Parquet(err)
+    }
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Loader(e) => write!(f, "could not list regions: {e}"),
+            ExportError::Region(e) => write!(f, "could not read region: {e}"),
+            ExportError::IO(e) => write!(f, "io error: {e}"),
+            #[cfg(feature = "arrow")]
+            ExportError::Arrow(e) => write!(f, "arrow error: {e}"),
+            #[cfg(feature = "arrow")]
+            ExportError::Parquet(e) => write!(f, "parquet error: {e}"),
+        }
+    }
+}
+
+impl Error for ExportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExportError::Loader(e) => Some(e),
+            ExportError::Region(e) => Some(e),
+            ExportError::IO(e) => Some(e),
+            #[cfg(feature = "arrow")]
+            ExportError::Arrow(e) => Some(e),
+            #[cfg(feature = "arrow")]
+            ExportError::Parquet(e) => Some(e),
+        }
+    }
+}
+
+/// Write a table with a row for each chunk the loader has, as CSV with a
+/// header. Rows are ordered by region, then by chunk within the region.
+///
+/// Chunks that cannot be read or parsed still get a row, with their coordinates and
+/// empty values for everything that could not be read. The writer is not
+/// buffered, so wrap it in a [`BufWriter`][std::io::BufWriter] if needed.
+pub fn chunks_table<S, L, W>(loader: &L, columns: &[ColumnSpec], mut writer: W) -> ExportResult<()>
+where
+    S: Read + Seek,
+    L: RegionLoader<S> + ?Sized,
+    W: Write,
+{
+    let header: Vec<_> = columns.iter().map(|c| csv_field(c.name())).collect();
+    writeln!(writer, "{}", header.join(","))?;
+
+    let mut line = String::new();
+    for_each_row(loader, columns, |row| {
+        line.clear();
+        for (i, value) in row.into_iter().enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            match value {
+                Value::Int(v) => line.push_str(&v.to_string()),
+                Value::Text(s) => line.push_str(&csv_field(&s)),
+                Value::Missing => {}
+            }
+        }
+        writeln!(writer, "{line}")?;
+        Ok(())
+    })?;
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Build an Arrow record batch with a row for each chunk the loader has, with
+/// the rows of [`chunks_table`]. Coordinates and counts are integers, and
+/// values that could not be read are null. Needs the `arrow` feature.
+#[cfg(feature = "arrow")]
+pub fn chunks_record_batch<S, L>(
+    loader: &L,
+    columns: &[ColumnSpec],
+) -> ExportResult<arrow_array::RecordBatch>
+where
+    S: Read + Seek,
+    L: RegionLoader<S> + ?Sized,
+{
+    arrow::record_batch(loader, columns)
+}
+
+/// Write a Parquet file with a row for each chunk the loader has, holding
+/// the record batch from `chunks_record_batch`. Needs the `arrow` feature.
+#[cfg(feature = "arrow")]
+pub fn chunks_parquet<S, L, W>(loader: &L, columns: &[ColumnSpec], writer: W) -> ExportResult<()>
+where
+    S: Read + Seek,
+    L: RegionLoader<S> + ?Sized,
+    W: Write + Send,
+{
+    let batch = arrow::record_batch(loader, columns)?;
+    let mut writer = parquet::arrow::ArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// A value in the table.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Int(i64),
+    Text(String),
+    Missing,
+}
+
+/// Call `f` with the values of each row of the table.
+fn for_each_row<S, L>(
+    loader: &L,
+    columns: &[ColumnSpec],
+    mut f: impl FnMut(Vec<Value>) -> ExportResult<()>,
+) -> ExportResult<()>
+where
+    S: Read + Seek,
+    L: RegionLoader<S> + ?Sized,
+{
+    let filters: Vec<_> = columns
+        .iter()
+        .map(|c| match c {
+            ColumnSpec::BlockCount(name) => Some(BlockFilter::new().name(name.as_str())),
+            _ => None,
+        })
+        .collect();
+    let wants_sections =
+        filters.iter().any(Option::is_some) || columns.contains(&ColumnSpec::Biomes);
+
+    let mut regions = loader.list()?;
+    regions.sort_by_key(|&(x, z)| (z, x));
+
+    for (rx, rz) in regions {
+        // The region was listed, so if it cannot be opened it is unreadable
+        // rather than missing.
+        let Some(mut region) = loader.region(rx, rz) else {
+            let msg = format!("region {}, {} could not be opened", rx.0, rz.0);
+            return Err(crate::Error::IO(io::Error::new(io::ErrorKind::InvalidData, msg)).into());
+        };
+
+        region.scan_chunks(|x, z, data| {
+            let side = CHUNKS_PER_REGION_SIDE as isize;
+            let pos = (
+                CCoord(rx.0 * side + x as isize),
+                CCoord(rz.0 * side + z as isize),
+            );
+            // A chunk that could not be read has nothing to parse.
+            let data = data.unwrap_or_default();
+            let fields = fastnbt::from_bytes::<FieldsChunk>(data).ok();
+            let has_palettes = fields
+                .as_ref()
+                .and_then(|f| f.data_version)
+                .is_some_and(|v| v >= DataVersion::V1_13);
+
+            let sections = if !wants_sections {
+                Sections::Unread
+            } else if has_palettes {
+                Sections::read(data, &filters, columns.contains(&ColumnSpec::Biomes))
+            } else {
+                Sections::Unparsable
+            };
+
+            let row = columns
+                .iter()
+                .zip(&filters)
+                .map(|(column, filter)| value(column, filter, pos, fields.as_ref(), &sections))
+                .collect();
+            f(row)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// What could be read of a chunk's sections.
+enum Sections {
+    /// Nothing needed the sections.
+    Unread,
+    /// The palettes had nothing being counted, so the chunk was not parsed.
+    NothingCounted,
+    Parsed(Box<JavaChunk>),
+    /// The chunk could not be parsed, or is from before 1.13 and has no
+    /// palettes.
+    Unparsable,
+}
+
+impl Sections {
+    fn read(data: &[u8], filters: &[Option<BlockFilter>], biomes: bool) -> Self {
+        if !biomes {
+            let Ok(palette) = ChunkPalette::from_bytes(data) else {
+                return Sections::Unparsable;
+            };
+            let counted = palette
+                .blocks()
+                .iter()
+                .any(|b| filters.iter().flatten().any(|f| f.matches(b)));
+            if !counted {
+                return Sections::NothingCounted;
+            }
+        }
+
+        match JavaChunk::from_bytes(data) {
+            Ok(chunk) => Sections::Parsed(Box::new(chunk)),
+            Err(_) => Sections::Unparsable,
+        }
+    }
+}
+
+fn value(
+    column: &ColumnSpec,
+    filter: &Option<BlockFilter>,
+    (x, z): (CCoord, CCoord),
+    fields: Option<&FieldsChunk>,
+    sections: &Sections,
+) -> Value {
+    let int = |v: Option<i64>| v.map_or(Value::Missing, Value::Int);
+    let level = fields.and_then(|f| f.level.as_ref());
+
+    match column {
+        ColumnSpec::X => Value::Int(x.0 as i64),
+        ColumnSpec::Z => Value::Int(z.0 as i64),
+        ColumnSpec::DataVersion => int(fields.and_then(|f| f.data_version).map(|v| v.0 as i64)),
+        ColumnSpec::InhabitedTime => int(level
+            .and_then(|l| l.inhabited_time)
+            .or_else(|| fields?.inhabited_time)),
+        ColumnSpec::LastUpdate => int(level
+            .and_then(|l| l.last_update)
+            .or_else(|| fields?.last_update)),
+        ColumnSpec::Status => level
+            .and_then(|l| l.status.clone())
+            .or_else(|| fields?.status.clone())
+            .map_or(Value::Missing, Value::Text),
+        ColumnSpec::BlockCount(_) => match (sections, filter) {
+            (Sections::NothingCounted, _) => Value::Int(0),
+            (Sections::Parsed(chunk), Some(filter)) => {
+                Value::Int(count_blocks(chunk.as_ref(), filter).values().sum::<u64>() as i64)
+            }
+            _ => Value::Missing,
+        },
+        ColumnSpec::Biomes => match sections {
+            Sections::Parsed(chunk) => Value::Text(biome_histogram(chunk.as_ref())),
+            _ => Value::Missing,
+        },
+    }
+}
+
+/// The number of each biome in a chunk as a JSON object, sorted by id.
+fn biome_histogram(chunk: &JavaChunk) -> String {
+    let mut counts = BTreeMap::new();
+    for y in chunk.y_range().step_by(4) {
+        for z in (0..16).step_by(4) {
+            for x in (0..16).step_by(4) {
+                if let Some(biome) = chunk.biome(x, y, z) {
+                    *counts.entry(biome.name_1_18()).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let entries: Vec<_> = counts
+        .into_iter()
+        .map(|(name, count)| format!("\"{name}\":{count}"))
+        .collect();
+    format!("{{{}}}", entries.join(","))
+}
+
+/// Quote a CSV field if it needs it.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+// Just the fields wanted, for both chunk layouts. The sections are skipped.
+
+#[derive(Deserialize)]
+struct FieldsChunk {
+    #[serde(rename = "DataVersion")]
+    data_version: Option<DataVersion>,
+
+    #[serde(rename = "Level")]
+    level: Option<Fields>,
+
+    #[serde(rename = "Status")]
+    status: Option<String>,
+
+    #[serde(rename = "InhabitedTime")]
+    inhabited_time: Option<i64>,
+
+    #[serde(rename = "LastUpdate")]
+    last_update: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct Fields {
+    #[serde(rename = "Status")]
+    status: Option<String>,
+
+    #[serde(rename = "InhabitedTime")]
+    inhabited_time: Option<i64>,
+
+    #[serde(rename = "LastUpdate")]
+    last_update: Option<i64>,
+}
+
+#[cfg(feature = "arrow")]
+mod arrow {
+    use std::{
+        io::{Read, Seek},
+        sync::Arc,
+    };
+
+    use arrow_array::{
+        builder::{Int64Builder, StringBuilder},
+        ArrayRef, RecordBatch,
+    };
+    use arrow_schema::{DataType, Field, Schema};
+
+    use super::{for_each_row, ColumnSpec, ExportResult, Value};
+    use crate::RegionLoader;
+
+    enum Builder {
+        Int(Int64Builder),
+        Text(StringBuilder),
+    }
+
+    pub(super) fn record_batch<S, L>(
+        loader: &L,
+        columns: &[ColumnSpec],
+    ) -> ExportResult<RecordBatch>
+    where
+        S: Read + Seek,
+        L: RegionLoader<S> + ?Sized,
+    {
+        let mut builders: Vec<_> = columns
+            .iter()
+            .map(|c| match c {
+                ColumnSpec::Status | ColumnSpec::Biomes => Builder::Text(StringBuilder::new()),
+                _ => Builder::Int(Int64Builder::new()),
+            })
+            .collect();
+
+        for_each_row(loader, columns, |row| {
+            for (builder, value) in builders.iter_mut().zip(row) {
+                match (builder, value) {
+                    (Builder::Int(b), Value::Int(v)) => b.append_value(v),
+                    (Builder::Text(b), Value::Text(s)) => b.append_value(s),
+                    (Builder::Int(b), _) => b.append_null(),
+                    (Builder::Text(b), _) => b.append_null(),
+                }
+            }
+            Ok(())
+        })?;
+
+        let fields: Vec<_> = columns
+            .iter()
+            .zip(&builders)
+            .map(|(column, builder)| {
+                let data_type = match builder {
+                    Builder::Int(_) => DataType::Int64,
+                    Builder::Text(_) => DataType::Utf8,
+                };
+                let nullable = !matches!(column, ColumnSpec::X | ColumnSpec::Z);
+                Field::new(column.name(), data_type, nullable)
+            })
+            .collect();
+        let arrays: Vec<ArrayRef> = builders
+            .into_iter()
+            .map(|builder| match builder {
+                Builder::Int(mut b) => Arc::new(b.finish()) as ArrayRef,
+                Builder::Text(mut b) => Arc::new(b.finish()) as ArrayRef,
+            })
+            .collect();
+
+        Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+    }
+}
@@ -1,15 +1,22 @@
-use crate::{JavaChunk, LoaderError};
-use crate::{LoaderResult, Region};
+use crate::JavaChunk;
+use crate::{LoaderError, LoaderResult, Region, DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN};
 use crate::{RCoord, RegionLoader};
 use std::fs::File;
+#[cfg(feature = "mmap")]
+use std::io::Cursor;
 use std::marker::PhantomData;
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+/// Loads regions from a directory of region files.
+///
+/// If no directory has the exact name given, a directory whose name only
+/// differs in ASCII case is used instead, eg `Region` for `region`. Worlds
+/// copied from Windows can have these, which only matters on case-sensitive
+/// filesystems. The same goes for the directories the region directory is in.
 pub struct RegionFileLoader {
     region_dir: PathBuf,
+    max_uncompressed_len: usize,
     _d: PhantomData<JavaChunk>,
 }
 
@@ -17,44 +24,226 @@ impl RegionFileLoader {
     pub fn new(region_dir: PathBuf) -> Self {
         Self {
             region_dir,
+            max_uncompressed_len: DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN,
             _d: PhantomData,
         }
     }
+
+    /// Set the most bytes a chunk can be once decompressed, for the regions
+    /// this loader returns. See [`Region::with_max_uncompressed_len`].
+    pub fn with_max_uncompressed_len(mut self, limit: usize) -> Self {
+        self.max_uncompressed_len = limit;
+        self
+    }
+
+    /// The region files in the directory, sorted by their x then z
+    /// coordinate. See [`RegionEntry`].
+    ///
+    /// Unlike [`list`][RegionLoader::list], empty files are included, flagged
+    /// with [`empty`][RegionEntry::empty].
+    pub fn list_detailed(&self) -> LoaderResult<Vec<RegionEntry>> {
+        list_region_entries(&self.region_dir)
+    }
 }
 
 impl RegionLoader<File> for RegionFileLoader {
     fn region(&self, x: RCoord, z: RCoord) -> Option<Region<File>> {
-        let path = self.region_dir.join(format!("r.{}.{}.mca", x.0, z.0));
+        let path = region_path(&find_dir(&self.region_dir)?, x, z)?;
         let file = std::fs::File::open(path).ok()?;
         let region = Region::from_stream(file).ok()?; // TODO: Really need to return Result not option.
 
-        Some(region)
+        Some(region.with_max_uncompressed_len(self.max_uncompressed_len))
     }
 
     fn list(&self) -> LoaderResult<Vec<(RCoord, RCoord)>> {
-        let paths = std::fs::read_dir(&self.region_dir).map_err(|e| LoaderError(e.to_string()))?;
-
-        let paths = paths
-            .into_iter()
-            .filter_map(|path| path.ok())
-            .map(|path| path.path())
-            .filter(|path| path.is_file())
-            .filter(|path| {
-                let ext = path.extension();
-                ext.is_some() && ext.unwrap() == "mca"
-            })
-            .filter(|path| fs::metadata(path).unwrap().len() > 0)
-            .filter_map(|p| coords_from_region(&p))
-            .collect();
+        list_regions(&self.region_dir)
+    }
+}
+
+/// Loads regions from a directory of region files by memory mapping them,
+/// rather than reading them with a file handle. Reading chunks is then copying
+/// from memory rather than a read syscall, which makes random access to chunks
+/// faster, eg serving map tiles. Requires the `mmap` feature.
+///
+/// Regions from this loader are backed by a slice, so chunks can be
+/// decompressed without copying their compressed data first with
+/// [`Region::decompress_chunk_into`]. The loader and its regions are `Send`
+/// and `Sync`.
+///
+/// To map a single region file, create the map with [`memmap2::Mmap`] and pass
+/// it to [`Region::from_stream`] in a [`Cursor`].
+#[cfg(feature = "mmap")]
+#[derive(Debug, Clone)]
+pub struct MmapRegionLoader {
+    region_dir: PathBuf,
+    max_uncompressed_len: usize,
+}
+
+#[cfg(feature = "mmap")]
+impl MmapRegionLoader {
+    /// Create a loader for the region files in the given directory.
+    ///
+    /// # Safety
+    ///
+    /// A memory map sees any changes made to the file, so a region file being
+    /// modified while it is mapped is undefined behaviour. Region files must
+    /// not be changed, eg by a running server, while any region returned by
+    /// this loader is alive. See [`memmap2::Mmap::map`].
+    pub unsafe fn new(region_dir: PathBuf) -> Self {
+        Self {
+            region_dir,
+            max_uncompressed_len: DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN,
+        }
+    }
+
+    /// Set the most bytes a chunk can be once decompressed, for the regions
+    /// this loader returns. See [`Region::with_max_uncompressed_len`].
+    pub fn with_max_uncompressed_len(mut self, limit: usize) -> Self {
+        self.max_uncompressed_len = limit;
+        self
+    }
+
+    /// The region files in the directory. See
+    /// [`RegionFileLoader::list_detailed`].
+    pub fn list_detailed(&self) -> LoaderResult<Vec<RegionEntry>> {
+        list_region_entries(&self.region_dir)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl RegionLoader<Cursor<memmap2::Mmap>> for MmapRegionLoader {
+    fn region(&self, x: RCoord, z: RCoord) -> Option<Region<Cursor<memmap2::Mmap>>> {
+        let path = region_path(&find_dir(&self.region_dir)?, x, z)?;
+        let file = std::fs::File::open(path).ok()?;
+
+        // SAFETY: The creator of the loader promised the files are not
+        // modified while mapped.
+        let map = unsafe { memmap2::Mmap::map(&file) }.ok()?;
+        let region = Region::from_stream(Cursor::new(map)).ok()?;
+        Some(region.with_max_uncompressed_len(self.max_uncompressed_len))
+    }
 
-        Ok(paths)
+    fn list(&self) -> LoaderResult<Vec<(RCoord, RCoord)>> {
+        list_regions(&self.region_dir)
+    }
+}
+
+/// Find a directory, matching each name on the way to it regardless of ASCII
+/// case when there is no exact match. If several match, eg `Region` and
+/// `REGION`, the first in sorted order is used. `None` if nothing matches.
+pub(crate) fn find_dir(dir: &Path) -> Option<PathBuf> {
+    if dir.is_dir() {
+        return Some(dir.to_owned());
     }
+
+    let name = dir.file_name()?;
+    let parent = match dir.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => find_dir(parent)?,
+        // A relative path of one name is looked for in the current directory.
+        _ => PathBuf::new(),
+    };
+    let listing = if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        &parent
+    };
+
+    let mut found: Vec<_> = std::fs::read_dir(listing)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().eq_ignore_ascii_case(name))
+        .map(|entry| parent.join(entry.file_name()))
+        .filter(|path| path.is_dir())
+        .collect();
+    found.sort();
+    found.into_iter().next()
+}
+
+/// Find the file for a region. Old worlds have McRegion `.mcr` files rather
+/// than Anvil `.mca` ones. Worlds converted to Anvil keep their old files, so
+/// `.mca` is preferred.
+fn region_path(region_dir: &Path, x: RCoord, z: RCoord) -> Option<PathBuf> {
+    ["mca", "mcr"]
+        .iter()
+        .map(|ext| region_dir.join(format!("r.{}.{}.{}", x.0, z.0, ext)))
+        .find(|path| path.is_file())
+}
+
+/// A region file in a directory, from
+/// [`RegionFileLoader::list_detailed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionEntry {
+    pub x: RCoord,
+    pub z: RCoord,
+
+    /// The file the region is loaded from. When a region has both an `.mca`
+    /// and an `.mcr` file this is the `.mca` one, as that is the one loaded.
+    pub path: PathBuf,
+
+    /// The length of the file in bytes.
+    pub size: u64,
+
+    /// When the file was last modified. `None` if the platform does not
+    /// record it.
+    pub modified: Option<SystemTime>,
+
+    /// Whether the file is empty. Minecraft sometimes leaves empty region
+    /// files behind, which have no chunks and cannot be loaded.
+    pub empty: bool,
+}
+
+/// Regions from [`list_region_entries`] that can be loaded.
+fn list_regions(region_dir: &Path) -> LoaderResult<Vec<(RCoord, RCoord)>> {
+    Ok(list_region_entries(region_dir)?
+        .into_iter()
+        .filter(|entry| !entry.empty)
+        .map(|entry| (entry.x, entry.z))
+        .collect())
+}
+
+fn list_region_entries(region_dir: &Path) -> LoaderResult<Vec<RegionEntry>> {
+    let dir = find_dir(region_dir).ok_or_else(|| LoaderError::missing_dir(region_dir))?;
+    let paths = std::fs::read_dir(dir)?;
+
+    let mut entries: Vec<_> = paths
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let ext = path.extension()?;
+            if ext != "mca" && ext != "mcr" {
+                return None;
+            }
+
+            let (x, z) = coords_from_region(&path)?;
+            // Files removed while listing are skipped rather than failing.
+            let meta = std::fs::metadata(&path).ok().filter(|m| m.is_file())?;
+
+            Some(RegionEntry {
+                x,
+                z,
+                size: meta.len(),
+                modified: meta.modified().ok(),
+                empty: meta.len() == 0,
+                path,
+            })
+        })
+        .collect();
+
+    // A region can have both an .mca and .mcr file. Sorting puts the .mca
+    // first, which is the one kept, matching `region_path`.
+    entries.sort_by(|a, b| (a.x, a.z, a.path.extension()).cmp(&(b.x, b.z, b.path.extension())));
+    entries.dedup_by_key(|entry| (entry.x, entry.z));
+
+    Ok(entries)
 }
 
+/// The coordinates of a region file named `r.<x>.<z>.<ext>`. Anything else is
+/// not a region file, including names with more to them such as the
+/// `r.0.0.mca:Zone.Identifier` files left by copying from Windows.
 fn coords_from_region(region: &Path) -> Option<(RCoord, RCoord)> {
     let filename = region.file_name()?.to_str()?;
-    let mut parts = filename.split('.').skip(1);
-    let x = parts.next()?.parse::<isize>().ok()?;
-    let z = parts.next()?.parse::<isize>().ok()?;
-    Some((RCoord(x), RCoord(z)))
+    let ["r", x, z, _] = filename.split('.').collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    Some((RCoord(x.parse().ok()?), RCoord(z.parse().ok()?)))
 }
@@ -0,0 +1,333 @@
+//! Sample chunks and regions for testing code that uses this crate, without
+//! needing a Minecraft world of your own. Needs the `test-fixtures` feature.
+//!
+//! The chunks are real chunks saved by the game at several data versions,
+//! as uncompressed NBT. Regions are built in memory from them, ready for
+//! [`Region::from_stream`]:
+//!
+//! ```
+//! # use fastanvil::{fixtures, JavaChunk, Region};
+//! let mut region = Region::from_stream(fixtures::region_1_18()).unwrap();
+//! let data = region.read_chunk(0, 0).unwrap().unwrap();
+//! assert!(JavaChunk::from_bytes(&data).is_ok());
+//! ```
+//!
+//! There are also [malformed chunks][malformed_chunks] and a [malformed
+//! region][malformed_region] for checking that bad input is handled.
+
+use std::io::Cursor;
+
+use fastnbt::{nbt, LongArray};
+
+use crate::{header_offset_for, Region, SECTOR_SIZE};
+
+/// A 1.12 chunk, DataVersion 1139. This is before the palette format of 1.13.
+pub const fn chunk_1_12() -> &'static [u8] {
+    include_bytes!("../resources/1.12.chunk")
+}
+
+/// A 1.16.2 chunk, DataVersion 2578, from Etho's Let's Play world.
+pub const fn chunk_1_16() -> &'static [u8] {
+    include_bytes!("../resources/etho.chunk")
+}
+
+/// A 1.17 chunk, DataVersion 2724.
+pub const fn chunk_1_17_0() -> &'static [u8] {
+    include_bytes!("../resources/1.17.0.chunk")
+}
+
+/// A 1.17.1 chunk, DataVersion 2730.
+pub const fn chunk_1_17() -> &'static [u8] {
+    include_bytes!("../resources/1.17.1.chunk")
+}
+
+/// A 1.17.1 chunk from a world with a custom height, using a datapack.
+pub const fn chunk_1_17_custom_heights() -> &'static [u8] {
+    include_bytes!("../resources/1.17.1-custom-heights.chunk")
+}
+
+/// A chunk from the 21w44a snapshot of 1.18, DataVersion 2845. This has the
+/// 1.18 layout, with sections from y = -64.
+pub const fn chunk_1_18() -> &'static [u8] {
+    include_bytes!("../resources/21w44a-test1.nbt")
+}
+
+/// A 1.17.1 chunk with strings using characters outside of the Basic
+/// Multilingual Plane, which NBT stores differently to UTF-8.
+pub const fn chunk_unicode() -> &'static [u8] {
+    include_bytes!("../resources/unicode.chunk")
+}
+
+/// A chunk in the 1.18 layout with blocks and fields from mods, which the
+/// game itself never writes.
+pub const fn chunk_modded() -> &'static [u8] {
+    include_bytes!("../resources/modded.chunk")
+}
+
+/// A 1.16.2 chunk with no blocks at all.
+pub const fn chunk_empty() -> &'static [u8] {
+    include_bytes!("../resources/etho-empty.chunk")
+}
+
+/// A 1.16.2 chunk with heightmaps in the format from before 1.16, as found in
+/// worlds that were upgraded.
+pub const fn chunk_old_heightmaps() -> &'static [u8] {
+    include_bytes!("../resources/etho-old-heightmaps.chunk")
+}
+
+/// A 1.16.2 chunk with blocks up to the top of the world.
+pub const fn chunk_max_heights() -> &'static [u8] {
+    include_bytes!("../resources/etho-max-heights.chunk")
+}
+
+/// 1.16.2 chunks with old heightmaps alongside new ones, as found in worlds
+/// that were upgraded.
+pub const fn chunks_old_in_new() -> [&'static [u8]; 2] {
+    [
+        include_bytes!("../resources/etho-old-in-new.chunk"),
+        include_bytes!("../resources/etho-old-in-new2.chunk"),
+    ]
+}
+
+/// A chunk in the 1.18 layout with no sections at all, as saved for chunks
+/// that have barely started generating. It has a full status and a heightmap
+/// all the same, so code that trusts either finds no blocks behind them. The
+/// heightmap puts the surface at y = 64.
+pub fn chunk_without_sections() -> Vec<u8> {
+    // 64 is stored as 128 above the bottom of the world, in 9 bits per
+    // column with 7 columns to a long.
+    let heights = (0..7).fold(0i64, |long, i| long | 128 << (9 * i));
+
+    fastnbt::to_bytes(&nbt!({
+        "DataVersion": 2860,
+        "Status": "full",
+        "xPos": 0,
+        "yPos": -4,
+        "zPos": 0,
+        "Heightmaps": {
+            "MOTION_BLOCKING": LongArray::new(vec![heights; 37]),
+        },
+    }))
+    .unwrap()
+}
+
+/// A chunk in the 1.18 layout whose sections have palettes but no packed
+/// data, as the game saves sections of a single block:
+///
+/// * Stone in the section from y = -64, with a plains biome.
+/// * Air in the section from y = -48.
+/// * Nothing in the section from y = -32, which has no `block_states`.
+///
+/// Sections above are missing.
+pub fn chunk_palette_only() -> Vec<u8> {
+    fastnbt::to_bytes(&nbt!({
+        "DataVersion": 2860,
+        "Status": "full",
+        "xPos": 0,
+        "yPos": -4,
+        "zPos": 0,
+        "sections": [
+            {
+                "Y": -4i8,
+                "block_states": { "palette": [{ "Name": "minecraft:stone" }] },
+                "biomes": { "palette": ["minecraft:plains"] },
+            },
+            {
+                "Y": -3i8,
+                "block_states": { "palette": [{ "Name": "minecraft:air" }] },
+                "biomes": { "palette": ["minecraft:plains"] },
+            },
+            { "Y": -2i8 },
+        ],
+    }))
+    .unwrap()
+}
+
+/// A chunk of an entity region in the 1.18 layout, DataVersion 2860, at
+/// (0, 0). It has one of each type modelled by [`TypedEntity`] and a cow:
+///
+/// * An item of 3 diamonds.
+/// * A glow item frame facing south, holding a named map.
+/// * An armor stand with its head and left arm posed.
+/// * A villager named Bob, who is a level 2 librarian from the plains.
+/// * A cow.
+///
+/// [`TypedEntity`]: crate::entity::TypedEntity
+pub fn entity_chunk() -> Vec<u8> {
+    fastnbt::to_bytes(&nbt!({
+        "DataVersion": 2860,
+        "Position": [I; 0, 0],
+        "Entities": [
+            {
+                "id": "minecraft:item",
+                "Pos": [3.5, 64.0, 7.25],
+                "Motion": [0.0, -0.04, 0.0],
+                "Rotation": [90.0f32, 0.0f32],
+                "UUID": [I; 1, 2, 3, 4],
+                "OnGround": 1i8,
+                "Age": 120i16,
+                "PickupDelay": 0i16,
+                "Item": { "id": "minecraft:diamond", "Count": 3i8 },
+            },
+            {
+                "id": "minecraft:glow_item_frame",
+                "Pos": [1.5, 65.5, 0.03125],
+                "Motion": [0.0, 0.0, 0.0],
+                "Rotation": [0.0f32, 0.0f32],
+                "UUID": [I; -5, 6, -7, 8],
+                "Facing": 3i8,
+                "ItemRotation": 0i8,
+                "TileX": 1,
+                "TileY": 65,
+                "TileZ": 0,
+                "Item": {
+                    "id": "minecraft:filled_map",
+                    "Count": 1i8,
+                    "tag": { "map": 7, "display": { "Name": r#"{"text":"Home"}"# } },
+                },
+            },
+            {
+                "id": "minecraft:armor_stand",
+                "Pos": [5.5, 64.0, 5.5],
+                "Motion": [0.0, 0.0, 0.0],
+                "Rotation": [180.0f32, 0.0f32],
+                "UUID": [I; 9, 10, 11, 12],
+                "ShowArms": 1i8,
+                "Pose": {
+                    "Head": [10.0f32, 20.0f32, 0.0f32],
+                    "LeftArm": [-90.0f32, 0.0f32, 0.0f32],
+                },
+            },
+            {
+                "id": "minecraft:villager",
+                "Pos": [8.0, 64.0, 12.5],
+                "Motion": [0.0, -0.0784, 0.0],
+                "Rotation": [45.0f32, -10.0f32],
+                "UUID": [I; 13, 14, 15, 16],
+                "CustomName": r#"{"text":"Bob"}"#,
+                "Health": 20.0f32,
+                "VillagerData": {
+                    "profession": "minecraft:librarian",
+                    "level": 2,
+                    "type": "minecraft:plains",
+                },
+            },
+            {
+                "id": "minecraft:cow",
+                "Pos": [12.5, 64.0, 2.5],
+                "Motion": [0.0, -0.0784, 0.0],
+                "Rotation": [270.0f32, 0.0f32],
+                "UUID": [I; 17, 18, 19, 20],
+                "Health": 10.0f32,
+            },
+        ],
+    }))
+    .unwrap()
+}
+
+/// Chunks that cannot be parsed, each with a description of what is wrong
+/// with it. Parsing any of them should fail without panicking.
+pub fn malformed_chunks() -> Vec<(&'static str, Vec<u8>)> {
+    let chunk = chunk_1_18();
+
+    vec![
+        ("empty", vec![]),
+        ("truncated", chunk[..chunk.len() / 2].to_vec()),
+        ("root is not a compound", vec![1, 0, 0, 5]),
+        ("unknown tag", vec![10, 0, 0, 13, 0, 1, b'a', 0]),
+        (
+            "string longer than the data",
+            vec![10, 0, 0, 8, 0, 1, b'a', 0, 50, b'x'],
+        ),
+        (
+            "list longer than the data",
+            vec![10, 0, 0, 9, 0, 1, b'a', 3, 0, 0, 0, 100, 0, 0, 0, 1],
+        ),
+    ]
+}
+
+/// A region holding the given chunks, as `(x, z, uncompressed data)`.
+pub fn region(chunks: &[(usize, usize, &[u8])]) -> Cursor<Vec<u8>> {
+    let mut region = Region::new(Cursor::new(vec![])).unwrap();
+    for &(x, z, data) in chunks {
+        region.write_chunk(x, z, data).unwrap();
+    }
+
+    let mut stream = region.into_inner().unwrap();
+    stream.set_position(0);
+    stream
+}
+
+/// A region with [`chunk_1_12`] at (0, 0).
+pub fn region_1_12() -> Cursor<Vec<u8>> {
+    region(&[(0, 0, chunk_1_12())])
+}
+
+/// A region with [`chunk_1_16`] at (0, 0).
+pub fn region_1_16() -> Cursor<Vec<u8>> {
+    region(&[(0, 0, chunk_1_16())])
+}
+
+/// A region with [`chunk_1_17`] at (0, 0).
+pub fn region_1_17() -> Cursor<Vec<u8>> {
+    region(&[(0, 0, chunk_1_17())])
+}
+
+/// A region with [`chunk_1_18`] at (0, 0).
+pub fn region_1_18() -> Cursor<Vec<u8>> {
+    region(&[(0, 0, chunk_1_18())])
+}
+
+/// A region with a chunk of each version along x, from [`chunk_1_12`] at
+/// (0, 0) to [`chunk_1_18`] at (4, 0).
+pub fn region_mixed_versions() -> Cursor<Vec<u8>> {
+    region(&[
+        (0, 0, chunk_1_12()),
+        (1, 0, chunk_1_16()),
+        (2, 0, chunk_1_17_0()),
+        (3, 0, chunk_1_17()),
+        (4, 0, chunk_1_18()),
+    ])
+}
+
+/// An entity region with [`entity_chunk`] at (0, 0).
+pub fn region_entities() -> Cursor<Vec<u8>> {
+    region(&[(0, 0, &entity_chunk())])
+}
+
+/// A region that loads, but has broken chunks:
+///
+/// * (0, 0) is [`chunk_1_18`], and is fine.
+/// * (1, 0) has an unknown compression scheme.
+/// * (2, 0) has a header pointing past the end of the region.
+/// * (3, 0) is compressed data that is corrupt.
+/// * (4, 0) is not NBT once decompressed.
+pub fn malformed_region() -> Cursor<Vec<u8>> {
+    let mut data = region(&[
+        (0, 0, chunk_1_18()),
+        (1, 0, chunk_1_17()),
+        (2, 0, chunk_1_16()),
+        (3, 0, chunk_1_12()),
+        (4, 0, b"not nbt"),
+    ])
+    .into_inner();
+
+    let offset = |data: &[u8], x: usize| {
+        let pos = header_offset_for(x, 0);
+        u32::from_be_bytes([0, data[pos], data[pos + 1], data[pos + 2]]) as usize * SECTOR_SIZE
+    };
+
+    // The compression scheme follows the chunk's length.
+    let chunk = offset(&data, 1);
+    data[chunk + 4] = 42;
+
+    let past_end = (data.len() / SECTOR_SIZE + 10) as u32;
+    let pos = header_offset_for(2, 0);
+    data[pos..pos + 3].copy_from_slice(&past_end.to_be_bytes()[1..]);
+
+    // Corrupt the data after the zlib header.
+    let chunk = offset(&data, 3);
+    data[chunk + 7..chunk + 64].fill(0xFF);
+
+    Cursor::new(data)
+}
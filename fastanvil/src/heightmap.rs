@@ -0,0 +1,65 @@
+use crate::{Block, BlockArchetype, Chunk, SectionFill};
+
+/// The heightmaps Minecraft keeps for each chunk, which differ in the blocks
+/// they count as the surface. See [`recompute_heightmap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeightmapKind {
+    /// The highest block that is not air, eg including flowers and water.
+    WorldSurface,
+    /// The highest block that blocks motion or contains a fluid, eg water but
+    /// not flowers. This is the heightmap used for rendering.
+    MotionBlocking,
+    /// Like [`MotionBlocking`][Self::MotionBlocking], but looking through
+    /// leaves.
+    MotionBlockingNoLeaves,
+    /// The highest block that blocks motion, looking through water.
+    OceanFloor,
+}
+
+impl HeightmapKind {
+    fn is_surface(self, block: &Block) -> bool {
+        match self {
+            HeightmapKind::WorldSurface => block.archetype != BlockArchetype::Airy,
+            HeightmapKind::MotionBlocking => block.blocks_motion() || block.has_fluid(),
+            HeightmapKind::MotionBlockingNoLeaves => {
+                (block.blocks_motion() || block.has_fluid()) && !block.is_leaves()
+            }
+            HeightmapKind::OceanFloor => block.blocks_motion(),
+        }
+    }
+}
+
+/// Calculate a heightmap of the chunk from its blocks, for chunks that were
+/// saved without one, eg by older versions or structure tools.
+///
+/// Heights are given for each column, increasing in x then z, as the y just
+/// above the highest block counted by `kind`. Columns without any such block
+/// have the bottom of the chunk as their height. Whether a block is airy is
+/// decided the same way as when rendering.
+pub fn recompute_heightmap<C: Chunk + ?Sized>(chunk: &C, kind: HeightmapKind) -> [i16; 256] {
+    let y_range = chunk.y_range();
+    let mut map = [y_range.start as i16; 256];
+
+    for z in 0..16 {
+        for x in 0..16 {
+            let mut y = y_range.end - 1;
+
+            while y >= y_range.start {
+                // Sections of air have no surface, so skip to the one below.
+                if chunk.section_fill(y) == Some(SectionFill::Air) {
+                    y = y.div_euclid(16) * 16 - 1;
+                    continue;
+                }
+
+                if chunk.block(x, y, z).is_some_and(|b| kind.is_surface(b)) {
+                    map[z * 16 + x] = (y + 1) as i16;
+                    break;
+                }
+
+                y -= 1;
+            }
+        }
+    }
+
+    map
+}
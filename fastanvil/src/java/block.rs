@@ -1,12 +1,33 @@
-use std::collections::HashMap;
+use std::{error::Error, fmt::Display, str::FromStr};
 
+use fastnbt::{borrow::CowStr, Map};
 use serde::Deserialize;
 
+use crate::{ParseResourceLocationError, ResourceLocationRef};
+
+/// A block and its state properties.
+///
+/// Blocks can be displayed and parsed in the syntax used by commands, eg
+/// `minecraft:oak_stairs[facing=east,half=bottom]`. Displayed properties are
+/// sorted by name. When parsing, names without a namespace are taken to be in
+/// the `minecraft` namespace.
+///
+/// ```
+/// # use fastanvil::Block;
+/// let block: Block = "oak_stairs[half=bottom,facing=east]".parse().unwrap();
+/// assert_eq!(block.name(), "minecraft:oak_stairs");
+/// assert_eq!(
+///     block.to_string(),
+///     "minecraft:oak_stairs[facing=east,half=bottom]"
+/// );
+/// ```
 #[derive(Debug, Clone)]
 pub struct Block {
     pub(crate) name: String,
     pub(crate) encoded: String,
     pub(crate) archetype: BlockArchetype,
+    // Sorted by property name.
+    pub(crate) properties: Vec<(String, String)>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -22,10 +43,47 @@ impl Block {
         &self.name
     }
 
+    /// The name of the block as a namespaced id. Names are checked when blocks
+    /// are deserialized or parsed, so this does not need to check them again.
+    pub fn id(&self) -> ResourceLocationRef<'_> {
+        ResourceLocationRef::new_unchecked(&self.name)
+    }
+
     pub fn snowy(&self) -> bool {
         self.archetype == BlockArchetype::Snowy
     }
 
+    /// Whether the block stops entities moving through it, eg stone but not
+    /// grass, torches or air. This is what the `MOTION_BLOCKING` heightmaps
+    /// look for, along with fluids.
+    pub fn blocks_motion(&self) -> bool {
+        self.archetype != BlockArchetype::Airy
+            && self.archetype != BlockArchetype::Watery
+            && !is_passable(&self.name)
+    }
+
+    /// Whether the block is or contains a fluid, eg water, lava or a
+    /// waterlogged block.
+    pub fn has_fluid(&self) -> bool {
+        self.archetype == BlockArchetype::Watery
+            || self.name == "minecraft:lava"
+            || self.property("waterlogged") == Some("true")
+    }
+
+    /// Whether the block is leaves of any kind.
+    pub fn is_leaves(&self) -> bool {
+        self.name.ends_with("_leaves")
+    }
+
+    /// Get the value of a block state property, eg `facing`.
+    pub fn property(&self, key: &str) -> Option<&str> {
+        let i = self
+            .properties
+            .binary_search_by(|(k, _)| k.as_str().cmp(key))
+            .ok()?;
+        Some(&self.properties[i].1)
+    }
+
     /// A string of the format "id|prop1=val1,prop2=val2". The properties are
     /// ordered lexigraphically. This somewhat matches the way Minecraft stores
     /// variants in blockstates, but with the block ID/name prepended.
@@ -34,13 +92,19 @@ impl Block {
     }
 }
 
-#[derive(Deserialize)]
+/// An item of a block palette, as stored in the `block_states` of a 1.18+
+/// section or the `Palette` of an older one. This is the raw form of a
+/// [`Block`], borrowing its strings from the input where possible.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "PascalCase")]
-struct BlockRaw {
-    name: String,
+pub struct PaletteItem<'a> {
+    /// The namespaced name of the block, eg `minecraft:stone`.
+    #[serde(borrow)]
+    pub name: CowStr<'a>,
 
-    #[serde(default)]
-    properties: HashMap<String, String>,
+    /// The block state properties, eg `facing` to `north`.
+    #[serde(default, borrow)]
+    pub properties: Map<CowStr<'a>, CowStr<'a>>,
 }
 
 impl<'de> Deserialize<'de> for Block {
@@ -48,21 +112,56 @@ impl<'de> Deserialize<'de> for Block {
     where
         D: serde::Deserializer<'de>,
     {
-        let raw: BlockRaw = Deserialize::deserialize(deserializer)?;
-        let snowy = raw.properties.get("snowy").map(String::as_str) == Some("true");
+        let raw: PaletteItem = Deserialize::deserialize(deserializer)?;
 
-        let mut id = raw.name.clone() + "|";
-        let mut sep = "";
+        // Catch corrupt names here rather than wherever the block is used.
+        parse_name(&raw.name).map_err(serde::de::Error::custom)?;
+
+        Ok(raw.into())
+    }
+}
 
-        let mut props = raw
+/// Parse the name of a block. Modded blocks are sometimes saved with uppercase
+/// letters in their names, which ids do not otherwise allow. These are
+/// accepted, and kept as they are.
+pub(crate) fn parse_name(
+    name: &str,
+) -> Result<ResourceLocationRef<'_>, ParseResourceLocationError> {
+    match ResourceLocationRef::parse(name) {
+        Err(e) if name.bytes().any(|b| b.is_ascii_uppercase()) => {
+            ResourceLocationRef::parse(&name.to_ascii_lowercase()).map_err(|_| e)?;
+            Ok(ResourceLocationRef::new_unchecked(name))
+        }
+        parsed => parsed,
+    }
+}
+
+impl<'a> From<PaletteItem<'a>> for Block {
+    fn from(raw: PaletteItem<'a>) -> Self {
+        let properties = raw
             .properties
-            .iter()
-            .filter(|(k, _)| *k != "waterlogged") // TODO: Handle water logging. See note below
-            .filter(|(k, _)| *k != "powered") // TODO: Handle power
-            .collect::<Vec<_>>();
+            .into_iter()
+            .map(|(k, v)| (k.into_inner().into_owned(), v.into_inner().into_owned()))
+            .collect();
+
+        Block::from_parts(raw.name.into_inner().into_owned(), properties)
+    }
+}
 
+impl Block {
+    pub(crate) fn from_parts(name: String, mut properties: Vec<(String, String)>) -> Self {
         // need to sort the properties for a consistent ID
-        props.sort_unstable();
+        properties.sort_unstable();
+
+        let snowy = properties.iter().any(|(k, v)| k == "snowy" && v == "true");
+
+        let mut id = name.clone() + "|";
+        let mut sep = "";
+
+        let props = properties
+            .iter()
+            .filter(|(k, _)| k != "waterlogged") // TODO: Handle water logging. See note below
+            .filter(|(k, _)| k != "powered"); // TODO: Handle power
 
         for (k, v) in props {
             id = id + sep + k + "=" + v;
@@ -71,19 +170,101 @@ impl<'de> Deserialize<'de> for Block {
 
         let arch = if snowy {
             BlockArchetype::Snowy
-        } else if is_watery(&raw.name) {
+        } else if is_watery(&name) {
             BlockArchetype::Watery
-        } else if is_airy(&raw.name) {
+        } else if is_airy(&name) {
             BlockArchetype::Airy
         } else {
             BlockArchetype::Normal
         };
 
-        Ok(Self {
-            name: raw.name,
+        Self {
+            name,
             archetype: arch,
             encoded: id,
-        })
+            properties,
+        }
+    }
+}
+
+impl Display for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)?;
+
+        if self.properties.is_empty() {
+            return Ok(());
+        }
+
+        let mut sep = "[";
+        for (k, v) in &self.properties {
+            write!(f, "{sep}{k}={v}")?;
+            sep = ",";
+        }
+        f.write_str("]")
+    }
+}
+
+/// Error from parsing a [`Block`] from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBlockError(String);
+
+impl Error for ParseBlockError {}
+
+impl Display for ParseBlockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Block {
+    type Err = ParseBlockError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |msg: &str| ParseBlockError(format!("{msg}: {s:?}"));
+
+        let (name, props) = match s.split_once('[') {
+            Some((name, rest)) => {
+                let props = rest
+                    .strip_suffix(']')
+                    .ok_or_else(|| err("expected property list to end with ']'"))?;
+                (name, Some(props))
+            }
+            None => (s, None),
+        };
+
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(err("missing block name"));
+        }
+
+        let name = parse_name(name)
+            .map_err(|_| err("invalid block name"))?
+            .to_string();
+
+        // `name[]` is allowed and has no properties.
+        let props = props.filter(|p| !p.trim().is_empty());
+        let mut properties: Vec<(String, String)> = vec![];
+
+        for prop in props.into_iter().flat_map(|p| p.split(',')) {
+            let (k, v) = prop
+                .split_once('=')
+                .ok_or_else(|| err("expected property of the form key=value"))?;
+            let (k, v) = (k.trim(), v.trim());
+
+            if k.is_empty() || v.is_empty() {
+                return Err(err("expected property of the form key=value"));
+            }
+            if v.contains(['=', '[', ']']) {
+                return Err(err("invalid property value"));
+            }
+            if properties.iter().any(|(existing, _)| existing == k) {
+                return Err(err(&format!("duplicate property {k:?}")));
+            }
+
+            properties.push((k.to_owned(), v.to_owned()));
+        }
+
+        Ok(Block::from_parts(name, properties))
     }
 }
 
@@ -95,7 +276,7 @@ fn is_watery(block: &str) -> bool {
             | "minecraft:bubble_column"
             | "minecraft:kelp"
             | "minecraft:kelp_plant"
-            | "minecraft:sea_grass"
+            | "minecraft:seagrass"
             | "minecraft:tall_seagrass"
     )
 }
@@ -104,3 +285,90 @@ fn is_watery(block: &str) -> bool {
 fn is_airy(block: &str) -> bool {
     matches!(block, "minecraft:air" | "minecraft:cave_air")
 }
+
+/// Blocks other than air and water that entities can move through, and so are
+/// left out of motion blocking heightmaps. Covers vanilla blocks by name and
+/// the common families by suffix.
+fn is_passable(block: &str) -> bool {
+    let name = block.strip_prefix("minecraft:").unwrap_or(block);
+
+    const SUFFIXES: &[&str] = &[
+        "_sapling",
+        "_tulip",
+        "_torch",
+        "_sign",
+        "_banner",
+        "_button",
+        "_pressure_plate",
+        "_carpet",
+        "_rail",
+        "_mushroom",
+        "_fungus",
+        "_coral",
+        "_coral_fan",
+        "_coral_wall_fan",
+        "_vines",
+        "_vines_plant",
+    ];
+
+    matches!(
+        name,
+        "lava"
+            | "grass"
+            | "short_grass"
+            | "tall_grass"
+            | "fern"
+            | "large_fern"
+            | "dead_bush"
+            | "dandelion"
+            | "poppy"
+            | "blue_orchid"
+            | "allium"
+            | "azure_bluet"
+            | "oxeye_daisy"
+            | "lily_of_the_valley"
+            | "wither_rose"
+            | "sunflower"
+            | "lilac"
+            | "rose_bush"
+            | "peony"
+            | "pink_petals"
+            | "sugar_cane"
+            | "vine"
+            | "glow_lichen"
+            | "sculk_vein"
+            | "hanging_roots"
+            | "crimson_roots"
+            | "warped_roots"
+            | "cornflower"
+            | "torchflower"
+            | "spore_blossom"
+            | "torch"
+            | "redstone_wire"
+            | "tripwire"
+            | "tripwire_hook"
+            | "lever"
+            | "ladder"
+            | "rail"
+            | "snow"
+            | "cobweb"
+            | "fire"
+            | "soul_fire"
+            | "nether_portal"
+            | "end_portal"
+            | "end_gateway"
+            | "wheat"
+            | "carrots"
+            | "potatoes"
+            | "beetroots"
+            | "nether_wart"
+            | "sweet_berry_bush"
+            | "melon_stem"
+            | "pumpkin_stem"
+            | "attached_melon_stem"
+            | "attached_pumpkin_stem"
+            | "cocoa"
+            | "structure_void"
+            | "light"
+    ) || SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+
+use fastnbt::{
+    error::{Error, Result},
+    from_bytes,
+};
+use serde::Deserialize;
+
+use super::block::parse_name;
+use crate::{Block, PaletteItem};
+
+/// The blocks a chunk uses, read from the palettes of its sections without
+/// deserializing anything else. The block states and every other array are
+/// skipped over without being read, so this is much quicker than parsing the
+/// whole chunk when only asking whether a chunk has a block at all.
+///
+/// Palettes can hold blocks that are no longer in the section, eg after they
+/// were mined, so a block being in the palette does not always mean it is in
+/// the chunk.
+///
+/// Works with 1.18+ chunks and chunks from 1.13 to 1.17.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkPalette {
+    // Deduplicated.
+    blocks: Vec<Block>,
+}
+
+impl ChunkPalette {
+    /// Read the palettes of a chunk's uncompressed NBT.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let chunk: PaletteChunk = from_bytes(data)?;
+
+        let current = chunk
+            .sections
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s.block_states)
+            .flat_map(|s| s.palette);
+        let pre18 = chunk
+            .level
+            .into_iter()
+            .flat_map(|l| l.sections)
+            .flat_map(|s| s.palette);
+
+        // Items still borrow from the input, so only the unique ones are
+        // turned into blocks. Palettes are short enough to not need hashing.
+        let mut unique: Vec<PaletteItem> = vec![];
+        for item in current.chain(pre18) {
+            if !unique.contains(&item) {
+                parse_name(&item.name).map_err(<Error as serde::de::Error>::custom)?;
+                unique.push(item);
+            }
+        }
+
+        Ok(Self {
+            blocks: unique.into_iter().map(Block::from).collect(),
+        })
+    }
+
+    /// The blocks in the palettes, each only once.
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+}
+
+/// Each block in any of the palettes, once. Blocks are the same if they have
+/// the same name and properties.
+pub(crate) fn unique_blocks<'a>(
+    palettes: impl Iterator<Item = &'a [Block]>,
+) -> impl Iterator<Item = &'a Block> {
+    let mut seen = HashSet::new();
+    palettes
+        .flatten()
+        .filter(move |b| seen.insert((b.name.as_str(), b.properties.as_slice())))
+}
+
+// Just enough of both chunk layouts to find the palettes. Serde skips every
+// other field.
+
+#[derive(Deserialize)]
+struct PaletteChunk<'a> {
+    #[serde(borrow)]
+    sections: Option<Vec<CurrentPaletteSection<'a>>>,
+
+    #[serde(rename = "Level", borrow)]
+    level: Option<Pre18PaletteLevel<'a>>,
+}
+
+#[derive(Deserialize)]
+struct CurrentPaletteSection<'a> {
+    #[serde(borrow)]
+    block_states: Option<PaletteOnly<'a>>,
+}
+
+#[derive(Deserialize)]
+struct PaletteOnly<'a> {
+    #[serde(default, borrow)]
+    palette: Vec<PaletteItem<'a>>,
+}
+
+#[derive(Deserialize)]
+struct Pre18PaletteLevel<'a> {
+    #[serde(rename = "Sections", default, borrow)]
+    sections: Vec<Pre18PaletteSection<'a>>,
+}
+
+#[derive(Deserialize)]
+struct Pre18PaletteSection<'a> {
+    #[serde(rename = "Palette", default, borrow)]
+    palette: Vec<PaletteItem<'a>>,
+}
@@ -0,0 +1,222 @@
+use std::ops::Range;
+use std::sync::RwLock;
+
+use fastnbt::{borrow::RawCompound, error::Result, from_bytes};
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+
+use crate::{
+    biome::Biome, Block, Chunk, HeightMode, Heightmaps, NormalizedSection, Section, SectionFill,
+    SectionLike, SectionTower,
+};
+
+use super::{surface_heightmap, with_y_pos, y_range};
+
+/// A 1.18+ chunk that only decodes a section when something in it is first
+/// looked at, eg a single block.
+///
+/// Loading records the NBT of each section without deserializing its
+/// palettes, which is most of the work of parsing a chunk. This makes loading
+/// much quicker when only a few blocks are needed, at the cost of a little
+/// extra work when every section ends up being decoded. Decoded sections are
+/// cached, so each is decoded at most once.
+///
+/// Chunks from before 1.18 are not supported, use
+/// [`JavaChunk`][crate::JavaChunk] for those.
+///
+/// ```no_run
+/// # use fastanvil::{Chunk, LazyJavaChunk};
+/// # let data: Vec<u8> = unimplemented!();
+/// let chunk = LazyJavaChunk::from_bytes(&data).unwrap();
+///
+/// // Only the section holding y = 64 is decoded.
+/// let block = chunk.block(0, 64, 0);
+/// ```
+#[derive(Debug)]
+pub struct LazyJavaChunk {
+    pub data_version: i32,
+
+    /// Can be missing if the chunk hasn't been generated properly yet.
+    pub sections: Option<SectionTower<LazySection>>,
+
+    pub heightmaps: Option<Heightmaps>,
+
+    pub status: String,
+
+    /// See [`Chunk::inhabited_time`].
+    pub inhabited_time: Option<i64>,
+
+    /// See [`Chunk::last_update`].
+    pub last_update: Option<i64>,
+
+    y_pos: Option<i32>,
+
+    lazy_heightmap: RwLock<Option<[i16; 256]>>,
+}
+
+#[derive(Deserialize)]
+struct LazyCurrentChunk {
+    #[serde(rename = "DataVersion")]
+    data_version: i32,
+
+    sections: Option<SectionTower<LazySection>>,
+
+    #[serde(rename = "yPos")]
+    y_pos: Option<i32>,
+
+    #[serde(rename = "Heightmaps")]
+    heightmaps: Option<Heightmaps>,
+
+    #[serde(rename = "Status")]
+    status: String,
+
+    #[serde(rename = "InhabitedTime")]
+    inhabited_time: Option<i64>,
+
+    #[serde(rename = "LastUpdate")]
+    last_update: Option<i64>,
+}
+
+impl LazyJavaChunk {
+    /// Load a chunk, without decoding any of its sections.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let chunk: LazyCurrentChunk = from_bytes(data)?;
+
+        Ok(Self {
+            data_version: chunk.data_version,
+            sections: with_y_pos(chunk.sections, chunk.y_pos),
+            heightmaps: chunk.heightmaps,
+            status: chunk.status,
+            inhabited_time: chunk.inhabited_time,
+            last_update: chunk.last_update,
+            y_pos: chunk.y_pos,
+            lazy_heightmap: RwLock::new(None),
+        })
+    }
+
+    /// Work out the heights of the chunk's surface, the same way as
+    /// [`JavaChunk::recalculate_heightmap`][crate::JavaChunk::recalculate_heightmap].
+    pub fn recalculate_heightmap(&self, mode: HeightMode) {
+        let map = surface_heightmap(self, self.heightmaps.as_ref(), self.data_version, mode);
+        *self.lazy_heightmap.write().unwrap() = Some(map);
+    }
+
+    fn section_for_y(&self, y: isize) -> Option<&NormalizedSection> {
+        self.sections.as_ref()?.get_section_for_y(y)?.decoded()
+    }
+}
+
+/// A section of a [`LazyJavaChunk`], kept as NBT until it is first needed.
+pub struct LazySection {
+    y: i8,
+    nbt: Box<[u8]>,
+    decoded: OnceCell<Option<NormalizedSection>>,
+}
+
+impl LazySection {
+    /// Whether the section has been decoded yet.
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.get().is_some()
+    }
+
+    /// The section decoded, decoding it if this is the first time it is
+    /// needed. This is `None` if the section's NBT is not a valid section, in
+    /// which case the chunk behaves as if the section were missing.
+    pub fn decoded(&self) -> Option<&NormalizedSection> {
+        self.decoded
+            .get_or_init(|| {
+                let section: Section = RawCompound::from_bytes(&self.nbt).parse().ok()?;
+                Some(NormalizedSection::from_current(section, &mut vec![]))
+            })
+            .as_ref()
+    }
+}
+
+impl std::fmt::Debug for LazySection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazySection")
+            .field("y", &self.y)
+            .field("decoded", &self.decoded.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SectionLike for LazySection {
+    fn is_terminator(&self) -> bool {
+        false
+    }
+
+    fn y(&self) -> i8 {
+        self.y
+    }
+}
+
+impl<'de> Deserialize<'de> for LazySection {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Only the y is read now, everything else is skipped over.
+        #[derive(Deserialize)]
+        struct Header {
+            #[serde(rename = "Y")]
+            y: i8,
+        }
+
+        let raw = RawCompound::deserialize(deserializer)?;
+        let header: Header = raw.parse().map_err(serde::de::Error::custom)?;
+
+        Ok(Self {
+            y: header.y,
+            nbt: raw.as_bytes().into(),
+            decoded: OnceCell::new(),
+        })
+    }
+}
+
+impl Chunk for LazyJavaChunk {
+    fn status(&self) -> String {
+        self.status.clone()
+    }
+
+    fn surface_height(&self, x: usize, z: usize, mode: HeightMode) -> isize {
+        let mut heightmap = self.lazy_heightmap.read().unwrap();
+        if heightmap.is_none() {
+            drop(heightmap);
+            self.recalculate_heightmap(mode);
+            heightmap = self.lazy_heightmap.read().unwrap();
+        }
+        heightmap.unwrap()[z * 16 + x] as isize
+    }
+
+    fn biome(&self, x: usize, y: isize, z: usize) -> Option<Biome> {
+        let sec = self.section_for_y(y)?;
+        let sec_y = (y - sec.y() as isize * 16) as usize;
+        sec.biome(x, sec_y, z)
+    }
+
+    fn block(&self, x: usize, y: isize, z: usize) -> Option<&Block> {
+        let sec = self.section_for_y(y)?;
+        let sec_y = (y - sec.y() as isize * 16) as usize;
+        Some(sec.block(x, sec_y, z))
+    }
+
+    fn y_range(&self) -> Range<isize> {
+        y_range(self.sections.as_ref(), self.y_pos)
+    }
+
+    fn section_fill(&self, y: isize) -> Option<SectionFill> {
+        self.section_for_y(y)?.fill()
+    }
+
+    fn section_block_counts(&self, y: isize) -> Option<Vec<(&Block, usize)>> {
+        Some(self.section_for_y(y)?.block_counts())
+    }
+    fn inhabited_time(&self) -> Option<i64> {
+        self.inhabited_time
+    }
+
+    fn last_update(&self) -> Option<i64> {
+        self.last_update
+    }
+}
@@ -0,0 +1,140 @@
+use std::ops::Range;
+
+use fastnbt::ByteArray;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use crate::{biome::Biome, Block, Chunk, HeightMode};
+
+use super::AIR;
+
+const HEIGHT: usize = 128;
+
+/// A chunk from the McRegion format, used by `.mcr` region files before Anvil
+/// replaced it in 1.2. The regions themselves are read the same as Anvil ones
+/// with [`Region`][crate::Region].
+///
+/// Blocks are stored as numeric IDs and 4-bit data values rather than names,
+/// and the world is 128 blocks high. [`block_id`][Self::block_id] and
+/// [`block_data`][Self::block_data] give the stored values. Through the
+/// [`Chunk`] trait, blocks other than air are named `legacy:<id>` with a `data`
+/// property, eg `legacy:35[data=14]`, since this crate does not map old IDs to
+/// modern names. Biomes were not stored, so are always `None`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct McRegionChunk {
+    pub level: McRegionLevel,
+}
+
+/// The contents of a [`McRegionChunk`].
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct McRegionLevel {
+    #[serde(rename = "xPos")]
+    pub x_pos: i32,
+
+    #[serde(rename = "zPos")]
+    pub z_pos: i32,
+
+    /// Block IDs, one byte per block, increasing in y, then z, then x.
+    pub blocks: ByteArray,
+
+    /// Block data values, 4 bits per block in the same order as `blocks`.
+    pub data: ByteArray,
+
+    /// For each column, increasing in x then z, the lowest y that sky light
+    /// fully reaches.
+    pub height_map: Option<ByteArray>,
+
+    /// Whether trees, ores etc have been added to the chunk's terrain.
+    #[serde(default)]
+    pub terrain_populated: bool,
+
+    pub last_update: Option<i64>,
+}
+
+impl McRegionChunk {
+    pub fn from_bytes(data: &[u8]) -> fastnbt::error::Result<Self> {
+        fastnbt::from_bytes(data)
+    }
+
+    /// Get the numeric ID of the block at x,y,z, where x and z are relative to
+    /// the chunk ie 0..16. Returns `None` if x or z is outside of 0..16 or y
+    /// is outside of 0..128.
+    pub fn block_id(&self, x: usize, y: isize, z: usize) -> Option<u8> {
+        let i = index(x, y, z)?;
+        self.level.blocks.get(i).map(|&id| id as u8)
+    }
+
+    /// Get the data value of the block at x,y,z, eg the colour of wool. See
+    /// [`block_id`][Self::block_id].
+    pub fn block_data(&self, x: usize, y: isize, z: usize) -> Option<u8> {
+        let i = index(x, y, z)?;
+        let byte = *self.level.data.get(i / 2)? as u8;
+
+        // Even blocks are in the low nibble.
+        Some(byte >> (i % 2 * 4) & 0xf)
+    }
+}
+
+fn index(x: usize, y: isize, z: usize) -> Option<usize> {
+    if x >= 16 || z >= 16 {
+        return None;
+    }
+    let y = usize::try_from(y).ok().filter(|&y| y < HEIGHT)?;
+    Some((x * 16 + z) * HEIGHT + y)
+}
+
+/// The blocks given for each ID and data value, indexed by `id << 4 | data`.
+static LEGACY_BLOCKS: Lazy<Vec<Block>> = Lazy::new(|| {
+    (0..256 * 16)
+        .map(|i| match i >> 4 {
+            0 => AIR.clone(),
+            id => Block::from_parts(
+                format!("legacy:{id}"),
+                vec![("data".to_owned(), (i & 0xf).to_string())],
+            ),
+        })
+        .collect()
+});
+
+impl Chunk for McRegionChunk {
+    fn status(&self) -> String {
+        // Unpopulated chunks are on the edge of what has been generated, the
+        // same as chunks without a full status in later versions.
+        match self.level.terrain_populated {
+            true => "full".to_owned(),
+            false => "unpopulated".to_owned(),
+        }
+    }
+
+    fn surface_height(&self, x: usize, z: usize, mode: HeightMode) -> isize {
+        if let (HeightMode::Trust, Some(map)) = (mode, &self.level.height_map) {
+            if let Some(&height) = map.get(z * 16 + x) {
+                return height as u8 as isize;
+            }
+        }
+
+        (0..HEIGHT as isize)
+            .rev()
+            .find(|&y| self.block_id(x, y, z).unwrap_or(0) != 0)
+            .map_or(0, |y| y + 1)
+    }
+
+    fn biome(&self, _x: usize, _y: isize, _z: usize) -> Option<Biome> {
+        None
+    }
+
+    fn block(&self, x: usize, y: isize, z: usize) -> Option<&Block> {
+        let id = self.block_id(x, y, z)? as usize;
+        let data = self.block_data(x, y, z)? as usize;
+        LEGACY_BLOCKS.get(id << 4 | data)
+    }
+
+    fn y_range(&self) -> Range<isize> {
+        0..HEIGHT as isize
+    }
+    fn last_update(&self) -> Option<i64> {
+        self.level.last_update
+    }
+}
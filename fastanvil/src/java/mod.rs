@@ -1,89 +1,343 @@
 use std::ops::Range;
+use std::sync::RwLock;
 
 use fastnbt::{error::Result, from_bytes};
+use serde::de::IgnoredAny;
 pub mod pre18;
 
 mod block;
 mod chunk;
+mod chunk_palette;
 mod heightmaps;
+mod lazy;
+mod mcregion;
+mod normalized;
+mod preserve;
 mod section;
 mod section_data;
 mod section_tower;
 
 pub use block::*;
 pub use chunk::*;
+pub use chunk_palette::*;
 pub use heightmaps::*;
+pub use lazy::*;
+pub use mcregion::*;
+pub use normalized::*;
+pub use preserve::*;
 pub use section::*;
 pub use section_data::*;
 pub use section_tower::*;
 
 use once_cell::sync::Lazy;
 
-use crate::{biome::Biome, Chunk, HeightMode};
+use crate::{
+    biome::Biome, expand_heightmap, recompute_heightmap, version::DataVersion, Chunk, Diagnostic,
+    DiagnosticKind, HeightMode, HeightmapKind, SectionFill,
+};
 
 pub static AIR: Lazy<Block> = Lazy::new(|| Block {
     name: "minecraft:air".to_owned(),
     encoded: "minecraft:air|".to_owned(),
     archetype: BlockArchetype::Airy,
+    properties: vec![],
+});
+pub static WATER: Lazy<Block> = Lazy::new(|| Block {
+    name: "minecraft:water".to_owned(),
+    encoded: "minecraft:water|".to_owned(),
+    archetype: BlockArchetype::Watery,
+    properties: vec![],
 });
 pub static SNOW_BLOCK: Lazy<Block> = Lazy::new(|| Block {
     name: "minecraft:snow_block".to_owned(),
     encoded: "minecraft:snow_block|".to_owned(),
     archetype: BlockArchetype::Snowy,
+    properties: vec![],
 });
 
-/// A Minecraft chunk.
+/// A Minecraft chunk, from 1.18 or from about 1.15 to 1.17.
+///
+/// Whatever version the chunk was saved with, its sections are converted to
+/// [`NormalizedSection`]s after deserializing, so the chunk behaves the same
+/// for all of them. Supporting a new layout only needs deserializing it and
+/// converting its sections.
 #[derive(Debug)]
-pub enum JavaChunk {
-    Post18(CurrentJavaChunk),
-    Pre18(pre18::JavaChunk),
+pub struct JavaChunk {
+    pub data_version: i32,
+
+    /// Can be missing if the chunk hasn't been generated properly yet.
+    pub sections: Option<SectionTower<NormalizedSection>>,
+
+    pub heightmaps: Option<Heightmaps>,
+
+    // Status of the chunk. Typically anything except 'full' means the chunk
+    // hasn't been fully generated yet. We use this to skip chunks on map edges
+    // that haven't been fully generated yet.
+    pub status: String,
+
+    /// See [`Chunk::inhabited_time`].
+    pub inhabited_time: Option<i64>,
+
+    /// See [`Chunk::last_update`].
+    pub last_update: Option<i64>,
+
+    // The lowest section, for chunks saved with one, to place a chunk without
+    // sections at the bottom of the world.
+    y_pos: Option<i32>,
+
+    lazy_heightmap: RwLock<Option<[i16; 256]>>,
 }
 
 impl JavaChunk {
     pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_bytes_with_diagnostics(data, &mut vec![])
+    }
+
+    /// Parse a chunk into whichever type suits its version, for code that
+    /// works with chunks of any version through the [`Chunk`] trait. Chunks
+    /// are parsed as a `JavaChunk` if they can be, and as a [`McRegionChunk`]
+    /// if they have McRegion's blocks directly in their `Level`. Anvil chunks
+    /// from before 1.9, whose blocks are in `Level.Sections`, are neither and
+    /// give the error from parsing them as a `JavaChunk`. The chunk can be
+    /// sent between threads, as every `Chunk` is `Send` and `Sync`.
+    ///
+    /// ```no_run
+    /// # use fastanvil::{Chunk, JavaChunk};
+    /// # let data: Vec<u8> = vec![];
+    /// let chunk = JavaChunk::boxed(&data).unwrap();
+    /// println!("{}", chunk.status());
+    /// ```
+    pub fn boxed(data: &[u8]) -> Result<Box<dyn Chunk>> {
+        #[derive(serde::Deserialize)]
+        struct McRegionProbe {
+            #[serde(rename = "Level")]
+            level: McRegionProbeLevel,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct McRegionProbeLevel {
+            #[serde(rename = "Blocks")]
+            blocks: Option<IgnoredAny>,
+        }
+
+        // Nearly every chunk is a `JavaChunk`, so is only parsed once.
+        let err = match Self::from_bytes(data) {
+            Ok(chunk) => return Ok(Box::new(chunk)),
+            Err(err) => err,
+        };
+
+        match from_bytes::<McRegionProbe>(data) {
+            Ok(probe) if probe.level.blocks.is_some() => {
+                Ok(Box::new(McRegionChunk::from_bytes(data)?))
+            }
+            _ => Err(err),
+        }
+    }
+
+    /// Parse a chunk like [`from_bytes`][Self::from_bytes], adding a
+    /// [`Diagnostic`] to `diagnostics` for anything unexpected that was worked
+    /// around, eg a missing heightmap. Nothing is allocated unless there is
+    /// something to report.
+    pub fn from_bytes_with_diagnostics(
+        data: &[u8],
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<Self> {
         let chunk: Result<CurrentJavaChunk> = from_bytes(data);
 
-        match chunk {
-            Ok(chunk) => Ok(Self::Post18(chunk)),
-            Err(_) => Ok(Self::Pre18(from_bytes::<pre18::JavaChunk>(data)?)),
+        let chunk = match chunk {
+            Ok(chunk) => Self::from_current(chunk, diagnostics),
+            Err(_) => Self::from_pre18(from_bytes(data)?, diagnostics),
+        };
+
+        // Versions newer than the crate knows may well work, but have not been
+        // checked.
+        let version = DataVersion(chunk.data_version);
+        if version < DataVersion::V1_13 || version.is_newer_than_known() {
+            diagnostics.push(Diagnostic::new(DiagnosticKind::UnknownDataVersion(
+                chunk.data_version,
+            )));
+        }
+
+        let has_heightmap = chunk
+            .heightmaps
+            .as_ref()
+            .is_some_and(|hm| hm.motion_blocking.is_some());
+        if chunk.sections.is_some() && !has_heightmap {
+            diagnostics.push(Diagnostic::new(DiagnosticKind::MissingHeightmap));
+        }
+
+        Ok(chunk)
+    }
+
+    fn from_current(chunk: CurrentJavaChunk, diagnostics: &mut Vec<Diagnostic>) -> Self {
+        Self {
+            data_version: chunk.data_version,
+            sections: with_y_pos(chunk.sections, chunk.y_pos)
+                .map(|tower| tower.map(|sec| NormalizedSection::from_current(sec, diagnostics))),
+            heightmaps: chunk.heightmaps,
+            status: chunk.status,
+            inhabited_time: chunk.inhabited_time,
+            last_update: chunk.last_update,
+            y_pos: chunk.y_pos,
+            lazy_heightmap: RwLock::new(None),
+        }
+    }
+
+    fn from_pre18(chunk: pre18::JavaChunk, diagnostics: &mut Vec<Diagnostic>) -> Self {
+        let level = chunk.level;
+        let biomes = level.biomes;
+
+        Self {
+            data_version: chunk.data_version,
+            sections: level.sections.map(|tower| {
+                let y_min = tower.y_min();
+                tower.map(|sec| {
+                    NormalizedSection::from_pre18(sec, biomes.as_ref(), y_min, diagnostics)
+                })
+            }),
+            heightmaps: level.heightmaps,
+            status: level.status,
+            inhabited_time: level.inhabited_time,
+            last_update: level.last_update,
+            y_pos: None,
+            lazy_heightmap: RwLock::new(None),
+        }
+    }
+
+    /// Work out the heights of the chunk's surface used by
+    /// [`surface_height`][Chunk::surface_height]. With [`HeightMode::Trust`]
+    /// the stored motion blocking heightmap is used, or recomputed with
+    /// [`recompute_heightmap`] if the chunk does not have one. With
+    /// [`HeightMode::Calculate`] the highest block that is not air is used.
+    ///
+    /// A chunk without sections has no surface, so every column is at the
+    /// bottom of [`y_range`][Chunk::y_range] whatever its heightmap says.
+    pub fn recalculate_heightmap(&self, mode: HeightMode) {
+        let map = surface_heightmap(self, self.heightmaps.as_ref(), self.data_version, mode);
+        *self.lazy_heightmap.write().unwrap() = Some(map);
+    }
+
+    /// Every block in the palettes of the chunk's sections, each only once.
+    /// This is a quick way to tell whether the chunk might contain a block,
+    /// see [`ChunkPalette`] for the caveats.
+    pub fn palette_blocks(&self) -> impl Iterator<Item = &Block> {
+        let sections = self.sections.as_ref().map(|s| s.sections()).unwrap_or(&[]);
+        chunk_palette::unique_blocks(sections.iter().map(|s| s.palette()))
+    }
+
+    fn section_for_y(&self, y: isize) -> Option<&NormalizedSection> {
+        self.sections.as_ref()?.get_section_for_y(y)
+    }
+}
+
+/// Put the bottom of the sections at the chunk's `yPos`, if it has one. The
+/// lowest section saved is not always the bottom of the world, eg 1.18 saves
+/// sections below it holding only light.
+fn with_y_pos<S: SectionLike>(
+    sections: Option<SectionTower<S>>,
+    y_pos: Option<i32>,
+) -> Option<SectionTower<S>> {
+    match y_pos {
+        Some(y_pos) => sections.map(|tower| tower.with_min_section(y_pos as isize)),
+        None => sections,
+    }
+}
+
+/// The range of y covered by a chunk's sections. A chunk without sections
+/// covers nothing, placed at its `yPos` if it has one.
+fn y_range<S>(sections: Option<&SectionTower<S>>, y_pos: Option<i32>) -> Range<isize> {
+    match sections {
+        Some(sections) => sections.y_min()..sections.y_max(),
+        None => {
+            let y_min = y_pos.map_or(0, |y| y as isize * 16);
+            y_min..y_min
         }
     }
 }
 
-// TODO: Find a better way to dispatch these methods.
+/// The heightmap used for [`surface_height`][Chunk::surface_height], from the
+/// stored motion blocking heightmap if trusted, otherwise from the blocks.
+/// Chunks without blocks ignore their stored heightmap, which protochunks
+/// save before they have any.
+fn surface_heightmap<C: Chunk>(
+    chunk: &C,
+    heightmaps: Option<&Heightmaps>,
+    data_version: i32,
+    mode: HeightMode,
+) -> [i16; 256] {
+    let y_range = chunk.y_range();
+    let stored = match mode {
+        HeightMode::Trust if !y_range.is_empty() => heightmaps
+            .and_then(|hm| hm.motion_blocking.as_ref())
+            .map(|hm| {
+                let mut map = [0; 256];
+                map.copy_from_slice(&expand_heightmap(hm, y_range.start, data_version));
+                map
+            }),
+        _ => None,
+    };
+
+    stored.unwrap_or_else(|| match mode {
+        HeightMode::Trust => recompute_heightmap(chunk, HeightmapKind::MotionBlocking),
+        HeightMode::Calculate => recompute_heightmap(chunk, HeightmapKind::WorldSurface),
+    })
+}
+
+impl From<CurrentJavaChunk> for JavaChunk {
+    fn from(chunk: CurrentJavaChunk) -> Self {
+        Self::from_current(chunk, &mut vec![])
+    }
+}
+
+impl From<pre18::JavaChunk> for JavaChunk {
+    fn from(chunk: pre18::JavaChunk) -> Self {
+        Self::from_pre18(chunk, &mut vec![])
+    }
+}
+
 impl Chunk for JavaChunk {
     fn status(&self) -> String {
-        match self {
-            JavaChunk::Post18(c) => c.status(),
-            JavaChunk::Pre18(c) => c.status(),
-        }
+        self.status.clone()
     }
 
     fn surface_height(&self, x: usize, z: usize, mode: HeightMode) -> isize {
-        match self {
-            JavaChunk::Post18(c) => c.surface_height(x, z, mode),
-            JavaChunk::Pre18(c) => c.surface_height(x, z, mode),
+        let mut heightmap = self.lazy_heightmap.read().unwrap();
+        if heightmap.is_none() {
+            drop(heightmap);
+            self.recalculate_heightmap(mode);
+            heightmap = self.lazy_heightmap.read().unwrap();
         }
+        heightmap.unwrap()[z * 16 + x] as isize
     }
 
     fn biome(&self, x: usize, y: isize, z: usize) -> Option<Biome> {
-        match self {
-            JavaChunk::Post18(c) => c.biome(x, y, z),
-            JavaChunk::Pre18(c) => c.biome(x, y, z),
-        }
+        let sec = self.section_for_y(y)?;
+        let sec_y = (y - sec.y() as isize * 16) as usize;
+        sec.biome(x, sec_y, z)
     }
 
     fn block(&self, x: usize, y: isize, z: usize) -> Option<&Block> {
-        match self {
-            JavaChunk::Post18(c) => c.block(x, y, z),
-            JavaChunk::Pre18(c) => c.block(x, y, z),
-        }
+        let sec = self.section_for_y(y)?;
+        let sec_y = (y - sec.y() as isize * 16) as usize;
+        Some(sec.block(x, sec_y, z))
     }
 
     fn y_range(&self) -> Range<isize> {
-        match self {
-            JavaChunk::Post18(c) => c.y_range(),
-            JavaChunk::Pre18(c) => c.y_range(),
-        }
+        y_range(self.sections.as_ref(), self.y_pos)
+    }
+
+    fn section_fill(&self, y: isize) -> Option<SectionFill> {
+        self.section_for_y(y)?.fill()
+    }
+
+    fn section_block_counts(&self, y: isize) -> Option<Vec<(&Block, usize)>> {
+        Some(self.section_for_y(y)?.block_counts())
+    }
+    fn inhabited_time(&self) -> Option<i64> {
+        self.inhabited_time
+    }
+
+    fn last_update(&self) -> Option<i64> {
+        self.last_update
     }
 }
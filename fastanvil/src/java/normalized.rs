@@ -0,0 +1,268 @@
+use std::convert::TryFrom;
+
+use fastnbt::{IntArray, LongArray};
+use once_cell::sync::OnceCell;
+
+use crate::{
+    biome::Biome, biomes_bits_per_block, bits_per_block, palette_counts, pre18::Pre18Section,
+    unpack_section_indices, Block, Diagnostic, DiagnosticKind, Section, SectionFill, StatesIter,
+};
+
+use super::AIR;
+
+const BLOCKS_PER_SECTION: usize = 16 * 16 * 16;
+
+/// A 16x16x16 section of a chunk, in a form shared by every chunk version
+/// [`JavaChunk`][crate::JavaChunk] supports. Whatever shape a section had in
+/// the chunk NBT, it is converted to this after deserializing, so blocks and
+/// biomes are looked up the same way for all versions.
+#[derive(Debug)]
+pub struct NormalizedSection {
+    y: i8,
+    // Never empty. Without states the first item fills the section.
+    palette: Vec<Block>,
+    states: Option<PackedStates>,
+    biomes: NormalizedBiomes,
+}
+
+impl NormalizedSection {
+    fn new(
+        y: i8,
+        palette: Vec<Block>,
+        data: Option<LongArray>,
+        biomes: NormalizedBiomes,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Self {
+        // A section without block states is air, unless its palette has a
+        // single block to fill it with.
+        let (palette, states) = match data {
+            Some(data) if !palette.is_empty() => {
+                let states = PackedStates::new(data, palette.len());
+                (palette, Some(states))
+            }
+            None if palette.len() == 1 => (palette, None),
+            data => {
+                // Sections above and below the world are often saved empty,
+                // only report ones that had something in them.
+                if data.is_some() || palette.len() > 1 {
+                    diagnostics.push(Diagnostic::new(DiagnosticKind::InvalidBlockStates {
+                        section_y: y,
+                    }));
+                }
+                (vec![AIR.clone()], None)
+            }
+        };
+
+        if biomes.has_unknown() {
+            diagnostics.push(Diagnostic::new(DiagnosticKind::UnknownBiome {
+                section_y: y,
+            }));
+        }
+
+        Self {
+            y,
+            palette,
+            states,
+            biomes,
+        }
+    }
+
+    pub(crate) fn from_current(section: Section, diagnostics: &mut Vec<Diagnostic>) -> Self {
+        let (palette, data) = section.block_states.into_parts();
+        let (biome_palette, biome_data) = section.biomes.into_parts();
+
+        let biomes = match biome_data {
+            Some(data) if !biome_palette.is_empty() => {
+                let bits = biomes_bits_per_block(biome_palette.len());
+                let cells = StatesIter::new(bits, 4 * 4 * 4, &data)
+                    .map(|i| biome_palette.get(i).copied())
+                    .collect();
+                NormalizedBiomes::Cells(cells)
+            }
+            None if biome_palette.len() == 1 => NormalizedBiomes::Uniform(Some(biome_palette[0])),
+            _ => NormalizedBiomes::Uniform(None),
+        };
+
+        Self::new(section.y, palette, data, biomes, diagnostics)
+    }
+
+    /// Convert a pre-1.18 section. Biomes were stored for the whole chunk
+    /// rather than in sections before 1.18, `y_min` is the lowest y of the
+    /// chunk they start from.
+    pub(crate) fn from_pre18(
+        section: Pre18Section,
+        biomes: Option<&IntArray>,
+        y_min: isize,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Self {
+        // v1.15 was only x/z, i32 per column.
+        const V1_15: usize = 16 * 16;
+
+        let mut unknown = false;
+        let mut biome = |id: i32| {
+            let biome = Biome::try_from(id).ok();
+            unknown |= biome.is_none();
+            biome
+        };
+
+        let biomes = match biomes {
+            None => NormalizedBiomes::Uniform(None),
+            Some(biomes) if biomes.len() == V1_15 => {
+                NormalizedBiomes::Columns(biomes.iter().map(|&b| biome(b)).collect())
+            }
+            Some(biomes) => {
+                // After 1.15 biomes are split into 4-wide cubes, so 4x4x4 per
+                // section, stored from the bottom of the chunk up.
+                let start = (section.y as isize * 16 - y_min) / 4 * 16;
+                let cells = (start..start + 64)
+                    .map(|i| biome(*biomes.get(usize::try_from(i).ok()?)?))
+                    .collect();
+                NormalizedBiomes::Cells(cells)
+            }
+        };
+
+        if unknown {
+            diagnostics.push(Diagnostic::new(DiagnosticKind::UnknownBiome {
+                section_y: section.y,
+            }));
+        }
+
+        let data = section.block_states.map(|states| states.into_packed().0);
+        Self::new(section.y, section.palette, data, biomes, diagnostics)
+    }
+
+    /// The y of the section, in sections rather than blocks. The section
+    /// covers blocks `16 * y` to `16 * y + 15`.
+    pub fn y(&self) -> i8 {
+        self.y
+    }
+
+    /// The blocks that appear in the section. This is never empty.
+    pub fn palette(&self) -> &[Block] {
+        &self.palette
+    }
+
+    /// Unpack the palette index of every block in the section into `out`,
+    /// increasing in x, then z, then y. This is the same order as
+    /// [`try_iter_indices`][Self::try_iter_indices], and each index is into
+    /// [`palette`][Self::palette].
+    ///
+    /// This avoids looking up a [`Block`] for every position, eg to upload a
+    /// section to the GPU as indices with its palette alongside.
+    ///
+    /// ```no_run
+    /// # use fastanvil::NormalizedSection;
+    /// # let section: NormalizedSection = todo!();
+    /// let mut indices = [0; 4096];
+    /// section.unpack_indices(&mut indices);
+    /// let block = &section.palette()[indices[0] as usize];
+    /// ```
+    pub fn unpack_indices(&self, out: &mut [u16; 4096]) {
+        match &self.states {
+            Some(states) => states.unpack_into(out),
+            None => out.fill(0),
+        }
+    }
+
+    /// Get the block at x,y,z, where x,y,z are relative to the section ie
+    /// 0..16.
+    pub fn block(&self, x: usize, sec_y: usize, z: usize) -> &Block {
+        let i = match &self.states {
+            Some(states) => states.unpacked()[(sec_y * 16 * 16) + z * 16 + x] as usize,
+            None => 0,
+        };
+
+        self.palette.get(i).unwrap_or(&AIR)
+    }
+
+    /// Get the biome at x,y,z, where x,y,z are relative to the section ie
+    /// 0..16.
+    pub fn biome(&self, x: usize, sec_y: usize, z: usize) -> Option<Biome> {
+        match &self.biomes {
+            NormalizedBiomes::Uniform(biome) => *biome,
+            NormalizedBiomes::Cells(cells) => {
+                let i = (sec_y / 4) * 4 * 4 + (z / 4) * 4 + x / 4;
+                cells.get(i).copied().flatten()
+            }
+            NormalizedBiomes::Columns(columns) => columns.get(z * 16 + x).copied().flatten(),
+        }
+    }
+
+    /// Get iterator for the palette indices of each block. This will increase
+    /// in x, then z, then y, the same as
+    /// [`BlockData::try_iter_indices`][crate::BlockData::try_iter_indices].
+    ///
+    /// Returns None if the single block in the palette fills the section.
+    pub fn try_iter_indices(&self) -> Option<impl Iterator<Item = usize> + '_> {
+        let states = self.states.as_ref()?;
+        Some(states.unpacked().iter().map(|&i| i as usize))
+    }
+
+    /// Work out the fill of the section from its palette.
+    pub fn fill(&self) -> Option<SectionFill> {
+        SectionFill::from_palette(&self.palette)
+    }
+
+    /// Count how many of each palette item there are in the section.
+    pub fn block_counts(&self) -> Vec<(&Block, usize)> {
+        match self.try_iter_indices() {
+            Some(indices) => palette_counts(&self.palette, indices),
+            None => vec![(&self.palette[0], BLOCKS_PER_SECTION)],
+        }
+    }
+}
+
+/// Packed palette indices, unpacked on first use.
+#[derive(Debug)]
+struct PackedStates {
+    data: LongArray,
+    bits: usize,
+    unpacked: OnceCell<Box<[u16; BLOCKS_PER_SECTION]>>,
+}
+
+impl PackedStates {
+    fn new(data: LongArray, palette_len: usize) -> Self {
+        Self {
+            data,
+            bits: bits_per_block(palette_len),
+            unpacked: OnceCell::new(),
+        }
+    }
+
+    fn unpacked(&self) -> &[u16; BLOCKS_PER_SECTION] {
+        self.unpacked.get_or_init(|| {
+            let mut unpacked = Box::new([0; BLOCKS_PER_SECTION]);
+            unpack_section_indices(&self.data, self.bits, &mut unpacked);
+            unpacked
+        })
+    }
+
+    fn unpack_into(&self, out: &mut [u16; BLOCKS_PER_SECTION]) {
+        match self.unpacked.get() {
+            Some(unpacked) => out.copy_from_slice(&unpacked[..]),
+            None => unpack_section_indices(&self.data, self.bits, out),
+        }
+    }
+}
+
+impl NormalizedBiomes {
+    fn has_unknown(&self) -> bool {
+        let unknown = |b: &Option<Biome>| matches!(b, Some(Biome::Unknown));
+        match self {
+            NormalizedBiomes::Uniform(biome) => unknown(biome),
+            NormalizedBiomes::Cells(biomes) | NormalizedBiomes::Columns(biomes) => {
+                biomes.iter().any(unknown)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum NormalizedBiomes {
+    /// A single biome for the whole section, if known.
+    Uniform(Option<Biome>),
+    /// One biome per 4x4x4 cell, increasing in x, then z, then y.
+    Cells(Vec<Option<Biome>>),
+    /// One biome per column, increasing in x then z. Used by 1.15.
+    Columns(Vec<Option<Biome>>),
+}
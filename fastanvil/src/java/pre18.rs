@@ -1,16 +1,14 @@
-use std::convert::TryFrom;
-use std::ops::Range;
-use std::sync::RwLock;
-
 use fastnbt::IntArray;
 use once_cell::sync::OnceCell;
 use serde::Deserialize;
 
-use crate::java::AIR;
-use crate::{biome::Biome, Block, Chunk, HeightMode};
-use crate::{bits_per_block, expand_heightmap, Heightmaps, PackedBits, SectionLike, SectionTower};
+use crate::Block;
+use crate::{
+    bits_per_block, unpack_section_indices, Heightmaps, PackedBits, SectionLike, SectionTower,
+};
 
-/// A Minecraft chunk.
+/// A chunk from before 1.18, laid out as it is stored. Usually you want a
+/// [`JavaChunk`][crate::JavaChunk], which this can be converted into.
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct JavaChunk {
@@ -18,75 +16,6 @@ pub struct JavaChunk {
     pub level: Level,
 }
 
-impl Chunk for JavaChunk {
-    fn status(&self) -> String {
-        self.level.status.clone()
-    }
-
-    fn surface_height(&self, x: usize, z: usize, mode: HeightMode) -> isize {
-        let mut heightmap = self.level.lazy_heightmap.read().unwrap();
-        if heightmap.is_none() {
-            drop(heightmap);
-            self.recalculate_heightmap(mode);
-            heightmap = self.level.lazy_heightmap.read().unwrap();
-        }
-        heightmap.unwrap()[z * 16 + x] as isize
-    }
-
-    fn biome(&self, x: usize, y: isize, z: usize) -> Option<Biome> {
-        let biomes = self.level.biomes.as_ref()?;
-
-        // After 1.15 Each biome in i32, biomes split into 4-wide cubes, so
-        // 4x4x4 per section.
-
-        // v1.15 was only x/z, i32 per column.
-        const V1_15: usize = 16 * 16;
-
-        match biomes.len() {
-            V1_15 => {
-                // 1x1 columns stored z then x.
-                let i = z * 16 + x;
-                let biome = biomes[i];
-                Biome::try_from(biome).ok()
-            }
-            _ => {
-                // Assume latest
-                let range = self.y_range();
-                let y_shifted = (y.clamp(range.start, range.end - 1) - range.start) as usize;
-                let i = (z / 4) * 4 + (x / 4) + (y_shifted / 4) * 16;
-
-                let biome = *biomes.get(i)?;
-                Biome::try_from(biome).ok()
-            }
-        }
-    }
-
-    fn block(&self, x: usize, y: isize, z: usize) -> Option<&Block> {
-        let sec = self.level.sections.as_ref()?.get_section_for_y(y)?;
-
-        // If a section is entirely air, then the block states are missing
-        // entirely, presumably to save space.
-        match &sec.block_states {
-            None => Some(&AIR),
-            Some(blockstates) => {
-                let sec_y = (y - sec.y as isize * 16) as usize;
-                let pal_index = blockstates.state(x, sec_y, z, sec.palette.len());
-                sec.palette.get(pal_index)
-            }
-        }
-    }
-
-    fn y_range(&self) -> std::ops::Range<isize> {
-        match &self.level.sections {
-            Some(sections) => Range {
-                start: sections.y_min(),
-                end: sections.y_max(),
-            },
-            None => Range { start: 0, end: 0 },
-        }
-    }
-}
-
 /// A level describes the contents of the chunk in the world.
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
@@ -109,67 +38,9 @@ pub struct Level {
     // that haven't been fully generated yet.
     pub status: String,
 
-    #[serde(skip)]
-    lazy_heightmap: RwLock<Option<[i16; 256]>>,
-}
+    pub inhabited_time: Option<i64>,
 
-impl JavaChunk {
-    pub fn recalculate_heightmap(&self, mode: HeightMode) {
-        // TODO: Find top section and start there, pointless checking 320 down
-        // if its a 1.16 chunk.
-
-        let mut map = [0; 256];
-
-        match mode {
-            HeightMode::Trust => {
-                let updated = self
-                    .level
-                    .heightmaps
-                    .as_ref()
-                    .and_then(|hm| hm.motion_blocking.as_ref())
-                    .map(|hm| {
-                        // unwrap, if heightmaps exists, sections should... 🤞
-                        let y_min = self.level.sections.as_ref().unwrap().y_min();
-                        expand_heightmap(hm, y_min, self.data_version)
-                    })
-                    .map(|hm| map.copy_from_slice(hm.as_slice()))
-                    .is_some();
-
-                if updated {
-                    *self.level.lazy_heightmap.write().unwrap() = Some(map);
-                    return;
-                }
-            }
-            HeightMode::Calculate => {} // fall through to calc mode
-        }
-
-        let y_range = self.y_range();
-        let y_end = y_range.end;
-
-        for z in 0..16 {
-            for x in 0..16 {
-                // start at top until we hit a non-air block.
-                for i in y_range.clone() {
-                    let y = y_end - i;
-                    let block = self.block(x, y - 1, z);
-
-                    if block.is_none() {
-                        continue;
-                    }
-
-                    if !["minecraft:air", "minecraft:cave_air"]
-                        .as_ref()
-                        .contains(&block.unwrap().name())
-                    {
-                        map[z * 16 + x] = y as i16;
-                        break;
-                    }
-                }
-            }
-        }
-
-        *self.level.lazy_heightmap.write().unwrap() = Some(map);
-    }
+    pub last_update: Option<i64>,
 }
 
 /// A vertical section of a chunk (ie a 16x16x16 block cube), for before 1.18.
@@ -184,6 +55,25 @@ pub struct Pre18Section {
     pub palette: Vec<Block>,
 }
 
+impl Pre18Section {
+    /// The blocks that appear in the section.
+    pub fn palette(&self) -> &[Block] {
+        &self.palette
+    }
+
+    /// Unpack the palette index of every block in the section into `out`,
+    /// increasing in x, then z, then y. Both the padded 1.16+ packing and the
+    /// tightly packed one before it are handled.
+    pub fn unpack_indices(&self, out: &mut [u16; 4096]) {
+        match &self.block_states {
+            Some(states) => {
+                unpack_section_indices(&states.packed.0, bits_per_block(self.palette.len()), out)
+            }
+            None => out.fill(0),
+        }
+    }
+}
+
 impl SectionLike for Pre18Section {
     fn is_terminator(&self) -> bool {
         self.palette.is_empty() && self.block_states.is_none()
@@ -248,6 +138,13 @@ impl Pre18Blockstates {
     }
 }
 
+impl Pre18Blockstates {
+    /// Take the packed states.
+    pub(crate) fn into_packed(self) -> PackedBits {
+        self.packed
+    }
+}
+
 impl<'de> Deserialize<'de> for Pre18Blockstates {
     fn deserialize<D>(d: D) -> Result<Self, D::Error>
     where
@@ -0,0 +1,45 @@
+use fastnbt::{
+    error::Result,
+    value::{ListMerge, MergeStrategy},
+    Value,
+};
+use serde::Serialize;
+
+/// Write a chunk that was deserialized into your own types and modified,
+/// keeping every field of the original chunk that the types do not have, eg
+/// data added by mods or fields from newer versions. Returns the uncompressed
+/// NBT of the chunk, eg to write with
+/// [`Region::write_chunk`][crate::Region::write_chunk].
+///
+/// `original` is the uncompressed NBT the chunk was read from. `modified` is
+/// serialized and merged into it: compounds are merged key by key, and lists
+/// of the same length element by element, so a section keeps its unknown
+/// fields as long as the list of sections keeps its order and length. Lists
+/// that change length, and everything else `modified` has, replace what the
+/// original had. Fields `modified` does not have, including `None` options,
+/// are kept from the original, so fields cannot be removed this way.
+///
+/// ```no_run
+/// # use fastanvil::write_chunk_preserving;
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Serialize, Deserialize)]
+/// struct Chunk {
+///     #[serde(rename = "Status")]
+///     status: String,
+/// }
+///
+/// # let original: Vec<u8> = vec![];
+/// let mut chunk: Chunk = fastnbt::from_bytes(&original).unwrap();
+/// chunk.status = "minecraft:full".to_owned();
+/// let data = write_chunk_preserving(&original, &chunk).unwrap();
+/// ```
+pub fn write_chunk_preserving<T: Serialize>(original: &[u8], modified: &T) -> Result<Vec<u8>> {
+    let mut chunk: Value = fastnbt::from_bytes(original)?;
+
+    // Through bytes rather than a Value, so that `None` options are left
+    // out rather than being an error.
+    let modified: Value = fastnbt::from_bytes(&fastnbt::to_bytes(modified)?)?;
+
+    chunk.merge(&modified, MergeStrategy::new().lists(ListMerge::ByIndex));
+    fastnbt::to_bytes(&chunk)
+}
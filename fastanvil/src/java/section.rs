@@ -1,6 +1,10 @@
-use serde::Deserialize;
+use serde::{
+    de::{self, value::BorrowedStrDeserializer},
+    Deserialize, Deserializer,
+};
 
-use crate::{biome::Biome, BiomeData, Block, BlockData};
+use super::block::parse_name;
+use crate::{biome::Biome, BiomeData, Block, BlockData, SectionBiomes, SectionBlockStates};
 
 pub trait SectionLike {
     fn is_terminator(&self) -> bool;
@@ -13,13 +17,54 @@ pub struct Section {
     #[serde(rename = "Y")]
     pub y: i8,
 
-    #[serde(default)]
+    #[serde(default, deserialize_with = "block_states")]
     pub block_states: BlockData<Block>,
 
-    #[serde(default)]
+    #[serde(default, deserialize_with = "biomes")]
     pub biomes: BiomeData<Biome>,
 }
 
+// Sections are read through the public types, so that those always match
+// what the crate itself reads.
+
+fn block_states<'de, D: Deserializer<'de>>(d: D) -> Result<BlockData<Block>, D::Error> {
+    let states = SectionBlockStates::deserialize(d)?;
+    let palette = states
+        .palette
+        .into_iter()
+        .map(|item| {
+            parse_name(&item.name).map_err(de::Error::custom)?;
+            Ok(Block::from(item))
+        })
+        .collect::<Result<_, D::Error>>()?;
+
+    Ok(BlockData::from_parts(palette, states.data))
+}
+
+fn biomes<'de, D: Deserializer<'de>>(d: D) -> Result<BiomeData<Biome>, D::Error> {
+    let biomes = SectionBiomes::deserialize(d)?;
+    let palette = biomes
+        .palette
+        .iter()
+        .map(|name| Biome::deserialize(BorrowedStrDeserializer::<D::Error>::new(name)))
+        .collect::<Result<_, _>>()?;
+
+    Ok(BiomeData::from_parts(palette, biomes.data))
+}
+
+impl Section {
+    /// The blocks that appear in the section.
+    pub fn palette(&self) -> &[Block] {
+        self.block_states.palette()
+    }
+
+    /// Unpack the palette index of every block in the section into `out`,
+    /// increasing in x, then z, then y. See [`BlockData::unpack_indices`].
+    pub fn unpack_indices(&self, out: &mut [u16; 4096]) {
+        self.block_states.unpack_indices(out)
+    }
+}
+
 impl SectionLike for Section {
     fn is_terminator(&self) -> bool {
         false
@@ -1,9 +1,12 @@
 use bit_field::BitField;
+use fastnbt::borrow::CowStr;
 use fastnbt::LongArray;
 
 use serde::Deserialize;
 use std::fmt::Debug;
 
+use crate::{unpack_section_indices, PaletteItem};
+
 #[derive(Deserialize, Debug)]
 #[serde(transparent)]
 pub struct BlockData<T: Debug> {
@@ -59,6 +62,30 @@ impl<T: Debug> BlockData<T> {
     pub fn palette(&self) -> &[T] {
         self.inner.palette.as_slice()
     }
+
+    /// Unpack the palette index of every block into `out`, increasing in x,
+    /// then z, then y. If there are no block states every index is 0, ie the
+    /// single item in the palette fills the section.
+    pub fn unpack_indices(&self, out: &mut [u16; 4096]) {
+        match &self.inner.data {
+            Some(data) => {
+                let bits = blockstates_bits_per_block(self.inner.palette.len());
+                unpack_section_indices(data, bits, out);
+            }
+            None => out.fill(0),
+        }
+    }
+
+    /// Take the palette and packed data.
+    pub(crate) fn into_parts(self) -> (Vec<T>, Option<LongArray>) {
+        (self.inner.palette, self.inner.data)
+    }
+
+    pub(crate) fn from_parts(palette: Vec<T>, data: Option<LongArray>) -> Self {
+        Self {
+            inner: DataInner { data, palette },
+        }
+    }
 }
 
 impl<T: Debug> BiomeData<T> {
@@ -86,6 +113,78 @@ impl<T: Debug> BiomeData<T> {
     pub fn palette(&self) -> &[T] {
         self.inner.palette.as_slice()
     }
+
+    /// Take the palette and packed data.
+    pub(crate) fn into_parts(self) -> (Vec<T>, Option<LongArray>) {
+        (self.inner.palette, self.inner.data)
+    }
+
+    pub(crate) fn from_parts(palette: Vec<T>, data: Option<LongArray>) -> Self {
+        Self {
+            inner: DataInner { data, palette },
+        }
+    }
+}
+
+/// The `block_states` of a 1.18+ section. This mirrors the layout in the chunk
+/// NBT, for use in your own chunk structures. [`JavaChunk`][crate::JavaChunk]
+/// reads its sections through this too.
+///
+/// ```no_run
+/// use fastanvil::{SectionBiomes, SectionBlockStates};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Section<'a> {
+///     #[serde(rename = "Y")]
+///     y: i8,
+///     #[serde(borrow)]
+///     block_states: Option<SectionBlockStates<'a>>,
+///     #[serde(borrow)]
+///     biomes: Option<SectionBiomes<'a>>,
+/// }
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SectionBlockStates<'a> {
+    #[serde(borrow)]
+    pub palette: Vec<PaletteItem<'a>>,
+
+    /// Packed indices into the palette. Missing if the palette has a single
+    /// item, which fills the whole section.
+    pub data: Option<LongArray>,
+}
+
+impl<'a> SectionBlockStates<'a> {
+    /// Get iterator for the palette indices of each block, the same as
+    /// [`BlockData::try_iter_indices`].
+    pub fn try_iter_indices(&self) -> Option<StatesIter<'_>> {
+        let data = self.data.as_ref()?;
+        let bits = blockstates_bits_per_block(self.palette.len());
+        Some(StatesIter::new(bits, 16 * 16 * 16, data))
+    }
+}
+
+/// The `biomes` of a 1.18+ section. This mirrors the layout in the chunk NBT,
+/// for use in your own chunk structures. See [`SectionBlockStates`].
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SectionBiomes<'a> {
+    /// Namespaced biome names, eg `minecraft:plains`.
+    #[serde(borrow)]
+    pub palette: Vec<CowStr<'a>>,
+
+    /// Packed indices into the palette. Missing if the palette has a single
+    /// item, which fills the whole section.
+    pub data: Option<LongArray>,
+}
+
+impl<'a> SectionBiomes<'a> {
+    /// Get iterator for the palette indices of each 4x4x4 cell of biome, the
+    /// same as [`BiomeData::try_iter_indices`].
+    pub fn try_iter_indices(&self) -> Option<StatesIter<'_>> {
+        let data = self.data.as_ref()?;
+        let bits = biomes_bits_per_block(self.palette.len());
+        Some(StatesIter::new(bits, 4 * 4 * 4, data))
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -155,7 +254,7 @@ fn blockstates_bits_per_block(palette_len: usize) -> usize {
 }
 
 /// Number of bits that will be used per block in block_states data for biomes.
-fn biomes_bits_per_block(palette_len: usize) -> usize {
+pub(crate) fn biomes_bits_per_block(palette_len: usize) -> usize {
     std::cmp::max(1, min_bits_for_n_states(palette_len)) as usize
     // std::cmp::max((palette_len as f64).log2().ceil() as usize, 1)
 }
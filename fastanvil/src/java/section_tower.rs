@@ -38,6 +38,56 @@ impl<S> SectionTower<S> {
     pub fn y_max(&self) -> isize {
         self.y_max
     }
+
+    /// Convert each section, keeping them at the same heights.
+    pub(crate) fn map<T>(self, f: impl FnMut(S) -> T) -> SectionTower<T> {
+        SectionTower {
+            sections: self.sections.into_iter().map(f).collect(),
+            map: self.map,
+            y_min: self.y_min,
+            y_max: self.y_max,
+        }
+    }
+}
+
+impl<S: SectionLike> SectionTower<S> {
+    /// Set the lowest section of the tower, eg from the `yPos` of a 1.18
+    /// chunk, rather than working it out from the sections that were saved.
+    /// Sections below it, such as those saved only for their light, are left
+    /// out of the tower.
+    pub(crate) fn with_min_section(self, min: isize) -> Self {
+        let max = self
+            .sections
+            .iter()
+            .map(|s| s.y() as isize)
+            .filter(|&y| y >= min)
+            .max()
+            .unwrap_or(min - 1);
+
+        Self::build(self.sections, min, max)
+    }
+
+    /// A tower from section `min` to section `max` inclusive. Sections outside
+    /// of that are kept, but can't be looked up by y.
+    fn build(sections: Vec<S>, min: isize, max: isize) -> Self {
+        let mut map = vec![None; (1 + max - min).max(0) as usize];
+
+        for (i, sec) in sections.iter().enumerate() {
+            let y = sec.y() as isize;
+            if y < min || y > max {
+                continue;
+            }
+
+            map[(y - min) as usize] = Some(i);
+        }
+
+        Self {
+            sections,
+            map,
+            y_min: 16 * min,
+            y_max: 16 * (max + 1),
+        }
+    }
 }
 
 impl<'de, S: SectionLike + Deserialize<'de>> Deserialize<'de> for SectionTower<S> {
@@ -81,25 +131,7 @@ impl<'de, S: SectionLike + Deserialize<'de>> Deserialize<'de> for SectionTower<S
             .map(|s| s.y())
             .unwrap() as isize;
 
-        let mut sparse_sections = vec![None; (1 + max - min) as usize];
-
-        for (i, sec) in sections.iter().enumerate() {
-            // Don't bother adding the null section.
-            if sec.y() == lowest_section.y() && null_term {
-                continue;
-            }
-
-            let sec_index = (sec.y() as isize - min) as usize;
-
-            sparse_sections[sec_index] = Some(i);
-        }
-
-        Ok(Self {
-            sections,
-            map: sparse_sections,
-            y_min: 16 * min,
-            y_max: 16 * (max + 1),
-        })
+        Ok(Self::build(sections, min, max))
     }
 }
 
@@ -8,38 +8,135 @@
 //! for serializing back into a region.
 //!
 //! You can create your own chunk structures to (de)serialize using [`fastnbt`].
+//! Chunks from before 1.18 can be rewritten into the 1.18 layout with
+//! [`upgrade::chunk_to_1_18`], so only that layout needs handling. The
+//! [`version`] module names the game versions that saved a world and its
+//! chunks.
 //!
 //! [`Region`] can be given a `Read`, `Write` and `Seek` type eg a file in
 //! order to read and write chunk data.
+//! For other IO, eg async, [`RegionIndex`], [`chunk_payload`] and
+//! [`decode_chunk`] parse and decode regions without doing any IO themselves.
+//!
+//! Times such as chunk timestamps can be read as a `SystemTime`, or as a
+//! `time::OffsetDateTime` with the `time` feature enabled. See [`EpochTime`].
+//!
+//! Rendering, ie the [`Palette`] and [`TopShadeRenderer`] and the `tex`
+//! module for making palettes from textures, is behind the `render` feature.
+//! It is enabled by default. Turn off default features to only parse worlds,
+//! without compiling `image` and the renderers.
+//!
+//! The `fast-hash` feature enables the feature of the same name in
+//! [`fastnbt`], switching maps used internally and in palettes to a faster
+//! hasher. See [`fastnbt::Map`].
+//!
+//! The `mmap` feature adds `MmapRegionLoader`, which memory maps region files
+//! rather than reading them through a file handle.
+//!
+//! The `serde_json` feature adds the `text_component` module, for the JSON
+//! text in signs and written books.
+//!
+//! The [`entity`] module reads entities into typed structs, for the fields
+//! all entities share and a few common types such as item frames.
+//!
+//! The [`export`] module writes a table of chunks as CSV, eg for data
+//! analysis. The `arrow` feature adds Arrow and Parquet output to it.
+//!
+//! The `test-fixtures` feature adds the `fixtures` module, with real chunks
+//! from several versions of the game and regions built from them, for testing
+//! code that uses this crate.
 
 pub mod biome;
+pub mod data;
+pub mod entity;
+pub mod export;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod fixtures;
+#[cfg(feature = "render")]
 pub mod tex;
+#[cfg(feature = "serde_json")]
+pub mod text_component;
+pub mod upgrade;
+pub mod version;
 
+mod activity;
 mod bits;
+mod block_entity;
+mod compression;
+mod diagnostics;
 mod dimension;
+#[cfg(feature = "render")]
+mod elevation;
+mod epoch;
 mod files;
+mod heightmap;
 mod java;
+#[cfg(feature = "render")]
+mod map_colours;
 mod region;
+mod region_index;
+#[cfg(feature = "render")]
 mod render;
+#[cfg(feature = "render")]
 mod rendered_palette;
+mod resource_location;
+mod stats;
+mod world;
+#[cfg(feature = "render")]
+mod world_image;
 
+pub use activity::*;
 pub use bits::*;
+pub use block_entity::*;
+pub use compression::*;
+pub use diagnostics::*;
 pub use dimension::*;
+#[cfg(feature = "render")]
+pub use elevation::*;
+pub use epoch::*;
 pub use files::*;
+pub use heightmap::*;
 pub use java::*;
+#[cfg(feature = "render")]
+pub use map_colours::*;
 pub use region::*;
+pub use region_index::*;
+#[cfg(feature = "render")]
 pub use render::*;
+#[cfg(feature = "render")]
 pub use rendered_palette::*;
+pub use resource_location::*;
+pub use stats::*;
+pub use world::*;
+#[cfg(feature = "render")]
+pub use world_image::*;
 
 #[cfg(test)]
 mod test;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     IO(std::io::Error),
+    /// A zlib chunk's compressed data was corrupt. Corrupt gzip chunks are
+    /// read through flate2's `GzDecoder`, which reports them as an
+    /// [`IO`][Error::IO] error of kind
+    /// [`InvalidInput`][std::io::ErrorKind::InvalidInput].
+    Decompress(flate2::DecompressError),
     InvalidOffset(isize, isize),
     UnknownCompression(u8),
     ChunkTooLarge,
+    /// The chunk at x, z within its region would be more than `limit` bytes
+    /// once decompressed, so reading it stopped. See
+    /// [`Region::with_max_uncompressed_len`].
+    UncompressedTooLarge {
+        x: usize,
+        z: usize,
+        limit: usize,
+    },
+    /// A [`ScanCursor`] was used with a region that has changed since the
+    /// cursor was taken.
+    StaleCursor,
 }
 
 impl From<std::io::Error> for Error {
@@ -48,12 +145,19 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<flate2::DecompressError> for Error {
+    fn from(err: flate2::DecompressError) -> Error {
+        Error::Decompress(err)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::IO(e) => f.write_fmt(format_args!("io error: {e:?}")),
+            Error::IO(e) => f.write_fmt(format_args!("io error: {e}")),
+            Error::Decompress(e) => f.write_fmt(format_args!("chunk is corrupt: {e}")),
             Error::InvalidOffset(x, z) => {
                 f.write_fmt(format_args!("invalid offset: x = {x}, z = {z}"))
             }
@@ -61,8 +165,20 @@ impl std::fmt::Display for Error {
                 "compression scheme ({scheme}) was not recognised for chunk"
             )),
             Error::ChunkTooLarge => f.write_str("chunk too large to store"),
+            Error::UncompressedTooLarge { x, z, limit } => f.write_fmt(format_args!(
+                "chunk at x = {x}, z = {z} is more than {limit} bytes uncompressed"
+            )),
+            Error::StaleCursor => f.write_str("region has changed since the scan cursor was taken"),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IO(e) => Some(e),
+            Error::Decompress(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,517 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{biome::Biome, Block, Palette, Rgba};
+
+// The base colours vanilla uses when drawing blocks on in-game maps.
+const GRASS: Rgba = [127, 178, 56, 255];
+const SAND: Rgba = [247, 233, 163, 255];
+const WOOL: Rgba = [199, 199, 199, 255];
+const FIRE: Rgba = [255, 0, 0, 255];
+const ICE: Rgba = [160, 160, 255, 255];
+const METAL: Rgba = [167, 167, 167, 255];
+const PLANT: Rgba = [0, 124, 0, 255];
+const SNOW: Rgba = [255, 255, 255, 255];
+const CLAY: Rgba = [164, 168, 184, 255];
+const DIRT: Rgba = [151, 109, 77, 255];
+const STONE: Rgba = [112, 112, 112, 255];
+const WATER: Rgba = [64, 64, 255, 255];
+const WOOD: Rgba = [143, 119, 72, 255];
+const QUARTZ: Rgba = [255, 252, 245, 255];
+const GOLD: Rgba = [250, 238, 77, 255];
+const DIAMOND: Rgba = [92, 219, 213, 255];
+const LAPIS: Rgba = [74, 128, 255, 255];
+const EMERALD: Rgba = [0, 217, 58, 255];
+const PODZOL: Rgba = [129, 86, 49, 255];
+const NETHER: Rgba = [112, 2, 0, 255];
+const CRIMSON_NYLIUM: Rgba = [189, 48, 49, 255];
+const CRIMSON_STEM: Rgba = [148, 63, 97, 255];
+const CRIMSON_HYPHAE: Rgba = [92, 25, 29, 255];
+const WARPED_NYLIUM: Rgba = [22, 126, 134, 255];
+const WARPED_STEM: Rgba = [58, 142, 140, 255];
+const WARPED_HYPHAE: Rgba = [86, 44, 62, 255];
+const WARPED_WART_BLOCK: Rgba = [20, 180, 133, 255];
+const DEEPSLATE: Rgba = [100, 100, 100, 255];
+const RAW_IRON: Rgba = [216, 175, 147, 255];
+const GLOW_LICHEN: Rgba = [127, 167, 150, 255];
+
+pub(crate) const DYES: [&str; 16] = [
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "light_gray",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+
+/// Map colours of dyed blocks such as wool, in the order of [`DYES`].
+const DYE_COLOURS: [Rgba; 16] = [
+    SNOW,
+    [216, 127, 51, 255],
+    [178, 76, 216, 255],
+    [102, 153, 216, 255],
+    [229, 229, 51, 255],
+    [127, 204, 25, 255],
+    [242, 127, 165, 255],
+    [76, 76, 76, 255],
+    [153, 153, 153, 255],
+    [76, 127, 153, 255],
+    [127, 63, 178, 255],
+    [51, 76, 178, 255],
+    [102, 76, 51, 255],
+    [102, 127, 51, 255],
+    [153, 51, 51, 255],
+    [25, 25, 25, 255],
+];
+
+/// Map colours of dyed terracotta, in the order of [`DYES`].
+const TERRACOTTA_COLOURS: [Rgba; 16] = [
+    [209, 177, 161, 255],
+    [159, 82, 36, 255],
+    [149, 87, 108, 255],
+    [112, 108, 138, 255],
+    [186, 133, 36, 255],
+    [103, 117, 53, 255],
+    [160, 77, 78, 255],
+    [57, 41, 35, 255],
+    [135, 107, 98, 255],
+    [87, 92, 92, 255],
+    [122, 73, 88, 255],
+    [76, 62, 92, 255],
+    [76, 50, 35, 255],
+    [76, 82, 42, 255],
+    [142, 60, 46, 255],
+    [37, 22, 16, 255],
+];
+
+const ORANGE: Rgba = DYE_COLOURS[1];
+const MAGENTA: Rgba = DYE_COLOURS[2];
+const YELLOW: Rgba = DYE_COLOURS[4];
+const LIME: Rgba = DYE_COLOURS[5];
+const PURPLE: Rgba = DYE_COLOURS[10];
+const CYAN: Rgba = DYE_COLOURS[9];
+const BROWN: Rgba = DYE_COLOURS[12];
+const GREEN: Rgba = DYE_COLOURS[13];
+const RED: Rgba = DYE_COLOURS[14];
+const BLACK: Rgba = DYE_COLOURS[15];
+
+/// The map colour of a dye, eg `light_blue`.
+pub(crate) fn dye_colour(dye: &str) -> Option<Rgba> {
+    let i = DYES.iter().position(|d| *d == dye)?;
+    Some(DYE_COLOURS[i])
+}
+
+/// Split a dyed block id into its dye and the rest, eg `light_blue_wool` into
+/// `light_blue` and `wool`.
+fn split_dye(id: &str) -> Option<(usize, &str)> {
+    // Take the longest match, so light_blue is not taken as blue.
+    DYES.iter()
+        .enumerate()
+        .filter_map(|(i, dye)| Some((i, id.strip_prefix(dye)?.strip_prefix('_')?)))
+        .min_by_key(|(_, rest)| rest.len())
+}
+
+/// Wood types and the map colour of their planks, which is also the colour of
+/// the top of their logs.
+const WOODS: [(&str, Rgba); 11] = [
+    ("oak", WOOD),
+    ("spruce", PODZOL),
+    ("birch", SAND),
+    ("jungle", DIRT),
+    ("acacia", ORANGE),
+    ("dark_oak", BROWN),
+    ("mangrove", RED),
+    ("cherry", TERRACOTTA_COLOURS[0]),
+    ("bamboo", YELLOW),
+    ("crimson", CRIMSON_STEM),
+    ("warped", WARPED_STEM),
+];
+
+/// The colour vanilla Minecraft draws a block with on in-game maps, for a
+/// compact table of common blocks. The id may be given with or without the
+/// `minecraft:` namespace, eg `minecraft:stone` or `stone`. Blocks that do not
+/// show on maps, such as glass, have no colour.
+pub fn vanilla_map_colour(id: &str) -> Option<Rgba> {
+    let id = id.strip_prefix("minecraft:").unwrap_or(id);
+
+    let colour = match id {
+        "grass_block" => GRASS,
+        "dirt"
+        | "coarse_dirt"
+        | "rooted_dirt"
+        | "farmland"
+        | "dirt_path"
+        | "granite"
+        | "polished_granite"
+        | "brown_mushroom_block" => DIRT,
+        "podzol" => PODZOL,
+        "mycelium" | "amethyst_block" => PURPLE,
+        "mud" => TERRACOTTA_COLOURS[9],
+        "mud_bricks" => TERRACOTTA_COLOURS[8],
+        "clay" => CLAY,
+        "sand" | "sandstone" | "smooth_sandstone" | "cut_sandstone" | "chiseled_sandstone"
+        | "end_stone" | "end_stone_bricks" | "glowstone" | "bone_block" => SAND,
+        "red_sand"
+        | "red_sandstone"
+        | "smooth_red_sandstone"
+        | "cut_red_sandstone"
+        | "terracotta"
+        | "pumpkin"
+        | "carved_pumpkin"
+        | "jack_o_lantern"
+        | "copper_block"
+        | "cut_copper"
+        | "raw_copper_block" => ORANGE,
+        "stone"
+        | "cobblestone"
+        | "mossy_cobblestone"
+        | "smooth_stone"
+        | "stone_bricks"
+        | "mossy_stone_bricks"
+        | "cracked_stone_bricks"
+        | "chiseled_stone_bricks"
+        | "andesite"
+        | "polished_andesite"
+        | "gravel"
+        | "bedrock"
+        | "furnace"
+        | "dispenser"
+        | "dropper"
+        | "observer"
+        | "piston"
+        | "sticky_piston"
+        | "stonecutter"
+        | "cauldron" => STONE,
+        "diorite"
+        | "polished_diorite"
+        | "quartz_block"
+        | "smooth_quartz"
+        | "quartz_pillar"
+        | "chiseled_quartz_block"
+        | "quartz_bricks"
+        | "sea_lantern" => QUARTZ,
+        "deepslate"
+        | "cobbled_deepslate"
+        | "polished_deepslate"
+        | "deepslate_bricks"
+        | "deepslate_tiles"
+        | "chiseled_deepslate"
+        | "reinforced_deepslate" => DEEPSLATE,
+        "tuff" => TERRACOTTA_COLOURS[7],
+        "calcite" => TERRACOTTA_COLOURS[0],
+        "dripstone_block" | "pointed_dripstone" => TERRACOTTA_COLOURS[12],
+        "water" | "bubble_column" | "kelp" | "kelp_plant" | "seagrass" | "tall_seagrass" => WATER,
+        "lava" | "fire" | "redstone_block" | "tnt" => FIRE,
+        "ice" | "packed_ice" | "blue_ice" | "frosted_ice" => ICE,
+        "snow" | "snow_block" | "powder_snow" | "white_wool" => SNOW,
+        "netherrack"
+        | "nether_bricks"
+        | "red_nether_bricks"
+        | "cracked_nether_bricks"
+        | "chiseled_nether_bricks"
+        | "nether_quartz_ore"
+        | "nether_gold_ore"
+        | "magma_block" => NETHER,
+        "soul_sand" | "soul_soil" => BROWN,
+        "basalt"
+        | "polished_basalt"
+        | "smooth_basalt"
+        | "blackstone"
+        | "polished_blackstone"
+        | "polished_blackstone_bricks"
+        | "gilded_blackstone"
+        | "obsidian"
+        | "crying_obsidian"
+        | "coal_block"
+        | "netherite_block"
+        | "ancient_debris" => BLACK,
+        "crimson_nylium" => CRIMSON_NYLIUM,
+        "crimson_hyphae" | "stripped_crimson_hyphae" => CRIMSON_HYPHAE,
+        "warped_nylium" | "oxidized_copper" | "oxidized_cut_copper" => WARPED_NYLIUM,
+        "warped_hyphae" | "stripped_warped_hyphae" => WARPED_HYPHAE,
+        "warped_wart_block" => WARPED_WART_BLOCK,
+        "weathered_copper" | "weathered_cut_copper" => WARPED_STEM,
+        "exposed_copper" | "exposed_cut_copper" => TERRACOTTA_COLOURS[8],
+        "nether_wart_block" | "shroomlight" | "red_mushroom_block" | "bricks" | "red_mushroom" => {
+            RED
+        }
+        "purpur_block" | "purpur_pillar" => MAGENTA,
+        "prismarine" => CYAN,
+        "prismarine_bricks" | "dark_prismarine" | "diamond_block" => DIAMOND,
+        "iron_block"
+        | "anvil"
+        | "chipped_anvil"
+        | "damaged_anvil"
+        | "heavy_weighted_pressure_plate" => METAL,
+        "raw_iron_block" => RAW_IRON,
+        "gold_block" | "raw_gold_block" | "light_weighted_pressure_plate" | "bell" => GOLD,
+        "emerald_block" => EMERALD,
+        "lapis_block" => LAPIS,
+        "melon" => LIME,
+        "hay_block" | "sponge" | "wet_sponge" => YELLOW,
+        "moss_block" | "moss_carpet" | "dried_kelp_block" => GREEN,
+        "glow_lichen" => GLOW_LICHEN,
+        "mushroom_stem" | "cobweb" => WOOL,
+        "bookshelf" | "crafting_table" | "chest" | "trapped_chest" | "barrel" | "note_block"
+        | "jukebox" | "lectern" | "composter" => WOOD,
+        "grass" | "tall_grass" | "fern" | "large_fern" | "dead_bush" | "vine" | "lily_pad"
+        | "cactus" | "sugar_cane" | "bamboo" | "sweet_berry_bush" | "dandelion" | "poppy"
+        | "blue_orchid" | "allium" | "azure_bluet" | "red_tulip" | "orange_tulip"
+        | "white_tulip" | "pink_tulip" | "oxeye_daisy" | "cornflower" | "lily_of_the_valley"
+        | "sunflower" | "lilac" | "rose_bush" | "peony" | "wheat" | "carrots" | "potatoes"
+        | "beetroots" | "azalea" | "flowering_azalea" | "big_dripleaf" | "small_dripleaf" => PLANT,
+        _ => return vanilla_family_colour(id),
+    };
+
+    Some(colour)
+}
+
+/// Colours of families of vanilla blocks, such as each colour of wool or each
+/// type of wood.
+fn vanilla_family_colour(id: &str) -> Option<Rgba> {
+    if id.ends_with("_leaves") || id.ends_with("_sapling") || id.ends_with("_propagule") {
+        return Some(PLANT);
+    }
+
+    if let Some(ore) = id.strip_suffix("_ore") {
+        return Some(if ore.starts_with("deepslate_") {
+            DEEPSLATE
+        } else {
+            STONE
+        });
+    }
+
+    if let Some((dye, rest)) = split_dye(id) {
+        return match rest {
+            "terracotta" => Some(TERRACOTTA_COLOURS[dye]),
+            "wool" | "carpet" | "concrete" | "concrete_powder" | "stained_glass"
+            | "stained_glass_pane" | "shulker_box" | "bed" | "banner" | "wall_banner"
+            | "candle" | "glazed_terracotta" => Some(DYE_COLOURS[dye]),
+            _ => None,
+        };
+    }
+
+    let wood = id.strip_prefix("stripped_").unwrap_or(id);
+    WOODS.iter().find_map(|(name, colour)| {
+        wood.strip_prefix(name)?.strip_prefix('_')?;
+        Some(*colour)
+    })
+}
+
+/// Words in block ids that suggest a colour, checked in order. Used for
+/// blocks that are not in the vanilla table, eg from mods.
+const WORD_COLOURS: &[(&[&str], Rgba)] = &[
+    (
+        &[
+            "leaves", "leaf", "sapling", "flower", "bush", "vine", "vines", "fern", "shrub",
+            "reeds", "crop", "crops",
+        ],
+        PLANT,
+    ),
+    (
+        &[
+            "planks", "plank", "log", "logs", "wood", "wooden", "bark", "timber",
+        ],
+        WOOD,
+    ),
+    (&["grass", "turf", "moss"], GRASS),
+    (&["deepslate"], DEEPSLATE),
+    (&["netherrack", "nether"], NETHER),
+    (&["sand", "sandstone"], SAND),
+    (&["dirt", "soil", "mud", "loam", "peat"], DIRT),
+    (&["snow"], SNOW),
+    (&["ice", "frost"], ICE),
+    (&["water"], WATER),
+    (&["lava", "magma"], FIRE),
+    (&["gold", "golden"], GOLD),
+    (&["diamond"], DIAMOND),
+    (&["emerald"], EMERALD),
+    (&["lapis"], LAPIS),
+    (&["copper", "bronze", "brass"], ORANGE),
+    (
+        &[
+            "iron",
+            "steel",
+            "metal",
+            "tin",
+            "silver",
+            "aluminum",
+            "aluminium",
+            "lead",
+            "nickel",
+            "machine",
+            "casing",
+        ],
+        METAL,
+    ),
+    (&["quartz", "marble", "chalk"], QUARTZ),
+    (&["clay"], CLAY),
+    (
+        &["obsidian", "coal", "basalt", "blackstone", "asphalt"],
+        BLACK,
+    ),
+    (&["wool", "cloth"], WOOL),
+    (
+        &[
+            "stone",
+            "cobblestone",
+            "cobble",
+            "brick",
+            "bricks",
+            "rock",
+            "slate",
+            "gravel",
+            "granite",
+            "andesite",
+            "limestone",
+            "ore",
+        ],
+        STONE,
+    ),
+];
+
+/// Guess the map colour of any block, eg one added by a mod. This first looks
+/// for a vanilla block of the same name in any namespace, then looks at the
+/// words making up the name, eg `_ore`, `_planks` or `_leaves`. Returns `None`
+/// if nothing in the name suggests a colour.
+pub fn guess_map_colour(id: &str) -> Option<Rgba> {
+    let path = id.split_once(':').map_or(id, |(_, path)| path);
+
+    if let Some(colour) = vanilla_map_colour(path) {
+        return Some(colour);
+    }
+
+    // Mods tend to name their blocks like vanilla ones, eg a dye colour first.
+    if let Some((dye, rest)) = split_dye(path) {
+        return Some(match rest.contains("terracotta") {
+            true => TERRACOTTA_COLOURS[dye],
+            false => DYE_COLOURS[dye],
+        });
+    }
+
+    let words: Vec<_> = path.split('_').collect();
+    WORD_COLOURS
+        .iter()
+        .find(|(names, _)| words.iter().any(|w| names.contains(w)))
+        .map(|(_, colour)| *colour)
+}
+
+/// A [`Palette`] that falls back to map colours for blocks its primary palette
+/// has no colour for, see [`Palette::try_pick`]. This avoids holes in maps of
+/// modded worlds, or of versions newer than the primary palette.
+///
+/// Blocks are first looked up in a table of vanilla map colours, see
+/// [`vanilla_map_colour`], then their colour is guessed from their name, see
+/// [`guess_map_colour`]. Anything left is drawn with the colour of stone.
+///
+/// How often each fallback was used is counted, so you can see how much of a
+/// map was guessed. See [`stats`][Self::stats].
+///
+/// ```no_run
+/// # use fastanvil::{HeightMode, Palette, RenderedPalette, TopShadeRenderer};
+/// # fn load_palette() -> RenderedPalette { unimplemented!() }
+/// let palette = load_palette().with_fallbacks();
+/// let renderer = TopShadeRenderer::new(&palette, HeightMode::Trust);
+/// // render some regions...
+/// let stats = palette.stats();
+/// println!("{} of the blocks drawn were guessed", stats.total());
+/// ```
+#[derive(Debug, Default)]
+pub struct FallbackPalette<P> {
+    primary: P,
+    vanilla: AtomicUsize,
+    guessed: AtomicUsize,
+    unknown: AtomicUsize,
+}
+
+/// How many times a [`FallbackPalette`] fell back rather than using its primary
+/// palette.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FallbackStats {
+    /// Blocks found in the table of vanilla map colours.
+    pub vanilla: usize,
+    /// Blocks whose colour was guessed from their name.
+    pub guessed: usize,
+    /// Blocks that could not be guessed, drawn as stone.
+    pub unknown: usize,
+}
+
+impl FallbackStats {
+    /// The total number of times a fallback was used.
+    pub fn total(&self) -> usize {
+        self.vanilla + self.guessed + self.unknown
+    }
+}
+
+impl<P: Palette> FallbackPalette<P> {
+    pub fn new(primary: P) -> Self {
+        Self {
+            primary,
+            vanilla: AtomicUsize::new(0),
+            guessed: AtomicUsize::new(0),
+            unknown: AtomicUsize::new(0),
+        }
+    }
+
+    /// The palette tried before any fallback.
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    /// How many times each fallback has been used so far.
+    pub fn stats(&self) -> FallbackStats {
+        FallbackStats {
+            vanilla: self.vanilla.load(Ordering::Relaxed),
+            guessed: self.guessed.load(Ordering::Relaxed),
+            unknown: self.unknown.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Set the counts of fallbacks back to zero.
+    pub fn reset_stats(&self) {
+        self.vanilla.store(0, Ordering::Relaxed);
+        self.guessed.store(0, Ordering::Relaxed);
+        self.unknown.store(0, Ordering::Relaxed);
+    }
+
+    fn fallback(&self, block: &Block) -> Rgba {
+        let name = block.name();
+        let vanilla = match name.split_once(':') {
+            Some((namespace, _)) => namespace == "minecraft",
+            None => true,
+        };
+
+        let vanilla_colour = match vanilla {
+            true => vanilla_map_colour(name),
+            false => None,
+        };
+
+        let (counter, colour) = match vanilla_colour {
+            Some(colour) => (&self.vanilla, colour),
+            None => match guess_map_colour(name) {
+                Some(colour) => (&self.guessed, colour),
+                None => (&self.unknown, STONE),
+            },
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+        colour
+    }
+}
+
+impl<P: Palette> Palette for FallbackPalette<P> {
+    fn pick(&self, block: &Block, biome: Option<Biome>) -> Rgba {
+        self.primary
+            .try_pick(block, biome)
+            .unwrap_or_else(|| self.fallback(block))
+    }
+}
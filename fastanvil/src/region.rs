@@ -2,30 +2,80 @@ use std::convert::TryFrom;
 use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use flate2::read::ZlibEncoder;
-use flate2::Compression;
 use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
 
-use crate::{Error, Result};
+use crate::{
+    chunk_payload, decode_chunk_limited, ChunkBitmap, ChunkWrite, CompressionPolicy, EpochTime,
+    Error, RegionIndex, Result,
+};
+
+/// The number of chunks along each side of a region. A region is 32 by 32
+/// chunks.
+pub const CHUNKS_PER_REGION_SIDE: usize = 32;
+
+/// The number of chunks in a region.
+pub const CHUNKS_PER_REGION: usize = CHUNKS_PER_REGION_SIDE * CHUNKS_PER_REGION_SIDE;
 
 /// the size in bytes of a 'sector' in a region file. Sectors are Minecraft's size unit
 /// for chunks. For example, a chunk might be `3 * SECTOR_SIZE` bytes. The
 /// actual compressed bytes of a chunk may be smaller and the exact value is
 /// tracking in the chunk header.
-pub(crate) const SECTOR_SIZE: usize = 4096;
+pub const SECTOR_SIZE: usize = 4096;
 
-/// the size of the region file header.
-pub(crate) const REGION_HEADER_SIZE: usize = 2 * SECTOR_SIZE;
+/// the size of the region file header, a sector of chunk locations followed by
+/// a sector of timestamps.
+pub const REGION_HEADER_SIZE: usize = 2 * SECTOR_SIZE;
 
 /// size of header for each chunk in the region file. This header proceeds the
 /// compressed chunk data.
-pub(crate) const CHUNK_HEADER_SIZE: usize = 5;
+pub const CHUNK_HEADER_SIZE: usize = 5;
+
+/// The most sectors a chunk can take up in a region, as its sector count is
+/// stored in a single byte.
+pub const MAX_CHUNK_SECTORS: usize = 255;
+
+/// The longest compressed chunk that fits in a region, ie that takes up
+/// [`MAX_CHUNK_SECTORS`] including its chunk header. Just under 1 MiB.
+pub const MAX_COMPRESSED_CHUNK_LEN: usize = MAX_CHUNK_SECTORS * SECTOR_SIZE - CHUNK_HEADER_SIZE;
+
+/// The most bytes a chunk can decompress to before reading it fails, unless
+/// changed with [`Region::with_max_uncompressed_len`]. Vanilla chunks are
+/// rarely more than a few MiB, so this leaves room for heavily modded ones
+/// while stopping a small chunk that claims to be gigabytes from using up
+/// memory.
+pub const DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN: usize = 32 * 1024 * 1024;
 
 /// A Minecraft Region.
 pub struct Region<S> {
     stream: S,
     // last offset is always the next valid place to write a chunk.
     offsets: Vec<u64>,
+    write_mode: WriteMode,
+    compression: CompressionPolicy,
+    max_uncompressed_len: usize,
+}
+
+/// How a [`Region`] places chunks when writing them. See
+/// [`Region::with_write_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteMode {
+    /// Write a chunk over its old data if it fits in the space up to the next
+    /// chunk, otherwise at the end of the region. Only the bytes the chunk
+    /// needs are written, and its sector count is updated to match.
+    #[default]
+    Reuse,
+
+    /// Leave everything but the chunks that actually change bit-for-bit
+    /// identical, so rewriting an unmodified region gives the same bytes.
+    ///
+    /// Writing a chunk with the same compression and compressed data it
+    /// already has does nothing. A changed chunk is written over its old data
+    /// if it fits in the sectors it already has, keeping its sector count,
+    /// otherwise it is moved to the end of the region, as are new chunks.
+    /// Either way the rest of its sectors are zeroed, as Minecraft does.
+    /// Sectors a moved chunk leaves behind are not touched.
+    Preserve,
 }
 
 impl<S> Region<S>
@@ -42,75 +92,254 @@ where
         let mut tmp = Self {
             stream,
             offsets: vec![],
+            write_mode: WriteMode::default(),
+            compression: CompressionPolicy::default(),
+            max_uncompressed_len: DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN,
         };
 
+        tmp.load_offsets()?;
+        Ok(tmp)
+    }
+
+    /// Rebuild the sorted offsets of the chunks in the region from the header.
+    fn load_offsets(&mut self) -> Result<()> {
+        self.offsets.clear();
+
         let mut max_offset = 0;
         let mut max_offsets_sector_count = 0;
 
-        for z in 0..32 {
-            for x in 0..32 {
-                let loc = tmp.location(x, z)?;
-                if loc.offset == 0 && loc.sectors == 0 {
-                    continue;
-                }
-
-                tmp.offsets.push(loc.offset);
-                if loc.offset > max_offset {
-                    max_offset = loc.offset;
-                    max_offsets_sector_count = loc.sectors;
-                }
+        for (_, _, loc) in self.index()?.chunks() {
+            self.offsets.push(loc.offset);
+            if loc.offset > max_offset {
+                max_offset = loc.offset;
+                max_offsets_sector_count = loc.sectors;
             }
         }
 
-        tmp.offsets.sort_unstable();
+        self.offsets.sort_unstable();
 
         // we add an offset representing the end of sectors that are in use.
-        tmp.offsets.push(max_offset + max_offsets_sector_count);
-        Ok(tmp)
+        // With no chunks this is the end of the header, see Region::new.
+        let header_sectors = (REGION_HEADER_SIZE / SECTOR_SIZE) as u64;
+        self.offsets
+            .push((max_offset + max_offsets_sector_count).max(header_sectors));
+        Ok(())
     }
 
     /// Read the chunk located at the chunk coordindates x, z. These should
     /// both be 0..32. The chunk data returned is uncompressed NBT.
     pub fn read_chunk(&mut self, x: usize, z: usize) -> Result<Option<Vec<u8>>> {
-        self.compression_scheme(x, z)?
-            .map(|scheme| match scheme {
-                CompressionScheme::Zlib => {
-                    let mut decoder = flate2::write::ZlibDecoder::new(vec![]);
-                    self.read_compressed_chunk(x, z, &mut decoder)?;
-                    Ok(decoder.finish()?)
-                }
-                CompressionScheme::Gzip => {
-                    let mut decoder = flate2::write::GzDecoder::new(vec![]);
-                    self.read_compressed_chunk(x, z, &mut decoder)?;
-                    Ok(decoder.finish()?)
-                }
-                CompressionScheme::Uncompressed => {
-                    let mut buf = vec![];
-                    self.read_compressed_chunk(x, z, &mut buf)?;
-                    Ok(buf)
+        let mut buf = vec![];
+        Ok(self.read_chunk_into(x, z, &mut buf)?.then_some(buf))
+    }
+
+    /// Read the uncompressed NBT of the chunk located at the chunk coordinates
+    /// x, z into `buf`, replacing its contents. The coordinates should both be
+    /// 0..32. Reusing the same buffer when reading many chunks avoids an
+    /// allocation for each chunk.
+    ///
+    /// Returns a bool indicating if a chunk was found at the given x,z. If
+    /// there was no chunk, `buf` is left empty.
+    pub fn read_chunk_into(&mut self, x: usize, z: usize, buf: &mut Vec<u8>) -> Result<bool> {
+        buf.clear();
+
+        let metadata = match self.read_chunk_header(x, z)? {
+            Some(metadata) => metadata,
+            None => return Ok(false),
+        };
+
+        let len = metadata.len_within(x, z, self.max_uncompressed_len)?;
+        match metadata.compression_scheme {
+            CompressionScheme::Uncompressed => {
+                buf.reserve(len);
+                self.read_compressed_chunk(x, z, buf)?;
+            }
+            scheme => {
+                // Read the whole chunk before decompressing it, so that errors
+                // reading the stream and corrupt data can be told apart.
+                let mut compressed = Vec::with_capacity(len);
+                self.read_compressed_chunk(x, z, &mut compressed)?;
+                self.decode(x, z, scheme, &compressed, buf)?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Call `f` with the coordinates and uncompressed NBT of every chunk in the
+    /// region, in order of z then x, reusing one buffer for every chunk. Used
+    /// to scan whole regions for a few fields of each chunk, which is quick
+    /// since serde skips every field not asked for.
+    ///
+    /// A chunk that cannot be read, eg because it is corrupt, is given to `f`
+    /// as its error rather than ending the scan, so `f` decides whether to
+    /// skip it. The scan stops at the first error `f` returns.
+    pub(crate) fn scan_chunks<E>(
+        &mut self,
+        mut f: impl FnMut(usize, usize, Result<&[u8]>) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        let mut buf = vec![];
+        for z in 0..CHUNKS_PER_REGION_SIDE {
+            for x in 0..CHUNKS_PER_REGION_SIDE {
+                match self.read_chunk_into(x, z, &mut buf) {
+                    Ok(true) => f(x, z, Ok(&buf))?,
+                    Ok(false) => {}
+                    Err(e) => f(x, z, Err(e))?,
                 }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the chunk located at the chunk coordinates x, z without
+    /// decompressing it. The coordinates should both be 0..32. The chunk data
+    /// is returned exactly as stored in the region, along with the compression
+    /// scheme it was stored with.
+    ///
+    /// Along with [`write_raw_chunk`][`Region::write_raw_chunk`] this allows
+    /// moving chunks between regions without a decompress/recompress cycle.
+    pub fn read_raw_chunk(
+        &mut self,
+        x: usize,
+        z: usize,
+    ) -> Result<Option<(CompressionScheme, Vec<u8>)>> {
+        self.read_chunk_header(x, z)?
+            .map(|metadata| {
+                let mut buf = Vec::with_capacity(metadata.compressed_len as usize);
+                self.read_compressed_chunk(x, z, &mut buf)?;
+                Ok((metadata.compression_scheme, buf))
             })
             .transpose()
     }
 
+    /// Copy the chunk at the chunk coordinates x, z into the same coordinates
+    /// of another region, replacing any chunk already there. The chunk is
+    /// copied in its compressed form.
+    ///
+    /// Returns a bool indicating if a chunk was found at the given x,z. If
+    /// there was no chunk, the other region is left unchanged.
+    pub fn copy_chunk_to<T>(&mut self, other: &mut Region<T>, x: usize, z: usize) -> Result<bool>
+    where
+        T: Read + Write + Seek,
+    {
+        match self.read_raw_chunk(x, z)? {
+            Some((scheme, data)) => {
+                other.write_raw_chunk(x, z, scheme, &data)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Set how chunks are placed when writing them. The default is
+    /// [`WriteMode::Reuse`].
+    pub fn with_write_mode(mut self, mode: WriteMode) -> Self {
+        self.write_mode = mode;
+        self
+    }
+
+    /// Set how [`write_chunk`][Self::write_chunk] compresses chunks. The
+    /// default is [`CompressionPolicy::default`].
+    pub fn with_compression(mut self, policy: CompressionPolicy) -> Self {
+        self.compression = policy;
+        self
+    }
+
+    /// Set the most bytes a chunk can be once decompressed. Reading a bigger
+    /// chunk fails with [`Error::UncompressedTooLarge`], without allocating
+    /// much more than the limit. The default is
+    /// [`DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN`].
+    pub fn with_max_uncompressed_len(mut self, limit: usize) -> Self {
+        self.max_uncompressed_len = limit;
+        self
+    }
+
+    /// Decompress the chunk at x, z into `buf`, up to the limit.
+    fn decode(
+        &self,
+        x: usize,
+        z: usize,
+        scheme: CompressionScheme,
+        payload: &[u8],
+        buf: &mut Vec<u8>,
+    ) -> Result<()> {
+        let limit = self.max_uncompressed_len;
+        if !decode_chunk_limited(scheme, payload, buf, limit)? {
+            buf.clear();
+            return Err(Error::UncompressedTooLarge { x, z, limit });
+        }
+        Ok(())
+    }
+
     /// Get the location of the chunk in the stream.
     pub(crate) fn location(&mut self, x: usize, z: usize) -> io::Result<ChunkLocation> {
-        self.stream.seek(SeekFrom::Start(header_pos(x, z)))?;
+        self.stream
+            .seek(SeekFrom::Start(header_offset_for(x, z) as u64))?;
 
         let mut buf = [0u8; 4];
         self.stream.read_exact(&mut buf[..])?;
 
-        let mut offset = 0u64;
-        offset |= (buf[0] as u64) << 16;
-        offset |= (buf[1] as u64) << 8;
-        offset |= buf[2] as u64;
-        let sectors = buf[3] as u64;
+        Ok(ChunkLocation::from_bytes(&buf))
+    }
+
+    /// Read and parse the header of the region, which gives the location and
+    /// timestamp of every chunk. See [`RegionIndex`].
+    pub fn index(&mut self) -> Result<RegionIndex> {
+        self.stream.rewind()?;
 
-        Ok(ChunkLocation { offset, sectors })
+        let mut header = [0u8; REGION_HEADER_SIZE];
+        self.stream.read_exact(&mut header)?;
+
+        Ok(RegionIndex::parse(&header))
+    }
+
+    /// The chunks present in the region, as x, z. Only the header of the
+    /// region is read, so this is quick even for a full region. Chunks
+    /// increase in x, then z.
+    pub fn present_chunks(&mut self) -> Result<impl Iterator<Item = (usize, usize)>> {
+        Ok(self.chunk_bitmap()?.iter())
+    }
+
+    /// The number of chunks present in the region. See
+    /// [`present_chunks`][Self::present_chunks].
+    pub fn chunk_count(&mut self) -> Result<usize> {
+        Ok(self.chunk_bitmap()?.len())
+    }
+
+    /// The set of chunks present in the region. See
+    /// [`present_chunks`][Self::present_chunks].
+    pub fn chunk_bitmap(&mut self) -> Result<ChunkBitmap> {
+        Ok(self.index()?.chunk_bitmap())
+    }
+
+    /// Get the timestamp of the last time the chunk at chunk coordinates x, z
+    /// was saved, in seconds since the Unix epoch. The coordinates should both
+    /// be 0..32. Absent chunks have a timestamp of zero.
+    pub fn timestamp(&mut self, x: usize, z: usize) -> Result<u32> {
+        if x >= CHUNKS_PER_REGION_SIDE || z >= CHUNKS_PER_REGION_SIDE {
+            return Err(Error::InvalidOffset(x as isize, z as isize));
+        }
+
+        self.stream.seek(SeekFrom::Start(timestamp_pos(x, z)))?;
+        Ok(self.stream.read_u32::<BigEndian>()?)
+    }
+
+    /// Get the time the chunk at chunk coordinates x, z was last saved, see
+    /// [`timestamp`][Self::timestamp]. This can be any [`EpochTime`], eg
+    /// [`SystemTime`][std::time::SystemTime], or `time::OffsetDateTime` with
+    /// the `time` feature enabled.
+    ///
+    /// Returns `None` for chunks without a timestamp, or if the timestamp
+    /// cannot be represented by `T`.
+    pub fn last_saved<T: EpochTime>(&mut self, x: usize, z: usize) -> Result<Option<T>> {
+        match self.timestamp(x, z)? {
+            0 => Ok(None),
+            secs => Ok(T::from_epoch_millis(secs as i64 * 1000)),
+        }
     }
 
     /// Low level method. Read a compressed chunk into the given writer. The
-    /// `compression_scheme` method can be used to discover how the chunk
+    /// `read_chunk_header` method can be used to discover how the chunk
     /// written is compressed, allowing you to write directly to a decompresser.
     ///
     /// Returns a bool indicating if a chunk was found at the given x,z.
@@ -120,7 +349,7 @@ where
         z: usize,
         writer: &mut dyn Write,
     ) -> Result<bool> {
-        if x >= 32 || z >= 32 {
+        if x >= CHUNKS_PER_REGION_SIDE || z >= CHUNKS_PER_REGION_SIDE {
             return Err(Error::InvalidOffset(x as isize, z as isize));
         }
 
@@ -134,7 +363,7 @@ where
 
             let mut buf = [0u8; 5];
             self.stream.read_exact(&mut buf)?;
-            let metadata = ChunkMeta::new(&buf)?;
+            let metadata = ChunkHeader::new_at(&buf, &loc)?;
 
             let mut adapted = (&mut self.stream).take(metadata.compressed_len as u64);
 
@@ -150,11 +379,11 @@ where
         Ok(self.stream)
     }
 
-    /// Low level method. Get the compression scheme that a given chunk is
-    /// compressed with in the region. Used in conjuction with
+    /// Low level method. Get the compression scheme and compressed length of a
+    /// given chunk in the region. Used in conjuction with
     /// `read_compressed_chunk`.
-    fn compression_scheme(&mut self, x: usize, z: usize) -> Result<Option<CompressionScheme>> {
-        if x >= 32 || z >= 32 {
+    fn read_chunk_header(&mut self, x: usize, z: usize) -> Result<Option<ChunkHeader>> {
+        if x >= CHUNKS_PER_REGION_SIDE || z >= CHUNKS_PER_REGION_SIDE {
             return Err(Error::InvalidOffset(x as isize, z as isize));
         }
 
@@ -168,17 +397,342 @@ where
 
             let mut buf = [0u8; 5];
             self.stream.read_exact(&mut buf)?;
-            let metadata = ChunkMeta::new(&buf)?;
 
-            Ok(Some(metadata.compression_scheme))
+            Ok(Some(ChunkHeader::new_at(&buf, &loc)?))
         }
     }
 
+    /// Get how the chunk at chunk coordinates x, z is stored: its compressed
+    /// length, sector count, compression scheme and timestamp. The
+    /// coordinates should both be 0..32. Only the headers are read, the chunk
+    /// is not decompressed.
+    pub fn chunk_meta(&mut self, x: usize, z: usize) -> Result<Option<ChunkMeta>> {
+        if x >= CHUNKS_PER_REGION_SIDE || z >= CHUNKS_PER_REGION_SIDE {
+            return Err(Error::InvalidOffset(x as isize, z as isize));
+        }
+
+        let loc = self.location(x, z)?;
+        if loc.offset == 0 && loc.sectors == 0 {
+            return Ok(None);
+        }
+
+        let timestamp = self.timestamp(x, z)?;
+        self.meta_at(loc, timestamp).map(Some)
+    }
+
+    /// Get the [`ChunkMeta`] of every chunk present in the region, along with
+    /// its chunk coordinates. Only the headers are read, so this is a cheap
+    /// way to find what is taking up space in a region.
+    ///
+    /// ```no_run
+    /// # use fastanvil::Region;
+    /// # let file = std::fs::File::open("r.0.0.mca").unwrap();
+    /// let mut region = Region::from_stream(file).unwrap();
+    /// let mut total = 0;
+    /// for meta in region.chunk_metas().unwrap() {
+    ///     let (x, z, meta) = meta.unwrap();
+    ///     println!("{x}, {z}: {} bytes", meta.compressed_len);
+    ///     total += meta.compressed_len as u64;
+    /// }
+    /// println!("{total} bytes in total");
+    /// ```
+    pub fn chunk_metas(
+        &mut self,
+    ) -> Result<impl Iterator<Item = Result<(usize, usize, ChunkMeta)>> + '_> {
+        let index = self.index()?;
+        let chunks = index
+            .chunks()
+            .map(|(x, z, loc)| Ok((x, z, loc, index.timestamp(x, z)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(chunks
+            .into_iter()
+            .map(move |(x, z, loc, timestamp)| Ok((x, z, self.meta_at(loc, timestamp)?))))
+    }
+
+    /// Read the header of the chunk at `loc` to make its [`ChunkMeta`].
+    fn meta_at(&mut self, loc: ChunkLocation, timestamp: u32) -> Result<ChunkMeta> {
+        self.stream
+            .seek(SeekFrom::Start(loc.offset * SECTOR_SIZE as u64))?;
+
+        let mut buf = [0u8; CHUNK_HEADER_SIZE];
+        self.stream.read_exact(&mut buf)?;
+        let header = ChunkHeader::new(&buf)?;
+
+        Ok(ChunkMeta {
+            compressed_len: header.compressed_len,
+            sectors: loc.sectors,
+            compression: header.compression_scheme,
+            timestamp,
+        })
+    }
+
     pub fn iter(&mut self) -> RegionIter<'_, S> {
-        RegionIter::new(self)
+        RegionIter::new(self, ScanCursor::default())
+    }
+
+    /// Iterate over the chunks a previous iteration had not got to yet, as
+    /// recorded by [`RegionIter::cursor`]. Chunks already yielded are skipped.
+    ///
+    /// Fails with [`Error::StaleCursor`] if the region's header has changed
+    /// since the chunks were yielded, eg because a chunk has been saved since.
+    pub fn iter_from(&mut self, cursor: &ScanCursor) -> Result<RegionIter<'_, S>> {
+        self.check_cursor(cursor)?;
+        Ok(RegionIter::new(self, *cursor))
     }
 
-    fn chunk_meta(&self, compressed_chunk_size: u32, scheme: CompressionScheme) -> [u8; 5] {
+    /// Check that the region is the one the cursor was taken from.
+    pub(crate) fn check_cursor(&mut self, cursor: &ScanCursor) -> Result<()> {
+        if cursor.next > CHUNKS_PER_REGION {
+            return Err(Error::StaleCursor);
+        }
+        match cursor.header {
+            Some(hash) if hash != self.header_hash()? => Err(Error::StaleCursor),
+            _ => Ok(()),
+        }
+    }
+
+    /// Read the next chunk after the cursor, moving the cursor past it.
+    pub(crate) fn next_chunk(&mut self, cursor: &mut ScanCursor) -> Option<Result<ChunkData>> {
+        // The header is hashed before the first chunk is read, so the cursor
+        // matches the region its chunks came from.
+        if cursor.header.is_none() {
+            match self.header_hash() {
+                Ok(hash) => cursor.header = Some(hash),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        while cursor.next < CHUNKS_PER_REGION {
+            let index = cursor.next;
+            cursor.next += 1;
+
+            let x = index % CHUNKS_PER_REGION_SIDE;
+            let z = index / CHUNKS_PER_REGION_SIDE;
+            match self.read_chunk(x, z) {
+                Ok(Some(data)) => return Some(Ok(ChunkData { x, z, data })),
+                Ok(None) => {} // chunk absent, fine.
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        None
+    }
+
+    /// A hash of the locations and timestamps of the chunks. Saving a chunk
+    /// changes its timestamp, and usually its location, so changes the hash.
+    fn header_hash(&mut self) -> Result<u64> {
+        self.stream.rewind()?;
+
+        let mut header = [0u8; REGION_HEADER_SIZE];
+        self.stream.read_exact(&mut header)?;
+
+        // FNV-1a, as the std hashers may change between releases and cursors
+        // can be kept between runs.
+        Ok(header.iter().fold(0xcbf29ce484222325u64, |hash, &b| {
+            (hash ^ b as u64).wrapping_mul(0x100000001b3)
+        }))
+    }
+
+    /// Check the region for inconsistencies, such as those left behind by a
+    /// crash while the region was being written. Every chunk present is read
+    /// and decompressed, so this reads the whole region.
+    ///
+    /// An empty list means no problems were found. See [`Region::repair`] to
+    /// fix the problems found.
+    pub fn validate(&mut self) -> Result<Vec<RegionProblem>> {
+        Ok(self.check()?.0)
+    }
+
+    /// Copy every chunk that can still be read into a new region, along with
+    /// its timestamp. Chunks are packed one after another, so the new region
+    /// has none of the problems reported by [`validate`][Self::validate].
+    /// Chunks that cannot be read are left out.
+    pub fn repack_into<T>(&mut self, stream: T) -> Result<Region<T>>
+    where
+        T: Read + Write + Seek,
+    {
+        let (_, chunks) = self.salvage()?;
+        let mut region = Region::new(stream)?;
+        region.write_salvaged(chunks)?;
+        Ok(region)
+    }
+
+    /// Validate the region, also returning the coordinates of the chunks that
+    /// could be read and decompressed despite any problems.
+    #[allow(clippy::type_complexity)]
+    fn check(&mut self) -> Result<(Vec<RegionProblem>, Vec<(usize, usize)>)> {
+        let end = self.stream.seek(SeekFrom::End(0))?;
+        let mut problems = vec![];
+        let mut readable = vec![];
+        // sector ranges claimed by each chunk, as (start, end, x, z).
+        let mut claims = vec![];
+
+        for z in 0..CHUNKS_PER_REGION_SIDE {
+            for x in 0..CHUNKS_PER_REGION_SIDE {
+                let loc = self.location(x, z)?;
+                if loc.offset == 0 && loc.sectors == 0 {
+                    continue;
+                }
+
+                let start = loc.offset * SECTOR_SIZE as u64;
+                let header_sectors = (REGION_HEADER_SIZE / SECTOR_SIZE) as u64;
+
+                if loc.offset < header_sectors
+                    || loc.sectors == 0
+                    || start + CHUNK_HEADER_SIZE as u64 > end
+                {
+                    problems.push(RegionProblem::OutOfBounds {
+                        x,
+                        z,
+                        offset: loc.offset,
+                        sectors: loc.sectors,
+                    });
+                    continue;
+                }
+
+                claims.push((loc.offset, loc.offset + loc.sectors, x, z));
+
+                self.stream.seek(SeekFrom::Start(start))?;
+                let length = self.stream.read_u32::<BigEndian>()? as u64;
+                let scheme = self.stream.read_u8()?;
+
+                let allocated = loc.sectors * SECTOR_SIZE as u64;
+                if length + 4 > allocated {
+                    problems.push(RegionProblem::LengthExceedsAllocation {
+                        x,
+                        z,
+                        length,
+                        allocated,
+                    });
+                }
+
+                let scheme = match CompressionScheme::try_from(scheme) {
+                    Ok(scheme) => scheme,
+                    Err(_) => {
+                        problems.push(RegionProblem::UnknownCompression { x, z, scheme });
+                        continue;
+                    }
+                };
+
+                let remaining = end - start - CHUNK_HEADER_SIZE as u64;
+                match self.decompress_current(length, remaining, scheme)? {
+                    None => readable.push((x, z)),
+                    Some(reason) => problems.push(RegionProblem::Undecompressible { x, z, reason }),
+                }
+            }
+        }
+
+        claims.sort_unstable();
+
+        // the claim reaching furthest so far, as (end, x, z).
+        let mut furthest: Option<(u64, usize, usize)> = None;
+        for (start, end, x, z) in claims {
+            if let Some((furthest_end, other_x, other_z)) = furthest {
+                if start < furthest_end {
+                    problems.push(RegionProblem::Overlap {
+                        x,
+                        z,
+                        other_x,
+                        other_z,
+                    });
+                }
+                if end <= furthest_end {
+                    continue;
+                }
+            }
+            furthest = Some((end, x, z));
+        }
+
+        Ok((problems, readable))
+    }
+
+    /// Decompress the chunk data at the current position of the stream,
+    /// discarding the result. `length` is the length stored with the chunk,
+    /// and `remaining` the number of bytes left in the stream.
+    ///
+    /// Returns why the chunk could not be decompressed, if it could not be.
+    /// Errors are only returned for failures of the stream itself.
+    fn decompress_current(
+        &mut self,
+        length: u64,
+        remaining: u64,
+        scheme: CompressionScheme,
+    ) -> Result<Option<String>> {
+        // the length includes the compression scheme byte.
+        let compressed_len = match length.checked_sub(1) {
+            Some(len) => len,
+            None => return Ok(Some("chunk has a length of zero".to_string())),
+        };
+
+        if compressed_len > remaining {
+            return Ok(Some(format!(
+                "chunk is {} bytes but only {} remain in the region",
+                compressed_len, remaining
+            )));
+        }
+
+        let mut compressed = vec![0; compressed_len as usize];
+        self.stream.read_exact(&mut compressed)?;
+
+        let cap = (self.max_uncompressed_len as u64).saturating_add(1);
+        let res = match scheme {
+            CompressionScheme::Zlib => io::copy(
+                &mut flate2::read::ZlibDecoder::new(compressed.as_slice()).take(cap),
+                &mut io::sink(),
+            ),
+            CompressionScheme::Gzip => io::copy(
+                &mut flate2::read::GzDecoder::new(compressed.as_slice()).take(cap),
+                &mut io::sink(),
+            ),
+            CompressionScheme::Uncompressed => Ok(compressed_len),
+        };
+
+        Ok(match res {
+            Ok(len) if len > self.max_uncompressed_len as u64 => Some(format!(
+                "chunk is more than {} bytes uncompressed",
+                self.max_uncompressed_len
+            )),
+            Ok(_) => None,
+            Err(e) => Some(e.to_string()),
+        })
+    }
+
+    /// Read every chunk that can be decompressed in its raw form, along with
+    /// its timestamp.
+    fn salvage(&mut self) -> Result<(Vec<RegionProblem>, Vec<SalvagedChunk>)> {
+        let (problems, readable) = self.check()?;
+
+        let mut chunks = Vec::with_capacity(readable.len());
+        for (x, z) in readable {
+            // Not read_raw_chunk, which fails for chunks longer than their
+            // sectors. check() found the data of these within the file and
+            // decompressing within the limit, so the length can be trusted.
+            let loc = self.location(x, z)?;
+            self.stream
+                .seek(SeekFrom::Start(loc.offset * SECTOR_SIZE as u64))?;
+
+            let mut buf = [0u8; CHUNK_HEADER_SIZE];
+            self.stream.read_exact(&mut buf)?;
+            let header = ChunkHeader::new(&buf)?;
+
+            let mut data = vec![0; header.compressed_len as usize];
+            self.stream.read_exact(&mut data)?;
+
+            let timestamp = self.timestamp(x, z)?;
+            chunks.push(SalvagedChunk {
+                x,
+                z,
+                scheme: header.compression_scheme,
+                data,
+                timestamp,
+            });
+        }
+
+        Ok((problems, chunks))
+    }
+
+    fn chunk_header(&self, compressed_chunk_size: u32, scheme: CompressionScheme) -> [u8; 5] {
         let mut buf = [0u8; 5];
         let mut c = Cursor::new(buf.as_mut_slice());
 
@@ -196,6 +750,58 @@ where
     }
 }
 
+impl<T> Region<Cursor<T>>
+where
+    T: AsRef<[u8]>,
+{
+    /// Get the compressed data of the chunk at the chunk coordinates x, z as a
+    /// slice of the buffer backing the region, along with its compression
+    /// scheme. The coordinates should both be 0..32. This is the borrowed form
+    /// of [`read_raw_chunk`][Region::read_raw_chunk], nothing is copied.
+    pub fn raw_chunk_slice(
+        &self,
+        x: usize,
+        z: usize,
+    ) -> Result<Option<(CompressionScheme, &[u8])>> {
+        if x >= CHUNKS_PER_REGION_SIDE || z >= CHUNKS_PER_REGION_SIDE {
+            return Err(Error::InvalidOffset(x as isize, z as isize));
+        }
+
+        let data = self.stream.get_ref().as_ref();
+        let eof = || Error::IO(io::ErrorKind::UnexpectedEof.into());
+
+        let pos = header_offset_for(x, z);
+        let loc = ChunkLocation::from_bytes(data.get(pos..pos + 4).ok_or_else(eof)?);
+
+        if loc.is_absent() {
+            return Ok(None);
+        }
+
+        let start = loc.byte_range().start as usize;
+        chunk_payload(data.get(start..).ok_or_else(eof)?).map(Some)
+    }
+
+    /// Decompress the chunk at the chunk coordinates x, z into `buf`,
+    /// replacing its contents. This is the same as
+    /// [`read_chunk_into`][Region::read_chunk_into], but decompresses straight
+    /// from the buffer backing the region rather than copying the compressed
+    /// data out of it first. This suits regions in memory or memory mapped.
+    ///
+    /// Returns a bool indicating if a chunk was found at the given x,z. If
+    /// there was no chunk, `buf` is left empty.
+    pub fn decompress_chunk_into(&self, x: usize, z: usize, buf: &mut Vec<u8>) -> Result<bool> {
+        buf.clear();
+
+        let (scheme, compressed) = match self.raw_chunk_slice(x, z)? {
+            Some(chunk) => chunk,
+            None => return Ok(false),
+        };
+
+        self.decode(x, z, scheme, compressed, buf)?;
+        Ok(true)
+    }
+}
+
 impl<S> Region<S>
 where
     S: Read + Write + Seek,
@@ -210,18 +816,25 @@ where
         Ok(Self {
             stream,
             offsets: vec![2], // 2 is the end of the header
+            write_mode: WriteMode::default(),
+            compression: CompressionPolicy::default(),
+            max_uncompressed_len: DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN,
         })
     }
 
     /// Write the given uncompressed NBT chunk data to the chunk coordinates x,
     /// z. The coordinates should both be 0..32. The chunk data will be
-    /// compressed with zlib by default. You can use write_compressed_chunk if
-    /// you want more control.
-    pub fn write_chunk(&mut self, x: usize, z: usize, uncompressed_chunk: &[u8]) -> Result<()> {
-        let mut buf = vec![];
-        let mut enc = ZlibEncoder::new(uncompressed_chunk, Compression::fast());
-        enc.read_to_end(&mut buf)?;
-        self.write_compressed_chunk(x, z, CompressionScheme::Zlib, &buf)
+    /// compressed according to the region's compression policy, by default
+    /// with zlib. See [`with_compression`][Self::with_compression], or use
+    /// write_compressed_chunk if you want more control.
+    pub fn write_chunk(
+        &mut self,
+        x: usize,
+        z: usize,
+        uncompressed_chunk: &[u8],
+    ) -> Result<ChunkWrite> {
+        let (scheme, buf) = self.compression.compress(uncompressed_chunk)?;
+        self.write_compressed_chunk(x, z, scheme, &buf)
     }
 
     /// Low level method. Write the given compressed chunk data to the stream.
@@ -233,11 +846,34 @@ where
         z: usize,
         scheme: CompressionScheme,
         compressed_chunk: &[u8],
-    ) -> Result<()> {
+    ) -> Result<ChunkWrite> {
         let loc = self.location(x, z)?;
-        let required_sectors =
-            unstable_div_ceil(CHUNK_HEADER_SIZE + compressed_chunk.len(), SECTOR_SIZE);
+        let required_sectors = sector_count_for_len(compressed_chunk.len());
 
+        let sectors = if self.write_mode == WriteMode::Preserve {
+            self.write_preserving(x, z, loc, scheme, compressed_chunk, required_sectors)?
+        } else {
+            self.write_reusing(x, z, loc, scheme, compressed_chunk, required_sectors)?;
+            required_sectors
+        };
+
+        Ok(ChunkWrite {
+            scheme,
+            compressed_len: compressed_chunk.len(),
+            sectors,
+        })
+    }
+
+    /// Write a chunk for [`WriteMode::Reuse`].
+    fn write_reusing(
+        &mut self,
+        x: usize,
+        z: usize,
+        loc: ChunkLocation,
+        scheme: CompressionScheme,
+        compressed_chunk: &[u8],
+        required_sectors: usize,
+    ) -> Result<()> {
         if loc.offset == 0 && loc.sectors == 0 {
             // chunk does not exist in the region yet.
             let offset = *self.offsets.last().expect("offset should always exist");
@@ -273,12 +909,131 @@ where
         Ok(())
     }
 
+    /// Write a chunk for [`WriteMode::Preserve`], returning the number of
+    /// sectors it has.
+    fn write_preserving(
+        &mut self,
+        x: usize,
+        z: usize,
+        loc: ChunkLocation,
+        scheme: CompressionScheme,
+        compressed_chunk: &[u8],
+        required_sectors: usize,
+    ) -> Result<usize> {
+        // A broken chunk can't be the same, so just gets replaced.
+        if !loc.is_absent() {
+            if let Ok(Some((old_scheme, old))) = self.read_raw_chunk(x, z) {
+                if old_scheme == scheme && old == compressed_chunk {
+                    return Ok(loc.sectors as usize);
+                }
+            }
+        }
+
+        // New chunks have no sectors, so always go at the end.
+        if required_sectors <= loc.sectors as usize {
+            self.set_chunk(loc.offset, scheme, compressed_chunk)?;
+            self.pad_sectors(compressed_chunk.len(), loc.sectors as usize)?;
+            Ok(loc.sectors as usize)
+        } else {
+            if !loc.is_absent() {
+                if let Ok(i) = self.offsets.binary_search(&loc.offset) {
+                    self.offsets.remove(i);
+                }
+            }
+            let offset = *self.offsets.last().expect("offset should always exist");
+
+            self.offsets.push(offset + required_sectors as u64);
+            self.set_chunk(offset, scheme, compressed_chunk)?;
+            self.pad_sectors(compressed_chunk.len(), required_sectors)?;
+            self.set_header(x, z, offset, required_sectors)?;
+            Ok(required_sectors)
+        }
+    }
+
+    /// Zero the rest of the sectors of a chunk just written by `set_chunk`.
+    fn pad_sectors(&mut self, compressed_len: usize, sectors: usize) -> Result<()> {
+        let padding = sectors * SECTOR_SIZE - CHUNK_HEADER_SIZE - compressed_len;
+        io::copy(&mut io::repeat(0).take(padding as u64), &mut self.stream)?;
+        Ok(())
+    }
+
+    /// Fix the problems found by [`validate`][Self::validate] according to
+    /// the given policy, returning the problems that were found. After
+    /// repairing, the region can be read and written as normal.
+    ///
+    /// With [`RepairPolicy::Repack`] the stream is rewritten from the start
+    /// but not truncated, so it may be left with unused data at its end. Use
+    /// [`repack_into`][Self::repack_into] to write to a fresh stream instead.
+    pub fn repair(&mut self, policy: RepairPolicy) -> Result<Vec<RegionProblem>> {
+        match policy {
+            RepairPolicy::ZeroBroken => {
+                let problems = self.validate()?;
+
+                let mut broken: Vec<_> = problems.iter().flat_map(|p| p.chunks()).collect();
+                broken.sort_unstable();
+                broken.dedup();
+
+                for (x, z) in broken {
+                    self.set_header(x, z, 0, 0)?;
+                    self.set_timestamp(x, z, 0)?;
+                }
+
+                self.load_offsets()?;
+                Ok(problems)
+            }
+            RepairPolicy::Repack => {
+                let (problems, chunks) = self.salvage()?;
+
+                self.stream.rewind()?;
+                self.stream.write_all(&[0; REGION_HEADER_SIZE])?;
+                self.offsets = vec![2];
+
+                self.write_salvaged(chunks)?;
+                Ok(problems)
+            }
+        }
+    }
+
+    fn write_salvaged(&mut self, chunks: Vec<SalvagedChunk>) -> Result<()> {
+        for chunk in chunks {
+            self.write_raw_chunk(chunk.x, chunk.z, chunk.scheme, &chunk.data)?;
+            self.set_timestamp(chunk.x, chunk.z, chunk.timestamp)?;
+        }
+        Ok(())
+    }
+
+    /// Write chunk data in the form returned by
+    /// [`read_raw_chunk`][`Region::read_raw_chunk`], ie already compressed with
+    /// the given scheme. This is the same as
+    /// [`write_compressed_chunk`][`Region::write_compressed_chunk`].
+    pub fn write_raw_chunk(
+        &mut self,
+        x: usize,
+        z: usize,
+        scheme: CompressionScheme,
+        compressed_chunk: &[u8],
+    ) -> Result<ChunkWrite> {
+        self.write_compressed_chunk(x, z, scheme, compressed_chunk)
+    }
+
+    /// Set the timestamp of the chunk at chunk coordinates x, z, in seconds
+    /// since the Unix epoch. Writing a chunk does not change its timestamp.
+    pub fn set_timestamp(&mut self, x: usize, z: usize, timestamp: u32) -> Result<()> {
+        if x >= CHUNKS_PER_REGION_SIDE || z >= CHUNKS_PER_REGION_SIDE {
+            return Err(Error::InvalidOffset(x as isize, z as isize));
+        }
+
+        self.stream.seek(SeekFrom::Start(timestamp_pos(x, z)))?;
+        self.stream.write_u32::<BigEndian>(timestamp)?;
+        Ok(())
+    }
+
     /// Write the chunk data to the given offset, does no checking.
     fn set_chunk(&mut self, offset: u64, scheme: CompressionScheme, chunk: &[u8]) -> Result<()> {
         self.stream
             .seek(SeekFrom::Start(offset * SECTOR_SIZE as u64))?;
 
-        self.stream.write_all(&self.chunk_meta(
+        self.stream.write_all(&self.chunk_header(
             chunk.len() as u32, // doesn't include header size
             scheme,
         ))?;
@@ -295,7 +1050,7 @@ where
         offset: u64,
         new_sector_count: usize,
     ) -> Result<()> {
-        if new_sector_count > 255 {
+        if new_sector_count > MAX_CHUNK_SECTORS {
             return Err(Error::ChunkTooLarge);
         }
 
@@ -306,14 +1061,15 @@ where
         buf[3] = new_sector_count as u8;
 
         // seek to header
-        self.stream.seek(SeekFrom::Start(header_pos(x, z)))?;
+        self.stream
+            .seek(SeekFrom::Start(header_offset_for(x, z) as u64))?;
         self.stream.write_all(&buf)?;
         Ok(())
     }
 }
 
 /// Various compression schemes that NBT data is typically compressed with.
-#[derive(Debug, TryFromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
 #[repr(u8)]
 pub enum CompressionScheme {
     Gzip = 1,
@@ -321,35 +1077,158 @@ pub enum CompressionScheme {
     Uncompressed = 3,
 }
 
+/// A problem with the chunks of a region, see [`Region::validate`]. Chunk
+/// coordinates are within the region, ie 0..32.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegionProblem {
+    /// The chunk's sectors start inside the region header, past the end of
+    /// the region, or it has no sectors at all.
+    OutOfBounds {
+        x: usize,
+        z: usize,
+        offset: u64,
+        sectors: u64,
+    },
+    /// The chunk's sectors overlap those claimed by another chunk, so writing
+    /// to one of them would corrupt the other.
+    Overlap {
+        x: usize,
+        z: usize,
+        other_x: usize,
+        other_z: usize,
+    },
+    /// The length stored at the start of the chunk data is more than the
+    /// sectors allocated to the chunk can hold. Both are in bytes.
+    LengthExceedsAllocation {
+        x: usize,
+        z: usize,
+        length: u64,
+        allocated: u64,
+    },
+    /// The chunk is stored with an unrecognised compression scheme.
+    UnknownCompression { x: usize, z: usize, scheme: u8 },
+    /// The chunk data could not be read in full or decompressed.
+    Undecompressible { x: usize, z: usize, reason: String },
+}
+
+impl RegionProblem {
+    /// The coordinates of the chunk with the problem.
+    pub fn chunk(&self) -> (usize, usize) {
+        match *self {
+            RegionProblem::OutOfBounds { x, z, .. }
+            | RegionProblem::Overlap { x, z, .. }
+            | RegionProblem::LengthExceedsAllocation { x, z, .. }
+            | RegionProblem::UnknownCompression { x, z, .. }
+            | RegionProblem::Undecompressible { x, z, .. } => (x, z),
+        }
+    }
+
+    /// Every chunk involved in the problem. This is only more than one chunk
+    /// for overlaps.
+    fn chunks(&self) -> impl Iterator<Item = (usize, usize)> {
+        let other = match *self {
+            RegionProblem::Overlap {
+                other_x, other_z, ..
+            } => Some((other_x, other_z)),
+            _ => None,
+        };
+        std::iter::once(self.chunk()).chain(other)
+    }
+}
+
+impl std::fmt::Display for RegionProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (x, z) = self.chunk();
+        write!(f, "chunk ({x}, {z}) ")?;
+
+        match self {
+            RegionProblem::OutOfBounds {
+                offset, sectors, ..
+            } => write!(
+                f,
+                "has sectors {offset}..{} outside of the region",
+                offset + sectors
+            ),
+            RegionProblem::Overlap {
+                other_x, other_z, ..
+            } => write!(f, "overlaps chunk ({other_x}, {other_z})"),
+            RegionProblem::LengthExceedsAllocation {
+                length, allocated, ..
+            } => write!(
+                f,
+                "has a length of {length} bytes but only {allocated} bytes allocated"
+            ),
+            RegionProblem::UnknownCompression { scheme, .. } => {
+                write!(f, "has unknown compression scheme ({scheme})")
+            }
+            RegionProblem::Undecompressible { reason, .. } => {
+                write!(f, "could not be decompressed: {reason}")
+            }
+        }
+    }
+}
+
+/// How [`Region::repair`] deals with the problems in a region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairPolicy {
+    /// Remove every chunk with a problem from the region header, including
+    /// both chunks of an overlap. Nothing else in the region is changed.
+    ZeroBroken,
+    /// Rewrite the region with every chunk that can still be decompressed
+    /// packed one after another, removing the rest. This keeps chunks that
+    /// overlap or exceed their allocation if their data is intact.
+    Repack,
+}
+
+/// A chunk read in its raw form during repair.
+struct SalvagedChunk {
+    x: usize,
+    z: usize,
+    scheme: CompressionScheme,
+    data: Vec<u8>,
+    timestamp: u32,
+}
+
+/// Iterates over the chunks present in a region, in index order, ie along x
+/// then z. Created by [`Region::iter`] and [`Region::iter_from`].
 pub struct RegionIter<'a, S>
 where
     S: Read + Seek,
 {
     inner: &'a mut Region<S>,
-    index: usize,
+    cursor: ScanCursor,
 }
 
 impl<'a, S> RegionIter<'a, S>
 where
     S: Read + Seek,
 {
-    fn new(inner: &'a mut Region<S>) -> Self {
-        Self { inner, index: 0 }
+    fn new(inner: &'a mut Region<S>, cursor: ScanCursor) -> Self {
+        Self { inner, cursor }
     }
 
-    fn next_xz(&mut self) -> Option<(usize, usize)> {
-        let index = self.index;
-        self.index += 1;
-
-        if index == 32 * 32 {
-            return None;
-        }
-
-        let x = index % 32;
-        let z = index / 32;
-        Some((x, z))
+    /// Where the iteration has got to, to carry on from later with
+    /// [`Region::iter_from`], eg after a restart.
+    pub fn cursor(&self) -> ScanCursor {
+        self.cursor
     }
 }
+
+/// Where an iteration over the chunks of a region got to. Cursors can be
+/// serialized, eg to save progress through a long scan.
+///
+/// A cursor only stays valid while the region is unchanged. It records a hash
+/// of the region's header, which holds the location and timestamp of every
+/// chunk, and [`Region::iter_from`] fails if the hash no longer matches. The
+/// default cursor is the start of a region, and is valid for any region.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanCursor {
+    // Index of the next chunk to read.
+    next: usize,
+    // Hash of the header, set when the first chunk is read.
+    header: Option<u64>,
+}
+
 pub struct ChunkData {
     pub x: usize,
     pub z: usize,
@@ -363,17 +1242,132 @@ where
     type Item = Result<ChunkData>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some((x, z)) = self.next_xz() {
-            let c = self.inner.read_chunk(x, z);
+        self.inner.next_chunk(&mut self.cursor)
+    }
+}
 
-            match c {
-                Ok(Some(c)) => return Some(Ok(ChunkData { x, z, data: c })),
-                Ok(None) => {} // chunk absent, fine.
-                Err(e) => return Some(Err(e)),
+impl<S> Region<S>
+where
+    S: Read,
+{
+    /// Read a region from a stream that cannot seek, such as a pipe or network
+    /// connection. The header is read immediately, then chunks are read in
+    /// the order they appear in the stream via the returned iterator. There is
+    /// no random access to chunks in this mode.
+    pub fn from_read(reader: S) -> Result<RegionStream<S>> {
+        RegionStream::new(reader)
+    }
+}
+
+/// Iterator over the chunks of a region read from a stream that cannot seek.
+/// See [`Region::from_read`].
+///
+/// Chunks are returned in the order they are stored in the stream rather than
+/// by their coordinates. Iteration stops after the first error, since the
+/// position in the stream is then unknown.
+pub struct RegionStream<R> {
+    reader: R,
+    // chunks left to read, sorted by descending offset so we can pop the next.
+    remaining: Vec<(ChunkLocation, usize, usize)>,
+    // current position in the stream in bytes.
+    position: u64,
+    failed: bool,
+    max_uncompressed_len: usize,
+}
+
+impl<R: Read> RegionStream<R> {
+    fn new(mut reader: R) -> Result<Self> {
+        let mut header = [0; REGION_HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+
+        let mut remaining: Vec<_> = RegionIndex::parse(&header)
+            .chunks()
+            .map(|(x, z, loc)| (loc, x, z))
+            .collect();
+
+        remaining.sort_unstable_by_key(|(loc, _, _)| std::cmp::Reverse(loc.offset));
+
+        Ok(Self {
+            reader,
+            remaining,
+            position: REGION_HEADER_SIZE as u64,
+            failed: false,
+            max_uncompressed_len: DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN,
+        })
+    }
+
+    /// Set the most bytes a chunk can be once decompressed. See
+    /// [`Region::with_max_uncompressed_len`].
+    pub fn with_max_uncompressed_len(mut self, limit: usize) -> Self {
+        self.max_uncompressed_len = limit;
+        self
+    }
+
+    fn read_next(&mut self, loc: ChunkLocation, x: usize, z: usize) -> Result<Vec<u8>> {
+        let start = loc.offset * SECTOR_SIZE as u64;
+        if start < self.position {
+            return Err(Error::IO(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "chunk overlaps a previous chunk or the region header",
+            )));
+        }
+
+        // Skip to the start of the chunk.
+        let skip = start - self.position;
+        let skipped = io::copy(&mut (&mut self.reader).take(skip), &mut io::sink())?;
+        if skipped != skip {
+            return Err(Error::IO(io::ErrorKind::UnexpectedEof.into()));
+        }
+
+        let mut buf = [0u8; CHUNK_HEADER_SIZE];
+        self.reader.read_exact(&mut buf)?;
+        let metadata = ChunkHeader::new_at(&buf, &loc)?;
+        let limit = self.max_uncompressed_len;
+        let len = metadata.len_within(x, z, limit)?;
+
+        let mut compressed = Vec::with_capacity(len);
+        (&mut self.reader)
+            .take(len as u64)
+            .read_to_end(&mut compressed)?;
+        if compressed.len() != len {
+            return Err(Error::IO(io::ErrorKind::UnexpectedEof.into()));
+        }
+
+        self.position = start + (CHUNK_HEADER_SIZE + compressed.len()) as u64;
+
+        let data = match metadata.compression_scheme {
+            CompressionScheme::Uncompressed => compressed,
+            scheme => {
+                let mut buf = vec![];
+                decode_chunk_limited(scheme, &compressed, &mut buf, limit)?;
+                buf
             }
+        };
+
+        if data.len() > limit {
+            return Err(Error::UncompressedTooLarge { x, z, limit });
         }
+        Ok(data)
+    }
+}
 
-        None
+impl<R: Read> Iterator for RegionStream<R> {
+    type Item = Result<ChunkData>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let (loc, x, z) = self.remaining.pop()?;
+
+        match self.read_next(loc, x, z) {
+            Ok(data) => Some(Ok(ChunkData { x, z, data })),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
     }
 }
 
@@ -388,11 +1382,36 @@ pub const fn unstable_div_ceil(lhs: usize, rhs: usize) -> usize {
     }
 }
 
-fn header_pos(x: usize, z: usize) -> u64 {
-    (4 * ((x % 32) + (z % 32) * 32)) as u64
+/// The number of sectors a chunk takes up in a region, given the length of its
+/// compressed data. This includes the [`CHUNK_HEADER_SIZE`] bytes of chunk
+/// header that come before the data. Chunks needing more than
+/// [`MAX_CHUNK_SECTORS`] cannot be written.
+///
+/// ```
+/// # use fastanvil::{sector_count_for_len, CHUNK_HEADER_SIZE, SECTOR_SIZE};
+/// assert_eq!(sector_count_for_len(0), 1);
+/// assert_eq!(sector_count_for_len(SECTOR_SIZE - CHUNK_HEADER_SIZE), 1);
+/// assert_eq!(sector_count_for_len(SECTOR_SIZE - CHUNK_HEADER_SIZE + 1), 2);
+/// ```
+pub const fn sector_count_for_len(compressed_len: usize) -> usize {
+    unstable_div_ceil(CHUNK_HEADER_SIZE + compressed_len, SECTOR_SIZE)
 }
 
-#[derive(Debug)]
+/// The offset in the region header of the location of the chunk at chunk
+/// coordinates x, z. The chunk's timestamp is at the same offset in the
+/// second sector of the header. Coordinates are taken modulo
+/// [`CHUNKS_PER_REGION_SIDE`], so chunk coordinates in the world can be given
+/// as long as they are not negative.
+pub const fn header_offset_for(x: usize, z: usize) -> usize {
+    let side = CHUNKS_PER_REGION_SIDE;
+    4 * ((x % side) + (z % side) * side)
+}
+
+pub(crate) fn timestamp_pos(x: usize, z: usize) -> u64 {
+    (SECTOR_SIZE + header_offset_for(x, z)) as u64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ChunkLocation {
     /// The offset, in units of 4kiB sectors, into the region file this chunk is
     /// located at. Offset 0 is the start of the file.
@@ -402,23 +1421,75 @@ pub struct ChunkLocation {
     pub sectors: u64,
 }
 
+/// How a chunk is stored in a region, from the region header and the header
+/// before the chunk's data. See [`Region::chunk_meta`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkMeta {
+    /// The length of the compressed chunk data, not including its header.
+    pub compressed_len: u32,
+
+    /// The number of 4 kiB sectors the chunk takes up in the region. This can
+    /// be more than the compressed data needs, see [`sector_count_for_len`].
+    pub sectors: u64,
+
+    pub compression: CompressionScheme,
+
+    /// When the chunk was last saved, see [`Region::timestamp`].
+    pub timestamp: u32,
+}
+
 /// Encodes how the NBT-Data is compressed
 #[derive(Debug)]
-struct ChunkMeta {
+pub(crate) struct ChunkHeader {
     pub compressed_len: u32,
     pub compression_scheme: CompressionScheme,
 }
 
-impl ChunkMeta {
-    fn new(mut data: &[u8]) -> Result<Self> {
+impl ChunkHeader {
+    pub(crate) fn new(mut data: &[u8]) -> Result<Self> {
         let len = data.read_u32::<BigEndian>()?;
         let scheme = data.read_u8()?;
         let scheme =
             CompressionScheme::try_from(scheme).map_err(|_| Error::UnknownCompression(scheme))?;
 
+        // this len include the compression byte.
+        let compressed_len = len.checked_sub(1).ok_or_else(|| {
+            Error::IO(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "chunk has a length of zero",
+            ))
+        })?;
+
         Ok(Self {
-            compressed_len: len - 1, // this len include the compression byte.
+            compressed_len,
             compression_scheme: scheme,
         })
     }
+
+    /// Read the header of the chunk stored at `loc`. The length is checked to
+    /// fit in the sectors given to the chunk, so that it can be trusted when
+    /// allocating for the chunk's data.
+    pub(crate) fn new_at(data: &[u8], loc: &ChunkLocation) -> Result<Self> {
+        let header = Self::new(data)?;
+        let allocated = (loc.sectors * SECTOR_SIZE as u64).saturating_sub(CHUNK_HEADER_SIZE as u64);
+        if header.compressed_len as u64 > allocated {
+            return Err(Error::IO(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "chunk is {} bytes but only {} are allocated to it",
+                    header.compressed_len, allocated
+                ),
+            )));
+        }
+        Ok(header)
+    }
+
+    /// The compressed length, failing if it is more than `limit`. Compressed
+    /// data that large decompresses to more than the limit anyway.
+    pub(crate) fn len_within(&self, x: usize, z: usize, limit: usize) -> Result<usize> {
+        match self.compressed_len as usize {
+            len if len > limit => Err(Error::UncompressedTooLarge { x, z, limit }),
+            len => Ok(len),
+        }
+    }
 }
@@ -0,0 +1,360 @@
+//! The parts of reading a region that do not need any IO. [`Region`] is built
+//! on these, but they can also be used directly by code that does its own
+//! reads, eg async code.
+//!
+//! [`Region`]: crate::Region
+
+use std::convert::TryFrom;
+use std::io::{self, Read};
+
+use byteorder::{BigEndian, ByteOrder};
+use flate2::{Decompress, FlushDecompress, Status};
+
+use crate::{
+    header_offset_for,
+    region::{timestamp_pos, ChunkHeader},
+    ChunkLocation, CompressionScheme, Error, Result, CHUNKS_PER_REGION, CHUNKS_PER_REGION_SIDE,
+    CHUNK_HEADER_SIZE, REGION_HEADER_SIZE,
+};
+
+/// The header of a region, parsed without doing any IO. This says where each
+/// chunk is in the region, and when it was last saved.
+///
+/// This lets you read regions however you like, eg asynchronously: read the
+/// first [`REGION_HEADER_SIZE`] bytes of the region and parse them, read the
+/// [`byte_range`][ChunkLocation::byte_range] of a chunk, then decode it with
+/// [`chunk_payload`] and [`decode_chunk`].
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use fastanvil::{chunk_payload, decode_chunk, Region, RegionIndex};
+/// # let mut region = Region::new(Cursor::new(vec![])).unwrap();
+/// # region.write_chunk(1, 2, &[1, 2, 3]).unwrap();
+/// # let bytes = region.into_inner().unwrap().into_inner();
+/// let header = bytes[..fastanvil::REGION_HEADER_SIZE].try_into().unwrap();
+/// let index = RegionIndex::parse(header);
+///
+/// let location = index.location(1, 2).unwrap().unwrap();
+/// let range = location.byte_range();
+/// let end = (range.end as usize).min(bytes.len());
+/// let sectors = &bytes[range.start as usize..end];
+///
+/// let (scheme, payload) = chunk_payload(sectors).unwrap();
+/// assert_eq!(decode_chunk(scheme, payload).unwrap(), [1, 2, 3]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegionIndex {
+    locations: Vec<ChunkLocation>,
+    timestamps: Vec<u32>,
+}
+
+impl RegionIndex {
+    /// Parse the header at the start of a region.
+    pub fn parse(header: &[u8; REGION_HEADER_SIZE]) -> Self {
+        let mut locations = Vec::with_capacity(CHUNKS_PER_REGION);
+        let mut timestamps = Vec::with_capacity(CHUNKS_PER_REGION);
+
+        for z in 0..CHUNKS_PER_REGION_SIDE {
+            for x in 0..CHUNKS_PER_REGION_SIDE {
+                let pos = header_offset_for(x, z);
+                locations.push(ChunkLocation::from_bytes(&header[pos..pos + 4]));
+
+                let pos = timestamp_pos(x, z) as usize;
+                timestamps.push(BigEndian::read_u32(&header[pos..pos + 4]));
+            }
+        }
+
+        Self {
+            locations,
+            timestamps,
+        }
+    }
+
+    /// Get where the chunk at chunk coordinates x, z is in the region, or
+    /// `None` if it is absent. The coordinates should both be 0..32.
+    pub fn location(&self, x: usize, z: usize) -> Result<Option<ChunkLocation>> {
+        let loc = self.locations[index(x, z)?];
+        Ok((!loc.is_absent()).then_some(loc))
+    }
+
+    /// Get the timestamp of the last time the chunk at chunk coordinates x, z
+    /// was saved, in seconds since the Unix epoch. The coordinates should both
+    /// be 0..32. Absent chunks have a timestamp of zero.
+    pub fn timestamp(&self, x: usize, z: usize) -> Result<u32> {
+        Ok(self.timestamps[index(x, z)?])
+    }
+
+    /// Iterate over the chunks present in the region, giving their chunk
+    /// coordinates and location. Chunks increase in x, then z.
+    pub fn chunks(&self) -> impl Iterator<Item = (usize, usize, ChunkLocation)> + '_ {
+        self.locations
+            .iter()
+            .enumerate()
+            .filter(|(_, loc)| !loc.is_absent())
+            .map(|(i, loc)| (i % CHUNKS_PER_REGION_SIDE, i / CHUNKS_PER_REGION_SIDE, *loc))
+    }
+
+    /// The set of chunks present in the region.
+    pub fn chunk_bitmap(&self) -> ChunkBitmap {
+        let mut bitmap = ChunkBitmap::new();
+        for (x, z, _) in self.chunks() {
+            bitmap.insert(x, z);
+        }
+        bitmap
+    }
+}
+
+/// A set of the chunks of a region, one bit per chunk. It is small enough to
+/// keep one for every region of a world, eg to draw the area of it that has
+/// been explored. See [`Region::chunk_bitmap`] and
+/// [`RegionLoader::coverage`].
+///
+/// Chunk x, z is bit `z * 32 + x`, counting from the least significant bit of
+/// the first word.
+///
+/// ```
+/// # use fastanvil::ChunkBitmap;
+/// let mut bitmap = ChunkBitmap::new();
+/// bitmap.insert(3, 1);
+/// bitmap.insert(31, 31);
+/// assert!(bitmap.contains(3, 1));
+/// assert_eq!(bitmap.len(), 2);
+/// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [(3, 1), (31, 31)]);
+/// ```
+///
+/// [`Region::chunk_bitmap`]: crate::Region::chunk_bitmap
+/// [`RegionLoader::coverage`]: crate::RegionLoader::coverage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ChunkBitmap {
+    words: [u64; CHUNKS_PER_REGION / 64],
+}
+
+impl ChunkBitmap {
+    /// An empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A set from its words, as given by [`words`][Self::words].
+    pub fn from_words(words: [u64; CHUNKS_PER_REGION / 64]) -> Self {
+        Self { words }
+    }
+
+    /// The bits of the set, see [`ChunkBitmap`].
+    pub fn words(&self) -> &[u64; CHUNKS_PER_REGION / 64] {
+        &self.words
+    }
+
+    /// Add the chunk at x, z to the set.
+    ///
+    /// # Panics
+    ///
+    /// If x or z are not both 0..32.
+    pub fn insert(&mut self, x: usize, z: usize) {
+        assert!(
+            x < CHUNKS_PER_REGION_SIDE && z < CHUNKS_PER_REGION_SIDE,
+            "chunk {x}, {z} is outside of a region"
+        );
+        let i = z * CHUNKS_PER_REGION_SIDE + x;
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    /// Whether the chunk at x, z is in the set. Always false if x or z are not
+    /// both 0..32.
+    pub fn contains(&self, x: usize, z: usize) -> bool {
+        if x >= CHUNKS_PER_REGION_SIDE || z >= CHUNKS_PER_REGION_SIDE {
+            return false;
+        }
+        let i = z * CHUNKS_PER_REGION_SIDE + x;
+        self.words[i / 64] & 1 << (i % 64) != 0
+    }
+
+    /// The number of chunks in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Whether the set has no chunks.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// The chunks in the set as x, z. Chunks increase in x, then z.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> {
+        let words = self.words;
+        (0..CHUNKS_PER_REGION)
+            .filter(move |i| words[i / 64] & 1 << (i % 64) != 0)
+            .map(|i| (i % CHUNKS_PER_REGION_SIDE, i / CHUNKS_PER_REGION_SIDE))
+    }
+}
+
+fn index(x: usize, z: usize) -> Result<usize> {
+    if x >= CHUNKS_PER_REGION_SIDE || z >= CHUNKS_PER_REGION_SIDE {
+        return Err(Error::InvalidOffset(x as isize, z as isize));
+    }
+    Ok(z * CHUNKS_PER_REGION_SIDE + x)
+}
+
+/// Split the data of a chunk as stored in a region into its compression scheme
+/// and its compressed payload. `data` should start at the start of the chunk,
+/// eg the start of its [`byte_range`][ChunkLocation::byte_range], and can
+/// continue past its end.
+pub fn chunk_payload(data: &[u8]) -> Result<(CompressionScheme, &[u8])> {
+    let metadata = ChunkHeader::new(data)?;
+
+    let end = CHUNK_HEADER_SIZE + metadata.compressed_len as usize;
+    let payload = data
+        .get(CHUNK_HEADER_SIZE..end)
+        .ok_or_else(|| Error::IO(io::ErrorKind::UnexpectedEof.into()))?;
+
+    Ok((metadata.compression_scheme, payload))
+}
+
+/// Decompress the payload of a chunk into its uncompressed NBT. See
+/// [`chunk_payload`] for getting the payload from a region.
+///
+/// No limit is put on how big the chunk is uncompressed. A [`Region`] stops
+/// at [`DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN`], see
+/// [`Region::with_max_uncompressed_len`].
+///
+/// [`Region`]: crate::Region
+/// [`Region::with_max_uncompressed_len`]: crate::Region::with_max_uncompressed_len
+/// [`DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN`]: crate::DEFAULT_MAX_UNCOMPRESSED_CHUNK_LEN
+pub fn decode_chunk(scheme: CompressionScheme, payload: &[u8]) -> Result<Vec<u8>> {
+    let mut buf = vec![];
+    decode_chunk_into(scheme, payload, &mut buf)?;
+    Ok(buf)
+}
+
+/// Like [`decode_chunk`], but decompresses into `buf`, replacing its contents.
+/// Reusing the same buffer for many chunks avoids an allocation for each.
+pub fn decode_chunk_into(
+    scheme: CompressionScheme,
+    payload: &[u8],
+    buf: &mut Vec<u8>,
+) -> Result<()> {
+    decode_chunk_limited(scheme, payload, buf, usize::MAX)?;
+    Ok(())
+}
+
+/// Like [`decode_chunk_into`], but stops once more than `limit` bytes have
+/// been decompressed, returning false. The buffer is grown no further than
+/// just past the limit, so a small payload claiming to decompress to
+/// gigabytes does not allocate them.
+pub(crate) fn decode_chunk_limited(
+    scheme: CompressionScheme,
+    payload: &[u8],
+    buf: &mut Vec<u8>,
+    limit: usize,
+) -> Result<bool> {
+    buf.clear();
+    // One byte past the limit tells a chunk of exactly the limit from a
+    // bigger one.
+    let cap = limit.saturating_add(1);
+    buf.reserve(uncompressed_size_hint(scheme, payload.len()).min(cap));
+
+    match scheme {
+        CompressionScheme::Zlib => inflate_zlib(payload, buf, cap)?,
+        CompressionScheme::Gzip => {
+            flate2::bufread::GzDecoder::new(payload)
+                .take(cap as u64)
+                .read_to_end(buf)?;
+        }
+        CompressionScheme::Uncompressed => {
+            buf.extend_from_slice(&payload[..payload.len().min(cap)])
+        }
+    }
+
+    Ok(buf.len() <= limit)
+}
+
+/// Decompress zlib data onto the end of `buf`, growing it as needed, until
+/// the stream ends or `buf` holds `cap` bytes. Corrupt data gives
+/// [`Error::Decompress`], and data that stops short of the end of the stream
+/// an [`UnexpectedEof`][io::ErrorKind::UnexpectedEof] IO error.
+fn inflate_zlib(payload: &[u8], buf: &mut Vec<u8>, cap: usize) -> Result<()> {
+    let mut inflater = Decompress::new(true);
+
+    loop {
+        if buf.len() >= cap {
+            return Ok(());
+        }
+
+        if buf.len() == buf.capacity() {
+            let growth = buf.capacity().max(MIN_GROWTH).min(cap - buf.len());
+            buf.reserve_exact(growth);
+        }
+
+        let (read, written) = (inflater.total_in(), inflater.total_out());
+        let input = &payload[read as usize..];
+
+        if inflater.decompress_vec(input, buf, FlushDecompress::None)? == Status::StreamEnd {
+            return Ok(());
+        }
+
+        if inflater.total_in() == read && inflater.total_out() == written {
+            return Err(Error::IO(io::ErrorKind::UnexpectedEof.into()));
+        }
+    }
+}
+
+/// The least to grow the buffer by when it fills while decompressing.
+const MIN_GROWTH: usize = 4096;
+
+/// Chunk NBT typically compresses to between a fifth and a tenth of its size.
+const COMPRESSION_RATIO_HINT: usize = 8;
+
+/// Chunks are rarely bigger than this uncompressed, so guesses are capped at
+/// it. Bigger chunks grow the buffer as they decompress.
+const MAX_SIZE_HINT: usize = 2 * 1024 * 1024;
+
+/// Guess how big a chunk will be uncompressed, to reserve space for it before
+/// decompressing. A good guess saves growing the buffer several times as it
+/// fills, and the buffer still doubles from it if it is too small.
+pub(crate) fn uncompressed_size_hint(scheme: CompressionScheme, compressed_len: usize) -> usize {
+    match scheme {
+        CompressionScheme::Uncompressed => compressed_len,
+        _ => compressed_len
+            .saturating_mul(COMPRESSION_RATIO_HINT)
+            .min(MAX_SIZE_HINT),
+    }
+}
+
+impl ChunkLocation {
+    /// Parse a location from the 4 bytes of it in a region header.
+    pub(crate) fn from_bytes(buf: &[u8]) -> Self {
+        let offset = (buf[0] as u64) << 16 | (buf[1] as u64) << 8 | buf[2] as u64;
+        let sectors = buf[3] as u64;
+
+        ChunkLocation { offset, sectors }
+    }
+
+    /// Locations of zero mean the chunk is not in the region.
+    pub(crate) fn is_absent(&self) -> bool {
+        self.offset == 0 && self.sectors == 0
+    }
+
+    /// The range of bytes in the region that the chunk's sectors cover. The
+    /// chunk's data starts at the start of the range, but might not fill it.
+    /// The last sector of a region is not always padded, so the range can
+    /// extend past the end of the region.
+    pub fn byte_range(&self) -> std::ops::Range<u64> {
+        let sector = crate::region::SECTOR_SIZE as u64;
+        self.offset * sector..(self.offset + self.sectors) * sector
+    }
+}
+
+impl TryFrom<&[u8]> for RegionIndex {
+    type Error = Error;
+
+    /// Parse a header from a slice, which must be at least
+    /// [`REGION_HEADER_SIZE`] bytes long. Anything after the header is ignored.
+    fn try_from(data: &[u8]) -> Result<Self> {
+        let header = data
+            .get(..REGION_HEADER_SIZE)
+            .and_then(|h| h.try_into().ok())
+            .ok_or_else(|| Error::IO(io::ErrorKind::UnexpectedEof.into()))?;
+
+        Ok(Self::parse(header))
+    }
+}
@@ -1,9 +1,16 @@
 use std::{
+    any::Any,
     cmp::Ordering,
-    io::{Read, Seek, Write},
+    collections::{BTreeMap, BTreeSet},
+    io::{Read, Seek},
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex, PoisonError},
 };
 
-use crate::{Block, BlockArchetype, CCoord, Chunk, HeightMode, JavaChunk, RCoord, RegionLoader};
+use crate::{
+    Block, BlockArchetype, BlockFilter, CCoord, Chunk, Diagnostic, DiagnosticKind, FallbackPalette,
+    HeightMode, JavaChunk, RCoord, Region, RegionLoader, SectionFill, SNOW_BLOCK, WATER,
+};
 
 use super::biome::Biome;
 
@@ -11,47 +18,500 @@ pub type Rgba = [u8; 4];
 
 /// Palette can be used to take a block description to produce a colour that it
 /// should render to.
+///
+/// [`TopShadeRenderer`] calls [`pick`][Palette::pick] for the blocks it walks
+/// through in each column, from the surface down, and stops once the colours
+/// it has picked add up to an opaque colour. So:
+///
+/// * The block is the one the column shows after [`SurfaceRules`] have been
+///   applied, eg waterlogged blocks are passed as water. Air is never picked.
+/// * The biome is the biome at the block, or `None` if the chunk has no biome
+///   there, eg McRegion chunks or chunks saved before biomes were generated.
+/// * The alpha of the colour is how much of the blocks below show through,
+///   with 255 hiding them entirely. The alpha of water is replaced with one
+///   based on how deep it is.
+///
+/// Palettes are picked from for every block of every column rendered, so
+/// `pick` should be cheap. The renderers take palettes by reference, and can
+/// take a `&dyn Palette` to choose a palette at runtime. [`DebugPalette`] is a
+/// palette for testing rendering without any block colours.
 pub trait Palette {
+    /// The colour to draw the block with.
     fn pick(&self, block: &Block, biome: Option<Biome>) -> Rgba;
+
+    /// Pick a colour for the block, or `None` if the palette has no colour for
+    /// it rather than the colour it would use to mark it as missing. This is
+    /// how a [`FallbackPalette`] knows when to fall back. By default this
+    /// never misses.
+    fn try_pick(&self, block: &Block, biome: Option<Biome>) -> Option<Rgba> {
+        Some(self.pick(block, biome))
+    }
+
+    /// Fall back to map colours for blocks this palette has no colour for.
+    /// See [`FallbackPalette`].
+    fn with_fallbacks(self) -> FallbackPalette<Self>
+    where
+        Self: Sized,
+    {
+        FallbackPalette::new(self)
+    }
+}
+
+/// A palette giving each block name its own opaque colour, made from a hash of
+/// the name. Blocks with the same name are the same colour whatever their
+/// properties or biome, and the colours are the same from run to run.
+///
+/// The colours don't look like the blocks, but show the structure of a map
+/// without needing any block colours, eg to test a renderer.
+///
+/// ```
+/// # use fastanvil::{DebugPalette, HeightMode, Palette, TopShadeRenderer};
+/// let palette: &dyn Palette = &DebugPalette;
+/// let renderer = TopShadeRenderer::new(palette, HeightMode::Trust);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DebugPalette;
+
+impl Palette for DebugPalette {
+    fn pick(&self, block: &Block, _: Option<Biome>) -> Rgba {
+        // FNV-1a, as the std hashers may change between releases.
+        let hash = block.name().bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x100000001b3)
+        });
+        let [r, g, b, ..] = hash.to_le_bytes();
+        [r, g, b, 255]
+    }
+}
+
+/// Which part of a dimension to render. The default renders each column from
+/// the top of the world down to the first block that isn't air, which suits the
+/// overworld and the end.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DimensionOpts {
+    /// Ignore any blocks above this height, eg the bedrock roof of the nether.
+    pub max_y: Option<isize>,
+    /// Ignore any blocks below this height.
+    pub min_y: Option<isize>,
+    /// When a column is cut by `max_y`, skip down through the solid blocks
+    /// below the cut until reaching air, then render the first block below
+    /// that air. This shows the floor of the caves under a roof rather than
+    /// the cross section of the roof itself.
+    pub cave: bool,
+}
+
+impl DimensionOpts {
+    pub fn overworld() -> Self {
+        Self::default()
+    }
+
+    /// Render the floor under the roof of the nether.
+    pub fn nether() -> Self {
+        Self {
+            max_y: Some(127),
+            min_y: None,
+            cave: true,
+        }
+    }
+}
+
+/// What a [`TopShadeRenderer`] draws.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// The top-down view of the world, like an in-game map.
+    #[default]
+    Surface,
+    /// The floor of the highest cave below the surface of each column,
+    /// coloured by how far below the surface it is. Columns without a cave are
+    /// left transparent.
+    Caves(DepthRamp),
+}
+
+/// Colours for cave depths, fading linearly from `shallow` just below the
+/// surface to `deep` at `max_depth` blocks below the surface and beyond.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthRamp {
+    pub shallow: Rgba,
+    pub deep: Rgba,
+    pub max_depth: isize,
+}
+
+impl Default for DepthRamp {
+    fn default() -> Self {
+        Self {
+            shallow: [255, 220, 120, 255],
+            deep: [30, 40, 150, 255],
+            max_depth: 64,
+        }
+    }
+}
+
+impl DepthRamp {
+    pub fn colour(&self, depth: isize) -> Rgba {
+        let t = depth.clamp(0, self.max_depth.max(1)) as f32 / self.max_depth.max(1) as f32;
+        let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+        [
+            lerp(self.shallow[0], self.deep[0]),
+            lerp(self.shallow[1], self.deep[1]),
+            lerp(self.shallow[2], self.deep[2]),
+            lerp(self.shallow[3], self.deep[3]),
+        ]
+    }
+}
+
+/// How [`TopShadeRenderer`] darkens water by its depth, so deep ocean is
+/// darker than shallow water. Water `depth` blocks deep is darkened by
+/// `max_darkening * (depth / max_depth) ^ exponent`, and by `max_darkening`
+/// beyond `max_depth`. A darkening of 0 leaves the water's colour as it is and
+/// 1 makes it black.
+///
+/// An `exponent` of 1 darkens water evenly with depth. Smaller exponents
+/// darken shallow water more quickly, so the edges of oceans stand out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaterDepthShading {
+    pub max_darkening: f32,
+    pub max_depth: isize,
+    pub exponent: f32,
+}
+
+impl Default for WaterDepthShading {
+    fn default() -> Self {
+        Self {
+            max_darkening: 0.6,
+            max_depth: 48,
+            exponent: 0.5,
+        }
+    }
+}
+
+impl WaterDepthShading {
+    /// The darkening of water `depth` blocks deep, from 0 to 1.
+    pub fn darkening(&self, depth: isize) -> f32 {
+        let max_depth = self.max_depth.max(1);
+        let t = depth.clamp(0, max_depth) as f32 / max_depth as f32;
+        (self.max_darkening * t.powf(self.exponent)).clamp(0.0, 1.0)
+    }
+
+    /// Darken the colour of water `depth` blocks deep, keeping its alpha.
+    pub fn shade(&self, colour: Rgba, depth: isize) -> Rgba {
+        let keep = 1.0 - self.darkening(depth);
+        let [r, g, b, a] = colour;
+        let darken = |c: u8| (c as f32 * keep).round() as u8;
+        [darken(r), darken(g), darken(b), a]
+    }
+}
+
+/// What a [`TopShadeRenderer`] does with a block matching a rule in
+/// [`SurfaceRules`].
+#[derive(Debug, Clone)]
+pub enum SurfaceAction {
+    /// Look through the block to the one below, eg tall grass.
+    Skip,
+    /// Draw the block as water, eg waterlogged stairs.
+    Water,
+    /// Draw the block as another block, eg snow layers as snow. Blocks with
+    /// this action are drawn even when lying on top of the surface, since
+    /// the heightmap leaves some out, eg snow layers.
+    Replace(Block),
+}
+
+/// Rules for which block a column shows when rendering top-down, each a
+/// [`BlockFilter`] and what to do with blocks it matches. Rules are checked in
+/// order and the first match is used. Blocks matching no rule are drawn as
+/// they are.
+///
+/// The default rules skip grass, ferns and tall flowers, draw waterlogged
+/// blocks as water and draw snow layers as snow. [`SurfaceRules::empty`]
+/// draws every block as it is.
+///
+/// ```
+/// # use fastanvil::{BlockFilter, SurfaceAction, SurfaceRules};
+/// let rules = SurfaceRules::empty()
+///     .with_rule(BlockFilter::new().name("minecraft:*_carpet"), SurfaceAction::Skip)
+///     .with_defaults();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SurfaceRules {
+    rules: Vec<(BlockFilter, SurfaceAction)>,
 }
 
-pub struct TopShadeRenderer<'a, P: Palette> {
+impl SurfaceRules {
+    /// No rules, so every block is drawn as it is.
+    pub fn empty() -> Self {
+        Self { rules: vec![] }
+    }
+
+    /// Add a rule, checked after those already added.
+    pub fn with_rule(mut self, filter: BlockFilter, action: SurfaceAction) -> Self {
+        self.rules.push((filter, action));
+        self
+    }
+
+    /// Add the default rules, checked after those already added.
+    pub fn with_defaults(mut self) -> Self {
+        let plants = [
+            "grass",
+            "short_grass",
+            "tall_grass",
+            "fern",
+            "large_fern",
+            "dead_bush",
+            "sunflower",
+            "lilac",
+            "rose_bush",
+            "peony",
+        ]
+        .into_iter()
+        .fold(BlockFilter::new(), |filter, name| {
+            filter.name(format!("minecraft:{name}"))
+        });
+
+        self.rules.extend([
+            (
+                BlockFilter::new().property("waterlogged", "true"),
+                SurfaceAction::Water,
+            ),
+            (plants, SurfaceAction::Skip),
+            (
+                BlockFilter::new().name("minecraft:snow"),
+                SurfaceAction::Replace(SNOW_BLOCK.clone()),
+            ),
+        ]);
+        self
+    }
+
+    /// The action for the block from the first rule it matches, if any.
+    pub fn action(&self, block: &Block) -> Option<&SurfaceAction> {
+        self.rules
+            .iter()
+            .find(|(filter, _)| filter.matches(block))
+            .map(|(_, action)| action)
+    }
+
+    fn is_water(&self, block: &Block) -> bool {
+        block.archetype == BlockArchetype::Watery
+            || matches!(self.action(block), Some(SurfaceAction::Water))
+    }
+}
+
+impl Default for SurfaceRules {
+    fn default() -> Self {
+        Self::empty().with_defaults()
+    }
+}
+
+/// The blocks drawn on a map and the colours the palette picked for them, eg
+/// to draw a legend for the map. See [`TopShadeRenderer::with_legend`].
+///
+/// Colours are the ones the palette picked, so are tinted by biome but not
+/// shaded. A block tinted by several biomes has several colours. Blocks are in
+/// order of name, and each block's colours in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Legend {
+    blocks: BTreeMap<String, BTreeSet<Rgba>>,
+}
+
+impl Legend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a block's colour, returning whether it was new.
+    pub fn insert(&mut self, name: &str, colour: Rgba) -> bool {
+        match self.blocks.get_mut(name) {
+            Some(colours) => colours.insert(colour),
+            None => {
+                self.blocks
+                    .insert(name.to_owned(), BTreeSet::from([colour]));
+                true
+            }
+        }
+    }
+
+    /// Add everything in another legend, eg to combine the legends of several
+    /// regions.
+    pub fn merge(&mut self, other: &Legend) {
+        for (name, colour) in other.iter() {
+            self.insert(name, colour);
+        }
+    }
+
+    /// Whether the block was drawn in the colour.
+    pub fn contains(&self, name: &str, colour: Rgba) -> bool {
+        self.blocks
+            .get(name)
+            .is_some_and(|colours| colours.contains(&colour))
+    }
+
+    /// The colours the block was drawn in, empty if it was not drawn.
+    pub fn colours(&self, name: &str) -> impl Iterator<Item = Rgba> + '_ {
+        self.blocks.get(name).into_iter().flatten().copied()
+    }
+
+    /// Each block and colour drawn.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Rgba)> + '_ {
+        self.blocks
+            .iter()
+            .flat_map(|(name, colours)| colours.iter().map(move |c| (name.as_str(), *c)))
+    }
+
+    /// The number of different blocks drawn.
+    pub fn blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+pub struct TopShadeRenderer<'a, P: Palette + ?Sized> {
     palette: &'a P,
     height_mode: HeightMode,
+    dimension: DimensionOpts,
+    mode: RenderMode,
+    rules: SurfaceRules,
+    water_shading: Option<WaterDepthShading>,
+    // Shared with clones, as the region renderers take the renderer by value.
+    legend: Option<Arc<Mutex<Legend>>>,
+}
+
+// Not derived, as that would need the palette to be Clone rather than just the
+// reference to it.
+impl<P: Palette + ?Sized> Clone for TopShadeRenderer<'_, P> {
+    fn clone(&self) -> Self {
+        Self {
+            palette: self.palette,
+            height_mode: self.height_mode,
+            dimension: self.dimension,
+            mode: self.mode,
+            rules: self.rules.clone(),
+            water_shading: self.water_shading,
+            legend: self.legend.clone(),
+        }
+    }
 }
 
-impl<'a, P: Palette> TopShadeRenderer<'a, P> {
+impl<'a, P: Palette + ?Sized> TopShadeRenderer<'a, P> {
     pub fn new(palette: &'a P, mode: HeightMode) -> Self {
         Self {
             palette,
             height_mode: mode,
+            dimension: DimensionOpts::default(),
+            mode: RenderMode::default(),
+            rules: SurfaceRules::default(),
+            water_shading: None,
+            legend: None,
         }
     }
 
+    /// Set the rules for which block each column shows, see [`SurfaceRules`].
+    pub fn with_surface_rules(mut self, rules: SurfaceRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set what to render, see [`RenderMode`].
+    pub fn with_mode(mut self, mode: RenderMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set which part of the dimension to render, see [`DimensionOpts`].
+    pub fn with_dimension(mut self, dimension: DimensionOpts) -> Self {
+        self.dimension = dimension;
+        self
+    }
+
+    /// Darken water by how deep it is, see [`WaterDepthShading`]. Off by
+    /// default, when water is only made more opaque the deeper it is.
+    pub fn with_water_depth_shading(mut self, shading: Option<WaterDepthShading>) -> Self {
+        self.water_shading = shading;
+        self
+    }
+
+    /// Collect the blocks drawn and their colours into a new [`Legend`], read
+    /// with [`legend`][Self::legend]. Off by default. Only blocks drawn in
+    /// [`RenderMode::Surface`] are collected.
+    ///
+    /// Clones of the renderer collect into the same legend, so a renderer
+    /// passed by value to [`render_region`] still collects into the legend of
+    /// the renderer it was cloned from. The legend is added to once per chunk
+    /// with the blocks new to it, rather than for every block drawn.
+    ///
+    /// ```no_run
+    /// # use fastanvil::*;
+    /// # let loader = RegionFileLoader::new("region".into());
+    /// let renderer = TopShadeRenderer::new(&DebugPalette, HeightMode::Trust).with_legend(true);
+    /// let map = render_region(RCoord(0), RCoord(0), &loader, renderer.clone());
+    ///
+    /// for (name, colour) in renderer.legend().unwrap().iter() {
+    ///     println!("{name}: {colour:?}");
+    /// }
+    /// ```
+    pub fn with_legend(mut self, collect: bool) -> Self {
+        self.legend = collect.then(Default::default);
+        self
+    }
+
+    /// The renderer with a new legend if it is collecting one, for renders
+    /// that return the legend of what they drew alone.
+    pub(crate) fn with_own_legend(self) -> Self {
+        let collect = self.legend.is_some();
+        self.with_legend(collect)
+    }
+
+    /// The blocks drawn so far by this renderer and its clones, or `None` if
+    /// it is not collecting them, see [`with_legend`][Self::with_legend].
+    pub fn legend(&self) -> Option<Legend> {
+        let legend = self.legend.as_ref()?;
+        Some(
+            legend
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        )
+    }
+
     pub fn render<C: Chunk + ?Sized>(&self, chunk: &C, north: Option<&C>) -> [Rgba; 16 * 16] {
+        let north = north.map(|c| self.south_edge_heights(c));
+        self.render_with_north_heights(chunk, north)
+    }
+
+    /// Render a chunk, shading its northmost row using the given surface
+    /// heights of the southmost row of the chunk to the north, indexed by x.
+    /// This allows shading across a region border when the neighbouring chunk
+    /// itself isn't available.
+    pub fn render_with_north_heights<C: Chunk + ?Sized>(
+        &self,
+        chunk: &C,
+        north: Option<[isize; 16]>,
+    ) -> [Rgba; 16 * 16] {
         let mut data = [[0, 0, 0, 0]; 16 * 16];
 
-        if chunk.status() != "full" && chunk.status() != "spawn" {
-            // Chunks that have been fully generated will have a 'full' status.
-            // Skip chunks that don't; the way they render is unpredictable.
+        if !is_rendered(chunk) {
             return data;
         }
 
-        let y_range = chunk.y_range();
+        if let RenderMode::Caves(ramp) = self.mode {
+            return self.render_caves(chunk, ramp);
+        }
+
+        let y_min = self.y_min(chunk);
+        let heights = self.surface_heights(chunk);
+        let mut picked = vec![];
 
         for z in 0..16 {
             for x in 0..16 {
-                let air_height = chunk.surface_height(x, z, self.height_mode);
-                let block_height = (air_height - 1).max(y_range.start);
+                let air_height = heights[z * 16 + x];
+                let block_height = (air_height - 1).max(y_min);
 
-                let colour = self.drill_for_colour(x, block_height, z, chunk, y_range.start);
+                let colour = self.drill_for_colour(x, block_height, z, chunk, y_min, &mut picked);
 
                 let north_air_height = match z {
                     // if top of chunk, get height from the chunk above.
-                    0 => north
-                        .map(|c| c.surface_height(x, 15, self.height_mode))
-                        .unwrap_or(block_height),
-                    z => chunk.surface_height(x, z - 1, self.height_mode),
+                    0 => north.map(|heights| heights[x]).unwrap_or(block_height),
+                    z => heights[(z - 1) * 16 + x],
                 };
                 let colour = top_shade_colour(colour, air_height, north_air_height);
 
@@ -59,25 +519,140 @@ impl<'a, P: Palette> TopShadeRenderer<'a, P> {
             }
         }
 
+        if let Some(legend) = &self.legend {
+            add_to_legend(legend, picked);
+        }
+
         data
     }
 
+    /// The height of the air above the block each column shows, indexed by
+    /// `z * 16 + x`. These are the heights the surface is shaded by, so take
+    /// the dimension options into account, eg cutting through the nether
+    /// roof.
+    pub fn surface_heights<C: Chunk + ?Sized>(&self, chunk: &C) -> [isize; 16 * 16] {
+        let mut heights = [0; 16 * 16];
+        for z in 0..16 {
+            for x in 0..16 {
+                heights[z * 16 + x] = self.air_height(chunk, x, z);
+            }
+        }
+        heights
+    }
+
+    /// Surface heights of the southmost row of blocks in the chunk, indexed
+    /// by x. These are the heights needed to shade the chunk to the south.
+    pub fn south_edge_heights<C: Chunk + ?Sized>(&self, chunk: &C) -> [isize; 16] {
+        let mut heights = [0; 16];
+        for (x, height) in heights.iter_mut().enumerate() {
+            *height = self.air_height(chunk, x, 15);
+        }
+        heights
+    }
+
+    fn render_caves<C: Chunk + ?Sized>(&self, chunk: &C, ramp: DepthRamp) -> [Rgba; 16 * 16] {
+        let mut data = [[0, 0, 0, 0]; 16 * 16];
+        let y_min = self.y_min(chunk);
+
+        for z in 0..16 {
+            for x in 0..16 {
+                // Start from the surface, but never above the ceiling.
+                let mut top = chunk.surface_height(x, z, self.height_mode) - 1;
+                if let Some(max_y) = self.dimension.max_y {
+                    top = top.min(max_y);
+                }
+
+                if let Some(floor) = cave_floor(chunk, x, z, top, y_min) {
+                    data[z * 16 + x] = ramp.colour(top - floor);
+                }
+            }
+        }
+
+        data
+    }
+
+    fn y_min<C: Chunk + ?Sized>(&self, chunk: &C) -> isize {
+        let start = chunk.y_range().start;
+        self.dimension.min_y.map_or(start, |min| min.max(start))
+    }
+
+    /// Get the height of the air above the block to render for the column,
+    /// taking into account the dimension options.
+    fn air_height<C: Chunk + ?Sized>(&self, chunk: &C, x: usize, z: usize) -> isize {
+        let surface = chunk.surface_height(x, z, self.height_mode);
+
+        let max_y = match self.dimension.max_y {
+            // Only columns that reach the ceiling need cutting.
+            Some(max_y) if surface > max_y => max_y,
+            _ => return surface,
+        };
+
+        let y_min = self.y_min(chunk);
+        let is_air = |y| match chunk.block(x, y, z) {
+            Some(b) => b.archetype == BlockArchetype::Airy,
+            None => true,
+        };
+
+        let mut y = max_y;
+
+        if self.dimension.cave {
+            while y >= y_min && !is_air(y) {
+                y -= 1;
+            }
+
+            if y < y_min {
+                // Solid all the way down, so there's no cave floor to show.
+                // Show the cut through the roof instead.
+                return max_y + 1;
+            }
+        }
+
+        while y >= y_min && is_air(y) {
+            y -= 1;
+        }
+
+        y + 1
+    }
+
     /// Drill for colour. Starting at y_start, make way down the column until we
     /// have an opaque colour to return. This tackles things like transparency.
-    fn drill_for_colour<C: Chunk + ?Sized>(
-        &self,
+    fn drill_for_colour<'b, C: Chunk + ?Sized>(
+        &'b self,
         x: usize,
         y_start: isize,
         z: usize,
-        chunk: &C,
+        chunk: &'b C,
         y_min: isize,
+        picked: &mut Vec<(&'b Block, Rgba)>,
     ) -> Rgba {
         let mut y = y_start;
         let mut colour = [0, 0, 0, 0];
 
+        // Snow layers and the like are not part of the heightmap, so may be
+        // lying on top of the surface.
+        let below_ceiling = self.dimension.max_y.is_none_or(|max_y| y_start < max_y);
+        if below_ceiling {
+            if let Some(SurfaceAction::Replace(_)) = chunk
+                .block(x, y_start + 1, z)
+                .and_then(|b| self.rules.action(b))
+            {
+                y += 1;
+            }
+        }
+
         while colour[3] != 255 && y >= y_min {
             let current_biome = chunk.biome(x, y, z);
-            let current_block = chunk.block(x, y, z);
+            let mut current_block = chunk.block(x, y, z);
+
+            match current_block.and_then(|b| self.rules.action(b)) {
+                Some(SurfaceAction::Skip) => {
+                    y -= 1;
+                    continue;
+                }
+                Some(SurfaceAction::Water) => current_block = Some(&WATER),
+                Some(SurfaceAction::Replace(block)) => current_block = Some(block),
+                None => {}
+            }
 
             if let Some(current_block) = current_block {
                 match current_block.archetype {
@@ -87,8 +662,11 @@ impl<'a, P: Palette> TopShadeRenderer<'a, P> {
                     // TODO: Can potentially optimize this for ocean floor using
                     // heightmaps.
                     BlockArchetype::Watery => {
-                        let mut block_colour = self.palette.pick(current_block, current_biome);
-                        let water_depth = water_depth(x, y, z, chunk, y_min);
+                        let mut block_colour = self.pick(current_block, current_biome, picked);
+                        let water_depth = self.water_depth(x, y, z, chunk, y_min);
+                        if let Some(shading) = self.water_shading {
+                            block_colour = shading.shade(block_colour, water_depth);
+                        }
                         let alpha = water_depth_to_alpha(water_depth);
 
                         block_colour[3] = alpha as u8;
@@ -97,7 +675,7 @@ impl<'a, P: Palette> TopShadeRenderer<'a, P> {
                         y -= water_depth;
                     }
                     _ => {
-                        let block_colour = self.palette.pick(current_block, current_biome);
+                        let block_colour = self.pick(current_block, current_biome, picked);
                         colour = a_over_b_colour(colour, block_colour);
                         y -= 1;
                     }
@@ -109,6 +687,111 @@ impl<'a, P: Palette> TopShadeRenderer<'a, P> {
 
         colour
     }
+
+    /// Pick the colour of a block, keeping it for the legend if there is one.
+    /// Each block and colour is kept once. A chunk's blocks come from its
+    /// palettes, so the same block is usually the same reference and the
+    /// check is cheap.
+    fn pick<'b>(
+        &self,
+        block: &'b Block,
+        biome: Option<Biome>,
+        picked: &mut Vec<(&'b Block, Rgba)>,
+    ) -> Rgba {
+        let colour = self.palette.pick(block, biome);
+        if self.legend.is_some()
+            && !picked
+                .iter()
+                .any(|&(b, c)| std::ptr::eq(b, block) && c == colour)
+        {
+            picked.push((block, colour));
+        }
+        colour
+    }
+
+    fn water_depth<C: Chunk + ?Sized>(
+        &self,
+        x: usize,
+        mut y: isize,
+        z: usize,
+        chunk: &C,
+        y_min: isize,
+    ) -> isize {
+        let mut depth = 1;
+        while y > y_min {
+            let block = match chunk.block(x, y, z) {
+                Some(b) => b,
+                None => return depth,
+            };
+
+            if self.rules.is_water(block) {
+                depth += 1;
+            } else {
+                return depth;
+            }
+            y -= 1;
+        }
+        depth
+    }
+}
+
+/// Add the blocks picked for a chunk to the legend. Blocks of the same name
+/// from different sections are deduplicated first to keep the time the legend
+/// is locked short.
+fn add_to_legend(legend: &Mutex<Legend>, mut picked: Vec<(&Block, Rgba)>) {
+    picked.sort_unstable_by(|(a, a_colour), (b, b_colour)| {
+        (a.name(), a_colour).cmp(&(b.name(), b_colour))
+    });
+    picked.dedup_by(|(a, a_colour), (b, b_colour)| a.name() == b.name() && a_colour == b_colour);
+
+    let mut legend = legend.lock().unwrap_or_else(PoisonError::into_inner);
+    for (block, colour) in picked {
+        legend.insert(block.name(), colour);
+    }
+}
+
+/// Find the highest air block at or below `top` that has a non-air block below
+/// it, ie the floor of the highest cave. Sections that are entirely air or
+/// entirely solid are skipped over rather than checked block by block.
+fn cave_floor<C: Chunk + ?Sized>(
+    chunk: &C,
+    x: usize,
+    z: usize,
+    top: isize,
+    y_min: isize,
+) -> Option<isize> {
+    let is_air = |y| match chunk.block(x, y, z) {
+        Some(b) => b.archetype == BlockArchetype::Airy,
+        None => true,
+    };
+
+    let mut y = top;
+
+    while y > y_min {
+        let section_bottom = y.div_euclid(16) * 16;
+
+        match chunk.section_fill(y) {
+            // No air at all, so no floor in this section.
+            Some(SectionFill::Solid) => {
+                y = section_bottom - 1;
+                continue;
+            }
+            // Only the bottom block can have something solid beneath it.
+            Some(SectionFill::Air) if y > section_bottom => {
+                y = section_bottom;
+                continue;
+            }
+            _ => {}
+        }
+
+        if is_air(y) && !is_air(y - 1) {
+            return Some(y);
+        }
+
+        y -= 1;
+    }
+
+    None
 }
 
 /// Convert `water_depth` meters of water to an approximate opacity
@@ -130,30 +813,6 @@ fn water_depth_to_alpha(water_depth: isize) -> u8 {
     (180 + 2 * water_depth).min(250) as u8
 }
 
-fn water_depth<C: Chunk + ?Sized>(
-    x: usize,
-    mut y: isize,
-    z: usize,
-    chunk: &C,
-    y_min: isize,
-) -> isize {
-    let mut depth = 1;
-    while y > y_min {
-        let block = match chunk.block(x, y, z) {
-            Some(b) => b,
-            None => return depth,
-        };
-
-        if block.archetype == BlockArchetype::Watery {
-            depth += 1;
-        } else {
-            return depth;
-        }
-        y -= 1;
-    }
-    depth
-}
-
 /// Merge two potentially transparent colours, A and B, into one as if colour A
 /// was laid on top of colour B.
 ///
@@ -212,76 +871,425 @@ impl<T: Clone> RegionMap<T> {
     }
 }
 
-pub fn render_region<P: Palette, S>(
+/// Surface heights bordering a region, used to shade the edge of the region
+/// when rendering it without access to its neighbours.
+///
+/// Top-shading compares each block with the block directly north of it, so
+/// only the northern edge of a region depends on another region.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NeighbourHeights {
+    /// The south edge heights of the southmost row of chunks of the region to
+    /// the north, indexed by chunk x. `None` for missing chunks. See
+    /// [`TopShadeRenderer::south_edge_heights`].
+    pub north: [Option<[isize; 16]>; 32],
+}
+
+impl NeighbourHeights {
+    /// Get the heights needed to shade the region to the south of the given
+    /// region.
+    pub fn south_of<P: Palette + ?Sized, S: Read + Seek>(
+        region: &mut Region<S>,
+        renderer: &TopShadeRenderer<P>,
+    ) -> Self {
+        let mut heights = Self::default();
+        for (x, north) in heights.north.iter_mut().enumerate() {
+            *north =
+                load_chunk(region, x, 31, &mut vec![]).map(|c| renderer.south_edge_heights(&c));
+        }
+        heights
+    }
+}
+
+/// Render a single chunk to a 16 by 16 tile, indexed by `z * 16 + x`, for
+/// caching maps a chunk at a time. This is what [`render_region`] does for
+/// each chunk, so the tile is the same as the chunk's part of the region map.
+///
+/// `north` is the [`south_edge_heights`][TopShadeRenderer::south_edge_heights]
+/// of the chunk to the north, used to shade the chunk's northmost row. At the
+/// north edge of a region these are in [`NeighbourHeights::north`]. Without
+/// them the northmost row is shaded as if the chunk to the north were level
+/// with it.
+///
+/// ```no_run
+/// # use fastanvil::{render_chunk, DebugPalette, HeightMode, JavaChunk, TopShadeRenderer};
+/// # let (data, north_data): (Vec<u8>, Vec<u8>) = (vec![], vec![]);
+/// let renderer = TopShadeRenderer::new(&DebugPalette, HeightMode::Trust);
+/// let chunk = JavaChunk::from_bytes(&data).unwrap();
+/// let north = JavaChunk::from_bytes(&north_data).unwrap();
+///
+/// let tile = render_chunk(&chunk, &renderer, Some(renderer.south_edge_heights(&north)));
+/// ```
+pub fn render_chunk<P: Palette + ?Sized, C: Chunk + ?Sized>(
+    chunk: &C,
+    renderer: &TopShadeRenderer<P>,
+    north: Option<[isize; 16]>,
+) -> [Rgba; 16 * 16] {
+    renderer.render_with_north_heights(chunk, north)
+}
+
+pub fn render_region<P: Palette + ?Sized, S>(
     x: RCoord,
     z: RCoord,
     loader: &dyn RegionLoader<S>,
     renderer: TopShadeRenderer<P>,
 ) -> RegionMap<Rgba>
 where
-    S: Seek + Read + Write,
+    S: Seek + Read,
 {
+    render_region_with_context(x, z, loader, renderer, None)
+}
+
+/// Render a region, using the given heights to shade its northern edge. If
+/// `neighbours` is `None`, the region to the north is fetched from the loader
+/// instead, as in [`render_region`].
+pub fn render_region_with_context<P: Palette + ?Sized, S>(
+    x: RCoord,
+    z: RCoord,
+    loader: &dyn RegionLoader<S>,
+    renderer: TopShadeRenderer<P>,
+    neighbours: Option<&NeighbourHeights>,
+) -> RegionMap<Rgba>
+where
+    S: Seek + Read,
+{
+    render_region_with_diagnostics(x, z, loader, renderer, neighbours, &mut vec![])
+}
+
+/// Render a region like [`render_region_with_context`], adding a
+/// [`Diagnostic`] to `diagnostics` for each chunk of the region that could not
+/// be rendered or had data that was worked around, eg a missing heightmap.
+pub fn render_region_with_diagnostics<P: Palette + ?Sized, S>(
+    x: RCoord,
+    z: RCoord,
+    loader: &dyn RegionLoader<S>,
+    renderer: TopShadeRenderer<P>,
+    neighbours: Option<&NeighbourHeights>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> RegionMap<Rgba>
+where
+    S: Seek + Read,
+{
+    let policy = ChunkErrorPolicy::default();
+    match render_region_inner(x, z, loader, renderer, neighbours, policy, diagnostics) {
+        Ok(map) => map,
+        Err(_) => unreachable!("skipped chunks do not abort the render"),
+    }
+}
+
+/// What to do with a chunk of a region that cannot be rendered, see
+/// [`ChunkErrorPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnChunkError {
+    /// Leave the chunk blank, as if it were missing. This is what
+    /// [`render_region`] does.
+    #[default]
+    Skip,
+
+    /// Fill the chunk with a colour, eg to make corrupt chunks stand out.
+    Fill(Rgba),
+
+    /// Stop rendering the region and return the error.
+    Abort,
+}
+
+/// How [`render_region_with_policy`] handles chunks that cannot be rendered,
+/// eg because they are corrupt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct ChunkErrorPolicy {
+    pub on_error: OnChunkError,
+
+    /// Catch panics while loading and rendering each chunk, and handle them
+    /// like any other chunk that could not be rendered. Some corrupt chunks
+    /// cause panics rather than errors, which would otherwise take down the
+    /// whole render. The panic is still printed by the panic hook.
+    pub catch_panics: bool,
+}
+
+/// The chunks [`render_region_with_policy`] could not render.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RenderReport {
+    /// Why each chunk could not be rendered, with the chunk's world
+    /// coordinates, in the order the chunks were rendered.
+    pub skipped: Vec<Diagnostic>,
+
+    /// The blocks drawn in the region, if the renderer is collecting them,
+    /// see [`TopShadeRenderer::with_legend`]. This is only the blocks of this
+    /// region, not the ones the renderer collected before.
+    pub legend: Option<Legend>,
+}
+
+/// Render a region like [`render_region_with_context`], handling chunks that
+/// cannot be rendered as `policy` says. Returns the map along with a report of
+/// the chunks that could not be rendered. Missing chunks are not errors.
+///
+/// With [`OnChunkError::Abort`] the first chunk that cannot be rendered is
+/// returned as the error instead.
+///
+/// ```no_run
+/// # use fastanvil::*;
+/// # let loader = RegionFileLoader::new("region".into());
+/// let renderer = TopShadeRenderer::new(&DebugPalette, HeightMode::Trust);
+/// let mut policy = ChunkErrorPolicy::default();
+/// policy.on_error = OnChunkError::Fill([255, 0, 255, 255]);
+/// policy.catch_panics = true;
+///
+/// let (map, report) =
+///     render_region_with_policy(RCoord(0), RCoord(0), &loader, renderer, None, policy).unwrap();
+/// for skipped in report.skipped {
+///     eprintln!("{skipped}");
+/// }
+/// ```
+pub fn render_region_with_policy<P: Palette + ?Sized, S>(
+    x: RCoord,
+    z: RCoord,
+    loader: &dyn RegionLoader<S>,
+    renderer: TopShadeRenderer<P>,
+    neighbours: Option<&NeighbourHeights>,
+    policy: ChunkErrorPolicy,
+) -> Result<(RegionMap<Rgba>, RenderReport), Diagnostic>
+where
+    S: Seek + Read,
+{
+    let renderer = renderer.with_own_legend();
+    let legend = renderer.legend.clone();
+
+    let mut diagnostics = vec![];
+    let map = render_region_inner(x, z, loader, renderer, neighbours, policy, &mut diagnostics)?;
+
+    let skipped = diagnostics
+        .into_iter()
+        .filter(|d| d.kind.is_chunk_failure())
+        .collect();
+    let legend = legend.map(|legend| {
+        legend
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    });
+    Ok((map, RenderReport { skipped, legend }))
+}
+
+fn render_region_inner<P: Palette + ?Sized, S>(
+    x: RCoord,
+    z: RCoord,
+    loader: &dyn RegionLoader<S>,
+    renderer: TopShadeRenderer<P>,
+    neighbours: Option<&NeighbourHeights>,
+    policy: ChunkErrorPolicy,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<RegionMap<Rgba>, Diagnostic>
+where
+    S: Seek + Read,
+{
+    let (region_x, region_z) = (x, z);
     let mut map = RegionMap::new(x, z, [0u8; 4]);
 
     let mut region = match loader.region(x, z) {
         Some(r) => r,
-        None => return map,
+        None => return Ok(map),
     };
 
-    let mut cache: [Option<JavaChunk>; 32] = Default::default();
-
-    // TODO: actually let this fail rather than flatten the result.
-
-    // Cache the last row of chunks from the above region to allow top-shading
-    // on region boundaries.
-    if let Some(mut r) = loader.region(x, RCoord(z.0 - 1)) {
-        for (x, entry) in cache.iter_mut().enumerate() {
-            *entry = r
-                .read_chunk(x, 31)
-                .ok()
-                .flatten()
-                .and_then(|b| JavaChunk::from_bytes(&b).ok())
-        }
-    }
+    // Cache the heights of the last row of chunks from the above region to
+    // allow top-shading on region boundaries.
+    let mut cache = match neighbours {
+        Some(neighbours) => neighbours.north,
+        None => match loader.region(x, RCoord(z.0 - 1)) {
+            Some(mut r) => NeighbourHeights::south_of(&mut r, &renderer).north,
+            None => Default::default(),
+        },
+    };
 
     for z in 0usize..32 {
         for (x, cache) in cache.iter_mut().enumerate() {
             let data = map.chunk_mut(CCoord(x as isize), CCoord(z as isize));
 
-            // TODO: actually let this fail rather than flatten the result.
-            let chunk_data = region
-                .read_chunk(x, z)
-                .ok()
-                .flatten()
-                .and_then(|chunk| JavaChunk::from_bytes(&chunk).ok())
-                .map(|chunk| {
-                    // Get the chunk at the same x coordinate from the cache. This
-                    // should be the chunk that is directly above the current. We
-                    // know this because once we have processed this chunk we put it
-                    // in the cache in the same place. So the next time we get the
-                    // current one will be when we're processing directly below us.
-                    //
-                    // Thanks to the default None value this works fine for the
-                    // first row or for any missing chunks.
-                    let north = cache.as_ref();
-
-                    let res = renderer.render(&chunk, north);
-                    *cache = Some(chunk);
-                    res
-                });
-
-            if let Some(d) = chunk_data {
-                data[..].clone_from_slice(&d);
+            let start = diagnostics.len();
+
+            // Get the heights at the same x coordinate from the cache. This
+            // should be from the chunk that is directly above the current.
+            // We know this because once we have processed this chunk we put
+            // its heights in the cache in the same place. So the next time
+            // we get the current one will be when we're processing directly
+            // below us.
+            //
+            // Thanks to the default None value this works fine for the
+            // first row or for any missing chunks.
+            let north = *cache;
+            let mut render = || {
+                let chunk = load_chunk(&mut region, x, z, diagnostics)?;
+                let tile = render_chunk(&chunk, &renderer, north);
+                Some((tile, renderer.south_edge_heights(&chunk)))
+            };
+
+            let rendered = if policy.catch_panics {
+                panic::catch_unwind(AssertUnwindSafe(render)).unwrap_or_else(|payload| {
+                    let kind = DiagnosticKind::ChunkPanicked(panic_message(payload));
+                    diagnostics.push(Diagnostic::new(kind));
+                    None
+                })
             } else {
-                // In the case where we failed to load this chunk for whatever
-                // reason, we treat it as a blank part of the map. This means
-                // the cache needs to reflect this.
-                *cache = None;
+                render()
+            };
+
+            let world = (
+                CCoord(region_x.0 * 32 + x as isize),
+                CCoord(region_z.0 * 32 + z as isize),
+            );
+            for diagnostic in &mut diagnostics[start..] {
+                diagnostic.chunk = Some(world);
+            }
+
+            if let Some((tile, heights)) = rendered {
+                data[..].clone_from_slice(&tile);
+                *cache = Some(heights);
+                continue;
+            }
+
+            // In the case where we failed to load this chunk for whatever
+            // reason, we treat it as a blank part of the map. This means
+            // the cache needs to reflect this.
+            *cache = None;
+
+            let failure = diagnostics[start..]
+                .iter()
+                .find(|d| d.kind.is_chunk_failure());
+            match (failure, policy.on_error) {
+                (None, _) | (_, OnChunkError::Skip) => {}
+                (Some(_), OnChunkError::Fill(colour)) => data.fill(colour),
+                (Some(failure), OnChunkError::Abort) => return Err(failure.clone()),
             }
         }
     }
 
-    map
+    Ok(map)
+}
+
+/// The message a panic was started with, if it was given one.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "panicked".to_owned(),
+        },
+    }
+}
+
+/// Re-render only the chunks of a region that have been saved since a previous
+/// render, patching them into the map produced by that render.
+///
+/// `since` is a Unix timestamp in seconds, and should be when the previous
+/// render started. Chunks with a timestamp at or after `since` are rendered
+/// again. Since top-shading depends on the heights of the chunk to the north,
+/// the chunk south of each changed chunk is re-rendered too, including the top
+/// row of this region when the bottom row of the region above has changed.
+///
+/// Returns the chunk coordinates within the region that were re-rendered, in
+/// the order they were rendered.
+pub fn render_region_incremental<P: Palette + ?Sized, S>(
+    x: RCoord,
+    z: RCoord,
+    loader: &dyn RegionLoader<S>,
+    renderer: TopShadeRenderer<P>,
+    previous: &mut RegionMap<Rgba>,
+    since: u32,
+) -> Vec<(usize, usize)>
+where
+    S: Seek + Read,
+{
+    let mut region = match loader.region(x, z) {
+        Some(r) => r,
+        None => return vec![],
+    };
+    let mut north_region = loader.region(x, RCoord(z.0 - 1));
+
+    // If we can't read a timestamp we re-render the chunk, which will render
+    // it blank if it really is unreadable, same as a full render would.
+    let changed = |r: &mut Region<S>, x, z| r.timestamp(x, z).map_or(true, |t| t >= since);
+
+    let mut dirty = [[false; 32]; 32];
+    for (z, row) in dirty.iter_mut().enumerate() {
+        for (x, dirty) in row.iter_mut().enumerate() {
+            *dirty = changed(&mut region, x, z);
+        }
+    }
+
+    // Mark the chunk south of every changed chunk. Work from the bottom up so
+    // that newly marked chunks don't cascade down the region.
+    for z in (0..31).rev() {
+        let (north, south) = dirty.split_at_mut(z + 1);
+        for (north, south) in north[z].iter().zip(south[0].iter_mut()) {
+            *south |= *north;
+        }
+    }
+
+    // The top row is shaded by the bottom row of the region to the north.
+    if let Some(r) = north_region.as_mut() {
+        for (x, dirty) in dirty[0].iter_mut().enumerate() {
+            *dirty |= changed(r, x, 31);
+        }
+    }
+
+    let mut updated = vec![];
+
+    for (z, row) in dirty.iter().enumerate() {
+        for (x, _) in row.iter().enumerate().filter(|(_, dirty)| **dirty) {
+            let north = match z {
+                0 => north_region
+                    .as_mut()
+                    .and_then(|r| load_chunk(r, x, 31, &mut vec![])),
+                z => load_chunk(&mut region, x, z - 1, &mut vec![]),
+            };
+
+            let data = previous.chunk_mut(CCoord(x as isize), CCoord(z as isize));
+
+            match load_chunk(&mut region, x, z, &mut vec![]) {
+                Some(chunk) => data.clone_from_slice(&renderer.render(&chunk, north.as_ref())),
+                None => data.fill([0u8; 4]),
+            }
+
+            updated.push((x, z));
+        }
+    }
+
+    updated
+}
+
+/// Whether a chunk is drawn at all. Chunks that have been fully generated have
+/// a 'full' status. Others are skipped, as the way they render is
+/// unpredictable.
+pub(crate) fn is_rendered<C: Chunk + ?Sized>(chunk: &C) -> bool {
+    let status = chunk.status();
+    status == "full" || status == "spawn"
+}
+
+/// Load a chunk to render, or `None` if it is missing or cannot be loaded. Why
+/// a chunk could not be loaded is added to `diagnostics`.
+pub(crate) fn load_chunk<S: Read + Seek>(
+    region: &mut Region<S>,
+    x: usize,
+    z: usize,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<JavaChunk> {
+    let data = match region.read_chunk(x, z) {
+        Ok(data) => data?,
+        Err(e) => {
+            let kind = DiagnosticKind::UnreadableChunk(e.to_string());
+            diagnostics.push(Diagnostic::new(kind));
+            return None;
+        }
+    };
+
+    match JavaChunk::from_bytes_with_diagnostics(&data, diagnostics) {
+        Ok(chunk) => Some(chunk),
+        Err(e) => {
+            let kind = DiagnosticKind::UnparsableChunk(e.to_string());
+            diagnostics.push(Diagnostic::new(kind));
+            None
+        }
+    }
 }
 
 /// Apply top-shading to the given colour based on the relative height of the
@@ -58,6 +58,15 @@ impl Palette for RenderedPalette {
     fn pick(&self, block: &Block, biome: Option<Biome>) -> Rgba {
         let missing_colour = [255, 0, 255, 255];
 
+        self.try_pick(block, biome).unwrap_or_else(|| {
+            debug!("could not draw {}", block.name());
+            debug!("description {}", block.encoded_description());
+
+            missing_colour
+        })
+    }
+
+    fn try_pick(&self, block: &Block, biome: Option<Biome>) -> Option<Rgba> {
         // A bunch of blocks in the game seem to be special cased outside of the
         // blockstate/model mechanism. For example leaves get coloured based on
         // the tree type and the biome type, but this is not encoded in the
@@ -68,60 +77,50 @@ impl Palette for RenderedPalette {
         if let Some(id) = block.name().strip_prefix("minecraft:") {
             match id {
                 "grass" | "tall_grass" | "vine" | "fern" | "large_fern" => {
-                    return self.pick_grass(biome);
+                    return Some(self.pick_grass(biome));
                 }
                 "grass_block" => {
                     if block.snowy() {
-                        return self.pick(&SNOW_BLOCK, biome);
+                        return self.try_pick(&SNOW_BLOCK, biome);
                     } else {
-                        return self.pick_grass(biome);
+                        return Some(self.pick_grass(biome));
                     };
                 }
-                "water" | "bubble_column" => return self.pick_water(biome),
+                "water" | "bubble_column" => return Some(self.pick_water(biome)),
                 "oak_leaves" | "jungle_leaves" | "acacia_leaves" | "dark_oak_leaves"
-                | "mangrove_leaves" => return self.pick_foliage(biome),
+                | "mangrove_leaves" => return Some(self.pick_foliage(biome)),
                 "birch_leaves" => {
-                    return [0x80, 0xa7, 0x55, 255]; // game hardcodes this
+                    return Some([0x80, 0xa7, 0x55, 255]); // game hardcodes this
                 }
                 "spruce_leaves" => {
-                    return [0x61, 0x99, 0x61, 255]; // game hardcodes this
+                    return Some([0x61, 0x99, 0x61, 255]); // game hardcodes this
                 }
                 // Kelp and seagrass don't look like much from the top as
                 // they're flat. Maybe in future hard code a green tint to make
                 // it show up?
                 "kelp" | "kelp_plant" | "seagrass" | "tall_seagrass" => {
-                    return self.pick_water(biome);
+                    return Some(self.pick_water(biome));
                 }
                 "snow" => {
-                    return self.pick(&SNOW_BLOCK, biome);
+                    return self.try_pick(&SNOW_BLOCK, biome);
                 }
                 // Occurs a lot for the end, as layer 0 will be air in the void.
                 // Rendering it black makes sense in the end, but might look
                 // weird if it ends up elsewhere.
                 "air" => {
-                    return [0, 0, 0, 255];
+                    return Some([0, 0, 0, 255]);
                 }
                 "cave_air" => {
-                    return [255, 0, 0, 255]; // when does this happen??
+                    return Some([255, 0, 0, 255]); // when does this happen??
                 }
                 // Otherwise fall through to the general mechanism.
                 _ => {}
             }
         }
 
-        let col = self
-            .blockstates
+        self.blockstates
             .get(block.encoded_description())
-            .or_else(|| self.blockstates.get(block.name()));
-
-        match col {
-            Some(c) => *c,
-            None => {
-                debug!("could not draw {}", block.name());
-                debug!("description {}", block.encoded_description());
-
-                missing_colour
-            }
-        }
+            .or_else(|| self.blockstates.get(block.name()))
+            .copied()
     }
 }
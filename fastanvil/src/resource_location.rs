@@ -0,0 +1,278 @@
+use std::{cmp::Ordering, error::Error, fmt::Display, hash::Hash, str::FromStr};
+
+use serde::{de::Visitor, Deserialize, Serialize};
+
+const DEFAULT_NAMESPACE: &str = "minecraft";
+
+/// A namespaced id, eg `minecraft:stone`, as used for the names of blocks,
+/// items, biomes and so on. Ids without a namespace are in the `minecraft`
+/// namespace, so `stone` and `minecraft:stone` are equal.
+///
+/// Namespaces may contain `a-z`, `0-9`, `_`, `-` and `.`, and paths may also
+/// contain `/`. Neither may be empty. Ids (de)serialize as strings.
+///
+/// This borrows the string it was parsed from. See [`ResourceLocation`] for
+/// an owned id.
+///
+/// ```
+/// # use fastanvil::ResourceLocationRef;
+/// let id = ResourceLocationRef::parse("stone").unwrap();
+/// assert_eq!(id.namespace(), "minecraft");
+/// assert_eq!(id.path(), "stone");
+/// assert_eq!(id.to_string(), "minecraft:stone");
+///
+/// assert!(ResourceLocationRef::parse("minecraft:Stone").is_err());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ResourceLocationRef<'a> {
+    namespace: &'a str,
+    path: &'a str,
+}
+
+/// An owned [`ResourceLocationRef`].
+#[derive(Debug, Clone)]
+pub struct ResourceLocation {
+    // Always has a namespace, so is split at `colon`.
+    id: String,
+    colon: usize,
+}
+
+/// Error from parsing a [`ResourceLocation`] or [`ResourceLocationRef`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseResourceLocationError(String);
+
+impl Error for ParseResourceLocationError {}
+
+impl Display for ParseResourceLocationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'a> ResourceLocationRef<'a> {
+    /// Parse an id, checking that it only contains allowed characters.
+    pub fn parse(s: &'a str) -> Result<Self, ParseResourceLocationError> {
+        let err = |msg: &str| ParseResourceLocationError(format!("{msg}: {s:?}"));
+
+        let (namespace, path) = s.split_once(':').unwrap_or((DEFAULT_NAMESPACE, s));
+
+        if namespace.is_empty() || !namespace.bytes().all(is_namespace_char) {
+            return Err(err("invalid namespace"));
+        }
+        if path.is_empty() || !path.bytes().all(|c| c == b'/' || is_namespace_char(c)) {
+            return Err(err("invalid path"));
+        }
+
+        Ok(Self { namespace, path })
+    }
+
+    /// Split an id that is already known to be valid.
+    pub(crate) fn new_unchecked(s: &'a str) -> Self {
+        let (namespace, path) = s.split_once(':').unwrap_or((DEFAULT_NAMESPACE, s));
+        Self { namespace, path }
+    }
+
+    /// The namespace, eg `minecraft`.
+    pub fn namespace(&self) -> &'a str {
+        self.namespace
+    }
+
+    /// The path, ie everything after the namespace, eg `stone`.
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Copy the id into a [`ResourceLocation`], adding its namespace if it
+    /// was left out.
+    pub fn into_owned(self) -> ResourceLocation {
+        ResourceLocation {
+            id: format!("{}:{}", self.namespace, self.path),
+            colon: self.namespace.len(),
+        }
+    }
+}
+
+impl ResourceLocation {
+    /// Parse an id, checking that it only contains allowed characters.
+    pub fn parse(s: &str) -> Result<Self, ParseResourceLocationError> {
+        Ok(ResourceLocationRef::parse(s)?.into_owned())
+    }
+
+    /// The namespace, eg `minecraft`.
+    pub fn namespace(&self) -> &str {
+        &self.id[..self.colon]
+    }
+
+    /// The path, ie everything after the namespace, eg `stone`.
+    pub fn path(&self) -> &str {
+        &self.id[self.colon + 1..]
+    }
+
+    /// The id with its namespace, eg `minecraft:stone`.
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    /// Borrow the id as a [`ResourceLocationRef`].
+    pub fn as_borrowed(&self) -> ResourceLocationRef<'_> {
+        ResourceLocationRef {
+            namespace: self.namespace(),
+            path: self.path(),
+        }
+    }
+}
+
+fn is_namespace_char(c: u8) -> bool {
+    matches!(c, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.')
+}
+
+impl FromStr for ResourceLocation {
+    type Err = ParseResourceLocationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl<'a> From<ResourceLocationRef<'a>> for ResourceLocation {
+    fn from(id: ResourceLocationRef<'a>) -> Self {
+        id.into_owned()
+    }
+}
+
+impl Display for ResourceLocationRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+impl Display for ResourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+// Ids are compared by namespace then path, rather than as strings, so that
+// `a:b` and `a0:b` order the same whichever type they are.
+
+impl PartialEq for ResourceLocationRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.namespace, self.path) == (other.namespace, other.path)
+    }
+}
+
+impl Eq for ResourceLocationRef<'_> {}
+
+impl PartialOrd for ResourceLocationRef<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ResourceLocationRef<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace, self.path).cmp(&(other.namespace, other.path))
+    }
+}
+
+impl Hash for ResourceLocationRef<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (self.namespace, self.path).hash(state)
+    }
+}
+
+impl PartialEq for ResourceLocation {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for ResourceLocation {}
+
+impl PartialOrd for ResourceLocation {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ResourceLocation {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_borrowed().cmp(&other.as_borrowed())
+    }
+}
+
+impl Hash for ResourceLocation {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_borrowed().hash(state)
+    }
+}
+
+impl<'a> PartialEq<ResourceLocationRef<'a>> for ResourceLocation {
+    fn eq(&self, other: &ResourceLocationRef<'a>) -> bool {
+        self.as_borrowed() == *other
+    }
+}
+
+impl<'a> PartialEq<ResourceLocation> for ResourceLocationRef<'a> {
+    fn eq(&self, other: &ResourceLocation) -> bool {
+        *self == other.as_borrowed()
+    }
+}
+
+impl Serialize for ResourceLocationRef<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Serialize for ResourceLocation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.id)
+    }
+}
+
+/// Deserializing borrows the id from the input, so fails for strings that
+/// need decoding, eg ones with characters outside of ASCII. Those are never
+/// valid ids anyway.
+impl<'de: 'a, 'a> Deserialize<'de> for ResourceLocationRef<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct V;
+
+        impl<'de> Visitor<'de> for V {
+            type Value = ResourceLocationRef<'de>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a borrowed namespaced id")
+            }
+
+            fn visit_borrowed_str<E: serde::de::Error>(
+                self,
+                v: &'de str,
+            ) -> Result<Self::Value, E> {
+                ResourceLocationRef::parse(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(V)
+    }
+}
+
+impl<'de> Deserialize<'de> for ResourceLocation {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct V;
+
+        impl<'de> Visitor<'de> for V {
+            type Value = ResourceLocation;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a namespaced id")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                ResourceLocation::parse(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(V)
+    }
+}
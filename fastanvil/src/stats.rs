@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+
+use crate::{Block, Chunk, ChunkPalette, Region, Result};
+
+/// Selects blocks by name and properties, eg for [`count_blocks`].
+///
+/// Names may contain any number of `*`, each matching any run of characters,
+/// eg `minecraft:*_ore`. A block matches if its name matches any of the names
+/// given, and it has every property given. A filter without names matches any
+/// name.
+///
+/// ```
+/// # use fastanvil::{Block, BlockFilter};
+/// let filter = BlockFilter::new()
+///     .name("minecraft:*_ore")
+///     .name("minecraft:ancient_debris")
+///     .property("lit", "false");
+///
+/// let block: Block = "minecraft:redstone_ore[lit=false]".parse().unwrap();
+/// assert!(filter.matches(&block));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockFilter {
+    names: Vec<String>,
+    properties: Vec<(String, String)>,
+}
+
+impl BlockFilter {
+    /// A filter that matches every block.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also match blocks with this name.
+    pub fn name(mut self, pattern: impl Into<String>) -> Self {
+        self.names.push(pattern.into());
+        self
+    }
+
+    /// Only match blocks with this property set to this value.
+    pub fn property(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn matches(&self, block: &Block) -> bool {
+        let name_matches =
+            self.names.is_empty() || self.names.iter().any(|p| glob_match(p, block.name()));
+
+        name_matches
+            && self
+                .properties
+                .iter()
+                .all(|(k, v)| block.property(k) == Some(v.as_str()))
+    }
+}
+
+/// Count the blocks in a chunk that match the filter, keyed by block name.
+///
+/// Where the chunk supports it (see [`Chunk::section_block_counts`]) this
+/// counts how often each palette index occurs in a section and only checks
+/// the filter once per palette entry, rather than looking at every block.
+///
+/// Summing the counts over every chunk in a region:
+///
+/// ```no_run
+/// # use std::collections::HashMap;
+/// # use fastanvil::{count_blocks, BlockFilter, JavaChunk, Region};
+/// # let file = std::fs::File::open("r.0.0.mca").unwrap();
+/// let mut region = Region::from_stream(file).unwrap();
+/// let filter = BlockFilter::new().name("minecraft:*_ore");
+/// let mut total: HashMap<String, u64> = HashMap::new();
+///
+/// for chunk in region.iter().flatten() {
+///     if let Ok(chunk) = JavaChunk::from_bytes(&chunk.data) {
+///         for (name, count) in count_blocks(&chunk, &filter) {
+///             *total.entry(name).or_default() += count;
+///         }
+///     }
+/// }
+/// ```
+pub fn count_blocks(chunk: &impl Chunk, filter: &BlockFilter) -> HashMap<String, u64> {
+    let mut counts: HashMap<String, u64> = HashMap::new();
+    let mut add = |block: &Block, count: u64| {
+        if count > 0 && filter.matches(block) {
+            match counts.get_mut(block.name()) {
+                Some(total) => *total += count,
+                None => {
+                    counts.insert(block.name().to_owned(), count);
+                }
+            }
+        }
+    };
+
+    let range = chunk.y_range();
+    let mut sec_y = range.start.div_euclid(16) * 16;
+
+    while sec_y < range.end {
+        match chunk.section_block_counts(sec_y) {
+            Some(section) => {
+                for (block, count) in section {
+                    add(block, count as u64);
+                }
+            }
+            None => {
+                let ys = sec_y.max(range.start)..(sec_y + 16).min(range.end);
+                for y in ys {
+                    for z in 0..16 {
+                        for x in 0..16 {
+                            if let Some(block) = chunk.block(x, y, z) {
+                                add(block, 1);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        sec_y += 16;
+    }
+
+    counts
+}
+
+/// Find the chunks of a region whose palettes have a block matching
+/// `predicate`, giving the names of the matching blocks for each, keyed by the
+/// chunk's coordinates within the region. Names are sorted and given once
+/// each.
+///
+/// Only the palettes of each chunk are read, using [`ChunkPalette`], so this
+/// is much quicker than looking at every block but has the same caveat: a
+/// block in a palette might no longer be in the chunk. Chunks that cannot be
+/// read or parsed are skipped.
+///
+/// ```no_run
+/// # use fastanvil::{find_blocks, BlockFilter, Region};
+/// # let file = std::fs::File::open("r.0.0.mca").unwrap();
+/// let mut region = Region::from_stream(file).unwrap();
+/// let filter = BlockFilter::new().name("minecraft:ancient_debris");
+///
+/// for ((x, z), _) in find_blocks(&mut region, |b| filter.matches(b)).unwrap() {
+///     println!("chunk {x}, {z} might have ancient debris");
+/// }
+/// ```
+pub fn find_blocks<S: Read + Seek>(
+    region: &mut Region<S>,
+    mut predicate: impl FnMut(&Block) -> bool,
+) -> Result<HashMap<(usize, usize), Vec<String>>> {
+    let mut found = HashMap::new();
+
+    region.scan_chunks(|x, z, data| -> Result<()> {
+        let Some(palette) = data
+            .ok()
+            .and_then(|data| ChunkPalette::from_bytes(data).ok())
+        else {
+            return Ok(());
+        };
+
+        let mut names: Vec<_> = palette
+            .blocks()
+            .iter()
+            .filter(|b| predicate(b))
+            .map(|b| b.name().to_owned())
+            .collect();
+
+        if !names.is_empty() {
+            names.sort_unstable();
+            names.dedup();
+            found.insert((x, z), names);
+        }
+        Ok(())
+    })?;
+
+    Ok(found)
+}
+
+/// Count how often each palette entry is used, given the palette index of
+/// every block in a section. Indices outside of the palette are ignored.
+pub(crate) fn palette_counts(
+    palette: &[Block],
+    indices: impl Iterator<Item = usize>,
+) -> Vec<(&Block, usize)> {
+    let mut counts = vec![0; palette.len()];
+    for i in indices {
+        if let Some(count) = counts.get_mut(i) {
+            *count += 1;
+        }
+    }
+
+    palette.iter().zip(counts).collect()
+}
+
+/// Match a name against a pattern where each `*` matches any run of
+/// characters, including none.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+
+    // There is always a first part, even for an empty pattern.
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match name.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let mut parts: Vec<&str> = parts.collect();
+    let last = match parts.pop() {
+        Some(last) => last,
+        // No wildcards, so the name must be exactly the pattern.
+        None => return rest.is_empty(),
+    };
+
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.len() >= last.len() && rest.ends_with(last)
+}
@@ -0,0 +1,86 @@
+use std::io::Cursor;
+
+use crate::{
+    chunk_activity, CCoord, Chunk, ChunkActivity, CompressionScheme::Zlib, JavaChunk,
+    LazyJavaChunk, Region,
+};
+
+const ETHO: &[u8] = crate::fixtures::chunk_1_16();
+const CHUNK_1_12: &[u8] = crate::fixtures::chunk_1_12();
+const CHUNK_1_18: &[u8] = crate::fixtures::chunk_1_18();
+
+#[test]
+fn chunks_have_inhabited_time_and_last_update() {
+    let pre18 = JavaChunk::from_bytes(ETHO).unwrap();
+    assert_eq!(pre18.inhabited_time(), Some(49651));
+    assert_eq!(pre18.last_update(), Some(212680109));
+
+    let current = JavaChunk::from_bytes(CHUNK_1_18).unwrap();
+    assert_eq!(current.inhabited_time(), Some(0));
+    assert_eq!(current.last_update(), Some(11295));
+
+    let lazy = LazyJavaChunk::from_bytes(CHUNK_1_18).unwrap();
+    assert_eq!(lazy.inhabited_time(), Some(0));
+    assert_eq!(lazy.last_update(), Some(11295));
+}
+
+#[test]
+fn chunk_activity_of_region() {
+    let mut region = Region::new(Cursor::new(vec![])).unwrap();
+    region.write_chunk(0, 0, CHUNK_1_18).unwrap();
+    region.write_chunk(3, 1, ETHO).unwrap();
+    region.write_chunk(5, 1, CHUNK_1_12).unwrap();
+    region.write_chunk(5, 5, &[1, 2, 3]).unwrap();
+    // Corrupt, so reading it fails.
+    region.write_raw_chunk(6, 6, Zlib, &[1, 2, 3]).unwrap();
+
+    let activity = chunk_activity(&mut region).unwrap();
+    assert_eq!(
+        activity,
+        vec![
+            ChunkActivity {
+                x: CCoord(15),
+                z: CCoord(7),
+                inhabited_time: 0,
+                last_update: 11295,
+            },
+            ChunkActivity {
+                x: CCoord(-5),
+                z: CCoord(-32),
+                inhabited_time: 49651,
+                last_update: 212680109,
+            },
+            ChunkActivity {
+                x: CCoord(0),
+                z: CCoord(7),
+                inhabited_time: 153,
+                last_update: 900,
+            },
+        ]
+    );
+}
+
+#[test]
+fn chunk_activity_defaults_missing_values_to_zero() {
+    let chunk = fastnbt::to_bytes(&fastnbt::nbt!({
+        "DataVersion": 3465,
+        "xPos": 1,
+        "zPos": -2,
+        "Status": "minecraft:full",
+    }))
+    .unwrap();
+
+    let mut region = Region::new(Cursor::new(vec![])).unwrap();
+    region.write_chunk(0, 0, &chunk).unwrap();
+
+    let activity = chunk_activity(&mut region).unwrap();
+    assert_eq!(
+        activity,
+        vec![ChunkActivity {
+            x: CCoord(1),
+            z: CCoord(-2),
+            inhabited_time: 0,
+            last_update: 0,
+        }]
+    );
+}
@@ -0,0 +1,73 @@
+use std::io::Cursor;
+
+use alloc_counter::{CountingAlloc, Counts};
+
+use crate::Region;
+
+const CHUNKS: &[&[u8]] = &[
+    include_bytes!("../../resources/chunk.nbt"),
+    crate::fixtures::chunk_1_17(),
+    crate::fixtures::chunk_1_18(),
+    crate::fixtures::chunk_1_16(),
+];
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn counting(f: impl FnOnce()) -> Counts {
+    alloc_counter::counting(f).0
+}
+
+/// A region with the first `size` by `size` chunks filled.
+fn region(size: usize) -> Region<Cursor<Vec<u8>>> {
+    let mut region = Region::new(Cursor::new(vec![])).unwrap();
+    for z in 0..size {
+        for x in 0..size {
+            region
+                .write_chunk(x, z, CHUNKS[(x + z) % CHUNKS.len()])
+                .unwrap();
+        }
+    }
+    region
+}
+
+#[test]
+fn reused_buffer_reduces_allocator_traffic() {
+    let mut region = region(8);
+
+    let fresh = counting(|| {
+        for z in 0..8 {
+            for x in 0..8 {
+                region.read_chunk(x, z).unwrap().unwrap();
+            }
+        }
+    });
+
+    let mut buf = vec![];
+    let reused = counting(|| {
+        for z in 0..8 {
+            for x in 0..8 {
+                assert!(region.read_chunk_into(x, z, &mut buf).unwrap());
+            }
+        }
+    });
+
+    assert!(reused.allocs + reused.reallocs < fresh.allocs + fresh.reallocs);
+    // The buffer only grows for the biggest chunk it sees.
+    assert!(reused.reallocs <= 2, "{reused:?}");
+}
+
+#[test]
+fn size_hint_avoids_growing_output() {
+    let mut region = region(CHUNKS.len());
+
+    for (x, chunk) in CHUNKS.iter().enumerate() {
+        let counts = counting(|| {
+            let data = region.read_chunk(x, 0).unwrap().unwrap();
+            assert_eq!(data, *chunk);
+        });
+
+        // The output is reserved up front, and at most doubled once.
+        assert!(counts.reallocs <= 1, "{counts:?}");
+    }
+}
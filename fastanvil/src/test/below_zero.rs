@@ -0,0 +1,197 @@
+use fastnbt::{nbt, LongArray, Value};
+
+use crate::{
+    count_blocks, fixtures, recompute_heightmap, BlockFilter, Chunk, HeightMode, HeightmapKind,
+    JavaChunk, LazyJavaChunk,
+};
+
+/// A 1.18 heightmap with the column at `x, z` at `height(x, z)`, for a world
+/// starting at `y_min`.
+fn heightmap(height: impl Fn(usize, usize) -> isize, y_min: isize) -> LongArray {
+    let mut longs = vec![0; 37];
+    for i in 0..256 {
+        let stored = (height(i % 16, i / 16) - y_min) as i64;
+        longs[i / 7] |= stored << (9 * (i % 7));
+    }
+    LongArray::new(longs)
+}
+
+/// A 1.18 chunk with the given sections, the lowest being at `y_pos`.
+fn chunk(y_pos: i32, sections: Vec<Value>, surface: impl Fn(usize, usize) -> isize) -> Vec<u8> {
+    fastnbt::to_bytes(&nbt!({
+        "DataVersion": 2860,
+        "Status": "full",
+        "yPos": y_pos,
+        "sections": sections,
+        "Heightmaps": {
+            "MOTION_BLOCKING": heightmap(surface, y_pos as isize * 16),
+        },
+    }))
+    .unwrap()
+}
+
+fn section(y: i8, block: &str) -> Value {
+    nbt!({"Y": y, "block_states": {"palette": [{"Name": block}]}})
+}
+
+fn name<C: Chunk + ?Sized>(chunk: &C, y: isize) -> Option<&str> {
+    chunk.block(0, y, 0).map(|b| b.name())
+}
+
+#[test]
+fn blocks_at_the_edges_of_the_world() {
+    let data = fixtures::chunk_1_18();
+    let java = JavaChunk::from_bytes(data).unwrap();
+    let lazy = LazyJavaChunk::from_bytes(data).unwrap();
+    let boxed = JavaChunk::boxed(data).unwrap();
+    let chunks: [&dyn Chunk; 3] = [&java, &lazy, boxed.as_ref()];
+
+    for chunk in chunks {
+        assert_eq!(chunk.y_range(), -64..320);
+        assert_eq!(name(chunk, -65), None);
+        assert_eq!(name(chunk, -64), Some("minecraft:bedrock"));
+        assert_eq!(name(chunk, -1), Some("minecraft:deepslate"));
+        assert_eq!(name(chunk, 0), Some("minecraft:deepslate"));
+        assert_eq!(name(chunk, 319), Some("minecraft:air"));
+        assert_eq!(name(chunk, 320), None);
+
+        assert!(chunk.biome(0, -64, 0).is_some());
+        assert!(chunk.biome(0, 319, 0).is_some());
+        assert!(chunk.biome(0, -65, 0).is_none());
+    }
+}
+
+#[test]
+fn counting_includes_blocks_below_zero() {
+    let chunk = JavaChunk::from_bytes(fixtures::chunk_1_18()).unwrap();
+    let counts = count_blocks(&chunk, &BlockFilter::new().name("minecraft:bedrock"));
+
+    let below_zero = (-64..0)
+        .flat_map(|y| (0..16).flat_map(move |z| (0..16).map(move |x| (x, y, z))))
+        .filter(|&(x, y, z)| chunk.block(x, y, z).unwrap().name() == "minecraft:bedrock")
+        .count() as u64;
+
+    assert!(below_zero > 0);
+    assert_eq!(counts["minecraft:bedrock"], below_zero);
+}
+
+#[test]
+fn sections_below_y_pos_are_not_part_of_the_world() {
+    // 1.18 saves sections outside of the world that only hold light.
+    let data = chunk(
+        -4,
+        vec![
+            nbt!({"Y": -5_i8, "SkyLight": [B; 0, 0, 0, 0]}),
+            section(-4, "minecraft:stone"),
+            section(-3, "minecraft:dirt"),
+            section(-2, "minecraft:air"),
+        ],
+        |_, _| 0,
+    );
+
+    let java = JavaChunk::from_bytes(&data).unwrap();
+    let lazy = LazyJavaChunk::from_bytes(&data).unwrap();
+    let chunks: [&dyn Chunk; 2] = [&java, &lazy];
+
+    for chunk in chunks {
+        assert_eq!(chunk.y_range(), -64..-16);
+        assert_eq!(name(chunk, -65), None);
+        assert_eq!(name(chunk, -64), Some("minecraft:stone"));
+        assert_eq!(name(chunk, -49), Some("minecraft:stone"));
+        assert_eq!(name(chunk, -48), Some("minecraft:dirt"));
+        assert_eq!(chunk.surface_height(0, 0, HeightMode::Trust), 0);
+        assert_eq!(chunk.surface_height(15, 15, HeightMode::Trust), 0);
+    }
+}
+
+#[test]
+fn missing_low_sections_keep_the_bottom_of_the_world() {
+    let data = chunk(
+        -4,
+        vec![section(-1, "minecraft:stone"), section(0, "minecraft:air")],
+        |_, _| 0,
+    );
+    let chunk = JavaChunk::from_bytes(&data).unwrap();
+
+    assert_eq!(chunk.y_range(), -64..16);
+    assert_eq!(name(&chunk, -64), None);
+    assert_eq!(name(&chunk, -17), None);
+    assert_eq!(name(&chunk, -16), Some("minecraft:stone"));
+    assert_eq!(name(&chunk, -1), Some("minecraft:stone"));
+    assert_eq!(name(&chunk, 0), Some("minecraft:air"));
+    assert_eq!(chunk.surface_height(0, 0, HeightMode::Trust), 0);
+    assert_eq!(chunk.surface_height(0, 0, HeightMode::Calculate), 0);
+}
+
+#[test]
+fn surfaces_can_be_below_zero() {
+    let data = chunk(
+        -4,
+        vec![
+            section(-4, "minecraft:stone"),
+            section(-3, "minecraft:air"),
+            section(-2, "minecraft:air"),
+        ],
+        |_, _| -48,
+    );
+    let chunk = JavaChunk::from_bytes(&data).unwrap();
+
+    assert_eq!(chunk.surface_height(0, 0, HeightMode::Trust), -48);
+    assert_eq!(chunk.surface_height(0, 0, HeightMode::Calculate), -48);
+
+    // A void column is at the very bottom of the world.
+    let data = chunk_void();
+    let chunk = JavaChunk::from_bytes(&data).unwrap();
+    assert_eq!(chunk.surface_height(0, 0, HeightMode::Trust), -64);
+    assert_eq!(
+        recompute_heightmap(&chunk, HeightmapKind::WorldSurface),
+        [-64; 256]
+    );
+}
+
+fn chunk_void() -> Vec<u8> {
+    chunk(-4, vec![section(-4, "minecraft:air")], |_, _| -64)
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn rendering_is_the_same_at_any_height() {
+    use crate::{DebugPalette, TopShadeRenderer};
+
+    // The same terrain, with its surface around zero or well above it.
+    let terrain = |y_pos: i32| {
+        let sections = (0..4)
+            .map(|i| {
+                let block = if i < 2 {
+                    "minecraft:stone"
+                } else {
+                    "minecraft:air"
+                };
+                section(y_pos as i8 + i as i8, block)
+            })
+            .collect();
+        let bottom = y_pos as isize * 16;
+        chunk(y_pos, sections, move |x, z| {
+            bottom + 28 + ((x + z) % 5) as isize
+        })
+    };
+
+    let palette = DebugPalette;
+    let renderer = TopShadeRenderer::new(&palette, HeightMode::Trust);
+
+    let low = JavaChunk::from_bytes(&terrain(-2)).unwrap();
+    let high = JavaChunk::from_bytes(&terrain(4)).unwrap();
+    assert_eq!(low.surface_height(0, 0, HeightMode::Trust), -4);
+    assert_eq!(low.surface_height(4, 0, HeightMode::Trust), 0);
+    assert_eq!(high.surface_height(4, 0, HeightMode::Trust), 96);
+
+    let rendered = renderer.render(&low, None);
+    assert_eq!(rendered, renderer.render(&high, None));
+    assert_eq!(
+        renderer.render_with_north_heights(&low, Some([-2; 16])),
+        renderer.render_with_north_heights(&high, Some([94; 16])),
+    );
+
+    // Shading still tells the heights apart.
+    assert_ne!(rendered[16], rendered[20]);
+}
@@ -0,0 +1,125 @@
+use crate::{Block, BlockArchetype, JavaChunk};
+
+const CHUNK_21W44A_1: &[u8] = crate::fixtures::chunk_1_18();
+
+#[test]
+fn display_sorts_properties() {
+    let block: Block = "minecraft:oak_stairs[half=bottom,waterlogged=false,facing=east]"
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        block.to_string(),
+        "minecraft:oak_stairs[facing=east,half=bottom,waterlogged=false]"
+    );
+}
+
+#[test]
+fn parse_bare_name() {
+    let block: Block = "minecraft:stone".parse().unwrap();
+    assert_eq!(block.name(), "minecraft:stone");
+    assert_eq!(block.encoded_description(), "minecraft:stone|");
+    assert_eq!(block.to_string(), "minecraft:stone");
+
+    let block: Block = "minecraft:stone[]".parse().unwrap();
+    assert_eq!(block.to_string(), "minecraft:stone");
+}
+
+#[test]
+fn parse_defaults_to_minecraft_namespace() {
+    let block: Block = "air".parse().unwrap();
+    assert_eq!(block.name(), "minecraft:air");
+    assert_eq!(block.archetype, BlockArchetype::Airy);
+
+    let block: Block = "mymod:thing".parse().unwrap();
+    assert_eq!(block.name(), "mymod:thing");
+}
+
+#[test]
+fn parse_matches_palette_encoding() {
+    let block: Block = "minecraft:grass_block[snowy=true]".parse().unwrap();
+    assert_eq!(
+        block.encoded_description(),
+        "minecraft:grass_block|snowy=true"
+    );
+    assert!(block.snowy());
+
+    // Same filtering as blocks read from a palette.
+    let block: Block = "minecraft:lever[powered=true,face=floor]".parse().unwrap();
+    assert_eq!(block.encoded_description(), "minecraft:lever|face=floor");
+}
+
+#[test]
+fn parse_allows_whitespace_in_properties() {
+    let block: Block = "minecraft:oak_stairs[ facing = east , half=bottom ]"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        block.to_string(),
+        "minecraft:oak_stairs[facing=east,half=bottom]"
+    );
+}
+
+#[test]
+fn parse_errors() {
+    for input in [
+        "",
+        "[facing=east]",
+        "minecraft:stone[",
+        "minecraft:stone]",
+        "minecraft:stone[facing]",
+        "minecraft:stone[facing=]",
+        "minecraft:stone[=east]",
+        "minecraft:stone[facing=east,]",
+        "minecraft:stone[facing=east,facing=west]",
+        "minecraft:stone[facing=east][half=top]",
+        "minecraft:stone[facing=east]x",
+        "minecraft:oak stairs",
+    ] {
+        assert!(
+            input.parse::<Block>().is_err(),
+            "{input:?} should not parse"
+        );
+    }
+}
+
+#[test]
+fn uppercase_modded_names() {
+    let block: Block = "MyMod:Copper_Ore[Lit=true]".parse().unwrap();
+    assert_eq!(block.name(), "MyMod:Copper_Ore");
+    assert_eq!(block.to_string(), "MyMod:Copper_Ore[Lit=true]");
+
+    let block: Block = "Copper_Ore".parse().unwrap();
+    assert_eq!(block.name(), "minecraft:Copper_Ore");
+
+    let value = fastnbt::nbt!({"Name": "MyMod:Copper_Ore"});
+    let block: Block = fastnbt::from_value(&value).unwrap();
+    assert_eq!(block.name(), "MyMod:Copper_Ore");
+
+    // Only the case is let off.
+    assert!("MyMod:Copper Ore".parse::<Block>().is_err());
+    let value = fastnbt::nbt!({"Name": "MyMod:Copper Ore"});
+    assert!(fastnbt::from_value::<Block>(&value).is_err());
+}
+
+#[test]
+fn round_trip_chunk_palette() {
+    let chunk = JavaChunk::from_bytes(CHUNK_21W44A_1).unwrap();
+
+    let mut with_properties = 0;
+    for section in chunk.sections.unwrap().sections() {
+        for block in section.palette() {
+            let parsed: Block = block.to_string().parse().unwrap();
+
+            assert_eq!(parsed.to_string(), block.to_string());
+            assert_eq!(parsed.name(), block.name());
+            assert_eq!(parsed.encoded_description(), block.encoded_description());
+            assert_eq!(parsed.archetype, block.archetype);
+            if block.to_string().contains('[') {
+                with_properties += 1;
+            }
+        }
+    }
+
+    assert!(with_properties > 0);
+}
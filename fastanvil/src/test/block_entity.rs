@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use fastnbt::{nbt, to_bytes};
+
+use crate::{SignSide, SignText};
+
+fn sign(name: &str) -> SignText {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources/block_entities")
+        .join(name);
+    fastnbt::from_bytes(&std::fs::read(path).unwrap()).unwrap()
+}
+
+fn welcome() -> [String; 4] {
+    [
+        r#"{"text":"Welcome"}"#.to_owned(),
+        r#"{"extra":[{"bold":true,"text":"to the"}],"text":""}"#.to_owned(),
+        r#"{"text":"base"}"#.to_owned(),
+        r#"{"text":""}"#.to_owned(),
+    ]
+}
+
+#[test]
+fn sign_before_1_20() {
+    let sign = sign("sign-1.19.nbt");
+    assert_eq!(
+        sign.front,
+        SignSide {
+            messages: welcome(),
+            color: Some("blue".to_owned()),
+            glowing: Some(true),
+        }
+    );
+    assert_eq!(sign.back, SignSide::default());
+    assert_eq!(sign.waxed, None);
+}
+
+#[test]
+fn sign_from_1_20() {
+    let sign = sign("sign-1.20.nbt");
+    assert_eq!(
+        sign.front,
+        SignSide {
+            messages: welcome(),
+            color: Some("blue".to_owned()),
+            glowing: Some(true),
+        }
+    );
+    assert_eq!(sign.back.messages[0], r#"{"text":"Back"}"#);
+    assert_eq!(sign.back.color.as_deref(), Some("black"));
+    assert_eq!(sign.back.glowing, Some(false));
+    assert_eq!(sign.waxed, Some(true));
+}
+
+#[test]
+fn both_layouts_give_the_same_front() {
+    assert_eq!(sign("sign-1.19.nbt").front, sign("sign-1.20.nbt").front);
+}
+
+#[test]
+fn sign_with_missing_fields() {
+    let bytes = to_bytes(&nbt!({ "id": "minecraft:sign", "Text1": "\"a\"" })).unwrap();
+    let sign: SignText = fastnbt::from_bytes(&bytes).unwrap();
+    assert_eq!(sign.front.messages[0], "\"a\"");
+    assert_eq!(sign.front.messages[3], "");
+    assert_eq!(sign.front.color, None);
+
+    let bytes = to_bytes(&nbt!({ "front_text": {} })).unwrap();
+    let sign: SignText = fastnbt::from_bytes(&bytes).unwrap();
+    assert_eq!(sign, SignText::default());
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn plain_lines() {
+    let lines = sign("sign-1.20.nbt").front.plain_lines();
+    assert_eq!(lines, ["Welcome", "to the", "base", ""]);
+    assert_eq!(sign("sign-1.19.nbt").front.plain_lines(), lines);
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use fastnbt::Value;
+
+use crate::{
+    data::{pack_chunk_pos, unpack_chunk_pos, ForcedChunks, IdCounts, Scoreboard},
+    CCoord,
+};
+
+fn resource(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources/data")
+        .join(name)
+}
+
+#[test]
+fn chunk_pos_packing() {
+    for (x, z) in [(0, 0), (-1, 2), (5, -7), (i32::MIN, i32::MAX)] {
+        let pos = (CCoord(x as isize), CCoord(z as isize));
+        assert_eq!(unpack_chunk_pos(pack_chunk_pos(pos.0, pos.1)), pos);
+    }
+
+    assert_eq!(pack_chunk_pos(CCoord(1), CCoord(2)), 1 | 2 << 32);
+    assert_eq!(unpack_chunk_pos(-1), (CCoord(-1), CCoord(-1)));
+    assert_eq!(unpack_chunk_pos(0xffff_ffff), (CCoord(-1), CCoord(0)));
+}
+
+#[test]
+fn forced_chunks() {
+    let forced = ForcedChunks::from_path(&resource("chunks.dat")).unwrap();
+    assert_eq!(forced.data_version, Some(3465));
+    assert_eq!(
+        forced.chunks,
+        [(0, 0), (-1, 2), (5, -7), (-30000, 29999)].map(|(x, z)| (CCoord(x), CCoord(z)))
+    );
+}
+
+#[test]
+fn forced_chunks_without_any_forced() {
+    let nbt = fastnbt::to_bytes(&fastnbt::nbt!({ "data": {}, "DataVersion": 3465 })).unwrap();
+    let forced = ForcedChunks::from_bytes(&nbt).unwrap();
+    assert!(forced.chunks.is_empty());
+}
+
+#[test]
+fn scoreboard() {
+    let scoreboard = Scoreboard::from_path(&resource("scoreboard.dat")).unwrap();
+    assert_eq!(scoreboard.data_version, Some(3465));
+
+    let deaths = scoreboard.objective("deaths").unwrap();
+    assert_eq!(deaths.criteria, "deathCount");
+    assert_eq!(deaths.render_type.as_deref(), Some("integer"));
+    assert_eq!(
+        deaths.display_name,
+        Some(Value::String(r#"{"text":"Deaths"}"#.to_owned()))
+    );
+    assert!(scoreboard.objective("missing").is_none());
+
+    let scores: Vec<_> = scoreboard
+        .scores_for("deaths")
+        .map(|s| (s.holder.as_str(), s.score, s.locked))
+        .collect();
+    assert_eq!(scores, [("Steve", 3, false), ("Alex", 12, true)]);
+    assert_eq!(scoreboard.scores_for("health").count(), 1);
+
+    assert_eq!(scoreboard.display_slots["slot_1"], "deaths");
+}
+
+#[test]
+fn id_counts() {
+    let counts = IdCounts::from_path(&resource("idcounts.dat")).unwrap();
+    assert_eq!(counts.map, Some(7));
+    assert_eq!(counts.data_version, Some(3465));
+}
+
+#[test]
+fn missing_data_file_errors() {
+    let err = Scoreboard::from_path(&resource("missing.dat")).unwrap_err();
+    assert!(err.io_error().is_some());
+}
@@ -0,0 +1,92 @@
+use fastnbt::{nbt, LongArray};
+
+use crate::{CCoord, Diagnostic, DiagnosticKind, JavaChunk};
+
+const CHUNK_1_17_1: &[u8] = crate::fixtures::chunk_1_17();
+const CHUNK_21W44A_1: &[u8] = crate::fixtures::chunk_1_18();
+
+fn diagnostics(data: &[u8]) -> Vec<DiagnosticKind> {
+    let mut diagnostics = vec![];
+    JavaChunk::from_bytes_with_diagnostics(data, &mut diagnostics).unwrap();
+    diagnostics.into_iter().map(|d| d.kind).collect()
+}
+
+#[test]
+fn normal_chunks_have_no_diagnostics() {
+    assert_eq!(diagnostics(CHUNK_1_17_1), vec![]);
+    assert_eq!(diagnostics(CHUNK_21W44A_1), vec![]);
+}
+
+#[test]
+fn worked_around_data_is_reported() {
+    let chunk = nbt!({
+        "DataVersion": 9999,
+        "Status": "full",
+        "sections": [
+            {
+                "Y": 0_i8,
+                // States without a palette to index.
+                "block_states": {"palette": [], "data": LongArray::new(vec![0; 256])},
+                "biomes": {"palette": ["minecraft:plains"]},
+            },
+            {
+                "Y": 1_i8,
+                "block_states": {"palette": [{"Name": "minecraft:stone"}]},
+                "biomes": {"palette": ["minecraft:not_a_biome"]},
+            },
+        ],
+    });
+
+    let data = fastnbt::to_bytes(&chunk).unwrap();
+    assert_eq!(
+        diagnostics(&data),
+        vec![
+            DiagnosticKind::InvalidBlockStates { section_y: 0 },
+            DiagnosticKind::UnknownBiome { section_y: 1 },
+            DiagnosticKind::UnknownDataVersion(9999),
+            DiagnosticKind::MissingHeightmap,
+        ]
+    );
+}
+
+#[test]
+fn unknown_pre18_biome_ids_are_reported() {
+    let mut biomes = vec![1; 1024];
+    biomes[100] = 12345;
+
+    let chunk = nbt!({
+        "DataVersion": 2586,
+        "Level": {
+            "xPos": 0,
+            "zPos": 0,
+            "Status": "full",
+            "Biomes": fastnbt::IntArray::new(biomes),
+            "Heightmaps": {"MOTION_BLOCKING": LongArray::new(vec![0; 37])},
+            "Sections": [
+                {"Y": 0_i8, "Palette": [{"Name": "minecraft:stone"}]},
+                {"Y": 1_i8, "Palette": [{"Name": "minecraft:stone"}]},
+            ],
+        },
+    });
+
+    let data = fastnbt::to_bytes(&chunk).unwrap();
+    assert_eq!(
+        diagnostics(&data),
+        vec![DiagnosticKind::UnknownBiome { section_y: 1 }]
+    );
+}
+
+#[test]
+fn diagnostic_display() {
+    let mut diagnostic = Diagnostic {
+        kind: DiagnosticKind::MissingHeightmap,
+        chunk: None,
+    };
+    assert_eq!(diagnostic.to_string(), "missing heightmap, recalculated");
+
+    diagnostic.chunk = Some((CCoord(-3), CCoord(4)));
+    assert_eq!(
+        diagnostic.to_string(),
+        "chunk -3, 4: missing heightmap, recalculated"
+    );
+}
@@ -0,0 +1,166 @@
+use fastnbt::{nbt, to_bytes, Value};
+
+use crate::{
+    entity::{Entity, EntityChunk, ItemStack, TypedEntity, VillagerData},
+    fixtures, CCoord, Region,
+};
+
+fn entities() -> Vec<TypedEntity> {
+    let mut region = Region::from_stream(fixtures::region_entities()).unwrap();
+    let data = region.read_chunk(0, 0).unwrap().unwrap();
+    let chunk = EntityChunk::from_bytes(&data).unwrap();
+
+    assert_eq!(chunk.data_version, 2860);
+    assert_eq!(chunk.position, (CCoord(0), CCoord(0)));
+    chunk.entities
+}
+
+fn entity(nbt: Value) -> TypedEntity {
+    fastnbt::from_bytes(&to_bytes(&nbt).unwrap()).unwrap()
+}
+
+#[test]
+fn item() {
+    let TypedEntity::Item(item) = &entities()[0] else {
+        panic!("not an item");
+    };
+
+    assert_eq!(item.entity.id, "minecraft:item");
+    assert_eq!(item.entity.pos, [3.5, 64.0, 7.25]);
+    assert_eq!(item.entity.motion, [0.0, -0.04, 0.0]);
+    assert_eq!(item.entity.rotation, [90.0, 0.0]);
+    assert_eq!(
+        item.entity.uuid,
+        Some(0x00000001_00000002_00000003_00000004)
+    );
+    assert_eq!(item.entity.custom_name, None);
+    assert_eq!(item.entity.other["Age"], Value::Short(120));
+    assert!(!item.entity.other.contains_key("Item"));
+
+    let stack = item.item.as_ref().unwrap();
+    assert_eq!(stack.id, "minecraft:diamond");
+    assert_eq!(stack.count, 3);
+    assert!(stack.other.is_empty());
+}
+
+#[test]
+fn item_frame() {
+    let TypedEntity::ItemFrame(frame) = &entities()[1] else {
+        panic!("not an item frame");
+    };
+
+    assert_eq!(frame.entity.id, "minecraft:glow_item_frame");
+    assert_eq!(frame.facing, Some(3));
+    assert_eq!(
+        frame.entity.uuid,
+        Some(0xFFFFFFFB_00000006_FFFFFFF9_00000008)
+    );
+    assert_eq!(frame.entity.other["TileY"], Value::Int(65));
+
+    let stack = frame.item.as_ref().unwrap();
+    assert_eq!(stack.id, "minecraft:filled_map");
+    assert_eq!(stack.count, 1);
+    assert_eq!(
+        Value::Compound(stack.other.clone()).pointer("/tag/map"),
+        Some(&Value::Int(7))
+    );
+}
+
+#[test]
+fn armor_stand() {
+    let TypedEntity::ArmorStand(stand) = &entities()[2] else {
+        panic!("not an armor stand");
+    };
+
+    assert_eq!(stand.pose.head, Some([10.0, 20.0, 0.0]));
+    assert_eq!(stand.pose.left_arm, Some([-90.0, 0.0, 0.0]));
+    assert_eq!(stand.pose.body, None);
+    assert_eq!(stand.entity.other["ShowArms"], Value::Byte(1));
+    assert!(!stand.entity.other.contains_key("Pose"));
+}
+
+#[test]
+fn villager() {
+    let TypedEntity::Villager(villager) = &entities()[3] else {
+        panic!("not a villager");
+    };
+
+    assert_eq!(
+        villager.data,
+        Some(VillagerData {
+            profession: "minecraft:librarian".to_owned(),
+            level: 2,
+            kind: "minecraft:plains".to_owned(),
+        })
+    );
+    assert_eq!(
+        villager.entity.custom_name,
+        Some(Value::String(r#"{"text":"Bob"}"#.to_owned()))
+    );
+}
+
+#[test]
+fn other_entities_are_kept_as_values() {
+    let cow = &entities()[4];
+
+    assert_eq!(cow.id(), Some("minecraft:cow"));
+    assert_eq!(cow.entity(), None);
+    let TypedEntity::Other(value) = cow else {
+        panic!("cow is modelled");
+    };
+    assert_eq!(value.pointer("/Health"), Some(&Value::Float(10.0)));
+}
+
+#[test]
+fn every_entity_has_its_id() {
+    let ids: Vec<_> = entities()
+        .iter()
+        .map(|e| e.id().unwrap().to_owned())
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            "minecraft:item",
+            "minecraft:glow_item_frame",
+            "minecraft:armor_stand",
+            "minecraft:villager",
+            "minecraft:cow",
+        ]
+    );
+}
+
+#[test]
+fn uuid_before_1_16() {
+    let pig = nbt!({ "id": "minecraft:pig", "UUIDMost": -1i64, "UUIDLeast": 2i64 });
+    let pig: Entity = fastnbt::from_bytes(&to_bytes(&pig).unwrap()).unwrap();
+
+    assert_eq!(pig.uuid, Some(0xFFFFFFFF_FFFFFFFF_00000000_00000002));
+    assert!(pig.other.is_empty());
+}
+
+#[test]
+fn item_count_from_1_20_5() {
+    let stack = nbt!({ "id": "minecraft:stick", "count": 64 });
+    let stack: ItemStack = fastnbt::from_value(&stack).unwrap();
+    assert_eq!(stack.count, 64);
+
+    let stack: ItemStack = fastnbt::from_value(&nbt!({ "id": "minecraft:stick" })).unwrap();
+    assert_eq!(stack.count, 1);
+}
+
+#[test]
+fn missing_fields_are_defaults() {
+    let TypedEntity::Villager(villager) = entity(nbt!({ "id": "minecraft:villager" })) else {
+        panic!("not a villager");
+    };
+
+    assert_eq!(villager.entity.pos, [0.0; 3]);
+    assert_eq!(villager.entity.uuid, None);
+    assert_eq!(villager.data, None);
+}
+
+#[test]
+fn wrong_type_of_field_is_an_error() {
+    let frame = nbt!({ "id": "minecraft:item_frame", "Facing": "up" });
+    assert!(fastnbt::from_bytes::<TypedEntity>(&to_bytes(&frame).unwrap()).is_err());
+}
@@ -0,0 +1,119 @@
+use std::{
+    io::Cursor,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use fastnbt::nbt;
+use serde::{Deserialize, Serialize};
+
+use crate::{EpochTime, Region};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Times<T: EpochTime> {
+    #[serde(rename = "LastPlayed", with = "crate::epoch_millis")]
+    last_played: T,
+    #[serde(rename = "Saved", with = "crate::epoch_seconds")]
+    saved: T,
+}
+
+#[test]
+fn system_time_round_trip() {
+    let times = Times {
+        last_played: UNIX_EPOCH + Duration::from_millis(1_600_000_000_123),
+        saved: UNIX_EPOCH + Duration::from_secs(1_600_000_000),
+    };
+
+    let bytes = fastnbt::to_bytes(&times).unwrap();
+    let value: fastnbt::Value = fastnbt::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        value,
+        nbt!({"LastPlayed": 1_600_000_000_123_i64, "Saved": 1_600_000_000_i64})
+    );
+
+    let back: Times<SystemTime> = fastnbt::from_bytes(&bytes).unwrap();
+    assert_eq!(back, times);
+}
+
+#[test]
+fn deserialize_from_any_integer() {
+    let bytes = fastnbt::to_bytes(&nbt!({"LastPlayed": 5_i8, "Saved": 7_i32})).unwrap();
+    let times: Times<SystemTime> = fastnbt::from_bytes(&bytes).unwrap();
+
+    assert_eq!(times.last_played, UNIX_EPOCH + Duration::from_millis(5));
+    assert_eq!(times.saved, UNIX_EPOCH + Duration::from_secs(7));
+}
+
+#[test]
+fn seconds_round_down() {
+    let times = Times {
+        last_played: UNIX_EPOCH - Duration::from_millis(1500),
+        saved: UNIX_EPOCH - Duration::from_millis(1500),
+    };
+
+    let bytes = fastnbt::to_bytes(&times).unwrap();
+    let value: fastnbt::Value = fastnbt::from_bytes(&bytes).unwrap();
+    assert_eq!(value, nbt!({"LastPlayed": -1500_i64, "Saved": -2_i64}));
+}
+
+#[test]
+fn seconds_out_of_range_errors() {
+    let bytes = fastnbt::to_bytes(&nbt!({"LastPlayed": 0_i64, "Saved": i64::MAX})).unwrap();
+    assert!(fastnbt::from_bytes::<Times<SystemTime>>(&bytes).is_err());
+}
+
+#[test]
+fn region_last_saved() {
+    let mut r = Region::new(Cursor::new(vec![])).unwrap();
+    r.write_chunk(1, 2, &[1, 2, 3]).unwrap();
+    assert_eq!(r.last_saved::<SystemTime>(1, 2).unwrap(), None);
+
+    r.set_timestamp(1, 2, 1_600_000_000).unwrap();
+    assert_eq!(
+        r.last_saved::<SystemTime>(1, 2).unwrap(),
+        Some(UNIX_EPOCH + Duration::from_secs(1_600_000_000))
+    );
+}
+
+#[cfg(feature = "time")]
+mod offset_date_time {
+    use time::{Duration, OffsetDateTime};
+
+    use super::*;
+
+    #[test]
+    fn offset_date_time_round_trip() {
+        let times = Times {
+            last_played: OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap()
+                + Duration::milliseconds(123),
+            saved: OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+        };
+
+        let bytes = fastnbt::to_bytes(&times).unwrap();
+        let value: fastnbt::Value = fastnbt::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            value,
+            nbt!({"LastPlayed": 1_600_000_000_123_i64, "Saved": 1_600_000_000_i64})
+        );
+
+        let back: Times<OffsetDateTime> = fastnbt::from_bytes(&bytes).unwrap();
+        assert_eq!(back, times);
+    }
+
+    #[test]
+    fn offset_date_time_out_of_range_errors() {
+        let bytes = fastnbt::to_bytes(&nbt!({"LastPlayed": i64::MAX, "Saved": 0_i64})).unwrap();
+        assert!(fastnbt::from_bytes::<Times<OffsetDateTime>>(&bytes).is_err());
+    }
+
+    #[test]
+    fn region_last_saved() {
+        let mut r = Region::new(Cursor::new(vec![])).unwrap();
+        r.write_chunk(1, 2, &[1, 2, 3]).unwrap();
+        r.set_timestamp(1, 2, 1_600_000_000).unwrap();
+
+        assert_eq!(
+            r.last_saved::<OffsetDateTime>(1, 2).unwrap(),
+            Some(OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap())
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use super::MemoryLoader;
+use crate::{
+    export::{chunks_table, ColumnSpec, ExportError},
+    RCoord,
+};
+
+const ETHO: &[u8] = crate::fixtures::chunk_1_16();
+const CHUNK_1_12: &[u8] = crate::fixtures::chunk_1_12();
+const CHUNK_1_18: &[u8] = crate::fixtures::chunk_1_18();
+
+fn loader() -> MemoryLoader {
+    let loader = MemoryLoader::default();
+    loader.write(0, 0, 0, 0, CHUNK_1_18, 0);
+    loader.write(0, 0, 3, 1, ETHO, 0);
+    loader.write(0, 0, 5, 5, &[1, 2, 3], 0);
+    loader.write(-1, 0, 31, 2, CHUNK_1_12, 0);
+    loader
+}
+
+fn table(columns: &[ColumnSpec]) -> String {
+    let mut out = vec![];
+    chunks_table(&loader(), columns, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn fields_of_each_chunk() {
+    let table = table(&[
+        ColumnSpec::X,
+        ColumnSpec::Z,
+        ColumnSpec::DataVersion,
+        ColumnSpec::InhabitedTime,
+        ColumnSpec::LastUpdate,
+        ColumnSpec::Status,
+    ]);
+
+    assert_eq!(
+        table,
+        "x,z,data_version,inhabited_time,last_update,status\n\
+         -1,2,1139,153,900,\n\
+         0,0,2845,0,11295,full\n\
+         3,1,2578,49651,212680109,full\n\
+         5,5,,,,\n"
+    );
+}
+
+#[test]
+fn unreadable_chunks_still_get_a_row() {
+    let loader = MemoryLoader::default();
+    loader.edit(0, 0, |region| {
+        region.write_chunk(0, 0, CHUNK_1_18).unwrap();
+        // Corrupt, so reading it fails.
+        region
+            .write_raw_chunk(1, 0, crate::CompressionScheme::Zlib, &[1, 2, 3])
+            .unwrap();
+    });
+
+    let mut out = vec![];
+    let columns = [
+        ColumnSpec::X,
+        ColumnSpec::Z,
+        ColumnSpec::DataVersion,
+        ColumnSpec::BlockCount("minecraft:stone".to_owned()),
+    ];
+    chunks_table(&loader, &columns, &mut out).unwrap();
+    let table = String::from_utf8(out).unwrap();
+    let rows: Vec<_> = table.lines().collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[1].starts_with("0,0,2845,"));
+    assert_eq!(rows[2], "1,0,,");
+}
+
+#[test]
+fn unreadable_regions_are_an_error() {
+    let loader = loader();
+    loader
+        .regions
+        .borrow_mut()
+        .insert((RCoord(1), RCoord(0)), vec![1, 2, 3]);
+
+    let mut out = vec![];
+    let res = chunks_table(&loader, &[ColumnSpec::X], &mut out);
+    assert!(matches!(res, Err(ExportError::Region(_))));
+}
+
+#[test]
+fn block_counts() {
+    let table = table(&[
+        ColumnSpec::X,
+        ColumnSpec::BlockCount("minecraft:*_ore".to_owned()),
+        ColumnSpec::BlockCount("minecraft:no_such_block".to_owned()),
+    ]);
+    let rows: Vec<_> = table.lines().collect();
+
+    assert_eq!(rows[0], "x,minecraft:*_ore,minecraft:no_such_block");
+    // Chunks from before 1.13 have no palette to count from.
+    assert_eq!(rows[1], "-1,,");
+    assert!(rows[2].starts_with("0,") && rows[2].ends_with(",0"));
+    assert!(rows[3].starts_with("3,") && rows[3].ends_with(",0"));
+    assert_eq!(rows[4], "5,,");
+
+    let ores: u64 = rows[2].split(',').nth(1).unwrap().parse().unwrap();
+    assert!(ores > 0);
+}
+
+#[test]
+fn biomes_are_counted_per_cell() {
+    let table = table(&[ColumnSpec::X, ColumnSpec::Biomes]);
+    let rows: Vec<_> = table.lines().collect();
+
+    assert_eq!(rows[0], "x,biomes");
+    // Quoted, as the histogram has commas and quotes in it.
+    assert!(rows[3].starts_with("3,\"{\"\"minecraft:"));
+
+    let cells: u64 = rows[3]
+        .trim_start_matches("3,\"{")
+        .trim_end_matches("}\"")
+        .split(',')
+        .map(|entry| entry.rsplit(':').next().unwrap().parse::<u64>().unwrap())
+        .sum();
+    // 6 sections of 64 cells.
+    assert_eq!(cells, 6 * 64);
+}
+
+#[test]
+fn names_are_quoted_in_header() {
+    let table = table(&[ColumnSpec::BlockCount("a,\"b\"".to_owned())]);
+    assert!(table.starts_with("\"a,\"\"b\"\"\"\n"));
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn record_batch_has_typed_columns() {
+    use arrow_array::{Array, Int64Array, StringArray};
+    use arrow_schema::DataType;
+
+    use crate::export::{chunks_parquet, chunks_record_batch};
+
+    let columns = [ColumnSpec::X, ColumnSpec::InhabitedTime, ColumnSpec::Status];
+    let batch = chunks_record_batch(&loader(), &columns).unwrap();
+
+    assert_eq!(batch.num_rows(), 4);
+    assert_eq!(batch.schema().field(0).name(), "x");
+    assert_eq!(batch.schema().field(2).data_type(), &DataType::Utf8);
+
+    let x = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(x.values(), &[-1, 0, 3, 5]);
+    let inhabited = batch
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(inhabited.value(2), 49651);
+    assert!(inhabited.is_null(3));
+    let status = batch
+        .column(2)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(status.value(1), "full");
+
+    let mut parquet = vec![];
+    chunks_parquet(&loader(), &columns, &mut parquet).unwrap();
+    assert!(parquet.starts_with(b"PAR1"));
+}
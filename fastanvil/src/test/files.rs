@@ -0,0 +1,272 @@
+use std::{
+    error::Error,
+    fs::{File, OpenOptions},
+};
+
+use super::TempPath;
+use crate::{CCoord, RCoord, Region, RegionFileLoader, RegionLoader};
+
+const CHUNK_1_17_1: &[u8] = crate::fixtures::chunk_1_17();
+
+/// A directory of region files.
+fn region_dir(name: &str) -> TempPath {
+    let dir = TempPath::dir(name);
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(dir.0.join("r.0.-1.mca"))
+        .unwrap();
+    let mut region = Region::new(file).unwrap();
+    region.write_chunk(2, 3, CHUNK_1_17_1).unwrap();
+    region.write_chunk(4, 5, &[1, 2, 3]).unwrap();
+
+    // Empty region files are not listed.
+    File::create(dir.0.join("r.1.1.mca")).unwrap();
+    File::create(dir.0.join("notes.txt")).unwrap();
+
+    dir
+}
+
+fn assert_loads_region<S>(loader: &dyn RegionLoader<S>)
+where
+    S: std::io::Read + std::io::Seek,
+{
+    assert_eq!(loader.list().unwrap(), vec![(RCoord(0), RCoord(-1))]);
+
+    let chunk = loader.read_chunk(CCoord(2), CCoord(-29)).unwrap().unwrap();
+    assert_eq!(chunk, CHUNK_1_17_1);
+    assert!(loader.read_chunk(CCoord(2), CCoord(3)).unwrap().is_none());
+
+    let mut region = loader.region(RCoord(0), RCoord(-1)).unwrap();
+    assert_eq!(region.read_chunk(4, 5).unwrap().unwrap(), &[1, 2, 3]);
+    assert!(loader.region(RCoord(1), RCoord(1)).is_none());
+}
+
+#[test]
+fn file_loader_loads_regions() {
+    let dir = region_dir("file");
+    assert_loads_region(&RegionFileLoader::new(dir.0.clone()));
+}
+
+#[test]
+fn file_loader_loads_mcregion_files() {
+    let dir = region_dir("mcr");
+
+    let create = |name: &str, chunk: &[u8]| {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(dir.0.join(name))
+            .unwrap();
+        Region::new(file).unwrap().write_chunk(0, 0, chunk).unwrap();
+    };
+    create("r.-1.0.mcr", &[4, 5, 6]);
+    // Left behind by converting the world to Anvil.
+    create("r.0.-1.mcr", &[7, 8, 9]);
+
+    let loader = RegionFileLoader::new(dir.0.clone());
+    assert_eq!(
+        loader.list().unwrap(),
+        vec![(RCoord(-1), RCoord(0)), (RCoord(0), RCoord(-1))]
+    );
+
+    let chunk = loader.read_chunk(CCoord(-32), CCoord(0)).unwrap().unwrap();
+    assert_eq!(chunk, &[4, 5, 6]);
+    let chunk = loader.read_chunk(CCoord(4), CCoord(-27)).unwrap().unwrap();
+    assert_eq!(chunk, &[1, 2, 3]);
+}
+
+#[test]
+fn list_detailed_includes_empty_files() {
+    let dir = region_dir("detailed");
+    let loader = RegionFileLoader::new(dir.0.clone());
+
+    let entries = loader.list_detailed().unwrap();
+    let coords: Vec<_> = entries.iter().map(|e| (e.x, e.z, e.empty)).collect();
+    assert_eq!(
+        coords,
+        vec![(RCoord(0), RCoord(-1), false), (RCoord(1), RCoord(1), true)]
+    );
+
+    let region = &entries[0];
+    assert_eq!(region.path, dir.0.join("r.0.-1.mca"));
+    assert_eq!(region.size, std::fs::metadata(&region.path).unwrap().len());
+    assert!(region.size > 0);
+    assert!(region.modified.is_some());
+    assert_eq!(entries[1].size, 0);
+}
+
+#[test]
+fn list_detailed_is_sorted_and_prefers_anvil_files() {
+    let dir = region_dir("detailed-sorted");
+    for name in ["r.-1.0.mcr", "r.0.-1.mcr", "r.-1.-1.mca", "r.-2.5.mca"] {
+        std::fs::write(dir.0.join(name), [0; 8]).unwrap();
+    }
+
+    let loader = RegionFileLoader::new(dir.0.clone());
+    let entries = loader.list_detailed().unwrap();
+    let names: Vec<_> = entries
+        .iter()
+        .map(|e| e.path.file_name().unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "r.-2.5.mca",
+            "r.-1.-1.mca",
+            "r.-1.0.mcr",
+            "r.0.-1.mca",
+            "r.1.1.mca"
+        ]
+    );
+
+    // The same order as list, without the empty region.
+    let coords: Vec<_> = entries.iter().map(|e| (e.x, e.z)).collect();
+    assert_eq!(loader.list().unwrap(), coords[..4]);
+}
+
+#[test]
+fn file_loader_finds_region_dir_ignoring_case() {
+    let dir = region_dir("case");
+    let name = dir.0.file_name().unwrap().to_str().unwrap();
+    let upper = dir.0.with_file_name(name.to_uppercase());
+
+    assert_loads_region(&RegionFileLoader::new(upper.clone()));
+    let entries = RegionFileLoader::new(upper).list_detailed().unwrap();
+    assert_eq!(entries[0].path, dir.0.join("r.0.-1.mca"));
+}
+
+#[test]
+fn file_loader_missing_dir_errors_with_path() {
+    let dir = region_dir("missing");
+    let missing = dir.0.join("region");
+    let loader = RegionFileLoader::new(missing.clone());
+
+    let err = loader.list().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("directory not found: {}", missing.display())
+    );
+    let source = err.source().unwrap().downcast_ref::<std::io::Error>();
+    assert_eq!(source.unwrap().kind(), std::io::ErrorKind::NotFound);
+
+    assert!(loader.region(RCoord(0), RCoord(-1)).is_none());
+}
+
+#[test]
+fn file_loader_ignores_files_that_are_not_regions() {
+    let dir = region_dir("not-regions");
+    for name in [
+        // Left by copying from Windows.
+        "r.0.-1.mca:Zone.Identifier",
+        "desktop.ini",
+        "Thumbs.db",
+        "r.0.0.0.mca",
+        "r.0.-1.mca.bak",
+        "x.2.2.mca",
+    ] {
+        std::fs::write(dir.0.join(name), [0; 8]).unwrap();
+    }
+
+    assert_loads_region(&RegionFileLoader::new(dir.0.clone()));
+    assert_eq!(
+        RegionFileLoader::new(dir.0.clone())
+            .list_detailed()
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn file_loader_limits_uncompressed_size() {
+    let dir = region_dir("limit");
+    let limit = CHUNK_1_17_1.len() - 1;
+    let loader = RegionFileLoader::new(dir.0.clone()).with_max_uncompressed_len(limit);
+
+    let err = loader.read_chunk(CCoord(2), CCoord(-29)).unwrap_err();
+    assert!(matches!(
+        err,
+        crate::Error::UncompressedTooLarge { x: 2, z: 3, limit: l } if l == limit
+    ));
+    // Smaller chunks are still read.
+    let mut region = loader.region(RCoord(0), RCoord(-1)).unwrap();
+    assert_eq!(region.read_chunk(4, 5).unwrap().unwrap(), &[1, 2, 3]);
+}
+
+#[test]
+fn file_loader_coverage() {
+    let dir = region_dir("coverage");
+    let loader = RegionFileLoader::new(dir.0.clone());
+
+    let coverage = loader.coverage().unwrap();
+    assert_eq!(coverage.len(), 1);
+
+    let (x, z, chunks) = coverage[0];
+    assert_eq!((x, z), (RCoord(0), RCoord(-1)));
+    assert_eq!(chunks.iter().collect::<Vec<_>>(), [(2, 3), (4, 5)]);
+}
+
+#[cfg(feature = "mmap")]
+mod mmap {
+    use crate::MmapRegionLoader;
+
+    use super::*;
+
+    #[test]
+    fn mmap_loader_loads_regions() {
+        let dir = region_dir("mmap");
+        // SAFETY: Nothing modifies the files while they are mapped.
+        let loader = unsafe { MmapRegionLoader::new(dir.0.clone()) };
+        assert_loads_region(&loader);
+        assert_eq!(loader.list_detailed().unwrap().len(), 2);
+
+        let region = loader.region(RCoord(0), RCoord(-1)).unwrap();
+        let mut buf = vec![];
+        assert!(region.decompress_chunk_into(2, 3, &mut buf).unwrap());
+        assert_eq!(buf, CHUNK_1_17_1);
+    }
+
+    #[test]
+    fn mmap_loader_limits_uncompressed_size() {
+        let dir = region_dir("mmap-limit");
+        // SAFETY: Nothing modifies the files while they are mapped.
+        let loader = unsafe { MmapRegionLoader::new(dir.0.clone()) }
+            .with_max_uncompressed_len(CHUNK_1_17_1.len() - 1);
+
+        let region = loader.region(RCoord(0), RCoord(-1)).unwrap();
+        let mut buf = vec![];
+        assert!(matches!(
+            region.decompress_chunk_into(2, 3, &mut buf),
+            Err(crate::Error::UncompressedTooLarge { x: 2, z: 3, .. })
+        ));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn mmap_loader_can_be_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MmapRegionLoader>();
+        assert_send_sync::<Region<std::io::Cursor<memmap2::Mmap>>>();
+
+        let dir = region_dir("mmap-threads");
+        // SAFETY: Nothing modifies the files while they are mapped.
+        let loader = unsafe { MmapRegionLoader::new(dir.0.clone()) };
+        let region = loader.region(RCoord(0), RCoord(-1)).unwrap();
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let mut buf = vec![];
+                    assert!(region.decompress_chunk_into(4, 5, &mut buf).unwrap());
+                    assert_eq!(buf, &[1, 2, 3]);
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    fixtures,
+    version::{region_data_versions, DataVersion},
+    Error, JavaChunk, Region,
+};
+
+fn data_version(chunk: &[u8]) -> Option<i32> {
+    #[derive(serde::Deserialize)]
+    struct Versioned {
+        #[serde(rename = "DataVersion")]
+        data_version: Option<i32>,
+    }
+
+    fastnbt::from_bytes::<Versioned>(chunk)
+        .unwrap()
+        .data_version
+}
+
+#[test]
+fn chunks_have_their_data_versions() {
+    assert_eq!(data_version(fixtures::chunk_1_12()), Some(1139));
+    assert_eq!(data_version(fixtures::chunk_1_16()), Some(2578));
+    assert_eq!(data_version(fixtures::chunk_1_17_0()), Some(2724));
+    assert_eq!(data_version(fixtures::chunk_1_17()), Some(2730));
+    assert_eq!(data_version(fixtures::chunk_1_18()), Some(2845));
+}
+
+#[test]
+fn chunks_from_1_13_parse() {
+    let chunks = [
+        fixtures::chunk_1_16(),
+        fixtures::chunk_1_17_0(),
+        fixtures::chunk_1_17(),
+        fixtures::chunk_1_17_custom_heights(),
+        fixtures::chunk_1_18(),
+        fixtures::chunk_unicode(),
+        fixtures::chunk_modded(),
+        fixtures::chunk_empty(),
+        fixtures::chunk_old_heightmaps(),
+        fixtures::chunk_max_heights(),
+    ];
+
+    for chunk in chunks.into_iter().chain(fixtures::chunks_old_in_new()) {
+        JavaChunk::from_bytes(chunk).unwrap();
+    }
+}
+
+#[test]
+fn malformed_chunks_fail_to_parse() {
+    for (what, chunk) in fixtures::malformed_chunks() {
+        assert!(JavaChunk::from_bytes(&chunk).is_err(), "{what}");
+    }
+}
+
+#[test]
+fn regions_hold_their_chunk() {
+    let regions = [
+        (fixtures::region_1_12(), fixtures::chunk_1_12()),
+        (fixtures::region_1_16(), fixtures::chunk_1_16()),
+        (fixtures::region_1_17(), fixtures::chunk_1_17()),
+        (fixtures::region_1_18(), fixtures::chunk_1_18()),
+    ];
+
+    for (stream, chunk) in regions {
+        let mut region = Region::from_stream(stream).unwrap();
+        assert_eq!(region.read_chunk(0, 0).unwrap().as_deref(), Some(chunk));
+    }
+}
+
+#[test]
+fn mixed_region_spans_versions() {
+    let mut region = Region::from_stream(fixtures::region_mixed_versions()).unwrap();
+    assert_eq!(
+        region_data_versions(&mut region).unwrap(),
+        Some(DataVersion(1139)..=DataVersion(2845))
+    );
+}
+
+#[test]
+fn malformed_region_has_broken_chunks() {
+    let mut region = Region::from_stream(fixtures::malformed_region()).unwrap();
+
+    let chunk = region.read_chunk(0, 0).unwrap().unwrap();
+    assert_eq!(chunk, fixtures::chunk_1_18());
+
+    assert!(matches!(
+        region.read_chunk(1, 0),
+        Err(Error::UnknownCompression(42))
+    ));
+    assert!(region.read_chunk(2, 0).is_err());
+    assert!(region.read_chunk(3, 0).is_err());
+
+    let chunk = region.read_chunk(4, 0).unwrap().unwrap();
+    assert!(JavaChunk::from_bytes(&chunk).is_err());
+}
@@ -0,0 +1,98 @@
+use std::ops::Range;
+
+use fastnbt::nbt;
+
+use crate::{
+    biome::Biome, recompute_heightmap, Block, Chunk, HeightMode, HeightmapKind, JavaChunk,
+};
+
+/// A chunk where every column is the same stack of blocks, starting at y 0.
+struct Columns(Vec<Block>);
+
+impl Columns {
+    fn new(blocks: &[&str]) -> Self {
+        Self(blocks.iter().map(|b| b.parse().unwrap()).collect())
+    }
+}
+
+impl Chunk for Columns {
+    fn status(&self) -> String {
+        "full".to_owned()
+    }
+
+    fn surface_height(&self, x: usize, z: usize, _mode: HeightMode) -> isize {
+        recompute_heightmap(self, HeightmapKind::MotionBlocking)[z * 16 + x] as isize
+    }
+
+    fn biome(&self, _x: usize, _y: isize, _z: usize) -> Option<Biome> {
+        None
+    }
+
+    fn block(&self, _x: usize, y: isize, _z: usize) -> Option<&Block> {
+        self.0.get(usize::try_from(y).ok()?)
+    }
+
+    fn y_range(&self) -> Range<isize> {
+        0..self.0.len() as isize
+    }
+}
+
+fn height(chunk: &Columns, kind: HeightmapKind) -> i16 {
+    let map = recompute_heightmap(chunk, kind);
+    assert!(map.iter().all(|&h| h == map[0]));
+    map[0]
+}
+
+#[test]
+fn kinds_count_different_blocks() {
+    let chunk = Columns::new(&[
+        "stone",
+        "water[level=0]",
+        "water[level=0]",
+        "oak_leaves[waterlogged=true]",
+        "oak_leaves",
+        "poppy",
+        "air",
+    ]);
+
+    assert_eq!(height(&chunk, HeightmapKind::WorldSurface), 6);
+    assert_eq!(height(&chunk, HeightmapKind::MotionBlocking), 5);
+    assert_eq!(chunk.surface_height(2, 9, HeightMode::Trust), 5);
+    assert_eq!(height(&chunk, HeightmapKind::MotionBlockingNoLeaves), 3);
+    assert_eq!(height(&chunk, HeightmapKind::OceanFloor), 5);
+
+    let ocean = Columns::new(&["sand", "water[level=0]", "kelp_plant", "air"]);
+
+    assert_eq!(height(&ocean, HeightmapKind::MotionBlocking), 3);
+    assert_eq!(height(&ocean, HeightmapKind::OceanFloor), 1);
+}
+
+#[test]
+fn empty_columns_are_at_the_bottom() {
+    let chunk = Columns::new(&["air", "torch", "air"]);
+
+    assert_eq!(height(&chunk, HeightmapKind::WorldSurface), 2);
+    assert_eq!(height(&chunk, HeightmapKind::MotionBlocking), 0);
+}
+
+#[test]
+fn missing_heightmap_is_recomputed() {
+    let chunk = nbt!({
+        "DataVersion": 2860,
+        "Status": "full",
+        "sections": [
+            {"Y": -4_i8, "block_states": {"palette": [{"Name": "minecraft:stone"}]}},
+            {"Y": -3_i8, "block_states": {"palette": [{"Name": "minecraft:grass"}]}},
+            {"Y": -2_i8, "block_states": {"palette": [{"Name": "minecraft:air"}]}},
+        ],
+    });
+
+    let data = fastnbt::to_bytes(&chunk).unwrap();
+    let chunk = JavaChunk::from_bytes(&data).unwrap();
+
+    assert_eq!(chunk.surface_height(3, 7, HeightMode::Trust), -48);
+    assert_eq!(
+        recompute_heightmap(&chunk, HeightmapKind::WorldSurface),
+        [-32; 256]
+    );
+}
@@ -0,0 +1,67 @@
+use crate::{Chunk, HeightMode, JavaChunk, LazyJavaChunk};
+
+const CHUNK_21W44A_1: &[u8] = crate::fixtures::chunk_1_18();
+const CHUNK_1_17_1: &[u8] = crate::fixtures::chunk_1_17();
+
+#[test]
+fn lazy_chunk_matches_java_chunk() {
+    let java = JavaChunk::from_bytes(CHUNK_21W44A_1).unwrap();
+    let lazy = LazyJavaChunk::from_bytes(CHUNK_21W44A_1).unwrap();
+
+    assert_eq!(java.data_version, lazy.data_version);
+    assert_eq!(java.status(), lazy.status());
+    assert_eq!(java.y_range(), lazy.y_range());
+
+    for y in java.y_range() {
+        for z in 0..16 {
+            for x in 0..16 {
+                let (a, b) = (java.block(x, y, z), lazy.block(x, y, z));
+                assert_eq!(
+                    a.map(|b| b.encoded_description()),
+                    b.map(|b| b.encoded_description())
+                );
+                assert_eq!(
+                    java.biome(x, y, z).map(|b| b as i32),
+                    lazy.biome(x, y, z).map(|b| b as i32)
+                );
+            }
+        }
+        assert_eq!(java.section_fill(y), lazy.section_fill(y));
+    }
+
+    for mode in [HeightMode::Trust, HeightMode::Calculate] {
+        java.recalculate_heightmap(mode);
+        lazy.recalculate_heightmap(mode);
+        for z in 0..16 {
+            for x in 0..16 {
+                assert_eq!(
+                    java.surface_height(x, z, mode),
+                    lazy.surface_height(x, z, mode)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn lazy_chunk_decodes_only_sections_queried() {
+    let chunk = LazyJavaChunk::from_bytes(CHUNK_21W44A_1).unwrap();
+    let sections = chunk.sections.as_ref().unwrap();
+
+    assert!(sections.sections().iter().all(|s| !s.is_decoded()));
+
+    chunk.block(0, 70, 0).unwrap();
+
+    let decoded: Vec<_> = sections
+        .sections()
+        .iter()
+        .filter(|s| s.is_decoded())
+        .map(|s| s.decoded().unwrap().y())
+        .collect();
+    assert_eq!(decoded, [4]);
+}
+
+#[test]
+fn lazy_chunk_rejects_pre18_chunks() {
+    assert!(LazyJavaChunk::from_bytes(CHUNK_1_17_1).is_err());
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use crate::{
+    guess_map_colour, vanilla_map_colour, Block, FallbackStats, Palette, RenderedPalette, Rgba,
+};
+
+const STONE: Rgba = [112, 112, 112, 255];
+const PLANT: Rgba = [0, 124, 0, 255];
+const WOOD: Rgba = [143, 119, 72, 255];
+const LIGHT_BLUE: Rgba = [102, 153, 216, 255];
+const BLUE: Rgba = [51, 76, 178, 255];
+
+fn block(s: &str) -> Block {
+    s.parse().unwrap()
+}
+
+fn rendered_palette() -> RenderedPalette {
+    let blockstates: HashMap<_, _> = [("minecraft:stone".to_owned(), [1, 2, 3, 255])]
+        .into_iter()
+        .collect();
+
+    RenderedPalette {
+        blockstates,
+        grass: image::RgbaImage::new(256, 256),
+        foliage: image::RgbaImage::new(256, 256),
+    }
+}
+
+#[test]
+fn vanilla_map_colours() {
+    assert_eq!(vanilla_map_colour("minecraft:stone"), Some(STONE));
+    assert_eq!(vanilla_map_colour("stone"), Some(STONE));
+    assert_eq!(vanilla_map_colour("light_blue_wool"), Some(LIGHT_BLUE));
+    assert_eq!(vanilla_map_colour("blue_concrete"), Some(BLUE));
+    assert_eq!(vanilla_map_colour("oak_planks"), Some(WOOD));
+    assert_eq!(vanilla_map_colour("stripped_oak_log"), Some(WOOD));
+    assert_eq!(
+        vanilla_map_colour("dark_oak_planks"),
+        Some([102, 76, 51, 255])
+    );
+    assert_eq!(vanilla_map_colour("dark_oak_leaves"), Some(PLANT));
+    assert_eq!(vanilla_map_colour("iron_ore"), Some(STONE));
+    assert_eq!(
+        vanilla_map_colour("deepslate_iron_ore"),
+        Some([100, 100, 100, 255])
+    );
+    assert_eq!(vanilla_map_colour("glass"), None);
+    assert_eq!(vanilla_map_colour("mymod:stone"), None);
+}
+
+#[test]
+fn guessed_map_colours() {
+    assert_eq!(guess_map_colour("mymod:stone"), Some(STONE));
+    assert_eq!(guess_map_colour("mymod:ruby_ore"), Some(STONE));
+    assert_eq!(guess_map_colour("biomesoplenty:fir_leaves"), Some(PLANT));
+    assert_eq!(guess_map_colour("mymod:maple_log"), Some(WOOD));
+    assert_eq!(guess_map_colour("mymod:light_blue_tiles"), Some(LIGHT_BLUE));
+    assert_eq!(guess_map_colour("mymod:limestone_bricks_wall"), Some(STONE));
+    // Words are matched whole, so this is not ice.
+    assert_eq!(guess_map_colour("mymod:cake_slice"), None);
+    assert_eq!(guess_map_colour("mymod:widget"), None);
+}
+
+#[test]
+fn rendered_palette_try_pick_misses() {
+    let palette = rendered_palette();
+    assert_eq!(
+        palette.try_pick(&block("minecraft:stone"), None),
+        Some([1, 2, 3, 255])
+    );
+    assert_eq!(palette.try_pick(&block("minecraft:granite"), None), None);
+    assert_eq!(
+        palette.pick(&block("minecraft:granite"), None),
+        [255, 0, 255, 255]
+    );
+}
+
+#[test]
+fn fallback_palette_only_used_on_miss() {
+    let palette = rendered_palette().with_fallbacks();
+
+    assert_eq!(
+        palette.pick(&block("minecraft:stone"), None),
+        [1, 2, 3, 255]
+    );
+    assert_eq!(palette.stats(), FallbackStats::default());
+
+    assert_eq!(
+        palette.pick(&block("minecraft:granite"), None),
+        [151, 109, 77, 255]
+    );
+    assert_eq!(palette.pick(&block("mymod:maple_log"), None), WOOD);
+    assert_eq!(palette.pick(&block("mymod:stone"), None), STONE);
+    assert_eq!(palette.pick(&block("mymod:widget"), None), STONE);
+
+    assert_eq!(
+        palette.stats(),
+        FallbackStats {
+            vanilla: 1,
+            guessed: 2,
+            unknown: 1,
+        }
+    );
+    assert_eq!(palette.stats().total(), 4);
+
+    palette.reset_stats();
+    assert_eq!(palette.stats().total(), 0);
+}
@@ -0,0 +1,123 @@
+use fastnbt::{nbt, ByteArray};
+
+use crate::{Chunk, HeightMode, JavaChunk, McRegionChunk};
+
+fn index(x: usize, y: usize, z: usize) -> usize {
+    (x * 16 + z) * 128 + y
+}
+
+fn chunk(populated: bool) -> McRegionChunk {
+    let mut blocks = vec![0i8; 16 * 16 * 128];
+    let mut data = vec![0i8; 16 * 16 * 128 / 2];
+    let mut height_map = vec![0i8; 16 * 16];
+
+    // Stone up to y 60 everywhere, with a column of red wool at 3, 7.
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..=60 {
+                blocks[index(x, y, z)] = 1;
+            }
+            height_map[z * 16 + x] = 61;
+        }
+    }
+    for y in 61..70 {
+        let i = index(3, y, 7);
+        blocks[i] = 35;
+        data[i / 2] |= 14 << (i % 2 * 4);
+    }
+    height_map[7 * 16 + 3] = 70;
+
+    let chunk = nbt!({
+        "Level": {
+            "xPos": 5,
+            "zPos": -2,
+            "Blocks": ByteArray::new(blocks),
+            "Data": ByteArray::new(data),
+            "HeightMap": ByteArray::new(height_map),
+            "TerrainPopulated": populated as i8,
+            "LastUpdate": 1234_i64,
+        }
+    });
+
+    McRegionChunk::from_bytes(&fastnbt::to_bytes(&chunk).unwrap()).unwrap()
+}
+
+#[test]
+fn mcregion_blocks() {
+    let chunk = chunk(true);
+    assert_eq!((chunk.level.x_pos, chunk.level.z_pos), (5, -2));
+    assert_eq!(chunk.y_range(), 0..128);
+
+    assert_eq!(chunk.block_id(0, 0, 0), Some(1));
+    assert_eq!(chunk.block_id(3, 65, 7), Some(35));
+    assert_eq!(chunk.block_data(3, 65, 7), Some(14));
+    assert_eq!(chunk.block_data(3, 66, 7), Some(14));
+    assert_eq!(chunk.block_id(3, 70, 7), Some(0));
+    assert_eq!(chunk.block_id(0, 128, 0), None);
+    assert_eq!(chunk.block_id(0, -1, 0), None);
+    // z = 16 would otherwise land on the next column along x.
+    assert_eq!(chunk.block_id(0, 0, 16), None);
+    assert_eq!(chunk.block_id(16, 0, 0), None);
+    assert_eq!(chunk.block_data(16, 0, 0), None);
+
+    assert_eq!(
+        chunk.block(0, 0, 0).unwrap().to_string(),
+        "legacy:1[data=0]"
+    );
+    assert_eq!(chunk.block(3, 61, 7).unwrap().property("data"), Some("14"));
+    assert_eq!(chunk.block(0, 100, 0).unwrap().name(), "minecraft:air");
+    assert!(chunk.block(0, 128, 0).is_none());
+    assert!(chunk.biome(0, 0, 0).is_none());
+}
+
+#[test]
+fn mcregion_heights() {
+    let chunk = chunk(true);
+    for mode in [HeightMode::Trust, HeightMode::Calculate] {
+        assert_eq!(chunk.surface_height(0, 0, mode), 61);
+        assert_eq!(chunk.surface_height(3, 7, mode), 70);
+    }
+}
+
+#[test]
+fn mcregion_status() {
+    assert_eq!(chunk(true).status(), "full");
+    assert_ne!(chunk(false).status(), "full");
+}
+
+#[test]
+fn boxed_picks_mcregion_without_data_version() {
+    let chunk = nbt!({
+        "Level": {
+            "xPos": 0,
+            "zPos": 0,
+            "Blocks": ByteArray::new(vec![1; 16 * 16 * 128]),
+            "Data": ByteArray::new(vec![0; 16 * 16 * 64]),
+        }
+    });
+
+    let chunk = JavaChunk::boxed(&fastnbt::to_bytes(&chunk).unwrap()).unwrap();
+    assert_eq!(chunk.y_range(), 0..128);
+    assert_eq!(chunk.block(0, 5, 0).unwrap().name(), "legacy:1");
+}
+
+#[test]
+fn boxed_does_not_take_old_anvil_chunks_as_mcregion() {
+    // Anvil before 1.9 had no DataVersion, and its blocks are in sections.
+    let chunk = nbt!({
+        "Level": {
+            "xPos": 0,
+            "zPos": 0,
+            "Sections": [{
+                "Y": 0_i8,
+                "Blocks": ByteArray::new(vec![1; 16 * 16 * 16]),
+                "Data": ByteArray::new(vec![0; 16 * 16 * 8]),
+            }],
+        }
+    });
+
+    let err = JavaChunk::boxed(&fastnbt::to_bytes(&chunk).unwrap())
+        .err()
+        .unwrap();
+    assert!(!err.to_string().contains("Blocks"), "{err}");
+}
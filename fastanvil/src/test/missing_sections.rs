@@ -0,0 +1,175 @@
+use fastnbt::nbt;
+
+use crate::{
+    biome::Biome, count_blocks, fixtures, BlockFilter, Chunk, HeightMode, JavaChunk, LazyJavaChunk,
+    SectionFill,
+};
+
+fn name<C: Chunk + ?Sized>(chunk: &C, y: isize) -> Option<&str> {
+    chunk.block(0, y, 0).map(|b| b.name())
+}
+
+#[test]
+fn chunk_without_sections_has_no_blocks() {
+    let data = fixtures::chunk_without_sections();
+    let java = JavaChunk::from_bytes(&data).unwrap();
+    let lazy = LazyJavaChunk::from_bytes(&data).unwrap();
+    let boxed = JavaChunk::boxed(&data).unwrap();
+    let chunks: [&dyn Chunk; 3] = [&java, &lazy, boxed.as_ref()];
+
+    for chunk in chunks {
+        assert_eq!(chunk.y_range(), -64..-64);
+        for y in [-65, -64, 0, 64, 319] {
+            assert_eq!(name(chunk, y), None);
+            assert!(chunk.biome(0, y, 0).is_none());
+            assert_eq!(chunk.section_fill(y), None);
+            assert!(chunk.section_block_counts(y).is_none());
+        }
+
+        // The stored heightmap claims a surface at 64, with nothing there.
+        for mode in [HeightMode::Trust, HeightMode::Calculate] {
+            assert_eq!(chunk.surface_height(0, 0, mode), -64);
+            assert_eq!(chunk.surface_height(15, 15, mode), -64);
+        }
+    }
+
+    assert_eq!(java.palette_blocks().count(), 0);
+    assert!(count_blocks(&java, &BlockFilter::new()).is_empty());
+    assert!(count_blocks(&lazy, &BlockFilter::new()).is_empty());
+}
+
+#[test]
+fn chunk_without_sections_or_y_pos_is_at_zero() {
+    let heightmap = fastnbt::LongArray::new(vec![0; 37]);
+    let chunks = [
+        nbt!({
+            "DataVersion": 2860,
+            "Status": "empty",
+            "Heightmaps": { "MOTION_BLOCKING": heightmap.clone() },
+        }),
+        nbt!({
+            "DataVersion": 2730,
+            "Level": {
+                "xPos": 0,
+                "zPos": 0,
+                "Status": "full",
+                "Heightmaps": { "MOTION_BLOCKING": heightmap },
+            },
+        }),
+    ];
+
+    for chunk in chunks {
+        let chunk = JavaChunk::from_bytes(&fastnbt::to_bytes(&chunk).unwrap()).unwrap();
+        assert!(chunk.sections.is_none());
+        assert_eq!(chunk.y_range(), 0..0);
+        assert_eq!(chunk.surface_height(3, 4, HeightMode::Trust), 0);
+        assert_eq!(name(&chunk, 0), None);
+    }
+}
+
+#[test]
+fn empty_sections_list_is_at_y_pos() {
+    let data = fastnbt::to_bytes(&nbt!({
+        "DataVersion": 2860,
+        "Status": "empty",
+        "yPos": -4,
+        "sections": [],
+    }))
+    .unwrap();
+
+    let java = JavaChunk::from_bytes(&data).unwrap();
+    let lazy = LazyJavaChunk::from_bytes(&data).unwrap();
+    let chunks: [&dyn Chunk; 2] = [&java, &lazy];
+    for chunk in chunks {
+        assert_eq!(chunk.y_range(), -64..-64);
+        assert_eq!(chunk.surface_height(0, 0, HeightMode::Trust), -64);
+        assert_eq!(name(chunk, -64), None);
+    }
+}
+
+#[test]
+fn palette_only_sections_are_filled() {
+    let data = fixtures::chunk_palette_only();
+    let java = JavaChunk::from_bytes(&data).unwrap();
+    let lazy = LazyJavaChunk::from_bytes(&data).unwrap();
+    let boxed = JavaChunk::boxed(&data).unwrap();
+    let chunks: [&dyn Chunk; 3] = [&java, &lazy, boxed.as_ref()];
+
+    for chunk in chunks {
+        assert_eq!(chunk.y_range(), -64..-16);
+        for y in [-64, -56, -49] {
+            assert_eq!(name(chunk, y), Some("minecraft:stone"));
+            assert_eq!(chunk.block(15, y, 15).unwrap().name(), "minecraft:stone");
+            assert!(matches!(chunk.biome(7, y, 7), Some(Biome::Plains)));
+        }
+        assert_eq!(name(chunk, -48), Some("minecraft:air"));
+        // The section without block states is air.
+        assert_eq!(name(chunk, -32), Some("minecraft:air"));
+        assert_eq!(name(chunk, -16), None);
+        assert_eq!(name(chunk, 64), None);
+
+        assert_eq!(chunk.section_fill(-64), Some(SectionFill::Solid));
+        assert_eq!(chunk.section_fill(-48), Some(SectionFill::Air));
+        assert_eq!(chunk.section_fill(-32), Some(SectionFill::Air));
+
+        // There's no stored heightmap, so both modes look at the blocks.
+        for mode in [HeightMode::Trust, HeightMode::Calculate] {
+            assert_eq!(chunk.surface_height(0, 0, mode), -48);
+        }
+    }
+
+    let counts = count_blocks(&java, &BlockFilter::new());
+    assert_eq!(counts["minecraft:stone"], 4096);
+    assert_eq!(count_blocks(&lazy, &BlockFilter::new()), counts);
+
+    let palette: Vec<_> = java.palette_blocks().map(|b| b.name()).collect();
+    assert_eq!(palette, ["minecraft:stone", "minecraft:air"]);
+}
+
+#[test]
+#[cfg(feature = "render")]
+fn chunk_without_sections_renders_empty() {
+    use std::io::Cursor;
+
+    use crate::{
+        render_region_with_policy, CCoord, ChunkErrorPolicy, DebugPalette, LoaderResult,
+        OnChunkError, RCoord, Region, RegionLoader, TopShadeRenderer,
+    };
+
+    let renderer = TopShadeRenderer::new(&DebugPalette, HeightMode::Trust);
+    let chunk = JavaChunk::from_bytes(&fixtures::chunk_without_sections()).unwrap();
+    assert_eq!(renderer.render(&chunk, None), [[0; 4]; 256]);
+
+    let palette_only = JavaChunk::from_bytes(&fixtures::chunk_palette_only()).unwrap();
+    assert!(renderer
+        .render(&palette_only, None)
+        .iter()
+        .all(|pixel| pixel[3] == 255));
+
+    struct Loader;
+
+    impl RegionLoader<Cursor<Vec<u8>>> for Loader {
+        fn region(&self, x: RCoord, z: RCoord) -> Option<Region<Cursor<Vec<u8>>>> {
+            let chunks = [
+                (0, 0, fixtures::chunk_without_sections()),
+                (1, 0, fixtures::chunk_palette_only()),
+            ];
+            let chunks: Vec<_> = chunks.iter().map(|(x, z, c)| (*x, *z, &c[..])).collect();
+            (x.0 == 0 && z.0 == 0).then(|| Region::from_stream(fixtures::region(&chunks)).unwrap())
+        }
+
+        fn list(&self) -> LoaderResult<Vec<(RCoord, RCoord)>> {
+            Ok(vec![(RCoord(0), RCoord(0))])
+        }
+    }
+
+    let policy = ChunkErrorPolicy {
+        on_error: OnChunkError::Abort,
+        catch_panics: false,
+    };
+    let (map, report) =
+        render_region_with_policy(RCoord(0), RCoord(0), &Loader, renderer, None, policy).unwrap();
+    assert!(report.skipped.is_empty());
+    assert!(map.chunk(CCoord(0), CCoord(0)).iter().all(|p| *p == [0; 4]));
+    assert!(map.chunk(CCoord(1), CCoord(0)).iter().all(|p| p[3] == 255));
+}
@@ -1,10 +1,48 @@
+use std::{cell::RefCell, collections::HashMap, io::Cursor, ops::Range, path::PathBuf};
+
 use fastnbt::{nbt, LongArray, Value};
 
+use crate::{
+    biome::Biome, Block, Chunk, HeightMode, JavaChunk, LoaderResult, RCoord, Region, RegionLoader,
+};
+
+mod activity;
+mod alloc;
+mod below_zero;
+mod block;
+mod block_entity;
+mod data;
+mod diagnostics;
+mod entity;
+mod epoch;
+mod export;
+mod files;
+mod fixtures;
+mod heightmap;
+mod lazy_chunk;
+#[cfg(feature = "render")]
+mod map_colours;
+mod mcregion;
+mod missing_sections;
+mod normalized;
+mod preserve;
 mod region;
+#[cfg(feature = "render")]
+mod render;
+mod resource_location;
 mod rogue_chunks;
 mod section_data;
+#[cfg(feature = "render")]
 mod standard_chunks;
+mod stats;
+#[cfg(feature = "serde_json")]
+mod text_component;
 mod unicode_chunk;
+mod upgrade;
+mod version;
+mod world;
+#[cfg(feature = "render")]
+mod world_image;
 
 #[test]
 fn nbt_macro_use() {
@@ -12,3 +50,106 @@ fn nbt_macro_use() {
     let val = nbt!([L;1,2,3]);
     assert_eq!(val, Value::LongArray(LongArray::new(vec![1, 2, 3])));
 }
+
+/// Hides the shortcuts a chunk provides, eg its section fill, forcing callers
+/// to check every block.
+struct NoFastPath<'a>(&'a JavaChunk);
+
+impl<'a> Chunk for NoFastPath<'a> {
+    fn status(&self) -> String {
+        self.0.status()
+    }
+
+    fn surface_height(&self, x: usize, z: usize, mode: HeightMode) -> isize {
+        self.0.surface_height(x, z, mode)
+    }
+
+    fn biome(&self, x: usize, y: isize, z: usize) -> Option<Biome> {
+        self.0.biome(x, y, z)
+    }
+
+    fn block(&self, x: usize, y: isize, z: usize) -> Option<&Block> {
+        self.0.block(x, y, z)
+    }
+
+    fn y_range(&self) -> Range<isize> {
+        self.0.y_range()
+    }
+}
+
+/// A path in the temporary directory, unique to the test and process. It is
+/// removed, with anything under it, when dropped.
+pub(crate) struct TempPath(pub(crate) PathBuf);
+
+impl TempPath {
+    pub(crate) fn new(name: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("fastanvil-test-{}-{}", name, std::process::id())))
+    }
+
+    /// An empty directory.
+    pub(crate) fn dir(name: &str) -> Self {
+        let path = Self::new(name);
+        std::fs::create_dir_all(&path.0).unwrap();
+        path
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = if self.0.is_dir() {
+            std::fs::remove_dir_all(&self.0)
+        } else {
+            std::fs::remove_file(&self.0)
+        };
+    }
+}
+
+/// Regions held in memory, keyed by region coordinates.
+#[derive(Default)]
+pub(crate) struct MemoryLoader {
+    regions: RefCell<HashMap<(RCoord, RCoord), Vec<u8>>>,
+}
+
+impl MemoryLoader {
+    /// Change the region at `rx`, `rz`, which starts out empty.
+    pub(crate) fn edit(&self, rx: isize, rz: isize, f: impl FnOnce(&mut Region<Cursor<Vec<u8>>>)) {
+        let mut regions = self.regions.borrow_mut();
+        let mut region = match regions.remove(&(RCoord(rx), RCoord(rz))) {
+            Some(data) => Region::from_stream(Cursor::new(data)).unwrap(),
+            None => Region::new(Cursor::new(vec![])).unwrap(),
+        };
+
+        f(&mut region);
+
+        regions.insert(
+            (RCoord(rx), RCoord(rz)),
+            region.into_inner().unwrap().into_inner(),
+        );
+    }
+
+    pub(crate) fn write(
+        &self,
+        rx: isize,
+        rz: isize,
+        x: usize,
+        z: usize,
+        chunk: &[u8],
+        timestamp: u32,
+    ) {
+        self.edit(rx, rz, |region| {
+            region.write_chunk(x, z, chunk).unwrap();
+            region.set_timestamp(x, z, timestamp).unwrap();
+        })
+    }
+}
+
+impl RegionLoader<Cursor<Vec<u8>>> for MemoryLoader {
+    fn region(&self, x: RCoord, z: RCoord) -> Option<Region<Cursor<Vec<u8>>>> {
+        let data = self.regions.borrow().get(&(x, z))?.clone();
+        Region::from_stream(Cursor::new(data)).ok()
+    }
+
+    fn list(&self) -> LoaderResult<Vec<(RCoord, RCoord)>> {
+        Ok(self.regions.borrow().keys().cloned().collect())
+    }
+}
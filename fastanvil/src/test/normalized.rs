@@ -0,0 +1,305 @@
+use fastnbt::{nbt, IntArray, LongArray, Value};
+
+use crate::{pre18::Pre18Section, Chunk, HeightMode, JavaChunk, Section};
+
+// The same two sections saved in the 1.15, 1.16 and 1.18 layouts. The lower
+// section has a 17 block palette so needs 5 bits per block, which does not
+// divide 64 and so differs between the tightly packed 1.15 layout and the
+// padded 1.16 layout. The upper section is all air.
+
+const WOOLS: [&str; 16] = [
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "light_gray",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+
+fn palette() -> Value {
+    let mut palette = vec![nbt!({"Name": "minecraft:air"})];
+    for (i, colour) in WOOLS.iter().enumerate() {
+        // Give some blocks properties too.
+        palette.push(match i % 4 {
+            0 => nbt!({
+                "Name": format!("minecraft:{}_bed", colour),
+                "Properties": {"facing": "north", "part": "head"},
+            }),
+            _ => nbt!({"Name": format!("minecraft:{}_wool", colour)}),
+        });
+    }
+    Value::List(palette)
+}
+
+/// The palette index of each block in the lower section, in x, z, y order.
+fn indices() -> Vec<u64> {
+    (0..16 * 16 * 16)
+        .map(|i| {
+            let (x, z, y) = (i % 16, (i / 16) % 16, i / 256);
+            // Leave the top of the section air for surface heights.
+            if y > 12 - x % 3 {
+                0
+            } else {
+                ((x * 7 + z * 3 + y) % 17) as u64
+            }
+        })
+        .collect()
+}
+
+fn pack_padded(indices: &[u64], bits: usize) -> LongArray {
+    let per_long = 64 / bits;
+    let longs = indices
+        .chunks(per_long)
+        .map(|chunk| {
+            let long = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |long, (i, v)| long | v << (i * bits));
+            long as i64
+        })
+        .collect();
+    LongArray::new(longs)
+}
+
+fn pack_tight(indices: &[u64], bits: usize) -> LongArray {
+    let mut longs = vec![0u64; indices.len() * bits / 64];
+    for (i, v) in indices.iter().enumerate() {
+        let start = i * bits;
+        longs[start / 64] |= v << (start % 64);
+        if start % 64 + bits > 64 {
+            longs[start / 64 + 1] |= v >> (64 - start % 64);
+        }
+    }
+    LongArray::new(longs.into_iter().map(|l| l as i64).collect())
+}
+
+/// Biome cell in the lower section is desert if x + z + y of the cell is odd.
+fn is_desert(x: usize, y: usize, z: usize) -> bool {
+    (x + y + z) % 2 == 1
+}
+
+fn chunk_1_18() -> JavaChunk {
+    let biome_cells: Vec<u64> = (0..64)
+        .map(|i| is_desert(i % 4, i / 16, (i / 4) % 4) as u64)
+        .collect();
+
+    let chunk = nbt!({
+        "DataVersion": 2860,
+        "Status": "full",
+        "sections": [
+            {
+                "Y": 0_i8,
+                "block_states": {
+                    "palette": palette(),
+                    "data": pack_padded(&indices(), 5),
+                },
+                "biomes": {
+                    "palette": ["minecraft:plains", "minecraft:desert"],
+                    "data": pack_padded(&biome_cells, 1),
+                },
+            },
+            {
+                "Y": 1_i8,
+                "block_states": {"palette": [{"Name": "minecraft:air"}]},
+                "biomes": {"palette": ["minecraft:plains"]},
+            },
+        ],
+    });
+
+    JavaChunk::from_bytes(&fastnbt::to_bytes(&chunk).unwrap()).unwrap()
+}
+
+fn chunk_pre18(data_version: i32, states: LongArray) -> JavaChunk {
+    // Biomes are for the whole chunk, 4x4x4 cells from the bottom up.
+    let biomes = (0..1024)
+        .map(|i| {
+            let (x, z, y) = (i % 4, (i / 4) % 4, i / 16);
+            match y < 4 && is_desert(x, y, z) {
+                true => 2,
+                false => 1,
+            }
+        })
+        .collect();
+
+    let chunk = nbt!({
+        "DataVersion": data_version,
+        "Level": {
+            "xPos": 0,
+            "zPos": 0,
+            "Status": "full",
+            "Biomes": IntArray::new(biomes),
+            "Sections": [
+                {"Y": -1_i8},
+                {
+                    "Y": 0_i8,
+                    "Palette": palette(),
+                    "BlockStates": states,
+                },
+                {"Y": 1_i8, "Palette": [{"Name": "minecraft:air"}]},
+            ],
+        },
+    });
+
+    JavaChunk::from_bytes(&fastnbt::to_bytes(&chunk).unwrap()).unwrap()
+}
+
+fn assert_equivalent(expected: &JavaChunk, actual: &JavaChunk) {
+    assert_eq!(expected.y_range(), actual.y_range());
+
+    for y in expected.y_range() {
+        for z in 0..16 {
+            for x in 0..16 {
+                assert_eq!(
+                    expected.block(x, y, z).map(|b| b.encoded_description()),
+                    actual.block(x, y, z).map(|b| b.encoded_description()),
+                    "block at {x}, {y}, {z}"
+                );
+                assert_eq!(
+                    format!("{:?}", expected.biome(x, y, z)),
+                    format!("{:?}", actual.biome(x, y, z)),
+                    "biome at {x}, {y}, {z}"
+                );
+            }
+        }
+    }
+
+    for y in (expected.y_range()).step_by(16) {
+        assert_eq!(expected.section_fill(y), actual.section_fill(y));
+
+        let counts = |chunk: &JavaChunk| -> Vec<(String, usize)> {
+            let mut counts: Vec<_> = chunk
+                .section_block_counts(y)
+                .unwrap()
+                .into_iter()
+                .map(|(b, n)| (b.encoded_description().to_owned(), n))
+                .collect();
+            counts.sort();
+            counts
+        };
+        assert_eq!(counts(expected), counts(actual));
+    }
+
+    for z in 0..16 {
+        for x in 0..16 {
+            assert_eq!(
+                expected.surface_height(x, z, HeightMode::Calculate),
+                actual.surface_height(x, z, HeightMode::Calculate)
+            );
+        }
+    }
+}
+
+#[test]
+fn chunk_1_18_sections() {
+    let chunk = chunk_1_18();
+    assert_eq!(chunk.y_range(), 0..32);
+
+    let indices = indices();
+    let sections = chunk.sections.as_ref().unwrap().sections();
+    assert_eq!(sections[0].palette().len(), 17);
+    assert_eq!(
+        sections[0].try_iter_indices().unwrap().collect::<Vec<_>>(),
+        indices.iter().map(|&i| i as usize).collect::<Vec<_>>()
+    );
+    assert_eq!(sections[1].palette().len(), 1);
+    assert!(sections[1].try_iter_indices().is_none());
+
+    assert_eq!(chunk.block(0, 0, 0).unwrap().name(), "minecraft:air");
+    assert_eq!(chunk.block(1, 0, 0).unwrap().name(), "minecraft:pink_wool");
+    assert_eq!(
+        chunk.block(0, 1, 0).unwrap().encoded_description(),
+        "minecraft:white_bed|facing=north,part=head"
+    );
+    assert_eq!(chunk.block(0, 20, 0).unwrap().name(), "minecraft:air");
+    assert!(chunk.block(0, 32, 0).is_none());
+}
+
+#[test]
+fn pre18_padded_matches_1_18() {
+    let chunk = chunk_pre18(2586, pack_padded(&indices(), 5));
+    assert_equivalent(&chunk_1_18(), &chunk);
+}
+
+#[test]
+fn pre18_tightly_packed_matches_1_18() {
+    let chunk = chunk_pre18(2230, pack_tight(&indices(), 5));
+    assert_equivalent(&chunk_1_18(), &chunk);
+}
+
+#[test]
+fn unpack_indices_of_every_layout() {
+    let expected: Vec<u16> = indices().iter().map(|&i| i as u16).collect();
+
+    for chunk in [
+        chunk_1_18(),
+        chunk_pre18(2586, pack_padded(&indices(), 5)),
+        chunk_pre18(2230, pack_tight(&indices(), 5)),
+    ] {
+        let sections = chunk.sections.as_ref().unwrap();
+        let (lower, upper) = (
+            sections.get_section_for_y(0).unwrap(),
+            sections.get_section_for_y(16).unwrap(),
+        );
+        let mut out = [u16::MAX; 4096];
+
+        lower.unpack_indices(&mut out);
+        assert_eq!(out.to_vec(), expected);
+        assert_eq!(
+            lower.palette()[out[1] as usize].name(),
+            "minecraft:pink_wool"
+        );
+
+        // Unpacking again after block lookups have cached the indices.
+        lower.block(0, 0, 0);
+        let mut again = [u16::MAX; 4096];
+        lower.unpack_indices(&mut again);
+        assert_eq!(again, out);
+
+        upper.unpack_indices(&mut out);
+        assert!(out.iter().all(|&i| i == 0));
+    }
+}
+
+#[test]
+fn unpack_indices_of_raw_sections() {
+    let expected: Vec<u16> = indices().iter().map(|&i| i as u16).collect();
+    let mut out = [u16::MAX; 4096];
+
+    let section: Section = fastnbt::from_value(&nbt!({
+        "Y": 0_i8,
+        "block_states": {"palette": palette(), "data": pack_padded(&indices(), 5)},
+    }))
+    .unwrap();
+    section.unpack_indices(&mut out);
+    assert_eq!(out.to_vec(), expected);
+    assert_eq!(section.palette().len(), 17);
+
+    let section: Pre18Section = fastnbt::from_value(&nbt!({
+        "Y": 0_i8,
+        "Palette": palette(),
+        "BlockStates": pack_tight(&indices(), 5),
+    }))
+    .unwrap();
+    out.fill(u16::MAX);
+    section.unpack_indices(&mut out);
+    assert_eq!(out.to_vec(), expected);
+    assert_eq!(section.palette().len(), 17);
+
+    let air: Section = fastnbt::from_value(&nbt!({
+        "Y": 1_i8,
+        "block_states": {"palette": [{"Name": "minecraft:air"}]},
+    }))
+    .unwrap();
+    air.unpack_indices(&mut out);
+    assert!(out.iter().all(|&i| i == 0));
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use fastnbt::{LongArray, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::{write_chunk_preserving, Chunk, JavaChunk};
+
+const MODDED: &[u8] = crate::fixtures::chunk_modded();
+
+/// Only some of the fields of a 1.18 chunk. The modded chunk has fields at
+/// the root and in its sections that these know nothing about.
+#[derive(Serialize, Deserialize)]
+struct PartialChunk {
+    #[serde(rename = "DataVersion")]
+    data_version: i32,
+    #[serde(rename = "Status")]
+    status: String,
+    sections: Vec<PartialSection>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PartialSection {
+    #[serde(rename = "Y")]
+    y: i8,
+    block_states: Option<PartialStates>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PartialStates {
+    palette: Vec<PartialBlock>,
+    data: Option<LongArray>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PartialBlock {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Properties")]
+    properties: Option<HashMap<String, String>>,
+}
+
+fn original() -> Value {
+    fastnbt::from_bytes(MODDED).unwrap()
+}
+
+#[test]
+fn unmodified_chunk_round_trips() {
+    let chunk: PartialChunk = fastnbt::from_bytes(MODDED).unwrap();
+    let data = write_chunk_preserving(MODDED, &chunk).unwrap();
+
+    let written: Value = fastnbt::from_bytes(&data).unwrap();
+    assert_eq!(written, original());
+    assert!(written.pointer("/ForgeCaps/mymod:energy/stored").is_some());
+    assert!(written.pointer("/sections/0/mymod:ores/0/kind").is_some());
+}
+
+#[test]
+fn modified_chunk_keeps_unknown_fields() {
+    let mut chunk: PartialChunk = fastnbt::from_bytes(MODDED).unwrap();
+    chunk.status = "features".to_owned();
+    let block = &mut chunk.sections[3].block_states.as_mut().unwrap().palette[0];
+    block.name = "minecraft:diamond_block".to_owned();
+
+    let data = write_chunk_preserving(MODDED, &chunk).unwrap();
+
+    let mut expected = original();
+    *expected.pointer_mut("/Status").unwrap() = Value::String("features".to_owned());
+    *expected
+        .pointer_mut("/sections/3/block_states/palette/0/Name")
+        .unwrap() = Value::String("minecraft:diamond_block".to_owned());
+    assert_eq!(fastnbt::from_bytes::<Value>(&data).unwrap(), expected);
+
+    let parsed = JavaChunk::from_bytes(&data).unwrap();
+    assert_eq!(parsed.status(), "features");
+}
+
+#[test]
+fn changed_lists_replace_the_original() {
+    let mut chunk: PartialChunk = fastnbt::from_bytes(MODDED).unwrap();
+    chunk.sections.truncate(1);
+
+    let data = write_chunk_preserving(MODDED, &chunk).unwrap();
+    let written: Value = fastnbt::from_bytes(&data).unwrap();
+
+    // The sections are the ones given, so lose their unknown fields, but
+    // the rest of the chunk is kept.
+    assert!(written.pointer("/sections/1").is_none());
+    assert!(written.pointer("/sections/0/mymod:ores").is_none());
+    assert_eq!(
+        written.pointer("/ForgeCaps"),
+        original().pointer("/ForgeCaps")
+    );
+}
@@ -1,7 +1,14 @@
 use std::io::{Cursor, Read, Seek, Write};
 
+use fastnbt::Value;
+
 use crate::{
-    ChunkLocation, CompressionScheme::Uncompressed, Error, Region, CHUNK_HEADER_SIZE, SECTOR_SIZE,
+    chunk_payload, decode_chunk, header_offset_for, sector_count_for_len, ChunkBitmap,
+    ChunkLocation, ChunkMeta, ChunkWrite, CompressionLevel, CompressionPolicy,
+    CompressionScheme::{Gzip, Uncompressed, Zlib},
+    Error, Region, RegionIndex, RegionProblem, RepairPolicy, ScanCursor, WriteMode,
+    CHUNKS_PER_REGION_SIDE, CHUNK_HEADER_SIZE, MAX_CHUNK_SECTORS, MAX_COMPRESSED_CHUNK_LEN,
+    REGION_HEADER_SIZE, SECTOR_SIZE,
 };
 
 fn new_empty() -> Region<Cursor<Vec<u8>>> {
@@ -30,8 +37,8 @@ fn n_sector_chunk(n: usize) -> Vec<u8> {
 fn new_region_should_be_empty() {
     let mut r = new_empty();
 
-    for x in 0..32 {
-        for z in 0..32 {
+    for x in 0..CHUNKS_PER_REGION_SIDE {
+        for z in 0..CHUNKS_PER_REGION_SIDE {
             let chunk = r.read_chunk(x, z);
             assert!(matches!(chunk, Ok(None)))
         }
@@ -82,6 +89,51 @@ fn oversized_chunk_fails() {
     assert!(matches!(res, Err(Error::ChunkTooLarge)))
 }
 
+#[test]
+fn sector_count_for_len_includes_chunk_header() {
+    assert_eq!(sector_count_for_len(0), 1);
+    assert_eq!(sector_count_for_len(SECTOR_SIZE - CHUNK_HEADER_SIZE), 1);
+    assert_eq!(sector_count_for_len(SECTOR_SIZE - CHUNK_HEADER_SIZE + 1), 2);
+    assert_eq!(sector_count_for_len(SECTOR_SIZE), 2);
+
+    assert_eq!(
+        sector_count_for_len(MAX_COMPRESSED_CHUNK_LEN),
+        MAX_CHUNK_SECTORS
+    );
+    assert_eq!(
+        sector_count_for_len(MAX_COMPRESSED_CHUNK_LEN + 1),
+        MAX_CHUNK_SECTORS + 1
+    );
+}
+
+#[test]
+fn largest_chunk_fits_in_max_sectors() {
+    let mut r = new_empty();
+    let write = r
+        .write_compressed_chunk(0, 0, Uncompressed, &vec![0; MAX_COMPRESSED_CHUNK_LEN])
+        .unwrap();
+    assert_eq!(write.sectors, MAX_CHUNK_SECTORS);
+    assert_location(&mut r, 0, 0, 2, 255);
+
+    let res = r.write_compressed_chunk(1, 0, Uncompressed, &vec![0; MAX_COMPRESSED_CHUNK_LEN + 1]);
+    assert!(matches!(res, Err(Error::ChunkTooLarge)))
+}
+
+#[test]
+fn header_offsets() {
+    assert_eq!(header_offset_for(0, 0), 0);
+    assert_eq!(header_offset_for(1, 0), 4);
+    assert_eq!(header_offset_for(31, 0), 124);
+    assert_eq!(header_offset_for(0, 1), 128);
+    assert_eq!(header_offset_for(31, 31), SECTOR_SIZE - 4);
+
+    // World chunk coordinates wrap to within the region.
+    assert_eq!(
+        header_offset_for(CHUNKS_PER_REGION_SIDE + 2, 3 * CHUNKS_PER_REGION_SIDE + 1),
+        header_offset_for(2, 1)
+    );
+}
+
 #[test]
 fn write_several_chunks() {
     let mut r = new_empty();
@@ -187,6 +239,144 @@ fn load_from_existing_buffer() {
     assert_location(&mut r, 0, 1, 3, 2);
 }
 
+#[test]
+fn read_raw_chunk_is_not_decompressed() {
+    let mut r = new_empty();
+    r.write_chunk(0, 0, &[1, 2, 3]).unwrap();
+
+    let (scheme, raw) = r.read_raw_chunk(0, 0).unwrap().unwrap();
+    assert_eq!(scheme, Zlib);
+    assert_ne!(raw, &[1, 2, 3]);
+    assert!(matches!(r.read_raw_chunk(0, 1), Ok(None)));
+    assert!(matches!(
+        r.read_raw_chunk(32, 0),
+        Err(Error::InvalidOffset(32, 0))
+    ));
+}
+
+#[test]
+fn write_raw_chunk_round_trips() {
+    let mut r = new_empty();
+    r.write_raw_chunk(3, 4, Uncompressed, &[1, 2, 3]).unwrap();
+
+    let (scheme, raw) = r.read_raw_chunk(3, 4).unwrap().unwrap();
+    assert_eq!(scheme, Uncompressed);
+    assert_eq!(raw, &[1, 2, 3]);
+}
+
+#[test]
+fn copy_chunk_to_other_region() {
+    let chunk = fastnbt::to_bytes(&fastnbt::nbt!({
+        "DataVersion": 2860,
+        "Status": "full",
+        "sections": [{"Y": 0_i8}],
+    }))
+    .unwrap();
+
+    let mut src = new_empty();
+    src.write_chunk(5, 6, &chunk).unwrap();
+
+    let mut dst = new_empty();
+    dst.write_compressed_chunk(5, 6, Uncompressed, &n_sector_chunk(2))
+        .unwrap();
+    dst.write_compressed_chunk(0, 0, Uncompressed, &[1, 2, 3])
+        .unwrap();
+
+    assert!(src.copy_chunk_to(&mut dst, 5, 6).unwrap());
+    assert!(!src.copy_chunk_to(&mut dst, 0, 0).unwrap());
+
+    // compressed bytes are identical, so the chunk parses identically.
+    assert_eq!(
+        src.read_raw_chunk(5, 6).unwrap(),
+        dst.read_raw_chunk(5, 6).unwrap()
+    );
+
+    let copied: Value = fastnbt::from_bytes(&dst.read_chunk(5, 6).unwrap().unwrap()).unwrap();
+    let original: Value = fastnbt::from_bytes(&chunk).unwrap();
+    assert_eq!(original, copied);
+
+    // absent chunks leave the destination untouched.
+    assert_eq!(dst.read_chunk(0, 0).unwrap().unwrap(), &[1, 2, 3]);
+}
+
+#[test]
+fn read_chunk_into_reuses_buffer() {
+    let mut r = new_empty();
+    r.write_chunk(0, 0, &[1, 2, 3]).unwrap();
+    r.write_compressed_chunk(1, 0, Uncompressed, &[4, 5])
+        .unwrap();
+
+    let mut buf = vec![9; 100];
+    assert!(r.read_chunk_into(0, 0, &mut buf).unwrap());
+    assert_eq!(buf, &[1, 2, 3]);
+
+    assert!(r.read_chunk_into(1, 0, &mut buf).unwrap());
+    assert_eq!(buf, &[4, 5]);
+
+    // absent chunks leave the buffer empty.
+    assert!(!r.read_chunk_into(2, 0, &mut buf).unwrap());
+    assert!(buf.is_empty());
+    assert!(buf.capacity() >= 100);
+
+    assert!(matches!(
+        r.read_chunk_into(32, 0, &mut buf),
+        Err(Error::InvalidOffset(32, 0))
+    ));
+}
+
+#[test]
+fn raw_chunk_slice_borrows_compressed_data() {
+    let mut r = new_empty();
+    r.write_chunk(0, 0, &[1, 2, 3]).unwrap();
+    r.write_compressed_chunk(1, 0, Uncompressed, &[4, 5])
+        .unwrap();
+
+    let (_, owned) = r.read_raw_chunk(0, 0).unwrap().unwrap();
+    let (scheme, raw) = r.raw_chunk_slice(0, 0).unwrap().unwrap();
+    assert_eq!(scheme, Zlib);
+    assert_eq!(raw, owned);
+
+    let (scheme, raw) = r.raw_chunk_slice(1, 0).unwrap().unwrap();
+    assert_eq!(scheme, Uncompressed);
+    assert_eq!(raw, &[4, 5]);
+
+    assert!(r.raw_chunk_slice(2, 0).unwrap().is_none());
+    assert!(matches!(
+        r.raw_chunk_slice(0, 32),
+        Err(Error::InvalidOffset(0, 32))
+    ));
+}
+
+#[test]
+fn decompress_chunk_into_matches_read_chunk() {
+    let mut r = new_empty();
+    r.write_chunk(0, 0, &[1, 2, 3]).unwrap();
+    r.write_compressed_chunk(1, 0, Uncompressed, &[4, 5])
+        .unwrap();
+
+    let mut buf = vec![9; 100];
+    assert!(r.decompress_chunk_into(0, 0, &mut buf).unwrap());
+    assert_eq!(buf, &[1, 2, 3]);
+
+    assert!(r.decompress_chunk_into(1, 0, &mut buf).unwrap());
+    assert_eq!(buf, &[4, 5]);
+
+    assert!(!r.decompress_chunk_into(2, 0, &mut buf).unwrap());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn raw_chunk_slice_of_truncated_region_errors() {
+    let mut r = new_empty();
+    r.write_chunk(0, 0, &[1, 2, 3]).unwrap();
+
+    let mut data = r.into_inner().unwrap().into_inner();
+    data.truncate(REGION_HEADER_SIZE + 6);
+    let r = Region::from_stream(Cursor::new(data.as_slice())).unwrap();
+
+    assert!(matches!(r.raw_chunk_slice(0, 0), Err(Error::IO(_))));
+}
+
 // TODO: Should we always zero out space? Would likely be good for compression.
 // TODO: defrag?
 
@@ -195,3 +385,979 @@ fn load_from_existing_buffer() {
 // abstraction on top of this providing this. Something that copies a region and
 // only write the to copy until done, then atomically moves the file over the
 // old region.
+
+#[test]
+fn timestamps_default_to_zero_and_can_be_set() {
+    let mut r = new_empty();
+    r.write_chunk(1, 2, &[1, 2, 3]).unwrap();
+    assert_eq!(r.timestamp(1, 2).unwrap(), 0);
+
+    r.set_timestamp(1, 2, 1_600_000_000).unwrap();
+    assert_eq!(r.timestamp(1, 2).unwrap(), 1_600_000_000);
+    assert_eq!(r.timestamp(2, 1).unwrap(), 0);
+
+    // timestamps survive reloading the region.
+    let mut r = Region::from_stream(r.into_inner().unwrap()).unwrap();
+    assert_eq!(r.timestamp(1, 2).unwrap(), 1_600_000_000);
+    assert!(matches!(
+        r.timestamp(32, 0),
+        Err(Error::InvalidOffset(32, 0))
+    ));
+}
+
+#[test]
+fn chunk_meta_reads_headers() {
+    let mut r = new_empty();
+    r.write_compressed_chunk(1, 2, Uncompressed, &n_sector_chunk(2))
+        .unwrap();
+    r.write_raw_chunk(3, 0, Gzip, &[1, 2, 3]).unwrap();
+    r.set_timestamp(1, 2, 1_600_000_000).unwrap();
+
+    assert_eq!(
+        r.chunk_meta(1, 2).unwrap(),
+        Some(ChunkMeta {
+            compressed_len: (2 * SECTOR_SIZE - CHUNK_HEADER_SIZE) as u32,
+            sectors: 2,
+            compression: Uncompressed,
+            timestamp: 1_600_000_000,
+        })
+    );
+    assert_eq!(
+        r.chunk_meta(3, 0).unwrap(),
+        Some(ChunkMeta {
+            compressed_len: 3,
+            sectors: 1,
+            compression: Gzip,
+            timestamp: 0,
+        })
+    );
+    assert_eq!(r.chunk_meta(0, 0).unwrap(), None);
+    assert!(matches!(
+        r.chunk_meta(0, 32),
+        Err(Error::InvalidOffset(0, 32))
+    ));
+}
+
+#[test]
+fn chunk_metas_of_every_present_chunk() {
+    let mut r = new_empty();
+    r.write_raw_chunk(5, 1, Zlib, &[0; 10]).unwrap();
+    r.write_raw_chunk(0, 0, Uncompressed, &n_sector_chunk(3))
+        .unwrap();
+
+    let metas = r
+        .chunk_metas()
+        .unwrap()
+        .collect::<crate::Result<Vec<_>>>()
+        .unwrap();
+    let found: Vec<_> = metas
+        .iter()
+        .map(|(x, z, meta)| (*x, *z, meta.compressed_len, meta.sectors, meta.compression))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (
+                0,
+                0,
+                (3 * SECTOR_SIZE - CHUNK_HEADER_SIZE) as u32,
+                3,
+                Uncompressed
+            ),
+            (5, 1, 10, 1, Zlib),
+        ]
+    );
+
+    let total: u64 = metas.iter().map(|(_, _, meta)| meta.sectors).sum();
+    assert_eq!(total, 4);
+
+    assert_eq!(new_empty().chunk_metas().unwrap().count(), 0);
+}
+
+#[test]
+fn from_read_yields_chunks_in_stream_order() {
+    let mut r = new_empty();
+    r.write_chunk(5, 5, &[1, 2, 3]).unwrap();
+    r.write_compressed_chunk(0, 0, Uncompressed, &n_sector_chunk(2))
+        .unwrap();
+    r.write_chunk(31, 31, &[4, 5]).unwrap();
+    let data = r.into_inner().unwrap().into_inner();
+
+    // A slice is Read but not Seek.
+    let chunks: Vec<_> = Region::from_read(data.as_slice())
+        .unwrap()
+        .map(|c| c.unwrap())
+        .map(|c| (c.x, c.z, c.data))
+        .collect();
+
+    assert_eq!(
+        chunks,
+        vec![
+            (5, 5, vec![1, 2, 3]),
+            (0, 0, n_sector_chunk(2)),
+            (31, 31, vec![4, 5])
+        ]
+    );
+}
+
+#[test]
+fn from_read_empty_region() {
+    let data = new_empty().into_inner().unwrap().into_inner();
+    assert_eq!(Region::from_read(data.as_slice()).unwrap().count(), 0);
+}
+
+#[test]
+fn from_read_truncated_stream_errors_then_stops() {
+    let mut r = new_empty();
+    r.write_chunk(0, 0, &[1, 2, 3]).unwrap();
+    r.write_compressed_chunk(1, 0, Uncompressed, &n_sector_chunk(1))
+        .unwrap();
+    let data = r.into_inner().unwrap().into_inner();
+    let data = &data[..data.len() - 10];
+
+    let mut chunks = Region::from_read(data).unwrap();
+    assert_eq!(chunks.next().unwrap().unwrap().data, vec![1, 2, 3]);
+    assert!(matches!(chunks.next(), Some(Err(Error::IO(_)))));
+    assert!(chunks.next().is_none());
+
+    assert!(Region::from_read(&data[..100]).is_err());
+}
+
+/// Apply a change to the raw bytes of a region and load it again.
+fn corrupt(r: Region<Cursor<Vec<u8>>>, f: impl FnOnce(&mut Vec<u8>)) -> Region<Cursor<Vec<u8>>> {
+    let mut data = r.into_inner().unwrap().into_inner();
+    f(&mut data);
+    Region::from_stream(Cursor::new(data)).unwrap()
+}
+
+fn set_location(data: &mut [u8], x: usize, z: usize, offset: u32, sectors: u8) {
+    let pos = 4 * (x + z * 32);
+    data[pos..pos + 3].copy_from_slice(&offset.to_be_bytes()[1..]);
+    data[pos + 3] = sectors;
+}
+
+/// A region with three chunks of a single sector each, at offsets 2, 3 and 4.
+fn three_chunks() -> Region<Cursor<Vec<u8>>> {
+    let mut r = new_empty();
+    r.write_chunk(0, 0, &[1; 100]).unwrap();
+    r.write_chunk(1, 0, &[2; 100]).unwrap();
+    r.write_chunk(2, 0, &[3; 100]).unwrap();
+    r
+}
+
+#[test]
+fn validate_healthy_region() {
+    let mut r = three_chunks();
+    r.write_compressed_chunk(0, 1, Uncompressed, &n_sector_chunk(3))
+        .unwrap();
+    assert_eq!(r.validate().unwrap(), vec![]);
+    assert_eq!(new_empty().validate().unwrap(), vec![]);
+}
+
+#[test]
+fn validate_finds_overlapping_chunks() {
+    // chunk (1, 0) now claims the sectors of (0, 0) too.
+    let mut r = corrupt(three_chunks(), |data| set_location(data, 1, 0, 2, 1));
+
+    assert_eq!(
+        r.validate().unwrap(),
+        vec![RegionProblem::Overlap {
+            x: 1,
+            z: 0,
+            other_x: 0,
+            other_z: 0,
+        }]
+    );
+}
+
+#[test]
+fn validate_finds_out_of_bounds_chunks() {
+    let mut r = corrupt(three_chunks(), |data| {
+        set_location(data, 0, 0, 1, 1);
+        set_location(data, 1, 0, 100, 1);
+    });
+
+    assert_eq!(
+        r.validate().unwrap(),
+        vec![
+            RegionProblem::OutOfBounds {
+                x: 0,
+                z: 0,
+                offset: 1,
+                sectors: 1,
+            },
+            RegionProblem::OutOfBounds {
+                x: 1,
+                z: 0,
+                offset: 100,
+                sectors: 1,
+            },
+        ]
+    );
+}
+
+#[test]
+fn validate_finds_length_exceeding_allocation() {
+    let mut r = corrupt(three_chunks(), |data| {
+        let start = 3 * SECTOR_SIZE;
+        data[start..start + 4].copy_from_slice(&5000u32.to_be_bytes());
+    });
+
+    let problems = r.validate().unwrap();
+    assert_eq!(
+        problems[0],
+        RegionProblem::LengthExceedsAllocation {
+            x: 1,
+            z: 0,
+            length: 5000,
+            allocated: SECTOR_SIZE as u64,
+        }
+    );
+    // the zlib data now has junk on the end, or runs off the end of the region.
+    assert!(matches!(
+        problems[1],
+        RegionProblem::Undecompressible { x: 1, z: 0, .. }
+    ));
+}
+
+#[test]
+fn validate_finds_bad_compression() {
+    let mut r = three_chunks();
+    r.write_compressed_chunk(3, 0, Zlib, &[1, 2, 3, 4]).unwrap();
+    let mut r = corrupt(r, |data| data[2 * SECTOR_SIZE + 4] = 9);
+
+    let problems = r.validate().unwrap();
+    assert_eq!(
+        problems[0],
+        RegionProblem::UnknownCompression {
+            x: 0,
+            z: 0,
+            scheme: 9
+        }
+    );
+    assert!(matches!(
+        problems[1],
+        RegionProblem::Undecompressible { x: 3, z: 0, .. }
+    ));
+    assert_eq!(problems.len(), 2);
+    assert_eq!(
+        problems[0].to_string(),
+        "chunk (0, 0) has unknown compression scheme (9)"
+    );
+}
+
+#[test]
+fn validate_finds_zero_length_chunk() {
+    let mut r = corrupt(three_chunks(), |data| {
+        let start = 2 * SECTOR_SIZE;
+        data[start..start + 4].copy_from_slice(&0u32.to_be_bytes());
+    });
+
+    assert!(matches!(
+        r.validate().unwrap()[..],
+        [RegionProblem::Undecompressible { x: 0, z: 0, .. }]
+    ));
+    assert!(matches!(r.read_chunk(0, 0), Err(Error::IO(_))));
+}
+
+#[test]
+fn repair_zero_broken_removes_broken_chunks() {
+    let mut r = three_chunks();
+    r.set_timestamp(2, 0, 1234).unwrap();
+    let mut r = corrupt(r, |data| {
+        set_location(data, 1, 0, 2, 1);
+        data[4 * SECTOR_SIZE + 4] = 9;
+    });
+
+    let problems = r.repair(RepairPolicy::ZeroBroken).unwrap();
+    assert_eq!(problems.len(), 2);
+
+    assert_eq!(r.validate().unwrap(), vec![]);
+    assert!(matches!(r.read_chunk(0, 0), Ok(None)));
+    assert!(matches!(r.read_chunk(1, 0), Ok(None)));
+    assert!(matches!(r.read_chunk(2, 0), Ok(None)));
+    assert_eq!(r.timestamp(2, 0).unwrap(), 0);
+
+    // the region can still be written to.
+    r.write_chunk(5, 5, &[4; 100]).unwrap();
+    assert_eq!(r.read_chunk(5, 5).unwrap().unwrap(), vec![4; 100]);
+    assert_eq!(r.validate().unwrap(), vec![]);
+}
+
+#[test]
+fn repair_repack_keeps_intact_chunks() {
+    let mut r = three_chunks();
+    r.set_timestamp(1, 0, 1234).unwrap();
+    let mut r = corrupt(r, |data| {
+        // (0, 0) claims the sector of (1, 0) too, but both are intact.
+        set_location(data, 0, 0, 2, 2);
+        data[4 * SECTOR_SIZE + 4] = 9;
+    });
+
+    let problems = r.repair(RepairPolicy::Repack).unwrap();
+    assert_eq!(problems.len(), 2);
+
+    assert_eq!(r.validate().unwrap(), vec![]);
+    assert_eq!(r.read_chunk(0, 0).unwrap().unwrap(), vec![1; 100]);
+    assert_eq!(r.read_chunk(1, 0).unwrap().unwrap(), vec![2; 100]);
+    assert!(matches!(r.read_chunk(2, 0), Ok(None)));
+    assert_eq!(r.timestamp(1, 0).unwrap(), 1234);
+    assert_location(&mut r, 0, 0, 2, 1);
+    assert_location(&mut r, 1, 0, 3, 1);
+
+    r.write_chunk(0, 0, &[5; 100]).unwrap();
+    assert_eq!(r.read_chunk(1, 0).unwrap().unwrap(), vec![2; 100]);
+}
+
+#[test]
+fn repack_into_new_region() {
+    let mut r = three_chunks();
+    r.set_timestamp(2, 0, 1234).unwrap();
+    let mut r = corrupt(r, |data| set_location(data, 1, 0, 100, 1));
+
+    let mut repacked = r.repack_into(Cursor::new(vec![])).unwrap();
+    assert_eq!(repacked.validate().unwrap(), vec![]);
+    assert_eq!(repacked.read_chunk(0, 0).unwrap().unwrap(), vec![1; 100]);
+    assert!(matches!(repacked.read_chunk(1, 0), Ok(None)));
+    assert_eq!(repacked.read_chunk(2, 0).unwrap().unwrap(), vec![3; 100]);
+    assert_eq!(repacked.timestamp(2, 0).unwrap(), 1234);
+
+    // the original is untouched.
+    assert_eq!(r.validate().unwrap().len(), 1);
+}
+
+#[test]
+fn repair_keeps_chunks_longer_than_their_sectors() {
+    let oversized = || {
+        let mut r = new_empty();
+        r.write_compressed_chunk(0, 0, Uncompressed, &n_sector_chunk(2))
+            .unwrap();
+        r.write_compressed_chunk(1, 0, Uncompressed, &[3; 100])
+            .unwrap();
+        // (0, 0) still has all its data, but only claims its first sector.
+        corrupt(r, |data| set_location(data, 0, 0, 2, 1))
+    };
+
+    let mut r = oversized();
+    assert!(matches!(
+        r.validate().unwrap()[..],
+        [RegionProblem::LengthExceedsAllocation { x: 0, z: 0, .. }]
+    ));
+
+    let mut repacked = r.repack_into(Cursor::new(vec![])).unwrap();
+    assert_eq!(repacked.validate().unwrap(), vec![]);
+    assert_eq!(
+        repacked.read_chunk(0, 0).unwrap().unwrap(),
+        n_sector_chunk(2)
+    );
+    assert_eq!(repacked.read_chunk(1, 0).unwrap().unwrap(), vec![3; 100]);
+
+    let mut r = oversized();
+    assert_eq!(r.repair(RepairPolicy::Repack).unwrap().len(), 1);
+    assert_eq!(r.validate().unwrap(), vec![]);
+    assert_eq!(r.read_chunk(0, 0).unwrap().unwrap(), n_sector_chunk(2));
+    assert_eq!(r.read_chunk(1, 0).unwrap().unwrap(), vec![3; 100]);
+}
+
+/// Read chunks the way async code would: fetch the header and each chunk's
+/// bytes separately, leaving the parsing and decoding to the sans-io API.
+#[test]
+fn region_index_reads_chunks_without_region() {
+    let mut r = new_empty();
+    r.write_chunk(0, 0, &[1, 2, 3]).unwrap();
+    r.write_compressed_chunk(31, 1, Uncompressed, &[4, 5])
+        .unwrap();
+    r.write_chunk(2, 31, &n_sector_chunk(3)).unwrap();
+    r.set_timestamp(31, 1, 1234).unwrap();
+
+    let expected: Vec<_> = [(0, 0), (31, 1), (2, 31)]
+        .into_iter()
+        .map(|(x, z)| r.read_chunk(x, z).unwrap().unwrap())
+        .collect();
+
+    let bytes = r.into_inner().unwrap().into_inner();
+    let header = bytes[..REGION_HEADER_SIZE].try_into().unwrap();
+    let index = RegionIndex::parse(header);
+
+    let chunks: Vec<_> = index
+        .chunks()
+        .map(|(x, z, loc)| {
+            let range = loc.byte_range();
+            // The last chunk is not padded to a whole sector.
+            let end = (range.end as usize).min(bytes.len());
+            let sectors = &bytes[range.start as usize..end];
+            let (scheme, payload) = chunk_payload(sectors).unwrap();
+            ((x, z), decode_chunk(scheme, payload).unwrap())
+        })
+        .collect();
+
+    assert_eq!(
+        chunks.iter().map(|(xz, _)| *xz).collect::<Vec<_>>(),
+        [(0, 0), (31, 1), (2, 31)]
+    );
+    for ((_, actual), expected) in chunks.iter().zip(&expected) {
+        assert_eq!(actual, expected);
+    }
+
+    assert_eq!(index.timestamp(31, 1).unwrap(), 1234);
+    assert_eq!(index.location(1, 0).unwrap(), None);
+    assert!(matches!(
+        index.location(32, 0),
+        Err(Error::InvalidOffset(32, 0))
+    ));
+}
+
+#[test]
+fn region_index_matches_region() {
+    let mut r = new_empty();
+    r.write_chunk(3, 4, &[1, 2, 3]).unwrap();
+    r.write_chunk(4, 3, &n_sector_chunk(2)).unwrap();
+
+    let index = r.index().unwrap();
+    for z in 0..CHUNKS_PER_REGION_SIDE {
+        for x in 0..CHUNKS_PER_REGION_SIDE {
+            let loc = r.location(x, z).unwrap();
+            assert_eq!(
+                index.location(x, z).unwrap(),
+                Some(loc).filter(|l| l.offset != 0)
+            );
+        }
+    }
+}
+
+#[test]
+fn chunk_payload_of_truncated_chunk_fails() {
+    let mut r = new_empty();
+    r.write_compressed_chunk(0, 0, Uncompressed, &[1, 2, 3, 4])
+        .unwrap();
+
+    let bytes = r.into_inner().unwrap().into_inner();
+    let start = 2 * SECTOR_SIZE;
+    let (_, payload) = chunk_payload(&bytes[start..]).unwrap();
+    assert_eq!(payload, [1, 2, 3, 4]);
+
+    let truncated = &bytes[start..start + CHUNK_HEADER_SIZE + 2];
+    assert!(matches!(chunk_payload(truncated), Err(Error::IO(_))));
+    assert!(matches!(
+        decode_chunk(Zlib, &[1, 2, 3]),
+        Err(Error::Decompress(_))
+    ));
+}
+
+#[test]
+fn corrupt_chunks_keep_their_error_type() {
+    let chunk = n_sector_chunk(2);
+    let corrupt = |scheme| {
+        let policy = CompressionPolicy::new(scheme, CompressionLevel::Best);
+        let (_, mut compressed) = policy.compress(&chunk).unwrap();
+        let mid = compressed.len() / 2;
+        compressed[mid..mid + 8].fill(0xff);
+        decode_chunk(scheme, &compressed).unwrap_err()
+    };
+
+    assert!(matches!(corrupt(Zlib), Error::Decompress(_)));
+    assert!(matches!(
+        corrupt(Gzip),
+        Error::IO(e) if e.kind() == std::io::ErrorKind::InvalidInput
+    ));
+}
+
+#[test]
+fn decode_chunk_of_truncated_stream_is_unexpected_eof() {
+    let policy = CompressionPolicy::new(Zlib, CompressionLevel::Best);
+    let (_, compressed) = policy.compress(&n_sector_chunk(2)).unwrap();
+
+    let err = decode_chunk(Zlib, &compressed[..compressed.len() / 2]).unwrap_err();
+    assert!(matches!(&err, Error::IO(e) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+}
+
+#[test]
+fn errors_keep_their_source() {
+    use std::error::Error as _;
+
+    let err = decode_chunk(Zlib, &[1, 2, 3]).unwrap_err();
+    assert!(err.source().unwrap().is::<flate2::DecompressError>());
+
+    /// A stream that times out reading anything after the region header.
+    struct TimesOut(Cursor<Vec<u8>>);
+
+    impl Read for TimesOut {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.position() >= REGION_HEADER_SIZE as u64 {
+                return Err(std::io::ErrorKind::TimedOut.into());
+            }
+            self.0.read(buf)
+        }
+    }
+
+    impl Seek for TimesOut {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    let mut r = new_empty();
+    r.write_chunk(0, 0, &[1, 2, 3]).unwrap();
+    let mut r = Region::from_stream(TimesOut(r.into_inner().unwrap())).unwrap();
+
+    let err = r.read_chunk(0, 0).unwrap_err();
+    let source = err.source().unwrap().downcast_ref::<std::io::Error>();
+    assert_eq!(source.unwrap().kind(), std::io::ErrorKind::TimedOut);
+}
+
+/// A region as the game might leave it: chunks padded to whole sectors with
+/// stale bytes after their data, more sectors than a chunk needs, and an
+/// unused sector between chunks.
+fn vanilla_like_region() -> Vec<u8> {
+    let mut data = vec![0u8; REGION_HEADER_SIZE];
+
+    let put = |data: &mut Vec<u8>, x: usize, z: usize, sectors: usize, payload: &[u8]| {
+        let offset = data.len() / SECTOR_SIZE;
+        let pos = 4 * (z * 32 + x);
+        data[pos..pos + 4].copy_from_slice(&[0, 0, offset as u8, sectors as u8]);
+        data[SECTOR_SIZE + pos..SECTOR_SIZE + pos + 4].copy_from_slice(&1234u32.to_be_bytes());
+
+        data.extend((payload.len() as u32 + 1).to_be_bytes());
+        data.push(Uncompressed as u8);
+        data.extend(payload);
+        data.resize((offset + sectors) * SECTOR_SIZE, 0xab);
+    };
+
+    put(&mut data, 0, 0, 2, b"hello");
+    put(&mut data, 5, 7, 2, &[7; 5000]);
+    data.resize(data.len() + SECTOR_SIZE, 0xcd);
+    put(&mut data, 31, 31, 1, b"bye");
+    data
+}
+
+fn rewrite_all(mode: WriteMode) -> Vec<u8> {
+    let mut r = Region::from_stream(Cursor::new(vanilla_like_region()))
+        .unwrap()
+        .with_write_mode(mode);
+
+    for z in 0..CHUNKS_PER_REGION_SIDE {
+        for x in 0..CHUNKS_PER_REGION_SIDE {
+            if let Some((scheme, data)) = r.read_raw_chunk(x, z).unwrap() {
+                let timestamp = r.timestamp(x, z).unwrap();
+                r.write_raw_chunk(x, z, scheme, &data).unwrap();
+                r.set_timestamp(x, z, timestamp).unwrap();
+            }
+        }
+    }
+
+    r.into_inner().unwrap().into_inner()
+}
+
+#[test]
+fn preserve_rewrite_of_unmodified_region_is_identical() {
+    assert_eq!(rewrite_all(WriteMode::Preserve), vanilla_like_region());
+
+    // Reusing space shrinks the sector count of the first chunk.
+    assert_ne!(rewrite_all(WriteMode::Reuse), vanilla_like_region());
+}
+
+#[test]
+fn preserve_only_touches_modified_chunks() {
+    let original = vanilla_like_region();
+    let mut r = Region::from_stream(Cursor::new(original.clone()))
+        .unwrap()
+        .with_write_mode(WriteMode::Preserve);
+
+    // Fits in its sectors, so stays put with the same sector count.
+    r.write_compressed_chunk(0, 0, Uncompressed, b"hi").unwrap();
+    assert_location(&mut r, 0, 0, 2, 2);
+
+    // Too big for its sectors, so moves to the end.
+    r.write_compressed_chunk(5, 7, Uncompressed, &[8; 10000])
+        .unwrap();
+    assert_location(&mut r, 5, 7, 8, 3);
+
+    assert_eq!(r.read_chunk(0, 0).unwrap().unwrap(), b"hi");
+    assert_eq!(r.read_chunk(5, 7).unwrap().unwrap(), [8; 10000]);
+    assert_eq!(r.read_chunk(31, 31).unwrap().unwrap(), b"bye");
+
+    let data = r.into_inner().unwrap().into_inner();
+    let chunk_start = 2 * SECTOR_SIZE;
+    let moved_header = header_offset_for(5, 7);
+
+    // The first chunk's sectors are rewritten and zero padded.
+    assert_eq!(data[chunk_start + CHUNK_HEADER_SIZE..][..2], *b"hi");
+    assert!(data[chunk_start + CHUNK_HEADER_SIZE + 2..4 * SECTOR_SIZE]
+        .iter()
+        .all(|&b| b == 0));
+
+    // Everything else before the moved chunk is untouched, including the
+    // sectors it left behind.
+    assert_eq!(data[..moved_header], original[..moved_header]);
+    assert_eq!(
+        data[moved_header + 4..chunk_start],
+        original[moved_header + 4..chunk_start]
+    );
+    assert_eq!(
+        data[4 * SECTOR_SIZE..original.len()],
+        original[4 * SECTOR_SIZE..]
+    );
+    assert_eq!(data.len(), 11 * SECTOR_SIZE);
+}
+
+const CHUNK_21W44A_1: &[u8] = crate::fixtures::chunk_1_18();
+
+#[test]
+fn every_compression_policy_reads_back() {
+    for policy in [
+        CompressionPolicy::default(),
+        CompressionPolicy::new(Zlib, CompressionLevel::Best),
+        CompressionPolicy::new(Gzip, CompressionLevel::Default),
+        CompressionPolicy::new(Zlib, CompressionLevel::Custom(3)),
+        CompressionPolicy::new(Zlib, CompressionLevel::Custom(100)),
+        CompressionPolicy::auto(CompressionLevel::Fastest),
+        CompressionPolicy::uncompressed(),
+    ] {
+        let mut r = new_empty().with_compression(policy);
+        let write = r.write_chunk(1, 2, CHUNK_21W44A_1).unwrap();
+
+        assert_eq!(write.scheme, policy.scheme, "{policy:?}");
+        assert_eq!(
+            r.read_raw_chunk(1, 2).unwrap().unwrap().1.len(),
+            write.compressed_len
+        );
+        assert_location(&mut r, 1, 2, 2, write.sectors as u64);
+        assert_eq!(r.read_chunk(1, 2).unwrap().unwrap(), CHUNK_21W44A_1);
+    }
+}
+
+#[test]
+fn better_compression_is_smaller() {
+    let len = |level| {
+        let policy = CompressionPolicy::new(Zlib, level);
+        policy.compress(CHUNK_21W44A_1).unwrap().1.len()
+    };
+
+    assert!(len(CompressionLevel::Best) < len(CompressionLevel::Fastest));
+    assert!(len(CompressionLevel::Fastest) < len(CompressionLevel::Custom(0)));
+}
+
+#[test]
+fn auto_compression_stores_tiny_chunks_uncompressed() {
+    let mut r = new_empty().with_compression(CompressionPolicy::auto(CompressionLevel::Best));
+
+    let write = r.write_chunk(0, 0, &[1, 2, 3]).unwrap();
+    assert_eq!(
+        write,
+        ChunkWrite {
+            scheme: Uncompressed,
+            compressed_len: 3,
+            sectors: 1
+        }
+    );
+
+    // Random data doesn't compress, so isn't worth compressing.
+    let noise: Vec<u8> = crate::bits::tests::random_longs(3 * SECTOR_SIZE, 0x2545f4914f6cdd1d)
+        .into_iter()
+        .map(|l| l as u8)
+        .collect();
+    let write = r.write_chunk(1, 0, &noise).unwrap();
+    assert_eq!(write.scheme, Uncompressed);
+    assert_eq!(r.read_chunk(1, 0).unwrap().unwrap(), noise);
+
+    let write = r.write_chunk(2, 0, CHUNK_21W44A_1).unwrap();
+    assert_eq!(write.scheme, Zlib);
+}
+
+#[test]
+fn iter_from_cursor_carries_on_where_iteration_stopped() {
+    let mut r = three_chunks();
+    r.write_chunk(5, 7, &[4; 100]).unwrap();
+
+    let mut iter = r.iter();
+    let first = iter.next().unwrap().unwrap();
+    assert_eq!((first.x, first.z), (0, 0));
+    let cursor = iter.cursor();
+
+    // Cursors survive being saved.
+    let saved = serde_json::to_string(&cursor).unwrap();
+    let cursor: ScanCursor = serde_json::from_str(&saved).unwrap();
+
+    let rest: Vec<_> = r
+        .iter_from(&cursor)
+        .unwrap()
+        .map(|c| {
+            let c = c.unwrap();
+            (c.x, c.z, c.data[0])
+        })
+        .collect();
+    assert_eq!(rest, vec![(1, 0, 2), (2, 0, 3), (5, 7, 4)]);
+
+    let mut iter = r.iter_from(&cursor).unwrap();
+    iter.by_ref().for_each(drop);
+    let end = iter.cursor();
+    assert!(r.iter_from(&end).unwrap().next().is_none());
+}
+
+#[test]
+fn iter_from_default_cursor_is_whole_region() {
+    let mut r = three_chunks();
+    assert_eq!(r.iter_from(&ScanCursor::default()).unwrap().count(), 3);
+}
+
+#[test]
+fn iter_from_changed_region_errors() {
+    let mut r = three_chunks();
+    let mut iter = r.iter();
+    iter.next().unwrap().unwrap();
+    let cursor = iter.cursor();
+
+    // Unchanged chunks being read doesn't matter.
+    r.read_chunk(2, 0).unwrap();
+    assert!(r.iter_from(&cursor).is_ok());
+
+    r.set_timestamp(2, 0, 1234).unwrap();
+    assert!(matches!(r.iter_from(&cursor), Err(Error::StaleCursor)));
+
+    let mut r = three_chunks();
+    r.write_chunk(9, 9, &[5; 100]).unwrap();
+    assert!(matches!(r.iter_from(&cursor), Err(Error::StaleCursor)));
+}
+
+/// A chunk that is tiny compressed but 4 MiB uncompressed.
+fn compression_bomb(scheme: crate::CompressionScheme) -> Vec<u8> {
+    let policy = CompressionPolicy::new(scheme, CompressionLevel::Default);
+    let (_, compressed) = policy.compress(&vec![0; 4 * 1024 * 1024]).unwrap();
+    assert!(compressed.len() < 16 * 1024);
+    compressed
+}
+
+#[test]
+fn oversized_chunk_length_is_rejected_before_allocating() {
+    // A one sector chunk whose header claims it is nearly 4 GiB.
+    let mut r = new_empty();
+    r.write_raw_chunk(1, 2, Zlib, &[0; 10]).unwrap();
+    let mut data = r.into_inner().unwrap().into_inner();
+    let start = REGION_HEADER_SIZE;
+    data[start..start + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+
+    let mut r = Region::from_stream(Cursor::new(data.clone())).unwrap();
+    let is_invalid =
+        |err: &Error| matches!(err, Error::IO(e) if e.kind() == std::io::ErrorKind::InvalidData);
+
+    let err = r.read_chunk(1, 2).unwrap_err();
+    assert!(is_invalid(&err), "{err}");
+    let err = r.read_raw_chunk(1, 2).unwrap_err();
+    assert!(is_invalid(&err), "{err}");
+    let mut buf = vec![];
+    let err = r.read_chunk_into(1, 2, &mut buf).unwrap_err();
+    assert!(is_invalid(&err), "{err}");
+    assert_eq!(buf.capacity(), 0);
+
+    let chunks: Vec<_> = Region::from_read(data.as_slice()).unwrap().collect();
+    assert!(matches!(&chunks[..], [Err(err)] if is_invalid(err)));
+}
+
+#[test]
+fn compressed_length_over_max_uncompressed_len_is_rejected() {
+    let limit = 64;
+    let mut r = new_empty().with_max_uncompressed_len(limit);
+    r.write_raw_chunk(1, 2, Zlib, &[0; 1000]).unwrap();
+
+    let mut buf = vec![];
+    assert!(matches!(
+        r.read_chunk_into(1, 2, &mut buf),
+        Err(Error::UncompressedTooLarge { x: 1, z: 2, .. })
+    ));
+    assert_eq!(buf.capacity(), 0);
+
+    let data = r.into_inner().unwrap().into_inner();
+    let stream = Region::from_read(data.as_slice())
+        .unwrap()
+        .with_max_uncompressed_len(limit);
+    let chunks: Vec<_> = stream.collect();
+    assert!(matches!(
+        &chunks[..],
+        [Err(Error::UncompressedTooLarge { x: 1, z: 2, .. })]
+    ));
+}
+
+#[test]
+fn reading_stops_at_max_uncompressed_len() {
+    let limit = 64 * 1024;
+
+    for scheme in [Zlib, Gzip] {
+        let mut r = new_empty().with_max_uncompressed_len(limit);
+        r.write_raw_chunk(3, 4, scheme, &compression_bomb(scheme))
+            .unwrap();
+
+        let mut buf = vec![];
+        let err = r.read_chunk_into(3, 4, &mut buf).unwrap_err();
+        assert!(
+            matches!(err, Error::UncompressedTooLarge { x: 3, z: 4, limit: l } if l == limit),
+            "{scheme:?}: {err}"
+        );
+        assert!(buf.is_empty());
+        // Nowhere near the 4 MiB the chunk would be.
+        assert!(
+            buf.capacity() <= 2 * limit,
+            "{scheme:?}: {}",
+            buf.capacity()
+        );
+    }
+}
+
+#[test]
+fn zlib_buffer_grows_no_further_than_max_uncompressed_len() {
+    let limit = 64 * 1024;
+    let mut r = new_empty().with_max_uncompressed_len(limit);
+    r.write_raw_chunk(0, 0, Zlib, &compression_bomb(Zlib))
+        .unwrap();
+
+    let mut buf = vec![];
+    assert!(r.read_chunk_into(0, 0, &mut buf).is_err());
+    assert!(buf.capacity() <= limit + 1, "{}", buf.capacity());
+}
+
+#[test]
+fn chunk_of_exactly_max_uncompressed_len_is_read() {
+    let data = n_sector_chunk(3);
+
+    for scheme in [Zlib, Gzip, Uncompressed] {
+        let policy = CompressionPolicy::new(scheme, CompressionLevel::Default);
+        let mut r = new_empty().with_compression(policy);
+        r.write_chunk(0, 0, &data).unwrap();
+
+        let mut r = r.with_max_uncompressed_len(data.len());
+        assert_eq!(r.read_chunk(0, 0).unwrap().unwrap(), data, "{scheme:?}");
+
+        let mut r = r.with_max_uncompressed_len(data.len() - 1);
+        assert!(
+            matches!(
+                r.read_chunk(0, 0),
+                Err(Error::UncompressedTooLarge { x: 0, z: 0, .. })
+            ),
+            "{scheme:?}"
+        );
+    }
+}
+
+#[test]
+fn other_read_paths_stop_at_max_uncompressed_len() {
+    let limit = 64 * 1024;
+    let mut r = new_empty();
+    r.write_raw_chunk(1, 2, Zlib, &compression_bomb(Zlib))
+        .unwrap();
+    r.write_chunk(0, 0, &[1, 2, 3]).unwrap();
+
+    // Decompressing from the backing buffer.
+    let data = r.into_inner().unwrap().into_inner();
+    let r = Region::from_stream(Cursor::new(data.as_slice()))
+        .unwrap()
+        .with_max_uncompressed_len(limit);
+    let mut buf = vec![];
+    assert!(matches!(
+        r.decompress_chunk_into(1, 2, &mut buf),
+        Err(Error::UncompressedTooLarge { x: 1, z: 2, .. })
+    ));
+
+    // Validation reports the chunk rather than decompressing all of it.
+    let mut r = r;
+    let problems = r.validate().unwrap();
+    assert!(
+        matches!(
+            &problems[..],
+            [RegionProblem::Undecompressible { x: 1, z: 2, reason }] if reason.contains("65536")
+        ),
+        "{problems:?}"
+    );
+
+    // Streaming, which stops at the first error.
+    let chunks: Vec<_> = Region::from_read(data.as_slice())
+        .unwrap()
+        .with_max_uncompressed_len(limit)
+        .collect();
+    assert!(matches!(
+        &chunks[..],
+        [Err(Error::UncompressedTooLarge { x: 1, z: 2, .. })]
+    ));
+}
+
+/// Every x, z where `x + 2z` is a multiple of 5, and each corner.
+fn chunk_pattern() -> Vec<(usize, usize)> {
+    let mut pattern = vec![];
+    for z in 0..CHUNKS_PER_REGION_SIDE {
+        for x in 0..CHUNKS_PER_REGION_SIDE {
+            let corner = (x == 0 || x == 31) && (z == 0 || z == 31);
+            if (x + 2 * z) % 5 == 0 || corner {
+                pattern.push((x, z));
+            }
+        }
+    }
+    pattern
+}
+
+#[test]
+fn present_chunks_of_patterned_region() {
+    let expected = chunk_pattern();
+    let chunks: Vec<_> = expected.iter().map(|&(x, z)| (x, z, &[1u8][..])).collect();
+    let mut r = Region::from_stream(crate::fixtures::region(&chunks)).unwrap();
+
+    assert_eq!(r.present_chunks().unwrap().collect::<Vec<_>>(), expected);
+    assert_eq!(r.chunk_count().unwrap(), expected.len());
+
+    let bitmap = r.chunk_bitmap().unwrap();
+    assert_eq!(bitmap.len(), expected.len());
+    for z in 0..CHUNKS_PER_REGION_SIDE {
+        for x in 0..CHUNKS_PER_REGION_SIDE {
+            assert_eq!(
+                bitmap.contains(x, z),
+                expected.contains(&(x, z)),
+                "{x}, {z}"
+            );
+        }
+    }
+
+    let index = r.index().unwrap();
+    assert_eq!(index.chunk_bitmap(), bitmap);
+    assert!(index.chunks().map(|(x, z, _)| (x, z)).eq(bitmap.iter()));
+}
+
+#[test]
+fn present_chunks_of_empty_region() {
+    let mut r = new_empty();
+
+    assert_eq!(r.present_chunks().unwrap().count(), 0);
+    assert_eq!(r.chunk_count().unwrap(), 0);
+    assert!(r.chunk_bitmap().unwrap().is_empty());
+}
+
+#[test]
+fn present_chunks_follow_writes() {
+    let mut r = new_empty();
+    r.write_chunk(7, 9, &[1, 2, 3]).unwrap();
+    r.write_chunk(0, 31, &[1, 2, 3]).unwrap();
+
+    assert_eq!(
+        r.present_chunks().unwrap().collect::<Vec<_>>(),
+        [(7, 9), (0, 31)]
+    );
+}
+
+#[test]
+fn chunk_bitmap_words() {
+    let mut bitmap = ChunkBitmap::new();
+    bitmap.insert(0, 0);
+    bitmap.insert(31, 1);
+    bitmap.insert(31, 31);
+
+    let mut words = [0; 16];
+    words[0] = 1 | 1 << 63;
+    words[15] = 1 << 63;
+    assert_eq!(bitmap.words(), &words);
+    assert_eq!(ChunkBitmap::from_words(words), bitmap);
+
+    assert!(!bitmap.contains(32, 0));
+    assert!(!bitmap.contains(0, 32));
+}
+
+#[test]
+#[should_panic]
+fn chunk_bitmap_insert_outside_region_panics() {
+    ChunkBitmap::new().insert(32, 0);
+}
@@ -0,0 +1,981 @@
+use std::{cell::RefCell, convert::TryFrom, ops::Range};
+
+use crate::{
+    biome::Biome, render_chunk, render_region, render_region_elevation, render_region_incremental,
+    render_region_with_context, render_region_with_diagnostics, render_region_with_policy, Block,
+    BlockArchetype, CCoord, Chunk, ChunkErrorPolicy, DebugPalette, DepthRamp, DiagnosticKind,
+    DimensionOpts, HeightMode, JavaChunk, NeighbourHeights, OnChunkError, Palette, RCoord,
+    RegionLoader, RenderMode, Rgba, SurfaceRules, TopShadeRenderer, WaterDepthShading,
+};
+
+use super::{MemoryLoader, NoFastPath};
+
+const CHUNK_1_17_1: &[u8] = crate::fixtures::chunk_1_17();
+const CHUNK_21W44A_1: &[u8] = crate::fixtures::chunk_1_18();
+
+/// Gives each block a colour based on its name, so different chunks render
+/// differently.
+struct NamePalette;
+
+impl Palette for NamePalette {
+    fn pick(&self, block: &Block, _: Option<Biome>) -> Rgba {
+        let name = block.name();
+        [name.len() as u8, name.as_bytes()[10], 0, 255]
+    }
+}
+
+fn renderer() -> TopShadeRenderer<'static, NamePalette> {
+    TopShadeRenderer::new(&NamePalette, HeightMode::Trust)
+}
+
+fn loader_with_chunks() -> MemoryLoader {
+    let loader = MemoryLoader::default();
+    for z in 0..4 {
+        for x in 0..4 {
+            loader.write(0, 0, x, z, CHUNK_1_17_1, 100);
+        }
+        loader.write(0, -1, z, 31, CHUNK_1_17_1, 100);
+    }
+    loader
+}
+
+#[test]
+fn loader_reads_chunks_by_world_coordinates() {
+    let loader = MemoryLoader::default();
+    loader.write(0, 0, 3, 4, CHUNK_1_17_1, 100);
+    loader.write(-1, -1, 31, 30, CHUNK_21W44A_1, 100);
+
+    assert_eq!(
+        loader.read_chunk(CCoord(3), CCoord(4)).unwrap().unwrap(),
+        CHUNK_1_17_1
+    );
+
+    let mut buf = vec![];
+    assert!(loader
+        .read_chunk_into(CCoord(-1), CCoord(-2), &mut buf)
+        .unwrap());
+    assert_eq!(buf, CHUNK_21W44A_1);
+
+    // missing chunks and missing regions are both absent.
+    assert!(!loader
+        .read_chunk_into(CCoord(4), CCoord(4), &mut buf)
+        .unwrap());
+    assert!(buf.is_empty());
+    assert!(loader.read_chunk(CCoord(40), CCoord(0)).unwrap().is_none());
+}
+
+#[test]
+fn loader_parses_chunks_by_world_coordinates() {
+    #[derive(serde::Deserialize)]
+    struct StatusOnly {
+        #[serde(rename = "Status")]
+        status: String,
+    }
+
+    let loader = MemoryLoader::default();
+    loader.write(-1, -1, 31, 30, CHUNK_21W44A_1, 100);
+    loader.write(0, 0, 5, 6, &[1, 2, 3], 100);
+
+    let chunk = loader.chunk(CCoord(-1), CCoord(-2)).unwrap().unwrap();
+    assert_eq!(chunk.status, "full");
+    let chunk: StatusOnly = loader.chunk_as(CCoord(-1), CCoord(-2)).unwrap().unwrap();
+    assert_eq!(chunk.status, "full");
+
+    assert!(loader.chunk(CCoord(4), CCoord(4)).unwrap().is_none());
+    assert!(loader
+        .chunk_as::<StatusOnly>(CCoord(40), CCoord(0))
+        .unwrap()
+        .is_none());
+
+    let err = loader.chunk(CCoord(5), CCoord(6)).unwrap_err();
+    assert!(
+        err.to_string().starts_with("could not parse chunk"),
+        "{err}"
+    );
+    assert!(loader.chunk_as::<StatusOnly>(CCoord(5), CCoord(6)).is_err());
+}
+
+#[test]
+fn render_reports_chunks_it_could_not_render() {
+    let loader = MemoryLoader::default();
+    loader.write(-1, 2, 0, 0, CHUNK_1_17_1, 100);
+    loader.write(-1, 2, 5, 6, &[1, 2, 3], 100);
+
+    let mut diagnostics = vec![];
+    render_region_with_diagnostics(
+        RCoord(-1),
+        RCoord(2),
+        &loader,
+        renderer(),
+        None,
+        &mut diagnostics,
+    );
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].chunk, Some((CCoord(-27), CCoord(70))));
+    assert!(matches!(
+        diagnostics[0].kind,
+        DiagnosticKind::UnparsableChunk(_)
+    ));
+}
+
+/// A region of good chunks with one corrupt chunk, cut short part way
+/// through, at chunk 2, 1 of the region.
+fn loader_with_corrupt_chunk() -> MemoryLoader {
+    let loader = loader_with_chunks();
+    loader.write(0, 0, 2, 1, &CHUNK_1_17_1[..CHUNK_1_17_1.len() / 2], 100);
+    loader
+}
+
+fn render_with_policy(
+    loader: &MemoryLoader,
+    on_error: OnChunkError,
+) -> Result<(crate::RegionMap<Rgba>, crate::RenderReport), crate::Diagnostic> {
+    let policy = ChunkErrorPolicy {
+        on_error,
+        catch_panics: false,
+    };
+    render_region_with_policy(RCoord(0), RCoord(0), loader, renderer(), None, policy)
+}
+
+#[test]
+fn render_policy_skips_corrupt_chunk() {
+    let loader = loader_with_corrupt_chunk();
+    let (map, report) = render_with_policy(&loader, OnChunkError::Skip).unwrap();
+
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].chunk, Some((CCoord(2), CCoord(1))));
+    assert!(matches!(
+        report.skipped[0].kind,
+        DiagnosticKind::UnparsableChunk(_)
+    ));
+
+    // Every other chunk is rendered, and the corrupt one is blank.
+    assert!(map.chunk(CCoord(2), CCoord(1)).iter().all(|c| *c == [0; 4]));
+    for (x, z) in [(1, 1), (3, 1), (2, 0), (2, 2), (3, 3)] {
+        let chunk = map.chunk(CCoord(x), CCoord(z));
+        assert!(chunk.iter().all(|c| c[3] == 255), "{x}, {z}");
+    }
+}
+
+#[test]
+fn render_policy_fills_corrupt_chunk() {
+    let loader = loader_with_corrupt_chunk();
+    let colour = [255, 0, 255, 255];
+    let (map, report) = render_with_policy(&loader, OnChunkError::Fill(colour)).unwrap();
+
+    assert_eq!(report.skipped.len(), 1);
+    assert!(map.chunk(CCoord(2), CCoord(1)).iter().all(|c| *c == colour));
+    // Missing chunks are not errors, so are left blank.
+    assert!(map.chunk(CCoord(5), CCoord(5)).iter().all(|c| *c == [0; 4]));
+
+    // The chunk below is shaded as if there were nothing to its north, same
+    // as with the default policy.
+    let (skipped, _) = render_with_policy(&loader, OnChunkError::Skip).unwrap();
+    assert_eq!(
+        map.chunk(CCoord(2), CCoord(2)),
+        skipped.chunk(CCoord(2), CCoord(2))
+    );
+}
+
+#[test]
+fn render_policy_aborts_on_corrupt_chunk() {
+    let loader = loader_with_corrupt_chunk();
+    let Err(err) = render_with_policy(&loader, OnChunkError::Abort) else {
+        panic!("render did not abort");
+    };
+    assert_eq!(err.chunk, Some((CCoord(2), CCoord(1))));
+    assert!(
+        err.to_string()
+            .starts_with("chunk 2, 1: could not parse chunk"),
+        "{err}"
+    );
+
+    assert!(render_with_policy(&loader_with_chunks(), OnChunkError::Abort).is_ok());
+}
+
+#[test]
+fn render_policy_catches_panics() {
+    /// Panics on every block, as a stand-in for corrupt data that panics
+    /// deeper down.
+    struct PanicPalette;
+
+    impl Palette for PanicPalette {
+        fn pick(&self, block: &Block, _: Option<Biome>) -> Rgba {
+            panic!("bad block {}", block.name())
+        }
+    }
+
+    let loader = MemoryLoader::default();
+    loader.write(0, 0, 0, 0, CHUNK_1_17_1, 100);
+    let renderer = TopShadeRenderer::new(&PanicPalette, HeightMode::Trust);
+    let policy = ChunkErrorPolicy {
+        on_error: OnChunkError::Fill([9, 9, 9, 255]),
+        catch_panics: true,
+    };
+
+    let (map, report) =
+        render_region_with_policy(RCoord(0), RCoord(0), &loader, renderer, None, policy).unwrap();
+
+    assert_eq!(report.skipped.len(), 1);
+    let DiagnosticKind::ChunkPanicked(message) = &report.skipped[0].kind else {
+        panic!("{:?}", report.skipped[0]);
+    };
+    assert!(message.contains("bad block"), "{message}");
+    assert!(map
+        .chunk(CCoord(0), CCoord(0))
+        .iter()
+        .all(|c| *c == [9, 9, 9, 255]));
+}
+
+#[test]
+fn incremental_render_with_no_changes_does_nothing() {
+    let loader = loader_with_chunks();
+    let mut map = render_region(RCoord(0), RCoord(0), &loader, renderer());
+    let before = map.data.clone();
+
+    let updated =
+        render_region_incremental(RCoord(0), RCoord(0), &loader, renderer(), &mut map, 101);
+
+    assert!(updated.is_empty());
+    assert!(before == map.data);
+}
+
+#[test]
+fn incremental_render_updates_changed_chunk_and_its_south_neighbour() {
+    let loader = loader_with_chunks();
+    let mut map = render_region(RCoord(0), RCoord(0), &loader, renderer());
+    let before = map.data.clone();
+
+    loader.write(0, 0, 1, 1, CHUNK_21W44A_1, 200);
+
+    let updated =
+        render_region_incremental(RCoord(0), RCoord(0), &loader, renderer(), &mut map, 150);
+
+    assert_eq!(updated, vec![(1, 1), (1, 2)]);
+    assert!(before != map.data);
+
+    let full = render_region(RCoord(0), RCoord(0), &loader, renderer());
+    assert!(full.data == map.data);
+}
+
+#[test]
+fn incremental_render_updates_top_row_when_north_region_changes() {
+    let loader = loader_with_chunks();
+    let mut map = render_region(RCoord(0), RCoord(0), &loader, renderer());
+
+    loader.write(0, -1, 2, 31, CHUNK_21W44A_1, 200);
+
+    let updated =
+        render_region_incremental(RCoord(0), RCoord(0), &loader, renderer(), &mut map, 150);
+
+    assert_eq!(updated, vec![(2, 0)]);
+
+    let full = render_region(RCoord(0), RCoord(0), &loader, renderer());
+    assert!(full.data == map.data);
+}
+
+#[test]
+fn incremental_render_of_missing_region_does_nothing() {
+    let loader = loader_with_chunks();
+    let mut map = render_region(RCoord(0), RCoord(0), &loader, renderer());
+
+    let updated = render_region_incremental(RCoord(5), RCoord(5), &loader, renderer(), &mut map, 0);
+
+    assert!(updated.is_empty());
+}
+
+#[test]
+fn neighbour_heights_shade_north_edge_without_north_region() {
+    let both = loader_with_chunks();
+    let expected = render_region(RCoord(0), RCoord(0), &both, renderer());
+
+    // Same region, but the loader doesn't have the region to the north.
+    let south_only = MemoryLoader::default();
+    south_only.regions.borrow_mut().insert(
+        (RCoord(0), RCoord(0)),
+        both.regions.borrow()[&(RCoord(0), RCoord(0))].clone(),
+    );
+
+    let without = render_region(RCoord(0), RCoord(0), &south_only, renderer());
+
+    let mut north = both.region(RCoord(0), RCoord(-1)).unwrap();
+    let heights = NeighbourHeights::south_of(&mut north, &renderer());
+    let with = render_region_with_context(
+        RCoord(0),
+        RCoord(0),
+        &south_only,
+        renderer(),
+        Some(&heights),
+    );
+
+    // The north edge is only right with the context.
+    let edge = |map: &crate::RegionMap<Rgba>| map.chunk(CCoord(0), CCoord(0))[..16].to_vec();
+    assert!(edge(&without) != edge(&expected));
+    assert!(edge(&with) == edge(&expected));
+
+    // With the context the whole map matches, and without it only the edge
+    // is affected.
+    assert!(with.data == expected.data);
+    assert!(without.chunk(CCoord(0), CCoord(1)) == expected.chunk(CCoord(0), CCoord(1)));
+}
+
+#[test]
+fn neighbour_heights_override_loader() {
+    let loader = loader_with_chunks();
+    let without_north = render_region_with_context(
+        RCoord(0),
+        RCoord(0),
+        &loader,
+        renderer(),
+        Some(&NeighbourHeights::default()),
+    );
+
+    let mut regions = loader.regions.borrow().clone();
+    regions.remove(&(RCoord(0), RCoord(-1)));
+    let other = MemoryLoader {
+        regions: RefCell::new(regions),
+    };
+
+    assert!(without_north.data == render_region(RCoord(0), RCoord(0), &other, renderer()).data);
+}
+
+#[test]
+fn render_chunk_matches_region_render() {
+    let loader = loader_with_chunks();
+    loader.write(0, 0, 1, 0, CHUNK_21W44A_1, 100);
+    loader.write(0, -1, 2, 31, CHUNK_21W44A_1, 100);
+    let map = render_region(RCoord(0), RCoord(0), &loader, renderer());
+
+    let chunk = |rz: isize, x: usize, z: usize| {
+        let mut region = loader.region(RCoord(0), RCoord(rz)).unwrap();
+        JavaChunk::from_bytes(&region.read_chunk(x, z).unwrap().unwrap()).unwrap()
+    };
+    let renderer = renderer();
+
+    // Within the region, and on its north edge with the region above.
+    for (x, z, north) in [(1, 1, chunk(0, 1, 0)), (2, 0, chunk(-1, 2, 31))] {
+        let heights = renderer.south_edge_heights(&north);
+        let tile = render_chunk(&chunk(0, x, z), &renderer, Some(heights));
+        assert!(tile[..] == *map.chunk(CCoord(x as isize), CCoord(z as isize)));
+    }
+
+    // Without the chunk to the north only the northmost row can differ.
+    let tile = render_chunk(&chunk(0, 1, 1), &renderer, None);
+    assert!(tile[..] != *map.chunk(CCoord(1), CCoord(1)));
+    assert!(tile[16..] == map.chunk(CCoord(1), CCoord(1))[16..]);
+}
+
+/// A chunk where every column is the same, given bottom to top.
+struct ColumnChunk {
+    column: Vec<Block>,
+    /// The height given by the heightmap, if not the top non-air block.
+    surface: Option<isize>,
+}
+
+impl ColumnChunk {
+    fn new(layers: &[(&str, usize)]) -> Self {
+        let mut column = vec![];
+        for (name, count) in layers {
+            let archetype = match *name {
+                "minecraft:air" => BlockArchetype::Airy,
+                _ => BlockArchetype::Normal,
+            };
+            for _ in 0..*count {
+                column.push(Block {
+                    name: name.to_string(),
+                    encoded: name.to_string(),
+                    archetype: archetype.clone(),
+                    properties: vec![],
+                });
+            }
+        }
+        Self {
+            column,
+            surface: None,
+        }
+    }
+
+    /// A column of the given blocks from y 0, with the heightmap giving
+    /// `surface` as the surface height.
+    fn parse(blocks: &[&str], surface: isize) -> Self {
+        Self {
+            column: blocks.iter().map(|b| b.parse().unwrap()).collect(),
+            surface: Some(surface),
+        }
+    }
+
+    /// Bedrock floor, netherrack, a big cave, then a blackstone roof topped
+    /// with bedrock.
+    fn nether() -> Self {
+        Self::new(&[
+            ("minecraft:bedrock", 1),
+            ("minecraft:netherrack", 30),
+            ("minecraft:air", 70),
+            ("minecraft:blackstone", 26),
+            ("minecraft:bedrock", 1),
+        ])
+    }
+}
+
+impl Chunk for ColumnChunk {
+    fn status(&self) -> String {
+        "full".to_string()
+    }
+
+    fn surface_height(&self, _: usize, _: usize, _: HeightMode) -> isize {
+        if let Some(surface) = self.surface {
+            return surface;
+        }
+
+        let top = self
+            .column
+            .iter()
+            .rposition(|b| b.archetype != BlockArchetype::Airy);
+        top.map_or(0, |y| y as isize + 1)
+    }
+
+    fn biome(&self, _: usize, _: isize, _: usize) -> Option<Biome> {
+        None
+    }
+
+    fn block(&self, _: usize, y: isize, _: usize) -> Option<&Block> {
+        self.column.get(usize::try_from(y).ok()?)
+    }
+
+    fn y_range(&self) -> Range<isize> {
+        0..self.column.len() as isize
+    }
+}
+
+struct PrimaryPalette;
+
+impl Palette for PrimaryPalette {
+    fn pick(&self, block: &Block, _: Option<Biome>) -> Rgba {
+        match block.name() {
+            "minecraft:netherrack" => [255, 0, 0, 255],
+            "minecraft:blackstone" => [0, 255, 0, 255],
+            "minecraft:bedrock" => [0, 0, 255, 255],
+            _ => [0, 0, 0, 0],
+        }
+    }
+}
+
+/// Render the chunk and return the colour of a block not on the chunk edge,
+/// with shading undone.
+fn render_middle(chunk: &ColumnChunk, dimension: DimensionOpts) -> Rgba {
+    let renderer =
+        TopShadeRenderer::new(&PrimaryPalette, HeightMode::Trust).with_dimension(dimension);
+    let data = renderer.render(chunk, None);
+
+    // Every column is the same height, so the shading is the same too.
+    let shaded = data[8 * 16 + 8];
+    shaded.map(|c| if c >= 220 { 255 } else { c })
+}
+
+#[test]
+fn default_dimension_renders_roof() {
+    let colour = render_middle(&ColumnChunk::nether(), DimensionOpts::default());
+    assert_eq!(colour, [0, 0, 255, 255]);
+}
+
+#[test]
+fn nether_dimension_renders_cave_floor() {
+    let colour = render_middle(&ColumnChunk::nether(), DimensionOpts::nether());
+    assert_eq!(colour, [255, 0, 0, 255]);
+}
+
+#[test]
+fn max_y_without_cave_renders_first_block_below_ceiling() {
+    let in_roof = DimensionOpts {
+        max_y: Some(110),
+        ..Default::default()
+    };
+    let in_cave = DimensionOpts {
+        max_y: Some(60),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        render_middle(&ColumnChunk::nether(), in_roof),
+        [0, 255, 0, 255]
+    );
+    assert_eq!(
+        render_middle(&ColumnChunk::nether(), in_cave),
+        [255, 0, 0, 255]
+    );
+}
+
+#[test]
+fn cave_without_air_gap_renders_cut_through_roof() {
+    let solid = ColumnChunk::new(&[("minecraft:blackstone", 128)]);
+    let colour = render_middle(&solid, DimensionOpts::nether());
+    assert_eq!(colour, [0, 255, 0, 255]);
+}
+
+#[test]
+fn min_y_hides_blocks_below() {
+    let dimension = DimensionOpts {
+        max_y: Some(60),
+        min_y: Some(20),
+        cave: false,
+    };
+
+    // The netherrack floor is at 30, so still visible.
+    let colour = render_middle(&ColumnChunk::nether(), dimension);
+    assert_eq!(colour, [255, 0, 0, 255]);
+
+    let dimension = DimensionOpts {
+        min_y: Some(40),
+        ..dimension
+    };
+    let colour = render_middle(&ColumnChunk::nether(), dimension);
+    assert_eq!(colour[3], 0);
+}
+
+fn cave_renderer() -> TopShadeRenderer<'static, PrimaryPalette> {
+    TopShadeRenderer::new(&PrimaryPalette, HeightMode::Calculate)
+        .with_mode(RenderMode::Caves(DepthRamp::default()))
+}
+
+#[test]
+fn caves_coloured_by_depth_of_floor() {
+    // Surface is the bedrock at y=127, cave floor is at y=31.
+    let data = cave_renderer().render(&ColumnChunk::nether(), None);
+    assert_eq!(data[0], DepthRamp::default().colour(127 - 31));
+    assert_eq!(data[0], DepthRamp::default().deep);
+
+    let shallow = ColumnChunk::new(&[
+        ("minecraft:netherrack", 10),
+        ("minecraft:air", 5),
+        ("minecraft:netherrack", 10),
+    ]);
+    let data = cave_renderer().render(&shallow, None);
+    assert_eq!(data[0], DepthRamp::default().colour(24 - 10));
+}
+
+#[test]
+fn caves_skip_columns_without_caves() {
+    let solid = ColumnChunk::new(&[("minecraft:netherrack", 64)]);
+    let data = cave_renderer().render(&solid, None);
+    assert!(data.iter().all(|c| *c == [0, 0, 0, 0]));
+}
+
+#[test]
+fn caves_respect_ceiling() {
+    // With the ceiling in the middle of the cave, the cave floor is found
+    // straight away.
+    let renderer = cave_renderer().with_dimension(DimensionOpts {
+        max_y: Some(50),
+        ..Default::default()
+    });
+    let data = renderer.render(&ColumnChunk::nether(), None);
+    assert_eq!(data[0], DepthRamp::default().colour(50 - 31));
+}
+
+#[test]
+fn depth_ramp_interpolates() {
+    let ramp = DepthRamp {
+        shallow: [0, 0, 0, 255],
+        deep: [200, 100, 0, 255],
+        max_depth: 100,
+    };
+
+    assert_eq!(ramp.colour(-5), [0, 0, 0, 255]);
+    assert_eq!(ramp.colour(50), [100, 50, 0, 255]);
+    assert_eq!(ramp.colour(100), [200, 100, 0, 255]);
+    assert_eq!(ramp.colour(1000), [200, 100, 0, 255]);
+}
+
+fn render_real_caves(data: &[u8]) -> [Rgba; 16 * 16] {
+    let chunk = JavaChunk::from_bytes(data).unwrap();
+
+    let fast = cave_renderer().render(&chunk, None);
+    let slow = cave_renderer().render(&NoFastPath(&chunk), None);
+    assert!(fast == slow);
+
+    fast
+}
+
+#[test]
+fn caves_in_real_chunks() {
+    // An ocean chunk with solid ground below.
+    let ocean = render_real_caves(CHUNK_1_17_1);
+    assert!(ocean.iter().all(|c| *c == [0, 0, 0, 0]));
+
+    // Caves under the whole chunk, mostly deep.
+    let caves = render_real_caves(CHUNK_21W44A_1);
+    assert!(caves.iter().all(|c| c[3] == 255));
+    assert_eq!(caves[0], [30, 40, 150, 255]);
+    assert_eq!(caves[17], [30, 40, 150, 255]);
+    assert_eq!(caves[255], [65, 68, 145, 255]);
+}
+
+struct SurfacePalette;
+
+impl Palette for SurfacePalette {
+    fn pick(&self, block: &Block, _: Option<Biome>) -> Rgba {
+        match block.name() {
+            // Like the rendered palette, plants in water look like water.
+            "minecraft:water" | "minecraft:kelp_plant" | "minecraft:seagrass" => [0, 0, 255, 255],
+            "minecraft:snow_block" => [255, 255, 255, 255],
+            "minecraft:stone" => [128, 128, 128, 255],
+            _ => [255, 0, 0, 255],
+        }
+    }
+}
+
+fn render_surface(chunk: &ColumnChunk, rules: SurfaceRules) -> Rgba {
+    let renderer =
+        TopShadeRenderer::new(&SurfacePalette, HeightMode::Trust).with_surface_rules(rules);
+    renderer.render(chunk, None)[8 * 16 + 8]
+}
+
+#[test]
+fn snow_layers_render_as_snow() {
+    // Snow layers don't block motion, so are above the heightmap.
+    let chunk = ColumnChunk::parse(&["stone", "oak_leaves", "snow[layers=1]"], 2);
+
+    let colour = render_surface(&chunk, SurfaceRules::default());
+    assert_eq!(
+        colour,
+        render_surface(
+            &ColumnChunk::parse(&["snow_block"], 1),
+            SurfaceRules::default()
+        )
+    );
+
+    let colour_without_rules = render_surface(&chunk, SurfaceRules::empty());
+    assert_ne!(colour, colour_without_rules);
+}
+
+#[test]
+fn waterlogged_blocks_render_as_water() {
+    let chunk = ColumnChunk::parse(
+        &[
+            "stone",
+            "oak_stairs[waterlogged=true]",
+            "water[level=0]",
+            "water[level=0]",
+        ],
+        4,
+    );
+    let water = ColumnChunk::parse(
+        &[
+            "stone",
+            "water[level=0]",
+            "water[level=0]",
+            "water[level=0]",
+        ],
+        4,
+    );
+
+    assert_eq!(
+        render_surface(&chunk, SurfaceRules::default()),
+        render_surface(&water, SurfaceRules::default())
+    );
+    assert_ne!(
+        render_surface(&chunk, SurfaceRules::empty()),
+        render_surface(&water, SurfaceRules::empty())
+    );
+}
+
+#[test]
+fn plants_render_the_block_below() {
+    let chunk = ColumnChunk::parse(
+        &["stone", "tall_grass[half=lower]", "tall_grass[half=upper]"],
+        3,
+    );
+    let bare = ColumnChunk::parse(&["stone"], 1);
+
+    assert_eq!(
+        render_surface(&chunk, SurfaceRules::default()),
+        render_surface(&bare, SurfaceRules::default())
+    );
+    assert_ne!(
+        render_surface(&chunk, SurfaceRules::empty()),
+        render_surface(&bare, SurfaceRules::empty())
+    );
+}
+
+/// An ocean trench running north to south, getting deeper to the east. Column
+/// x has water `1 + 4 * x` blocks deep over stone, with the surface at y 63.
+struct TrenchChunk {
+    stone: Block,
+    water: Block,
+}
+
+impl TrenchChunk {
+    fn new() -> Self {
+        Self {
+            stone: "stone".parse().unwrap(),
+            water: "water[level=0]".parse().unwrap(),
+        }
+    }
+}
+
+impl Chunk for TrenchChunk {
+    fn status(&self) -> String {
+        "full".to_string()
+    }
+
+    fn surface_height(&self, _: usize, _: usize, _: HeightMode) -> isize {
+        63
+    }
+
+    fn biome(&self, _: usize, _: isize, _: usize) -> Option<Biome> {
+        None
+    }
+
+    fn block(&self, x: usize, y: isize, _: usize) -> Option<&Block> {
+        let floor = 62 - (1 + 4 * x as isize);
+        match y {
+            0..=62 if y > floor => Some(&self.water),
+            0..=62 => Some(&self.stone),
+            _ => None,
+        }
+    }
+
+    fn y_range(&self) -> Range<isize> {
+        0..64
+    }
+}
+
+/// The brightness of each column of a row of the trench.
+fn trench_brightness(shading: Option<WaterDepthShading>) -> Vec<u32> {
+    let renderer =
+        TopShadeRenderer::new(&SurfacePalette, HeightMode::Trust).with_water_depth_shading(shading);
+    let data = renderer.render(&TrenchChunk::new(), None);
+
+    (0..16)
+        .map(|x| data[8 * 16 + x][..3].iter().map(|&c| c as u32).sum())
+        .collect()
+}
+
+#[test]
+fn water_depth_shading_darkens_deep_water() {
+    let shaded = trench_brightness(Some(WaterDepthShading::default()));
+    let plain = trench_brightness(None);
+
+    // A gradient across the trench, getting darker with depth until the
+    // default maximum depth of 48 blocks.
+    assert!(shaded[..12].windows(2).all(|w| w[0] > w[1]), "{shaded:?}");
+    assert!(shaded[12..].windows(2).all(|w| w[0] == w[1]), "{shaded:?}");
+    assert!(shaded[0] > 2 * shaded[15], "{shaded:?}");
+
+    // Shallow water barely changes, but the deep ocean is much darker.
+    assert!(plain[0] - shaded[0] < 40);
+    assert!(plain[15] > shaded[15] + 50);
+}
+
+#[test]
+fn water_depth_shading_curve() {
+    let linear = WaterDepthShading {
+        max_darkening: 0.5,
+        max_depth: 10,
+        exponent: 1.0,
+    };
+    assert_eq!(linear.darkening(0), 0.0);
+    assert_eq!(linear.darkening(5), 0.25);
+    assert_eq!(linear.darkening(10), 0.5);
+    assert_eq!(linear.darkening(100), 0.5);
+    assert_eq!(linear.shade([200, 100, 50, 180], 10), [100, 50, 25, 180]);
+
+    // Smaller exponents darken shallow water sooner.
+    let curved = WaterDepthShading {
+        exponent: 0.5,
+        ..linear
+    };
+    assert!(curved.darkening(2) > linear.darkening(2));
+    assert_eq!(curved.darkening(10), 0.5);
+}
+
+#[test]
+fn water_plants_count_as_water_depth() {
+    let water = ColumnChunk::parse(
+        &[
+            "stone",
+            "water[level=0]",
+            "water[level=0]",
+            "water[level=0]",
+        ],
+        4,
+    );
+    let plants = ColumnChunk::parse(
+        &["stone", "seagrass", "kelp_plant[age=0]", "water[level=0]"],
+        4,
+    );
+
+    let render = |chunk: &ColumnChunk| {
+        TopShadeRenderer::new(&SurfacePalette, HeightMode::Trust)
+            .with_water_depth_shading(Some(WaterDepthShading::default()))
+            .render(chunk, None)[8 * 16 + 8]
+    };
+    assert_eq!(render(&plants), render(&water));
+}
+
+#[test]
+fn elevation_of_region() {
+    let etho = crate::fixtures::chunk_1_16();
+    let loader = MemoryLoader::default();
+    loader.write(0, 0, 1, 2, etho, 100);
+    loader.write(0, 0, 0, 0, CHUNK_1_17_1, 100);
+
+    let map = render_region_elevation(RCoord(0), RCoord(0), &loader, renderer(), true);
+    assert_eq!(map.heights.len(), 512 * 512);
+
+    // Columns of the etho chunk, from the corner of the chunk at 16, 32.
+    for (x, z, name) in [
+        (0, 0, "minecraft:water"),
+        (3, 3, "minecraft:sand"),
+        (12, 2, "minecraft:sand"),
+    ] {
+        assert_eq!(map.height(16 + x, 32 + z), Some(63));
+        assert_eq!(map.top_block(16 + x, 32 + z).unwrap().name(), name);
+    }
+
+    // Missing chunks.
+    assert_eq!(map.height(100, 100), None);
+    assert_eq!(map.heights[100 * 512 + 100], i16::MIN);
+    assert!(map.top_block(100, 100).is_none());
+
+    // Heights match what the renderer shades by.
+    let chunk = JavaChunk::from_bytes(etho).unwrap();
+    let heights = renderer().surface_heights(&chunk);
+    for z in 0..16 {
+        for x in 0..16 {
+            assert_eq!(map.height(16 + x, 32 + z), Some(heights[z * 16 + x] as i16));
+        }
+    }
+
+    let map = render_region_elevation(RCoord(0), RCoord(0), &loader, renderer(), false);
+    assert!(map.top_blocks.is_none());
+    assert!(map.blocks.is_empty());
+    assert_eq!(map.height(3, 3), Some(63));
+}
+
+#[test]
+fn elevation_of_missing_region() {
+    let map = render_region_elevation(
+        RCoord(0),
+        RCoord(0),
+        &MemoryLoader::default(),
+        renderer(),
+        true,
+    );
+    assert!(map.heights.iter().all(|&h| h == i16::MIN));
+    assert!(map.top_blocks.unwrap().iter().all(|&i| i == u32::MAX));
+}
+
+#[test]
+fn debug_palette_colours_by_name() {
+    let pick = |block: &str| DebugPalette.pick(&block.parse::<Block>().unwrap(), None);
+
+    assert_eq!(pick("minecraft:sand"), pick("minecraft:sand"));
+    assert_eq!(
+        pick("minecraft:water[level=0]"),
+        pick("minecraft:water[level=3]")
+    );
+    assert_ne!(pick("minecraft:sand"), pick("minecraft:water"));
+    assert_eq!(pick("minecraft:sand")[3], 255);
+}
+
+#[test]
+fn render_region_with_dyn_debug_palette() {
+    let etho = crate::fixtures::chunk_1_16();
+    let loader = MemoryLoader::default();
+    loader.write(0, 0, 1, 2, etho, 100);
+
+    let palette: &dyn Palette = &DebugPalette;
+    let renderer = TopShadeRenderer::new(palette, HeightMode::Trust);
+    let map = render_region(RCoord(0), RCoord(0), &loader, renderer);
+
+    assert!(map
+        .chunk(CCoord(1), CCoord(2))
+        .iter()
+        .all(|pixel| pixel[3] == 255));
+    assert!(map.chunk(CCoord(0), CCoord(0)).iter().all(|p| *p == [0; 4]));
+
+    // The same as rendering with the palette's own type.
+    let renderer = TopShadeRenderer::new(&DebugPalette, HeightMode::Trust);
+    let expected = render_region(RCoord(0), RCoord(0), &loader, renderer);
+    assert_eq!(
+        map.chunk(CCoord(1), CCoord(2)),
+        expected.chunk(CCoord(1), CCoord(2))
+    );
+}
+
+#[test]
+fn legend_off_by_default() {
+    assert!(renderer().legend().is_none());
+
+    let (_, report) = render_with_policy(&loader_with_chunks(), OnChunkError::Skip).unwrap();
+    assert!(report.legend.is_none());
+}
+
+#[test]
+fn legend_has_only_blocks_drawn() {
+    let legend = |dimension| {
+        let renderer = TopShadeRenderer::new(&PrimaryPalette, HeightMode::Trust)
+            .with_dimension(dimension)
+            .with_legend(true);
+        renderer.render(&ColumnChunk::nether(), None);
+        renderer.legend().unwrap()
+    };
+
+    let roof = legend(DimensionOpts::default());
+    assert_eq!(
+        roof.iter().collect::<Vec<_>>(),
+        [("minecraft:bedrock", [0, 0, 255, 255])]
+    );
+
+    let floor = legend(DimensionOpts::nether());
+    assert_eq!(
+        floor.iter().collect::<Vec<_>>(),
+        [("minecraft:netherrack", [255, 0, 0, 255])]
+    );
+}
+
+#[test]
+fn legend_has_blocks_seen_through_water() {
+    let chunk = ColumnChunk::parse(&["stone", "stone", "water", "water"], 4);
+    let renderer = TopShadeRenderer::new(&SurfacePalette, HeightMode::Trust).with_legend(true);
+    renderer.render(&chunk, None);
+
+    let legend = renderer.legend().unwrap();
+    let names: Vec<_> = legend.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["minecraft:stone", "minecraft:water"]);
+    assert!(legend.contains(
+        "minecraft:stone",
+        SurfacePalette.pick(&chunk.column[1], None)
+    ));
+}
+
+#[test]
+fn legend_of_region_render() {
+    let loader = loader_with_chunks();
+    let renderer = renderer().with_legend(true);
+    let policy = ChunkErrorPolicy::default();
+
+    let (_, report) = render_region_with_policy(
+        RCoord(0),
+        RCoord(0),
+        &loader,
+        renderer.clone(),
+        None,
+        policy,
+    )
+    .unwrap();
+    let legend = report.legend.unwrap();
+    assert!(!legend.is_empty());
+    for (name, colour) in legend.iter() {
+        let block: Block = name.parse().unwrap();
+        assert_eq!(colour, NamePalette.pick(&block, None), "{name}");
+    }
+
+    // The report's legend is the region's alone, but clones of the renderer
+    // share one.
+    assert!(renderer.legend().unwrap().is_empty());
+    render_region(RCoord(0), RCoord(0), &loader, renderer.clone());
+    assert_eq!(renderer.legend().unwrap(), legend);
+}
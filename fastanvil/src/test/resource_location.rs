@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use fastnbt::nbt;
+
+use crate::{Block, JavaChunk, ResourceLocation, ResourceLocationRef};
+
+#[test]
+fn parse_splits_namespace_and_path() {
+    let id = ResourceLocationRef::parse("mymod:ores/tin_ore").unwrap();
+    assert_eq!(id.namespace(), "mymod");
+    assert_eq!(id.path(), "ores/tin_ore");
+
+    let id = ResourceLocation::parse("stone").unwrap();
+    assert_eq!(id.namespace(), "minecraft");
+    assert_eq!(id.path(), "stone");
+    assert_eq!(id.as_str(), "minecraft:stone");
+}
+
+#[test]
+fn parse_errors() {
+    for input in [
+        "",
+        ":stone",
+        "minecraft:",
+        "Minecraft:stone",
+        "minecraft:Stone",
+        "minecraft:oak stairs",
+        "my/mod:stone",
+        "a:b:c",
+        "minecraft:stone[]",
+    ] {
+        assert!(
+            ResourceLocationRef::parse(input).is_err(),
+            "{input:?} should not parse"
+        );
+    }
+}
+
+#[test]
+fn default_namespace_is_equal() {
+    let bare = ResourceLocationRef::parse("stone").unwrap();
+    let full = ResourceLocationRef::parse("minecraft:stone").unwrap();
+    assert_eq!(bare, full);
+    assert_eq!(bare.into_owned(), full);
+
+    let set: HashSet<_> = [bare.into_owned(), full.into_owned()].into();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn ordered_by_namespace_then_path() {
+    let mut ids: Vec<ResourceLocation> = ["a0:b", "a:c", "a:b"]
+        .into_iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    ids.sort();
+
+    let ids: Vec<_> = ids.iter().map(|id| id.as_str()).collect();
+    assert_eq!(ids, ["a:b", "a:c", "a0:b"]);
+}
+
+#[test]
+fn serde_as_strings() {
+    let id = ResourceLocation::parse("stone").unwrap();
+    let data = fastnbt::to_bytes(&nbt!({ "id": id })).unwrap();
+
+    let value: fastnbt::Value = fastnbt::from_bytes(&data).unwrap();
+    assert_eq!(value, nbt!({ "id": "minecraft:stone" }));
+
+    #[derive(serde::Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        id: ResourceLocationRef<'a>,
+    }
+
+    let borrowed: Borrowed = fastnbt::from_bytes(&data).unwrap();
+    assert_eq!(borrowed.id, id);
+
+    let bad = fastnbt::to_bytes(&nbt!({ "id": "not an id" })).unwrap();
+    assert!(fastnbt::from_bytes::<Borrowed>(&bad).is_err());
+}
+
+#[test]
+fn block_ids() {
+    let block: Block = "oak_stairs[facing=east]".parse().unwrap();
+    assert_eq!(block.id().namespace(), "minecraft");
+    assert_eq!(block.id().path(), "oak_stairs");
+}
+
+#[test]
+fn corrupt_block_names_fail_to_parse() {
+    let chunk = nbt!({
+        "DataVersion": 2860,
+        "Status": "full",
+        "sections": [
+            {"Y": 0_i8, "block_states": {"palette": [{"Name": "minecraft:st\u{1}one"}]}},
+        ],
+    });
+
+    let data = fastnbt::to_bytes(&chunk).unwrap();
+    assert!(JavaChunk::from_bytes(&data).is_err());
+}
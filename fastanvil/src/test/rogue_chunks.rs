@@ -1,11 +1,13 @@
-use crate::{Chunk, HeightMode, JavaChunk};
+use fastnbt::{nbt, LongArray, Value};
 
-const ETHO_OLD_HEIGHTS: &[u8] = include_bytes!("../../resources/etho-old-heightmaps.chunk");
-const ETHO_MAX_HEIGHTS: &[u8] = include_bytes!("../../resources/etho-max-heights.chunk");
-const ETHO_OLD_IN_NEW: &[u8] = include_bytes!("../../resources/etho-old-in-new.chunk");
-const ETHO_OLD_IN_NEW2: &[u8] = include_bytes!("../../resources/etho-old-in-new2.chunk");
-const ETHO: &[u8] = include_bytes!("../../resources/etho.chunk");
-const ETHO_EMPTY: &[u8] = include_bytes!("../../resources/etho-empty.chunk");
+use crate::{Chunk, HeightMode, JavaChunk, LazyJavaChunk};
+
+const ETHO_OLD_HEIGHTS: &[u8] = crate::fixtures::chunk_old_heightmaps();
+const ETHO_MAX_HEIGHTS: &[u8] = crate::fixtures::chunk_max_heights();
+const ETHO_OLD_IN_NEW: &[u8] = crate::fixtures::chunks_old_in_new()[0];
+const ETHO_OLD_IN_NEW2: &[u8] = crate::fixtures::chunks_old_in_new()[1];
+const ETHO: &[u8] = crate::fixtures::chunk_1_16();
+const ETHO_EMPTY: &[u8] = crate::fixtures::chunk_empty();
 
 fn extract_heights(chunk: &mut dyn Chunk) -> [isize; 256] {
     let mut heights = [0; 256];
@@ -158,3 +160,34 @@ fn etho_empty() {
 
     assert_eq!(expected_heights, heights);
 }
+
+#[test]
+fn palette_needing_more_than_16_bits() {
+    // 65,537 blocks in the palette need 17 bits an index, more than any
+    // vanilla section uses. Every index is 0.
+    let palette = (0..=u16::MAX as usize + 1)
+        .map(|i| nbt!({ "Name": format!("minecraft:b{i}") }))
+        .collect();
+    let mut section = nbt!({
+        "Y": 0i8,
+        "block_states": { "data": LongArray::new(vec![0; 4096usize.div_ceil(64 / 17)]) },
+    });
+    if let Value::Compound(section) = &mut section {
+        if let Some(Value::Compound(states)) = section.get_mut("block_states") {
+            states.insert("palette".to_owned(), Value::List(palette));
+        }
+    }
+    let data = fastnbt::to_bytes(&nbt!({
+        "DataVersion": 2860,
+        "Status": "full",
+        "yPos": -4,
+        "sections": [section],
+    }))
+    .unwrap();
+
+    let chunk = JavaChunk::from_bytes(&data).unwrap();
+    assert_eq!(chunk.block(1, 5, 2).unwrap().name(), "minecraft:b0");
+
+    let lazy = LazyJavaChunk::from_bytes(&data).unwrap();
+    assert_eq!(lazy.block(1, 5, 2).unwrap().name(), "minecraft:b0");
+}
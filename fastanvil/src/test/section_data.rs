@@ -1,4 +1,12 @@
-use crate::{min_bits_for_n_states, StatesIter};
+use std::borrow::Cow;
+
+use serde::Deserialize;
+
+use crate::{
+    min_bits_for_n_states, Block, CurrentJavaChunk, SectionBiomes, SectionBlockStates, StatesIter,
+};
+
+const CHUNK_21W44A_1: &[u8] = crate::fixtures::chunk_1_18();
 
 #[test]
 fn iter_zeroes() {
@@ -43,3 +51,71 @@ fn min_bits() {
         assert_eq!(ideal(i), min_bits_for_n_states(i));
     }
 }
+
+#[test]
+fn public_section_types_match_java_chunk() {
+    #[derive(Deserialize)]
+    struct Chunk<'a> {
+        #[serde(borrow)]
+        sections: Vec<Section<'a>>,
+    }
+
+    #[derive(Deserialize)]
+    struct Section<'a> {
+        #[serde(rename = "Y")]
+        y: i8,
+        #[serde(borrow)]
+        block_states: Option<SectionBlockStates<'a>>,
+        #[serde(borrow)]
+        biomes: Option<SectionBiomes<'a>>,
+    }
+
+    let chunk: Chunk = fastnbt::from_bytes(CHUNK_21W44A_1).unwrap();
+    let java: CurrentJavaChunk = fastnbt::from_bytes(CHUNK_21W44A_1).unwrap();
+    let tower = java.sections.unwrap();
+
+    assert_eq!(chunk.sections.len(), tower.sections().len());
+
+    for (raw, parsed) in chunk.sections.iter().zip(tower.sections()) {
+        assert_eq!(raw.y, parsed.y);
+
+        let states = raw.block_states.as_ref().unwrap();
+        let blocks: Vec<Block> = states.palette.iter().cloned().map(Block::from).collect();
+        assert_eq!(
+            blocks
+                .iter()
+                .map(Block::encoded_description)
+                .collect::<Vec<_>>(),
+            parsed
+                .block_states
+                .palette()
+                .iter()
+                .map(Block::encoded_description)
+                .collect::<Vec<_>>()
+        );
+
+        // Palette item strings are plain ASCII, so should be borrowed.
+        assert!(states
+            .palette
+            .iter()
+            .all(|item| matches!(item.name.0, Cow::Borrowed(_))));
+
+        assert_eq!(
+            states.try_iter_indices().map(Iterator::collect::<Vec<_>>),
+            parsed
+                .block_states
+                .try_iter_indices()
+                .map(Iterator::collect::<Vec<_>>)
+        );
+
+        let biomes = raw.biomes.as_ref().unwrap();
+        assert_eq!(biomes.palette.len(), parsed.biomes.palette().len());
+        assert_eq!(
+            biomes.try_iter_indices().map(Iterator::collect::<Vec<_>>),
+            parsed
+                .biomes
+                .try_iter_indices()
+                .map(Iterator::collect::<Vec<_>>)
+        );
+    }
+}
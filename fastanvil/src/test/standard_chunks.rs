@@ -5,12 +5,11 @@ use std::{
 
 use crate::{biome::Biome, Block, Chunk, HeightMode, JavaChunk, Palette, Rgba, TopShadeRenderer};
 
-// const CHUNK_1_12: &[u8] = include_bytes!("../../resources/1.12.chunk");
-const CHUNK_1_17_0: &[u8] = include_bytes!("../../resources/1.17.0.chunk");
-const CHUNK_1_17_1: &[u8] = include_bytes!("../../resources/1.17.1.chunk");
-const CHUNK_21W44A_1: &[u8] = include_bytes!("../../resources/21w44a-test1.nbt");
-const CHUNK_CUSTOM_HEIGHTS_1_17_1: &[u8] =
-    include_bytes!("../../resources/1.17.1-custom-heights.chunk");
+// const CHUNK_1_12: &[u8] = crate::fixtures::chunk_1_12();
+const CHUNK_1_17_0: &[u8] = crate::fixtures::chunk_1_17_0();
+const CHUNK_1_17_1: &[u8] = crate::fixtures::chunk_1_17();
+const CHUNK_21W44A_1: &[u8] = crate::fixtures::chunk_1_18();
+const CHUNK_CUSTOM_HEIGHTS_1_17_1: &[u8] = crate::fixtures::chunk_1_17_custom_heights();
 
 /// A palette that colours blocks based on the hash of their full description.
 /// Will produce gibberish looking maps but is great for testing rendering isn't
@@ -579,11 +578,11 @@ fn chunk_1_17_0() {
 #[test]
 fn chunk_1_17_1() {
     let expected = [
-        [132, 66, 119, 255],
+        [158, 108, 89, 255],
         [159, 109, 90, 255],
-        [133, 65, 125, 255],
         [145, 66, 119, 255],
-        [133, 65, 128, 255],
+        [145, 66, 119, 255],
+        [146, 66, 122, 255],
         [146, 66, 122, 255],
         [147, 66, 125, 255],
         [147, 66, 125, 255],
@@ -611,12 +610,12 @@ fn chunk_1_17_1() {
         [128, 56, 109, 255],
         [129, 56, 112, 255],
         [144, 57, 43, 255],
-        [113, 56, 100, 255],
         [133, 90, 74, 255],
-        [113, 56, 100, 255],
+        [133, 90, 74, 255],
+        [133, 90, 74, 255],
         [136, 93, 76, 255],
         [123, 57, 98, 255],
-        [113, 56, 102, 255],
+        [123, 57, 98, 255],
         [124, 56, 99, 255],
         [125, 56, 102, 255],
         [125, 56, 105, 255],
@@ -627,12 +626,12 @@ fn chunk_1_17_1() {
         [128, 56, 109, 255],
         [128, 56, 109, 255],
         [144, 57, 42, 255],
-        [113, 56, 96, 255],
+        [120, 57, 90, 255],
         [132, 88, 71, 255],
         [132, 88, 71, 255],
         [133, 90, 74, 255],
-        [113, 56, 100, 255],
-        [113, 56, 100, 255],
+        [121, 57, 94, 255],
+        [121, 57, 94, 255],
         [123, 57, 98, 255],
         [124, 56, 99, 255],
         [125, 56, 102, 255],
@@ -647,7 +646,7 @@ fn chunk_1_17_1() {
         [119, 57, 88, 255],
         [119, 57, 88, 255],
         [119, 57, 88, 255],
-        [113, 56, 96, 255],
+        [120, 57, 90, 255],
         [120, 57, 90, 255],
         [121, 57, 94, 255],
         [123, 57, 98, 255],
@@ -658,7 +657,7 @@ fn chunk_1_17_1() {
         [142, 100, 83, 255],
         [126, 56, 107, 255],
         [126, 56, 107, 255],
-        [115, 56, 115, 255],
+        [144, 57, 42, 255],
         [118, 57, 84, 255],
         [118, 57, 84, 255],
         [119, 57, 88, 255],
@@ -666,11 +665,11 @@ fn chunk_1_17_1() {
         [119, 57, 88, 255],
         [120, 57, 90, 255],
         [121, 57, 94, 255],
-        [113, 56, 100, 255],
-        [113, 56, 102, 255],
+        [121, 57, 94, 255],
+        [123, 57, 98, 255],
         [123, 57, 98, 255],
         [137, 94, 77, 255],
-        [114, 56, 107, 255],
+        [139, 95, 79, 255],
         [141, 98, 81, 255],
         [141, 98, 81, 255],
         [142, 100, 83, 255],
@@ -678,7 +677,7 @@ fn chunk_1_17_1() {
         [117, 57, 81, 255],
         [117, 57, 81, 255],
         [118, 57, 84, 255],
-        [112, 56, 90, 255],
+        [118, 57, 84, 255],
         [119, 57, 88, 255],
         [119, 57, 88, 255],
         [120, 57, 90, 255],
@@ -695,13 +694,13 @@ fn chunk_1_17_1() {
         [106, 59, 58, 255],
         [117, 57, 81, 255],
         [118, 57, 84, 255],
-        [112, 56, 90, 255],
+        [118, 57, 84, 255],
         [119, 57, 88, 255],
         [119, 57, 88, 255],
         [120, 57, 90, 255],
         [120, 57, 90, 255],
         [121, 57, 94, 255],
-        [113, 56, 100, 255],
+        [121, 57, 94, 255],
         [123, 57, 98, 255],
         [124, 56, 99, 255],
         [139, 95, 79, 255],
@@ -711,21 +710,21 @@ fn chunk_1_17_1() {
         [106, 59, 56, 255],
         [106, 59, 58, 255],
         [117, 57, 81, 255],
-        [112, 56, 90, 255],
-        [112, 56, 90, 255],
+        [118, 57, 84, 255],
+        [118, 57, 84, 255],
+        [119, 57, 88, 255],
         [119, 57, 88, 255],
-        [112, 56, 93, 255],
         [120, 57, 90, 255],
-        [113, 56, 96, 255],
-        [113, 56, 100, 255],
+        [120, 57, 90, 255],
+        [121, 57, 94, 255],
         [121, 57, 94, 255],
         [123, 57, 98, 255],
         [124, 56, 99, 255],
         [139, 95, 79, 255],
         [141, 98, 81, 255],
         [106, 59, 54, 255],
-        [110, 57, 79, 255],
-        [111, 57, 82, 255],
+        [106, 59, 56, 255],
+        [106, 59, 58, 255],
         [116, 57, 76, 255],
         [117, 57, 81, 255],
         [118, 57, 84, 255],
@@ -733,7 +732,7 @@ fn chunk_1_17_1() {
         [118, 57, 84, 255],
         [119, 57, 88, 255],
         [120, 57, 90, 255],
-        [113, 56, 96, 255],
+        [120, 57, 90, 255],
         [121, 57, 94, 255],
         [121, 57, 94, 255],
         [123, 57, 98, 255],
@@ -742,13 +741,13 @@ fn chunk_1_17_1() {
         [106, 59, 54, 255],
         [106, 59, 54, 255],
         [106, 59, 56, 255],
-        [111, 57, 82, 255],
+        [106, 59, 58, 255],
         [116, 57, 76, 255],
         [117, 57, 81, 255],
         [117, 57, 81, 255],
         [118, 57, 84, 255],
         [118, 57, 84, 255],
-        [112, 56, 93, 255],
+        [119, 57, 88, 255],
         [119, 57, 88, 255],
         [120, 57, 90, 255],
         [121, 57, 94, 255],
@@ -757,7 +756,7 @@ fn chunk_1_17_1() {
         [123, 57, 98, 255],
         [119, 75, 56, 255],
         [106, 59, 54, 255],
-        [109, 57, 75, 255],
+        [106, 59, 54, 255],
         [106, 59, 56, 255],
         [114, 58, 73, 255],
         [116, 57, 76, 255],
@@ -766,22 +765,22 @@ fn chunk_1_17_1() {
         [117, 57, 81, 255],
         [118, 57, 84, 255],
         [119, 57, 88, 255],
-        [112, 56, 93, 255],
+        [119, 57, 88, 255],
         [120, 57, 90, 255],
         [120, 57, 90, 255],
         [121, 57, 94, 255],
         [121, 57, 94, 255],
-        [109, 57, 75, 255],
+        [119, 75, 56, 255],
         [119, 75, 56, 255],
         [106, 59, 54, 255],
         [106, 59, 54, 255],
         [113, 58, 69, 255],
         [113, 58, 69, 255],
         [114, 58, 73, 255],
-        [110, 57, 79, 255],
+        [114, 58, 73, 255],
         [114, 58, 73, 255],
         [117, 57, 81, 255],
-        [112, 56, 90, 255],
+        [118, 57, 84, 255],
         [119, 57, 88, 255],
         [119, 57, 88, 255],
         [120, 57, 90, 255],
@@ -793,11 +792,11 @@ fn chunk_1_17_1() {
         [113, 58, 69, 255],
         [113, 58, 69, 255],
         [113, 58, 69, 255],
-        [109, 57, 75, 255],
+        [113, 58, 69, 255],
         [114, 58, 73, 255],
         [114, 58, 73, 255],
         [116, 57, 76, 255],
-        [111, 57, 87, 255],
+        [117, 57, 81, 255],
         [118, 57, 84, 255],
         [119, 57, 88, 255],
         [119, 57, 88, 255],
@@ -806,7 +805,7 @@ fn chunk_1_17_1() {
         [104, 70, 52, 255],
         [119, 75, 56, 255],
         [119, 75, 56, 255],
-        [109, 57, 75, 255],
+        [119, 75, 56, 255],
         [113, 58, 69, 255],
         [113, 58, 69, 255],
         [113, 58, 69, 255],
@@ -814,21 +813,21 @@ fn chunk_1_17_1() {
         [114, 58, 73, 255],
         [116, 57, 76, 255],
         [117, 57, 81, 255],
-        [112, 56, 90, 255],
+        [118, 57, 84, 255],
         [118, 57, 84, 255],
         [119, 57, 88, 255],
         [119, 57, 88, 255],
         [120, 57, 90, 255],
-        [109, 57, 75, 255],
         [119, 75, 56, 255],
-        [109, 57, 75, 255],
+        [119, 75, 56, 255],
+        [119, 75, 56, 255],
         [119, 75, 56, 255],
         [113, 58, 69, 255],
         [113, 58, 69, 255],
         [113, 58, 69, 255],
         [113, 58, 69, 255],
         [113, 58, 69, 255],
-        [110, 57, 79, 255],
+        [114, 58, 73, 255],
         [116, 57, 76, 255],
         [117, 57, 81, 255],
         [118, 57, 84, 255],
@@ -1106,6 +1105,24 @@ fn chunk_custom_heights_1_17_1() {
     assert_eq!(expected, exercise_render(&chunk));
 }
 
+#[test]
+fn boxed_chunks_render_the_same() {
+    for data in [
+        CHUNK_21W44A_1,
+        CHUNK_1_17_0,
+        CHUNK_1_17_1,
+        CHUNK_CUSTOM_HEIGHTS_1_17_1,
+    ] {
+        let chunk = JavaChunk::from_bytes(data).unwrap();
+        let boxed = JavaChunk::boxed(data).unwrap();
+        assert_eq!(exercise_render(&chunk), exercise_render(boxed.as_ref()));
+
+        // Boxed chunks can be rendered on another thread.
+        let render = std::thread::spawn(move || exercise_render(boxed.as_ref()));
+        assert_eq!(exercise_render(&chunk), render.join().unwrap());
+    }
+}
+
 fn calculate_hash<T: Hash + ?Sized>(t: &T) -> u64 {
     let mut s = DefaultHasher::new();
     t.hash(&mut s);
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use crate::{count_blocks, Block, BlockFilter, Chunk, JavaChunk};
+
+use super::NoFastPath;
+
+const CHUNKS: &[&[u8]] = &[
+    crate::fixtures::chunk_1_16(),
+    crate::fixtures::chunk_1_17(),
+    crate::fixtures::chunk_1_18(),
+];
+
+fn block(s: &str) -> Block {
+    s.parse().unwrap()
+}
+
+#[test]
+fn filter_names() {
+    let ores = BlockFilter::new().name("minecraft:*_ore");
+    assert!(ores.matches(&block("minecraft:iron_ore")));
+    assert!(ores.matches(&block("minecraft:deepslate_iron_ore")));
+    assert!(!ores.matches(&block("minecraft:iron_block")));
+    assert!(!ores.matches(&block("minecraft:ore_thing")));
+
+    let filter = BlockFilter::new()
+        .name("minecraft:stone")
+        .name("*:*deepslate*");
+    assert!(filter.matches(&block("minecraft:stone")));
+    assert!(filter.matches(&block("mymod:deepslate")));
+    assert!(filter.matches(&block("minecraft:cobbled_deepslate_wall")));
+    assert!(!filter.matches(&block("minecraft:stone_bricks")));
+    assert!(!filter.matches(&block("minecraft:granite")));
+
+    assert!(BlockFilter::new().matches(&block("minecraft:granite")));
+}
+
+#[test]
+fn filter_properties() {
+    let filter = BlockFilter::new()
+        .name("minecraft:*_stairs")
+        .property("half", "top")
+        .property("facing", "east");
+
+    assert!(filter.matches(&block(
+        "minecraft:oak_stairs[facing=east,half=top,shape=straight]"
+    )));
+    assert!(!filter.matches(&block("minecraft:oak_stairs[facing=east,half=bottom]")));
+    assert!(!filter.matches(&block("minecraft:oak_stairs[half=top]")));
+    assert!(!filter.matches(&block("minecraft:oak_slab[facing=east,half=top]")));
+}
+
+#[test]
+fn counts_match_checking_every_block() {
+    let filters = [
+        BlockFilter::new(),
+        BlockFilter::new().name("minecraft:*_ore"),
+        BlockFilter::new()
+            .name("minecraft:*")
+            .property("snowy", "false"),
+    ];
+
+    for data in CHUNKS {
+        let chunk = JavaChunk::from_bytes(data).unwrap();
+
+        for filter in &filters {
+            let fast = count_blocks(&chunk, filter);
+            let slow = count_blocks(&NoFastPath(&chunk), filter);
+            assert_eq!(fast, slow);
+        }
+    }
+}
+
+#[test]
+fn counts_cover_every_block() {
+    for data in CHUNKS {
+        let chunk = JavaChunk::from_bytes(data).unwrap();
+
+        let mut expected: HashMap<String, u64> = HashMap::new();
+        for y in chunk.y_range() {
+            for z in 0..16 {
+                for x in 0..16 {
+                    if let Some(block) = chunk.block(x, y, z) {
+                        *expected.entry(block.name().to_owned()).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        assert_eq!(count_blocks(&chunk, &BlockFilter::new()), expected);
+    }
+}
+
+#[test]
+fn counts_only_filtered_blocks() {
+    let chunk = JavaChunk::from_bytes(CHUNKS[2]).unwrap();
+    let counts = count_blocks(&chunk, &BlockFilter::new().name("minecraft:*_ore"));
+
+    assert!(!counts.is_empty());
+    assert!(counts.keys().all(|name| name.ends_with("_ore")));
+}
+
+#[test]
+fn palette_blocks_are_unique_and_cover_chunk() {
+    use std::collections::HashSet;
+
+    for data in CHUNKS {
+        let chunk = JavaChunk::from_bytes(data).unwrap();
+        let palette: Vec<_> = chunk.palette_blocks().map(|b| b.to_string()).collect();
+        let unique: HashSet<_> = palette.iter().cloned().collect();
+        assert_eq!(palette.len(), unique.len());
+
+        for y in chunk.y_range() {
+            for z in 0..16 {
+                for x in 0..16 {
+                    let block = chunk.block(x, y, z).unwrap();
+                    assert!(unique.contains(&block.to_string()), "{block}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn chunk_palette_matches_chunk() {
+    use crate::ChunkPalette;
+
+    for data in CHUNKS {
+        let chunk = JavaChunk::from_bytes(data).unwrap();
+        let mut expected: Vec<_> = chunk.palette_blocks().map(|b| b.to_string()).collect();
+        let mut palette: Vec<_> = ChunkPalette::from_bytes(data)
+            .unwrap()
+            .blocks()
+            .iter()
+            .map(|b| b.to_string())
+            .collect();
+
+        expected.sort();
+        palette.sort();
+
+        // Normalized sections fill empty sections with air, which need not be
+        // in the chunk's own palettes.
+        expected.retain(|b| b != "minecraft:air" || palette.contains(b));
+        assert_eq!(expected, palette);
+    }
+}
+
+#[test]
+fn find_blocks_in_region() {
+    use std::io::Cursor;
+
+    use crate::{find_blocks, Region};
+
+    let mut region = Region::new(Cursor::new(vec![])).unwrap();
+    for (i, data) in CHUNKS.iter().enumerate() {
+        region.write_chunk(i, 1, data).unwrap();
+    }
+    region.write_chunk(5, 5, &[1, 2, 3]).unwrap();
+    // Corrupt, so reading it fails.
+    region
+        .write_raw_chunk(6, 6, crate::CompressionScheme::Zlib, &[1, 2, 3])
+        .unwrap();
+
+    let filter = BlockFilter::new().name("minecraft:*_ore");
+    let found = find_blocks(&mut region, |b| filter.matches(b)).unwrap();
+
+    for (i, data) in CHUNKS.iter().enumerate() {
+        let chunk = JavaChunk::from_bytes(data).unwrap();
+        let mut expected: Vec<_> = chunk
+            .palette_blocks()
+            .filter(|b| filter.matches(b))
+            .map(|b| b.name().to_owned())
+            .collect();
+        expected.sort();
+        expected.dedup();
+
+        match found.get(&(i, 1)) {
+            Some(names) => assert_eq!(names, &expected),
+            None => assert!(expected.is_empty()),
+        }
+    }
+    assert!(!found.is_empty());
+    assert!(!found.contains_key(&(5, 5)));
+}
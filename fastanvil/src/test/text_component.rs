@@ -0,0 +1,62 @@
+use crate::text_component::{to_plain, Content, TextComponent};
+
+#[test]
+fn plain_forms() {
+    assert_eq!(to_plain(r#""hello""#), "hello");
+    assert_eq!(to_plain(r#"["a", {"text": "b"}, ["c", "d"]]"#), "abcd");
+    assert_eq!(to_plain(r#"{"text": 12}"#), "12");
+    assert_eq!(to_plain("true"), "true");
+    assert_eq!(to_plain("[]"), "");
+}
+
+#[test]
+fn not_json_is_kept() {
+    assert_eq!(to_plain("hello there"), "hello there");
+    assert_eq!(to_plain(""), "");
+}
+
+#[test]
+fn sign_line_with_nested_extra() {
+    let line = r#"{"extra":[{"bold":true,"color":"dark_red","text":"Warning"},{"text":": ","extra":[{"italic":true,"text":"mobs"}]}],"text":""}"#;
+    assert_eq!(to_plain(line), "Warning: mobs");
+
+    let component = TextComponent::parse(line).unwrap();
+    assert_eq!(component.extra[0].style.bold, Some(true));
+    assert_eq!(component.extra[0].style.color.as_deref(), Some("dark_red"));
+    assert_eq!(component.extra[1].extra[0].style.italic, Some(true));
+}
+
+#[test]
+fn list_children_follow_the_first() {
+    let component = TextComponent::parse(r#"[{"text":"a","bold":true},"b"]"#).unwrap();
+    assert_eq!(component.style.bold, Some(true));
+    assert_eq!(component.extra.len(), 1);
+    assert_eq!(component.extra[0].content, Content::Text("b".into()));
+}
+
+#[test]
+fn translate_substitutes_arguments() {
+    let plain = to_plain(r#"{"translate":"x.y","fallback":"%s and %s","with":["a",{"text":"b"}]}"#);
+    assert_eq!(plain, "a and b");
+
+    let plain =
+        to_plain(r#"{"translate":"x.y","fallback":"%2$s, %1$s, 100%% %d","with":["a","b"]}"#);
+    assert_eq!(plain, "b, a, 100% %d");
+
+    // Without a fallback the key is the best there is.
+    assert_eq!(
+        to_plain(r#"{"translate":"block.minecraft.stone"}"#),
+        "block.minecraft.stone"
+    );
+}
+
+#[test]
+fn other_content() {
+    assert_eq!(to_plain(r#"{"keybind":"key.jump"}"#), "key.jump");
+    assert_eq!(to_plain(r#"{"selector":"@p"}"#), "@p");
+    assert_eq!(
+        to_plain(r#"{"score":{"name":"Steve","objective":"kills","value":"3"}}"#),
+        "3"
+    );
+    assert_eq!(to_plain(r#"{"nbt":"Items","block":"0 0 0"}"#), "");
+}
@@ -1,6 +1,6 @@
 use crate::JavaChunk;
 
-const UNICODE_CHUNK: &[u8] = include_bytes!("../../resources/unicode.chunk");
+const UNICODE_CHUNK: &[u8] = crate::fixtures::chunk_unicode();
 
 #[test]
 fn unicode_chunk() {
@@ -0,0 +1,242 @@
+use fastnbt::{nbt, IntArray, LongArray, Value};
+
+use crate::{upgrade::chunk_to_1_18, Chunk, HeightMode, JavaChunk};
+
+const CHUNK_1_17_0: &[u8] = crate::fixtures::chunk_1_17_0();
+const CHUNK_1_17_1: &[u8] = crate::fixtures::chunk_1_17();
+const CHUNK_CUSTOM_HEIGHTS_1_17_1: &[u8] = crate::fixtures::chunk_1_17_custom_heights();
+const CHUNK_ETHO: &[u8] = crate::fixtures::chunk_1_16();
+const CHUNK_21W44A_1: &[u8] = crate::fixtures::chunk_1_18();
+
+fn upgrade(data: &[u8]) -> Value {
+    let chunk: Value = fastnbt::from_bytes(data).unwrap();
+    chunk_to_1_18(&chunk).unwrap()
+}
+
+fn parse(chunk: &Value) -> JavaChunk {
+    JavaChunk::from_bytes(&fastnbt::to_bytes(chunk).unwrap()).unwrap()
+}
+
+fn assert_same_blocks(expected: &JavaChunk, actual: &JavaChunk) {
+    assert_eq!(expected.y_range(), actual.y_range());
+
+    for y in expected.y_range() {
+        for z in 0..16 {
+            for x in 0..16 {
+                assert_eq!(
+                    expected.block(x, y, z).map(|b| b.encoded_description()),
+                    actual.block(x, y, z).map(|b| b.encoded_description()),
+                    "block at {x}, {y}, {z}"
+                );
+            }
+        }
+    }
+
+    for z in 0..16 {
+        for x in 0..16 {
+            assert_eq!(
+                expected.surface_height(x, z, HeightMode::Trust),
+                actual.surface_height(x, z, HeightMode::Trust),
+                "height at {x}, {z}"
+            );
+        }
+    }
+}
+
+#[test]
+fn real_chunks_keep_their_blocks() {
+    for data in [
+        CHUNK_1_17_0,
+        CHUNK_1_17_1,
+        CHUNK_CUSTOM_HEIGHTS_1_17_1,
+        CHUNK_ETHO,
+    ] {
+        let upgraded = upgrade(data);
+        assert_same_blocks(&JavaChunk::from_bytes(data).unwrap(), &parse(&upgraded));
+    }
+}
+
+#[test]
+fn layout_is_renamed() {
+    let chunk = upgrade(CHUNK_1_17_1);
+
+    assert_eq!(chunk.pointer("/DataVersion"), Some(&Value::Int(2860)));
+    assert_eq!(chunk.pointer("/yPos"), Some(&Value::Int(0)));
+    assert!(chunk.pointer("/Level").is_none());
+    assert!(chunk.pointer("/xPos").is_some());
+    assert!(chunk.pointer("/Status").is_some());
+    assert!(chunk.pointer("/block_entities").is_some());
+    assert!(chunk.pointer("/block_ticks").is_some());
+    assert!(chunk.pointer("/fluid_ticks").is_some());
+    assert!(chunk.pointer("/structures/starts").is_some());
+    assert!(chunk.pointer("/structures/References").is_some());
+
+    // The empty section below the world is dropped.
+    assert_eq!(chunk.pointer("/sections/0/Y"), Some(&Value::Byte(0)));
+    assert!(chunk.pointer("/sections/0/block_states/palette").is_some());
+    assert!(chunk.pointer("/sections/0/biomes/palette").is_some());
+    assert!(chunk.pointer("/sections/0/Palette").is_none());
+}
+
+#[test]
+fn custom_heights_start_below_zero() {
+    let chunk = upgrade(CHUNK_CUSTOM_HEIGHTS_1_17_1);
+    assert_eq!(chunk.pointer("/yPos"), Some(&Value::Int(-8)));
+
+    // The top section only has light.
+    let sections = match chunk.pointer("/sections") {
+        Some(Value::List(sections)) => sections,
+        _ => panic!("no sections"),
+    };
+    let top = sections
+        .iter()
+        .find(|s| s.pointer("/Y") == Some(&Value::Byte(6)));
+    assert!(top.unwrap().pointer("/block_states").is_none());
+    assert!(top.unwrap().pointer("/SkyLight").is_some());
+}
+
+#[test]
+fn chunks_already_in_1_18_layout_are_unchanged() {
+    let chunk: Value = fastnbt::from_bytes(CHUNK_21W44A_1).unwrap();
+    assert_eq!(chunk_to_1_18(&chunk).unwrap(), chunk);
+}
+
+#[test]
+fn chunks_without_palettes_are_an_error() {
+    let chunk = nbt!({
+        "DataVersion": 1343,
+        "Level": {
+            "Sections": [{"Y": 0_i8, "Blocks": [B; 0, 0]}],
+        },
+    });
+    assert!(chunk_to_1_18(&chunk).is_err());
+
+    // McRegion chunks have no DataVersion.
+    assert!(chunk_to_1_18(&nbt!({"Level": {}})).is_err());
+    assert!(chunk_to_1_18(&nbt!([1, 2])).is_err());
+}
+
+/// A chunk with a section of 17 different blocks, needing 5 bits per block,
+/// in the tightly packed layout from before 1.16.
+fn chunk_1_15(biomes: IntArray) -> Value {
+    let indices: Vec<u64> = (0..4096).map(|i| (i * 7 % 17) as u64).collect();
+    let mut states = vec![0i64; 4096 * 5 / 64];
+    for (i, &index) in indices.iter().enumerate() {
+        let bit = i * 5;
+        states[bit / 64] |= (index << (bit % 64)) as i64;
+        if bit % 64 + 5 > 64 {
+            states[bit / 64 + 1] |= (index >> (64 - bit % 64)) as i64;
+        }
+    }
+
+    let palette: Vec<Value> = (0..17)
+        .map(|i| nbt!({"Name": format!("minecraft:block_{}", i)}))
+        .collect();
+
+    // Every column is 15 high, packed into 9 bits without padding.
+    let mut heights = vec![0i64; 36];
+    for i in 0..256 {
+        let bit = i * 9;
+        heights[bit / 64] |= 15 << (bit % 64);
+        if bit % 64 + 9 > 64 {
+            heights[bit / 64 + 1] |= 15 >> (64 - bit % 64);
+        }
+    }
+
+    nbt!({
+        "DataVersion": 2230,
+        "Level": {
+            "xPos": 0,
+            "zPos": 0,
+            "Status": "full",
+            "Biomes": biomes,
+            "Heightmaps": {"MOTION_BLOCKING": LongArray::new(heights)},
+            "TileEntities": [],
+            "Sections": [
+                {"Y": -1_i8},
+                {"Y": 0_i8, "Palette": palette, "BlockStates": LongArray::new(states)},
+            ],
+        },
+    })
+}
+
+#[test]
+fn tightly_packed_states_and_heights_are_repacked() {
+    let chunk = chunk_1_15(IntArray::new(vec![1; 1024]));
+    let upgraded = chunk_to_1_18(&chunk).unwrap();
+
+    let data = match upgraded.pointer("/sections/0/block_states/data") {
+        Some(Value::LongArray(data)) => data.len(),
+        _ => panic!("no block states"),
+    };
+    // 12 indices to a long, with padding.
+    assert_eq!(data, 342);
+    match upgraded.pointer("/Heightmaps/MOTION_BLOCKING") {
+        Some(Value::LongArray(heights)) => assert_eq!(heights.len(), 37),
+        _ => panic!("no heightmap"),
+    }
+
+    let upgraded = parse(&upgraded);
+    assert_same_blocks(&parse(&chunk), &upgraded);
+    assert_eq!(upgraded.surface_height(3, 4, HeightMode::Trust), 15);
+}
+
+#[test]
+fn biomes_become_section_palettes() {
+    // Desert in the upper half of each cell column of the section, plains
+    // below. The mountains id was renamed in 1.18, and 1000 is unknown.
+    let biomes = (0..1024)
+        .map(|i| match (i % 16, i / 16) {
+            (0, _) => 3,
+            (1, _) => 1000,
+            (_, y) if y % 4 >= 2 => 2,
+            _ => 1,
+        })
+        .collect();
+    let chunk = chunk_to_1_18(&chunk_1_15(IntArray::new(biomes))).unwrap();
+
+    let palette = chunk.pointer("/sections/0/biomes/palette").unwrap();
+    assert_eq!(
+        palette,
+        &nbt!([
+            "minecraft:windswept_hills",
+            "minecraft:plains",
+            "minecraft:desert"
+        ])
+    );
+
+    let chunk = parse(&chunk);
+    let name = |x, y, z| format!("{:?}", chunk.biome(x, y, z).unwrap());
+    assert_eq!(name(0, 0, 0), "WindsweptHills");
+    assert_eq!(name(4, 0, 0), "Plains");
+    assert_eq!(name(8, 0, 0), "Plains");
+    assert_eq!(name(8, 8, 0), "Desert");
+}
+
+#[test]
+fn column_biomes_fill_every_cell() {
+    let biomes = (0..256).map(|i| if i % 16 < 8 { 2 } else { 1 }).collect();
+    let chunk = chunk_to_1_18(&chunk_1_15(IntArray::new(biomes))).unwrap();
+    let chunk = parse(&chunk);
+
+    for y in 0..16 {
+        assert_eq!(format!("{:?}", chunk.biome(0, y, 0).unwrap()), "Desert");
+        assert_eq!(format!("{:?}", chunk.biome(12, y, 15).unwrap()), "Plains");
+    }
+}
+
+#[test]
+fn biomes_line_up_when_the_bottom_sections_are_air() {
+    // Only the section at Y 2 has blocks. The biomes still start at the
+    // bottom of the world, just above the section with only light.
+    let biomes = (0..1024).map(|i| if i / 64 == 2 { 2 } else { 1 }).collect();
+    let mut chunk = chunk_1_15(IntArray::new(biomes));
+    *chunk.pointer_mut("/Level/Sections/1/Y").unwrap() = Value::Byte(2);
+
+    let chunk = chunk_to_1_18(&chunk).unwrap();
+    assert_eq!(chunk.pointer("/yPos"), Some(&Value::Int(0)));
+    assert_eq!(
+        chunk.pointer("/sections/0/biomes/palette"),
+        Some(&nbt!(["minecraft:desert"]))
+    );
+}
@@ -0,0 +1,70 @@
+use std::io::Cursor;
+
+use crate::{
+    version::{region_data_versions, DataVersion},
+    CompressionScheme::Zlib,
+    Region,
+};
+
+const ETHO: &[u8] = crate::fixtures::chunk_1_16();
+const CHUNK_1_12: &[u8] = crate::fixtures::chunk_1_12();
+const CHUNK_1_17_1: &[u8] = crate::fixtures::chunk_1_17();
+
+#[test]
+fn releases_have_names() {
+    assert_eq!(DataVersion(3337).to_release_name(), Some("1.19.4"));
+    assert_eq!(DataVersion::V1_13.to_release_name(), Some("1.13"));
+    assert_eq!(DataVersion(2578).to_release_name(), Some("1.16.2"));
+
+    // Snapshots, and versions before data versions.
+    assert_eq!(DataVersion(2845).to_release_name(), None);
+    assert_eq!(DataVersion(100).to_release_name(), None);
+
+    assert!(DataVersion(5000).is_newer_than_known());
+    assert!(!DataVersion::V1_21.is_newer_than_known());
+}
+
+#[test]
+fn releases_by_name() {
+    assert_eq!(DataVersion::try_from("1.18"), Ok(DataVersion::V1_18));
+    assert_eq!(DataVersion::try_from("1.12.2"), Ok(DataVersion(1343)));
+
+    let err = DataVersion::try_from("23w13a").unwrap_err();
+    assert_eq!(err.to_string(), "unknown release: \"23w13a\"");
+}
+
+#[test]
+fn versions_order_by_release() {
+    assert!(DataVersion::V1_9 < DataVersion::V1_10);
+    assert!(DataVersion::try_from("1.16.5").unwrap() < DataVersion::V1_17);
+}
+
+#[test]
+fn region_with_mixed_versions() {
+    let mut region = Region::new(Cursor::new(vec![])).unwrap();
+    region.write_chunk(0, 0, ETHO).unwrap();
+    region.write_chunk(3, 1, CHUNK_1_17_1).unwrap();
+    region.write_chunk(5, 1, CHUNK_1_12).unwrap();
+    region.write_chunk(5, 5, &[1, 2, 3]).unwrap();
+    // Corrupt, so reading it fails.
+    region.write_raw_chunk(6, 6, Zlib, &[1, 2, 3]).unwrap();
+
+    let versions = region_data_versions(&mut region).unwrap();
+    assert_eq!(versions, Some(DataVersion(1139)..=DataVersion(2730)));
+}
+
+#[test]
+fn region_without_versions() {
+    let mut region = Region::new(Cursor::new(vec![])).unwrap();
+    assert_eq!(region_data_versions(&mut region).unwrap(), None);
+
+    let chunk = fastnbt::to_bytes(&fastnbt::nbt!({"Level": {"xPos": 0}})).unwrap();
+    region.write_chunk(0, 0, &chunk).unwrap();
+    assert_eq!(region_data_versions(&mut region).unwrap(), None);
+
+    region.write_chunk(1, 0, ETHO).unwrap();
+    assert_eq!(
+        region_data_versions(&mut region).unwrap(),
+        Some(DataVersion(2578)..=DataVersion(2578))
+    );
+}
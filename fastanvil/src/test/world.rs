@@ -0,0 +1,310 @@
+use std::{io::Write, path::Path};
+
+use fastnbt::{nbt, Value};
+use flate2::{write::GzEncoder, Compression};
+
+use super::TempPath;
+use crate::{
+    version::{DataVersion, GameVersion},
+    Dimension, Error, RCoord, RegionLoader, ResourceLocation, World, WorldCursor,
+};
+
+/// A world directory with the given region directories.
+fn world_dir(name: &str, region_dirs: &[&str]) -> TempPath {
+    let dir = TempPath::dir(name);
+    for region_dir in region_dirs {
+        std::fs::create_dir_all(dir.0.join(region_dir)).unwrap();
+    }
+    dir
+}
+
+fn write_level_dat(world: &Path, level: &Value) {
+    let mut file = GzEncoder::new(Vec::new(), Compression::default());
+    file.write_all(&fastnbt::to_bytes(level).unwrap()).unwrap();
+    std::fs::write(world.join("level.dat"), file.finish().unwrap()).unwrap();
+}
+
+/// Write a region whose chunks are a single byte, at the given coordinates
+/// within the region.
+fn write_region(dir: &Path, name: &str, chunks: &[(usize, usize, u8)]) {
+    let file = std::fs::File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(dir.join(name))
+        .unwrap();
+    let mut region = crate::Region::new(file).unwrap();
+    for &(x, z, byte) in chunks {
+        region.write_chunk(x, z, &[byte]).unwrap();
+    }
+}
+
+/// A world with two regions in the overworld and one in the nether.
+fn scan_world(name: &str) -> TempPath {
+    let world = world_dir(name, &["region", "DIM-1/region"]);
+    write_region(
+        &world.0.join("region"),
+        "r.0.0.mca",
+        &[(0, 0, 1), (3, 1, 2)],
+    );
+    write_region(&world.0.join("region"), "r.-1.0.mca", &[(31, 31, 3)]);
+    write_region(&world.0.join("DIM-1/region"), "r.0.0.mca", &[(1, 0, 4)]);
+    world
+}
+
+fn custom(id: &str) -> Dimension {
+    Dimension::Custom(ResourceLocation::parse(id).unwrap())
+}
+
+#[test]
+fn world_finds_vanilla_and_datapack_dimensions() {
+    let world = world_dir(
+        "world-dimensions",
+        &[
+            "region",
+            "DIM1/region",
+            "dimensions/mypack/sky/region",
+            "dimensions/mypack/sky/entities",
+            "dimensions/mypack/caves/deep/region",
+            "dimensions/other/mining/region",
+            // Not dimensions.
+            "dimensions/mypack/empty",
+            "dimensions/Bad/name/region",
+        ],
+    );
+    std::fs::write(world.0.join("dimensions/mypack/sky/region/r.0.0.mca"), []).unwrap();
+
+    let world = World::open(&world.0).unwrap();
+    assert_eq!(
+        world.dimensions(),
+        vec![
+            Dimension::Overworld,
+            Dimension::End,
+            custom("mypack:caves/deep"),
+            custom("mypack:sky"),
+            custom("other:mining"),
+        ]
+    );
+
+    let loader = world.region_loader(&custom("mypack:sky")).unwrap();
+    assert!(loader.list().unwrap().is_empty());
+    assert!(world.region_loader(&Dimension::Nether).is_none());
+    assert!(world.region_loader(&custom("mypack:empty")).is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn world_stops_at_symlink_loops() {
+    let world = world_dir("world-symlink-loop", &["dimensions/mypack/sky/region"]);
+    let sky = world.0.join("dimensions/mypack/sky");
+    std::os::unix::fs::symlink(&sky, sky.join("loop")).unwrap();
+
+    let dimensions = World::open(&world.0).unwrap().dimensions();
+    assert_eq!(dimensions[0], custom("mypack:sky"));
+    assert_eq!(
+        dimensions.last(),
+        Some(&custom("mypack:sky/loop/loop/loop/loop/loop/loop/loop"))
+    );
+}
+
+#[test]
+fn world_uses_vanilla_dimensions_under_dimensions_dir() {
+    let world = world_dir(
+        "world-vanilla-dimensions",
+        &[
+            "region",
+            "dimensions/minecraft/overworld/region",
+            "dimensions/minecraft/the_nether/region",
+        ],
+    );
+    // Only the nether is under `dimensions`, so that is where it is loaded
+    // from.
+    let nether = world.0.join("dimensions/minecraft/the_nether/region");
+    std::fs::write(nether.join("r.1.2.mca"), []).unwrap();
+
+    let world = World::open(&world.0).unwrap();
+    assert_eq!(
+        world.dimensions(),
+        vec![Dimension::Overworld, Dimension::Nether]
+    );
+
+    let regions = world
+        .region_loader(&Dimension::Nether)
+        .unwrap()
+        .list_detailed()
+        .unwrap();
+    assert_eq!(regions.len(), 1);
+    assert_eq!(regions[0].path, nether.join("r.1.2.mca"));
+}
+
+#[test]
+fn world_without_directory_errors() {
+    let err = World::open(Path::new("/no/such/world")).unwrap_err();
+    assert_eq!(err.to_string(), "directory not found: /no/such/world");
+}
+
+#[test]
+fn world_finds_directories_ignoring_case() {
+    let world = world_dir(
+        "world-case",
+        &["Region", "dim-1/REGION", "Dimensions/mypack/sky/Region"],
+    );
+    write_region(&world.0.join("Region"), "r.0.0.mca", &[(0, 0, 1)]);
+
+    let world = World::open(&world.0).unwrap();
+    assert_eq!(
+        world.dimensions(),
+        vec![
+            Dimension::Overworld,
+            Dimension::Nether,
+            custom("mypack:sky")
+        ]
+    );
+
+    let loader = world.region_loader(&Dimension::Overworld).unwrap();
+    assert_eq!(loader.list().unwrap(), vec![(RCoord(0), RCoord(0))]);
+}
+
+#[test]
+fn dimension_ids_display_as_ids() {
+    assert_eq!(Dimension::Nether.to_string(), "minecraft:the_nether");
+    assert_eq!(custom("mypack:sky").to_string(), "mypack:sky");
+    assert_eq!(
+        Dimension::from(ResourceLocation::parse("the_end").unwrap()),
+        Dimension::End
+    );
+    assert_eq!(
+        custom("mypack:sky").region_dir(Path::new("w")),
+        Path::new("w/dimensions/mypack/sky/region")
+    );
+}
+
+#[test]
+fn game_version_from_level_dat() {
+    let dir = world_dir("world-game-version", &["region"]);
+    write_level_dat(
+        &dir.0,
+        &nbt!({
+            "Data": {
+                "DataVersion": 3337,
+                "LevelName": "test",
+                "Version": {"Id": 3337, "Name": "1.19.4", "Series": "main", "Snapshot": 0_i8},
+            },
+        }),
+    );
+
+    let world = World::open(&dir.0).unwrap();
+    assert_eq!(
+        world.game_version().unwrap(),
+        Some(GameVersion {
+            data_version: DataVersion(3337),
+            name: Some("1.19.4".to_owned()),
+            snapshot: false,
+        })
+    );
+
+    write_level_dat(
+        &dir.0,
+        &nbt!({"Data": {"DataVersion": 3442, "Version": {"Name": "23w13a", "Snapshot": 1_i8}}}),
+    );
+    let version = world.game_version().unwrap().unwrap();
+    assert_eq!(version.name.as_deref(), Some("23w13a"));
+    assert!(version.snapshot);
+    assert_eq!(version.data_version.to_release_name(), None);
+}
+
+#[test]
+fn game_version_of_old_or_missing_level_dat() {
+    let dir = world_dir("world-old-game-version", &["region"]);
+    let world = World::open(&dir.0).unwrap();
+    assert!(world.game_version().is_err());
+
+    // Before 1.9.
+    write_level_dat(&dir.0, &nbt!({"Data": {"version": 19133}}));
+    assert_eq!(world.game_version().unwrap(), None);
+}
+
+#[test]
+fn scan_yields_every_chunk_in_order() {
+    let dir = scan_world("world-scan");
+    let world = World::open(&dir.0).unwrap();
+
+    let chunks: Vec<_> = world
+        .scan()
+        .unwrap()
+        .map(|c| {
+            let c = c.unwrap();
+            (c.dimension, c.region.0 .0, c.chunk.data[0])
+        })
+        .collect();
+    assert_eq!(
+        chunks,
+        vec![
+            (Dimension::Overworld, -1, 3),
+            (Dimension::Overworld, 0, 1),
+            (Dimension::Overworld, 0, 2),
+            (Dimension::Nether, 0, 4),
+        ]
+    );
+}
+
+#[test]
+fn scan_from_cursor_carries_on_where_scan_stopped() {
+    let dir = scan_world("world-scan-resume");
+    let world = World::open(&dir.0).unwrap();
+
+    // Stop part way through the second region.
+    for taken in 0..=4 {
+        let mut scan = world.scan().unwrap();
+        let first: Vec<_> = scan.by_ref().take(taken).map(|c| c.unwrap()).collect();
+        let saved = serde_json::to_string(&scan.cursor()).unwrap();
+        let cursor: WorldCursor = serde_json::from_str(&saved).unwrap();
+
+        let rest: Vec<_> = world
+            .scan_from(cursor)
+            .unwrap()
+            .map(|c| c.unwrap().chunk.data[0])
+            .collect();
+        let all: Vec<_> = first.iter().map(|c| c.chunk.data[0]).chain(rest).collect();
+        assert_eq!(all, vec![3, 1, 2, 4], "stopped after {taken}");
+    }
+}
+
+#[test]
+fn scan_from_skips_finished_regions_even_if_changed() {
+    let dir = scan_world("world-scan-finished");
+    let world = World::open(&dir.0).unwrap();
+
+    let mut scan = world.scan().unwrap();
+    scan.next().unwrap().unwrap();
+    scan.next().unwrap().unwrap();
+    let cursor = scan.cursor();
+
+    // The finished region changing doesn't matter.
+    write_region(&dir.0.join("region"), "r.-1.0.mca", &[(0, 0, 9)]);
+    let rest: Vec<_> = world
+        .scan_from(cursor)
+        .unwrap()
+        .map(|c| c.unwrap().chunk.data[0])
+        .collect();
+    assert_eq!(rest, vec![2, 4]);
+}
+
+#[test]
+fn scan_from_changed_region_errors() {
+    let dir = scan_world("world-scan-stale");
+    let world = World::open(&dir.0).unwrap();
+
+    let mut scan = world.scan().unwrap();
+    scan.next().unwrap().unwrap();
+    scan.next().unwrap().unwrap();
+    let cursor = scan.cursor();
+
+    // The region the scan was part way through gets a new chunk.
+    write_region(&dir.0.join("region"), "r.0.0.mca", &[(5, 5, 9)]);
+    let mut scan = world.scan_from(cursor).unwrap();
+    let err = scan.next().unwrap().err().unwrap();
+    assert_eq!(err.to_string(), Error::StaleCursor.to_string());
+    assert!(scan.next().is_none());
+}
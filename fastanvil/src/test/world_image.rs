@@ -0,0 +1,144 @@
+use std::fs::OpenOptions;
+
+use super::TempPath;
+use crate::{
+    biome::Biome, render_world_image, Block, Dimension, HeightMode, Palette, RCoord, Region, Rgba,
+    TopShadeRenderer, WorldImageOpts,
+};
+
+const CHUNK_1_17_1: &[u8] = crate::fixtures::chunk_1_17();
+
+/// A world with two overworld regions, r.0.0 and r.2.1, each with a single
+/// chunk.
+fn world_dir(name: &str) -> TempPath {
+    let dir = TempPath::new(name);
+    let region_dir = Dimension::Overworld.region_dir(&dir.0);
+    std::fs::create_dir_all(&region_dir).unwrap();
+
+    for (x, z, chunk) in [(0, 0, (1, 2)), (2, 1, (31, 31))] {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(region_dir.join(format!("r.{x}.{z}.mca")))
+            .unwrap();
+        let mut region = Region::new(file).unwrap();
+        region.write_chunk(chunk.0, chunk.1, CHUNK_1_17_1).unwrap();
+    }
+
+    dir
+}
+
+struct OpaquePalette;
+
+impl Palette for OpaquePalette {
+    fn pick(&self, _: &Block, _: Option<Biome>) -> Rgba {
+        [100, 100, 100, 255]
+    }
+}
+
+#[test]
+fn world_image_stitches_regions() {
+    let world = world_dir("world-image");
+    let renderer = TopShadeRenderer::new(&OpaquePalette, HeightMode::Trust);
+    let opts = WorldImageOpts {
+        max_size: None,
+        ..Default::default()
+    };
+
+    let rendered = render_world_image(&world.0, &renderer, opts).unwrap();
+    assert_eq!(rendered.origin, (RCoord(0), RCoord(0)));
+    assert_eq!(rendered.scale, 1);
+    assert_eq!(rendered.image.dimensions(), (3 * 512, 2 * 512));
+
+    let alpha = |x: u32, z: u32| rendered.image.get_pixel(x, z).0[3];
+
+    // Chunk 1, 2 of r.0.0.
+    assert_eq!(alpha(16 + 5, 32 + 5), 255);
+    // Chunk 31, 31 of r.2.1.
+    assert_eq!(alpha(2 * 512 + 511, 512 + 511), 255);
+    // Missing chunks and regions are transparent.
+    assert_eq!(alpha(5, 5), 0);
+    assert_eq!(alpha(512 + 100, 100), 0);
+    assert_eq!(alpha(100, 512 + 100), 0);
+}
+
+#[test]
+fn world_image_scales_down_big_worlds() {
+    let world = world_dir("world-image-scaled");
+    let renderer = TopShadeRenderer::new(&OpaquePalette, HeightMode::Trust);
+    let opts = WorldImageOpts {
+        max_size: Some(512),
+        ..Default::default()
+    };
+
+    let rendered = render_world_image(&world.0, &renderer, opts).unwrap();
+    assert_eq!(rendered.scale, 3);
+    assert_eq!(rendered.image.dimensions(), (512, 342));
+
+    let alpha = |x: u32, z: u32| rendered.image.get_pixel(x, z).0[3];
+    assert_eq!(alpha((16 + 5) / 3, (32 + 5) / 3), 255);
+    assert_eq!(alpha(511, 341), 255);
+    assert_eq!(alpha(5 / 3, 5 / 3), 0);
+}
+
+#[test]
+fn world_image_of_empty_dimension() {
+    let world = world_dir("world-image-empty");
+    let renderer = TopShadeRenderer::new(&OpaquePalette, HeightMode::Trust);
+    let opts = WorldImageOpts {
+        dimension: Dimension::Nether,
+        ..Default::default()
+    };
+
+    // The nether has never been visited, so has no region directory.
+    assert!(render_world_image(&world.0, &renderer, opts.clone()).is_err());
+
+    std::fs::create_dir_all(Dimension::Nether.region_dir(&world.0)).unwrap();
+    let rendered = render_world_image(&world.0, &renderer, opts).unwrap();
+    assert_eq!(rendered.image.dimensions(), (0, 0));
+}
+
+#[test]
+fn world_image_legend() {
+    let world = world_dir("world-image-legend");
+    let renderer = TopShadeRenderer::new(&OpaquePalette, HeightMode::Trust);
+
+    let rendered = render_world_image(&world.0, &renderer, WorldImageOpts::default()).unwrap();
+    assert!(rendered.legend.is_none());
+
+    let renderer = renderer.with_legend(true);
+    let rendered = render_world_image(&world.0, &renderer, WorldImageOpts::default()).unwrap();
+    let legend = rendered.legend.unwrap();
+    assert!(legend.blocks() > 1);
+    assert!(legend
+        .iter()
+        .all(|(_, colour)| colour == [100, 100, 100, 255]));
+    assert!(legend.colours("minecraft:air").next().is_none());
+}
+
+#[test]
+fn world_image_too_big_without_max_size_is_an_error() {
+    let world = world_dir("world-image-too-big");
+    // A region so far east the image would be more than u32::MAX wide.
+    let far = Dimension::Overworld
+        .region_dir(&world.0)
+        .join("r.9000000.0.mca");
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(far)
+        .unwrap();
+    Region::new(file).unwrap();
+
+    let renderer = TopShadeRenderer::new(&OpaquePalette, HeightMode::Trust);
+    let opts = WorldImageOpts {
+        max_size: None,
+        ..Default::default()
+    };
+    let err = render_world_image(&world.0, &renderer, opts).err().unwrap();
+    assert!(err.to_string().contains("too big"), "{err}");
+}
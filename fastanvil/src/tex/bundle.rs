@@ -0,0 +1,256 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{de, Deserialize, Serialize};
+
+use super::{Blockstate, Error, Model, Part, Renderer, Result, Texture, Variant, Variants};
+
+/// The version of the bundle format written by [`Renderer::save_bundle`].
+/// Bundles of any other version are stale.
+pub const BUNDLE_VERSION: u32 = 2;
+
+const MAGIC: &[u8; 4] = b"FATB";
+
+// The magic, the version, the key of the source assets and the hash of the
+// bundle's own data.
+const HEADER_LEN: usize = 4 + 4 + 8 + 8;
+
+// Blockstates are split by kind, as NBT has no way to write enum variants
+// that hold data. Each `Variants` is written as a list, since untagged enums
+// can't read bools back from NBT.
+#[derive(Serialize, Deserialize)]
+struct Assets {
+    variants: HashMap<String, HashMap<String, Vec<Variant>>>,
+    multipart: HashMap<String, Vec<Vec<Variant>>>,
+    models: HashMap<String, Model>,
+    textures: HashMap<String, TextureBytes>,
+}
+
+/// A texture written as a list of bytes, rather than a list of numbers that
+/// are each deserialized separately.
+struct TextureBytes(Texture);
+
+impl Serialize for TextureBytes {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for TextureBytes {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> de::Visitor<'de> for BytesVisitor {
+            type Value = TextureBytes;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("texture bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> std::result::Result<Self::Value, E> {
+                Ok(TextureBytes(v.to_vec()))
+            }
+        }
+
+        d.deserialize_bytes(BytesVisitor)
+    }
+}
+
+/// A key for the asset files in `dir` and its subdirectories, made from the
+/// path, size and modification time of each. Give it to
+/// [`save_bundle`][Renderer::save_bundle] and
+/// [`load_bundle`][Renderer::load_bundle] so that a bundle goes stale when
+/// the assets it was made from change.
+///
+/// The files are not read, so this is quick even for the thousands of files
+/// of a resource pack.
+pub fn assets_key(dir: &Path) -> Result<u64> {
+    let mut files = vec![];
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(rel) = dirs.pop() {
+        for entry in std::fs::read_dir(dir.join(&rel)).map_err(Error::Io)? {
+            let entry = entry.map_err(Error::Io)?;
+            let meta = entry.metadata().map_err(Error::Io)?;
+            let path = rel.join(entry.file_name());
+            if meta.is_dir() {
+                dirs.push(path);
+            } else {
+                let modified = meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .unwrap_or_default();
+                files.push((path, meta.len(), modified));
+            }
+        }
+    }
+
+    // Directories are listed in no particular order.
+    files.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    files.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+impl Renderer {
+    /// Save the assets of the renderer to a single file, that
+    /// [`load_bundle`][Renderer::load_bundle] reads back far quicker than
+    /// the thousands of JSON and PNG files they come from.
+    ///
+    /// `key` identifies the assets the renderer was made from, and is stored
+    /// so that loading can tell the bundle is stale once they change. Use
+    /// [`assets_key`] for assets in a directory, or any value that changes
+    /// with the assets, eg a hash of a resource pack's zip.
+    ///
+    /// The models used by the blockstates are saved with their parents
+    /// already merged in, so loading needs no resolving. Models that fail to
+    /// resolve are left out, and so fail when rendering as they would have
+    /// anyway. Block entity mappings and the cache capacity are not saved.
+    pub fn save_bundle(&self, path: &Path, key: u64) -> Result<()> {
+        let mut models = HashMap::new();
+        for name in self.blockstate_models() {
+            if let Ok(mut model) = self.flatten_model(name) {
+                model.parent = None;
+                models.insert(name.to_owned(), model);
+            }
+        }
+
+        let mut variants = HashMap::new();
+        let mut multipart = HashMap::new();
+        for (id, bs) in &self.blockstates {
+            match bs {
+                Blockstate::Variants(v) => {
+                    let v = v.iter().map(|(k, v)| (k.clone(), to_list(v))).collect();
+                    variants.insert(id.clone(), v);
+                }
+                Blockstate::Multipart(parts) => {
+                    let parts = parts.iter().map(|p| to_list(&p.apply)).collect();
+                    multipart.insert(id.clone(), parts);
+                }
+            }
+        }
+
+        let assets = Assets {
+            variants,
+            multipart,
+            models,
+            textures: self
+                .textures
+                .iter()
+                .map(|(name, tex)| (name.clone(), TextureBytes(tex.clone())))
+                .collect(),
+        };
+        let nbt = fastnbt::to_bytes(&assets).map_err(Error::Nbt)?;
+
+        let mut data = Vec::with_capacity(HEADER_LEN + nbt.len());
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&BUNDLE_VERSION.to_be_bytes());
+        data.extend_from_slice(&key.to_be_bytes());
+        data.extend_from_slice(&hash(&nbt).to_be_bytes());
+        data.extend_from_slice(&nbt);
+
+        std::fs::write(path, data).map_err(Error::Io)
+    }
+
+    /// Load a renderer from a bundle written by
+    /// [`save_bundle`][Renderer::save_bundle]. It has the default block
+    /// entity mappings and cache capacity.
+    ///
+    /// Bundles saved with a different `key`, written by another version of the
+    /// format, or that are truncated or corrupt, give [`Error::StaleBundle`].
+    /// The bundle should then be made again from the original assets.
+    pub fn load_bundle(path: &Path, key: u64) -> Result<Self> {
+        let data = std::fs::read(path).map_err(Error::Io)?;
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+            return Err(Error::StaleBundle);
+        }
+
+        let version = u32::from_be_bytes(data[4..8].try_into().unwrap());
+        let saved_key = u64::from_be_bytes(data[8..16].try_into().unwrap());
+        let expected_hash = u64::from_be_bytes(data[16..24].try_into().unwrap());
+        let nbt = &data[HEADER_LEN..];
+        if version != BUNDLE_VERSION || saved_key != key || hash(nbt) != expected_hash {
+            return Err(Error::StaleBundle);
+        }
+
+        let assets: Assets = fastnbt::from_bytes(nbt).map_err(Error::Nbt)?;
+        let blockstates = assets
+            .variants
+            .into_iter()
+            .map(|(id, v)| {
+                let v = v.into_iter().map(|(k, v)| (k, from_list(v))).collect();
+                (id, Blockstate::Variants(v))
+            })
+            .chain(assets.multipart.into_iter().map(|(id, parts)| {
+                let parts = parts
+                    .into_iter()
+                    .map(|v| Part {
+                        apply: from_list(v),
+                    })
+                    .collect();
+                (id, Blockstate::Multipart(parts))
+            }))
+            .collect();
+        let textures = assets
+            .textures
+            .into_iter()
+            .map(|(name, tex)| (name, tex.0))
+            .collect();
+
+        Ok(Renderer::new(blockstates, assets.models, textures))
+    }
+
+    /// The names of the models used by any blockstate.
+    fn blockstate_models(&self) -> Vec<&str> {
+        let mut models = vec![];
+        for bs in self.blockstates.values() {
+            match bs {
+                Blockstate::Variants(variants) => variants
+                    .values()
+                    .for_each(|v| models.extend(variant_models(v))),
+                Blockstate::Multipart(parts) => parts
+                    .iter()
+                    .for_each(|p| models.extend(variant_models(&p.apply))),
+            }
+        }
+        models
+    }
+}
+
+fn to_list(variants: &Variants) -> Vec<Variant> {
+    match variants {
+        Variants::Single(variant) => vec![variant.clone()],
+        Variants::Many(variants) => variants.clone(),
+    }
+}
+
+fn from_list(mut variants: Vec<Variant>) -> Variants {
+    match variants.len() {
+        1 => Variants::Single(variants.remove(0)),
+        _ => Variants::Many(variants),
+    }
+}
+
+fn variant_models(variants: &Variants) -> impl Iterator<Item = &str> {
+    let variants = match variants {
+        Variants::Single(variant) => std::slice::from_ref(variant),
+        Variants::Many(variants) => variants.as_slice(),
+    };
+    variants.iter().map(|v| v.model.as_str())
+}
+
+/// The hash stored in a bundle to check it is not corrupt. The hasher used may
+/// change between versions of Rust, which only makes older bundles stale.
+fn hash(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(data);
+    hasher.finish()
+}
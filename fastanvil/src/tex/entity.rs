@@ -0,0 +1,219 @@
+use crate::{map_colours::dye_colour, Rgba};
+
+use super::Texture;
+
+/// Where to find a flat top-down texture for a block whose appearance comes
+/// from a block entity model rather than a block model, eg chests and beds.
+///
+/// Texture names may contain a `*`, which is replaced with whatever the `*` in
+/// the matching [`EntityBlock`] id matched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntityTexture {
+    /// Use a texture as-is, eg `minecraft:block/*_wool`.
+    Texture(String),
+    /// Crop a region out of an entity texture atlas, eg the lid of a chest
+    /// from `minecraft:entity/chest/normal`. The atlas width is needed since
+    /// textures are stored as raw RGBA data.
+    Atlas {
+        texture: String,
+        atlas_width: usize,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    },
+    /// A single solid colour.
+    Colour(Rgba),
+    /// A solid colour taken from the map colour of the block, see
+    /// [`map_colour`]. Fails if the block has no known map colour.
+    MapColour,
+}
+
+/// A block that should be textured using [`EntityTexture`]s rather than its
+/// block model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityBlock {
+    /// The block id to match, eg `minecraft:chest`. May contain a single `*`
+    /// that matches any run of characters, eg `minecraft:*_bed`.
+    pub id: String,
+    /// Textures to try in order. The first that can be found is used.
+    pub textures: Vec<EntityTexture>,
+}
+
+impl EntityBlock {
+    pub fn new(id: impl Into<String>, textures: Vec<EntityTexture>) -> Self {
+        Self {
+            id: id.into(),
+            textures,
+        }
+    }
+
+    /// If the given block id matches, returns what the `*` matched, or an
+    /// empty string if there is no wildcard.
+    pub(crate) fn matches<'a>(&self, id: &'a str) -> Option<&'a str> {
+        match self.id.split_once('*') {
+            Some((prefix, suffix)) => {
+                let rest = id.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                (!rest.is_empty()).then_some(rest)
+            }
+            None => (self.id == id).then_some(""),
+        }
+    }
+}
+
+/// The built-in block entity mappings used by [`Renderer`][super::Renderer].
+/// Covers chests, beds, banners and mob heads.
+pub fn default_entity_blocks() -> Vec<EntityBlock> {
+    use EntityTexture::*;
+
+    let chest_lid = |texture: &str| Atlas {
+        texture: texture.to_owned(),
+        atlas_width: 64,
+        x: 14,
+        y: 0,
+        width: 14,
+        height: 14,
+    };
+
+    // Mob heads use the top of the head from the mob's own texture.
+    let head_top = |texture: &str| Atlas {
+        texture: texture.to_owned(),
+        atlas_width: 64,
+        x: 8,
+        y: 0,
+        width: 8,
+        height: 8,
+    };
+
+    let mut blocks = vec![
+        EntityBlock::new(
+            "minecraft:chest",
+            vec![chest_lid("minecraft:entity/chest/normal"), MapColour],
+        ),
+        EntityBlock::new(
+            "minecraft:trapped_chest",
+            vec![chest_lid("minecraft:entity/chest/trapped"), MapColour],
+        ),
+        EntityBlock::new(
+            "minecraft:ender_chest",
+            vec![chest_lid("minecraft:entity/chest/ender"), MapColour],
+        ),
+        EntityBlock::new(
+            "minecraft:*_bed",
+            vec![
+                Atlas {
+                    texture: "minecraft:entity/bed/*".to_owned(),
+                    atlas_width: 64,
+                    x: 6,
+                    y: 6,
+                    width: 16,
+                    height: 16,
+                },
+                Texture("minecraft:block/*_wool".to_owned()),
+                MapColour,
+            ],
+        ),
+        // Wall banners must come first, otherwise `*_banner` would match them
+        // with a colour of eg `red_wall`.
+        EntityBlock::new(
+            "minecraft:*_wall_banner",
+            vec![Texture("minecraft:block/*_wool".to_owned()), MapColour],
+        ),
+        EntityBlock::new(
+            "minecraft:*_banner",
+            vec![Texture("minecraft:block/*_wool".to_owned()), MapColour],
+        ),
+    ];
+
+    let heads = [
+        ("skeleton_skull", "minecraft:entity/skeleton/skeleton"),
+        (
+            "wither_skeleton_skull",
+            "minecraft:entity/skeleton/wither_skeleton",
+        ),
+        ("zombie_head", "minecraft:entity/zombie/zombie"),
+        ("creeper_head", "minecraft:entity/creeper/creeper"),
+        ("piglin_head", "minecraft:entity/piglin/piglin"),
+        ("player_head", "minecraft:entity/player/wide/steve"),
+    ];
+
+    for (head, texture) in heads {
+        let (mob, kind) = head.rsplit_once('_').unwrap();
+        let mut textures = vec![head_top(texture)];
+        if head == "player_head" {
+            // Older versions keep Steve at the root of the entity textures.
+            textures.push(head_top("minecraft:entity/steve"));
+        }
+        textures.push(MapColour);
+
+        blocks.push(EntityBlock::new(
+            format!("minecraft:{}", head),
+            textures.clone(),
+        ));
+        blocks.push(EntityBlock::new(
+            format!("minecraft:{}_wall_{}", mob, kind),
+            textures,
+        ));
+    }
+
+    blocks.push(EntityBlock::new("minecraft:dragon_head", vec![MapColour]));
+    blocks.push(EntityBlock::new(
+        "minecraft:dragon_wall_head",
+        vec![MapColour],
+    ));
+
+    blocks
+}
+
+/// The colour a block is drawn with on in-game maps, for the blocks covered by
+/// [`default_entity_blocks`].
+pub fn map_colour(id: &str) -> Option<Rgba> {
+    let id = id.strip_prefix("minecraft:").unwrap_or(id);
+
+    match id {
+        "chest" | "trapped_chest" => return Some([143, 119, 72, 255]),
+        "ender_chest" => return Some([112, 112, 112, 255]),
+        "dragon_head" | "dragon_wall_head" => return Some([25, 25, 25, 255]),
+        _ => {}
+    }
+
+    let colour = id
+        .strip_suffix("_wall_banner")
+        .or_else(|| id.strip_suffix("_banner"))
+        .or_else(|| id.strip_suffix("_bed"))?;
+
+    dye_colour(colour)
+}
+
+/// Crop a region out of RGBA atlas data, returning `None` if the region does
+/// not fit within the atlas.
+pub(crate) fn crop(
+    atlas: &[u8],
+    atlas_width: usize,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> Option<Texture> {
+    if atlas_width == 0 || x + width > atlas_width {
+        return None;
+    }
+
+    let atlas_height = atlas.len() / (atlas_width * 4);
+    if atlas_height * atlas_width * 4 != atlas.len() || y + height > atlas_height {
+        return None;
+    }
+
+    let mut tex = Vec::with_capacity(width * height * 4);
+    for row in y..y + height {
+        let start = (row * atlas_width + x) * 4;
+        tex.extend_from_slice(&atlas[start..start + width * 4]);
+    }
+
+    Some(tex)
+}
+
+/// A 16x16 texture of a single colour.
+pub(crate) fn solid(colour: Rgba) -> Texture {
+    colour.repeat(16 * 16)
+}
@@ -1,45 +1,60 @@
-use serde::Deserialize;
-use std::collections::HashMap;
+use fastnbt::Map;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+mod bundle;
+mod entity;
+mod orientation;
+pub use bundle::{assets_key, BUNDLE_VERSION};
+pub use entity::*;
+
+use orientation::TopFace;
 
 #[cfg(test)]
 mod test;
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Variant {
     pub model: String,
+    /// Degrees to turn the model about the x axis, in steps of 90. Applied
+    /// before `y`.
     pub x: Option<usize>,
+    /// Degrees to turn the model clockwise when seen from above, in steps of
+    /// 90.
     pub y: Option<usize>,
+    /// Whether textures keep their orientation in the world rather than
+    /// turning with the model.
     pub uvlock: Option<bool>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum Variants {
     Single(Variant),
     Many(Vec<Variant>),
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum Blockstate {
     Variants(HashMap<String, Variants>),
     Multipart(Vec<Part>),
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Part {
     //when: Option<serde_json::Value>,
     pub apply: Variants,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Model {
     pub parent: Option<String>,
     pub textures: Option<HashMap<String, String>>,
     pub elements: Option<Vec<Element>>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Element {
     pub from: [f32; 3],
     pub to: [f32; 3],
@@ -47,7 +62,7 @@ pub struct Element {
     pub rotation: Option<Rotation>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[allow(dead_code)]
 pub struct Rotation {
     origin: Vec<f32>,
@@ -57,15 +72,18 @@ pub struct Rotation {
     rescale: bool,
 }
 
-#[derive(Deserialize, Debug, Clone)]
-#[allow(dead_code)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Face {
     texture: String,
     uv: Option<[f32; 4]>,
+    /// Clockwise rotation of the texture in degrees.
+    #[serde(default)]
+    rotation: u32,
 }
 
 pub type Texture = Vec<u8>; // RGBA 16x16 image.
 
+/// An error rendering a block or loading assets.
 #[derive(Debug)]
 pub enum Error {
     Unsupported,
@@ -76,6 +94,10 @@ pub enum Error {
     MissingTexture(String, String, String), // Missing the actual texture, ie the PNG.
     MissingElements(String, String, String),
     MissingTextureVariable(String, String, String, String), // A texture variable eg '#all' had no value assigned.
+    MissingEntityTexture(String), // None of the textures for a block entity could be found.
+    StaleBundle, // A bundle is from other assets or another version of the format, or is corrupt.
+    Io(std::io::Error), // Reading the assets directory or a bundle file failed.
+    Nbt(fastnbt::error::Error), // A bundle's assets could not be written or read as NBT.
 }
 
 fn merge_models(child: &Model, mut parent: Model) -> Result<Model> {
@@ -134,10 +156,26 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub trait Render {
     fn get_top(&mut self, id: &str, encoded_props: &str) -> Result<Texture>;
 }
+
+/// The default number of block states [`Renderer`] remembers the top texture
+/// of. See [`Renderer::with_cache_capacity`].
+pub const DEFAULT_CACHE_CAPACITY: usize = 16 * 1024;
+
 pub struct Renderer {
     blockstates: HashMap<String, Blockstate>,
     models: HashMap<String, Model>,
     textures: HashMap<String, Texture>,
+    entity_blocks: Vec<EntityBlock>,
+
+    // Models with all their parents merged in, keyed by model name. There is
+    // a finite number of models so this is unbounded.
+    flattened: Map<String, Model>,
+
+    // Resolved top textures keyed by block id then encoded properties. Nested
+    // so that lookups can be done with borrowed strings.
+    tops: Map<String, Map<String, Arc<Texture>>>,
+    tops_len: usize,
+    cache_capacity: usize,
 }
 
 impl Renderer {
@@ -150,36 +188,121 @@ impl Renderer {
             blockstates,
             models,
             textures,
+            entity_blocks: default_entity_blocks(),
+            flattened: Map::default(),
+            tops: Map::default(),
+            tops_len: 0,
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
         }
     }
 
-    fn model_get_top(&self, id: &str, encoded_props: &str, model_name: &str) -> Result<Texture> {
-        let model = self.flatten_model(model_name)?;
-        // Look at elements. Try just looking in the first one for 'up'.
+    /// Replace the mappings used for blocks rendered by block entities, eg
+    /// chests and beds. These are checked before the block models, so can
+    /// also be used to override the texture of any other block. Defaults to
+    /// [`default_entity_blocks`].
+    pub fn with_entity_blocks(mut self, entity_blocks: Vec<EntityBlock>) -> Self {
+        self.entity_blocks = entity_blocks;
+        self.tops.clear();
+        self.tops_len = 0;
+        self
+    }
 
-        let els = &model.elements.ok_or_else(|| {
-            Error::MissingElements(
-                id.to_owned(),
-                encoded_props.to_owned(),
-                model_name.to_owned(),
-            )
-        })?;
+    /// Set how many block states to remember the top texture of. Once full the
+    /// cache is cleared and starts filling again. Each entry is a 16x16 RGBA
+    /// texture, so around 1 KiB. Zero disables the cache. Defaults to
+    /// [`DEFAULT_CACHE_CAPACITY`].
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self.tops.clear();
+        self.tops_len = 0;
+        self
+    }
 
-        let el = els.get(0).ok_or_else(|| {
-            Error::MissingElements(
-                id.to_owned(),
-                encoded_props.to_owned(),
-                model_name.to_owned(),
-            )
-        })?;
+    /// The texture of the top of a block, like [`Render::get_top`], shared
+    /// with the cache rather than copied out of it. This is the cheaper of
+    /// the two when the same block states are looked up many times.
+    pub fn top(&mut self, id: &str, encoded_props: &str) -> Result<Arc<Texture>> {
+        if let Some(tex) = self.cached_top(id, encoded_props) {
+            return Ok(Arc::clone(tex));
+        }
 
-        let face = el.faces.get("up").ok_or_else(|| {
+        let tex = Arc::new(self.resolve_top(id, encoded_props)?);
+        self.cache_top(id, encoded_props, &tex);
+        Ok(tex)
+    }
+
+    fn cached_top(&self, id: &str, encoded_props: &str) -> Option<&Arc<Texture>> {
+        self.tops.get(id)?.get(encoded_props)
+    }
+
+    fn cache_top(&mut self, id: &str, encoded_props: &str, tex: &Arc<Texture>) {
+        if self.cache_capacity == 0 {
+            return;
+        }
+
+        if self.tops_len >= self.cache_capacity {
+            self.tops.clear();
+            self.tops_len = 0;
+        }
+
+        self.tops
+            .entry(id.to_owned())
+            .or_default()
+            .insert(encoded_props.to_owned(), Arc::clone(tex));
+        self.tops_len += 1;
+    }
+
+    fn entity_get_top(&self, id: &str) -> Option<Result<Texture>> {
+        let (block, matched) = self
+            .entity_blocks
+            .iter()
+            .find_map(|block| Some((block, block.matches(id)?)))?;
+
+        let tex = block.textures.iter().find_map(|tex| match tex {
+            EntityTexture::Texture(name) => self.extract_texture(&name.replace('*', matched)).ok(),
+            EntityTexture::Atlas {
+                texture,
+                atlas_width,
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let atlas = self.textures.get(&texture.replace('*', matched))?;
+                entity::crop(atlas, *atlas_width, *x, *y, *width, *height)
+            }
+            EntityTexture::Colour(colour) => Some(entity::solid(*colour)),
+            EntityTexture::MapColour => map_colour(id).map(entity::solid),
+        });
+
+        Some(tex.ok_or_else(|| Error::MissingEntityTexture(id.to_owned())))
+    }
+
+    fn model_get_top(
+        &mut self,
+        id: &str,
+        encoded_props: &str,
+        variant: &Variant,
+    ) -> Result<Texture> {
+        let model_name = &variant.model;
+        if !self.flattened.contains_key(model_name) {
+            let model = self.flatten_model(model_name)?;
+            self.flattened.insert(model_name.to_owned(), model);
+        }
+        let model = &self.flattened[model_name];
+        let missing_elements = || {
             Error::MissingElements(
                 id.to_owned(),
                 encoded_props.to_owned(),
                 model_name.to_owned(),
             )
-        })?;
+        };
+
+        // Use the first element with a face that points up once the
+        // elements and the variant are rotated.
+        let els = model.elements.as_ref().ok_or_else(missing_elements)?;
+        let top = TopFace::find(els, variant).ok_or_else(missing_elements)?;
+        let face = top.face;
 
         let tex = &face.texture;
 
@@ -187,6 +310,7 @@ impl Renderer {
             Some(rest) => {
                 model
                     .textures
+                    .as_ref()
                     .ok_or(Error::MissingModelTextures)?
                     .get(rest) // we just checked with 'starts_with'.
                     .ok_or_else(|| {
@@ -202,7 +326,43 @@ impl Renderer {
             None => (*tex).clone(),
         };
 
-        self.extract_texture(&tex)
+        Ok(top.render(&self.extract_texture(&tex)?))
+    }
+
+    fn resolve_top(&mut self, id: &str, encoded_props: &str) -> Result<Texture> {
+        // Blocks like chests and beds have no useful block model, their look
+        // comes from the block entity instead.
+        if let Some(tex) = self.entity_get_top(id) {
+            return tex;
+        }
+
+        let bs = self
+            .blockstates
+            .get(id)
+            .ok_or_else(|| Error::MissingBlockstate(id.to_string()))?;
+
+        let variant = match bs {
+            // Block is made up variants based on its properties.
+            Blockstate::Variants(variants) => {
+                // Get the variant or variants that correspond to this exact block.
+                let v = variants.get(encoded_props).ok_or_else(|| {
+                    Error::MissingVariant(id.to_string(), encoded_props.to_string())
+                })?;
+
+                match v {
+                    Variants::Single(variant) => variant.clone(),
+                    Variants::Many(variants) => {
+                        // TODO: Should probably actually pick one at random or
+                        // something. How does the game actually decide which
+                        // variant to use?
+                        variants[0].clone()
+                    }
+                }
+            }
+            Blockstate::Multipart(_) => return Err(Error::Unsupported),
+        };
+
+        self.model_get_top(id, encoded_props, &variant)
     }
 
     fn get_model(&self, model: &str) -> Result<&Model> {
@@ -243,34 +403,7 @@ impl Renderer {
 impl Render for Renderer {
     // TODO: Make a trait.
     fn get_top(&mut self, id: &str, encoded_props: &str) -> Result<Texture> {
-        let bs = self
-            .blockstates
-            .get(id)
-            .ok_or_else(|| Error::MissingBlockstate(id.to_string()))?;
-
-        match bs {
-            // Block is made up variants based on its properties.
-            Blockstate::Variants(variants) => {
-                // Get the variant or variants that correspond to this exact block.
-                let v = variants.get(encoded_props).ok_or_else(|| {
-                    Error::MissingVariant(id.to_string(), encoded_props.to_string())
-                })?;
-
-                match v {
-                    Variants::Single(variant) => {
-                        let model_name = &variant.model;
-                        self.model_get_top(id, encoded_props, model_name)
-                    }
-                    Variants::Many(variants) => {
-                        // TODO: Should probably actually pick one at random or
-                        // something. How does the game actually decide which
-                        // variant to use?
-                        let model_name = &variants[0].model;
-                        self.model_get_top(id, encoded_props, model_name)
-                    }
-                }
-            }
-            Blockstate::Multipart(_) => Err(Error::Unsupported),
-        }
+        self.top(id, encoded_props)
+            .map(|tex| Arc::try_unwrap(tex).unwrap_or_else(|tex| (*tex).clone()))
     }
 }
@@ -0,0 +1,355 @@
+use super::{Element, Face, Rotation, Texture, Variant};
+
+/// The centre of a block, which variants are rotated about.
+const CENTRE: [f32; 3] = [8.0, 8.0, 8.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// A right handed rotation by a number of quarter turns about an axis through
+/// `origin`. Quarter turns keep directions along the axes, so faces stay
+/// faces.
+#[derive(Debug, Clone, Copy)]
+struct QuarterTurn {
+    axis: Axis,
+    turns: i32,
+    origin: [f32; 3],
+}
+
+impl QuarterTurn {
+    fn inverse(self) -> Self {
+        Self {
+            turns: -self.turns,
+            ..self
+        }
+    }
+
+    fn rotate_vector(self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        let (sin, cos) = match self.turns.rem_euclid(4) {
+            0 => (0.0, 1.0),
+            1 => (1.0, 0.0),
+            2 => (0.0, -1.0),
+            _ => (-1.0, 0.0),
+        };
+
+        match self.axis {
+            Axis::X => [x, y * cos - z * sin, y * sin + z * cos],
+            Axis::Y => [x * cos + z * sin, y, -x * sin + z * cos],
+            Axis::Z => [x * cos - y * sin, x * sin + y * cos, z],
+        }
+    }
+
+    fn rotate_point(self, p: [f32; 3]) -> [f32; 3] {
+        let o = self.origin;
+        let [x, y, z] = self.rotate_vector([p[0] - o[0], p[1] - o[1], p[2] - o[2]]);
+        [x + o[0], y + o[1], z + o[2]]
+    }
+
+    fn rotate_direction(self, dir: Direction) -> Direction {
+        Direction::from_vector(self.rotate_vector(dir.vector()))
+    }
+
+    /// The rotation of an element, if it is a whole number of quarter turns.
+    /// Other angles, used for things like the crossed planes of plants, are
+    /// ignored as they do not turn one face into another.
+    fn of_element(rotation: &Rotation) -> Option<Self> {
+        let axis = match rotation.axis.as_str() {
+            "x" => Axis::X,
+            "y" => Axis::Y,
+            "z" => Axis::Z,
+            _ => return None,
+        };
+        let origin = match rotation.origin[..] {
+            [x, y, z] => [x, y, z],
+            _ => return None,
+        };
+
+        let turns = rotation.angle / 90.0;
+        (turns != 0.0 && turns.fract() == 0.0).then_some(Self {
+            axis,
+            turns: turns as i32,
+            origin,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "down" => Direction::Down,
+            "up" => Direction::Up,
+            "north" => Direction::North,
+            "south" => Direction::South,
+            "west" => Direction::West,
+            "east" => Direction::East,
+            _ => return None,
+        })
+    }
+
+    fn vector(self) -> [f32; 3] {
+        match self {
+            Direction::Down => [0.0, -1.0, 0.0],
+            Direction::Up => [0.0, 1.0, 0.0],
+            Direction::North => [0.0, 0.0, -1.0],
+            Direction::South => [0.0, 0.0, 1.0],
+            Direction::West => [-1.0, 0.0, 0.0],
+            Direction::East => [1.0, 0.0, 0.0],
+        }
+    }
+
+    /// The direction a rotated vector is closest to. Quarter turns give
+    /// exact vectors, so this is only for the sake of rounding.
+    fn from_vector([x, y, z]: [f32; 3]) -> Self {
+        if y.abs() >= x.abs() && y.abs() >= z.abs() {
+            if y < 0.0 {
+                Direction::Down
+            } else {
+                Direction::Up
+            }
+        } else if z.abs() >= x.abs() {
+            if z < 0.0 {
+                Direction::North
+            } else {
+                Direction::South
+            }
+        } else if x < 0.0 {
+            Direction::West
+        } else {
+            Direction::East
+        }
+    }
+
+    /// The UV the game gives a point on a face in this direction when the
+    /// face does not give one. Both are 0 to 16 over the block.
+    fn auto_uv(self, [x, y, z]: [f32; 3]) -> [f32; 2] {
+        match self {
+            Direction::Down => [x, 16.0 - z],
+            Direction::Up => [x, z],
+            Direction::North => [16.0 - x, 16.0 - y],
+            Direction::South => [x, 16.0 - y],
+            Direction::West => [z, 16.0 - y],
+            Direction::East => [16.0 - z, 16.0 - y],
+        }
+    }
+
+    /// A point on a face in this direction with the given automatic UV, the
+    /// reverse of [`auto_uv`][Self::auto_uv]. The point is on the side of the
+    /// block the face looks out of.
+    fn point_for_uv(self, [u, v]: [f32; 2]) -> [f32; 3] {
+        match self {
+            Direction::Down => [u, 0.0, 16.0 - v],
+            Direction::Up => [u, 16.0, v],
+            Direction::North => [16.0 - u, 16.0 - v, 0.0],
+            Direction::South => [u, 16.0 - v, 16.0],
+            Direction::West => [0.0, 16.0 - v, u],
+            Direction::East => [16.0, 16.0 - v, 16.0 - u],
+        }
+    }
+}
+
+/// The face of a model that is seen from above once the model's elements and
+/// the blockstate variant are rotated, and how to map the view from above
+/// onto its texture.
+#[derive(Debug)]
+pub(super) struct TopFace<'a> {
+    element: &'a Element,
+    pub(super) face: &'a Face,
+    /// The direction of the face before any rotation.
+    direction: Direction,
+    element_turn: Option<QuarterTurn>,
+    /// The variant's rotation, about x then about y.
+    variant_turns: [QuarterTurn; 2],
+    uvlock: bool,
+}
+
+impl<'a> TopFace<'a> {
+    /// Find the face of the first element that ends up facing up.
+    ///
+    /// Variants are rotated about x and then about y, clockwise when looking
+    /// along the axis towards the origin, so `y: 90` turns the north face to
+    /// the east. Elements are rotated about their own origin in the other
+    /// direction, as the game does.
+    pub(super) fn find(elements: &'a [Element], variant: &Variant) -> Option<Self> {
+        let quarter = |degrees: Option<usize>| -((degrees.unwrap_or(0) / 90) as i32);
+        let variant_turns = [
+            QuarterTurn {
+                axis: Axis::X,
+                turns: quarter(variant.x),
+                origin: CENTRE,
+            },
+            QuarterTurn {
+                axis: Axis::Y,
+                turns: quarter(variant.y),
+                origin: CENTRE,
+            },
+        ];
+
+        elements.iter().find_map(|element| {
+            let element_turn = element.rotation.as_ref().and_then(QuarterTurn::of_element);
+
+            element.faces.iter().find_map(|(name, face)| {
+                let direction = Direction::from_name(name)?;
+                let top = TopFace {
+                    element,
+                    face,
+                    direction,
+                    element_turn,
+                    variant_turns,
+                    uvlock: variant.uvlock.unwrap_or(false),
+                };
+                (top.to_world_direction(direction) == Direction::Up).then_some(top)
+            })
+        })
+    }
+
+    fn to_model(&self, p: [f32; 3]) -> [f32; 3] {
+        match self.element_turn {
+            Some(turn) => turn.rotate_point(p),
+            None => p,
+        }
+    }
+
+    fn to_world(&self, p: [f32; 3]) -> [f32; 3] {
+        let [x, y] = self.variant_turns;
+        y.rotate_point(x.rotate_point(p))
+    }
+
+    fn to_world_direction(&self, dir: Direction) -> Direction {
+        let dir = match self.element_turn {
+            Some(turn) => turn.rotate_direction(dir),
+            None => dir,
+        };
+        let [x, y] = self.variant_turns;
+        y.rotate_direction(x.rotate_direction(dir))
+    }
+
+    fn world_to_element(&self, p: [f32; 3]) -> [f32; 3] {
+        let [x, y] = self.variant_turns;
+        let p = x.inverse().rotate_point(y.inverse().rotate_point(p));
+        match self.element_turn {
+            Some(turn) => turn.inverse().rotate_point(p),
+            None => p,
+        }
+    }
+
+    /// The texture of the face as seen from above, with x to the right and z
+    /// downwards, stretched over the whole texture if the face does not cover
+    /// the whole block.
+    ///
+    /// Only square textures can be turned. Others, such as the strips of
+    /// animated textures, are returned as they are.
+    pub(super) fn render(&self, tex: &Texture) -> Texture {
+        let Some(side) = square_side(tex) else {
+            return tex.clone();
+        };
+
+        let from = self.element.from;
+        let to = self.element.to;
+
+        // Where the face is when seen from above.
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for corner in 0..8 {
+            let p = [
+                if corner & 1 == 0 { from[0] } else { to[0] },
+                if corner & 2 == 0 { from[1] } else { to[1] },
+                if corner & 4 == 0 { from[2] } else { to[2] },
+            ];
+            let p = self.to_world(self.to_model(p));
+            for i in 0..3 {
+                min[i] = min[i].min(p[i]);
+                max[i] = max[i].max(p[i]);
+            }
+        }
+
+        // The range of the face's automatic UV, which is also its UV if it
+        // does not give one.
+        let [u0, v0] = self.direction.auto_uv(from);
+        let [u1, v1] = self.direction.auto_uv(to);
+        let auto = [u0.min(u1), v0.min(v1), u0.max(u1), v0.max(v1)];
+        let uv = self.face.uv.unwrap_or(auto);
+
+        let mut out = Vec::with_capacity(tex.len());
+        for row in 0..side {
+            for col in 0..side {
+                let s = (col as f32 + 0.5) / side as f32;
+                let t = (row as f32 + 0.5) / side as f32;
+                let world = [
+                    min[0] + s * (max[0] - min[0]),
+                    max[1],
+                    min[2] + t * (max[2] - min[2]),
+                ];
+
+                let [au, av] = self.direction.auto_uv(self.world_to_element(world));
+                let a = fraction(au, auto[0], auto[2]);
+                let b = fraction(av, auto[1], auto[3]);
+                let [a, b] = rotate_face_uv([a, b], self.face.rotation);
+                let mut point = [uv[0] + a * (uv[2] - uv[0]), uv[1] + b * (uv[3] - uv[1])];
+
+                // With uvlock the texture keeps its orientation in the world
+                // rather than turning with the block.
+                if self.uvlock {
+                    let model_direction = match self.element_turn {
+                        Some(turn) => turn.rotate_direction(self.direction),
+                        None => self.direction,
+                    };
+                    let p = self.to_world(model_direction.point_for_uv(point));
+                    point = Direction::Up.auto_uv(p);
+                }
+
+                let x = pixel(point[0], side);
+                let y = pixel(point[1], side);
+                let i = (y * side + x) * 4;
+                out.extend_from_slice(&tex[i..i + 4]);
+            }
+        }
+
+        out
+    }
+}
+
+/// The width of a square RGBA texture.
+fn square_side(tex: &Texture) -> Option<usize> {
+    let pixels = tex.len() / 4;
+    let side = (pixels as f64).sqrt().round() as usize;
+    (side > 0 && side * side * 4 == tex.len()).then_some(side)
+}
+
+/// How far `value` is from `start` to `end`, from 0 to 1.
+fn fraction(value: f32, start: f32, end: f32) -> f32 {
+    if end > start {
+        (value - start) / (end - start)
+    } else {
+        0.0
+    }
+}
+
+/// Turn a position on a face by the face's rotation. Faces are rotated
+/// clockwise in steps of 90 degrees.
+fn rotate_face_uv([a, b]: [f32; 2], degrees: u32) -> [f32; 2] {
+    match (degrees / 90) % 4 {
+        0 => [a, b],
+        1 => [b, 1.0 - a],
+        2 => [1.0 - a, 1.0 - b],
+        _ => [1.0 - b, a],
+    }
+}
+
+/// The pixel of a texture `side` pixels wide at a UV coordinate from 0 to 16.
+fn pixel(coord: f32, side: usize) -> usize {
+    ((coord / 16.0 * side as f32).floor().max(0.0) as usize).min(side - 1)
+}
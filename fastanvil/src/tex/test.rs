@@ -1,4 +1,5 @@
 use super::*;
+use crate::test::TempPath;
 use serde_json;
 
 fn cube_model() -> Model {
@@ -299,3 +300,522 @@ fn stairs() {
 
     assert_eq!(tex, acacia_planks_texture());
 }
+
+#[test]
+fn top_textures_are_cached() {
+    let mut renderer = acacia_stairs_renderer();
+    let straight = "facing=east,half=top,shape=straight";
+    let north = "facing=north,half=top,shape=straight";
+
+    let first = renderer
+        .get_top("minecraft:acacia_stairs", straight)
+        .unwrap();
+    let again = renderer
+        .get_top("minecraft:acacia_stairs", straight)
+        .unwrap();
+    assert_eq!(first, again);
+    assert_eq!(renderer.tops_len, 1);
+
+    // Hits share the cached texture rather than copying it.
+    let shared = renderer.top("minecraft:acacia_stairs", straight).unwrap();
+    assert!(std::sync::Arc::ptr_eq(
+        &shared,
+        &renderer.top("minecraft:acacia_stairs", straight).unwrap()
+    ));
+    assert_eq!(*shared, first);
+
+    renderer.get_top("minecraft:acacia_stairs", north).unwrap();
+    assert_eq!(renderer.tops_len, 2);
+    assert!(renderer
+        .cached_top("minecraft:acacia_stairs", north)
+        .is_some());
+
+    // Both states share a model, which is only flattened once.
+    assert_eq!(renderer.flattened.len(), 1);
+}
+
+#[test]
+fn top_cache_is_cleared_when_full() {
+    let mut renderer = acacia_stairs_renderer().with_cache_capacity(1);
+    let straight = "facing=east,half=top,shape=straight";
+    let north = "facing=north,half=top,shape=straight";
+
+    renderer
+        .get_top("minecraft:acacia_stairs", straight)
+        .unwrap();
+    renderer.get_top("minecraft:acacia_stairs", north).unwrap();
+
+    assert_eq!(renderer.tops_len, 1);
+    assert!(renderer
+        .cached_top("minecraft:acacia_stairs", straight)
+        .is_none());
+    assert!(renderer
+        .cached_top("minecraft:acacia_stairs", north)
+        .is_some());
+}
+
+#[test]
+fn top_cache_can_be_disabled() {
+    let mut renderer = cobblestone_renderer().with_cache_capacity(0);
+
+    let tex = renderer.get_top("minecraft:cobblestone", "").unwrap();
+    assert_eq!(tex, cobblestone_texture());
+    assert_eq!(renderer.tops_len, 0);
+    assert!(renderer.tops.is_empty());
+}
+
+#[test]
+fn errors_are_not_cached() {
+    let mut renderer = cobblestone_renderer();
+
+    assert!(renderer.get_top("minecraft:cobblestone", "nope").is_err());
+    assert!(renderer.get_top("minecraft:cobblestone", "nope").is_err());
+    assert_eq!(renderer.tops_len, 0);
+}
+
+fn chest_blockstate() -> Blockstate {
+    serde_json::from_str(
+        r##"
+        {
+            "variants": {
+                "facing=north,type=single,waterlogged=false": {
+                    "model": "minecraft:block/chest"
+                }
+            }
+        }
+        "##,
+    )
+    .unwrap()
+}
+
+fn chest_model() -> Model {
+    serde_json::from_str(
+        r##"
+        {
+            "textures": {
+                "particle": "block/oak_planks"
+            }
+        }
+        "##,
+    )
+    .unwrap()
+}
+
+/// A 64x64 atlas where every pixel's red channel is its x coordinate and
+/// green channel its y coordinate.
+fn coordinate_atlas() -> Texture {
+    let mut atlas = vec![];
+    for y in 0..64 {
+        for x in 0..64 {
+            atlas.extend_from_slice(&[x, y, 0, 255]);
+        }
+    }
+    atlas
+}
+
+fn entity_renderer(textures: Vec<(&str, Texture)>) -> Renderer {
+    let blockstates = vec![("minecraft:chest".to_owned(), chest_blockstate())]
+        .into_iter()
+        .collect();
+
+    let models = vec![("minecraft:block/chest".to_owned(), chest_model())]
+        .into_iter()
+        .collect();
+
+    let textures = textures
+        .into_iter()
+        .map(|(name, tex)| (name.to_owned(), tex))
+        .collect();
+
+    Renderer::new(blockstates, models, textures)
+}
+
+#[test]
+fn chest_uses_lid_from_entity_atlas() {
+    let mut renderer = entity_renderer(vec![("minecraft:entity/chest/normal", coordinate_atlas())]);
+
+    let tex = renderer
+        .get_top(
+            "minecraft:chest",
+            "facing=north,type=single,waterlogged=false",
+        )
+        .unwrap();
+
+    assert_eq!(tex.len(), 14 * 14 * 4);
+    assert_eq!(&tex[..4], &[14, 0, 0, 255]);
+    assert_eq!(&tex[tex.len() - 4..], &[27, 13, 0, 255]);
+}
+
+#[test]
+fn bed_colour_comes_from_block_name() {
+    let mut renderer = entity_renderer(vec![
+        ("minecraft:entity/bed/red", coordinate_atlas()),
+        ("minecraft:block/blue_wool", cobblestone_texture()),
+    ]);
+
+    let red = renderer.get_top("minecraft:red_bed", "part=head").unwrap();
+    assert_eq!(red.len(), 16 * 16 * 4);
+    assert_eq!(&red[..4], &[6, 6, 0, 255]);
+
+    // No bed atlas for blue, so falls back to the wool.
+    let blue = renderer.get_top("minecraft:blue_bed", "part=head").unwrap();
+    assert_eq!(blue, cobblestone_texture());
+}
+
+#[test]
+fn entity_blocks_fall_back_to_map_colour() {
+    let mut renderer = entity_renderer(vec![]);
+
+    let chest = renderer.get_top("minecraft:chest", "").unwrap();
+    assert_eq!(chest, [143, 119, 72, 255].repeat(16 * 16));
+
+    let banner = renderer
+        .get_top("minecraft:light_blue_wall_banner", "facing=east")
+        .unwrap();
+    assert_eq!(banner, [102, 153, 216, 255].repeat(16 * 16));
+}
+
+#[test]
+fn entity_blocks_without_texture_or_map_colour_error() {
+    let mut renderer = entity_renderer(vec![]);
+
+    assert!(matches!(
+        renderer.get_top("minecraft:zombie_wall_head", "facing=east"),
+        Err(Error::MissingEntityTexture(_))
+    ));
+}
+
+#[test]
+fn entity_blocks_can_be_overridden() {
+    let mut renderer = entity_renderer(vec![("minecraft:block/oak_planks", cobblestone_texture())])
+        .with_entity_blocks(vec![
+            EntityBlock::new(
+                "minecraft:chest",
+                vec![EntityTexture::Texture(
+                    "minecraft:block/oak_planks".to_owned(),
+                )],
+            ),
+            EntityBlock::new("minecraft:*_bed", vec![EntityTexture::Colour([1, 2, 3, 4])]),
+        ]);
+
+    assert_eq!(
+        renderer.get_top("minecraft:chest", "").unwrap(),
+        cobblestone_texture()
+    );
+    assert_eq!(
+        renderer.get_top("minecraft:red_bed", "").unwrap(),
+        [1, 2, 3, 4].repeat(16 * 16)
+    );
+    assert!(renderer.get_top("minecraft:red_banner", "").is_err());
+}
+
+/// A 16x16 texture where every pixel's red channel is its x coordinate and
+/// green channel its y coordinate.
+fn coordinate_texture() -> Texture {
+    let mut tex = vec![];
+    for y in 0..16 {
+        for x in 0..16 {
+            tex.extend_from_slice(&[x, y, 0, 255]);
+        }
+    }
+    tex
+}
+
+fn solid(colour: u8) -> Texture {
+    [colour, colour, colour, 255].repeat(16 * 16)
+}
+
+/// The pixel at x, y of a 16x16 texture.
+fn pixel(tex: &Texture, x: usize, y: usize) -> &[u8] {
+    let i = (y * 16 + x) * 4;
+    &tex[i..i + 4]
+}
+
+/// A renderer for `minecraft:test`, with the given variants and a model
+/// `minecraft:block/test` with a single element. Each face of the element
+/// has a texture of its own, eg `#north` is `minecraft:block/north`.
+fn rotation_renderer(variants: &str, element: &str) -> Renderer {
+    let blockstate = serde_json::from_str(&format!(r#"{{"variants": {variants}}}"#)).unwrap();
+    let model = serde_json::from_str(&format!(
+        r##"{{
+            "textures": {{
+                "up": "minecraft:block/up",
+                "down": "minecraft:block/down",
+                "north": "minecraft:block/north",
+                "south": "minecraft:block/south",
+                "west": "minecraft:block/west",
+                "east": "minecraft:block/east"
+            }},
+            "elements": [{element}]
+        }}"##
+    ))
+    .unwrap();
+
+    let blockstates = vec![("minecraft:test".to_owned(), blockstate)]
+        .into_iter()
+        .collect();
+    let models = vec![("minecraft:block/test".to_owned(), model)]
+        .into_iter()
+        .collect();
+    let textures = vec![
+        ("minecraft:block/up".to_owned(), coordinate_texture()),
+        ("minecraft:block/down".to_owned(), solid(1)),
+        ("minecraft:block/north".to_owned(), solid(2)),
+        ("minecraft:block/south".to_owned(), solid(3)),
+        ("minecraft:block/west".to_owned(), solid(4)),
+        ("minecraft:block/east".to_owned(), solid(5)),
+    ]
+    .into_iter()
+    .collect();
+
+    Renderer::new(blockstates, models, textures)
+}
+
+const CUBE: &str = r##"{
+    "from": [0, 0, 0],
+    "to": [16, 16, 16],
+    "faces": {
+        "down": {"texture": "#down"},
+        "up": {"texture": "#up"},
+        "north": {"texture": "#north"},
+        "south": {"texture": "#south"},
+        "west": {"texture": "#west"},
+        "east": {"texture": "#east"}
+    }
+}"##;
+
+#[test]
+fn variant_x_rotation_puts_another_face_on_top() {
+    let mut renderer = rotation_renderer(
+        r#"{
+            "r=0": {"model": "minecraft:block/test"},
+            "r=90": {"model": "minecraft:block/test", "x": 90},
+            "r=180": {"model": "minecraft:block/test", "x": 180},
+            "r=270": {"model": "minecraft:block/test", "x": 270},
+            "r=270_90": {"model": "minecraft:block/test", "x": 270, "y": 90}
+        }"#,
+        CUBE,
+    );
+    let mut top = |props| renderer.get_top("minecraft:test", props).unwrap();
+
+    assert_eq!(top("r=0"), coordinate_texture());
+    assert_eq!(top("r=90"), solid(3));
+    assert_eq!(top("r=180"), solid(1));
+    assert_eq!(top("r=270"), solid(2));
+    // Turning about y after x does not change what is on top.
+    assert_eq!(top("r=270_90"), solid(2));
+}
+
+#[test]
+fn variant_y_rotation_turns_the_top_texture() {
+    let mut renderer = rotation_renderer(
+        r#"{
+            "y=90": {"model": "minecraft:block/test", "y": 90},
+            "y=180": {"model": "minecraft:block/test", "y": 180},
+            "y=90,uvlock": {"model": "minecraft:block/test", "y": 90, "uvlock": true}
+        }"#,
+        CUBE,
+    );
+    let mut top = |props| renderer.get_top("minecraft:test", props).unwrap();
+
+    // Turned clockwise, so the bottom left of the texture is now top left.
+    let tex = top("y=90");
+    assert_eq!(pixel(&tex, 0, 0), [0, 15, 0, 255]);
+    assert_eq!(pixel(&tex, 15, 0), [0, 0, 0, 255]);
+    assert_eq!(pixel(&tex, 3, 5), [5, 12, 0, 255]);
+
+    let tex = top("y=180");
+    assert_eq!(pixel(&tex, 0, 0), [15, 15, 0, 255]);
+
+    // The texture stays in place with uvlock.
+    assert_eq!(top("y=90,uvlock"), coordinate_texture());
+}
+
+#[test]
+fn element_rotation_by_quarter_turns() {
+    let rotated = CUBE.replace(
+        r#""from""#,
+        r#""rotation": {"origin": [8, 8, 8], "axis": "x", "angle": 90}, "from""#,
+    );
+    let mut renderer = rotation_renderer(r#"{"": {"model": "minecraft:block/test"}}"#, &rotated);
+    assert_eq!(renderer.get_top("minecraft:test", "").unwrap(), solid(2));
+
+    // Rotations that are not quarter turns are ignored.
+    let rotated = CUBE.replace(
+        r#""from""#,
+        r#""rotation": {"origin": [8, 8, 8], "axis": "y", "angle": 45}, "from""#,
+    );
+    let mut renderer = rotation_renderer(r#"{"": {"model": "minecraft:block/test"}}"#, &rotated);
+    assert_eq!(
+        renderer.get_top("minecraft:test", "").unwrap(),
+        coordinate_texture()
+    );
+}
+
+#[test]
+fn face_rotation_and_uv() {
+    let element = r##"{
+        "from": [0, 0, 0],
+        "to": [16, 16, 16],
+        "faces": {"up": {"texture": "#up", "rotation": 90}}
+    }"##;
+    let mut renderer = rotation_renderer(r#"{"": {"model": "minecraft:block/test"}}"#, element);
+    let tex = renderer.get_top("minecraft:test", "").unwrap();
+    assert_eq!(pixel(&tex, 0, 0), [0, 15, 0, 255]);
+
+    // Only the top left of the texture, stretched over the face.
+    let element = r##"{
+        "from": [0, 0, 0],
+        "to": [16, 16, 16],
+        "faces": {"up": {"texture": "#up", "uv": [0, 0, 8, 8]}}
+    }"##;
+    let mut renderer = rotation_renderer(r#"{"": {"model": "minecraft:block/test"}}"#, element);
+    let tex = renderer.get_top("minecraft:test", "").unwrap();
+    assert_eq!(pixel(&tex, 0, 0), [0, 0, 0, 255]);
+    assert_eq!(pixel(&tex, 15, 15), [7, 7, 0, 255]);
+}
+
+#[test]
+fn stairs_keep_top_texture_in_place_with_uvlock() {
+    let mut renderer = acacia_stairs_renderer();
+    renderer.textures.insert(
+        "minecraft:block/acacia_planks".to_owned(),
+        coordinate_texture(),
+    );
+
+    for props in [
+        "facing=east,half=bottom,shape=straight",
+        "facing=north,half=bottom,shape=straight",
+        "facing=south,half=bottom,shape=straight",
+        "facing=west,half=bottom,shape=straight",
+        "facing=east,half=top,shape=straight",
+        "facing=west,half=top,shape=straight",
+    ] {
+        let tex = renderer.get_top("minecraft:acacia_stairs", props).unwrap();
+        assert_eq!(tex, coordinate_texture(), "{props}");
+    }
+}
+
+/// The key bundles are saved with, as if for some assets.
+const KEY: u64 = 0x1234;
+
+fn stairs_states() -> Vec<String> {
+    match acacia_stairs_blockstate() {
+        Blockstate::Variants(variants) => variants.into_keys().collect(),
+        Blockstate::Multipart(_) => unreachable!(),
+    }
+}
+
+#[test]
+fn bundle_round_trip() {
+    let path = TempPath::new("round-trip");
+    let mut renderer = acacia_stairs_renderer();
+    renderer.textures.insert(
+        "minecraft:block/acacia_planks".to_owned(),
+        coordinate_texture(),
+    );
+    renderer.save_bundle(&path.0, KEY).unwrap();
+
+    let mut loaded = Renderer::load_bundle(&path.0, KEY).unwrap();
+
+    // Some states fail as not every stairs model is here, and should fail the
+    // same way from the bundle.
+    let mut rendered = 0;
+    for props in stairs_states() {
+        let expected = renderer.get_top("minecraft:acacia_stairs", &props);
+        let actual = loaded.get_top("minecraft:acacia_stairs", &props);
+        assert_eq!(format!("{actual:?}"), format!("{expected:?}"), "{props}");
+        rendered += expected.is_ok() as usize;
+    }
+    assert!(rendered > 0);
+}
+
+#[test]
+fn bundle_has_models_already_flattened() {
+    let path = TempPath::new("flattened");
+    cobblestone_renderer().save_bundle(&path.0, KEY).unwrap();
+
+    let loaded = Renderer::load_bundle(&path.0, KEY).unwrap();
+
+    // Only the model the blockstate uses is kept, with its parents merged in.
+    assert_eq!(loaded.models.len(), 1);
+    let model = &loaded.models["minecraft:block/cobblestone"];
+    assert!(model.parent.is_none());
+    assert_eq!(
+        model.textures.as_ref().unwrap()["up"],
+        "minecraft:block/cobblestone"
+    );
+    assert_eq!(loaded.textures, cobblestone_renderer().textures);
+}
+
+#[test]
+fn changed_bundles_are_stale() {
+    let path = TempPath::new("stale");
+    cobblestone_renderer().save_bundle(&path.0, KEY).unwrap();
+    let data = std::fs::read(&path.0).unwrap();
+
+    let mut other_version = data.clone();
+    other_version[7] += 1;
+
+    let mut corrupt = data.clone();
+    let last = corrupt.len() - 2;
+    corrupt[last] ^= 0xFF;
+
+    for bad in [
+        other_version,
+        corrupt,
+        data[..data.len() - 1].to_vec(),
+        data[..10].to_vec(),
+        b"not a bundle at all".to_vec(),
+    ] {
+        std::fs::write(&path.0, bad).unwrap();
+        assert!(matches!(
+            Renderer::load_bundle(&path.0, KEY),
+            Err(Error::StaleBundle)
+        ));
+    }
+}
+
+#[test]
+fn bundles_of_other_assets_are_stale() {
+    let path = TempPath::new("other-assets");
+    cobblestone_renderer().save_bundle(&path.0, KEY).unwrap();
+
+    assert!(Renderer::load_bundle(&path.0, KEY).is_ok());
+    assert!(matches!(
+        Renderer::load_bundle(&path.0, KEY + 1),
+        Err(Error::StaleBundle)
+    ));
+}
+
+#[test]
+fn assets_key_changes_with_the_files() {
+    let path = TempPath::dir("assets-key");
+    let dir = &path.0;
+    let models = dir.join("models");
+    std::fs::create_dir_all(&models).unwrap();
+    std::fs::write(models.join("a.json"), "{}").unwrap();
+    std::fs::write(dir.join("b.png"), [1, 2, 3]).unwrap();
+
+    let key = assets_key(dir).unwrap();
+    assert_eq!(assets_key(dir).unwrap(), key);
+
+    std::fs::write(models.join("a.json"), "{\"parent\": \"x\"}").unwrap();
+    let changed = assets_key(dir).unwrap();
+    assert_ne!(changed, key);
+
+    std::fs::write(models.join("c.json"), "{}").unwrap();
+    assert_ne!(assets_key(dir).unwrap(), changed);
+
+    std::fs::remove_dir_all(dir).unwrap();
+    assert!(matches!(assets_key(dir), Err(Error::Io(_))));
+}
+
+#[test]
+fn missing_bundle_is_io_error() {
+    let path = TempPath::new("missing");
+    assert!(matches!(
+        Renderer::load_bundle(&path.0, KEY),
+        Err(Error::Io(_))
+    ));
+}
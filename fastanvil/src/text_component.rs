@@ -0,0 +1,264 @@
+//! Minecraft's JSON text components, as stored in strings in NBT, eg the text
+//! of signs and the pages of written books. Requires the `serde_json` feature.
+//!
+//! A component can be a plain string, an array of components, or an object
+//! with some content, formatting and `extra` components following it. Most of
+//! the time only the text is wanted, which [`to_plain`] gives.
+//!
+//! ```
+//! use fastanvil::text_component::to_plain;
+//!
+//! let line = r#"{"text":"Hello, ","extra":[{"text":"world","bold":true},"!"]}"#;
+//! assert_eq!(to_plain(line), "Hello, world!");
+//! ```
+
+use serde::Deserialize;
+
+/// Flatten the JSON text component in `json` into the plain text it displays,
+/// dropping any formatting. Strings that are not JSON, as some very old signs
+/// have, are returned as they are.
+pub fn to_plain(json: &str) -> String {
+    match TextComponent::parse(json) {
+        Ok(component) => component.to_plain(),
+        Err(_) => json.to_owned(),
+    }
+}
+
+/// A text component. Every form of component, ie strings, arrays and objects,
+/// deserializes to this.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(from = "Repr")]
+pub struct TextComponent {
+    pub content: Content,
+    pub style: Style,
+    /// Components displayed after this one, inheriting its style.
+    pub extra: Vec<TextComponent>,
+}
+
+/// What a [`TextComponent`] displays, before its `extra` components.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Content {
+    Text(String),
+    /// Text from the game's translations, with `%s` in it replaced by the
+    /// components in `with`. The fallback is used if the key is unknown.
+    Translate {
+        key: String,
+        fallback: Option<String>,
+        with: Vec<TextComponent>,
+    },
+    /// The key bound to a control, eg `key.jump`.
+    Keybind(String),
+    /// A scoreboard score. The value is filled in by the server.
+    Score {
+        name: String,
+        objective: String,
+        value: Option<String>,
+    },
+    /// An entity selector, eg `@p`, resolved by the server.
+    Selector(String),
+    /// NBT at a path in a block, entity or storage, resolved by the server.
+    Nbt(String),
+}
+
+impl Default for Content {
+    fn default() -> Self {
+        Content::Text(String::new())
+    }
+}
+
+/// The formatting of a [`TextComponent`]. Unset values are inherited from the
+/// parent component.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+pub struct Style {
+    pub color: Option<String>,
+    pub font: Option<String>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated: Option<bool>,
+    pub insertion: Option<String>,
+}
+
+impl TextComponent {
+    /// Parse a component from JSON.
+    pub fn parse(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// The plain text of the component and all of its `extra` components,
+    /// without formatting.
+    ///
+    /// Translations are not known, so translated text uses its fallback if it
+    /// has one, otherwise its key. Keybinds give their key, and scores and NBT
+    /// only have text if the server has already filled it in.
+    pub fn to_plain(&self) -> String {
+        let mut out = String::new();
+        self.write_plain(&mut out);
+        out
+    }
+
+    fn write_plain(&self, out: &mut String) {
+        match &self.content {
+            Content::Text(text) => out.push_str(text),
+            Content::Translate {
+                key,
+                fallback,
+                with,
+            } => write_translated(fallback.as_ref().unwrap_or(key), with, out),
+            Content::Keybind(key) => out.push_str(key),
+            Content::Score { value, .. } => out.push_str(value.as_deref().unwrap_or_default()),
+            Content::Selector(selector) => out.push_str(selector),
+            Content::Nbt(_) => {}
+        }
+
+        for extra in &self.extra {
+            extra.write_plain(out);
+        }
+    }
+}
+
+/// Substitute the arguments into a translation format, which uses `%s` for
+/// the next argument, `%1$s` for a specific one and `%%` for a percent sign.
+fn write_translated(format: &str, with: &[TextComponent], out: &mut String) {
+    let mut next = 0;
+    let mut rest = format;
+
+    while let Some(i) = rest.find('%') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+
+        if let Some(after) = rest.strip_prefix('%') {
+            out.push('%');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('s') {
+            if let Some(arg) = with.get(next) {
+                arg.write_plain(out);
+            }
+            next += 1;
+            rest = after;
+        } else {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            match rest[digits..].strip_prefix("$s") {
+                Some(after) if digits > 0 => {
+                    let arg = rest[..digits].parse::<usize>().ok();
+                    if let Some(arg) = arg.and_then(|n| with.get(n.checked_sub(1)?)) {
+                        arg.write_plain(out);
+                    }
+                    rest = after;
+                }
+                _ => {
+                    // Not a format specifier, leave it as it was.
+                    out.push('%');
+                }
+            }
+        }
+    }
+
+    out.push_str(rest);
+}
+
+/// The forms a component takes in JSON.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Repr {
+    Primitive(Primitive),
+    // The first component is the parent of the rest.
+    List(Vec<TextComponent>),
+    Object(Box<ObjectRepr>),
+}
+
+#[derive(Deserialize)]
+struct ObjectRepr {
+    text: Option<Primitive>,
+    translate: Option<String>,
+    fallback: Option<String>,
+    #[serde(default)]
+    with: Vec<TextComponent>,
+    keybind: Option<String>,
+    score: Option<ScoreRepr>,
+    selector: Option<String>,
+    nbt: Option<String>,
+    #[serde(flatten)]
+    style: Style,
+    #[serde(default)]
+    extra: Vec<TextComponent>,
+}
+
+#[derive(Deserialize)]
+struct ScoreRepr {
+    name: String,
+    objective: String,
+    value: Option<String>,
+}
+
+/// Text is usually a string, but other primitives are accepted.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Primitive {
+    String(String),
+    Bool(bool),
+    Number(serde_json::Number),
+}
+
+impl From<Primitive> for String {
+    fn from(value: Primitive) -> Self {
+        match value {
+            Primitive::String(s) => s,
+            Primitive::Bool(b) => b.to_string(),
+            Primitive::Number(n) => n.to_string(),
+        }
+    }
+}
+
+impl From<Repr> for TextComponent {
+    fn from(repr: Repr) -> Self {
+        match repr {
+            Repr::Primitive(text) => TextComponent {
+                content: Content::Text(text.into()),
+                ..Default::default()
+            },
+            Repr::List(list) => {
+                let mut list = list.into_iter();
+                let mut first = list.next().unwrap_or_default();
+                first.extra.extend(list);
+                first
+            }
+            Repr::Object(obj) => {
+                let obj = *obj;
+                // The game checks for content in this order.
+                let content = if let Some(t) = obj.text {
+                    Content::Text(t.into())
+                } else if let Some(key) = obj.translate {
+                    Content::Translate {
+                        key,
+                        fallback: obj.fallback,
+                        with: obj.with,
+                    }
+                } else if let Some(score) = obj.score {
+                    Content::Score {
+                        name: score.name,
+                        objective: score.objective,
+                        value: score.value,
+                    }
+                } else if let Some(selector) = obj.selector {
+                    Content::Selector(selector)
+                } else if let Some(key) = obj.keybind {
+                    Content::Keybind(key)
+                } else if let Some(path) = obj.nbt {
+                    Content::Nbt(path)
+                } else {
+                    Content::default()
+                };
+
+                TextComponent {
+                    content,
+                    style: obj.style,
+                    extra: obj.extra,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Upgrade the NBT of old chunks to the layout of newer versions, so tools
+//! only need to read the newest layout.
+//!
+//! ```no_run
+//! # use fastanvil::{upgrade::chunk_to_1_18, JavaChunk};
+//! # use fastnbt::Value;
+//! # let data: Vec<u8> = vec![];
+//! let chunk: Value = fastnbt::from_bytes(&data).unwrap();
+//! let chunk = chunk_to_1_18(&chunk).unwrap();
+//! assert!(chunk.pointer("/sections").is_some());
+//! ```
+
+use fastnbt::{error::Result, LongArray, Map, Value};
+use serde::de::Error as _;
+
+use crate::{
+    biome::Biome, bits_per_block, expand_generic_1_15, min_bits_for_n_states,
+    unpack_section_indices, version::DataVersion, PackedBits, PackingMode,
+};
+
+/// Rewrite the NBT of a chunk from about 1.13 to 1.17 into the layout used
+/// from 1.18, so it can be read like a chunk saved by 1.18. Chunks already in
+/// the 1.18 layout are returned unchanged.
+///
+/// The contents of `Level` move to the top of the chunk and the fields that
+/// were renamed in 1.18 are renamed, eg `TileEntities` to `block_entities`
+/// and `Sections` to `sections`. In each section the `Palette` and
+/// `BlockStates` become `block_states`, repacked so indices do not span two
+/// longs, and the chunk's `Biomes` are split into a `biomes` palette for each
+/// section. Chunks from before 1.15 have a biome for each column, which is
+/// used for every cell of the column. Biome ids become the names 1.18 uses
+/// for them, which for biomes 1.18 removed is the biome the game replaced
+/// them with. Ids the game did not have become `minecraft:plains`, as they
+/// do when the game upgrades chunks. Fields this does not know about are
+/// kept as they are.
+///
+/// Only the layout changes. Blocks renamed before 1.18, entities and the like
+/// are left as they were, so the result suits reading rather than loading
+/// back into the game. It is given the data version of 1.18.
+///
+/// Chunks from before 1.13, which have no block palettes, are an error.
+pub fn chunk_to_1_18(chunk: &Value) -> Result<Value> {
+    let root = match chunk {
+        Value::Compound(root) => root,
+        _ => return Err(fastnbt::error::Error::custom("chunk is not a compound")),
+    };
+
+    let level = match root.get("Level") {
+        Some(Value::Compound(level)) => level,
+        Some(_) => {
+            return Err(fastnbt::error::Error::custom(
+                "chunk Level is not a compound",
+            ))
+        }
+        None if root.contains_key("DataVersion") => return Ok(chunk.clone()),
+        None => {
+            return Err(fastnbt::error::Error::custom(
+                "chunk has no DataVersion or Level, so is not a chunk",
+            ))
+        }
+    };
+
+    // Chunks from before 1.9 have no data version, or block palettes.
+    if !root.contains_key("DataVersion") {
+        return Err(fastnbt::error::Error::custom(
+            "chunk has no DataVersion, so is from before 1.13",
+        ));
+    }
+
+    let mut upgraded: Map<String, Value> = root
+        .iter()
+        .filter(|(k, _)| *k != "Level")
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    upgraded.insert("DataVersion".to_owned(), Value::Int(DataVersion::V1_18.0));
+
+    let biomes = match level.get("Biomes") {
+        Some(Value::IntArray(biomes)) => Some(&biomes[..]),
+        _ => None,
+    };
+
+    for (key, value) in level {
+        let (key, value) = match key.as_str() {
+            "Biomes" => continue,
+            "Sections" => {
+                let (sections, y_pos) = upgrade_sections(value, biomes)?;
+                upgraded.insert("yPos".to_owned(), Value::Int(y_pos));
+                ("sections", sections)
+            }
+            "Heightmaps" => ("Heightmaps", upgrade_heightmaps(value)),
+            "Structures" => ("structures", upgrade_structures(value)),
+            "TileEntities" => ("block_entities", value.clone()),
+            "TileTicks" => ("block_ticks", value.clone()),
+            "LiquidTicks" => ("fluid_ticks", value.clone()),
+            "Entities" => ("entities", value.clone()),
+            key => (key, value.clone()),
+        };
+        upgraded.insert(key.to_owned(), value);
+    }
+
+    Ok(Value::Compound(upgraded))
+}
+
+/// Upgrade the sections, returning them with the lowest section y of the
+/// chunk.
+fn upgrade_sections(sections: &Value, biomes: Option<&[i32]>) -> Result<(Value, i32)> {
+    let sections = match sections {
+        Value::List(sections) => sections,
+        _ => return Ok((sections.clone(), 0)),
+    };
+
+    let mut compounds = vec![];
+    for section in sections {
+        match section {
+            Value::Compound(section) => compounds.push(section),
+            _ => {
+                return Err(fastnbt::error::Error::custom(
+                    "chunk section is not a compound",
+                ))
+            }
+        }
+    }
+
+    // The biomes start at the bottom of the world. Below it the game keeps a
+    // section with only light, so the lowest section is either that one or,
+    // without light, the bottom section itself. Sections of air at the
+    // bottom are not saved, so the lowest section with blocks could be well
+    // above the bottom.
+    let y_min = compounds
+        .iter()
+        .filter_map(|s| Some((s.get("Y")?.as_i64()?, s.contains_key("Palette"))))
+        .min()
+        .map_or(0, |(y, blocks)| if blocks { y } else { y + 1 });
+
+    let mut upgraded = vec![];
+    for section in compounds {
+        if section.contains_key("Blocks") {
+            return Err(fastnbt::error::Error::custom(
+                "chunk is from before 1.13, which has no block palettes",
+            ));
+        }
+
+        // The empty sections below the world only have a Y.
+        if section.len() == 1 && section.contains_key("Y") {
+            continue;
+        }
+
+        upgraded.push(Value::Compound(upgrade_section(section, biomes, y_min)));
+    }
+
+    Ok((Value::List(upgraded), y_min as i32))
+}
+
+fn upgrade_section(
+    section: &Map<String, Value>,
+    biomes: Option<&[i32]>,
+    y_min: i64,
+) -> Map<String, Value> {
+    let mut upgraded: Map<String, Value> = section
+        .iter()
+        .filter(|(k, _)| *k != "Palette" && *k != "BlockStates")
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    // Sections with only light have no blocks or biomes in 1.18 either.
+    let palette = match section.get("Palette") {
+        Some(Value::List(palette)) if !palette.is_empty() => palette,
+        _ => return upgraded,
+    };
+
+    let mut block_states = Map::default();
+    block_states.insert("palette".to_owned(), Value::List(palette.clone()));
+    if let (true, Some(Value::LongArray(data))) = (palette.len() > 1, section.get("BlockStates")) {
+        let mut indices = [0; 4096];
+        unpack_section_indices(data, bits_per_block(palette.len()), &mut indices);
+        let bits = std::cmp::max(4, min_bits_for_n_states(palette.len()));
+        block_states.insert("data".to_owned(), Value::LongArray(pack(&indices, bits)));
+    }
+    upgraded.insert("block_states".to_owned(), Value::Compound(block_states));
+
+    let y = section.get("Y").and_then(Value::as_i64).unwrap_or(0);
+    if let Some(cells) = biomes.and_then(|biomes| section_biomes(biomes, y - y_min)) {
+        upgraded.insert("biomes".to_owned(), biome_palette(&cells));
+    }
+
+    upgraded
+}
+
+/// The biome ids of the 4x4x4 cells of the section `index` sections above
+/// the bottom of the world, increasing in x, then z, then y.
+fn section_biomes(biomes: &[i32], index: i64) -> Option<Vec<i32>> {
+    // Before 1.15 there is one biome for each column.
+    if biomes.len() == 256 {
+        let cells = (0..64)
+            .map(|i| {
+                let (x, z) = (i % 4, (i / 4) % 4);
+                biomes[z * 4 * 16 + x * 4]
+            })
+            .collect();
+        return Some(cells);
+    }
+
+    let start = usize::try_from(index).ok()? * 64;
+    biomes.get(start..start + 64).map(|cells| cells.to_vec())
+}
+
+fn biome_palette(cells: &[i32]) -> Value {
+    let mut palette: Vec<&str> = vec![];
+    let indices: Vec<u16> = cells
+        .iter()
+        .map(|&id| {
+            let name = biome_name(id);
+            let index = palette.iter().position(|&n| n == name).unwrap_or_else(|| {
+                palette.push(name);
+                palette.len() - 1
+            });
+            index as u16
+        })
+        .collect();
+
+    let mut biomes = Map::default();
+    if palette.len() > 1 {
+        let bits = min_bits_for_n_states(palette.len());
+        biomes.insert("data".to_owned(), Value::LongArray(pack(&indices, bits)));
+    }
+    biomes.insert(
+        "palette".to_owned(),
+        Value::List(palette.into_iter().map(Value::from).collect()),
+    );
+    Value::Compound(biomes)
+}
+
+/// Pack indices as in 1.16 and later, with padding so none span two longs.
+fn pack(indices: &[u16], bits: usize) -> LongArray {
+    LongArray::new(PackedBits::from_indices(indices, bits, PackingMode::Padded))
+}
+
+/// Heightmaps from 1.15 span longs, later ones are already padded.
+fn upgrade_heightmaps(heightmaps: &Value) -> Value {
+    let heightmaps = match heightmaps {
+        Value::Compound(heightmaps) => heightmaps,
+        _ => return heightmaps.clone(),
+    };
+
+    let upgraded = heightmaps
+        .iter()
+        .map(|(name, heights)| {
+            let heights = match heights {
+                Value::LongArray(data) if data.len() == 36 => {
+                    let mut heights = expand_generic_1_15(data, 9);
+                    heights.truncate(256);
+                    Value::LongArray(pack(&heights, 9))
+                }
+                heights => heights.clone(),
+            };
+            (name.clone(), heights)
+        })
+        .collect();
+    Value::Compound(upgraded)
+}
+
+fn upgrade_structures(structures: &Value) -> Value {
+    let structures = match structures {
+        Value::Compound(structures) => structures,
+        _ => return structures.clone(),
+    };
+
+    let upgraded = structures
+        .iter()
+        .map(|(k, v)| match k.as_str() {
+            "Starts" => ("starts".to_owned(), v.clone()),
+            _ => (k.clone(), v.clone()),
+        })
+        .collect();
+    Value::Compound(upgraded)
+}
+
+/// The 1.18 name of a numeric biome id.
+fn biome_name(id: i32) -> &'static str {
+    // Biomes added from 1.18 have no id, they only take the values after the
+    // last numeric one in the enum.
+    match Biome::try_from(id) {
+        Ok(biome) if id <= Biome::BasaltDeltas as i32 => biome.name_1_18(),
+        _ => Biome::Unknown.name_1_18(),
+    }
+}
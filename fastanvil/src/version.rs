@@ -0,0 +1,237 @@
+//! The Minecraft version a world or chunk was saved with.
+//!
+//! Every chunk and most files in a world record the `DataVersion` of the game
+//! that saved them. [`DataVersion`] names the releases, and
+//! [`region_data_versions`] finds the versions in a region, eg to warn about
+//! a world that was only partly upgraded.
+//!
+//! ```no_run
+//! # use std::path::Path;
+//! # use fastanvil::World;
+//! let world = World::open(Path::new("world")).unwrap();
+//! if let Some(version) = world.game_version().unwrap() {
+//!     let name = version.data_version.to_release_name();
+//!     println!("this world is {}", name.unwrap_or("a snapshot"));
+//! }
+//! ```
+
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{Read, Seek},
+    ops::RangeInclusive,
+};
+
+use serde::Deserialize;
+
+use crate::Region;
+
+/// The data version of every release since data versions were added in 1.9,
+/// oldest first. Snapshots and pre-releases have the versions in between.
+const RELEASES: &[(i32, &str)] = &[
+    (169, "1.9"),
+    (175, "1.9.1"),
+    (176, "1.9.2"),
+    (183, "1.9.3"),
+    (184, "1.9.4"),
+    (510, "1.10"),
+    (511, "1.10.1"),
+    (512, "1.10.2"),
+    (819, "1.11"),
+    (921, "1.11.1"),
+    (922, "1.11.2"),
+    (1139, "1.12"),
+    (1241, "1.12.1"),
+    (1343, "1.12.2"),
+    (1519, "1.13"),
+    (1628, "1.13.1"),
+    (1631, "1.13.2"),
+    (1952, "1.14"),
+    (1957, "1.14.1"),
+    (1963, "1.14.2"),
+    (1968, "1.14.3"),
+    (1976, "1.14.4"),
+    (2225, "1.15"),
+    (2227, "1.15.1"),
+    (2230, "1.15.2"),
+    (2566, "1.16"),
+    (2567, "1.16.1"),
+    (2578, "1.16.2"),
+    (2580, "1.16.3"),
+    (2584, "1.16.4"),
+    (2586, "1.16.5"),
+    (2724, "1.17"),
+    (2730, "1.17.1"),
+    (2860, "1.18"),
+    (2865, "1.18.1"),
+    (2975, "1.18.2"),
+    (3105, "1.19"),
+    (3117, "1.19.1"),
+    (3120, "1.19.2"),
+    (3218, "1.19.3"),
+    (3337, "1.19.4"),
+    (3463, "1.20"),
+    (3465, "1.20.1"),
+    (3578, "1.20.2"),
+    (3698, "1.20.3"),
+    (3700, "1.20.4"),
+    (3837, "1.20.5"),
+    (3839, "1.20.6"),
+    (3953, "1.21"),
+    (3955, "1.21.1"),
+    (4080, "1.21.2"),
+    (4082, "1.21.3"),
+    (4189, "1.21.4"),
+];
+
+/// The `DataVersion` of a chunk or file, which increases with every release
+/// and snapshot of the game. Versions compare in the order they were
+/// released.
+///
+/// ```
+/// # use fastanvil::version::DataVersion;
+/// let version = DataVersion::try_from("1.19.4").unwrap();
+/// assert_eq!(version, DataVersion(3337));
+/// assert_eq!(version.to_release_name(), Some("1.19.4"));
+/// assert!(version >= DataVersion::V1_18);
+///
+/// // A 1.20 snapshot.
+/// assert_eq!(DataVersion(3442).to_release_name(), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
+#[serde(transparent)]
+pub struct DataVersion(pub i32);
+
+impl DataVersion {
+    pub const V1_9: Self = Self(169);
+    pub const V1_10: Self = Self(510);
+    pub const V1_11: Self = Self(819);
+    pub const V1_12: Self = Self(1139);
+    /// The first version with block palettes.
+    pub const V1_13: Self = Self(1519);
+    pub const V1_14: Self = Self(1952);
+    /// The first version with 3D biomes.
+    pub const V1_15: Self = Self(2225);
+    /// The first version where block states do not span two longs.
+    pub const V1_16: Self = Self(2566);
+    pub const V1_17: Self = Self(2724);
+    /// The first version without a `Level` compound in chunks.
+    pub const V1_18: Self = Self(2860);
+    pub const V1_19: Self = Self(3105);
+    pub const V1_20: Self = Self(3463);
+    pub const V1_21: Self = Self(3953);
+
+    /// The name of the release with exactly this version, eg `1.19.4`. Gives
+    /// `None` for snapshots and for releases newer than this crate knows.
+    pub fn to_release_name(self) -> Option<&'static str> {
+        RELEASES
+            .binary_search_by_key(&self.0, |&(v, _)| v)
+            .ok()
+            .map(|i| RELEASES[i].1)
+    }
+
+    /// Whether this is newer than every release this crate knows about.
+    pub fn is_newer_than_known(self) -> bool {
+        RELEASES.last().is_some_and(|&(v, _)| self.0 > v)
+    }
+}
+
+impl From<i32> for DataVersion {
+    fn from(version: i32) -> Self {
+        Self(version)
+    }
+}
+
+/// The version of a release by its name, eg `1.19.4`.
+impl TryFrom<&str> for DataVersion {
+    type Error = UnknownReleaseError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        RELEASES
+            .iter()
+            .find(|&&(_, n)| n == name)
+            .map(|&(v, _)| Self(v))
+            .ok_or_else(|| UnknownReleaseError(name.to_owned()))
+    }
+}
+
+impl Display for DataVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Error from looking up a release by a name that is not known, see
+/// [`DataVersion`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownReleaseError(String);
+
+impl Error for UnknownReleaseError {}
+
+impl Display for UnknownReleaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown release: {:?}", self.0)
+    }
+}
+
+/// The version of the game that last saved a world, from its `level.dat`.
+/// See [`World::game_version`][crate::World::game_version].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameVersion {
+    pub data_version: DataVersion,
+
+    /// The name the game gives itself, eg `1.19.4` or `23w13a`. This is
+    /// known even for versions newer than this crate.
+    pub name: Option<String>,
+
+    /// Whether the game was a snapshot or pre-release.
+    pub snapshot: bool,
+}
+
+/// The oldest and newest `DataVersion` of the chunks in a region, or `None`
+/// if no chunk has one. Differing versions mean the region was saved by more
+/// than one version of the game, eg a world only partly upgraded.
+///
+/// Only the `DataVersion` of each chunk is read, so this is quick enough to
+/// scan a whole world. Chunks from before 1.9 have no data version, and are
+/// skipped along with chunks that cannot be read or parsed.
+///
+/// ```no_run
+/// # use fastanvil::{version::region_data_versions, Region};
+/// # let file = std::fs::File::open("r.0.0.mca").unwrap();
+/// let mut region = Region::from_stream(file).unwrap();
+/// if let Some(versions) = region_data_versions(&mut region).unwrap() {
+///     if versions.start() != versions.end() {
+///         println!("region has chunks from {} to {}", versions.start(), versions.end());
+///     }
+/// }
+/// ```
+pub fn region_data_versions<S: Read + Seek>(
+    region: &mut Region<S>,
+) -> crate::Result<Option<RangeInclusive<DataVersion>>> {
+    // Both chunk layouts have the version at the root.
+    #[derive(Deserialize)]
+    struct VersionChunk {
+        #[serde(rename = "DataVersion")]
+        data_version: Option<DataVersion>,
+    }
+
+    let mut range: Option<(DataVersion, DataVersion)> = None;
+
+    region.scan_chunks(|_, _, data| -> crate::Result<()> {
+        let Some(VersionChunk {
+            data_version: Some(version),
+        }) = data.ok().and_then(|data| fastnbt::from_bytes(data).ok())
+        else {
+            return Ok(());
+        };
+
+        range = Some(match range {
+            Some((min, max)) => (min.min(version), max.max(version)),
+            None => (version, version),
+        });
+        Ok(())
+    })?;
+
+    Ok(range.map(|(min, max)| min..=max))
+}
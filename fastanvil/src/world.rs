@@ -0,0 +1,420 @@
+use std::{
+    collections::{BTreeSet, VecDeque},
+    fmt::Display,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::read_gzip,
+    files::find_dir,
+    version::{DataVersion, GameVersion},
+    ChunkData, LoaderError, LoaderResult, RCoord, Region, RegionFileLoader, RegionLoader,
+    ResourceLocation, ScanCursor,
+};
+
+/// A dimension of a world, which each have their own directory of regions:
+/// one of the three vanilla dimensions, or a dimension added by a datapack.
+/// Dimensions sort with the vanilla ones first.
+///
+/// Displays as the dimension's id, eg `minecraft:the_nether`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dimension {
+    #[default]
+    Overworld,
+    Nether,
+    End,
+    /// A datapack dimension, stored under `dimensions/<namespace>/<path>`.
+    Custom(ResourceLocation),
+}
+
+impl Dimension {
+    /// The directory holding the dimension's region files, for the world in
+    /// the `world` directory.
+    pub fn region_dir(&self, world: &Path) -> PathBuf {
+        match self {
+            Dimension::Overworld => world.join("region"),
+            Dimension::Nether => world.join("DIM-1").join("region"),
+            Dimension::End => world.join("DIM1").join("region"),
+            Dimension::Custom(id) => world
+                .join("dimensions")
+                .join(id.namespace())
+                .join(id.path())
+                .join("region"),
+        }
+    }
+}
+
+/// The vanilla ids become the vanilla dimensions, anything else is a datapack
+/// dimension.
+impl From<ResourceLocation> for Dimension {
+    fn from(id: ResourceLocation) -> Self {
+        match id.as_str() {
+            "minecraft:overworld" => Dimension::Overworld,
+            "minecraft:the_nether" => Dimension::Nether,
+            "minecraft:the_end" => Dimension::End,
+            _ => Dimension::Custom(id),
+        }
+    }
+}
+
+impl Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dimension::Overworld => f.write_str("minecraft:overworld"),
+            Dimension::Nether => f.write_str("minecraft:the_nether"),
+            Dimension::End => f.write_str("minecraft:the_end"),
+            Dimension::Custom(id) => id.fmt(f),
+        }
+    }
+}
+
+/// A world directory and the dimensions in it.
+///
+/// The vanilla dimensions are in `region`, `DIM-1/region` and `DIM1/region`.
+/// Datapack dimensions are in `dimensions/<namespace>/<path>/region`, where
+/// the path can itself contain directories, eg `dimensions/mypack/sky/high`
+/// for `mypack:sky/high`. A vanilla dimension found under `dimensions`, eg
+/// `dimensions/minecraft/overworld`, is used if its usual directory is
+/// missing. Directories whose names differ only in ASCII case are found too,
+/// as for [`RegionFileLoader`].
+///
+/// ```no_run
+/// # use std::path::Path;
+/// # use fastanvil::{RegionLoader, World};
+/// let world = World::open(Path::new("world")).unwrap();
+/// for dim in world.dimensions() {
+///     let loader = world.region_loader(&dim).unwrap();
+///     println!("{dim}: {} regions", loader.list().unwrap().len());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct World {
+    root: PathBuf,
+    // Sorted by id, with the directory of each dimension's regions.
+    dimensions: Vec<(Dimension, PathBuf)>,
+}
+
+impl World {
+    /// Open the world in the `root` directory, finding its dimensions.
+    /// Dimensions are found once, when opening the world.
+    pub fn open(root: &Path) -> LoaderResult<Self> {
+        // Fails if the world itself is missing.
+        std::fs::read_dir(root).map_err(|_| LoaderError::missing_dir(root))?;
+
+        let mut dimensions: Vec<_> = [Dimension::Overworld, Dimension::Nether, Dimension::End]
+            .into_iter()
+            .filter_map(|dim| {
+                let dir = find_dir(&dim.region_dir(root))?;
+                Some((dim, dir))
+            })
+            .collect();
+
+        // A world without datapack dimensions has no `dimensions` directory.
+        if let Some(custom) = find_dir(&root.join("dimensions")) {
+            for namespace in std::fs::read_dir(&custom)? {
+                let namespace = namespace?;
+                if let Some(name) = namespace.file_name().to_str() {
+                    find_dimensions(&namespace.path(), name, "", &mut dimensions)?;
+                }
+            }
+        }
+
+        // The sort is stable, so the usual directories of the vanilla
+        // dimensions are kept over those under `dimensions`.
+        dimensions.sort_by(|a, b| a.0.cmp(&b.0));
+        dimensions.dedup_by(|a, b| a.0 == b.0);
+
+        Ok(Self {
+            root: root.to_owned(),
+            dimensions,
+        })
+    }
+
+    /// The directory the world is in.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The dimensions that have a region directory, vanilla ones first.
+    pub fn dimensions(&self) -> Vec<Dimension> {
+        self.dimensions.iter().map(|(dim, _)| dim.clone()).collect()
+    }
+
+    /// A loader for the regions of a dimension, or `None` if the world does
+    /// not have the dimension.
+    pub fn region_loader(&self, dim: &Dimension) -> Option<RegionFileLoader> {
+        self.dimensions
+            .iter()
+            .find(|(d, _)| d == dim)
+            .map(|(_, dir)| RegionFileLoader::new(dir.clone()))
+    }
+
+    /// Scan every chunk of every dimension, a region at a time. Dimensions are
+    /// scanned in the order of [`dimensions`][Self::dimensions], and their
+    /// regions sorted by x then z.
+    ///
+    /// Scans of large worlds take a while. Take a [`cursor`][WorldScan::cursor]
+    /// as you go to carry on from later with [`scan_from`][Self::scan_from].
+    ///
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use fastanvil::World;
+    /// let world = World::open(Path::new("world")).unwrap();
+    /// let mut scan = world.scan().unwrap();
+    /// for chunk in scan.by_ref().take(1000) {
+    ///     let chunk = chunk.unwrap();
+    ///     println!("{} {:?}", chunk.dimension, chunk.region);
+    /// }
+    /// let saved = serde_json::to_string(&scan.cursor()).unwrap();
+    /// ```
+    pub fn scan(&self) -> LoaderResult<WorldScan<'_>> {
+        self.scan_from(WorldCursor::default())
+    }
+
+    /// Carry on a scan from where a [`WorldCursor`] was taken. Regions that
+    /// the scan had finished are skipped by their coordinates, along with the
+    /// chunks it had already yielded from the region it was part way through.
+    ///
+    /// The region the scan was part way through must not have changed since.
+    /// If it has, the scan yields an [`Error::StaleCursor`][crate::Error] for
+    /// it and stops. Finished regions are not checked.
+    pub fn scan_from(&self, cursor: WorldCursor) -> LoaderResult<WorldScan<'_>> {
+        let mut pending = VecDeque::new();
+        for (dim, dir) in &self.dimensions {
+            let id = dim.to_string();
+            for (x, z) in RegionFileLoader::new(dir.clone()).list()? {
+                if !cursor.done.contains(&(id.clone(), x.0, z.0)) {
+                    pending.push_back((dim.clone(), x, z));
+                }
+            }
+        }
+
+        Ok(WorldScan {
+            world: self,
+            pending,
+            current: None,
+            done: cursor.done,
+            resume: cursor.current,
+        })
+    }
+
+    /// The version of the game that last saved the world, read from its
+    /// `level.dat`. Gives `None` for worlds last saved before 1.9, which did
+    /// not record it.
+    ///
+    /// The chunks of a world upgraded by opening it in a newer version keep
+    /// their old version until they are next saved. See
+    /// [`region_data_versions`][crate::version::region_data_versions].
+    pub fn game_version(&self) -> fastnbt::error::Result<Option<GameVersion>> {
+        #[derive(Deserialize)]
+        struct Level {
+            #[serde(rename = "Data")]
+            data: LevelData,
+        }
+
+        #[derive(Deserialize)]
+        struct LevelData {
+            #[serde(rename = "DataVersion")]
+            data_version: Option<DataVersion>,
+            #[serde(rename = "Version")]
+            version: Option<LevelVersion>,
+        }
+
+        #[derive(Deserialize)]
+        struct LevelVersion {
+            #[serde(rename = "Name")]
+            name: Option<String>,
+            #[serde(rename = "Snapshot", default)]
+            snapshot: bool,
+        }
+
+        let level: Level = fastnbt::from_bytes(&read_gzip(&self.root.join("level.dat"))?)?;
+        let Some(data_version) = level.data.data_version else {
+            return Ok(None);
+        };
+        let (name, snapshot) = match level.data.version {
+            Some(v) => (v.name, v.snapshot),
+            None => (None, false),
+        };
+
+        Ok(Some(GameVersion {
+            data_version,
+            name,
+            snapshot,
+        }))
+    }
+}
+
+/// A chunk found by a [`WorldScan`].
+pub struct WorldChunk {
+    pub dimension: Dimension,
+    /// The coordinates of the region the chunk is in.
+    pub region: (RCoord, RCoord),
+    /// The chunk, with coordinates within its region.
+    pub chunk: ChunkData,
+}
+
+/// Where a scan of a world got to: the regions it had finished, and how far
+/// it was through the next. Cursors can be serialized, eg to save progress
+/// through a long scan. The default cursor is the start of a scan.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldCursor {
+    // Finished regions, as the dimension id and region coordinates.
+    done: BTreeSet<(String, isize, isize)>,
+    current: Option<(String, isize, isize, ScanCursor)>,
+}
+
+/// Iterates over the chunks of a world. Created by [`World::scan`] and
+/// [`World::scan_from`].
+pub struct WorldScan<'a> {
+    world: &'a World,
+    // Regions yet to be opened, in order.
+    pending: VecDeque<(Dimension, RCoord, RCoord)>,
+    current: Option<ScanRegion>,
+    done: BTreeSet<(String, isize, isize)>,
+    // Where the region the scan is carrying on from had got to.
+    resume: Option<(String, isize, isize, ScanCursor)>,
+}
+
+struct ScanRegion {
+    dimension: Dimension,
+    x: RCoord,
+    z: RCoord,
+    region: Region<File>,
+    cursor: ScanCursor,
+}
+
+impl WorldScan<'_> {
+    /// Where the scan has got to, to carry on from later with
+    /// [`World::scan_from`].
+    pub fn cursor(&self) -> WorldCursor {
+        WorldCursor {
+            done: self.done.clone(),
+            current: match &self.current {
+                Some(r) => Some((r.dimension.to_string(), r.x.0, r.z.0, r.cursor)),
+                None => self.resume.clone(),
+            },
+        }
+    }
+
+    /// Open the next region to scan, or `None` at the end of the scan.
+    fn open_next(&mut self) -> Option<LoaderResult<ScanRegion>> {
+        loop {
+            let (dimension, x, z) = self.pending.pop_front()?;
+            let id = dimension.to_string();
+            let loader = self.world.region_loader(&dimension)?;
+
+            // Regions can be deleted during a scan, or be empty files.
+            let Some(mut region) = loader.region(x, z) else {
+                self.done.insert((id, x.0, z.0));
+                continue;
+            };
+
+            let mut cursor = ScanCursor::default();
+            if let Some((rid, rx, rz, resume)) = &self.resume {
+                if (rid, *rx, *rz) == (&id, x.0, z.0) {
+                    if let Err(e) = region.check_cursor(resume) {
+                        self.pending.clear();
+                        return Some(Err(e.into()));
+                    }
+                    cursor = *resume;
+                    self.resume = None;
+                }
+            }
+
+            return Some(Ok(ScanRegion {
+                dimension,
+                x,
+                z,
+                region,
+                cursor,
+            }));
+        }
+    }
+}
+
+impl Iterator for WorldScan<'_> {
+    type Item = LoaderResult<WorldChunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(current) = &mut self.current {
+                match current.region.next_chunk(&mut current.cursor) {
+                    Some(Ok(chunk)) => {
+                        return Some(Ok(WorldChunk {
+                            dimension: current.dimension.clone(),
+                            region: (current.x, current.z),
+                            chunk,
+                        }))
+                    }
+                    Some(Err(e)) => return Some(Err(e.into())),
+                    None => {
+                        self.done
+                            .insert((current.dimension.to_string(), current.x.0, current.z.0));
+                        self.current = None;
+                    }
+                }
+            }
+
+            match self.open_next()? {
+                Ok(region) => self.current = Some(region),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// How many directories deep a datapack dimension's path can be. Bounds the
+/// search when symlinks make a directory contain itself.
+const MAX_DIMENSION_DEPTH: usize = 8;
+
+/// Find the datapack dimensions at or below `dir`, which is the directory for
+/// `path` within `namespace`. Directories whose names are not valid in an id
+/// are skipped, as are any more than [`MAX_DIMENSION_DEPTH`] deep.
+fn find_dimensions(
+    dir: &Path,
+    namespace: &str,
+    path: &str,
+    found: &mut Vec<(Dimension, PathBuf)>,
+) -> LoaderResult<()> {
+    let depth = if path.is_empty() {
+        0
+    } else {
+        path.split('/').count()
+    };
+    if depth > MAX_DIMENSION_DEPTH || !dir.is_dir() {
+        return Ok(());
+    }
+
+    if !path.is_empty() {
+        if let Some(region) = find_dir(&dir.join("region")) {
+            if let Ok(id) = ResourceLocation::parse(&format!("{namespace}:{path}")) {
+                found.push((id.into(), region));
+            }
+        }
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+        // The dimension's own data rather than a nested dimension.
+        let own = ["region", "entities", "poi", "data"];
+        if !path.is_empty() && own.iter().any(|own| name.eq_ignore_ascii_case(own)) {
+            continue;
+        }
+
+        let path = if path.is_empty() {
+            name
+        } else {
+            format!("{path}/{name}")
+        };
+        find_dimensions(&entry.path(), namespace, &path, found)?;
+    }
+
+    Ok(())
+}
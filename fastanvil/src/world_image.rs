@@ -0,0 +1,136 @@
+use std::{io, path::Path};
+
+use image::RgbaImage;
+
+use crate::{
+    render_region, CCoord, Dimension, Legend, LoaderResult, Palette, RCoord, RegionFileLoader,
+    RegionLoader, TopShadeRenderer,
+};
+
+/// The length in pixels of a rendered region, one pixel per block.
+const REGION_LEN: u64 = 32 * 16;
+
+/// Options for [`render_world_image`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldImageOpts {
+    pub dimension: Dimension,
+
+    /// The most pixels the image may be wide or high. Bigger worlds are scaled
+    /// down to fit by a whole number factor. With `None` the image is never
+    /// scaled, however big the world is. Defaults to 16384.
+    pub max_size: Option<u32>,
+}
+
+impl Default for WorldImageOpts {
+    fn default() -> Self {
+        Self {
+            dimension: Dimension::default(),
+            max_size: Some(16384),
+        }
+    }
+}
+
+/// A whole world rendered into a single image by [`render_world_image`].
+pub struct WorldImage {
+    pub image: RgbaImage,
+
+    /// The region at the top left of the image.
+    pub origin: (RCoord, RCoord),
+
+    /// How many blocks each pixel covers along each side. This is 1 unless
+    /// the world had to be scaled down to fit the image.
+    pub scale: u32,
+
+    /// The blocks drawn in the world, if the renderer is collecting them, see
+    /// [`TopShadeRenderer::with_legend`]. Blocks are collected before scaling,
+    /// so some may not be in any pixel of a scaled image.
+    pub legend: Option<Legend>,
+}
+
+/// Render every region of a dimension of the world in the `world` directory
+/// into a single image, eg to save as one PNG of the whole world.
+///
+/// The image covers the smallest rectangle of regions containing all of
+/// them, with the most northwesterly region at the top left. Missing regions
+/// and chunks are left transparent. If the image would be bigger than
+/// `opts.max_size` it is scaled down, keeping one pixel out of each square of
+/// [`scale`][WorldImage::scale] pixels. Without a `max_size`, a world more
+/// than `u32::MAX` blocks across is an error.
+///
+/// ```no_run
+/// # use std::path::Path;
+/// # use fastanvil::{render_world_image, HeightMode, Palette, TopShadeRenderer, WorldImageOpts};
+/// # fn run(palette: &impl Palette) -> fastanvil::LoaderResult<()> {
+/// let renderer = TopShadeRenderer::new(palette, HeightMode::Trust);
+/// let world = render_world_image(Path::new("world"), &renderer, WorldImageOpts::default())?;
+/// world.image.save("map.png").unwrap();
+/// # Ok(())
+/// # }
+/// ```
+pub fn render_world_image<P: Palette + ?Sized>(
+    world: &Path,
+    renderer: &TopShadeRenderer<P>,
+    opts: WorldImageOpts,
+) -> LoaderResult<WorldImage> {
+    let loader = RegionFileLoader::new(opts.dimension.region_dir(world));
+    let coords = loader.list()?;
+
+    let renderer = renderer.clone().with_own_legend();
+
+    let (Some(xmin), Some(xmax), Some(zmin), Some(zmax)) = (
+        coords.iter().map(|c| c.0).min(),
+        coords.iter().map(|c| c.0).max(),
+        coords.iter().map(|c| c.1).min(),
+        coords.iter().map(|c| c.1).max(),
+    ) else {
+        return Ok(WorldImage {
+            image: RgbaImage::new(0, 0),
+            origin: (RCoord(0), RCoord(0)),
+            scale: 1,
+            legend: renderer.legend(),
+        });
+    };
+
+    let width = (xmax.0 - xmin.0 + 1) as u64 * REGION_LEN;
+    let height = (zmax.0 - zmin.0 + 1) as u64 * REGION_LEN;
+
+    let scale = match opts.max_size {
+        Some(max) => width.max(height).div_ceil(max.max(1) as u64),
+        None => 1,
+    };
+    let scaled = |len: u64| {
+        u32::try_from(len.div_ceil(scale)).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("world of {width} by {height} blocks is too big for an image without a max_size"),
+            )
+        })
+    };
+
+    let mut image = RgbaImage::new(scaled(width)?, scaled(height)?);
+
+    for (x, z) in coords {
+        let map = render_region(x, z, &loader, renderer.clone());
+
+        // Position of the region's top left pixel, before scaling.
+        let left = (x.0 - xmin.0) as u64 * REGION_LEN;
+        let top = (z.0 - zmin.0) as u64 * REGION_LEN;
+
+        for py in (top.next_multiple_of(scale)..top + REGION_LEN).step_by(scale as usize) {
+            for px in (left.next_multiple_of(scale)..left + REGION_LEN).step_by(scale as usize) {
+                let (bx, bz) = ((px - left) as usize, (py - top) as usize);
+                let chunk = map.chunk(CCoord(bx as isize / 16), CCoord(bz as isize / 16));
+                let pixel = chunk[(bz % 16) * 16 + bx % 16];
+
+                image.put_pixel((px / scale) as u32, (py / scale) as u32, image::Rgba(pixel));
+            }
+        }
+    }
+
+    Ok(WorldImage {
+        image,
+        origin: (xmin, zmin),
+        scale: scale as u32,
+        legend: renderer.legend(),
+    })
+}
@@ -1,6 +1,7 @@
-use std::ops::{Deref, DerefMut};
+use alloc::{borrow::ToOwned, vec::Vec};
+use core::ops::{Deref, DerefMut};
 
-use byteorder::{BigEndian, ByteOrder, NativeEndian, ReadBytesExt};
+use byteorder::{BigEndian, ByteOrder, NativeEndian};
 use serde::{de::Visitor, Deserialize, Serialize};
 use serde_bytes::Bytes;
 
@@ -10,6 +11,17 @@ pub(crate) const BYTE_ARRAY_TOKEN: &str = "__fastnbt_byte_array";
 pub(crate) const INT_ARRAY_TOKEN: &str = "__fastnbt_int_array";
 pub(crate) const LONG_ARRAY_TOKEN: &str = "__fastnbt_long_array";
 
+/// Visit an array that serde buffered before deserializing, eg for a flattened
+/// struct. It comes back as a newtype around the map the NBT deserializer gave
+/// for the array.
+fn visit_buffered<'de, D, V>(deserializer: D, visitor: V) -> Result<V::Value, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    V: Visitor<'de>,
+{
+    deserializer.deserialize_map(visitor)
+}
+
 /// NBT ByteArray that owns its data. This type preserves the exact NBT type
 /// when (de)serializing. This dereferences into a i8 slice, so should be usable
 /// basically anywhere a slice should be.
@@ -28,10 +40,17 @@ impl<'de> Deserialize<'de> for ByteArray {
         impl<'de> Visitor<'de> for InnerVisitor {
             type Value = ByteArray;
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("byte array")
             }
 
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                visit_buffered(deserializer, self)
+            }
+
             fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
             where
                 A: serde::de::MapAccess<'de>,
@@ -124,13 +143,9 @@ impl IntArray {
     }
 
     /// Produce a IntArray from raw data.
-    pub(crate) fn from_bytes<Ord: ByteOrder>(data: &[u8]) -> std::io::Result<Self> {
-        let data = data
-            .chunks_exact(4)
-            .map(|mut bs| bs.read_i32::<Ord>())
-            .collect::<std::io::Result<Vec<i32>>>()?;
-
-        Ok(IntArray { data })
+    pub(crate) fn from_bytes<Ord: ByteOrder>(data: &[u8]) -> Self {
+        let data = data.chunks_exact(4).map(Ord::read_i32).collect();
+        IntArray { data }
     }
 
     pub(crate) fn to_bytes(&self) -> &[u8] {
@@ -149,10 +164,17 @@ impl<'de> Deserialize<'de> for IntArray {
         impl<'de> Visitor<'de> for InnerVisitor {
             type Value = IntArray;
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("int array")
             }
 
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                visit_buffered(deserializer, self)
+            }
+
             fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
             where
                 A: serde::de::MapAccess<'de>,
@@ -163,10 +185,8 @@ impl<'de> Deserialize<'de> for IntArray {
                 let data = map.next_value::<&[u8]>()?;
 
                 match token {
-                    INT_ARRAY_TOKEN => IntArray::from_bytes::<BigEndian>(data)
-                        .map_err(|_| serde::de::Error::custom("could not read i32 for int array")),
-                    INT_ARRAY_VALUE_TOKEN => IntArray::from_bytes::<NativeEndian>(data)
-                        .map_err(|_| serde::de::Error::custom("could not read i32 for int array")),
+                    INT_ARRAY_TOKEN => Ok(IntArray::from_bytes::<BigEndian>(data)),
+                    INT_ARRAY_VALUE_TOKEN => Ok(IntArray::from_bytes::<NativeEndian>(data)),
                     _ => Err(serde::de::Error::custom("expected NBT int array token")),
                 }
             }
@@ -225,13 +245,9 @@ impl LongArray {
         Self { data }
     }
 
-    pub(crate) fn from_bytes<Ord: ByteOrder>(data: &[u8]) -> std::io::Result<Self> {
-        let data = data
-            .chunks_exact(8)
-            .map(|mut bs| bs.read_i64::<Ord>())
-            .collect::<std::io::Result<Vec<i64>>>()?;
-
-        Ok(LongArray { data })
+    pub(crate) fn from_bytes<Ord: ByteOrder>(data: &[u8]) -> Self {
+        let data = data.chunks_exact(8).map(Ord::read_i64).collect();
+        LongArray { data }
     }
 
     pub(crate) fn to_bytes(&self) -> &[u8] {
@@ -250,10 +266,17 @@ impl<'de> Deserialize<'de> for LongArray {
         impl<'de> Visitor<'de> for InnerVisitor {
             type Value = LongArray;
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("long array")
             }
 
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                visit_buffered(deserializer, self)
+            }
+
             fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
             where
                 A: serde::de::MapAccess<'de>,
@@ -264,10 +287,8 @@ impl<'de> Deserialize<'de> for LongArray {
                 let data = map.next_value::<&[u8]>()?;
 
                 match token {
-                    LONG_ARRAY_TOKEN => LongArray::from_bytes::<BigEndian>(data)
-                        .map_err(|_| serde::de::Error::custom("could not read i64 for long array")),
-                    LONG_ARRAY_VALUE_TOKEN => LongArray::from_bytes::<NativeEndian>(data)
-                        .map_err(|_| serde::de::Error::custom("could not read i64 for long array")),
+                    LONG_ARRAY_TOKEN => Ok(LongArray::from_bytes::<BigEndian>(data)),
+                    LONG_ARRAY_VALUE_TOKEN => Ok(LongArray::from_bytes::<NativeEndian>(data)),
                     _ => Err(serde::de::Error::custom("expected NBT long array token")),
                 }
             }
@@ -309,4 +330,4 @@ impl DerefMut for LongArray {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.data
     }
-}
\ No newline at end of file
+}
@@ -16,6 +16,12 @@
 //! The `iter()` methods return an iterator to the values read on demand from an
 //! internal reference to the input data.
 //!
+//! [`RawCompound`] skips over a whole compound, keeping its NBT to deserialize
+//! later.
+//!
+//! [`BorrowedValue`] is a [`Value`][crate::Value] that borrows its arrays and
+//! strings, for NBT whose structure is not known ahead of time.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -39,14 +45,62 @@
 //!         // do something
 //!     }
 //!# }
-
-use std::{borrow::Cow, fmt, marker::PhantomData};
-
-use byteorder::{BigEndian, ReadBytesExt};
+//! ```
+//!
+//! # Strings
+//!
+//! Strings can only be borrowed when Java's encoding of the string is the same
+//! as Rust's (see [`de`][`crate::de`] for details). Which types borrow depends
+//! on where they are used:
+//!
+//! | Type | Borrows? |
+//! | ---- | -------- |
+//! | `&'a str` | Always, but errors if the string would need converting. |
+//! | `Cow<'a, str>` | Never. Serde's implementation always creates an owned string. |
+//! | `Cow<'a, str>` with [`deserialize_cow_str`] | When possible. |
+//! | `HashMap<Cow<'a, str>, V>` with [`deserialize_cow_str_keys`] | Keys when possible. |
+//! | [`CowStr<'a>`] | When possible, in any position eg map keys, `Vec` elements or untagged enums. |
+//!
+//! Unfortunately there is nothing the deserializer can do to make a plain
+//! `Cow<'a, str>` borrow, as serde's `Deserialize` implementation for `Cow`
+//! always deserializes the owned form. Hence the need for the attribute:
+//!
+//! ```
+//! use fastnbt::borrow::{deserialize_cow_bytes, deserialize_cow_str, CowStr};
+//! use serde::Deserialize;
+//! use std::borrow::Cow;
+//!
+//! #[derive(Deserialize)]
+//! struct Sign<'a> {
+//!     #[serde(borrow, deserialize_with = "deserialize_cow_str")]
+//!     id: Cow<'a, str>,
+//!
+//!     #[serde(borrow)]
+//!     lines: Vec<CowStr<'a>>,
+//!
+//!     #[serde(borrow, deserialize_with = "deserialize_cow_bytes")]
+//!     data: Cow<'a, [u8]>,
+//! }
+//! ```
+
+use alloc::{
+    borrow::{Cow, ToOwned},
+    string::String,
+    vec::Vec,
+};
+use core::{fmt, marker::PhantomData};
+#[cfg(feature = "std")]
+use std::{collections::HashMap, hash::BuildHasher};
+
+use byteorder::BigEndian;
 use serde::{de::Visitor, Deserialize, Serialize};
 use serde_bytes::Bytes;
 
-use crate::{BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN};
+pub use crate::value::BorrowedValue;
+
+use crate::{
+    de::Deserializer, read::ReadSlice, DeOpts, BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN,
+};
 
 /// ByteArray can be used to deserialize the NBT data of the same name. This
 /// borrows from the original input data when deserializing. The carving masks
@@ -81,7 +135,7 @@ impl<'a, 'de: 'a> Deserialize<'de> for ByteArray<'a> {
         impl<'a, 'de: 'a> Visitor<'de> for InnerVisitor<'a> {
             type Value = ByteArray<'a>;
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("byte array")
             }
 
@@ -171,7 +225,7 @@ impl<'a, 'de: 'a> Deserialize<'de> for IntArray<'a> {
         impl<'a, 'de: 'a> Visitor<'de> for InnerVisitor<'a> {
             type Value = IntArray<'a>;
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("int array")
             }
 
@@ -261,7 +315,7 @@ impl<'a, 'de: 'a> Deserialize<'de> for LongArray<'a> {
         impl<'a, 'de: 'a> Visitor<'de> for InnerVisitor<'a> {
             type Value = LongArray<'a>;
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("long array")
             }
 
@@ -318,9 +372,155 @@ impl<'a> Serialize for LongArray<'a> {
     }
 }
 
-struct CowStr<'a>(Cow<'a, str>);
+pub(crate) const RAW_COMPOUND_TOKEN: &str = "__fastnbt_raw_compound";
+
+/// A compound left undeserialized, borrowing its NBT from the input. This lets
+/// parts of a structure be skipped over cheaply and deserialized later, or
+/// never, with [`parse`][Self::parse].
+///
+/// ```
+/// use fastnbt::borrow::RawCompound;
+/// use fastnbt::nbt;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Chunk<'a> {
+///     #[serde(borrow)]
+///     sections: Vec<RawCompound<'a>>,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Section {
+///     y: i8,
+/// }
+///
+/// let input = fastnbt::to_bytes(&nbt!({"sections": [{"y": 1i8}, {"y": 2i8}]})).unwrap();
+/// let chunk: Chunk = fastnbt::from_bytes(&input).unwrap();
+///
+/// let section: Section = chunk.sections[1].parse().unwrap();
+/// assert_eq!(section.y, 2);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RawCompound<'a> {
+    // The compound's entries and end tag, without its tag and name.
+    data: &'a [u8],
+}
+
+impl<'a> RawCompound<'a> {
+    /// The NBT of the compound's payload, ie its entries and the end tag that
+    /// closes it, but not its own tag or name.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Wrap the payload of a compound, such as one from
+    /// [`as_bytes`][Self::as_bytes]. Nothing is checked until it is parsed.
+    pub fn from_bytes(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Deserialize the compound into a `T`.
+    pub fn parse<T: Deserialize<'a>>(&self) -> crate::error::Result<T> {
+        self.parse_with_opts(DeOpts::new())
+    }
+
+    /// Like [`parse`][Self::parse] but with options.
+    pub fn parse_with_opts<T: Deserialize<'a>>(&self, opts: DeOpts) -> crate::error::Result<T> {
+        T::deserialize(&mut Deserializer::from_compound_payload(self.data, opts))
+    }
+}
+
+impl<'a, 'de: 'a> Deserialize<'de> for RawCompound<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct InnerVisitor<'a>(PhantomData<&'a ()>);
+        impl<'a, 'de: 'a> Visitor<'de> for InnerVisitor<'a> {
+            type Value = RawCompound<'a>;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("raw compound")
+            }
+
+            fn visit_borrowed_bytes<E>(self, data: &'de [u8]) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(RawCompound { data })
+            }
+        }
+        deserializer.deserialize_newtype_struct(RAW_COMPOUND_TOKEN, InnerVisitor(PhantomData))
+    }
+}
+
+/// Serializing parses the compound, so fails if it is not valid NBT.
+impl<'a> Serialize for RawCompound<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.parse::<crate::Value>()
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+/// A string that borrows from the input when possible. This can be used in any
+/// position a string appears, such as a map key, in a `Vec`, or inside an
+/// untagged enum. See the [module level documentation][`crate::borrow`] for
+/// more information.
+///
+/// ```
+/// use fastnbt::borrow::CowStr;
+/// use fastnbt::nbt;
+/// use std::collections::HashMap;
+///
+/// let input = fastnbt::to_bytes(&nbt!({"minecraft:stone": 1})).unwrap();
+/// let counts: HashMap<CowStr, i32> = fastnbt::from_bytes(&input).unwrap();
+///
+/// assert_eq!(counts["minecraft:stone"], 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CowStr<'a>(pub Cow<'a, str>);
+
+impl<'a> CowStr<'a> {
+    /// Get the inner `Cow`.
+    pub fn into_inner(self) -> Cow<'a, str> {
+        self.0
+    }
+}
+
+impl<'a> core::ops::Deref for CowStr<'a> {
+    type Target = str;
 
-impl<'de> serde::Deserialize<'de> for CowStr<'de> {
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> core::borrow::Borrow<str> for CowStr<'a> {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> fmt::Display for CowStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'a> Serialize for CowStr<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'a, 'de: 'a> serde::Deserialize<'de> for CowStr<'a> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
@@ -366,6 +566,8 @@ impl<'de> serde::de::Visitor<'de> for CowStrVisitor {
     }
 }
 
+/// Deserialize a `Cow<'a, str>` field, borrowing from the input when possible.
+/// Use with `#[serde(borrow, deserialize_with = "...")]`.
 pub fn deserialize_cow_str<'de, D>(deserializer: D) -> Result<Cow<'de, str>, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -373,3 +575,84 @@ where
     let wrapper = CowStr::deserialize(deserializer)?;
     Ok(wrapper.0)
 }
+
+/// Deserialize a map such as `HashMap<Cow<'a, str>, V>`, borrowing the keys
+/// from the input when possible. Use with `#[serde(borrow, deserialize_with =
+/// "...")]`. Needs the `std` feature.
+#[cfg(feature = "std")]
+pub fn deserialize_cow_str_keys<'de, D, V, S>(
+    deserializer: D,
+) -> Result<HashMap<Cow<'de, str>, V, S>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    V: Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    struct KeysVisitor<V, S>(PhantomData<(V, S)>);
+
+    impl<'de, V, S> Visitor<'de> for KeysVisitor<V, S>
+    where
+        V: Deserialize<'de>,
+        S: BuildHasher + Default,
+    {
+        type Value = HashMap<Cow<'de, str>, V, S>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a compound")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::MapAccess<'de>,
+        {
+            let mut result = HashMap::with_hasher(S::default());
+            while let Some((key, value)) = map.next_entry::<CowStr, V>()? {
+                result.insert(key.0, value);
+            }
+            Ok(result)
+        }
+    }
+
+    deserializer.deserialize_map(KeysVisitor(PhantomData))
+}
+
+struct CowBytesVisitor;
+
+impl<'de> serde::de::Visitor<'de> for CowBytesVisitor {
+    type Value = Cow<'de, [u8]>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("bytes")
+    }
+
+    fn visit_borrowed_bytes<E>(self, value: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Cow::Borrowed(value))
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Cow::Owned(value.to_owned()))
+    }
+
+    fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Cow::Owned(value))
+    }
+}
+
+/// Deserialize a `Cow<'a, [u8]>` field, borrowing from the input when possible.
+/// This works on the same NBT as deserializing `&[u8]`, such as a byte array or
+/// list of bytes. Use with `#[serde(borrow, deserialize_with = "...")]`.
+pub fn deserialize_cow_bytes<'de, D>(deserializer: D) -> Result<Cow<'de, [u8]>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserializer.deserialize_bytes(CowBytesVisitor)
+}
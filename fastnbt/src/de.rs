@@ -4,6 +4,7 @@
 //! * containers like `HashMap` and `Vec`.
 //! * an arbitrary [`Value`](../enum.Value.html).
 //! * enums. For NBT typically you want either internally or untagged enums.
+//!   Unit variants can also be read from integers, see [Enums](#enums).
 //!
 //! This deserializer only supports [`from_bytes`](fn.from_bytes.html). This is
 //! usually fine as most structures stored in this format are reasonably small,
@@ -52,10 +53,24 @@
 //! common for minecrafts internal strings and any world whose language falls in
 //! the basic multilingual plane.
 //!
+//! Serde's own implementation for `Cow` always produces an owned value, so to
+//! actually borrow use [`borrow::deserialize_cow_str`][`crate::borrow::deserialize_cow_str`]
+//! or [`borrow::CowStr`][`crate::borrow::CowStr`]. See [`borrow`][`crate::borrow`]
+//! for which positions can borrow.
+//!
 //! In future we could support a lazy string type that always borrows the
 //! underyling data and decodes when needed. Please open an issue if this is
 //! important to you.
 //!
+//! ## Names
+//!
+//! Names of compound entries, ie struct fields and map keys, are borrowed
+//! from the input in the same way, and given to serde as borrowed strings.
+//! Matching a name to a struct field, or skipping a field that is not wanted,
+//! allocates nothing unless the name has characters that need converting.
+//! Maps with `&str` or [`CowStr`][`crate::borrow::CowStr`] keys can borrow
+//! them too.
+//!
 //! # Representation of NBT arrays
 //!
 //! In order for [`Value`][`crate::Value`] to preserve all NBT information, the
@@ -64,6 +79,39 @@
 //! means that in order to deserialize NBT array types, you must use the types
 //! provided in this crate, eg [LongArray][`crate::LongArray`].
 //!
+//! # Enums
+//!
+//! Unit variants of an enum are read from a string of the variant's name, as
+//! serde usually does. Many fields in Minecraft's data are really enums stored
+//! as numbers though, eg a player's game mode. So unit variants can also be
+//! read from any integral tag, whose value is the index of the variant in the
+//! order they are declared. Explicit discriminants such as `Creative = 1` are
+//! not looked at, so declare the variants in order:
+//!
+//! ```
+//! # use serde::Deserialize;
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! enum GameMode {
+//!     Survival,
+//!     Creative,
+//!     Adventure,
+//!     Spectator,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Player {
+//!     #[serde(rename = "playerGameType")]
+//!     game_mode: GameMode,
+//! }
+//!
+//! # let bytes = fastnbt::to_bytes(&fastnbt::nbt!({ "playerGameType": 1 })).unwrap();
+//! let player: Player = fastnbt::from_bytes(&bytes).unwrap();
+//! assert_eq!(player.game_mode, GameMode::Creative);
+//! ```
+//!
+//! Negative values, and values past the last variant, are errors. Enums are
+//! still serialized as their variant's name.
+//!
 //! # 128 bit integers and UUIDs
 //!
 //! UUIDs tend to be stored in NBT using 4-long IntArrays. When deserializing
@@ -79,13 +127,58 @@
 //!   vectors.
 //! * Any integral value from NBT can be deserialized to bool. Any non-zero
 //!   value becomes `true`.
+//! * Any integral value from NBT can be deserialized to `f32` or `f64`, as
+//!   long as the float can hold it exactly. A long of 2^53 + 1 is an error as
+//!   an `f64`, for example. [`DeOpts::strict_floats`][crate::DeOpts::strict_floats]
+//!   turns this off.
 //! * You can deserialize a field to the unit type `()` or unit struct. This
 //!   ignores the value but ensures that it existed.
+//! * Keys of a map, eg a `HashMap`, can be strings, unit variant enums or
+//!   newtypes of these. This allows something like `HashMap<HeightmapKind,
+//!   LongArray>` for a chunk's heightmaps.
 //! * You cannot deserialize into anything other than a `struct` or similar
 //!   container eg `HashMap`. This is due to a misalignment between the NBT
 //!   format and Rust's types. Attempting to will give a `NoRootCompound` error.
 //!   This means you can never do `let s: String = from_bytes(...)`.
 //!
+//! # Degenerate input
+//!
+//! Documents that are malformed in these ways are read as the game reads
+//! them, which is leniently. The tests in `test/conformance.rs` check each of
+//! these, so change them together.
+//!
+//! * An end tag ends its compound straight away. End tags have no name, so if
+//!   an encoder wrote a name length after one, those bytes are read as the
+//!   next entry of the parent compound. Usually the first is `0`, which ends
+//!   the parent too, and the rest are left over. Left over bytes are ignored
+//!   unless [`DeOpts::strict_trailing`][crate::DeOpts::strict_trailing] is
+//!   set. Compounds that are skipped over rather than deserialized end the
+//!   same way, as do those read by the [`stream`][crate::stream] parser.
+//! * The empty string is a name like any other, distinct from every other
+//!   name. It can be a map key or, with `#[serde(rename = "")]`, a struct
+//!   field.
+//! * When a compound has the same name more than once, maps such as `HashMap`
+//!   and [`Value`][crate::Value] keep the last value, as the game does.
+//!   Structs derived with serde instead give a "duplicate field" error.
+//!
+//! # Multiple documents
+//!
+//! Some sources, such as the Minecraft network protocol, put several root
+//! compounds back to back. [`Deserializer::into_iter`] deserializes each in
+//! turn, stopping at the end of the input.
+//!
+//! ```rust
+//! # use fastnbt::{de::Deserializer, nbt, DeOpts, Value};
+//! let mut input = fastnbt::to_bytes(&nbt!({"a": 1})).unwrap();
+//! input.extend(fastnbt::to_bytes(&nbt!({"b": "two"})).unwrap());
+//!
+//! let mut stream = Deserializer::from_bytes(&input, DeOpts::new()).into_iter::<Value>();
+//! assert_eq!(stream.next().unwrap().unwrap(), nbt!({"a": 1}));
+//! assert_eq!(stream.next().unwrap().unwrap(), nbt!({"b": "two"}));
+//! assert!(stream.next().is_none());
+//! assert_eq!(stream.byte_offset(), input.len());
+//! ```
+//!
 //! # Example Minecraft types
 //!
 //! This section demonstrates writing types for a few real Minecraft structures.
@@ -221,15 +314,25 @@
 //! }
 //! ```
 
-use std::borrow::Cow;
-use std::convert::{TryFrom, TryInto};
-use std::ops::Range;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::convert::{TryFrom, TryInto};
+use core::marker::PhantomData;
+use core::ops::Range;
 
-use crate::de_arrays::ArrayWrapperAccess;
+use crate::borrow::RAW_COMPOUND_TOKEN;
+use crate::de_arrays::{ArraySeqAccess, ArrayWrapperAccess};
 use crate::error::{Error, Result};
-use crate::{DeOpts, Tag};
-use byteorder::{BigEndian, ReadBytesExt};
-
+use crate::java_string;
+use crate::read::ReadSlice;
+use crate::value::{EMPTY_LIST_TOKEN, VALUE_TOKEN};
+use crate::{DeOpts, InvalidStrings, Tag};
+use byteorder::BigEndian;
+
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::IntoDeserializer;
 use serde::de::Unexpected;
 use serde::{de, forward_to_deserialize_any, serde_if_integer128};
 
@@ -240,6 +343,14 @@ pub struct Deserializer<'de> {
     pub(crate) input: InputHelper<'de>,
     layers: Vec<Layer>,
     last_hint: Option<&'static str>,
+    // Length of the tuple being deserialized, if the next value is for one.
+    tuple_len: Option<usize>,
+    // Whether the input starts with the payload of the root compound, rather
+    // than its tag and name. See `RawCompound`.
+    headless: bool,
+    // Offset up to which the input has already been checked by skipping
+    // over it, see the list case of `consume_value`.
+    checked_to: usize,
     pub(crate) opts: DeOpts,
 }
 
@@ -250,23 +361,138 @@ impl<'de> Deserializer<'de> {
     /// [`de`]: ./index.html
     pub fn from_bytes(input: &'de [u8], opts: DeOpts) -> Self {
         Self {
-            input: InputHelper(input),
+            input: InputHelper::new(input),
             layers: vec![],
             last_hint: None,
+            tuple_len: None,
+            headless: false,
+            checked_to: 0,
             opts,
         }
     }
+
+    /// Create a Deserializer for input that is the payload of a compound, ie
+    /// its entries and end tag without the compound's own tag and name.
+    pub(crate) fn from_compound_payload(input: &'de [u8], opts: DeOpts) -> Self {
+        Self {
+            headless: true,
+            ..Self::from_bytes(input, opts)
+        }
+    }
+
+    /// Turn this into an iterator that deserializes each of several root
+    /// compounds placed one after the other in the input. See
+    /// [`StreamDeserializer`].
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter<T>(self) -> StreamDeserializer<'de, T>
+    where
+        T: de::Deserialize<'de>,
+    {
+        StreamDeserializer::new(self)
+    }
+
+    /// The number of bytes of input consumed so far. After an error this is
+    /// how far into the input the deserializer got.
+    pub fn byte_offset(&self) -> usize {
+        self.input.offset()
+    }
+
+    /// Check that another compound or list can be entered without going past
+    /// the maximum depth.
+    fn check_depth(&self) -> Result<()> {
+        if self.layers.len() >= self.opts.max_depth {
+            return Err(Error::max_depth());
+        }
+        Ok(())
+    }
+
+    /// How many more compounds or lists can be entered, for ignoring values.
+    fn remaining_depth(&self) -> usize {
+        self.opts.max_depth.saturating_sub(self.layers.len())
+    }
+}
+
+/// Iterator that deserializes a `T` from each of several root compounds
+/// placed back to back, as used by the Minecraft network protocol. Create one
+/// with [`Deserializer::into_iter`].
+///
+/// Iteration stops cleanly at the end of the input. If a document fails to
+/// deserialize the error is returned and iteration stops, since where the next
+/// document starts cannot be known. [`byte_offset`][Self::byte_offset] still
+/// says how much of the input was deserialized successfully.
+pub struct StreamDeserializer<'de, T> {
+    de: Deserializer<'de>,
+    offset: usize,
+    failed: bool,
+    output: PhantomData<T>,
+}
+
+impl<'de, T> StreamDeserializer<'de, T>
+where
+    T: de::Deserialize<'de>,
+{
+    /// Create a stream deserializer from a deserializer. Usually you would use
+    /// [`Deserializer::into_iter`] instead.
+    pub fn new(de: Deserializer<'de>) -> Self {
+        Self {
+            offset: de.byte_offset(),
+            de,
+            failed: false,
+            output: PhantomData,
+        }
+    }
+
+    /// The number of bytes of input that have been deserialized into a `T`
+    /// successfully. This is the offset that the next document starts at, or
+    /// that the failing document started at after an error.
+    ///
+    /// For where in the failing document the error was, see
+    /// [`error_offset`][Self::error_offset].
+    pub fn byte_offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The number of bytes of input consumed when the last error happened, if
+    /// there was one.
+    pub fn error_offset(&self) -> Option<usize> {
+        self.failed.then(|| self.de.byte_offset())
+    }
+}
+
+impl<'de, T> Iterator for StreamDeserializer<'de, T>
+where
+    T: de::Deserialize<'de>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.de.input.0.is_empty() {
+            return None;
+        }
+
+        match T::deserialize(&mut self.de) {
+            Ok(t) => {
+                self.offset = self.de.byte_offset();
+                Some(Ok(t))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
 }
 
+impl<'de, T> core::iter::FusedIterator for StreamDeserializer<'de, T> where T: de::Deserialize<'de> {}
+
 enum Stage {
     Tag,
-    Name,
     Value,
 }
 
 enum Layer {
     List {
-        remaining_elements: i32, // would make more sense as usize, but format is i32.
+        remaining_elements: usize,
         element_tag: Tag,
     },
     Compound {
@@ -279,7 +505,9 @@ enum Layer {
 /// input. If we wrote the helper functions as part of the Deserializer impl, it
 /// would force borrowing the entire deserializer mutably. This helper allows us
 /// to borrow just the input, making us free to also borrow/mutate the layers.
-pub(crate) struct InputHelper<'de>(pub(crate) &'de [u8]);
+/// The input still to be read, and the length of the whole input for working
+/// out how much has been consumed.
+pub(crate) struct InputHelper<'de>(pub(crate) &'de [u8], usize);
 
 fn visit_cow_str<'de, V>(v: V, s: Cow<'de, str>) -> Result<V::Value>
 where
@@ -297,16 +525,36 @@ where
 {
     let last_hint = de.last_hint;
     de.last_hint = None;
+    let tuple_len = de.tuple_len.take();
+
+    // Fixed size arrays and tuples are read straight from the elements of NBT
+    // arrays, and must be the same length.
+    if let (Some(len), Tag::ByteArray | Tag::IntArray | Tag::LongArray) = (tuple_len, tag) {
+        let size = de.input.consume_list_size()?;
+        if size != len {
+            return Err(Error::bespoke(format!(
+                "expected array of {}, {} has {}",
+                len, tag, size
+            )));
+        }
+
+        return visitor.visit_seq(ArraySeqAccess::new(de, tag, size)?);
+    }
 
     match tag {
         Tag::Byte => visitor.visit_i8(de.input.0.read_i8()?),
         Tag::Short => visitor.visit_i16(de.input.0.read_i16::<BigEndian>()?),
         Tag::Int => visitor.visit_i32(de.input.0.read_i32::<BigEndian>()?),
         Tag::Long => visitor.visit_i64(de.input.0.read_i64::<BigEndian>()?),
-        Tag::String => visit_cow_str(visitor, de.input.consume_size_prefixed_string()?),
+        Tag::String => visit_cow_str(
+            visitor,
+            de.input
+                .consume_size_prefixed_string(de.opts.invalid_strings)?,
+        ),
         Tag::Float => visitor.visit_f32(de.input.consume_float()?),
         Tag::Double => visitor.visit_f64(de.input.consume_double()?),
         Tag::Compound => {
+            de.check_depth()?;
             de.layers.push(Layer::Compound {
                 current_tag: None,
                 stage: Stage::Tag,
@@ -315,36 +563,88 @@ where
             visitor.visit_map(CompoundAccess::new(de))
         }
         Tag::List => {
-            let element_tag = de.input.consume_tag()?;
-            let size = de.input.consume_list_size()?;
-
-            // End values have no payload. An end tag on it's own is the payload
-            // of an empty compound. A logical interpretation is that this could
-            // be a list of zero-sized units, but this mean an easy short
-            // malicious payload of a massive list taking up lots of memory (as
-            // the Value type's unit variant would not be zero sized.
+            // A list's header is the only thing saying what its elements are,
+            // so a corrupt one leads to the rest of the input being misread,
+            // failing somewhere far from the list. Skipping over the list
+            // first checks that its elements at least have the structure
+            // claimed, so that the error can point at the list. Lists inside
+            // are checked along with it, so only need it once.
             //
-            // Some old chunks store empty lists as as 'list of end', so if the
-            // size is zero we let it slide.
-            if element_tag == Tag::End && size != 0 {
-                return Err(Error::bespoke(
-                    "unexpected list of type 'end', which is not supported".into(),
-                ));
+            // Only lists of lists and compounds can be misread far past their
+            // end. Other elements have a fixed size or their own length, so
+            // reading them fails at the list anyway and they are not checked.
+            let nested = matches!(
+                de.input.0.first().map(|&t| Tag::try_from(t)),
+                Some(Ok(Tag::List | Tag::Compound))
+            );
+            if nested && de.input.offset() >= de.checked_to {
+                let mut probe = InputHelper(de.input.0, de.input.1);
+                probe.ignore_value(Tag::List, de.remaining_depth())?;
+                de.checked_to = probe.offset();
             }
 
-            if size as usize >= de.opts.max_seq_len {
+            let (element_tag, size) = de.input.consume_list_header()?;
+
+            if size >= de.opts.max_seq_len {
                 return Err(Error::bespoke(format!(
                     "size ({}) greater than max sequence length ({})",
                     size, de.opts.max_seq_len,
                 )));
             }
 
+            // Tuples can be shorter than the list, as long as the missing
+            // trailing elements are options.
+            let missing = match tuple_len {
+                Some(len) if size > len => {
+                    return Err(Error::bespoke(format!(
+                        "expected tuple of {}, list has {}",
+                        len, size
+                    )));
+                }
+                Some(len) => len - size,
+                None => 0,
+            };
+
+            de.check_depth()?;
+
+            // Values keep the element tag of empty lists, see Value::EmptyList.
+            if size == 0 && element_tag != Tag::End && last_hint == Some(VALUE_TOKEN) {
+                return visitor.visit_map(de::value::MapDeserializer::new(core::iter::once((
+                    EMPTY_LIST_TOKEN,
+                    element_tag as u8,
+                ))));
+            }
+
             de.layers.push(Layer::List {
                 remaining_elements: size,
                 element_tag,
             });
 
-            visitor.visit_seq(ListAccess::new(de, size))
+            let Some(len) = tuple_len else {
+                return visitor.visit_seq(ListAccess::new(de, size));
+            };
+
+            let depth = de.layers.len();
+            let value = visitor.visit_seq(ListAccess::new(de, size).for_tuple(len, missing))?;
+
+            // Tuple visitors stop after the last element rather than asking
+            // for the end of the list, so the layer may still be here.
+            if de.layers.len() == depth {
+                match de.layers.pop() {
+                    Some(Layer::List {
+                        remaining_elements: 0,
+                        ..
+                    }) => {}
+                    _ => {
+                        return Err(Error::bespoke(format!(
+                            "expected tuple of {}, list has {}",
+                            len, size
+                        )))
+                    }
+                }
+            }
+
+            Ok(value)
         }
         Tag::ByteArray => {
             if last_hint == Some("seq") {
@@ -357,11 +657,7 @@ where
             let size = de.input.consume_list_size()?;
 
             // visitor.visit_borrowed_bytes(bs)
-            visitor.visit_map(ArrayWrapperAccess::bytes(
-                de,
-                size.try_into()
-                    .map_err(|_| Error::bespoke("nbt array size was negative".to_string()))?,
-            )?)
+            visitor.visit_map(ArrayWrapperAccess::bytes(de, size)?)
         }
         Tag::IntArray => {
             if last_hint == Some("seq") {
@@ -372,11 +668,7 @@ where
             }
 
             let size = de.input.consume_list_size()?;
-            visitor.visit_map(ArrayWrapperAccess::ints(
-                de,
-                size.try_into()
-                    .map_err(|_| Error::bespoke("nbt array size was negative".to_string()))?,
-            )?)
+            visitor.visit_map(ArrayWrapperAccess::ints(de, size)?)
         }
         Tag::LongArray => {
             if last_hint == Some("seq") {
@@ -387,11 +679,7 @@ where
             }
 
             let size = de.input.consume_list_size()?;
-            visitor.visit_map(ArrayWrapperAccess::longs(
-                de,
-                size.try_into()
-                    .map_err(|_| Error::bespoke("nbt array size was negative".to_string()))?,
-            )?)
+            visitor.visit_map(ArrayWrapperAccess::longs(de, size)?)
         }
         // This would really only occur when we encounter a list where the
         // element type is 'End', but we specifically handle that case, so we
@@ -402,6 +690,48 @@ where
     }
 }
 
+/// Read an integral value that is being deserialized as a float, along with
+/// its tag. Gives `None` without reading anything for other tags, which are
+/// deserialized as they are.
+fn get_integral_for_float(de: &mut Deserializer, ty: &str) -> Result<Option<(Tag, i64)>> {
+    let tag = match de.layers.last() {
+        Some(Layer::Compound {
+            current_tag: Some(tag),
+            stage: Stage::Value,
+        }) => *tag,
+        Some(Layer::List { element_tag, .. }) => *element_tag,
+        _ => return Ok(None),
+    };
+
+    let value = match tag {
+        Tag::Byte => de.input.0.read_i8()? as i64,
+        Tag::Short => de.input.0.read_i16::<BigEndian>()? as i64,
+        Tag::Int => de.input.0.read_i32::<BigEndian>()? as i64,
+        Tag::Long => de.input.0.read_i64::<BigEndian>()?,
+        _ => return Ok(None),
+    };
+
+    if de.opts.strict_floats {
+        return Err(Error::bespoke(format!(
+            "deserialize {}: expected float or double, found {} {}",
+            ty, tag, value
+        )));
+    }
+
+    Ok(Some((tag, value)))
+}
+
+/// The variant index an integral value gives an enum, see "Enums" in the
+/// module docs.
+pub(crate) fn variant_index(tag: Tag, value: i64) -> Result<u32> {
+    u32::try_from(value).map_err(|_| {
+        Error::bespoke(format!(
+            "deserialize enum: {} {} is not a variant index",
+            tag, value
+        ))
+    })
+}
+
 fn get_i128_value<'de>(de: &mut Deserializer<'de>) -> Result<i128> {
     let tag = match de.layers.last() {
         Some(Layer::Compound { current_tag, .. }) => current_tag.as_ref().ok_or_else(|| {
@@ -432,6 +762,10 @@ fn get_i128_value<'de>(de: &mut Deserializer<'de>) -> Result<i128> {
 }
 
 impl<'de> InputHelper<'de> {
+    pub(crate) fn new(input: &'de [u8]) -> Self {
+        Self(input, input.len())
+    }
+
     // Safely get a subslice, erroring if there's not enough input.
     pub(crate) fn subslice(&self, r: Range<usize>) -> Result<&'de [u8]> {
         if r.end <= self.0.len() {
@@ -441,42 +775,46 @@ impl<'de> InputHelper<'de> {
         }
     }
 
-    fn consume_tag(&mut self) -> Result<Tag> {
+    fn offset(&self) -> usize {
+        self.1 - self.0.len()
+    }
+
+    /// Read the length prefix of a string, checking that the string fits in
+    /// what is left of the input before anything is done with it. A corrupt
+    /// length can claim up to 64 KiB. Unlike list lengths, string lengths are
+    /// unsigned, so can't be negative.
+    fn consume_string_len(&mut self) -> Result<usize> {
+        let offset = self.offset();
+        let len = self.0.read_u16::<BigEndian>()? as usize;
+        if len > self.0.len() {
+            return Err(Error::string_past_eof(offset, len, self.0.len()));
+        }
+        Ok(len)
+    }
+
+    pub(crate) fn consume_tag(&mut self) -> Result<Tag> {
         let tag_byte = self.0.read_u8()?;
         Tag::try_from(tag_byte).map_err(|_| Error::invalid_tag(tag_byte))
     }
 
-    fn consume_name(&mut self) -> Result<Cow<'de, str>> {
-        self.consume_size_prefixed_string()
+    fn consume_name(&mut self, policy: InvalidStrings) -> Result<Cow<'de, str>> {
+        self.consume_size_prefixed_string(policy)
     }
 
-    fn consume_size_prefixed_string(&mut self) -> Result<Cow<'de, str>> {
-        let len = self.0.read_u16::<BigEndian>()? as usize;
-        let str_data = self.subslice(0..len)?;
-        let s = cesu8::from_java_cesu8(str_data)
-            .map_err(|_| Error::nonunicode_string(&self.0[..len]))?;
-
-        self.0 = &self.0[len..];
-        Ok(s)
+    fn consume_size_prefixed_string(&mut self, policy: InvalidStrings) -> Result<Cow<'de, str>> {
+        let str_data = self.consume_size_prefixed_bytes()?;
+        java_string::decode_with_policy(str_data, policy)
+            .ok_or_else(|| Error::nonunicode_string(str_data))
     }
 
     fn ignore_size_prefixed_string(&mut self) -> Result<()> {
-        let len = self.0.read_u16::<BigEndian>()? as usize;
-        self.0 = &self.0[len..];
+        self.consume_size_prefixed_bytes()?;
         Ok(())
     }
 
-    fn consume_size_prefixed_bytes(&mut self) -> Result<&'de [u8]> {
-        let len = self.0.read_u16::<BigEndian>()? as usize;
-        let str_data = self.subslice(0..len)?;
-
-        self.0 = &self.0[len..];
-        Ok(str_data)
-    }
-
-    pub(crate) fn consume_bytes(&mut self, size: i32) -> Result<&'de [u8]> {
-        let size: usize = size.try_into().map_err(|_| Error::invalid_size(size))?;
-        self.consume_bytes_usize(size)
+    pub(crate) fn consume_size_prefixed_bytes(&mut self) -> Result<&'de [u8]> {
+        let len = self.consume_string_len()?;
+        self.consume_bytes_usize(len)
     }
 
     pub(crate) fn consume_bytes_usize(&mut self, size: usize) -> Result<&'de [u8]> {
@@ -485,44 +823,91 @@ impl<'de> InputHelper<'de> {
         Ok(bs)
     }
 
-    fn consume_list_size(&mut self) -> Result<i32> {
-        Ok(self.0.read_i32::<BigEndian>()?)
+    /// Read the length of a list or array. Lengths are signed in NBT, and a
+    /// negative length is an error rather than being cast to a huge one.
+    pub(crate) fn consume_list_size(&mut self) -> Result<usize> {
+        let offset = self.offset();
+        let size = self.0.read_i32::<BigEndian>()?;
+        usize::try_from(size).map_err(|_| Error::negative_size(offset, size))
+    }
+
+    /// Read the element tag and length of a list, erroring if they cannot be
+    /// right. Errors say where the list started.
+    pub(crate) fn consume_list_header(&mut self) -> Result<(Tag, usize)> {
+        let offset = self.offset();
+        self.read_list_header()
+            .map_err(|e| Error::malformed_list(offset, e))
+    }
+
+    fn read_list_header(&mut self) -> Result<(Tag, usize)> {
+        let element_tag = self.consume_tag()?;
+        let size = self.consume_list_size()?;
+
+        // End values have no payload. An end tag on it's own is the payload of
+        // an empty compound. A logical interpretation is that this could be a
+        // list of zero-sized units, but this mean an easy short malicious
+        // payload of a massive list taking up lots of memory (as the Value
+        // type's unit variant would not be zero sized.
+        //
+        // Some old chunks store empty lists as as 'list of end', so if the
+        // size is zero we let it slide.
+        if element_tag == Tag::End && size != 0 {
+            return Err(Error::bespoke(format!(
+                "{} elements of type 'end', which is not supported",
+                size
+            )));
+        }
+
+        self.check_list_fits(element_tag, size)?;
+        Ok((element_tag, size))
+    }
+
+    // A list header can claim any size up to i32::MAX. Every element takes at
+    // least some bytes of input, so we can reject sizes that could not
+    // possibly fit in what remains before anything gets allocated based on
+    // them, eg a Vec with that capacity.
+    pub(crate) fn check_list_fits(&self, element_tag: Tag, size: usize) -> Result<()> {
+        let plausible = self.0.len() / min_payload_size(element_tag).max(1);
+        if size > plausible {
+            return Err(Error::bespoke(format!(
+                "list of {} {} elements cannot fit in remaining {} bytes of input",
+                size,
+                element_tag,
+                self.0.len()
+            )));
+        }
+
+        Ok(())
     }
 
     fn consume_float(&mut self) -> Result<f32> {
-        Ok(self.0.read_f32::<BigEndian>()?)
+        self.0.read_f32::<BigEndian>()
     }
 
     fn consume_double(&mut self) -> Result<f64> {
-        Ok(self.0.read_f64::<BigEndian>()?)
+        self.0.read_f64::<BigEndian>()
     }
 
-    fn ignore_value(&mut self, tag: Tag) -> Result<()> {
+    /// Skip over a value. `depth` is how many more compounds or lists can be
+    /// entered. Nothing is decoded or allocated, only the lengths needed to
+    /// find the end of the value are read. This is what ignored fields cost,
+    /// so it matters when only a few fields of a chunk are wanted.
+    pub(crate) fn ignore_value(&mut self, tag: Tag, depth: usize) -> Result<()> {
+        if matches!(tag, Tag::Compound | Tag::List) && depth == 0 {
+            return Err(Error::max_depth());
+        }
+
         match tag {
-            Tag::Byte => {
-                self.0.read_i8()?;
-            }
-            Tag::Short => {
-                self.0.read_i16::<BigEndian>()?;
-            }
-            Tag::Int => {
-                self.0.read_i32::<BigEndian>()?;
-            }
-            Tag::Long => {
-                self.0.read_i64::<BigEndian>()?;
-            }
-            Tag::Float => {
-                self.consume_float()?;
-            }
-            Tag::Double => {
-                self.consume_double()?;
+            Tag::Byte | Tag::Short | Tag::Int | Tag::Long | Tag::Float | Tag::Double => {
+                // Numbers are skipped without being read.
+                self.consume_bytes_usize(min_payload_size(tag))?;
             }
             Tag::String => {
                 self.ignore_size_prefixed_string()?;
             }
             Tag::ByteArray => {
                 let size = self.consume_list_size()?;
-                self.consume_bytes(size)?;
+                self.consume_bytes_usize(size)?;
             }
             Tag::IntArray => {
                 let size = self.consume_list_size()?;
@@ -544,15 +929,30 @@ impl<'de> InputHelper<'de> {
 
                     // consume the name.
                     self.ignore_size_prefixed_string()?;
-                    self.ignore_value(tag)?;
+
+                    // Most fields are numbers, so skip those here rather
+                    // than going round again.
+                    match fixed_payload_size(tag) {
+                        Some(size) => {
+                            self.consume_bytes_usize(size)?;
+                        }
+                        None => self.ignore_value(tag, depth - 1)?,
+                    }
                 }
             }
             Tag::List => {
-                let element_tag = self.consume_tag()?;
-                let size = self.consume_list_size()?;
-                for _ in 0..size {
-                    self.ignore_value(element_tag)?;
-                }
+                let offset = self.offset();
+                let (element_tag, size) = self.consume_list_header()?;
+
+                // Lists of numbers are skipped in one go, like arrays.
+                let elements = match fixed_payload_size(element_tag) {
+                    Some(width) => size
+                        .checked_mul(width)
+                        .ok_or_else(|| Error::bespoke("size too large".to_string()))
+                        .and_then(|len| self.consume_bytes_usize(len).map(drop)),
+                    None => (0..size).try_for_each(|_| self.ignore_value(element_tag, depth - 1)),
+                };
+                elements.map_err(|e| Error::malformed_list(offset, e))?;
             }
             Tag::End => {
                 // If we are trying to ignore a list of empty compounds, that
@@ -573,7 +973,7 @@ impl<'de> InputHelper<'de> {
 impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     type Error = Error;
 
-    forward_to_deserialize_any!(struct map identifier char i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 str string tuple);
+    forward_to_deserialize_any!(struct map identifier char i8 i16 i32 i64 u8 u16 u32 u64 str string);
 
     fn is_human_readable(&self) -> bool {
         false
@@ -588,13 +988,22 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 // No existing layers. This means we should be at the start of
                 // parsing, and we should be parsing a Compound. We need to get
                 // the tag and the following name and discard it.
-                let tag = self.input.consume_tag()?;
-                if tag != Tag::Compound {
-                    return Err(Error::no_root_compound());
+                if !core::mem::take(&mut self.headless) {
+                    let tag = self.input.consume_tag()?;
+                    if tag != Tag::Compound {
+                        return Err(Error::no_root_compound());
+                    }
+
+                    if !self.opts.unnamed_root {
+                        self.input.consume_name(self.opts.invalid_strings)?;
+                    }
                 }
 
-                self.input.consume_name()?;
+                // The hint was for the root compound, not its first value.
+                self.last_hint = None;
+                self.tuple_len = None;
 
+                self.check_depth()?;
                 self.layers.push(Layer::Compound {
                     current_tag: None,
                     stage: Stage::Tag,
@@ -612,11 +1021,10 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                         Stage::Tag => {
                             *current_tag = Some(self.input.consume_tag()?);
                             *stage = Stage::Value;
-                            return visit_cow_str(visitor, self.input.consume_name()?);
-                        }
-                        Stage::Name => {
-                            *stage = Stage::Value;
-                            return visit_cow_str(visitor, self.input.consume_name()?);
+                            return visit_cow_str(
+                                visitor,
+                                self.input.consume_name(self.opts.invalid_strings)?,
+                            );
                         }
                         Stage::Value => {
                             *stage = Stage::Tag;
@@ -651,14 +1059,65 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             )),
         }?;
 
-        match tag {
-            Tag::Byte => visitor.visit_bool(self.input.0.read_i8()? != 0),
-            Tag::Short => visitor.visit_bool(self.input.0.read_i16::<BigEndian>()? != 0),
-            Tag::Int => visitor.visit_bool(self.input.0.read_i32::<BigEndian>()? != 0),
-            Tag::Long => visitor.visit_bool(self.input.0.read_i64::<BigEndian>()? != 0),
-            _ => Err(Error::bespoke(
-                "deserialize bool: expected integral value".to_string(),
-            )),
+        let value = match tag {
+            Tag::Byte => self.input.0.read_i8()? as i64,
+            Tag::Short => self.input.0.read_i16::<BigEndian>()? as i64,
+            Tag::Int => self.input.0.read_i32::<BigEndian>()? as i64,
+            Tag::Long => self.input.0.read_i64::<BigEndian>()?,
+            _ => {
+                return Err(Error::bespoke(
+                    "deserialize bool: expected integral value".to_string(),
+                ))
+            }
+        };
+
+        if self.opts.strict_bools && (*tag != Tag::Byte || !matches!(value, 0 | 1)) {
+            return Err(Error::bespoke(format!(
+                "deserialize bool: expected byte of 0 or 1, found {} {}",
+                tag, value
+            )));
+        }
+
+        visitor.visit_bool(value != 0)
+    }
+
+    #[inline]
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match get_integral_for_float(self, "f32")? {
+            Some((tag, value)) => {
+                let float = value as f32;
+                if float as i128 != value as i128 {
+                    return Err(Error::bespoke(format!(
+                        "deserialize f32: {} {} cannot be represented exactly",
+                        tag, value
+                    )));
+                }
+                visitor.visit_f32(float)
+            }
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match get_integral_for_float(self, "f64")? {
+            Some((tag, value)) => {
+                let float = value as f64;
+                if float as i128 != value as i128 {
+                    return Err(Error::bespoke(format!(
+                        "deserialize f64: {} {} cannot be represented exactly",
+                        tag, value
+                    )));
+                }
+                visitor.visit_f64(float)
+            }
+            None => self.deserialize_any(visitor),
         }
     }
 
@@ -712,7 +1171,7 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
 
                 match el {
                     Tag::Byte => {
-                        let bs = self.input.consume_bytes(size)?;
+                        let bs = self.input.consume_bytes_usize(size)?;
                         visitor.visit_borrowed_bytes(bs)
                     }
                     Tag::Short => {
@@ -739,7 +1198,7 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             } => match tag {
                 Tag::ByteArray => {
                     let size = self.input.consume_list_size()?;
-                    let bs = self.input.consume_bytes(size)?;
+                    let bs = self.input.consume_bytes_usize(size)?;
                     visitor.visit_borrowed_bytes(bs)
                 }
                 Tag::IntArray => {
@@ -793,7 +1252,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             )),
         }?;
 
-        self.input.ignore_value(*tag)?;
+        let depth = self.remaining_depth();
+        self.input.ignore_value(*tag, depth)?;
         visitor.visit_unit()
     }
 
@@ -814,6 +1274,11 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             crate::BYTE_ARRAY_TOKEN => Tag::ByteArray,
             crate::INT_ARRAY_TOKEN => Tag::IntArray,
             crate::LONG_ARRAY_TOKEN => Tag::LongArray,
+            RAW_COMPOUND_TOKEN => Tag::Compound,
+            VALUE_TOKEN => {
+                self.last_hint = Some(VALUE_TOKEN);
+                return visitor.visit_newtype_struct(self);
+            }
             _ => return visitor.visit_newtype_struct(self),
         };
 
@@ -827,26 +1292,43 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             )),
         }?;
 
-        if target_tag == data_tag {
-            consume_value(self, visitor, target_tag)
-        } else {
-            Err(Error::bespoke(format!(
+        if target_tag != data_tag {
+            return Err(Error::bespoke(format!(
                 "expected {data_tag}, found {target_tag}"
-            )))
+            )));
         }
+
+        if name == RAW_COMPOUND_TOKEN {
+            // Skip over the compound, handing over the bytes skipped.
+            let start = self.input.0;
+            self.input
+                .ignore_value(Tag::Compound, self.remaining_depth())?;
+            let len = start.len() - self.input.0.len();
+            return visitor.visit_borrowed_bytes(&start[..len]);
+        }
+
+        consume_value(self, visitor, target_tag)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.tuple_len = Some(len);
+        self.deserialize_seq(visitor)
     }
 
     #[inline]
     fn deserialize_tuple_struct<V>(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        self.deserialize_tuple(len, visitor)
     }
 
     #[inline]
@@ -859,7 +1341,24 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_enum(UnitVariantAccess { de: self })
+        let tag = match self.layers.last() {
+            Some(Layer::Compound {
+                current_tag: Some(tag),
+                stage: Stage::Value,
+            }) => *tag,
+            Some(Layer::List { element_tag, .. }) => *element_tag,
+            _ => Tag::String,
+        };
+
+        let value = match tag {
+            Tag::Byte => self.input.0.read_i8()? as i64,
+            Tag::Short => self.input.0.read_i16::<BigEndian>()? as i64,
+            Tag::Int => self.input.0.read_i32::<BigEndian>()? as i64,
+            Tag::Long => self.input.0.read_i64::<BigEndian>()?,
+            _ => return visitor.visit_enum(UnitVariantAccess { de: self }),
+        };
+
+        visitor.visit_enum(variant_index(tag, value)?.into_deserializer())
     }
 
     #[inline]
@@ -880,7 +1379,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 current_tag: Some(tag),
                 stage: Stage::Value,
             } => {
-                self.input.ignore_value(*tag)?;
+                let depth = self.remaining_depth();
+                self.input.ignore_value(*tag, depth)?;
             }
             Layer::Compound {
                 current_tag: _,
@@ -908,11 +1408,39 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     }
 }
 
-fn try_size(size: i32, multiplier: usize) -> Result<usize> {
-    let size: usize = size
-        .try_into()
-        .map_err(|_| Error::bespoke("size was negative".to_string()))?;
+/// The smallest number of bytes the payload of a value with the given tag can
+/// take up in the input.
+fn min_payload_size(tag: Tag) -> usize {
+    match tag {
+        Tag::End => 0,
+        Tag::Byte => 1,
+        Tag::Short => 2,
+        Tag::Int => 4,
+        Tag::Long => 8,
+        Tag::Float => 4,
+        Tag::Double => 8,
+        Tag::String => 2,
+        // Element tag and size.
+        Tag::List => 5,
+        // Just the end tag.
+        Tag::Compound => 1,
+        Tag::ByteArray | Tag::IntArray | Tag::LongArray => 4,
+    }
+}
+
+/// The size of the payload of a value with the given tag, if every value with
+/// that tag is the same size.
+fn fixed_payload_size(tag: Tag) -> Option<usize> {
+    match tag {
+        Tag::Byte => Some(1),
+        Tag::Short => Some(2),
+        Tag::Int | Tag::Float => Some(4),
+        Tag::Long | Tag::Double => Some(8),
+        _ => None,
+    }
+}
 
+fn try_size(size: usize, multiplier: usize) -> Result<usize> {
     size.checked_mul(multiplier)
         .ok_or_else(|| Error::bespoke("size too large".to_string()))
 }
@@ -943,16 +1471,17 @@ impl<'a, 'de> de::MapAccess<'de> for CompoundAccess<'a, 'de> {
             return Ok(None);
         }
 
+        let name = self.de.input.consume_name(self.de.opts.invalid_strings)?;
+
         // Set the current layers next expected type.
         // TODO: Can probably do this by mutating top layer rather than pop/push.
         self.de.layers.pop().unwrap();
         self.de.layers.push(Layer::Compound {
             current_tag: Some(tag),
-            stage: Stage::Name,
+            stage: Stage::Value,
         });
 
-        // Should just be ready to read the name.
-        seed.deserialize(&mut *self.de).map(Some)
+        seed.deserialize(NameDeserializer { name }).map(Some)
     }
 
     #[inline]
@@ -964,14 +1493,177 @@ impl<'a, 'de> de::MapAccess<'de> for CompoundAccess<'a, 'de> {
     }
 }
 
+/// Deserializer for the name of a compound entry, ie a struct field or map
+/// key. Names in NBT are always strings, so only types that can be created from
+/// a string are supported: strings themselves, unit variant enums, and
+/// newtypes of these.
+struct NameDeserializer<'de> {
+    name: Cow<'de, str>,
+}
+
+macro_rules! unsupported_name {
+    ($($method:ident => $what:literal,)*) => {
+        $(
+            fn $method<V>(self, _visitor: V) -> Result<V::Value>
+            where
+                V: de::Visitor<'de>,
+            {
+                Err(Error::unsupported_key($what, &self.name))
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for NameDeserializer<'de> {
+    type Error = Error;
+
+    forward_to_deserialize_any!(char str string identifier);
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visit_cow_str(visitor, self.name)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.name {
+            Cow::Borrowed(s) => visitor.visit_borrowed_bytes(s.as_bytes()),
+            Cow::Owned(s) => visitor.visit_byte_buf(s.into_bytes()),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.name {
+            Cow::Borrowed(s) => visitor.visit_enum(BorrowedStrDeserializer::new(s)),
+            Cow::Owned(s) => visitor.visit_enum(s.into_deserializer()),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    unsupported_name! {
+        deserialize_bool => "bool",
+        deserialize_i8 => "i8",
+        deserialize_i16 => "i16",
+        deserialize_i32 => "i32",
+        deserialize_i64 => "i64",
+        deserialize_u8 => "u8",
+        deserialize_u16 => "u16",
+        deserialize_u32 => "u32",
+        deserialize_u64 => "u64",
+        deserialize_f32 => "f32",
+        deserialize_f64 => "f64",
+        deserialize_unit => "unit",
+        deserialize_seq => "sequence",
+        deserialize_map => "map",
+    }
+
+    serde_if_integer128! {
+        unsupported_name! {
+            deserialize_i128 => "i128",
+            deserialize_u128 => "u128",
+        }
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::unsupported_key("unit struct", &self.name))
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::unsupported_key("tuple", &self.name))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::unsupported_key("tuple struct", &self.name))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::unsupported_key("struct", &self.name))
+    }
+}
+
 struct ListAccess<'a, 'de> {
     de: &'a mut Deserializer<'de>,
-    hint: i32,
+    hint: usize,
+    // Elements past the end of the list to give to a tuple, see MissingElement.
+    missing: usize,
+    tuple_len: usize,
 }
 
 impl<'a, 'de> ListAccess<'a, 'de> {
-    fn new(de: &'a mut Deserializer<'de>, hint: i32) -> Self {
-        Self { de, hint }
+    fn new(de: &'a mut Deserializer<'de>, hint: usize) -> Self {
+        Self {
+            de,
+            hint,
+            missing: 0,
+            tuple_len: 0,
+        }
+    }
+
+    fn for_tuple(mut self, tuple_len: usize, missing: usize) -> Self {
+        self.tuple_len = tuple_len;
+        self.missing = missing;
+        self
     }
 }
 
@@ -979,7 +1671,7 @@ impl<'a, 'de> de::SeqAccess<'de> for ListAccess<'a, 'de> {
     type Error = Error;
 
     fn size_hint(&self) -> Option<usize> {
-        self.hint.try_into().ok()
+        Some(self.hint)
     }
 
     #[inline]
@@ -1002,6 +1694,13 @@ impl<'a, 'de> de::SeqAccess<'de> for ListAccess<'a, 'de> {
                     *remaining_elements -= 1;
                     let val = seed.deserialize(&mut *self.de)?;
                     Ok(Some(val))
+                } else if self.missing > 0 {
+                    self.missing -= 1;
+                    let val = seed.deserialize(MissingElement {
+                        tuple_len: self.tuple_len,
+                        list_len: self.hint,
+                    })?;
+                    Ok(Some(val))
                 } else {
                     self.de.layers.pop();
                     Ok(None)
@@ -1018,6 +1717,40 @@ impl<'a, 'de> de::SeqAccess<'de> for ListAccess<'a, 'de> {
     }
 }
 
+/// An element of a tuple past the end of the list it is being deserialized
+/// from. Deserializes as `None` if it is an option, otherwise errors.
+struct MissingElement {
+    tuple_len: usize,
+    list_len: usize,
+}
+
+impl<'de> de::Deserializer<'de> for MissingElement {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::bespoke(format!(
+            "expected tuple of {}, list has {}",
+            self.tuple_len, self.list_len
+        )))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
 struct UnitVariantAccess<'a, 'de> {
     de: &'a mut Deserializer<'de>,
 }
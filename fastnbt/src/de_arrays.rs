@@ -1,9 +1,16 @@
+use alloc::format;
+use alloc::string::ToString;
+
+use byteorder::BigEndian;
 use serde::de;
 use serde::de::value::BorrowedBytesDeserializer;
 use serde::de::value::BorrowedStrDeserializer;
+use serde::forward_to_deserialize_any;
 
 use crate::de::Deserializer;
 use crate::error::{Error, Result};
+use crate::read::ReadSlice;
+use crate::Tag;
 use crate::BYTE_ARRAY_TOKEN;
 use crate::INT_ARRAY_TOKEN;
 use crate::LONG_ARRAY_TOKEN;
@@ -80,3 +87,96 @@ impl<'a, 'de> de::MapAccess<'de> for ArrayWrapperAccess<'a, 'de> {
         seed.deserialize(dz)
     }
 }
+
+/// Access to the elements of an NBT array one at a time, for deserializing
+/// into fixed size arrays and tuples.
+pub(crate) struct ArraySeqAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    element_tag: Tag,
+    remaining: usize,
+}
+
+impl<'a, 'de> ArraySeqAccess<'a, 'de> {
+    pub(crate) fn new(de: &'a mut Deserializer<'de>, array_tag: Tag, size: usize) -> Result<Self> {
+        let element_tag = match array_tag {
+            Tag::ByteArray => Tag::Byte,
+            Tag::IntArray => Tag::Int,
+            Tag::LongArray => Tag::Long,
+            _ => {
+                return Err(Error::bespoke(format!(
+                    "expected NBT array, found {array_tag}"
+                )))
+            }
+        };
+        de.input.check_list_fits(element_tag, size)?;
+
+        Ok(Self {
+            de,
+            element_tag,
+            remaining: size,
+        })
+    }
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for ArraySeqAccess<'a, 'de> {
+    type Error = Error;
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(ArrayElement {
+            input: &mut self.de.input.0,
+            tag: self.element_tag,
+        })
+        .map(Some)
+    }
+}
+
+/// A single element of an NBT array.
+struct ArrayElement<'a, 'de> {
+    input: &'a mut &'de [u8],
+    tag: Tag,
+}
+
+impl<'a, 'de> de::Deserializer<'de> for ArrayElement<'a, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.tag {
+            Tag::Byte => visitor.visit_i8(self.input.read_i8()?),
+            Tag::Int => visitor.visit_i32(self.input.read_i32::<BigEndian>()?),
+            _ => visitor.visit_i64(self.input.read_i64::<BigEndian>()?),
+        }
+    }
+
+    /// Bytes of a ByteArray are often unsigned, eg biome IDs, so can be read as
+    /// u8 whatever their sign.
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.tag {
+            Tag::Byte => visitor.visit_u8(self.input.read_u8()?),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
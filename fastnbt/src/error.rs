@@ -1,24 +1,66 @@
 //! Contains the Error and Result type used by the deserializer.
-use std::fmt::Display;
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+};
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use std::{io, sync::Arc};
 
 /// Various errors that can occur during deserialization.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Error(String);
+///
+/// Errors caused by IO keep the [`io::Error`], see
+/// [`io_error`][Self::io_error]. It is also the error's
+/// [`source`][std::error::Error::source]. Without the `std` feature there is
+/// no IO, and errors are only their message.
+#[derive(Debug, Clone)]
+pub struct Error {
+    msg: String,
+    offset: Option<usize>,
+    // Shared so the error can stay Clone.
+    #[cfg(feature = "std")]
+    io: Option<Arc<io::Error>>,
+}
 
 /// Convenience type for Result.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.io.as_deref().map(|e| e as _)
+    }
+}
+
+// serde's stand in for `std::error::Error` without std.
+#[cfg(not(feature = "std"))]
+impl serde::de::StdError for Error {}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
 
-impl std::error::Error for Error {}
+/// Errors are equal if they have the same message, and the same kind of IO
+/// error if they were caused by one.
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "std")]
+        if self.io_error().map(|e| e.kind()) != other.io_error().map(|e| e.kind()) {
+            return false;
+        }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.0)
+        self.msg == other.msg
     }
 }
 
+impl Eq for Error {}
+
 impl serde::de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
-        Error(msg.to_string())
+        Error::new(msg.to_string())
     }
 }
 
@@ -28,41 +70,137 @@ impl serde::ser::Error for Error {
     where
         T: Display,
     {
-        Error(msg.to_string())
+        Error::new(msg.to_string())
     }
 }
 
-impl From<std::io::Error> for Error {
-    fn from(e: std::io::Error) -> Self {
-        Error(format!("io error: {}", e))
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error {
+            msg: format!("io error: {}", e),
+            offset: None,
+            io: Some(Arc::new(e)),
+        }
     }
 }
 
 impl Error {
+    fn new(msg: String) -> Error {
+        Error {
+            msg,
+            offset: None,
+            #[cfg(feature = "std")]
+            io: None,
+        }
+    }
+
+    /// The IO error that caused this error, if there was one. For example an
+    /// error of kind [`UnexpectedEof`][io::ErrorKind::UnexpectedEof] when
+    /// reading NBT that was cut short.
+    #[cfg(feature = "std")]
+    pub fn io_error(&self) -> Option<&io::Error> {
+        self.io.as_deref()
+    }
+
+    /// Where in the input the problem is, for errors that know. This is the
+    /// offset of a bad length, or of the header of a malformed list. Nothing in
+    /// NBT says where one value ends and the next starts, so a corrupt list can
+    /// otherwise go unnoticed until well past it.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
     pub(crate) fn invalid_tag(tag: u8) -> Error {
-        Error(format!("invalid nbt tag value: {}", tag))
+        Error::new(format!("invalid nbt tag value: {}", tag))
     }
 
-    pub(crate) fn invalid_size(size: i32) -> Error {
-        Error(format!("invalid nbt list/array size: {}", size))
+    /// A list or array whose length prefix, at `offset`, is negative.
+    pub(crate) fn negative_size(offset: usize, size: i32) -> Error {
+        Error {
+            offset: Some(offset),
+            ..Error::new(format!(
+                "invalid nbt list/array size at offset {}: {} is negative",
+                offset, size
+            ))
+        }
     }
 
     pub(crate) fn no_root_compound() -> Error {
-        Error("invalid nbt: no root compound".to_owned())
+        Error::new("invalid nbt: no root compound".to_owned())
     }
 
     pub(crate) fn nonunicode_string(data: &[u8]) -> Error {
-        Error(format!(
+        Error::new(format!(
             "invalid nbt string: nonunicode: {}",
             String::from_utf8_lossy(data)
         ))
     }
 
     pub(crate) fn unexpected_eof() -> Error {
-        Error("eof: unexpectedly ran out of input".to_owned())
+        Error::new("eof: unexpectedly ran out of input".to_owned())
+    }
+
+    /// Input that ran out part way through a value, as `Read::read_exact`
+    /// gives. The IO error is of kind
+    /// [`UnexpectedEof`][io::ErrorKind::UnexpectedEof].
+    pub(crate) fn read_past_eof() -> Error {
+        #[cfg(feature = "std")]
+        return io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer").into();
+
+        #[cfg(not(feature = "std"))]
+        Error::new("io error: failed to fill whole buffer".to_owned())
+    }
+
+    /// A string whose length prefix, at `offset`, claims more bytes than the
+    /// `remaining` input has. The IO error is of kind
+    /// [`UnexpectedEof`][io::ErrorKind::UnexpectedEof], the same as for other
+    /// input that is cut short.
+    pub(crate) fn string_past_eof(offset: usize, len: usize, remaining: usize) -> Error {
+        let msg = format!(
+            "eof: string at offset {} is {} bytes long, but only {} bytes of input remain",
+            offset, len, remaining
+        );
+        Error {
+            offset: Some(offset),
+            #[cfg(feature = "std")]
+            io: Some(Arc::new(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                msg.clone(),
+            ))),
+            msg,
+        }
+    }
+
+    /// An error found reading the list whose header is at `offset`. Errors
+    /// that already know where they are, such as from a list nested inside,
+    /// are kept as they are, so the innermost problem is the one reported.
+    /// Going too deep is not the list's fault, so is not wrapped either.
+    pub(crate) fn malformed_list(offset: usize, inner: Error) -> Error {
+        if inner.offset.is_some() || inner == Error::max_depth() {
+            return inner;
+        }
+
+        Error {
+            msg: format!("list at offset {:#x} is malformed: {}", offset, inner.msg),
+            offset: Some(offset),
+            ..inner
+        }
+    }
+
+    pub(crate) fn max_depth() -> Error {
+        Error::new("compounds and lists nested deeper than max depth".to_owned())
+    }
+
+    pub(crate) fn unsupported_key(ty: &str, name: &str) -> Error {
+        Error::new(format!(
+            "cannot deserialize compound key {:?} as {}: keys are strings, \
+             so must be deserialized as a string, unit variant enum, or newtype of these",
+            name, ty
+        ))
     }
 
     pub(crate) fn bespoke(msg: String) -> Error {
-        Error(msg)
+        Error::new(msg)
     }
 }
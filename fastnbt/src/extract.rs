@@ -0,0 +1,227 @@
+use alloc::vec::Vec;
+
+use serde::Deserialize;
+
+use crate::{
+    de::{Deserializer, InputHelper},
+    error::{Error, Result},
+    java_string, DeOpts, Map, Tag, Value,
+};
+
+/// Get the values at some paths in NBT data, without deserializing anything
+/// else. Each path is a list of compound keys separated by dots, eg
+/// `Level.Status`, and can index into lists by number, eg `sections.0.Y`.
+///
+/// The data is walked once. Only compounds and lists on the way to a path are
+/// entered; everything else is skipped over the same way ignored fields are
+/// when deserializing a struct, without being decoded. Walking stops as soon
+/// as every path has been found, so anything after that in the input is not
+/// checked. This makes it much faster than deserializing a struct when only a
+/// few values are wanted from each of many chunks, eg when scanning a world.
+///
+/// The result has a value for each path in order, `None` if nothing is
+/// there. A path into an array, or through a value that is not a compound or
+/// list, finds nothing.
+///
+/// ```
+/// # use fastnbt::{nbt, Value};
+/// let chunk = fastnbt::to_bytes(&nbt!({
+///     "Level": { "Status": "full", "xPos": 5, "zPos": -2 },
+///     "sections": [{ "Y": -4i8 }, { "Y": -3i8 }],
+/// }))
+/// .unwrap();
+///
+/// let values = fastnbt::extract(&chunk, &["Level.Status", "sections.1.Y", "Level.yPos"]).unwrap();
+/// assert_eq!(values, [Some(nbt!("full")), Some(nbt!(-3i8)), None]);
+/// ```
+pub fn extract(input: &[u8], paths: &[&str]) -> Result<Vec<Option<Value>>> {
+    let paths: Vec<Vec<&str>> = paths.iter().map(|p| p.split('.').collect()).collect();
+    let wanted: Vec<Wanted> = paths
+        .iter()
+        .enumerate()
+        .map(|(path, segments)| Wanted {
+            path,
+            rest: segments,
+        })
+        .collect();
+
+    let mut extractor = Extractor {
+        input: InputHelper::new(input),
+        opts: DeOpts::default(),
+        values: alloc::vec![None; paths.len()],
+        settled: alloc::vec![false; paths.len()],
+        unsettled: paths.len(),
+    };
+
+    if extractor.input.consume_tag()? != Tag::Compound {
+        return Err(Error::no_root_compound());
+    }
+    extractor.input.consume_size_prefixed_bytes()?;
+
+    let depth = extractor.opts.max_depth;
+    extractor.compound(&wanted, depth)?;
+
+    Ok(extractor.values)
+}
+
+/// A path still being looked for, with the segments left to find.
+#[derive(Clone, Copy)]
+struct Wanted<'p> {
+    path: usize,
+    rest: &'p [&'p str],
+}
+
+struct Extractor<'de> {
+    input: InputHelper<'de>,
+    opts: DeOpts,
+    values: Vec<Option<Value>>,
+    // Paths that have been found, or that can no longer be.
+    settled: Vec<bool>,
+    unsettled: usize,
+}
+
+impl<'de> Extractor<'de> {
+    fn settle(&mut self, path: usize, value: Option<Value>) {
+        if !self.settled[path] {
+            self.settled[path] = true;
+            self.values[path] = value;
+            self.unsettled -= 1;
+        }
+    }
+
+    fn done(&self) -> bool {
+        self.unsettled == 0
+    }
+
+    /// The paths in `wanted` whose next segment is matched, with that segment
+    /// taken off.
+    fn step<'p>(&self, wanted: &[Wanted<'p>], matches: impl Fn(&str) -> bool) -> Vec<Wanted<'p>> {
+        wanted
+            .iter()
+            .filter(|w| !self.settled[w.path] && matches(w.rest[0]))
+            .map(|w| Wanted {
+                path: w.path,
+                rest: &w.rest[1..],
+            })
+            .collect()
+    }
+
+    /// Walk a value that every path in `wanted` leads to or into. `depth` is
+    /// how many more compounds or lists can be entered.
+    fn value(&mut self, tag: Tag, wanted: &[Wanted], depth: usize) -> Result<()> {
+        if wanted.iter().any(|w| w.rest.is_empty()) {
+            // Some path ends here, so the whole value is needed. Any paths
+            // going further in can be found in it.
+            let value = self.read_value(tag, depth)?;
+            for w in wanted {
+                self.settle(w.path, lookup(&value, w.rest).cloned());
+            }
+            return Ok(());
+        }
+
+        match tag {
+            Tag::Compound => self.compound(wanted, depth),
+            Tag::List => self.list(wanted, depth),
+            _ => {
+                for w in wanted {
+                    self.settle(w.path, None);
+                }
+                self.input.ignore_value(tag, depth)
+            }
+        }
+    }
+
+    fn compound(&mut self, wanted: &[Wanted], depth: usize) -> Result<()> {
+        if depth == 0 {
+            return Err(Error::max_depth());
+        }
+
+        while !self.done() {
+            let tag = self.input.consume_tag()?;
+            if tag == Tag::End {
+                // Anything not found in the compound isn't there.
+                for w in wanted {
+                    self.settle(w.path, None);
+                }
+                break;
+            }
+
+            let name = self.input.consume_size_prefixed_bytes()?;
+            let next = self.step(wanted, |key| key_matches(name, key));
+
+            if next.is_empty() {
+                self.input.ignore_value(tag, depth - 1)?;
+            } else {
+                self.value(tag, &next, depth - 1)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn list(&mut self, wanted: &[Wanted], depth: usize) -> Result<()> {
+        if depth == 0 {
+            return Err(Error::max_depth());
+        }
+
+        let (element_tag, size) = self.input.consume_list_header()?;
+
+        for i in 0..size {
+            if self.done() {
+                return Ok(());
+            }
+
+            let next = self.step(wanted, |index| index.parse::<usize>() == Ok(i));
+            if next.is_empty() {
+                self.input.ignore_value(element_tag, depth - 1)?;
+            } else {
+                self.value(element_tag, &next, depth - 1)?;
+            }
+        }
+
+        for w in wanted {
+            self.settle(w.path, None);
+        }
+
+        Ok(())
+    }
+
+    /// Deserialize the value at the start of the input as a [`Value`].
+    fn read_value(&mut self, tag: Tag, depth: usize) -> Result<Value> {
+        let start = self.input.0;
+        self.input.ignore_value(tag, depth)?;
+        let payload = &start[..start.len() - self.input.0.len()];
+
+        // Deserialize it as the only entry of a compound, with an empty name.
+        let mut entry = Vec::with_capacity(payload.len() + 4);
+        entry.extend_from_slice(&[u8::from(tag), 0, 0]);
+        entry.extend_from_slice(payload);
+        entry.push(u8::from(Tag::End));
+
+        let mut de = Deserializer::from_compound_payload(&entry, self.opts.clone());
+        let mut compound = Map::<alloc::string::String, Value>::deserialize(&mut de)?;
+        compound
+            .remove("")
+            .ok_or_else(|| Error::bespoke("extracted value went missing".into()))
+    }
+}
+
+/// Whether a compound key from the input, in Java CESU-8, is `key`.
+fn key_matches(name: &[u8], key: &str) -> bool {
+    // CESU-8 and UTF-8 are the same for ASCII, except for the null character
+    // which CESU-8 never encodes as a single byte.
+    if name.is_ascii() {
+        return name == key.as_bytes();
+    }
+
+    java_string::decode(name).as_deref() == Some(key)
+}
+
+/// Follow the rest of a path through a value that was read whole.
+fn lookup<'v>(value: &'v Value, rest: &[&str]) -> Option<&'v Value> {
+    rest.iter().try_fold(value, |value, segment| match value {
+        Value::Compound(map) => map.get(*segment),
+        Value::List(list) => list.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
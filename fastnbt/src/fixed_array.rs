@@ -0,0 +1,85 @@
+//! (De)serialize arrays of any fixed size, for use with `#[serde(with =
+//! "fastnbt::fixed_array")]`. Serde itself only supports arrays of up to 32
+//! elements, which rules out eg the 37 longs of a heightmap or the 1024 bytes
+//! of a legacy biome array.
+//!
+//! Arrays can be deserialized from an NBT ByteArray, IntArray or LongArray, or
+//! from a List. The length must match exactly.
+//!
+//! ```
+//! # use serde::Deserialize;
+//! # use fastnbt::{nbt, LongArray};
+//! #[derive(Deserialize)]
+//! struct Heightmaps {
+//!     #[serde(rename = "WORLD_SURFACE", with = "fastnbt::fixed_array")]
+//!     world_surface: [i64; 37],
+//! }
+//!
+//! let surface = LongArray::new(vec![0; 37]);
+//! let nbt = fastnbt::to_bytes(&nbt!({ "WORLD_SURFACE": surface })).unwrap();
+//! let heightmaps: Heightmaps = fastnbt::from_bytes(&nbt).unwrap();
+//! assert_eq!(heightmaps.world_surface, [0; 37]);
+//! ```
+//!
+//! Arrays are serialized as a List, like arrays serde supports itself. Use
+//! [`ByteArray`][crate::ByteArray], [`IntArray`][crate::IntArray] or
+//! [`LongArray`][crate::LongArray] to write an NBT array.
+
+use alloc::vec::Vec;
+use core::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, Deserialize, Deserializer, SeqAccess, Visitor},
+    ser::{Serialize, SerializeTuple, Serializer},
+};
+
+pub fn serialize<S, T, const N: usize>(array: &[T; N], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    let mut tuple = serializer.serialize_tuple(N)?;
+    for element in array {
+        tuple.serialize_element(element)?;
+    }
+    tuple.end()
+}
+
+pub fn deserialize<'de, D, T, const N: usize>(deserializer: D) -> Result<[T; N], D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    deserializer.deserialize_tuple(N, ArrayVisitor(PhantomData))
+}
+
+struct ArrayVisitor<T, const N: usize>(PhantomData<T>);
+
+impl<'de, T, const N: usize> Visitor<'de> for ArrayVisitor<T, N>
+where
+    T: Deserialize<'de>,
+{
+    type Value = [T; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an array of {}", N)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut elements = Vec::with_capacity(N);
+        while let Some(element) = seq.next_element()? {
+            if elements.len() == N {
+                return Err(de::Error::invalid_length(N + 1, &self));
+            }
+            elements.push(element);
+        }
+
+        let len = elements.len();
+        elements
+            .try_into()
+            .map_err(|_| de::Error::invalid_length(len, &self))
+    }
+}
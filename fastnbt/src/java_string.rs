@@ -0,0 +1,210 @@
+//! Encoding and decoding of Java's modified UTF-8 (CESU-8), which NBT uses for
+//! all strings.
+//!
+//! This differs from UTF-8 in two ways. Characters outside of the Basic
+//! Multilingual Plane are written as a surrogate pair, each half encoded as
+//! three bytes, rather than as four bytes. And the null character is written
+//! as the two bytes `C0 80`, so that strings never contain a zero byte.
+
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+use alloc::{borrow::Cow, string::String};
+
+use crate::InvalidStrings;
+
+/// Decode a string in Java's CESU-8 form.
+///
+/// The vast majority of strings in NBT are plain ASCII: field names, block
+/// names, property values and so on. ASCII is represented identically in
+/// CESU-8 and UTF-8, so we check for it with a single scan and borrow the data
+/// directly. Anything else goes through the full conversion.
+pub(crate) fn decode(data: &[u8]) -> Option<Cow<'_, str>> {
+    if data.is_ascii() {
+        // SAFETY: ASCII is always valid UTF-8.
+        let s = unsafe { core::str::from_utf8_unchecked(data) };
+        return Some(Cow::Borrowed(s));
+    }
+
+    // Strings that are already valid UTF-8 are accepted as they are, even
+    // though Java would not write a null or a four byte character like that.
+    if let Ok(s) = core::str::from_utf8(data) {
+        return Some(Cow::Borrowed(s));
+    }
+
+    decode_cesu8(data).map(Cow::Owned)
+}
+
+/// Decode CESU-8 that is not valid UTF-8, or `None` if it is not valid CESU-8
+/// either.
+fn decode_cesu8(data: &[u8]) -> Option<String> {
+    let mut s = String::with_capacity(data.len());
+    let mut rest = data;
+
+    while let Some((&first, tail)) = rest.split_first() {
+        let (c, len) = match (first, tail) {
+            (0, _) => return None,
+            (0x01..=0x7F, _) => (first as char, 1),
+            (0xC0, [0x80, ..]) => ('\0', 2),
+            (0xED, [hi @ 0xA0..=0xAF, hi2, 0xED, lo @ 0xB0..=0xBF, lo2, ..])
+                if is_cont(*hi2) && is_cont(*lo2) =>
+            {
+                (surrogate_pair(*hi, *hi2, *lo, *lo2)?, 6)
+            }
+            // Lone surrogates and four byte characters are not valid.
+            (0xED, [0xA0..=0xBF, ..]) | (0xF0.., _) => return None,
+            _ => {
+                let width = match first {
+                    0xC2..=0xDF => 2,
+                    0xE0..=0xEF => 3,
+                    _ => return None,
+                };
+                let bytes = rest.get(..width)?;
+                (core::str::from_utf8(bytes).ok()?.chars().next()?, width)
+            }
+        };
+
+        s.push(c);
+        rest = &rest[len..];
+    }
+
+    Some(s)
+}
+
+/// Encode a string in Java's CESU-8 form. Borrows the string if it is the
+/// same in both.
+///
+/// As for decoding, nearly every string is ASCII, which only differs if it has
+/// a null in it. Both are checked a word at a time, so this is close to a
+/// straight copy. Other strings fall back to a byte by byte check.
+///
+/// Debug builds check that every borrowed string has no characters the full
+/// conversion would change, without allocating. Builds with the
+/// `verify-strings` feature also compare against the full conversion, and
+/// panic if they differ.
+#[cfg(feature = "std")]
+pub(crate) fn encode(s: &str) -> Cow<'_, [u8]> {
+    let bytes = s.as_bytes();
+    let same = if bytes.is_ascii() {
+        !bytes.contains(&0)
+    } else {
+        !bytes.iter().any(|&b| b == 0 || b >= 0xF0)
+    };
+
+    if !same {
+        return Cow::Owned(encode_cesu8(s));
+    }
+
+    debug_assert!(
+        s.chars().all(|c| c != '\0' && c.len_utf16() == 1),
+        "CESU-8 fast path borrowed {s:?}, which needs converting"
+    );
+    if cfg!(feature = "verify-strings") {
+        assert_eq!(
+            encode_cesu8(s),
+            bytes,
+            "CESU-8 fast path disagrees with full conversion for {s:?}"
+        );
+    }
+    Cow::Borrowed(bytes)
+}
+
+/// Encode a string in Java's CESU-8 form, character by character.
+#[cfg(feature = "std")]
+fn encode_cesu8(s: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(s.len() + s.len() / 2);
+    for c in s.chars() {
+        match c {
+            '\0' => out.extend_from_slice(&[0xC0, 0x80]),
+            c if c.len_utf16() == 2 => {
+                let mut pair = [0; 2];
+                for half in c.encode_utf16(&mut pair) {
+                    let half = *half as u32;
+                    out.extend_from_slice(&[
+                        0xE0 | (half >> 12) as u8,
+                        0x80 | (half >> 6 & 0x3F) as u8,
+                        0x80 | (half & 0x3F) as u8,
+                    ]);
+                }
+            }
+            c => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+
+    out
+}
+
+/// The character a surrogate pair encoded as two three byte sequences stands
+/// for, given the second and third bytes of each.
+fn surrogate_pair(hi: u8, hi2: u8, lo: u8, lo2: u8) -> Option<char> {
+    let high = 0xD000 | (hi as u32 & 0x3F) << 6 | (hi2 as u32 & 0x3F);
+    let low = 0xD000 | (lo as u32 & 0x3F) << 6 | (lo2 as u32 & 0x3F);
+    char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+}
+
+/// Decode a string in Java's CESU-8 form, dealing with invalid data according
+/// to the given policy. Returns `None` only for [`InvalidStrings::Error`].
+pub(crate) fn decode_with_policy(data: &[u8], policy: InvalidStrings) -> Option<Cow<'_, str>> {
+    if let Some(s) = decode(data) {
+        return Some(s);
+    }
+
+    match policy {
+        InvalidStrings::Error => None,
+        InvalidStrings::ReplaceInvalid => Some(Cow::Owned(decode_replacing(data))),
+        InvalidStrings::Lossy => Some(String::from_utf8_lossy(data)),
+    }
+}
+
+/// Decode CESU-8, replacing each invalid sequence with U+FFFD. Valid parts are
+/// decoded as they would be normally, including surrogate pairs and Java's
+/// two byte null.
+fn decode_replacing(mut data: &[u8]) -> String {
+    let mut s = String::with_capacity(data.len());
+
+    while !data.is_empty() {
+        let err = match core::str::from_utf8(data) {
+            Ok(valid) => {
+                s.push_str(valid);
+                break;
+            }
+            Err(err) => err,
+        };
+
+        let (valid, rest) = data.split_at(err.valid_up_to());
+        // SAFETY: from_utf8 checked everything up to valid_up_to.
+        s.push_str(unsafe { core::str::from_utf8_unchecked(valid) });
+
+        // UTF-8 rejects the parts of CESU-8 that differ from it, so check for
+        // those before giving up on the sequence.
+        let skip = match rest {
+            [0xC0, 0x80, ..] => {
+                s.push('\0');
+                2
+            }
+            [0xED, hi @ 0xA0..=0xAF, hi2, 0xED, lo @ 0xB0..=0xBF, lo2, ..]
+                if is_cont(*hi2) && is_cont(*lo2) =>
+            {
+                let c = surrogate_pair(*hi, *hi2, *lo, *lo2);
+                s.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+                6
+            }
+            // A lone surrogate.
+            [0xED, 0xA0..=0xBF, cont, ..] if is_cont(*cont) => {
+                s.push(char::REPLACEMENT_CHARACTER);
+                3
+            }
+            _ => {
+                s.push(char::REPLACEMENT_CHARACTER);
+                err.error_len().unwrap_or(rest.len())
+            }
+        };
+
+        data = &rest[skip..];
+    }
+
+    s
+}
+
+fn is_cont(b: u8) -> bool {
+    b & 0xC0 == 0x80
+}
@@ -7,14 +7,14 @@
 //! * For a `serde_json`-like `Value` type see [`Value`].
 //! * To easily create values, see the [`nbt`] macro.
 //! * For NBT array types see [`ByteArray`], [`IntArray`], and [`LongArray`].
-//! * For zero-copy NBT array types see [`borrow`].
+//! * For zero-copy NBT array and string types see [`borrow`].
 //!
 //! Both this and related crates are under one [fastnbt Github
 //! repository](https://github.com/owengage/fastnbt).
 //!
 //! ```toml
 //! [dependencies]
-//! fastnbt = "2"
+//! fastnbt = "3"
 //! ```
 //!
 //! # Byte, Int and Long array types
@@ -33,6 +33,10 @@
 //!
 //! For versions that borrow their data, see [`borrow`].
 //!
+//! When the length is known, eg the 4 ints of a UUID, an array can also be
+//! deserialized into a Rust array or tuple of that length, such as `[i32; 4]`.
+//! Arrays longer than 32 need [`fixed_array`].
+//!
 //! An example of deserializing a section of a chunk:
 //!
 //! ```no_run
@@ -117,39 +121,128 @@
 //!# }
 //! ```
 //!
+//! # Extracting a few values
+//!
+//! To get only a handful of values out of some NBT, eg the status and
+//! position of every chunk in a world, [`extract`] takes their paths and
+//! skips over everything else. This avoids the overhead of deserializing
+//! even a small struct.
+//!
+//! # Helpers for common idioms
+//!
+//! The [`serde_helpers`] module has adapters for `#[serde(with = "...")]` for
+//! values Minecraft stores in a roundabout way, such as UUIDs as an IntArray.
+//!
 //! # `Read` based parser
 //!
 //! A lower level parser also exists in the `stream` module that only requires
 //! the `Read` trait on the input. This parser however doesn't support
 //! deserializing to Rust objects directly.
 //!
+//! # Faster hashing
+//!
+//! Compounds in a [`Value`] are stored in a [`Map`], which is a std `HashMap`
+//! by default. With the `fast-hash` feature enabled it uses the much faster
+//! FxHash instead, which speeds up parsing large amounts of NBT into `Value`s.
+//! FxHash is not resistant to HashDoS attacks, so only enable this if you
+//! trust the NBT you parse or do not care.
+//!
+//! # Checking string encoding
+//!
+//! NBT strings are in Java's modified UTF-8. When serializing, strings that
+//! are the same in both, which is nearly all of them, are written without any
+//! conversion. Debug builds check that none of these have characters that
+//! need converting. The `verify-strings` feature also checks each against the
+//! full conversion and panics if the bytes differ, at some cost to speed.
+//!
+//! # `no_std`
+//!
+//! Without the default `std` feature this crate only needs `alloc`, eg for
+//! WebAssembly or embedded targets:
+//!
+//! ```toml
+//! [dependencies]
+//! fastnbt = { version = "3", default-features = false }
+//! ```
+//!
+//! Deserializing with [`from_bytes`], [`Value`] and the [`nbt`] macro all
+//! work the same. A few things change:
+//!
+//! * [`Map`] is a `BTreeMap`, as `HashMap` needs std.
+//! * Writing NBT, ie [`ser`] and [`to_bytes`], and the `Read` based
+//!   [`stream`] parser need std IO, so are not available.
+//! * [`Error`][error::Error] does not implement `std::error::Error`, and has
+//!   no IO error.
+//!
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
+#[cfg(feature = "std")]
 use ser::{Serializer, State};
-use serde::{de as serde_de, Deserialize, Serialize};
+#[cfg(feature = "std")]
+use serde::Serialize;
+use serde::{de as serde_de, Deserialize};
 
 pub mod borrow;
 pub mod de;
 pub mod error;
+pub mod fixed_array;
+#[cfg(feature = "std")]
 pub mod ser;
+pub mod serde_helpers;
+#[cfg(feature = "std")]
 pub mod stream;
 pub mod value;
 
 mod arrays;
 mod de_arrays;
+mod extract;
+mod java_string;
 #[macro_use]
 mod macros;
+mod read;
 
 pub use arrays::*;
+pub use extract::extract;
 pub use value::{from_value, to_value, Value};
 
-#[cfg(test)]
+/// The map used for compounds in a [`Value`]. This is a std `HashMap`, unless
+/// the `fast-hash` feature is enabled. Use this rather than naming `HashMap`
+/// directly to work with either.
+#[cfg(all(feature = "std", not(feature = "fast-hash")))]
+pub type Map<K, V> = std::collections::HashMap<K, V>;
+
+/// The map used for compounds in a [`Value`]. This is a `BTreeMap`, since the
+/// `std` feature is disabled.
+#[cfg(not(feature = "std"))]
+pub type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+
+/// The map used for compounds in a [`Value`]. This is a `HashMap` using
+/// FxHash, since the `fast-hash` feature is enabled.
+#[cfg(feature = "fast-hash")]
+pub type Map<K, V> = std::collections::HashMap<K, V, rustc_hash::FxBuildHasher>;
+
+// The tests use the std-only parts of the crate throughout, such as to_bytes.
+#[cfg(all(test, feature = "std"))]
 mod test;
 
+// Deserializing is all there is without std, so these check that from
+// hand-written NBT.
+#[cfg(all(test, not(feature = "std")))]
+mod test_no_std;
+
 use crate::{
     de::Deserializer,
     error::{Error, Result},
 };
-use std::{convert::TryFrom, fmt::Display, io::Write};
+use alloc::{format, string::ToString};
+#[cfg(feature = "std")]
+use alloc::{vec, vec::Vec};
+use core::{convert::TryFrom, fmt::Display};
+#[cfg(feature = "std")]
+use std::io::Write;
 
 /// An NBT tag. This does not carry the value or the name of the data.
 #[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
@@ -190,7 +283,7 @@ pub enum Tag {
 impl TryFrom<u8> for Tag {
     type Error = ();
 
-    fn try_from(value: u8) -> std::result::Result<Self, ()> {
+    fn try_from(value: u8) -> core::result::Result<Self, ()> {
         use Tag::*;
         Ok(match value {
             0 => End,
@@ -232,7 +325,7 @@ impl From<Tag> for u8 {
 }
 
 impl Display for Tag {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let s = match self {
             Tag::End => "end",
             Tag::Byte => "byte",
@@ -254,31 +347,126 @@ impl Display for Tag {
 
 /// Serialize some `T` into NBT data. See the [`ser`] module for more
 /// information.
+#[cfg(feature = "std")]
 pub fn to_bytes<T: Serialize>(v: &T) -> Result<Vec<u8>> {
-    let mut result = vec![];
-    let mut serializer = Serializer {
-        writer: &mut result,
-        state: State::Compound {
-            current_field: String::new(),
-        },
-    };
-    v.serialize(&mut serializer)?;
-    Ok(result)
+    to_bytes_with_opts(v, Default::default())
 }
 
 /// Serialize some `T` into NBT data. See the [`ser`] module for more
 /// information.
+#[cfg(feature = "std")]
 pub fn to_writer<T: Serialize, W: Write>(writer: W, v: &T) -> Result<()> {
+    to_writer_with_opts(writer, v, Default::default())
+}
+
+/// Serialize some `T` into NBT data with an unnamed root, as the network
+/// protocol uses since Minecraft 1.20.2. The root's tag is written followed
+/// straight away by its payload, with no name in between. This is the same as
+/// [`to_bytes_with_opts`] with [`SerOpts::unnamed_root`] set.
+///
+/// ```
+/// # use fastnbt::nbt;
+/// let bytes = fastnbt::to_bytes_unnamed(&nbt!({"a": 1i8})).unwrap();
+/// assert_eq!(bytes, [10, 1, 0, 1, b'a', 1, 0]);
+/// ```
+#[cfg(feature = "std")]
+pub fn to_bytes_unnamed<T: Serialize>(v: &T) -> Result<Vec<u8>> {
+    to_bytes_with_opts(v, SerOpts::new().unnamed_root(true))
+}
+
+/// Serialize some `T` into NBT data with an unnamed root. See
+/// [`to_bytes_unnamed`].
+#[cfg(feature = "std")]
+pub fn to_writer_unnamed<T: Serialize, W: Write>(writer: W, v: &T) -> Result<()> {
+    to_writer_with_opts(writer, v, SerOpts::new().unnamed_root(true))
+}
+
+/// Similar to [`to_bytes`] but with options.
+#[cfg(feature = "std")]
+pub fn to_bytes_with_opts<T: Serialize>(v: &T, opts: SerOpts) -> Result<Vec<u8>> {
+    let mut result = vec![];
+    to_writer_with_opts(&mut result, v, opts)?;
+    Ok(result)
+}
+
+/// Similar to [`to_writer`] but with options.
+#[cfg(feature = "std")]
+pub fn to_writer_with_opts<T: Serialize, W: Write>(writer: W, v: &T, opts: SerOpts) -> Result<()> {
+    let state = if opts.unnamed_root {
+        State::UnnamedRoot
+    } else {
+        State::Compound {
+            current_field: Vec::new(),
+        }
+    };
     let mut serializer = Serializer {
         writer,
-        state: State::Compound {
-            current_field: String::new(),
-        },
+        state,
+        key_order: opts.key_order,
     };
     v.serialize(&mut serializer)?;
     Ok(())
 }
 
+/// Options for customizing serialization. The defaults match the behaviour of
+/// [`to_bytes`].
+///
+/// ```
+/// # use fastnbt::{nbt, KeyOrder, SerOpts};
+/// let opts = SerOpts::new().key_order(KeyOrder::Sorted);
+/// let bytes = fastnbt::to_bytes_with_opts(&nbt!({"b": 1i8, "a": 2i8}), opts).unwrap();
+/// assert_eq!(bytes, [10, 0, 0, 1, 0, 1, b'a', 2, 1, 0, 1, b'b', 1, 0]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SerOpts {
+    /// The order compound entries are written in.
+    key_order: KeyOrder,
+    /// The root compound has no name.
+    unnamed_root: bool,
+}
+
+impl SerOpts {
+    /// Create new options. This object follows a builder pattern.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the order the entries of each compound are written in. See
+    /// [`KeyOrder`].
+    pub fn key_order(mut self, value: KeyOrder) -> Self {
+        self.key_order = value;
+        self
+    }
+
+    /// Set whether the root compound is written without a name, as the
+    /// network protocol expects since Minecraft 1.20.2. See
+    /// [`to_bytes_unnamed`].
+    pub fn unnamed_root(mut self, value: bool) -> Self {
+        self.unnamed_root = value;
+        self
+    }
+}
+
+/// The order compound entries are written in. Used with
+/// [`SerOpts::key_order`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyOrder {
+    /// The order the type being serialized gives them in. This is the default.
+    /// Structs are written in the order their fields are declared, but a
+    /// `HashMap`, and a [`Value`] compound, are written in whatever order they
+    /// iterate in, which can differ between runs.
+    #[default]
+    AsGiven,
+    /// Sorted by the bytes of their names, as written in NBT. The output
+    /// then depends only on the value being serialized, which is useful for
+    /// reproducible builds of data packs and structure files. See the
+    /// [`ser`] module for the guarantee this gives.
+    ///
+    /// Each compound is built up in memory before being written, so this is
+    /// slower than `AsGiven`.
+    Sorted,
+}
+
 /// Deserialize into a `T` from some NBT data. See the [`de`] module for more
 /// information.
 ///
@@ -307,10 +495,47 @@ where
     from_bytes_with_opts(input, Default::default())
 }
 
-/// Options for customozing deserialization.
+/// Deserialize into a `T` from NBT data with an unnamed root, as the network
+/// protocol uses since Minecraft 1.20.2. This is the same as
+/// [`from_bytes_with_opts`] with [`DeOpts::unnamed_root`] set.
+///
+/// ```
+/// # use fastnbt::{nbt, Value};
+/// let value: Value = fastnbt::from_bytes_unnamed(&[10, 1, 0, 1, b'a', 1, 0]).unwrap();
+/// assert_eq!(value, nbt!({"a": 1i8}));
+/// ```
+pub fn from_bytes_unnamed<'a, T>(input: &'a [u8]) -> Result<T>
+where
+    T: serde_de::Deserialize<'a>,
+{
+    from_bytes_with_opts(input, DeOpts::new().unnamed_root(true))
+}
+
+/// Options for customozing deserialization. The defaults match the behaviour
+/// of [`from_bytes`].
+///
+/// ```
+/// # use fastnbt::{DeOpts, Value};
+/// # let input = fastnbt::to_bytes(&fastnbt::nbt!({"a": 1})).unwrap();
+/// let opts = DeOpts::new().max_depth(64).strict_trailing(true);
+/// let value: Value = fastnbt::from_bytes_with_opts(&input, opts).unwrap();
+/// ```
+#[derive(Debug, Clone)]
 pub struct DeOpts {
     /// Maximum number of bytes a list or array can be.
     max_seq_len: usize,
+    /// Maximum nesting of compounds and lists, including the root compound.
+    max_depth: usize,
+    /// Error if there is data left after the root compound.
+    strict_trailing: bool,
+    /// What to do with strings that are not valid CESU-8.
+    invalid_strings: InvalidStrings,
+    /// The root compound has no name.
+    unnamed_root: bool,
+    /// Only a byte of 0 or 1 is a bool.
+    strict_bools: bool,
+    /// Only a float or double is a float.
+    strict_floats: bool,
 }
 
 impl DeOpts {
@@ -326,12 +551,99 @@ impl DeOpts {
         self.max_seq_len = value;
         self
     }
+
+    /// Set the maximum depth compounds and lists can be nested, where the root
+    /// compound has a depth of 1. This applies to ignored fields too. Deeply
+    /// nested data can otherwise overflow the stack. Unlimited by default.
+    pub fn max_depth(mut self, value: usize) -> Self {
+        self.max_depth = value;
+        self
+    }
+
+    /// Set whether it is an error for input to continue after the root
+    /// compound. By default any trailing bytes are ignored.
+    pub fn strict_trailing(mut self, value: bool) -> Self {
+        self.strict_trailing = value;
+        self
+    }
+
+    /// Set what to do with strings, including compound keys, that are not
+    /// valid Java CESU-8. By default they are an error. See
+    /// [`InvalidStrings`].
+    pub fn invalid_strings(mut self, value: InvalidStrings) -> Self {
+        self.invalid_strings = value;
+        self
+    }
+
+    /// Set whether the root compound is written without a name, ie its tag is
+    /// followed straight away by its payload. This is how the network
+    /// protocol sends NBT since Minecraft 1.20.2. By default the root has a
+    /// name, as in files and older versions of the protocol. See
+    /// [`from_bytes_unnamed`].
+    pub fn unnamed_root(mut self, value: bool) -> Self {
+        self.unnamed_root = value;
+        self
+    }
+
+    /// Set whether only a byte of 0 or 1 can be deserialized as a bool. By
+    /// default any byte, short, int or long is a bool, true if it is not
+    /// zero, which some worlds depend on. Strict bools are useful to find data
+    /// written incorrectly, eg by buggy mods.
+    pub fn strict_bools(mut self, value: bool) -> Self {
+        self.strict_bools = value;
+        self
+    }
+
+    /// Set whether only a float or double can be deserialized as an `f32` or
+    /// `f64`. By default a byte, short, int or long can be too, as some mods
+    /// write whole numbers that way. It is an error if the number cannot be
+    /// represented exactly, eg an int above 2^24 as an `f32`.
+    pub fn strict_floats(mut self, value: bool) -> Self {
+        self.strict_floats = value;
+        self
+    }
+}
+
+/// How to deserialize strings that are not valid Java CESU-8, the encoding NBT
+/// uses for strings. These turn up in worlds edited by old tools, that wrote
+/// raw Latin-1 for example. Used with [`DeOpts::invalid_strings`].
+///
+/// Strings borrowed from the input, ie `&str`, can never be invalid, as the
+/// replaced string has to be owned.
+///
+/// ```
+/// # use fastnbt::{DeOpts, InvalidStrings, Value};
+/// # let input = fastnbt::to_bytes(&fastnbt::nbt!({"a": 1})).unwrap();
+/// let opts = DeOpts::new().invalid_strings(InvalidStrings::ReplaceInvalid);
+/// let value: Value = fastnbt::from_bytes_with_opts(&input, opts).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidStrings {
+    /// Fail deserialization with an error. This is the default.
+    #[default]
+    Error,
+    /// Decode as much as possible as CESU-8, replacing each invalid sequence
+    /// with U+FFFD. Valid CESU-8 in the rest of the string, such as surrogate
+    /// pairs, is kept.
+    ReplaceInvalid,
+    /// Decode the string as UTF-8 with
+    /// [`String::from_utf8_lossy`][std::string::String::from_utf8_lossy],
+    /// replacing anything that is not UTF-8 with U+FFFD. This is cheaper than
+    /// `ReplaceInvalid` but does not understand CESU-8, so characters outside
+    /// the basic multilingual plane are replaced in strings that are invalid.
+    Lossy,
 }
 
 impl Default for DeOpts {
     fn default() -> Self {
         Self {
             max_seq_len: 100_000,
+            max_depth: usize::MAX,
+            strict_trailing: false,
+            invalid_strings: InvalidStrings::Error,
+            unnamed_root: false,
+            strict_bools: false,
+            strict_floats: false,
         }
     }
 }
@@ -351,7 +663,16 @@ where
         ));
     }
 
+    let strict_trailing = opts.strict_trailing;
     let mut des = Deserializer::from_bytes(input, opts);
     let t = T::deserialize(&mut des)?;
+
+    if strict_trailing && !des.input.0.is_empty() {
+        return Err(Error::bespoke(format!(
+            "{} trailing bytes after root compound",
+            des.input.0.len()
+        )));
+    }
+
     Ok(t)
 }
@@ -268,12 +268,12 @@ macro_rules! nbt_internal {
     };
 
     ({}) => {
-        $crate::Value::Compound(std::collections::HashMap::new())
+        $crate::Value::Compound($crate::Map::default())
     };
 
     ({ $($tt:tt)+ }) => {
         $crate::Value::Compound({
-            let mut object = std::collections::HashMap::new();
+            let mut object = $crate::Map::default();
             nbt_internal!(@object object () ($($tt)+) ($($tt)+));
             object
         })
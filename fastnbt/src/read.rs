@@ -0,0 +1,57 @@
+//! Reading big endian numbers from the front of a slice, which is all the
+//! deserializers need. `std::io::Read` would do the same, but is not available
+//! without std.
+
+use byteorder::ByteOrder;
+
+use crate::error::{Error, Result};
+
+pub(crate) trait ReadSlice {
+    /// Take `N` bytes from the front, or error if there are not enough.
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]>;
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn read_i8(&mut self) -> Result<i8> {
+        Ok(self.take::<1>()?[0] as i8)
+    }
+
+    fn read_u16<B: ByteOrder>(&mut self) -> Result<u16> {
+        Ok(B::read_u16(&self.take::<2>()?))
+    }
+
+    fn read_i16<B: ByteOrder>(&mut self) -> Result<i16> {
+        Ok(B::read_i16(&self.take::<2>()?))
+    }
+
+    fn read_i32<B: ByteOrder>(&mut self) -> Result<i32> {
+        Ok(B::read_i32(&self.take::<4>()?))
+    }
+
+    fn read_i64<B: ByteOrder>(&mut self) -> Result<i64> {
+        Ok(B::read_i64(&self.take::<8>()?))
+    }
+
+    fn read_f32<B: ByteOrder>(&mut self) -> Result<f32> {
+        Ok(B::read_f32(&self.take::<4>()?))
+    }
+
+    fn read_f64<B: ByteOrder>(&mut self) -> Result<f64> {
+        Ok(B::read_f64(&self.take::<8>()?))
+    }
+}
+
+impl ReadSlice for &[u8] {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        if self.len() < N {
+            // Like `Read::read_exact`, nothing is consumed.
+            return Err(Error::read_past_eof());
+        }
+
+        let (bytes, rest) = self.split_at(N);
+        *self = rest;
+        Ok(bytes.try_into().unwrap())
+    }
+}
@@ -8,15 +8,39 @@
 //!
 //! The examples directory contains some examples.
 //!
+//! To write NBT a value at a time rather than from one Rust value, see
+//! [`NbtWriter`].
+//!
 //! # 128 bit integers and UUIDs
 //!
 //! UUIDs tend to be stored in NBT using 4-long IntArrays. When serializing
 //! `i128` or `u128`, an IntArray of length 4 will be produced. This is stored
 //! as big endian i.e. the most significant bit (and int) is first.
 //!
+//! # Deterministic output
+//!
+//! By default compound entries are written in the order they are given, which
+//! for a `HashMap` or a [`Value`][crate::Value] can change from run to run.
+//! With [`KeyOrder::Sorted`][crate::KeyOrder::Sorted] they are sorted by name
+//! instead, so the output depends only on the value being serialized:
+//!
+//! ```
+//! # use fastnbt::{nbt, KeyOrder, SerOpts};
+//! let opts = SerOpts::new().key_order(KeyOrder::Sorted);
+//! let data = nbt!({"DataVersion": 3465, "palette": [{"Name": "minecraft:stone"}]});
+//! let bytes = fastnbt::to_bytes_with_opts(&data, opts).unwrap();
+//! ```
+//!
+//! Sorted output is stable across runs, platforms and versions of this crate.
+//! It is not changed outside of a new major version, and a test checks the
+//! exact bytes written for a representative document. Floats are written as
+//! their bits, so are exact too.
+//!
 mod array_serializer;
 mod name_serializer;
 mod serializer;
 mod write_nbt;
+mod writer;
 
 pub use serializer::*;
+pub use writer::*;
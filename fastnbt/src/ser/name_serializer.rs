@@ -3,6 +3,7 @@ use std::io::Write;
 use serde::{ser::Impossible, Serializer};
 
 use crate::error::Error;
+use crate::java_string;
 
 pub(crate) struct NameSerializer<W: Write> {
     pub(crate) name: W,
@@ -75,17 +76,20 @@ impl<W: Write> Serializer for &mut NameSerializer<W> {
     }
 
     fn serialize_char(self, c: char) -> Result<Self::Ok, Self::Error> {
-        self.name.write_all(&cesu8::to_java_cesu8(&c.to_string()))?;
+        self.name.write_all(&java_string::encode(&c.to_string()))?;
         Ok(())
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        self.name.write_all(&cesu8::to_java_cesu8(v))?;
+        self.name.write_all(&java_string::encode(v))?;
         Ok(())
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        self.name.write_all(v)?;
+        // Bytes are taken to be CESU-8 already, but UTF-8 is accepted too.
+        let name = java_string::decode(v)
+            .ok_or_else(|| Error::bespoke("field name was invalid cesu8".to_string()))?;
+        self.name.write_all(&java_string::encode(&name))?;
         Ok(())
     }
 
@@ -8,7 +8,7 @@ use serde::{
 
 use crate::{
     error::{Error, Result},
-    IntArray, Tag,
+    IntArray, KeyOrder, Tag,
 };
 
 use super::{
@@ -17,9 +17,16 @@ use super::{
 
 #[derive(Debug)]
 pub(crate) enum State {
-    ListStart { len: usize },
+    ListStart {
+        len: usize,
+    },
     ListRest,
-    Compound { current_field: String },
+    /// The name of the field being written, already encoded as CESU-8.
+    Compound {
+        current_field: Vec<u8>,
+    },
+    // The root, when it should be written without a name.
+    UnnamedRoot,
 }
 
 #[derive(Debug)]
@@ -31,6 +38,7 @@ pub(crate) enum TupleState {
 pub struct Serializer<W: Write> {
     pub(crate) writer: W,
     pub(crate) state: State,
+    pub(crate) key_order: KeyOrder,
 }
 
 impl<'a, W: Write> Serializer<W> {
@@ -44,8 +52,9 @@ impl<'a, W: Write> Serializer<W> {
             State::ListRest => {}
             State::Compound { current_field } => {
                 self.writer.write_tag(tag)?;
-                self.writer.write_size_prefixed_str(current_field)?;
+                self.writer.write_size_prefixed_bytes(current_field)?;
             }
+            State::UnnamedRoot => self.writer.write_tag(tag)?,
         }
         Ok(())
     }
@@ -232,6 +241,15 @@ impl<'a, W: 'a + Write> serde::ser::Serializer for &'a mut Serializer<W> {
                     tag: Tag::LongArray,
                 })
             }
+            "__fastnbt_empty_list" => {
+                // The value is the element tag as a u8. With the header
+                // written, serializing it writes just the byte.
+                self.try_write_header(Tag::List)?;
+                self.state = State::ListRest;
+                value.serialize(&mut *self)?;
+                self.writer.write_u32::<BigEndian>(0)?; // ie len
+                Ok(())
+            }
             _ => todo!("newtype variants that are not nbt arrays"),
         }
     }
@@ -286,7 +304,11 @@ impl<'a, W: 'a + Write> serde::ser::Serializer for &'a mut Serializer<W> {
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
         self.try_write_header(Tag::Compound)?;
-        Ok(SerializerMap { ser: self })
+        let sorted = match self.key_order {
+            KeyOrder::AsGiven => None,
+            KeyOrder::Sorted => Some(Vec::new()),
+        };
+        Ok(SerializerMap { ser: self, sorted })
     }
 
     fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
@@ -306,6 +328,9 @@ impl<'a, W: 'a + Write> serde::ser::Serializer for &'a mut Serializer<W> {
 
 pub struct SerializerMap<'a, W: Write> {
     ser: &'a mut Serializer<W>,
+    /// The entries so far when keys are sorted, as their encoded name and the
+    /// bytes of the whole entry. They are written out at the end.
+    sorted: Option<Vec<(Vec<u8>, Vec<u8>)>>,
 }
 
 impl<'ser, 'a, W: Write> serde::ser::SerializeMap for SerializerMap<'a, W> {
@@ -328,6 +353,13 @@ impl<'ser, 'a, W: Write> serde::ser::SerializeMap for SerializerMap<'a, W> {
     }
 
     fn end(self) -> Result<()> {
+        if let Some(mut entries) = self.sorted {
+            // Stable, so that any duplicate names stay in the order given.
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (_, entry) in entries {
+                self.ser.writer.write_all(&entry)?;
+            }
+        }
         self.ser.writer.write_tag(Tag::End)
     }
 
@@ -336,14 +368,34 @@ impl<'ser, 'a, W: Write> serde::ser::SerializeMap for SerializerMap<'a, W> {
         K: Serialize,
         V: Serialize,
     {
-        // Get the name ahead of time.
-        let mut name = Vec::new();
+        if let Some(entries) = &mut self.sorted {
+            let mut name = Vec::new();
+            key.serialize(&mut NameSerializer { name: &mut name })?;
+
+            let mut entry = Vec::new();
+            let mut ser = Serializer {
+                writer: &mut entry,
+                state: State::Compound {
+                    current_field: name.clone(),
+                },
+                key_order: self.ser.key_order,
+            };
+            value.serialize(&mut ser)?;
+            entries.push((name, entry));
+            return Ok(());
+        }
+
+        // Get the name ahead of time, reusing the previous field's buffer if
+        // nothing has replaced it since.
+        let mut name = match &mut self.ser.state {
+            State::Compound { current_field } => core::mem::take(current_field),
+            _ => Vec::new(),
+        };
+        name.clear();
         key.serialize(&mut NameSerializer { name: &mut name })?;
 
         self.ser.state = State::Compound {
-            current_field: cesu8::from_java_cesu8(&name)
-                .map_err(|_| Error::bespoke("field name was invalid cesu8".to_string()))?
-                .to_string(),
+            current_field: name,
         };
         value.serialize(&mut *self.ser)
     }
@@ -362,8 +414,7 @@ impl<'a, W: Write> serde::ser::SerializeStruct for SerializerMap<'a, W> {
     }
 
     fn end(self) -> Result<()> {
-        self.ser.writer.write_tag(Tag::End)?;
-        Ok(())
+        ser::SerializeMap::end(self)
     }
 }
 
@@ -4,6 +4,7 @@ use std::io::Write;
 use byteorder::{BigEndian, WriteBytesExt};
 
 use crate::error::{Error, Result};
+use crate::java_string;
 use crate::Tag;
 
 pub(crate) trait WriteNbt: Write {
@@ -13,10 +14,14 @@ pub(crate) trait WriteNbt: Write {
     }
 
     fn write_size_prefixed_str(&mut self, key: &str) -> Result<()> {
-        let key = cesu8::to_java_cesu8(key);
+        self.write_size_prefixed_bytes(&java_string::encode(key))
+    }
+
+    /// Write a string that is already encoded as CESU-8.
+    fn write_size_prefixed_bytes(&mut self, key: &[u8]) -> Result<()> {
         let len_bytes = key.len() as u16;
         self.write_u16::<BigEndian>(len_bytes)?;
-        self.write_all(&key)?;
+        self.write_all(key)?;
         Ok(())
     }
 
@@ -0,0 +1,285 @@
+use std::io::Write;
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+use crate::{
+    error::{Error, Result},
+    Tag,
+};
+
+use super::write_nbt::WriteNbt;
+
+/// Writes NBT a value at a time to any [`Write`], without holding the whole
+/// document in memory. This suits large documents built up by hand, such as
+/// structure exports, where serializing one big Rust value is not practical.
+///
+/// Compounds are ended with [`end_compound`][Self::end_compound] and need no
+/// size up front. Lists are prefixed by their length in NBT, so
+/// [`start_list`][Self::start_list] takes the number of elements, and writing
+/// more or fewer than that is an error. Elements of a list have no name, so
+/// the name given when writing them is ignored, and they must have the
+/// element tag of the list.
+///
+/// Call [`finish`][Self::finish] once done to check every compound and list
+/// was closed.
+///
+/// ```
+/// # use fastnbt::{ser::NbtWriter, Tag};
+/// # fn main() -> fastnbt::error::Result<()> {
+/// let mut writer = NbtWriter::new(Vec::new());
+/// writer.start_compound("")?;
+/// writer.int("DataVersion", 3465)?;
+/// writer.start_list("blocks", Tag::Compound, 2)?;
+/// for y in 0..2 {
+///     writer.start_compound("")?;
+///     writer.int_array("pos", &[0, y, 0])?;
+///     writer.int("state", 0)?;
+///     writer.end_compound()?;
+/// }
+/// writer.end_list()?;
+/// writer.end_compound()?;
+///
+/// let bytes = writer.finish()?;
+/// let value: fastnbt::Value = fastnbt::from_bytes(&bytes)?;
+/// assert_eq!(value.pointer("/blocks/1/pos"), Some(&fastnbt::nbt!([I; 0, 1, 0])));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NbtWriter<W: Write> {
+    writer: W,
+    scopes: Vec<Scope>,
+    root_written: bool,
+}
+
+#[derive(Debug)]
+enum Scope {
+    Compound,
+    List {
+        tag: Tag,
+        len: usize,
+        written: usize,
+    },
+}
+
+impl<W: Write> NbtWriter<W> {
+    /// Create a writer for the given sink. The first thing written must be
+    /// the root compound.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            scopes: Vec::new(),
+            root_written: false,
+        }
+    }
+
+    /// Start a compound. Values written after this are inside it until
+    /// [`end_compound`][Self::end_compound].
+    pub fn start_compound(&mut self, name: &str) -> Result<()> {
+        self.write_header(Tag::Compound, name)?;
+        self.scopes.push(Scope::Compound);
+        Ok(())
+    }
+
+    /// End the compound most recently started.
+    pub fn end_compound(&mut self) -> Result<()> {
+        match self.scopes.last() {
+            Some(Scope::Compound) => {
+                self.scopes.pop();
+                self.writer.write_tag(Tag::End)
+            }
+            Some(Scope::List { .. }) => Err(Error::bespoke(
+                "cannot end compound: inside a list, use end_list".to_owned(),
+            )),
+            None => Err(Error::bespoke(
+                "cannot end compound: no compound started".to_owned(),
+            )),
+        }
+    }
+
+    /// Start a list of `len` elements with the given tag. Exactly `len`
+    /// elements must be written before [`end_list`][Self::end_list]. An empty
+    /// list can have a tag of [`Tag::End`].
+    pub fn start_list(&mut self, name: &str, element_tag: Tag, len: usize) -> Result<()> {
+        if element_tag == Tag::End && len != 0 {
+            return Err(Error::bespoke(format!(
+                "cannot start list of {} elements with end tag",
+                len
+            )));
+        }
+
+        self.write_header(Tag::List, name)?;
+        self.writer.write_tag(element_tag)?;
+        self.writer.write_len(len)?;
+        self.scopes.push(Scope::List {
+            tag: element_tag,
+            len,
+            written: 0,
+        });
+        Ok(())
+    }
+
+    /// End the list most recently started. It is an error if fewer elements
+    /// were written than the list was started with.
+    pub fn end_list(&mut self) -> Result<()> {
+        match self.scopes.last() {
+            Some(&Scope::List { len, written, .. }) if written != len => Err(Error::bespoke(
+                format!("cannot end list: {} of {} elements written", written, len),
+            )),
+            Some(Scope::List { .. }) => {
+                self.scopes.pop();
+                Ok(())
+            }
+            Some(Scope::Compound) => Err(Error::bespoke(
+                "cannot end list: inside a compound, use end_compound".to_owned(),
+            )),
+            None => Err(Error::bespoke(
+                "cannot end list: no list started".to_owned(),
+            )),
+        }
+    }
+
+    pub fn byte(&mut self, name: &str, v: i8) -> Result<()> {
+        self.write_header(Tag::Byte, name)?;
+        self.writer.write_i8(v)?;
+        Ok(())
+    }
+
+    pub fn short(&mut self, name: &str, v: i16) -> Result<()> {
+        self.write_header(Tag::Short, name)?;
+        self.writer.write_i16::<BigEndian>(v)?;
+        Ok(())
+    }
+
+    pub fn int(&mut self, name: &str, v: i32) -> Result<()> {
+        self.write_header(Tag::Int, name)?;
+        self.writer.write_i32::<BigEndian>(v)?;
+        Ok(())
+    }
+
+    pub fn long(&mut self, name: &str, v: i64) -> Result<()> {
+        self.write_header(Tag::Long, name)?;
+        self.writer.write_i64::<BigEndian>(v)?;
+        Ok(())
+    }
+
+    pub fn float(&mut self, name: &str, v: f32) -> Result<()> {
+        self.write_header(Tag::Float, name)?;
+        self.writer.write_f32::<BigEndian>(v)?;
+        Ok(())
+    }
+
+    pub fn double(&mut self, name: &str, v: f64) -> Result<()> {
+        self.write_header(Tag::Double, name)?;
+        self.writer.write_f64::<BigEndian>(v)?;
+        Ok(())
+    }
+
+    pub fn string(&mut self, name: &str, v: &str) -> Result<()> {
+        self.write_header(Tag::String, name)?;
+        self.writer.write_size_prefixed_str(v)
+    }
+
+    pub fn byte_array(&mut self, name: &str, v: &[i8]) -> Result<()> {
+        self.write_header(Tag::ByteArray, name)?;
+        self.writer.write_len(v.len())?;
+        self.write_elements(v, i8::to_be_bytes)
+    }
+
+    pub fn int_array(&mut self, name: &str, v: &[i32]) -> Result<()> {
+        self.write_header(Tag::IntArray, name)?;
+        self.writer.write_len(v.len())?;
+        self.write_elements(v, i32::to_be_bytes)
+    }
+
+    pub fn long_array(&mut self, name: &str, v: &[i64]) -> Result<()> {
+        self.write_header(Tag::LongArray, name)?;
+        self.writer.write_len(v.len())?;
+        self.write_elements(v, i64::to_be_bytes)
+    }
+
+    /// Check the root compound was written and everything in it closed, then
+    /// flush and return the sink.
+    pub fn finish(mut self) -> Result<W> {
+        if let Some(scope) = self.scopes.last() {
+            let open = match scope {
+                Scope::Compound => "compound",
+                Scope::List { .. } => "list",
+            };
+            return Err(Error::bespoke(format!(
+                "cannot finish: {} scopes still open, innermost is a {}",
+                self.scopes.len(),
+                open
+            )));
+        }
+        if !self.root_written {
+            return Err(Error::bespoke(
+                "cannot finish: no root compound written".to_owned(),
+            ));
+        }
+
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Write the elements of an array. They are encoded a block at a time, so
+    /// that an unbuffered sink is not written to once per element, without
+    /// copying the whole of a big array.
+    fn write_elements<T: Copy, const N: usize>(
+        &mut self,
+        v: &[T],
+        to_bytes: fn(T) -> [u8; N],
+    ) -> Result<()> {
+        const BLOCK_LEN: usize = 8192;
+        let mut buf = Vec::with_capacity(BLOCK_LEN.min(v.len() * N));
+        for block in v.chunks(BLOCK_LEN / N) {
+            buf.clear();
+            buf.extend(block.iter().flat_map(|&x| to_bytes(x)));
+            self.writer.write_all(&buf)?;
+        }
+        Ok(())
+    }
+
+    /// Write the tag and name of a value, or just count it if it is an
+    /// element of a list.
+    fn write_header(&mut self, tag: Tag, name: &str) -> Result<()> {
+        match self.scopes.last_mut() {
+            Some(Scope::Compound) => {
+                self.writer.write_tag(tag)?;
+                self.writer.write_size_prefixed_str(name)
+            }
+            Some(Scope::List {
+                tag: element_tag,
+                len,
+                written,
+            }) => {
+                if tag != *element_tag {
+                    return Err(Error::bespoke(format!(
+                        "cannot write {} to list of {}",
+                        tag, element_tag
+                    )));
+                }
+                if written == len {
+                    return Err(Error::bespoke(format!(
+                        "cannot write more than {} elements to list",
+                        len
+                    )));
+                }
+                *written += 1;
+                Ok(())
+            }
+            None if self.root_written => Err(Error::bespoke(
+                "cannot write after the root compound has ended".to_owned(),
+            )),
+            None if tag != Tag::Compound => Err(Error::bespoke(format!(
+                "cannot write {} as root, it must be a compound",
+                tag
+            ))),
+            None => {
+                self.root_written = true;
+                self.writer.write_tag(tag)?;
+                self.writer.write_size_prefixed_str(name)
+            }
+        }
+    }
+}
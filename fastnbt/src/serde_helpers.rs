@@ -0,0 +1,159 @@
+//! Adapters for values Minecraft stores in a roundabout way, for use with
+//! `#[serde(with = "...")]`. Each module has a `serialize` and `deserialize`
+//! function, so they work on structs that derive either or both.
+//!
+//! * [`bool_from_byte`]: a bool stored as a byte of 0 or 1, eg `OnGround`.
+//! * [`uuid_from_int_array`]: a UUID stored as an IntArray of 4 ints, eg
+//!   `UUID` on entities since 1.16.
+//! * [`string_or_seq`]: a string, or a list of strings, eg the lines of a
+//!   book page or lore.
+//!
+//! See [`fixed_array`][crate::fixed_array] for arrays of a set length, eg the
+//! two ints of a position.
+//!
+//! ```
+//! # use serde::{Deserialize, Serialize};
+//! # use fastnbt::nbt;
+//! use fastnbt::serde_helpers::{bool_from_byte, uuid_from_int_array};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Entity {
+//!     #[serde(rename = "OnGround", with = "bool_from_byte")]
+//!     on_ground: bool,
+//!
+//!     #[serde(rename = "UUID", with = "uuid_from_int_array")]
+//!     uuid: u128,
+//! }
+//!
+//! let nbt = fastnbt::to_bytes(&nbt!({
+//!     "OnGround": 1i8,
+//!     "UUID": [I; 0, 0, 0, 7],
+//! }))
+//! .unwrap();
+//!
+//! let entity: Entity = fastnbt::from_bytes(&nbt).unwrap();
+//! assert!(entity.on_ground);
+//! assert_eq!(entity.uuid, 7);
+//! ```
+//!
+//! The deserializer already reads bools from bytes and `u128` from an
+//! IntArray when it can see the NBT. These adapters also work where serde
+//! buffers a value first, such as in `#[serde(flatten)]` structs and untagged
+//! enums, and when (de)serializing a [`Value`][crate::Value].
+//!
+//! The modules need no other crates. They can be used on fields next to ones
+//! using the `serde_as` of the `serde_with` crate.
+
+/// A bool stored as a byte. Any integer deserializes, with anything but zero
+/// being `true`, as does a bool. Serializes as a byte of 0 or 1.
+pub mod bool_from_byte {
+    use core::fmt;
+
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i8(*value as i8)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        deserializer.deserialize_any(BoolVisitor)
+    }
+
+    struct BoolVisitor;
+
+    impl<'de> de::Visitor<'de> for BoolVisitor {
+        type Value = bool;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a byte or bool")
+        }
+
+        fn visit_bool<E: de::Error>(self, v: bool) -> Result<bool, E> {
+            Ok(v)
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<bool, E> {
+            Ok(v != 0)
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<bool, E> {
+            Ok(v != 0)
+        }
+    }
+}
+
+/// A UUID stored as an IntArray of 4 ints, most significant first, as a
+/// `u128`. Serializes as an IntArray.
+pub mod uuid_from_int_array {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::IntArray;
+
+    pub fn serialize<S: Serializer>(uuid: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        let ints = (0..4)
+            .rev()
+            .map(|i| (uuid >> (32 * i)) as u32 as i32)
+            .collect();
+        IntArray::new(ints).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        let ints = IntArray::deserialize(deserializer)?;
+        if ints.len() != 4 {
+            return Err(de::Error::invalid_length(ints.len(), &"a UUID of 4 ints"));
+        }
+
+        Ok(ints
+            .iter()
+            .fold(0, |uuid, &int| uuid << 32 | int as u32 as u128))
+    }
+}
+
+/// A string, or a list of strings, as a `Vec<String>`. A single string is a
+/// `Vec` of one. Serializes a `Vec` of one as a string, and anything else as
+/// a list.
+pub mod string_or_seq {
+    use alloc::{string::String, vec, vec::Vec};
+    use core::fmt;
+
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(strings: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+        match strings {
+            [string] => serializer.serialize_str(string),
+            strings => serializer.collect_seq(strings),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<String>, D::Error> {
+        deserializer.deserialize_any(StringOrSeqVisitor)
+    }
+
+    struct StringOrSeqVisitor;
+
+    impl<'de> de::Visitor<'de> for StringOrSeqVisitor {
+        type Value = Vec<String>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a string or a list of strings")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            Ok(vec![v.into()])
+        }
+
+        fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+            Ok(vec![v])
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut strings = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(1024));
+            while let Some(string) = seq.next_element()? {
+                strings.push(string);
+            }
+            Ok(strings)
+        }
+    }
+}
@@ -1,8 +1,9 @@
 //! Allows streaming of NBT data without prior knowledge of the structure.
 
 use super::Tag;
+use crate::java_string;
 use byteorder::{BigEndian, ReadBytesExt};
-use std::{convert::TryFrom, io::Read, str};
+use std::{convert::TryFrom, io::Read, str, sync::Arc};
 
 /// An optional `String`.
 pub type Name = Option<String>;
@@ -38,6 +39,8 @@ pub enum Value {
 pub struct Error {
     msg: String,
     kind: ErrorKind,
+    // Shared so the error can stay Clone.
+    io: Option<Arc<std::io::Error>>,
 }
 
 #[derive(Debug, Clone)]
@@ -60,6 +63,10 @@ pub enum ErrorKind {
     /// Expected unicode data but was not valid. Parser remains valid if just
     /// this value was not unicode. Contained bytes are the invalid unicode data.
     Nonunicode(Vec<u8>),
+
+    /// Reading the input failed, other than by running out of it. The error
+    /// itself is available with [`Error::io_error`].
+    Io(std::io::ErrorKind),
 }
 
 impl Error {
@@ -72,39 +79,52 @@ impl Error {
         matches!(self.kind, ErrorKind::Eof)
     }
 
-    fn bespoke(msg: impl Into<String>) -> Self {
+    /// The IO error that caused this error, if there was one, eg to retry
+    /// reads that timed out.
+    pub fn io_error(&self) -> Option<&std::io::Error> {
+        self.io.as_deref()
+    }
+
+    fn new(msg: String, kind: ErrorKind) -> Self {
         Self {
-            msg: msg.into(),
-            kind: ErrorKind::Other,
+            msg,
+            kind,
+            io: None,
         }
     }
 
+    fn bespoke(msg: impl Into<String>) -> Self {
+        Self::new(msg.into(), ErrorKind::Other)
+    }
+
     fn invalid_tag(t: u8) -> Self {
-        Self {
-            msg: format!("invalid tag: {}", t),
-            kind: ErrorKind::InvalidTag,
-        }
+        Self::new(format!("invalid tag: {}", t), ErrorKind::InvalidTag)
     }
 
     fn nonunicode(d: Vec<u8>) -> Self {
-        Self {
-            msg: format!(
+        Self::new(
+            format!(
                 "invalid string, non-unicode: {}",
                 String::from_utf8_lossy(&d),
             ),
-            kind: ErrorKind::Nonunicode(d),
-        }
+            ErrorKind::Nonunicode(d),
+        )
+    }
+
+    fn negative_size(size: i32) -> Self {
+        Self::bespoke(format!("negative list/array size: {}", size))
     }
 
     fn eof() -> Self {
-        Self {
-            msg: "EOF".into(),
-            kind: ErrorKind::Eof,
-        }
+        Self::new("EOF".into(), ErrorKind::Eof)
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.io.as_deref().map(|e| e as _)
+    }
+}
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
@@ -114,16 +134,15 @@ impl std::fmt::Display for Error {
 
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Self {
-        match e.kind() {
-            std::io::ErrorKind::UnexpectedEof => Self {
-                msg: e.to_string(),
-                kind: ErrorKind::UnexpectedEof,
-            },
-            // Probably want to include the IO error in future.
-            _ => Self {
-                msg: e.to_string(),
-                kind: ErrorKind::Other,
-            },
+        let kind = match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => ErrorKind::UnexpectedEof,
+            kind => ErrorKind::Io(kind),
+        };
+
+        Self {
+            msg: e.to_string(),
+            kind,
+            io: Some(Arc::new(e)),
         }
     }
 }
@@ -131,6 +150,11 @@ impl From<std::io::Error> for Error {
 /// Convenience type for Result.
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The most elements we will allocate room for up front when reading an array.
+/// The size comes from the input, so we cannot trust it. Beyond this the vector
+/// grows as elements are actually read.
+const MAX_PREALLOC: usize = 4096;
+
 /// Parser can take any reader and parse it as NBT data. Does not do decompression.
 ///
 /// # Examples
@@ -287,16 +311,26 @@ impl<R: Read> Parser<R> {
     }
 
     fn read_size_prefixed_string(&mut self) -> Result<String> {
-        let name_len = self.reader.read_u16::<BigEndian>()? as usize;
-
-        let mut buf = vec![0; name_len];
-        self.reader.read_exact(&mut buf[..])?;
+        let len = self.reader.read_u16::<BigEndian>()? as usize;
+
+        // Corrupt input can claim a long string with nothing after it, so
+        // only allocate as the bytes arrive.
+        let mut buf = Vec::with_capacity(len.min(MAX_PREALLOC));
+        (&mut self.reader).take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() != len {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
 
-        Ok(cesu8::from_java_cesu8(&buf[..])
-            .map_err(|_| Error::nonunicode(Vec::from(&buf[..])))?
+        Ok(java_string::decode(&buf[..])
+            .ok_or_else(|| Error::nonunicode(Vec::from(&buf[..])))?
             .into_owned())
     }
 
+    fn read_array_size(&mut self) -> Result<usize> {
+        let size = self.reader.read_i32::<BigEndian>()?;
+        usize::try_from(size).map_err(|_| Error::negative_size(size))
+    }
+
     fn read_payload(&mut self, tag: Tag, name: Name) -> Result<Value> {
         match tag {
             Tag::Byte => Ok(Value::Byte(name, self.reader.read_i8()?)),
@@ -314,30 +348,38 @@ impl<R: Read> Parser<R> {
                 let element_tag = self.reader.read_u8()?;
                 let element_tag = u8_to_tag(element_tag)?;
                 let size = self.reader.read_i32::<BigEndian>()?;
+                // A negative size would never count down to the end of the
+                // list.
+                if size < 0 {
+                    return Err(Error::negative_size(size));
+                }
                 self.layers.push(Layer::List(element_tag, size));
                 Ok(Value::List(name, element_tag, size))
             }
             Tag::String => Ok(Value::String(name, self.read_size_prefixed_string()?)),
             Tag::ByteArray => {
-                let size = self.reader.read_i32::<BigEndian>()?;
-                let mut buf = vec![0u8; size as usize];
-                self.reader.read_exact(&mut buf[..])?;
+                let size = self.read_array_size()?;
+                let mut buf = Vec::with_capacity(size.min(MAX_PREALLOC));
+                (&mut self.reader).take(size as u64).read_to_end(&mut buf)?;
+                if buf.len() != size {
+                    return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+                }
                 Ok(Value::ByteArray(name, vec_u8_into_i8(buf)))
             }
             Tag::IntArray => {
-                let size = self.reader.read_i32::<BigEndian>()?;
-                let mut buf = vec![0i32; size as usize];
-                for i in 0..size {
-                    buf[i as usize] = self.reader.read_i32::<BigEndian>()?;
+                let size = self.read_array_size()?;
+                let mut buf = Vec::with_capacity(size.min(MAX_PREALLOC));
+                for _ in 0..size {
+                    buf.push(self.reader.read_i32::<BigEndian>()?);
                 }
 
                 Ok(Value::IntArray(name, buf))
             }
             Tag::LongArray => {
-                let size = self.reader.read_i32::<BigEndian>()?;
-                let mut buf = vec![0i64; size as usize];
-                for i in 0..size {
-                    buf[i as usize] = self.reader.read_i64::<BigEndian>()?;
+                let size = self.read_array_size()?;
+                let mut buf = Vec::with_capacity(size.min(MAX_PREALLOC));
+                for _ in 0..size {
+                    buf.push(self.reader.read_i64::<BigEndian>()?);
                 }
 
                 Ok(Value::LongArray(name, buf))
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use alloc_counter::{counting, CountingAlloc};
+use serde::Deserialize;
+
+use super::{
+    builder::Builder,
+    resources::{CHUNK_RAW, CHUNK_RAW_WITH_ENTITIES},
+};
+use crate::{borrow, from_bytes};
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocs<T>(f: impl FnOnce() -> T) -> (usize, T) {
+    let (counts, value) = counting(f);
+    (counts.total(), value)
+}
+
+#[derive(Deserialize)]
+struct Chunk<'a> {
+    #[serde(rename = "DataVersion")]
+    data_version: i32,
+
+    #[serde(rename = "Level")]
+    #[serde(borrow)]
+    level: Level<'a>,
+}
+
+#[derive(Deserialize)]
+struct Level<'a> {
+    #[serde(rename = "Status")]
+    status: &'a str,
+
+    #[serde(rename = "LastUpdate")]
+    last_update: i64,
+
+    #[serde(rename = "Heightmaps")]
+    #[serde(borrow)]
+    heightmaps: Heightmaps<'a>,
+}
+
+#[derive(Deserialize)]
+struct Heightmaps<'a> {
+    #[serde(rename = "WORLD_SURFACE")]
+    #[serde(borrow)]
+    world_surface: Option<borrow::LongArray<'a>>,
+}
+
+/// The allocations made by deserializing any document, eg for the stack of
+/// nested compounds, rather than for each field.
+fn fixed_allocs() -> usize {
+    #[derive(Deserialize)]
+    struct Empty {}
+
+    let input = Builder::new().start_compound("").end_compound().build();
+    allocs(|| from_bytes::<Empty>(&input).unwrap()).0
+}
+
+#[test]
+fn chunk_struct_allocates_nothing_for_field_names() {
+    // Every field name, including the many ignored ones in the sections and
+    // entities, is borrowed from the input.
+    for data in [CHUNK_RAW, CHUNK_RAW_WITH_ENTITIES] {
+        let (count, chunk) = allocs(|| from_bytes::<Chunk>(data).unwrap());
+        assert_eq!(count, fixed_allocs());
+        assert!(chunk.data_version > 0);
+        assert_eq!(chunk.level.status, "full");
+        assert!(chunk.level.heightmaps.world_surface.is_some());
+        assert!(chunk.level.last_update > 0);
+    }
+}
+
+#[test]
+fn ignored_fields_allocate_nothing() {
+    #[derive(Deserialize)]
+    struct Wanted {
+        a: i32,
+    }
+
+    let input = |ignored: usize| {
+        let mut builder = Builder::new().start_compound("");
+        for i in 0..ignored {
+            builder = builder.string(&format!("ignored_{}", i), "value");
+        }
+        builder.int("a", 1).end_compound().build()
+    };
+
+    for ignored in [0, 10, 1000] {
+        let input = input(ignored);
+        let (count, v) = allocs(|| from_bytes::<Wanted>(&input).unwrap());
+        assert_eq!(count, fixed_allocs(), "{ignored} ignored fields");
+        assert_eq!(v.a, 1);
+    }
+}
+
+#[test]
+fn map_keys_are_only_allocated_for_owned_keys() {
+    let input = Builder::new()
+        .start_compound("")
+        .int("a", 1)
+        .int("b", 2)
+        .end_compound()
+        .build();
+
+    let (borrowed, map) = allocs(|| from_bytes::<HashMap<&str, i32>>(&input).unwrap());
+    assert_eq!(map["b"], 2);
+    let (owned, map) = allocs(|| from_bytes::<HashMap<String, i32>>(&input).unwrap());
+    assert_eq!(map["b"], 2);
+
+    // The owned map needs a string for each key on top of the table.
+    assert_eq!(owned, borrowed + 2);
+}
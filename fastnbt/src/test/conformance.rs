@@ -0,0 +1,164 @@
+//! How malformed or unusual documents are read. These follow what the game
+//! accepts, see "Degenerate input" in the `de` module documentation.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::builder::Builder;
+use crate::{
+    error::Result, from_bytes, from_bytes_with_opts, stream, to_bytes, DeOpts, Tag, Value,
+};
+
+/// A compound holding a compound whose end tag is followed by a name length,
+/// as some encoders write, then an int the outer compound should hold.
+fn end_with_name() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .start_compound("inner")
+        .int("a", 1)
+        .tag(Tag::End)
+        .raw_bytes(&[0, 0])
+        .int("b", 2)
+        .end_compound()
+        .build()
+}
+
+#[test]
+fn end_tag_has_no_name() -> Result<()> {
+    // The end tag ends `inner`. The first byte of the name length is then an
+    // end tag for the root, and the rest of the input is left over.
+    let v: Value = from_bytes(&end_with_name())?;
+    assert_eq!(v, nbt!({"inner": {"a": 1}}));
+    Ok(())
+}
+
+#[test]
+fn bytes_after_end_tag_are_trailing() {
+    let input = end_with_name();
+    let opts = DeOpts::new().strict_trailing(true);
+    let err = from_bytes_with_opts::<Value>(&input, opts).unwrap_err();
+    assert_eq!(err.to_string(), "10 trailing bytes after root compound");
+}
+
+#[test]
+fn end_tag_followed_by_parent_entries() -> Result<()> {
+    // A name length of 0x0300 would swallow the int if end tags had names.
+    let input = Builder::new()
+        .start_compound("")
+        .start_compound("inner")
+        .tag(Tag::End)
+        .int("b", 2)
+        .end_compound()
+        .build();
+
+    let v: Value = from_bytes(&input)?;
+    assert_eq!(v, nbt!({"inner": {}, "b": 2}));
+    Ok(())
+}
+
+#[test]
+fn skipped_compounds_end_the_same_way() -> Result<()> {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Outer {
+        b: Option<i32>,
+    }
+
+    let v: Outer = from_bytes(&end_with_name())?;
+    assert_eq!(v, Outer { b: None });
+    Ok(())
+}
+
+#[test]
+fn stream_parser_agrees() -> stream::Result<()> {
+    let input = end_with_name();
+    let mut parser = stream::Parser::new(input.as_slice());
+
+    assert_eq!(parser.next()?, stream::Value::Compound(Some("".to_owned())));
+    assert_eq!(
+        parser.next()?,
+        stream::Value::Compound(Some("inner".to_owned()))
+    );
+    assert_eq!(parser.next()?, stream::Value::Int(Some("a".to_owned()), 1));
+    assert_eq!(parser.next()?, stream::Value::CompoundEnd);
+    assert_eq!(parser.next()?, stream::Value::CompoundEnd);
+    Ok(())
+}
+
+#[test]
+fn root_end_tag_is_not_a_compound() {
+    assert!(from_bytes::<Value>(&[0]).is_err());
+}
+
+#[test]
+fn empty_names_are_keys() -> Result<()> {
+    let input = Builder::new()
+        .start_compound("")
+        .int("", 1)
+        .int("a", 2)
+        .start_compound("")
+        .int("", 3)
+        .end_compound()
+        .end_compound()
+        .build();
+
+    // The second "" replaces the first, as in the game.
+    let v: Value = from_bytes(&input)?;
+    assert_eq!(v, nbt!({"": {"": 3}, "a": 2}));
+
+    let input = to_bytes(&nbt!({"": 1, "a": 2}))?;
+    let v: HashMap<String, i32> = from_bytes(&input)?;
+    assert_eq!(v.len(), 2);
+    assert_eq!(v[""], 1);
+    assert_eq!(v["a"], 2);
+    Ok(())
+}
+
+#[test]
+fn empty_names_as_struct_fields() -> Result<()> {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct V {
+        #[serde(rename = "")]
+        empty: i32,
+        a: i32,
+    }
+
+    let v: V = from_bytes(&to_bytes(&nbt!({"": 1, "a": 2}))?)?;
+    assert_eq!(v, V { empty: 1, a: 2 });
+    Ok(())
+}
+
+#[test]
+fn duplicate_names_in_maps_keep_the_last() -> Result<()> {
+    let input = Builder::new()
+        .start_compound("")
+        .int("a", 1)
+        .int("a", 2)
+        .end_compound()
+        .build();
+
+    let v: HashMap<String, i32> = from_bytes(&input)?;
+    assert_eq!(v["a"], 2);
+    let v: Value = from_bytes(&input)?;
+    assert_eq!(v, nbt!({"a": 2}));
+    Ok(())
+}
+
+#[test]
+fn duplicate_names_in_structs_are_an_error() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct V {
+        a: i32,
+    }
+
+    let input = Builder::new()
+        .start_compound("")
+        .int("a", 1)
+        .int("a", 2)
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<V>(&input).unwrap_err();
+    assert_eq!(err.to_string(), "duplicate field `a`");
+}
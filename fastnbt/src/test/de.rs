@@ -1,8 +1,9 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 
+use crate::de::Deserializer;
 use crate::error::{Error, Result};
-use crate::{from_bytes, Value};
+use crate::{from_bytes, from_bytes_with_opts, DeOpts, InvalidStrings, Value};
 use crate::{ByteArray, IntArray, LongArray, Tag};
 
 use super::builder::Builder;
@@ -104,6 +105,242 @@ fn bool_from_integral() {
     assert!(v.long);
 }
 
+#[test]
+fn bool_from_any_integral_by_default() {
+    let cases = [
+        (Builder::new().byte("val", -1), true),
+        (Builder::new().short("val", 0), false),
+        (Builder::new().short("val", -2), true),
+        (Builder::new().int("val", 0), false),
+        (Builder::new().int("val", i32::MIN), true),
+        (Builder::new().long("val", 0), false),
+        (Builder::new().long("val", -4), true),
+    ];
+
+    for (builder, expected) in cases {
+        let payload = Builder::new()
+            .start_compound("")
+            .raw_bytes(&builder.build())
+            .end_compound()
+            .build();
+        let v: Single<bool> = from_bytes(&payload).unwrap();
+        assert_eq!(v.val, expected);
+    }
+}
+
+#[test]
+fn strict_bools_only_from_byte_0_or_1() {
+    let opts = DeOpts::new().strict_bools(true);
+    let parse = |builder: Builder| {
+        let payload = Builder::new()
+            .start_compound("")
+            .raw_bytes(&builder.build())
+            .end_compound()
+            .build();
+        from_bytes_with_opts::<Single<bool>>(&payload, opts.clone()).map(|v| v.val)
+    };
+
+    assert!(parse(Builder::new().byte("val", 1)).unwrap());
+    assert!(!parse(Builder::new().byte("val", 0)).unwrap());
+
+    let rejected = [
+        Builder::new().byte("val", 2),
+        Builder::new().byte("val", -1),
+        Builder::new().short("val", 0),
+        Builder::new().short("val", 1),
+        Builder::new().short("val", -1),
+        Builder::new().int("val", 0),
+        Builder::new().int("val", 1),
+        Builder::new().int("val", -1),
+        Builder::new().long("val", 0),
+        Builder::new().long("val", 1),
+        Builder::new().long("val", -1),
+    ];
+    for builder in rejected {
+        let err = parse(builder).unwrap_err();
+        assert!(err.to_string().contains("expected byte of 0 or 1"), "{err}");
+    }
+
+    assert_eq!(
+        parse(Builder::new().int("val", -3))
+            .unwrap_err()
+            .to_string(),
+        "deserialize bool: expected byte of 0 or 1, found int -3"
+    );
+}
+
+fn parse_float<T: for<'de> Deserialize<'de> + Serialize>(
+    builder: Builder,
+    opts: DeOpts,
+) -> Result<T> {
+    let payload = Builder::new()
+        .start_compound("")
+        .raw_bytes(&builder.build())
+        .end_compound()
+        .build();
+    from_bytes_with_opts::<Single<T>>(&payload, opts).map(|v| v.val)
+}
+
+#[test]
+fn f32_from_integral_when_exact() {
+    let parse = |builder| parse_float::<f32>(builder, DeOpts::new());
+
+    assert_eq!(parse(Builder::new().byte("val", i8::MIN)).unwrap(), -128.0);
+    assert_eq!(
+        parse(Builder::new().short("val", i16::MAX)).unwrap(),
+        32767.0
+    );
+    assert_eq!(
+        parse(Builder::new().int("val", (1 << 24) - 1)).unwrap(),
+        16777215.0
+    );
+    assert_eq!(
+        parse(Builder::new().int("val", 1 << 24)).unwrap(),
+        16777216.0
+    );
+    assert_eq!(
+        parse(Builder::new().int("val", -(1 << 24))).unwrap(),
+        -16777216.0
+    );
+    assert_eq!(
+        parse(Builder::new().long("val", 1 << 24)).unwrap(),
+        16777216.0
+    );
+    assert_eq!(
+        parse(Builder::new().long("val", 1 << 40)).unwrap(),
+        1099511627776.0
+    );
+    assert_eq!(
+        parse(Builder::new().int("val", i32::MIN)).unwrap(),
+        -2147483648.0
+    );
+
+    let rejected = [
+        Builder::new().int("val", (1 << 24) + 1),
+        Builder::new().int("val", -(1 << 24) - 1),
+        Builder::new().int("val", i32::MAX),
+        Builder::new().long("val", (1 << 24) + 1),
+        Builder::new().long("val", i64::MAX),
+    ];
+    for builder in rejected {
+        let err = parse(builder).unwrap_err();
+        assert!(
+            err.to_string().contains("cannot be represented exactly"),
+            "{err}"
+        );
+    }
+
+    assert_eq!(
+        parse(Builder::new().int("val", 16777217))
+            .unwrap_err()
+            .to_string(),
+        "deserialize f32: int 16777217 cannot be represented exactly"
+    );
+}
+
+#[test]
+fn f64_from_integral_when_exact() {
+    let parse = |builder| parse_float::<f64>(builder, DeOpts::new());
+
+    assert_eq!(parse(Builder::new().byte("val", -1)).unwrap(), -1.0);
+    assert_eq!(
+        parse(Builder::new().short("val", i16::MIN)).unwrap(),
+        -32768.0
+    );
+    assert_eq!(
+        parse(Builder::new().int("val", i32::MAX)).unwrap(),
+        2147483647.0
+    );
+    assert_eq!(
+        parse(Builder::new().long("val", (1 << 53) - 1)).unwrap(),
+        9007199254740991.0
+    );
+    assert_eq!(
+        parse(Builder::new().long("val", 1 << 53)).unwrap(),
+        9007199254740992.0
+    );
+    assert_eq!(
+        parse(Builder::new().long("val", -(1 << 53))).unwrap(),
+        -9007199254740992.0
+    );
+    assert_eq!(
+        parse(Builder::new().long("val", i64::MIN)).unwrap(),
+        -9223372036854775808.0
+    );
+
+    let rejected = [
+        Builder::new().long("val", (1 << 53) + 1),
+        Builder::new().long("val", -(1 << 53) - 1),
+        Builder::new().long("val", i64::MAX),
+    ];
+    for builder in rejected {
+        let err = parse(builder).unwrap_err();
+        assert!(
+            err.to_string().contains("cannot be represented exactly"),
+            "{err}"
+        );
+    }
+
+    assert_eq!(
+        parse(Builder::new().long("val", 9007199254740993))
+            .unwrap_err()
+            .to_string(),
+        "deserialize f64: long 9007199254740993 cannot be represented exactly"
+    );
+}
+
+#[test]
+fn floats_from_list_of_integrals() {
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::Int, 3)
+        .int_payload(1)
+        .int_payload(-2)
+        .int_payload(1 << 24)
+        .end_compound()
+        .build();
+
+    let v: Single<Vec<f64>> = from_bytes(&payload).unwrap();
+    assert_eq!(v.val, [1.0, -2.0, 16777216.0]);
+    let v: Single<Vec<f32>> = from_bytes(&payload).unwrap();
+    assert_eq!(v.val, [1.0, -2.0, 16777216.0]);
+}
+
+#[test]
+fn strict_floats_only_from_float_or_double() {
+    let opts = DeOpts::new().strict_floats(true);
+
+    assert_eq!(
+        parse_float::<f32>(Builder::new().float("val", 1.5), opts.clone()).unwrap(),
+        1.5
+    );
+    assert_eq!(
+        parse_float::<f64>(Builder::new().double("val", 1.5), opts.clone()).unwrap(),
+        1.5
+    );
+
+    let rejected = [
+        Builder::new().byte("val", 1),
+        Builder::new().short("val", 1),
+        Builder::new().int("val", 1),
+        Builder::new().long("val", 1),
+    ];
+    for builder in rejected {
+        let err = parse_float::<f64>(builder, opts.clone()).unwrap_err();
+        assert!(
+            err.to_string().contains("expected float or double"),
+            "{err}"
+        );
+    }
+
+    assert_eq!(
+        parse_float::<f32>(Builder::new().short("val", 3), opts)
+            .unwrap_err()
+            .to_string(),
+        "deserialize f32: expected float or double, found short 3"
+    );
+}
+
 #[test]
 fn bool_from_none_integral() {
     #[derive(Deserialize)]
@@ -514,6 +751,100 @@ fn nested_hashmaps_with_enums() {
     }
 }
 
+#[test]
+fn hashmap_with_unit_variant_enum_keys() {
+    #[derive(Deserialize, PartialEq, Eq, Hash, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum HeightmapKind {
+        MotionBlocking,
+        MotionBlockingNoLeaves,
+        OceanFloor,
+        WorldSurface,
+        #[serde(rename = "LEGACY")]
+        Old,
+    }
+
+    let payload = Builder::new()
+        .start_compound("Heightmaps")
+        .long_array("MOTION_BLOCKING", &[1, 2])
+        .long_array("MOTION_BLOCKING_NO_LEAVES", &[3])
+        .long_array("OCEAN_FLOOR", &[4])
+        .long_array("WORLD_SURFACE", &[5])
+        .long_array("LEGACY", &[6])
+        .end_compound()
+        .build();
+
+    let v: HashMap<HeightmapKind, LongArray> = from_bytes(payload.as_slice()).unwrap();
+    assert_eq!(5, v.len());
+    assert_eq!(&[1, 2], &*v[&HeightmapKind::MotionBlocking]);
+    assert_eq!(&[3], &*v[&HeightmapKind::MotionBlockingNoLeaves]);
+    assert_eq!(&[4], &*v[&HeightmapKind::OceanFloor]);
+    assert_eq!(&[5], &*v[&HeightmapKind::WorldSurface]);
+    assert_eq!(&[6], &*v[&HeightmapKind::Old]);
+}
+
+#[test]
+fn hashmap_with_unknown_enum_key_errors() {
+    #[derive(Deserialize, PartialEq, Eq, Hash, Debug)]
+    enum Kind {
+        A,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .int("B", 1)
+        .end_compound()
+        .build();
+
+    let v: Result<HashMap<Kind, i32>> = from_bytes(payload.as_slice());
+    assert!(v.unwrap_err().to_string().contains("unknown variant"));
+}
+
+#[test]
+fn hashmap_with_newtype_keys() {
+    #[derive(Deserialize, PartialEq, Eq, Hash, Debug)]
+    struct BlockName(String);
+
+    #[derive(Deserialize, PartialEq, Eq, Hash, Debug)]
+    struct BorrowedName<'a>(&'a str);
+
+    let payload = Builder::new()
+        .start_compound("")
+        .int("minecraft:stone", 1)
+        .int("minecraft:dirt", 2)
+        .end_compound()
+        .build();
+
+    let v: HashMap<BlockName, i32> = from_bytes(payload.as_slice()).unwrap();
+    assert_eq!(v[&BlockName("minecraft:stone".to_string())], 1);
+    assert_eq!(v[&BlockName("minecraft:dirt".to_string())], 2);
+
+    let v: HashMap<BorrowedName, i32> = from_bytes(payload.as_slice()).unwrap();
+    assert_eq!(v[&BorrowedName("minecraft:stone")], 1);
+    assert_eq!(v[&BorrowedName("minecraft:dirt")], 2);
+}
+
+#[test]
+fn hashmap_with_unsupported_keys_errors() {
+    let payload = Builder::new()
+        .start_compound("")
+        .byte("1", 1)
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<HashMap<bool, i8>>(&payload).unwrap_err();
+    assert!(err.to_string().contains("as bool"));
+
+    let err = from_bytes::<HashMap<(), i8>>(&payload).unwrap_err();
+    assert!(err.to_string().contains("as unit"));
+
+    let err = from_bytes::<HashMap<i32, i8>>(&payload).unwrap_err();
+    assert!(err.to_string().contains("as i32"));
+
+    let err = from_bytes::<HashMap<(String, String), i8>>(&payload).unwrap_err();
+    assert!(err.to_string().contains("as tuple"));
+}
+
 #[test]
 fn simple_list() -> Result<()> {
     #[derive(Deserialize)]
@@ -778,6 +1109,69 @@ fn ignore_list_of_compound() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn ignore_lists_of_numbers() -> Result<()> {
+    #[derive(Deserialize)]
+    struct V {
+        a: u8,
+    }
+
+    let payload = Builder::new()
+        .start_compound("object")
+        .start_list("shorts", Tag::Short, 2)
+        .short_payload(1)
+        .short_payload(2)
+        .start_list("doubles", Tag::Double, 1)
+        .double_payload(1.5)
+        .start_list("empty", Tag::End, 0)
+        .start_list("nested", Tag::List, 2)
+        .start_anon_list(Tag::Long, 1)
+        .long_payload(3)
+        .start_anon_list(Tag::Float, 1)
+        .float_payload(4.0)
+        .byte("a", 123)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(payload.as_slice())?;
+    assert_eq!(v.a, 123);
+
+    Ok(())
+}
+
+#[test]
+fn ignore_list_of_numbers_past_eof_errors() {
+    #[derive(Deserialize, Debug)]
+    struct V {
+        _a: u8,
+    }
+
+    let payload = Builder::new()
+        .start_compound("object")
+        .start_list("ignored", Tag::Int, 2)
+        .int_payload(1)
+        .build();
+
+    assert!(from_bytes::<V>(payload.as_slice()).is_err());
+}
+
+#[test]
+fn ignore_list_of_end_with_elements_errors() {
+    #[derive(Deserialize, Debug)]
+    struct V {
+        _a: u8,
+    }
+
+    let payload = Builder::new()
+        .start_compound("object")
+        .start_list("ignored", Tag::End, 2)
+        .byte("_a", 1)
+        .end_compound()
+        .build();
+
+    assert!(from_bytes::<V>(payload.as_slice()).is_err());
+}
+
 #[test]
 fn byte_array_from_list_bytes() -> Result<()> {
     #[derive(Deserialize)]
@@ -1131,6 +1525,92 @@ fn unit_variant_enum() -> Result<()> {
     Ok(())
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+#[test]
+fn unit_variant_enum_from_integrals() {
+    let v: Single<GameMode> = from_bytes(
+        &Builder::new()
+            .start_compound("")
+            .byte("val", 1)
+            .end_compound()
+            .build(),
+    )
+    .unwrap();
+    assert_eq!(v.val, GameMode::Creative);
+
+    let v: Single<GameMode> = from_bytes(
+        &Builder::new()
+            .start_compound("")
+            .int("val", 3)
+            .end_compound()
+            .build(),
+    )
+    .unwrap();
+    assert_eq!(v.val, GameMode::Spectator);
+
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::Byte, 3)
+        .byte_payload(0)
+        .byte_payload(2)
+        .byte_payload(0)
+        .end_compound()
+        .build();
+    let v: Single<Vec<GameMode>> = from_bytes(&payload).unwrap();
+    assert_eq!(
+        v.val,
+        [GameMode::Survival, GameMode::Adventure, GameMode::Survival]
+    );
+
+    // Names still work too.
+    let v: Single<GameMode> = from_bytes(
+        &Builder::new()
+            .start_compound("")
+            .string("val", "Adventure")
+            .end_compound()
+            .build(),
+    )
+    .unwrap();
+    assert_eq!(v.val, GameMode::Adventure);
+}
+
+#[test]
+fn unit_variant_enum_from_invalid_integrals() {
+    let parse = |builder: Builder| {
+        from_bytes::<Single<GameMode>>(&builder.end_compound().build()).unwrap_err()
+    };
+
+    let err = parse(Builder::new().start_compound("").int("val", 4));
+    assert!(
+        err.to_string()
+            .contains("expected variant index 0 <= i < 4"),
+        "{err}"
+    );
+    let err = parse(Builder::new().start_compound("").byte("val", -1));
+    assert_eq!(
+        err.to_string(),
+        "deserialize enum: byte -1 is not a variant index"
+    );
+    let err = parse(Builder::new().start_compound("").long("val", 1 << 40));
+    assert!(err.to_string().contains("is not a variant index"), "{err}");
+}
+
+#[test]
+fn unit_variant_enum_from_integral_value() {
+    let v: GameMode = crate::from_value(&Value::Byte(2)).unwrap();
+    assert_eq!(v, GameMode::Adventure);
+    let v: GameMode = crate::from_value(&Value::Int(0)).unwrap();
+    assert_eq!(v, GameMode::Survival);
+    assert!(crate::from_value::<GameMode>(&Value::Short(9)).is_err());
+}
+
 #[test]
 fn integrals_in_fullvalue() {
     let payload = Builder::new()
@@ -1301,6 +1781,127 @@ fn can_borrow_cesu8_if_same_repr() {
     assert_eq!("abc", v.unwrap().name);
 }
 
+/// A compound with a Latin-1 key and value, as written by some old tools. The
+/// value also contains a CESU-8 surrogate pair.
+fn latin1_compound() -> Vec<u8> {
+    let mut value = b"caf\xe9 ".to_vec();
+    value.extend_from_slice(&cesu8::to_java_cesu8("😈"));
+
+    Builder::new()
+        .start_compound("")
+        .tag(Tag::String)
+        .raw_len(5)
+        .raw_bytes(b"na\xefve")
+        .raw_len(value.len())
+        .raw_bytes(&value)
+        .int("ok", 1)
+        .end_compound()
+        .build()
+}
+
+#[test]
+fn invalid_strings_error_by_default() {
+    let input = latin1_compound();
+    assert!(from_bytes::<Value>(&input).is_err());
+
+    let opts = DeOpts::new().invalid_strings(InvalidStrings::Error);
+    assert!(from_bytes_with_opts::<Value>(&input, opts).is_err());
+}
+
+#[test]
+fn invalid_strings_replaced() {
+    let input = latin1_compound();
+    let opts = DeOpts::new().invalid_strings(InvalidStrings::ReplaceInvalid);
+    let v: Value = from_bytes_with_opts(&input, opts).unwrap();
+
+    assert_eq!(
+        v,
+        Value::Compound(crate::Map::from_iter([
+            (
+                "na\u{FFFD}ve".to_string(),
+                Value::String("caf\u{FFFD} 😈".to_string())
+            ),
+            ("ok".to_string(), Value::Int(1)),
+        ]))
+    );
+}
+
+#[test]
+fn invalid_strings_lossy() {
+    let input = latin1_compound();
+    let opts = DeOpts::new().invalid_strings(InvalidStrings::Lossy);
+    let v: Value = from_bytes_with_opts(&input, opts).unwrap();
+
+    // the surrogate pair is not understood by UTF-8, each of its six bytes
+    // becomes a replacement character.
+    assert_eq!(
+        v,
+        Value::Compound(crate::Map::from_iter([
+            (
+                "na\u{FFFD}ve".to_string(),
+                Value::String(format!("caf\u{FFFD} {}", "\u{FFFD}".repeat(6)))
+            ),
+            ("ok".to_string(), Value::Int(1)),
+        ]))
+    );
+}
+
+#[test]
+fn invalid_strings_replaced_into_struct() {
+    #[derive(Deserialize, Debug)]
+    struct V {
+        #[serde(rename = "na\u{FFFD}ve")]
+        naive: String,
+        ok: i32,
+    }
+
+    let input = latin1_compound();
+    let opts = DeOpts::new().invalid_strings(InvalidStrings::ReplaceInvalid);
+    let v: V = from_bytes_with_opts(&input, opts).unwrap();
+    assert_eq!(v.naive, "caf\u{FFFD} 😈");
+    assert_eq!(v.ok, 1);
+}
+
+#[test]
+fn invalid_strings_replaced_keeps_java_null() {
+    // a lone surrogate is invalid, but the null next to it is not.
+    let input = Builder::new()
+        .start_compound("")
+        .tag(Tag::String)
+        .name("val")
+        .raw_len(7)
+        .raw_bytes(&[b'a', 0xED, 0xA0, 0x81, 0xC0, 0x80, b'b'])
+        .end_compound()
+        .build();
+
+    let opts = DeOpts::new().invalid_strings(InvalidStrings::ReplaceInvalid);
+    let v: Single<String> = from_bytes_with_opts(&input, opts).unwrap();
+    assert_eq!(v.val, "a\u{FFFD}\0b");
+}
+
+#[test]
+fn valid_cesu8_unaffected_by_invalid_string_policy() {
+    let null_and_pair = [&[0xC0, 0x80][..], &cesu8::to_java_cesu8("😈")].concat();
+    let input = Builder::new()
+        .start_compound("")
+        .tag(Tag::String)
+        .name("val")
+        .raw_len(null_and_pair.len())
+        .raw_bytes(&null_and_pair)
+        .end_compound()
+        .build();
+
+    for policy in [
+        InvalidStrings::Error,
+        InvalidStrings::ReplaceInvalid,
+        InvalidStrings::Lossy,
+    ] {
+        let v: Single<String> =
+            from_bytes_with_opts(&input, DeOpts::new().invalid_strings(policy)).unwrap();
+        assert_eq!(v.val, "\0😈");
+    }
+}
+
 #[test]
 fn can_cow_cesu8() {
     #[derive(Deserialize, Debug)]
@@ -1331,19 +1932,216 @@ fn can_cow_cesu8() {
 }
 
 #[test]
-fn large_list() {
-    let input = [10, 0, 0, 9, 0, 0, 10, 4, 0, 5, 252];
-    let _v: Result<Value> = from_bytes(&input);
-}
+fn cow_str_borrows_in_nested_positions() {
+    use crate::borrow::CowStr;
 
-#[test]
-fn hashmap_with_bytes() {
-    // Users should be able to decode strings as borrowed byte strings if they
-    // really want to.
-    let input = Builder::new()
-        .start_compound("")
-        .string("hello", "😈")
-        .end_compound()
+    #[derive(Deserialize, Debug)]
+    #[serde(untagged)]
+    enum E<'a> {
+        #[serde(borrow)]
+        Str(CowStr<'a>),
+        Int(i32),
+    }
+
+    #[derive(Deserialize, Debug)]
+    pub struct V<'a> {
+        #[serde(borrow)]
+        list: Vec<CowStr<'a>>,
+        #[serde(borrow)]
+        map: HashMap<CowStr<'a>, i32>,
+        #[serde(borrow)]
+        untagged: E<'a>,
+        #[serde(borrow)]
+        optional: Option<CowStr<'a>>,
+        #[serde(borrow)]
+        emoji: CowStr<'a>,
+    }
+
+    let modified_unicode_str = cesu8::to_java_cesu8("😈");
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("list", Tag::String, 2)
+        .string_payload("a")
+        .string_payload("b")
+        .start_compound("map")
+        .int("key", 1)
+        .end_compound()
+        .string("untagged", "c")
+        .string("optional", "d")
+        .tag(Tag::String)
+        .name("emoji")
+        .raw_len(modified_unicode_str.len())
+        .raw_bytes(&modified_unicode_str)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&input).unwrap();
+
+    assert!(v.list.iter().all(|s| matches!(s.0, Cow::Borrowed(_))));
+    assert_eq!(
+        vec!["a", "b"],
+        v.list.iter().map(|s| &**s).collect::<Vec<_>>()
+    );
+
+    let (key, value) = v.map.iter().next().unwrap();
+    assert!(matches!(key.0, Cow::Borrowed("key")));
+    assert_eq!(1, *value);
+    assert_eq!(1, v.map["key"]);
+
+    match v.untagged {
+        E::Str(CowStr(Cow::Borrowed(s))) => assert_eq!("c", s),
+        E::Str(s) => panic!("not borrowed: {:?}", s),
+        E::Int(i) => panic!("expected string, got {}", i),
+    }
+    assert!(matches!(v.optional, Some(CowStr(Cow::Borrowed("d")))));
+    assert!(matches!(v.emoji.0, Cow::Owned(_)));
+    assert_eq!("😈", &*v.emoji);
+}
+
+#[test]
+fn cow_keys_and_bytes() {
+    #[derive(Deserialize, Debug)]
+    pub struct V<'a> {
+        #[serde(borrow, deserialize_with = "crate::borrow::deserialize_cow_str_keys")]
+        map: HashMap<Cow<'a, str>, i32>,
+        #[serde(borrow, deserialize_with = "crate::borrow::deserialize_cow_bytes")]
+        bytes: Cow<'a, [u8]>,
+        #[serde(borrow, deserialize_with = "crate::borrow::deserialize_cow_bytes")]
+        list_bytes: Cow<'a, [u8]>,
+    }
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_compound("map")
+        .int("a", 1)
+        .int("b", 2)
+        .end_compound()
+        .byte_array("bytes", &[1, 2, 3])
+        .start_list("list_bytes", Tag::Byte, 2)
+        .byte_payload(4)
+        .byte_payload(5)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&input).unwrap();
+    assert!(v.map.keys().all(|k| matches!(k, Cow::Borrowed(_))));
+    assert_eq!(1, v.map["a"]);
+    assert_eq!(2, v.map["b"]);
+    assert!(matches!(v.bytes, Cow::Borrowed(&[1, 2, 3])));
+    assert!(matches!(v.list_bytes, Cow::Borrowed(&[4, 5])));
+}
+
+#[test]
+fn cesu8_key_names() {
+    let modified_unicode_str = cesu8::to_java_cesu8("😈");
+
+    let input = Builder::new()
+        .start_compound("")
+        .tag(Tag::String)
+        .raw_len(modified_unicode_str.len())
+        .raw_bytes(&modified_unicode_str)
+        .string_payload("value")
+        .string("é", "ascii key neighbour")
+        .end_compound()
+        .build();
+
+    let v: HashMap<String, String> = from_bytes(&input).unwrap();
+    assert_eq!("value", v["😈"]);
+    assert_eq!("ascii key neighbour", v["é"]);
+}
+
+#[test]
+fn large_list() {
+    let input = [10, 0, 0, 9, 0, 0, 10, 4, 0, 5, 252];
+    let _v: Result<Value> = from_bytes(&input);
+}
+
+#[test]
+fn absurd_list_length_into_value_fails_fast() {
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("list", Tag::Long, i32::MAX - 1)
+        .long_payload(1)
+        .end_compound()
+        .build();
+
+    let v: Result<Value> = from_bytes_with_opts(&input, DeOpts::new().max_seq_len(usize::MAX));
+    assert!(v.is_err());
+}
+
+#[test]
+fn absurd_list_length_into_vec_fails_fast() {
+    #[derive(Deserialize, Debug)]
+    struct V {
+        _list: Vec<Value>,
+    }
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("_list", Tag::Compound, i32::MAX - 1)
+        .end_anon_compound()
+        .end_compound()
+        .build();
+
+    let v: Result<V> = from_bytes_with_opts(&input, DeOpts::new().max_seq_len(usize::MAX));
+    assert!(v.is_err());
+}
+
+#[test]
+fn absurd_ignored_list_length_fails_fast() {
+    #[derive(Deserialize, Debug)]
+    struct V {}
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("ignored", Tag::List, i32::MAX)
+        .end_compound()
+        .build();
+
+    let v: Result<V> = from_bytes(&input);
+    assert!(v.is_err());
+}
+
+#[test]
+fn list_length_exactly_fitting_input_is_fine() {
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("list", Tag::Int, 2)
+        .int_payload(1)
+        .int_payload(2)
+        .end_compound()
+        .build();
+
+    let v: Value = from_bytes(&input).unwrap();
+    assert_eq!(v, nbt!({"list": [1, 2]}));
+}
+
+#[test]
+fn ignored_string_longer_than_input_errors() {
+    #[derive(Deserialize, Debug)]
+    struct V {}
+
+    let input = Builder::new()
+        .start_compound("")
+        .tag(Tag::String)
+        .name("ignored")
+        .raw_len(1000)
+        .raw_bytes(b"short")
+        .build();
+
+    let v: Result<V> = from_bytes(&input);
+    assert!(v.is_err());
+}
+
+#[test]
+fn hashmap_with_bytes() {
+    // Users should be able to decode strings as borrowed byte strings if they
+    // really want to.
+    let input = Builder::new()
+        .start_compound("")
+        .string("hello", "😈")
+        .end_compound()
         .build();
 
     let v: HashMap<&[u8], &[u8]> = from_bytes(&input).unwrap();
@@ -1431,3 +2229,658 @@ fn tuple_struct() {
     let v: Single<Rgb> = from_bytes(&input).unwrap();
     assert!(matches!(v.val, Rgb(1, 2, 3)));
 }
+
+#[test]
+fn entity_tuples() {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Entity {
+        pos: (f64, f64, f64),
+        motion: (f64, f64, f64),
+        rotation: (f32, f32),
+        on_ground: bool,
+    }
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("Pos", Tag::Double, 3)
+        .double_payload(1.5)
+        .double_payload(64.0)
+        .double_payload(-3.5)
+        .start_list("Motion", Tag::Double, 3)
+        .double_payload(0.0)
+        .double_payload(-0.08)
+        .double_payload(0.0)
+        .start_list("Rotation", Tag::Float, 2)
+        .float_payload(90.0)
+        .float_payload(-10.0)
+        .byte("OnGround", 1)
+        .end_compound()
+        .build();
+
+    let v: Entity = from_bytes(&input).unwrap();
+    assert_eq!(v.pos, (1.5, 64.0, -3.5));
+    assert_eq!(v.motion, (0.0, -0.08, 0.0));
+    assert_eq!(v.rotation, (90.0, -10.0));
+    assert!(v.on_ground);
+}
+
+#[test]
+fn tuple_from_longer_list_errors() {
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::Float, 3)
+        .float_payload(90.0)
+        .float_payload(-10.0)
+        .float_payload(0.0)
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Single<(f32, f32)>>(&input).unwrap_err();
+    assert!(err.to_string().contains("expected tuple of 2, list has 3"));
+}
+
+#[test]
+fn tuple_from_shorter_list_errors() {
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::Double, 2)
+        .double_payload(1.0)
+        .double_payload(2.0)
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Single<(f64, f64, f64)>>(&input).unwrap_err();
+    assert!(err.to_string().contains("expected tuple of 3, list has 2"));
+}
+
+#[test]
+fn tuple_trailing_options_from_shorter_list() {
+    #[derive(Deserialize)]
+    struct V {
+        a: (i32, Option<i32>, Option<i32>),
+        b: i32,
+    }
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("a", Tag::Int, 2)
+        .int_payload(1)
+        .int_payload(2)
+        .int("b", 3)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&input).unwrap();
+    assert_eq!(v.a, (1, Some(2), None));
+    assert_eq!(v.b, 3);
+}
+
+#[test]
+fn tuple_struct_from_shorter_list_errors() {
+    #[derive(Deserialize, Serialize, Debug)]
+    struct Rgb(u8, u8, u8);
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::Byte, 2)
+        .byte_payload(1)
+        .byte_payload(2)
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Single<Rgb>>(&input).unwrap_err();
+    assert!(err.to_string().contains("expected tuple of 3, list has 2"));
+}
+
+fn nested_compounds(depth: usize) -> Vec<u8> {
+    let mut builder = Builder::new().start_compound("").int("a", 1);
+    for _ in 1..depth {
+        builder = builder.start_compound("nested");
+    }
+    for _ in 1..depth {
+        builder = builder.end_compound();
+    }
+    builder.end_compound().build()
+}
+
+#[test]
+fn default_opts_behave_like_from_bytes() {
+    let valid = nested_compounds(5);
+    let mut trailing = valid.clone();
+    trailing.extend_from_slice(&[1, 2, 3]);
+    let truncated = &valid[..valid.len() - 2];
+    let long_list = Builder::new()
+        .start_compound("")
+        .start_list("list", Tag::Byte, 100_000)
+        .end_compound()
+        .build();
+
+    for input in [&valid[..], &trailing, truncated, &long_list] {
+        let plain = from_bytes::<Value>(input);
+        let with_opts = from_bytes_with_opts::<Value>(input, DeOpts::new());
+        match (plain, with_opts) {
+            (Ok(a), Ok(b)) => assert_eq!(a, b),
+            (Err(a), Err(b)) => assert_eq!(a.to_string(), b.to_string()),
+            (a, b) => panic!("from_bytes gave {:?}, with default opts gave {:?}", a, b),
+        }
+    }
+
+    assert!(from_bytes::<Value>(&trailing).is_ok());
+    assert!(from_bytes::<Value>(&nested_compounds(100)).is_ok());
+}
+
+#[test]
+fn max_depth_limits_nested_compounds() {
+    let input = nested_compounds(3);
+
+    let v: Result<Value> = from_bytes_with_opts(&input, DeOpts::new().max_depth(3));
+    assert!(v.is_ok());
+
+    let v: Result<Value> = from_bytes_with_opts(&input, DeOpts::new().max_depth(2));
+    assert!(v.is_err());
+
+    let v: Result<Value> = from_bytes_with_opts(&input, DeOpts::new().max_depth(0));
+    assert!(v.is_err());
+}
+
+#[test]
+fn max_depth_limits_nested_lists() {
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("outer", Tag::List, 1)
+        .start_anon_list(Tag::Int, 1)
+        .int_payload(1)
+        .end_compound()
+        .build();
+
+    let v: Result<Value> = from_bytes_with_opts(&input, DeOpts::new().max_depth(3));
+    assert!(v.is_ok());
+
+    let v: Result<Value> = from_bytes_with_opts(&input, DeOpts::new().max_depth(2));
+    assert!(v.is_err());
+}
+
+#[test]
+fn max_depth_applies_to_ignored_fields() {
+    #[derive(Deserialize)]
+    struct V {
+        a: i32,
+    }
+
+    let input = nested_compounds(4);
+
+    let v: V = from_bytes_with_opts(&input, DeOpts::new().max_depth(4)).unwrap();
+    assert_eq!(v.a, 1);
+
+    let v: Result<V> = from_bytes_with_opts(&input, DeOpts::new().max_depth(3));
+    assert!(v.is_err());
+}
+
+#[test]
+fn strict_trailing_rejects_data_after_root() {
+    let mut input = nested_compounds(1);
+    let v: Result<Value> = from_bytes_with_opts(&input, DeOpts::new().strict_trailing(true));
+    assert!(v.is_ok());
+
+    input.push(0);
+    let v: Result<Value> = from_bytes_with_opts(&input, DeOpts::new().strict_trailing(true));
+    assert!(v.is_err());
+
+    let v: Result<Value> = from_bytes_with_opts(&input, DeOpts::new().strict_trailing(false));
+    assert!(v.is_ok());
+}
+
+#[test]
+fn stream_of_documents() {
+    let first = Builder::new()
+        .start_compound("")
+        .int("a", 1)
+        .end_compound()
+        .build();
+    let second = Builder::new()
+        .start_compound("named")
+        .start_list("list", Tag::String, 2)
+        .string_payload("x")
+        .string_payload("y")
+        .start_compound("inner")
+        .double("d", 0.5)
+        .end_compound()
+        .end_compound()
+        .build();
+    let third = Builder::new()
+        .start_compound("")
+        .long_array("longs", &[1, 2])
+        .end_compound()
+        .build();
+
+    let input = [&first[..], &second, &third].concat();
+    let mut stream = Deserializer::from_bytes(&input, DeOpts::new()).into_iter::<Value>();
+
+    assert_eq!(stream.next().unwrap().unwrap(), nbt!({"a": 1}));
+    assert_eq!(stream.byte_offset(), first.len());
+
+    assert_eq!(
+        stream.next().unwrap().unwrap(),
+        nbt!({"list": ["x", "y"], "inner": {"d": 0.5}})
+    );
+    assert_eq!(stream.byte_offset(), first.len() + second.len());
+
+    assert_eq!(stream.next().unwrap().unwrap(), nbt!({"longs": [L; 1, 2]}));
+    assert_eq!(stream.byte_offset(), input.len());
+
+    assert!(stream.next().is_none());
+    assert_eq!(stream.error_offset(), None);
+}
+
+#[test]
+fn stream_of_documents_stops_at_error() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Doc {
+        a: i32,
+    }
+
+    let good = Builder::new()
+        .start_compound("")
+        .int("a", 1)
+        .end_compound()
+        .build();
+    let bad = Builder::new()
+        .start_compound("")
+        .int("a", 2)
+        .raw_bytes(&[99])
+        .end_compound()
+        .build();
+
+    let input = [&good[..], &bad, &good].concat();
+    let mut stream = Deserializer::from_bytes(&input, DeOpts::new()).into_iter::<Doc>();
+
+    assert_eq!(stream.next().unwrap().unwrap(), Doc { a: 1 });
+    assert!(stream.next().unwrap().is_err());
+    assert!(stream.next().is_none());
+
+    // the offset of the failed document, and how far into it the error was.
+    assert_eq!(stream.byte_offset(), good.len());
+    assert_eq!(stream.error_offset(), Some(good.len() + bad.len() - 1));
+}
+
+#[test]
+fn stream_of_no_documents() {
+    let mut stream = Deserializer::from_bytes(&[], DeOpts::new()).into_iter::<Value>();
+    assert!(stream.next().is_none());
+    assert_eq!(stream.byte_offset(), 0);
+}
+
+#[test]
+fn raw_compound_skips_and_parses_later() {
+    use crate::borrow::RawCompound;
+
+    #[derive(Deserialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        raw: RawCompound<'a>,
+        after: i32,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Inner {
+        a: i32,
+        b: String,
+    }
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_compound("raw")
+        .int("a", 1)
+        .string("b", "hello")
+        .start_list("ignored", Tag::Long, 2)
+        .long_payload(1)
+        .long_payload(2)
+        .end_compound()
+        .int("after", 3)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&input).unwrap();
+    assert_eq!(v.after, 3);
+    assert_eq!(*v.raw.as_bytes().last().unwrap(), Tag::End as u8);
+
+    let inner: Inner = v.raw.parse().unwrap();
+    assert_eq!(
+        inner,
+        Inner {
+            a: 1,
+            b: "hello".to_owned()
+        }
+    );
+
+    let value: Value = v.raw.parse().unwrap();
+    assert_eq!(
+        value,
+        crate::nbt!({"a": 1, "b": "hello", "ignored": [1i64, 2i64]})
+    );
+}
+
+#[test]
+fn raw_compound_in_list() {
+    use crate::borrow::RawCompound;
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::Compound, 2)
+        .int("val", 1)
+        .end_anon_compound()
+        .int("val", 2)
+        .end_anon_compound()
+        .end_compound()
+        .build();
+
+    let v: Single<Vec<RawCompound>> = from_bytes(&input).unwrap();
+    let values: Vec<Single<i32>> = v.val.iter().map(|raw| raw.parse().unwrap()).collect();
+    assert_eq!(values, [Single { val: 1 }, Single { val: 2 }]);
+}
+
+#[test]
+fn raw_compound_rejects_other_tags() {
+    use crate::borrow::RawCompound;
+
+    let input = Builder::new()
+        .start_compound("")
+        .int("val", 1)
+        .end_compound()
+        .build();
+    let v: Result<Single<RawCompound>> = from_bytes(&input);
+    assert!(v.is_err());
+}
+
+#[test]
+fn raw_compound_round_trips() {
+    use crate::borrow::RawCompound;
+
+    let original = crate::nbt!({"val": {"a": 1, "b": [1i8, 2i8]}});
+    let input = crate::to_bytes(&original).unwrap();
+
+    let v: Single<RawCompound> = from_bytes(&input).unwrap();
+    let output = crate::to_bytes(&v).unwrap();
+    assert_eq!(from_bytes::<Value>(&output).unwrap(), original);
+}
+
+#[test]
+fn io_errors_are_kept() {
+    use std::error::Error as _;
+
+    let input = Builder::new().start_compound("").int("val", 1).build();
+    let err = from_bytes::<Single<i64>>(&input[..input.len() - 2]).unwrap_err();
+
+    let io = err.io_error().unwrap();
+    assert_eq!(io.kind(), std::io::ErrorKind::UnexpectedEof);
+    assert!(err.source().unwrap().is::<std::io::Error>());
+
+    assert!(Error::bespoke("other".to_owned()).source().is_none());
+}
+
+#[test]
+fn key_name_longer_than_input_is_eof() {
+    let input = Builder::new()
+        .start_compound("")
+        .tag(Tag::Int)
+        .raw_len(0xFFFF)
+        .build();
+
+    for err in [
+        from_bytes::<Value>(&input).unwrap_err(),
+        from_bytes::<Single<i32>>(&input).unwrap_err(),
+    ] {
+        assert_eq!(
+            err.io_error().unwrap().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+        assert_eq!(
+            err.to_string(),
+            "eof: string at offset 4 is 65535 bytes long, but only 0 bytes of input remain"
+        );
+    }
+}
+
+#[test]
+fn string_payload_longer_than_input_is_eof() {
+    #[derive(Deserialize, Debug)]
+    struct Ignored {}
+
+    let input = Builder::new()
+        .start_compound("")
+        .tag(Tag::String)
+        .name("val")
+        .raw_len(0xFFFF)
+        .raw_bytes(b"abc")
+        .build();
+
+    let errs = [
+        from_bytes::<Value>(&input).unwrap_err(),
+        from_bytes::<Single<String>>(&input).unwrap_err(),
+        from_bytes::<Single<&[u8]>>(&input).unwrap_err(),
+        // Skipped strings are checked too.
+        from_bytes::<Ignored>(&input).unwrap_err(),
+    ];
+    for err in errs {
+        assert_eq!(
+            err.io_error().unwrap().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+        assert_eq!(
+            err.to_string(),
+            "eof: string at offset 9 is 65535 bytes long, but only 3 bytes of input remain"
+        );
+    }
+}
+
+#[test]
+fn negative_array_lengths_error_with_offset() {
+    #[derive(Deserialize, Debug)]
+    struct Ignored {}
+
+    for tag in [Tag::ByteArray, Tag::IntArray, Tag::LongArray] {
+        let input = Builder::new()
+            .start_compound("")
+            .tag(tag)
+            .name("val")
+            .int_payload(-1)
+            .end_compound()
+            .build();
+
+        let mut errs = vec![
+            from_bytes::<Value>(&input).unwrap_err(),
+            from_bytes::<Ignored>(&input).unwrap_err(),
+            from_bytes::<Single<&[u8]>>(&input).unwrap_err(),
+        ];
+        match tag {
+            Tag::ByteArray => errs.push(from_bytes::<Single<ByteArray>>(&input).unwrap_err()),
+            Tag::IntArray => {
+                errs.push(from_bytes::<Single<IntArray>>(&input).unwrap_err());
+                errs.push(from_bytes::<Single<i128>>(&input).unwrap_err());
+            }
+            _ => {
+                errs.push(from_bytes::<Single<LongArray>>(&input).unwrap_err());
+                errs.push(from_bytes::<Single<crate::borrow::LongArray>>(&input).unwrap_err());
+            }
+        }
+
+        for err in errs {
+            assert_eq!(
+                err.to_string(),
+                "invalid nbt list/array size at offset 9: -1 is negative",
+                "{}",
+                tag
+            );
+        }
+    }
+}
+
+#[test]
+fn negative_list_lengths_error_with_offset() {
+    #[derive(Deserialize, Debug)]
+    struct Ignored {}
+
+    for element_tag in [Tag::Int, Tag::Compound, Tag::End] {
+        let input = Builder::new()
+            .start_compound("")
+            .start_list("val", element_tag, i32::MIN)
+            .end_compound()
+            .build();
+
+        let errs = [
+            from_bytes::<Value>(&input).unwrap_err(),
+            from_bytes::<Ignored>(&input).unwrap_err(),
+            from_bytes::<Single<Vec<Value>>>(&input).unwrap_err(),
+            from_bytes::<Single<&[u8]>>(&input).unwrap_err(),
+            from_bytes::<Single<(i32, i32)>>(&input).unwrap_err(),
+        ];
+        for err in errs {
+            assert_eq!(
+                err.to_string(),
+                "invalid nbt list/array size at offset 10: -2147483648 is negative",
+            );
+        }
+    }
+}
+
+#[test]
+fn negative_nested_lengths_error_with_offset() {
+    // A list of lists, where the second inner list is negative.
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::List, 2)
+        .start_anon_list(Tag::Int, 1)
+        .int_payload(1)
+        .start_anon_list(Tag::Int, -5)
+        .end_compound()
+        .build();
+
+    for err in [
+        from_bytes::<Value>(&input).unwrap_err(),
+        from_bytes::<Single<Vec<Vec<i32>>>>(&input).unwrap_err(),
+    ] {
+        assert_eq!(
+            err.to_string(),
+            "invalid nbt list/array size at offset 24: -5 is negative",
+        );
+    }
+
+    // A list of arrays.
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::IntArray, 1)
+        .int_payload(-1)
+        .end_compound()
+        .build();
+    assert_eq!(
+        from_bytes::<Single<Vec<IntArray>>>(&input)
+            .unwrap_err()
+            .to_string(),
+        "invalid nbt list/array size at offset 14: -1 is negative",
+    );
+}
+
+#[test]
+fn list_of_end_errors_at_list() {
+    #[derive(Deserialize, Debug)]
+    struct Ignored {}
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::End, 3)
+        .end_compound()
+        .build();
+
+    for err in [
+        from_bytes::<Value>(&input).unwrap_err(),
+        from_bytes::<Ignored>(&input).unwrap_err(),
+        from_bytes::<Single<Vec<i32>>>(&input).unwrap_err(),
+    ] {
+        assert_eq!(
+            err.to_string(),
+            "list at offset 0x9 is malformed: 3 elements of type 'end', which is not supported",
+        );
+        assert_eq!(err.offset(), Some(9));
+    }
+}
+
+#[test]
+fn malformed_list_of_compounds_errors_at_list() {
+    #[derive(Deserialize, Debug)]
+    struct Ignored {}
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Item {
+        #[serde(rename = "Count")]
+        count: i8,
+    }
+
+    // Claims three compounds but has one, so the fields after it are read as
+    // the rest of the list.
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::Compound, 3)
+        .byte("Count", 1)
+        .end_anon_compound()
+        .string("id", "minecraft:stone")
+        .int("after", 1)
+        .end_compound()
+        .build();
+
+    for err in [
+        from_bytes::<Value>(&input).unwrap_err(),
+        from_bytes::<Ignored>(&input).unwrap_err(),
+        from_bytes::<Single<Vec<Item>>>(&input).unwrap_err(),
+    ] {
+        assert!(
+            err.to_string()
+                .starts_with("list at offset 0x9 is malformed: "),
+            "{}",
+            err
+        );
+        assert_eq!(err.offset(), Some(9));
+    }
+}
+
+#[test]
+fn malformed_nested_list_errors_at_inner_list() {
+    // A list of compounds, the second holding a list that claims to be of
+    // longs but runs out.
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::Compound, 2)
+        .byte("a", 1)
+        .end_anon_compound()
+        .start_list("inner", Tag::Long, 2)
+        .long_payload(1)
+        .end_anon_compound()
+        .end_compound()
+        .build();
+
+    for err in [
+        from_bytes::<Value>(&input).unwrap_err(),
+        from_bytes::<Single<Vec<Value>>>(&input).unwrap_err(),
+    ] {
+        assert_eq!(err.offset(), Some(28), "{}", err);
+        assert!(err
+            .to_string()
+            .starts_with("list at offset 0x1c is malformed: "));
+    }
+}
+
+#[test]
+fn invalid_tags_in_list_error_at_list() {
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::Compound, 1)
+        .raw_bytes(&[99])
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Value>(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "list at offset 0x9 is malformed: invalid nbt tag value: 99"
+    );
+}
@@ -5,6 +5,7 @@ use crate::error::Result;
 use crate::ByteArray;
 use crate::IntArray;
 use crate::LongArray;
+use crate::Tag;
 use crate::{from_bytes, test::builder::Builder};
 
 #[test]
@@ -206,3 +207,170 @@ fn nice_error_if_deserialize_array_to_seq() {
         Err(e) => assert!(e.to_string().contains("Array")),
     }
 }
+
+#[test]
+fn int_array_to_fixed_array() -> Result<()> {
+    #[derive(Deserialize)]
+    struct V {
+        #[serde(rename = "UUID")]
+        uuid: [i32; 4],
+        after: i8,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .int_array("UUID", &[1, -2, 3, -4])
+        .byte("after", 5)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&payload)?;
+    assert_eq!(v.uuid, [1, -2, 3, -4]);
+    assert_eq!(v.after, 5);
+
+    Ok(())
+}
+
+#[test]
+fn int_array_to_tuple() -> Result<()> {
+    #[derive(Deserialize)]
+    struct V {
+        pos: (i32, i64),
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .int_array("pos", &[7, -8])
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&payload)?;
+    assert_eq!(v.pos, (7, -8));
+
+    Ok(())
+}
+
+#[test]
+fn fixed_array_of_wrong_length_errors() {
+    #[derive(Deserialize, Debug)]
+    struct V {
+        _uuid: [i32; 4],
+    }
+
+    for ints in [&[1, 2, 3][..], &[1, 2, 3, 4, 5]] {
+        let payload = Builder::new()
+            .start_compound("")
+            .int_array("_uuid", ints)
+            .end_compound()
+            .build();
+
+        let err = from_bytes::<V>(&payload).unwrap_err();
+        assert!(
+            err.to_string().contains(&format!(
+                "expected array of 4, int-array has {}",
+                ints.len()
+            )),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn long_array_to_long_fixed_array() -> Result<()> {
+    #[derive(Deserialize)]
+    struct V {
+        #[serde(with = "crate::fixed_array")]
+        heightmap: [i64; 37],
+    }
+
+    let longs: Vec<i64> = (0..37).map(|i| i * (i64::MAX / 37)).collect();
+    let payload = Builder::new()
+        .start_compound("")
+        .long_array("heightmap", &longs)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&payload)?;
+    assert_eq!(&v.heightmap[..], &longs);
+
+    Ok(())
+}
+
+#[test]
+fn byte_array_to_unsigned_fixed_array() -> Result<()> {
+    #[derive(Deserialize)]
+    struct V {
+        #[serde(rename = "Biomes", with = "crate::fixed_array")]
+        biomes: [u8; 256],
+    }
+
+    let biomes: Vec<i8> = (0..=255u8).map(|b| b as i8).collect();
+    let payload = Builder::new()
+        .start_compound("")
+        .byte_array("Biomes", &biomes)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&payload)?;
+    assert!(v.biomes.iter().copied().eq(0..=255));
+
+    Ok(())
+}
+
+#[test]
+fn list_to_long_fixed_array() {
+    #[derive(Deserialize, Debug)]
+    struct V {
+        #[serde(with = "crate::fixed_array")]
+        _longs: [i64; 33],
+    }
+
+    let list = |len: i32| {
+        let mut builder = Builder::new()
+            .start_compound("")
+            .start_list("_longs", Tag::Long, len);
+        for i in 0..len {
+            builder = builder.long_payload(i as i64);
+        }
+        builder.end_compound().build()
+    };
+
+    let v = from_bytes::<V>(&list(33)).unwrap();
+    assert!(v._longs.iter().copied().eq(0..33));
+
+    let err = from_bytes::<V>(&list(32)).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("expected tuple of 33, list has 32"));
+}
+
+#[test]
+fn arrays_in_flattened_struct() -> Result<()> {
+    #[derive(Deserialize)]
+    struct Inner {
+        bs: ByteArray,
+        is: IntArray,
+        ls: LongArray,
+    }
+
+    #[derive(Deserialize)]
+    struct V {
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .byte_array("bs", &[1, -2])
+        .int_array("is", &[3, -4])
+        .long_array("ls", &[5, -6])
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&payload)?;
+    assert_eq!(&*v.inner.bs, &[1, -2]);
+    assert_eq!(&*v.inner.is, &[3, -4]);
+    assert_eq!(&*v.inner.ls, &[5, -6]);
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use serde::Deserialize;
+
+use crate::{extract, from_bytes, to_bytes, Tag, Value};
+
+use super::builder::Builder;
+use super::resources::{CHUNK_RAW, CHUNK_RAW_WITH_ENTITIES};
+
+fn nested() -> Vec<u8> {
+    to_bytes(&nbt!({
+        "Level": {
+            "Status": "full",
+            "xPos": 5,
+            "zPos": -2,
+            "Sections": [
+                { "Y": 0i8, "BlockLight": [B; 1, 2] },
+                { "Y": 1i8, "Palette": [{ "Name": "minecraft:stone" }] },
+            ],
+            "Heightmaps": { "WORLD_SURFACE": [L; 1, 2, 3] },
+        },
+        "DataVersion": 2730,
+        "ints": [1, 2, 3],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn leaves() {
+    let values = extract(&nested(), &["Level.Status", "Level.xPos", "DataVersion"]).unwrap();
+    assert_eq!(
+        values,
+        [Some(nbt!("full")), Some(nbt!(5)), Some(nbt!(2730))]
+    );
+}
+
+#[test]
+fn missing_paths_are_none() {
+    let values = extract(
+        &nested(),
+        &[
+            "Level.yPos",
+            "Nope",
+            "Level.Status.deeper",
+            "Level.Sections.5",
+        ],
+    )
+    .unwrap();
+    assert_eq!(values, [None, None, None, None]);
+}
+
+#[test]
+fn list_indices() {
+    let values = extract(
+        &nested(),
+        &[
+            "Level.Sections.1.Palette.0.Name",
+            "Level.Sections.0.Y",
+            "ints.2",
+            "ints.x",
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        values,
+        [
+            Some(nbt!("minecraft:stone")),
+            Some(nbt!(0i8)),
+            Some(nbt!(3)),
+            None
+        ]
+    );
+}
+
+#[test]
+fn whole_compounds_lists_and_arrays() {
+    let values = extract(
+        &nested(),
+        &[
+            "Level.Heightmaps",
+            "Level.Sections.0.BlockLight",
+            "Level.Sections",
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(values[0], Some(nbt!({ "WORLD_SURFACE": [L; 1, 2, 3] })));
+    assert_eq!(values[1], Some(nbt!([B; 1, 2])));
+    assert_eq!(
+        values[2].as_ref().unwrap().pointer("/1/Y"),
+        Some(&nbt!(1i8))
+    );
+}
+
+#[test]
+fn paths_inside_a_wanted_value() {
+    let values = extract(&nested(), &["Level.Heightmaps.WORLD_SURFACE", "Level"]).unwrap();
+    assert_eq!(values[0], Some(nbt!([L; 1, 2, 3])));
+    assert_eq!(
+        values[1].as_ref().unwrap().pointer("/zPos"),
+        Some(&nbt!(-2))
+    );
+}
+
+#[test]
+fn arrays_cannot_be_indexed() {
+    let values = extract(&nested(), &["Level.Heightmaps.WORLD_SURFACE.0"]).unwrap();
+    assert_eq!(values, [None]);
+}
+
+#[test]
+fn same_path_twice() {
+    let values = extract(&nested(), &["Level.zPos", "Level.zPos"]).unwrap();
+    assert_eq!(values, [Some(nbt!(-2)), Some(nbt!(-2))]);
+}
+
+#[test]
+fn no_paths() {
+    assert_eq!(extract(&nested(), &[]).unwrap(), []);
+}
+
+#[test]
+fn empty_lists_keep_their_tag() {
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("list", Tag::Int, 0)
+        .end_compound()
+        .build();
+
+    let values = extract(&input, &["list"]).unwrap();
+    assert_eq!(values, [Some(Value::EmptyList(Tag::Int))]);
+}
+
+#[test]
+fn non_ascii_keys() {
+    let input = to_bytes(&nbt!({ "ключ": { "😈": 1, "\0": 2 } })).unwrap();
+    let values = extract(&input, &["ключ.😈", "ключ.\0"]).unwrap();
+    assert_eq!(values, [Some(nbt!(1)), Some(nbt!(2))]);
+}
+
+#[test]
+fn stops_once_everything_is_found() {
+    let mut input = Builder::new().start_compound("").int("first", 1).build();
+    // Not NBT, and never reached.
+    input.extend_from_slice(&[99, 99, 99]);
+
+    assert_eq!(extract(&input, &["first"]).unwrap(), [Some(nbt!(1))]);
+    assert!(extract(&input, &["first", "second"]).is_err());
+}
+
+#[test]
+fn not_a_compound() {
+    let input = Builder::new().tag(Tag::Int).name("").int_payload(1).build();
+    assert!(extract(&input, &["a"]).is_err());
+}
+
+#[test]
+fn truncated_input() {
+    let input = nested();
+    let expected = extract(&input, &["ints"]).unwrap();
+
+    for len in 0..input.len() {
+        // Either an error, or it found the value before the input ran out.
+        if let Ok(values) = extract(&input[..len], &["ints"]) {
+            assert_eq!(values, expected, "{len}");
+        }
+    }
+    assert!(extract(&input[..3], &["ints"]).is_err());
+}
+
+#[test]
+fn same_as_deserializing_real_chunks() {
+    #[derive(Deserialize)]
+    struct Chunk {
+        #[serde(rename = "DataVersion")]
+        data_version: Value,
+        #[serde(rename = "Level")]
+        level: Level,
+    }
+
+    #[derive(Deserialize)]
+    struct Level {
+        #[serde(rename = "Status")]
+        status: Value,
+        #[serde(rename = "xPos")]
+        x: Value,
+        #[serde(rename = "zPos")]
+        z: Value,
+        #[serde(rename = "Sections")]
+        sections: Value,
+    }
+
+    for input in [CHUNK_RAW, CHUNK_RAW_WITH_ENTITIES] {
+        let chunk: Chunk = from_bytes(input).unwrap();
+        let values = extract(
+            input,
+            &[
+                "DataVersion",
+                "Level.Status",
+                "Level.xPos",
+                "Level.zPos",
+                "Level.Sections",
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            values,
+            [
+                Some(chunk.data_version),
+                Some(chunk.level.status),
+                Some(chunk.level.x),
+                Some(chunk.level.z),
+                Some(chunk.level.sections),
+            ]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::borrow::Cow;
+
+use crate::java_string::{decode, encode};
+
+const STRINGS: &[&str] = &[
+    "",
+    "hello",
+    "héllo wörld",
+    "\0",
+    "a\0b",
+    "😈",
+    "a😈b\0c",
+    "\u{FFFF}\u{10000}\u{10FFFF}",
+    "日本語",
+];
+
+#[test]
+fn encode_matches_java_cesu8() {
+    for s in STRINGS {
+        assert_eq!(encode(s), cesu8::to_java_cesu8(s), "{s:?}");
+    }
+}
+
+#[test]
+fn encode_borrows_when_same_as_utf8() {
+    assert!(matches!(encode("héllo"), Cow::Borrowed(_)));
+    assert!(matches!(encode("a\0b"), Cow::Owned(_)));
+    assert!(matches!(encode("😈"), Cow::Owned(_)));
+}
+
+#[test]
+fn decode_round_trips_encode() {
+    for s in STRINGS {
+        assert_eq!(decode(&encode(s)).as_deref(), Some(*s), "{s:?}");
+    }
+}
+
+#[test]
+fn decode_rejects_what_java_cesu8_rejects() {
+    let invalid: &[&[u8]] = &[
+        // Lone surrogates, high and low.
+        &[0xED, 0xA0, 0xBD],
+        &[b'a', 0xED, 0xB8, 0x88],
+        // High surrogate followed by something other than a low surrogate.
+        &[0xED, 0xA0, 0xBD, b'a', b'b', b'c'],
+        // Truncated sequences.
+        &[0xC0],
+        &[0xE6, 0x97],
+        // Not a leading byte.
+        &[0x80],
+        &[0xFF],
+    ];
+
+    for data in invalid {
+        assert!(cesu8::from_java_cesu8(data).is_err(), "{data:x?}");
+        assert_eq!(decode(data), None, "{data:x?}");
+    }
+}
+
+#[test]
+fn encode_matches_java_cesu8_on_sampled_strings() {
+    // Mostly ASCII, like real NBT, with the odd character from each width and
+    // the null thrown in.
+    let chars: Vec<char> = "aZ_:0 \0éö日\u{FFFF}😈\u{10FFFF}".chars().collect();
+    let mut state = 0x2545_F491_u32;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+
+    for _ in 0..2000 {
+        let len = next() % 40;
+        let s: String = (0..len)
+            .map(|_| match next() % 64 {
+                i if (i as usize) < chars.len() => chars[i as usize],
+                i => (b'a' + (i % 26) as u8) as char,
+            })
+            .collect();
+
+        assert_eq!(encode(&s), cesu8::to_java_cesu8(&s), "{s:?}");
+    }
+}
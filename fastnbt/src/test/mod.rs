@@ -10,14 +10,22 @@ mod de;
 #[allow(clippy::float_cmp)]
 mod value;
 
+mod alloc;
 mod builder;
+mod conformance;
 mod de_arrays;
+mod extract;
 mod fuzz;
+mod java_string;
+mod macros;
 mod minecraft_chunk;
+mod nested_lists;
+mod network;
 mod resources;
 mod ser;
+mod serde_helpers;
 mod stream;
-mod macros;
+mod writer;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Single<T: Serialize> {
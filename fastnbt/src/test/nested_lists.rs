@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Result, from_bytes, from_value, test::builder::Builder, to_bytes, to_value, IntArray,
+    LongArray, Tag, Value,
+};
+
+use super::Single;
+
+/// A pre-1.18 `Lights` list: a list of 16 lists of shorts, one for each
+/// section, most of them empty lists of `Tag::End`.
+fn lights() -> Vec<u8> {
+    let mut builder = Builder::new()
+        .start_compound("")
+        .start_list("Lights", Tag::List, 16);
+    for i in 0..16 {
+        builder = match i {
+            3 => builder
+                .start_anon_list(Tag::Short, 2)
+                .short_payload(1)
+                .short_payload(2),
+            7 => builder.start_anon_list(Tag::Short, 1).short_payload(3),
+            // Empty lists usually keep the tag of their elements.
+            9 => builder.start_anon_list(Tag::Short, 0),
+            _ => builder.start_anon_list(Tag::End, 0),
+        };
+    }
+    builder.end_compound().build()
+}
+
+fn expected_lights() -> Vec<Vec<i16>> {
+    let mut lights = vec![vec![]; 16];
+    lights[3] = vec![1, 2];
+    lights[7] = vec![3];
+    lights
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Lights {
+    #[serde(rename = "Lights")]
+    lights: Vec<Vec<i16>>,
+}
+
+#[test]
+fn lights_into_vec_of_vecs() -> Result<()> {
+    let v: Lights = from_bytes(&lights())?;
+    assert_eq!(v.lights, expected_lights());
+    Ok(())
+}
+
+#[test]
+fn lights_into_fixed_size_array() -> Result<()> {
+    #[derive(Deserialize)]
+    struct V {
+        #[serde(rename = "Lights")]
+        lights: [Vec<i16>; 16],
+    }
+
+    let v: V = from_bytes(&lights())?;
+    assert_eq!(v.lights.to_vec(), expected_lights());
+    Ok(())
+}
+
+#[test]
+fn lights_through_value() -> Result<()> {
+    let value: Value = from_bytes(&lights())?;
+    assert_eq!(value.pointer("/Lights/0"), Some(&Value::List(vec![])));
+    assert_eq!(
+        value.pointer("/Lights/9"),
+        Some(&Value::EmptyList(Tag::Short))
+    );
+    assert_eq!(
+        value.pointer("/Lights/3"),
+        Some(&Value::List(vec![Value::Short(1), Value::Short(2)]))
+    );
+
+    let v: Lights = from_value(&value)?;
+    assert_eq!(v.lights, expected_lights());
+
+    assert_eq!(to_bytes(&value)?, lights());
+    Ok(())
+}
+
+#[test]
+fn lights_round_trip() -> Result<()> {
+    let v = Lights {
+        lights: expected_lights(),
+    };
+    let bytes = to_bytes(&v)?;
+    assert_eq!(from_bytes::<Lights>(&bytes)?, v);
+
+    let value = to_value(&v)?;
+    assert_eq!(from_value::<Lights>(&value)?, v);
+    assert_eq!(from_bytes::<Value>(&bytes)?, value);
+    Ok(())
+}
+
+#[test]
+fn deeply_nested_lists() -> Result<()> {
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::List, 3)
+        .start_anon_list(Tag::List, 2)
+        .start_anon_list(Tag::Int, 2)
+        .int_payload(1)
+        .int_payload(2)
+        .start_anon_list(Tag::End, 0)
+        .start_anon_list(Tag::End, 0)
+        .start_anon_list(Tag::List, 1)
+        .start_anon_list(Tag::Int, 1)
+        .int_payload(3)
+        .end_compound()
+        .build();
+
+    let expected = vec![vec![vec![1, 2], vec![]], vec![], vec![vec![3]]];
+
+    let v: Single<Vec<Vec<Vec<i32>>>> = from_bytes(&payload)?;
+    assert_eq!(v.val, expected);
+
+    let value: Value = from_bytes(&payload)?;
+    assert_eq!(value, nbt!({ "val": [[[1, 2], []], [], [[3]]] }));
+    assert_eq!(
+        from_value::<Single<Vec<Vec<Vec<i32>>>>>(&value)?.val,
+        expected
+    );
+
+    assert_eq!(to_bytes(&Single { val: expected })?, payload);
+    Ok(())
+}
+
+#[test]
+fn tuple_of_lists() -> Result<()> {
+    // Each inner list can have its own element tag.
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::List, 3)
+        .start_anon_list(Tag::Byte, 2)
+        .byte_payload(1)
+        .byte_payload(2)
+        .start_anon_list(Tag::String, 1)
+        .string_payload("a")
+        .start_anon_list(Tag::End, 0)
+        .end_compound()
+        .build();
+
+    type T = (Vec<i8>, Vec<String>, Vec<f64>);
+    let expected: T = (vec![1, 2], vec!["a".to_owned()], vec![]);
+
+    let v: Single<T> = from_bytes(&payload)?;
+    assert_eq!(v.val, expected);
+
+    let value: Value = from_bytes(&payload)?;
+    assert_eq!(from_value::<Single<T>>(&value)?.val, expected);
+    assert_eq!(to_bytes(&Single { val: expected })?, payload);
+    Ok(())
+}
+
+#[test]
+fn arrays_of_lists() -> Result<()> {
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::List, 2)
+        .start_anon_list(Tag::List, 2)
+        .start_anon_list(Tag::Short, 1)
+        .short_payload(1)
+        .start_anon_list(Tag::End, 0)
+        .start_anon_list(Tag::List, 2)
+        .start_anon_list(Tag::End, 0)
+        .start_anon_list(Tag::Short, 2)
+        .short_payload(2)
+        .short_payload(3)
+        .end_compound()
+        .build();
+
+    let expected = [[vec![1], vec![]], [vec![], vec![2, 3]]];
+
+    let v: Single<[[Vec<i16>; 2]; 2]> = from_bytes(&payload)?;
+    assert_eq!(v.val, expected);
+
+    let value: Value = from_bytes(&payload)?;
+    assert_eq!(
+        from_value::<Single<[[Vec<i16>; 2]; 2]>>(&value)?.val,
+        expected
+    );
+    assert_eq!(to_bytes(&Single { val: expected })?, payload);
+
+    // The wrong number of lists is an error rather than a panic.
+    assert!(from_bytes::<Single<[Vec<i16>; 3]>>(&payload).is_err());
+    Ok(())
+}
+
+#[test]
+fn lists_of_lists_of_compounds() -> Result<()> {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Inner {
+        a: i32,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::List, 2)
+        .start_anon_list(Tag::Compound, 1)
+        .int("a", 1)
+        .end_anon_compound()
+        .start_anon_list(Tag::End, 0)
+        .end_compound()
+        .build();
+
+    let expected = vec![vec![Inner { a: 1 }], vec![]];
+
+    let v: Single<Vec<Vec<Inner>>> = from_bytes(&payload)?;
+    assert_eq!(v.val, expected);
+
+    let v: Option<Single<Vec<Vec<Inner>>>> = from_bytes(&payload)?;
+    assert_eq!(v.unwrap().val, expected);
+
+    assert_eq!(to_bytes(&Single { val: expected })?, payload);
+    Ok(())
+}
+
+#[test]
+fn lists_of_arrays_in_value() -> Result<()> {
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("val", Tag::List, 2)
+        .start_anon_list(Tag::IntArray, 2)
+        .int_payload(2)
+        .int_array_payload(&[1, 2])
+        .int_payload(0)
+        .start_anon_list(Tag::LongArray, 1)
+        .int_payload(1)
+        .long_array_payload(&[3])
+        .end_compound()
+        .build();
+
+    let value: Value = from_bytes(&payload)?;
+    assert_eq!(value, nbt!({ "val": [[[I; 1, 2], [I;]], [[L; 3]]] }));
+    assert_eq!(to_bytes(&value)?, payload);
+
+    #[derive(Deserialize)]
+    struct V {
+        val: (Vec<IntArray>, Vec<LongArray>),
+    }
+
+    let v: V = from_value(&value)?;
+    assert_eq!(v.val.0, [IntArray::new(vec![1, 2]), IntArray::new(vec![])]);
+    assert_eq!(v.val.1, [LongArray::new(vec![3])]);
+    Ok(())
+}
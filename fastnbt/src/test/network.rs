@@ -0,0 +1,90 @@
+//! NBT as sent over the network, where the root compound has an empty name
+//! before 1.20.2 and no name at all since.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    from_bytes, from_bytes_unnamed, from_bytes_with_opts, to_bytes, to_bytes_unnamed, DeOpts, Value,
+};
+
+/// A chat component, `{"text": "Hello", "color": "red"}`, as a pre-1.20.2
+/// server sends it.
+const NAMED_PAYLOAD: &[u8] = &[
+    0x0a, 0x00, 0x00, // root compound with empty name
+    0x08, 0x00, 0x04, b't', b'e', b'x', b't', 0x00, 0x05, b'H', b'e', b'l', b'l', b'o', //
+    0x08, 0x00, 0x05, b'c', b'o', b'l', b'o', b'r', 0x00, 0x03, b'r', b'e', b'd', //
+    0x00,
+];
+
+/// The same component as a 1.20.2+ server sends it.
+const UNNAMED_PAYLOAD: &[u8] = &[
+    0x0a, // root compound, no name
+    0x08, 0x00, 0x04, b't', b'e', b'x', b't', 0x00, 0x05, b'H', b'e', b'l', b'l', b'o', //
+    0x08, 0x00, 0x05, b'c', b'o', b'l', b'o', b'r', 0x00, 0x03, b'r', b'e', b'd', //
+    0x00,
+];
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Component {
+    text: String,
+    color: String,
+}
+
+fn component() -> Component {
+    Component {
+        text: "Hello".to_owned(),
+        color: "red".to_owned(),
+    }
+}
+
+#[test]
+fn named_root_payload() {
+    assert_eq!(from_bytes::<Component>(NAMED_PAYLOAD).unwrap(), component());
+    assert_eq!(to_bytes(&component()).unwrap(), NAMED_PAYLOAD);
+}
+
+#[test]
+fn unnamed_root_payload() {
+    assert_eq!(
+        from_bytes_unnamed::<Component>(UNNAMED_PAYLOAD).unwrap(),
+        component()
+    );
+    assert_eq!(to_bytes_unnamed(&component()).unwrap(), UNNAMED_PAYLOAD);
+}
+
+#[test]
+fn framings_are_not_interchangeable() {
+    assert!(from_bytes::<Component>(UNNAMED_PAYLOAD).is_err());
+    assert!(from_bytes_unnamed::<Component>(NAMED_PAYLOAD).is_err());
+}
+
+#[test]
+fn unnamed_root_option_matches_entry_point() {
+    let opts = DeOpts::new().unnamed_root(true).strict_trailing(true);
+    let value: Value = from_bytes_with_opts(UNNAMED_PAYLOAD, opts).unwrap();
+    assert_eq!(value, nbt!({"text": "Hello", "color": "red"}));
+}
+
+#[test]
+fn unnamed_root_round_trips_nested_values() {
+    let value = nbt!({
+        "dimension": {"name": "minecraft:overworld", "height": 384},
+        "entries": [{"id": 1}, {"id": 2}],
+        "data": [L; 1, 2, 3],
+    });
+
+    let bytes = to_bytes_unnamed(&value).unwrap();
+    // Only the root's empty name is missing.
+    assert_eq!(bytes.len(), to_bytes(&value).unwrap().len() - 2);
+    assert_eq!(from_bytes_unnamed::<Value>(&bytes).unwrap(), value);
+}
+
+#[test]
+fn unnamed_root_stream_of_packets() {
+    let mut input = UNNAMED_PAYLOAD.to_vec();
+    input.extend_from_slice(UNNAMED_PAYLOAD);
+
+    let de = crate::de::Deserializer::from_bytes(&input, DeOpts::new().unnamed_root(true));
+    let components: Vec<Component> = de.into_iter().collect::<crate::error::Result<_>>().unwrap();
+    assert_eq!(components, [component(), component()]);
+}
@@ -3,7 +3,7 @@ use std::{collections::HashMap, iter::FromIterator};
 use crate::{
     borrow, from_bytes,
     test::{resources::CHUNK_RAW_WITH_ENTITIES, Single, Wrap},
-    to_bytes, ByteArray, IntArray, LongArray, Tag, Value,
+    to_bytes, to_bytes_with_opts, ByteArray, IntArray, KeyOrder, LongArray, SerOpts, Tag, Value,
 };
 use serde::Serialize;
 use serde_bytes::Bytes;
@@ -709,6 +709,31 @@ fn bytes_as_fields() {
     assert_eq!(expected, to_bytes(&map).unwrap());
 }
 
+#[test]
+fn invalid_bytes_as_fields() {
+    let mut map = HashMap::new();
+    // A lone surrogate.
+    map.insert(Bytes::new(&[0xED, 0xA0, 0xBD]), "world");
+
+    assert!(to_bytes(&map).is_err());
+}
+
+#[test]
+fn field_names_round_trip() {
+    // Names needing conversion, following and followed by nested compounds
+    // and lists whose own field names are written in between.
+    let v = crate::nbt!({
+        "a\0b": 1,
+        "😈": { "inner": 2, "日本語": [{ "x": 3 }, { "y": 4 }] },
+        "plain": "😈",
+        "": { "": 5 },
+        "last": 6,
+    });
+
+    let bytes = to_bytes(&v).unwrap();
+    assert_eq!(from_bytes::<Value>(&bytes).unwrap(), v);
+}
+
 #[test]
 fn basic_newtype_variant_enum() {
     #[derive(Serialize, Debug, PartialEq)]
@@ -737,3 +762,110 @@ fn basic_newtype_variant_enum() {
 
     assert_eq!(actual, expected);
 }
+
+fn sorted() -> SerOpts {
+    SerOpts::new().key_order(KeyOrder::Sorted)
+}
+
+#[test]
+fn sorted_keys_for_hash_map() {
+    let map = HashMap::<_, _>::from_iter([("b", 2), ("c", 3), ("a", 1), ("B", 0)]);
+
+    let expected = Builder::new()
+        .start_compound("")
+        .int("B", 0)
+        .int("a", 1)
+        .int("b", 2)
+        .int("c", 3)
+        .end_compound()
+        .build();
+
+    assert_eq!(expected, to_bytes_with_opts(&map, sorted()).unwrap());
+}
+
+#[test]
+fn sorted_keys_at_every_level() {
+    #[derive(Serialize)]
+    struct Unsorted {
+        zeta: i32,
+        alpha: Vec<Value>,
+        mid: Value,
+    }
+
+    // The same, declared in sorted order.
+    #[derive(Serialize)]
+    struct Inner {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Serialize)]
+    struct Sorted {
+        alpha: Vec<Inner>,
+        mid: Inner,
+        zeta: i32,
+    }
+
+    let unsorted = Unsorted {
+        zeta: 1,
+        alpha: vec![
+            crate::nbt!({ "y": 2, "x": 3 }),
+            crate::nbt!({ "x": 4, "y": 5 }),
+        ],
+        mid: crate::nbt!({ "y": 6, "x": 7 }),
+    };
+    let sorted_struct = Sorted {
+        alpha: vec![Inner { x: 3, y: 2 }, Inner { x: 4, y: 5 }],
+        mid: Inner { x: 7, y: 6 },
+        zeta: 1,
+    };
+
+    assert_eq!(
+        to_bytes(&sorted_struct).unwrap(),
+        to_bytes_with_opts(&unsorted, sorted()).unwrap()
+    );
+}
+
+#[test]
+fn sorted_unnamed_root() {
+    let opts = sorted().unnamed_root(true);
+    let bytes = to_bytes_with_opts(&crate::nbt!({ "b": 1i8, "a": 2i8 }), opts).unwrap();
+    assert_eq!(bytes, [10, 1, 0, 1, b'a', 2, 1, 0, 1, b'b', 1, 0]);
+}
+
+#[test]
+fn sorted_output_is_pinned() {
+    // Sorted output is documented as stable across runs and versions. If this
+    // digest changes, so has the output, which is a breaking change.
+    let doc = crate::nbt!({
+        "DataVersion": 3465,
+        "name": "example:structure",
+        "size": [I; 2, 3, 1],
+        "palette": [
+            { "Name": "minecraft:stone" },
+            { "Name": "minecraft:oak_stairs", "Properties": { "waterlogged": "false", "facing": "north", "half": "bottom" } },
+        ],
+        "blocks": [
+            { "state": 0, "pos": [0, 0, 0] },
+            { "state": 1, "pos": [1, 2, 0], "nbt": { "Items": [], "CustomName": "😈 a\0b" } },
+        ],
+        "entities": [],
+        "scale": 0.1f32,
+        "weight": -2.5,
+        "seed": -1234567890123i64,
+        "flags": [B; 1, 0, -1],
+        "heights": [L; 1, -1],
+        "level": 7i16,
+        "on": 1i8,
+    });
+
+    let bytes = to_bytes_with_opts(&doc, sorted()).unwrap();
+
+    // FNV-1a, so the test needs no hashing crates.
+    let digest = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    assert_eq!(from_bytes::<Value>(&bytes).unwrap(), doc);
+    assert_eq!(bytes.len(), 460);
+    assert_eq!(digest, 0xf886_a50c_3c23_2b90, "{digest:#018x}");
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    from_bytes, from_value,
+    serde_helpers::{bool_from_byte, string_or_seq, uuid_from_int_array},
+    to_bytes, to_value, IntArray, Value,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Entity {
+    #[serde(rename = "OnGround", with = "bool_from_byte")]
+    on_ground: bool,
+
+    #[serde(rename = "UUID", with = "uuid_from_int_array")]
+    uuid: u128,
+
+    #[serde(rename = "Lore", with = "string_or_seq")]
+    lore: Vec<String>,
+}
+
+fn entity() -> Entity {
+    Entity {
+        on_ground: true,
+        uuid: 0x0000_0001_ffff_fffe_0000_0003_8000_0000,
+        lore: vec!["first".into(), "second".into()],
+    }
+}
+
+fn entity_nbt() -> Value {
+    nbt!({
+        "OnGround": 1i8,
+        "UUID": [I; 1, -2, 3, i32::MIN],
+        "Lore": ["first", "second"],
+    })
+}
+
+#[test]
+fn helpers_round_trip_through_bytes() {
+    let bytes = to_bytes(&entity()).unwrap();
+    assert_eq!(from_bytes::<Value>(&bytes).unwrap(), entity_nbt());
+    assert_eq!(from_bytes::<Entity>(&bytes).unwrap(), entity());
+}
+
+#[test]
+fn helpers_round_trip_through_values() {
+    assert_eq!(to_value(entity()).unwrap(), entity_nbt());
+    assert_eq!(from_value::<Entity>(&entity_nbt()).unwrap(), entity());
+}
+
+#[test]
+fn helpers_work_when_flattened() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Outer {
+        #[serde(rename = "id")]
+        _id: String,
+
+        #[serde(flatten)]
+        entity: Entity,
+    }
+
+    let mut nbt = entity_nbt();
+    if let Value::Compound(map) = &mut nbt {
+        map.insert("id".into(), Value::String("minecraft:cow".into()));
+    }
+
+    let outer: Outer = from_bytes(&to_bytes(&nbt).unwrap()).unwrap();
+    assert_eq!(outer.entity, entity());
+}
+
+#[test]
+fn bool_from_any_integer() {
+    #[derive(Deserialize)]
+    struct V {
+        #[serde(with = "bool_from_byte")]
+        val: bool,
+    }
+
+    for (nbt, expected) in [
+        (nbt!({"val": 0i8}), false),
+        (nbt!({"val": 2i16}), true),
+        (nbt!({"val": -1}), true),
+        (nbt!({"val": 0i64}), false),
+    ] {
+        let v: V = from_bytes(&to_bytes(&nbt).unwrap()).unwrap();
+        assert_eq!(v.val, expected);
+    }
+
+    let nbt = to_bytes(&nbt!({"val": "true"})).unwrap();
+    assert!(from_bytes::<V>(&nbt).is_err());
+}
+
+#[test]
+fn uuid_of_wrong_length_errors() {
+    #[derive(Debug, Deserialize)]
+    struct V {
+        #[serde(with = "uuid_from_int_array")]
+        _val: u128,
+    }
+
+    let nbt = to_bytes(&nbt!({"_val": IntArray::new(vec![1, 2, 3])})).unwrap();
+    let err = from_bytes::<V>(&nbt).unwrap_err();
+    assert!(err.to_string().contains("a UUID of 4 ints"), "{err}");
+
+    let nbt = to_bytes(&nbt!({"_val": [1, 2, 3, 4]})).unwrap();
+    assert!(from_bytes::<V>(&nbt).is_err());
+}
+
+#[test]
+fn single_string_is_a_seq_of_one() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct V {
+        #[serde(with = "string_or_seq")]
+        val: Vec<String>,
+    }
+
+    let single = nbt!({"val": "only"});
+    let v: V = from_value(&single).unwrap();
+    assert_eq!(v.val, ["only"]);
+    assert_eq!(to_value(&v).unwrap(), single);
+
+    let empty = nbt!({"val": []});
+    let v: V = from_bytes(&to_bytes(&empty).unwrap()).unwrap();
+    assert!(v.val.is_empty());
+
+    let nbt = to_bytes(&nbt!({"val": [1, 2]})).unwrap();
+    assert!(from_bytes::<V>(&nbt).is_err());
+}
@@ -437,3 +437,94 @@ fn list_of_compounds() -> Result<()> {
     assert!(matches!(parser.next(), Err(e) if e.is_eof()));
     Ok(())
 }
+
+#[test]
+fn absurd_array_lengths_do_not_preallocate() {
+    for tag in [Tag::ByteArray, Tag::IntArray, Tag::LongArray] {
+        let payload = Builder::new()
+            .tag(tag)
+            .name("arr")
+            .int_payload(i32::MAX)
+            .byte_payload(1)
+            .build();
+
+        let mut parser = Parser::new(payload.as_slice());
+        let err = parser.next().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnexpectedEof));
+    }
+}
+
+#[test]
+fn negative_array_length_errors() {
+    let payload = Builder::new()
+        .tag(Tag::IntArray)
+        .name("arr")
+        .int_payload(-1)
+        .build();
+
+    let mut parser = Parser::new(payload.as_slice());
+    assert!(parser.next().is_err());
+}
+
+#[test]
+fn negative_list_length_errors() {
+    let payload = Builder::new()
+        .start_list("list", Tag::Int, -1)
+        .int_payload(1)
+        .build();
+
+    let mut parser = Parser::new(payload.as_slice());
+    let err = parser.next().unwrap_err();
+    assert_eq!(err.to_string(), "negative list/array size: -1");
+}
+
+#[test]
+fn io_errors_are_kept() {
+    use std::error::Error as _;
+
+    struct TimesOut;
+
+    impl std::io::Read for TimesOut {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::TimedOut.into())
+        }
+    }
+
+    let err = Parser::new(TimesOut).next().unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        ErrorKind::Io(std::io::ErrorKind::TimedOut)
+    ));
+    assert_eq!(err.io_error().unwrap().kind(), std::io::ErrorKind::TimedOut);
+
+    let source = err.source().unwrap().downcast_ref::<std::io::Error>();
+    assert_eq!(source.unwrap().kind(), std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn string_longer_than_input_is_eof() {
+    let payload = Builder::new()
+        .start_compound("")
+        .tag(Tag::String)
+        .name("val")
+        .raw_len(0xFFFF)
+        .raw_bytes(b"abc")
+        .build();
+    let mut parser = Parser::new(payload.as_slice());
+
+    parser.next().unwrap();
+    let err = parser.next().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::UnexpectedEof));
+
+    // Key names go through the same path.
+    let payload = Builder::new()
+        .start_compound("")
+        .tag(Tag::Int)
+        .raw_len(0xFFFF)
+        .build();
+    let mut parser = Parser::new(payload.as_slice());
+
+    parser.next().unwrap();
+    let err = parser.next().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::UnexpectedEof));
+}
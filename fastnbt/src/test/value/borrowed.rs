@@ -0,0 +1,115 @@
+use std::borrow::Cow;
+
+use crate::{borrow::BorrowedValue, from_bytes, from_value, to_bytes, Tag, Value};
+
+use super::super::builder::Builder;
+
+#[test]
+fn to_owned_matches_value() {
+    let v = crate::nbt!({
+        "DataVersion": 3465,
+        "Status": "minecraft:full",
+        "InhabitedTime": 12i64,
+        "Heightmaps": {
+            "WORLD_SURFACE": [L; 1, 2, 3],
+        },
+        "CarvingMask": [B; 1, -1, 0],
+        "Pos": [I; 4, -5, 6],
+        "Motion": [0.5, -0.25],
+        "sections": [
+            { "Y": -4i8, "block_states": { "palette": [{ "Name": "minecraft:stone" }] } },
+            { "Y": -3i8, "entities": [] },
+        ],
+        "Scale": 2.5f32,
+        "Id": 7i16,
+        "Empty": {},
+    });
+    let bytes = to_bytes(&v).unwrap();
+
+    let borrowed: BorrowedValue = from_bytes(&bytes).unwrap();
+    assert_eq!(borrowed.to_owned(), v);
+    assert_eq!(borrowed.to_owned(), from_bytes::<Value>(&bytes).unwrap());
+}
+
+#[test]
+fn strings_and_keys_borrow_from_input() {
+    let bytes = to_bytes(&crate::nbt!({ "Name": "minecraft:stone" })).unwrap();
+
+    let v: BorrowedValue = from_bytes(&bytes).unwrap();
+    let BorrowedValue::Compound(v) = v else {
+        panic!("expected compound");
+    };
+    let (key, value) = v.iter().next().unwrap();
+    assert!(matches!(key, Cow::Borrowed("Name")));
+    assert!(matches!(
+        value,
+        BorrowedValue::String(Cow::Borrowed("minecraft:stone"))
+    ));
+}
+
+#[test]
+fn strings_needing_conversion_are_owned() {
+    let bytes = to_bytes(&crate::nbt!({ "😈": "a\0b", "Name": "😈" })).unwrap();
+
+    let v: BorrowedValue = from_bytes(&bytes).unwrap();
+    let BorrowedValue::Compound(v) = v else {
+        panic!("expected compound");
+    };
+    for (key, value) in &v {
+        let BorrowedValue::String(value) = value else {
+            panic!("expected string");
+        };
+        assert!(matches!(value, Cow::Owned(_)), "{key}: {value}");
+    }
+    let keys: Vec<_> = v.keys().filter(|k| matches!(k, Cow::Owned(_))).collect();
+    assert_eq!(keys, ["😈"]);
+}
+
+#[test]
+fn arrays_are_views_of_input() {
+    let input = Builder::new()
+        .start_compound("")
+        .byte_array("a", &[1, 2, 3])
+        .int_array("b", &[4, 5, 6])
+        .long_array("c", &[7, 8, 9])
+        .end_compound()
+        .build();
+
+    let v: BorrowedValue = from_bytes(&input).unwrap();
+    let BorrowedValue::Compound(v) = v else {
+        panic!("expected compound");
+    };
+    assert!(matches!(&v["a"], BorrowedValue::ByteArray(a) if a.iter().eq([1, 2, 3])));
+    assert!(matches!(&v["b"], BorrowedValue::IntArray(a) if a.iter().eq([4, 5, 6])));
+    assert!(matches!(&v["c"], BorrowedValue::LongArray(a) if a.iter().eq([7, 8, 9])));
+}
+
+#[test]
+fn empty_lists_keep_element_tag() {
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("compounds", Tag::Compound, 0)
+        .start_list("end", Tag::End, 0)
+        .end_compound()
+        .build();
+
+    let v: BorrowedValue = from_bytes(&input).unwrap();
+    let BorrowedValue::Compound(v) = v else {
+        panic!("expected compound");
+    };
+    assert!(matches!(
+        v["compounds"],
+        BorrowedValue::EmptyList(Tag::Compound)
+    ));
+    assert!(matches!(&v["end"], BorrowedValue::List(l) if l.is_empty()));
+}
+
+#[test]
+fn from_value_without_arrays() {
+    let v = crate::nbt!({ "Name": "minecraft:stone", "Count": 1i8 });
+
+    let borrowed: BorrowedValue = from_value(&v).unwrap();
+    assert_eq!(borrowed.to_owned(), v);
+
+    assert!(from_value::<BorrowedValue>(&crate::nbt!({ "a": [I; 1] })).is_err());
+}
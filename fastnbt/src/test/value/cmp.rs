@@ -0,0 +1,143 @@
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, BTreeSet, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use crate::{ByteArray, Map, Tag, Value};
+
+fn hash(value: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn floats_compare_by_bits() {
+    assert_eq!(Value::Float(f32::NAN), Value::Float(f32::NAN));
+    assert_eq!(Value::Double(f64::NAN), Value::Double(f64::NAN));
+    assert_eq!(
+        hash(&Value::Double(f64::NAN)),
+        hash(&Value::Double(f64::NAN))
+    );
+
+    let other_nan = f64::from_bits(f64::NAN.to_bits() + 1);
+    assert!(other_nan.is_nan());
+    assert_ne!(Value::Double(f64::NAN), Value::Double(other_nan));
+
+    assert_ne!(Value::Double(0.0), Value::Double(-0.0));
+    assert_eq!(
+        Value::Double(-0.0).total_cmp(&Value::Double(0.0)),
+        Ordering::Less
+    );
+    assert!(Value::Float(-1.5) < Value::Float(1.0));
+
+    // A document with a NaN in it equals itself.
+    let doc = nbt!({ "a": [f32::NAN, 1.0_f32] });
+    assert_eq!(doc, doc.clone());
+}
+
+#[test]
+fn different_types_are_ordered_by_tag() {
+    let mut values = vec![
+        nbt!({}),
+        nbt!([1]),
+        Value::String("a".to_owned()),
+        Value::ByteArray(ByteArray::new(vec![])),
+        Value::Double(-1.0),
+        Value::Float(-1.0),
+        Value::Long(1),
+        Value::Int(2),
+        Value::Short(3),
+        Value::Byte(4),
+    ];
+    values.sort();
+
+    let tags: Vec<_> = values
+        .iter()
+        .map(|v| match v {
+            Value::Byte(_) => Tag::Byte,
+            Value::Short(_) => Tag::Short,
+            Value::Int(_) => Tag::Int,
+            Value::Long(_) => Tag::Long,
+            Value::Float(_) => Tag::Float,
+            Value::Double(_) => Tag::Double,
+            Value::ByteArray(_) => Tag::ByteArray,
+            Value::String(_) => Tag::String,
+            Value::List(_) | Value::EmptyList(_) => Tag::List,
+            Value::Compound(_) => Tag::Compound,
+            _ => Tag::End,
+        })
+        .collect();
+    assert_eq!(
+        tags,
+        [
+            Tag::Byte,
+            Tag::Short,
+            Tag::Int,
+            Tag::Long,
+            Tag::Float,
+            Tag::Double,
+            Tag::ByteArray,
+            Tag::String,
+            Tag::List,
+            Tag::Compound,
+        ]
+    );
+}
+
+#[test]
+fn lists_compare_lexicographically() {
+    assert!(nbt!([1, 2]) < nbt!([1, 3]));
+    assert!(nbt!([1, 2]) < nbt!([1, 2, 0]));
+    assert!(nbt!([5]) > nbt!([1, 2, 0]));
+
+    // Empty lists keep their element tag, and only equal the same kind.
+    let empty = Value::List(vec![]);
+    let ints = Value::EmptyList(Tag::Int);
+    let strings = Value::EmptyList(Tag::String);
+    assert!(empty < ints && ints < strings);
+    assert!(strings < nbt!([1]));
+    assert_ne!(empty, ints);
+    assert_ne!(hash(&ints), hash(&strings));
+}
+
+#[test]
+fn compounds_compare_by_sorted_keys() {
+    let mut forwards = Map::default();
+    let mut backwards = Map::default();
+    for i in 0..20 {
+        forwards.insert(format!("key{i}"), Value::Int(i));
+        backwards.insert(format!("key{}", 19 - i), Value::Int(19 - i));
+    }
+    let forwards = Value::Compound(forwards);
+    let backwards = Value::Compound(backwards);
+    assert_eq!(forwards, backwards);
+    assert_eq!(forwards.cmp(&backwards), Ordering::Equal);
+    assert_eq!(hash(&forwards), hash(&backwards));
+
+    // The first differing key decides, whatever the values.
+    assert!(nbt!({ "a": 5, "b": 1 }) < nbt!({ "a": 5, "c": 0 }));
+    assert!(nbt!({ "a": 1, "z": 9 }) < nbt!({ "a": 2 }));
+    assert!(nbt!({ "a": 1 }) < nbt!({ "a": 1, "b": 0 }));
+}
+
+#[test]
+fn values_can_be_deduplicated() {
+    let values = [
+        nbt!({ "Name": "minecraft:stone" }),
+        nbt!({ "Name": "minecraft:dirt" }),
+        nbt!({ "Name": "minecraft:stone" }),
+        nbt!({ "Name": "minecraft:stone", "Properties": { "a": f64::NAN } }),
+        nbt!({ "Name": "minecraft:stone", "Properties": { "a": f64::NAN } }),
+    ];
+
+    let hashed: HashSet<_> = values.iter().collect();
+    let sorted: BTreeSet<_> = values.iter().collect();
+    assert_eq!(hashed.len(), 3);
+    assert_eq!(sorted.len(), 3);
+    assert_eq!(
+        sorted.into_iter().next(),
+        Some(&nbt!({ "Name": "minecraft:dirt" }))
+    );
+}
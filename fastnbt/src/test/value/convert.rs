@@ -0,0 +1,89 @@
+use std::convert::TryFrom;
+
+use crate::{value::TryFromValueError, ByteArray, IntArray, Map, Tag, Value};
+
+#[test]
+fn from_rust_values() {
+    assert_eq!(Value::from(1_i8), Value::Byte(1));
+    assert_eq!(Value::from(1_i16), Value::Short(1));
+    assert_eq!(Value::from(1_i32), Value::Int(1));
+    assert_eq!(Value::from(1_i64), Value::Long(1));
+    assert_eq!(Value::from(1.5_f32), Value::Float(1.5));
+    assert_eq!(Value::from(1.5_f64), Value::Double(1.5));
+    assert_eq!(Value::from("a"), Value::String("a".to_owned()));
+    assert_eq!(Value::from("a".to_owned()), Value::String("a".to_owned()));
+    assert_eq!(
+        Value::from(IntArray::new(vec![1, 2])),
+        Value::IntArray(IntArray::new(vec![1, 2]))
+    );
+}
+
+#[test]
+fn from_vec_and_map() {
+    let list = Value::from(vec![Value::from(1), Value::from(2)]);
+    assert_eq!(list, nbt!([1, 2]));
+
+    let mut map = Map::default();
+    map.insert("a".to_owned(), Value::from("b"));
+    assert_eq!(Value::from(map), nbt!({ "a": "b" }));
+}
+
+#[test]
+fn try_from_integers_that_fit() {
+    assert_eq!(i8::try_from(Value::Int(100)), Ok(100));
+    assert_eq!(i64::try_from(Value::Byte(-1)), Ok(-1));
+    assert_eq!(i32::try_from(Value::Long(1 << 20)), Ok(1 << 20));
+    assert_eq!(i16::try_from(Value::Short(-5)), Ok(-5));
+}
+
+#[test]
+fn try_from_integers_that_do_not_fit() {
+    let err = i8::try_from(Value::Int(300)).unwrap_err();
+    assert_eq!(err.value(), &Value::Int(300));
+    assert_eq!(err.to_string(), "cannot convert int to i8");
+
+    assert!(i32::try_from(Value::Long(i64::MAX)).is_err());
+    assert!(i32::try_from(Value::Double(1.0)).is_err());
+    assert!(i32::try_from(Value::String("1".to_owned())).is_err());
+}
+
+#[test]
+fn try_from_floats() {
+    assert_eq!(f32::try_from(Value::Float(1.5)), Ok(1.5));
+    assert_eq!(f64::try_from(Value::Float(1.5)), Ok(1.5));
+    assert_eq!(f64::try_from(Value::Double(2.5)), Ok(2.5));
+    assert!(f32::try_from(Value::Double(2.5)).is_err());
+    assert!(f64::try_from(Value::Int(1)).is_err());
+}
+
+#[test]
+fn try_from_bool() {
+    assert_eq!(bool::try_from(Value::Byte(0)), Ok(false));
+    assert_eq!(bool::try_from(Value::Byte(1)), Ok(true));
+    assert!(bool::try_from(Value::Byte(2)).is_err());
+    assert!(bool::try_from(Value::Int(1)).is_err());
+}
+
+#[test]
+fn try_from_owned_values() {
+    assert_eq!(String::try_from(nbt!("a")), Ok("a".to_owned()));
+    assert_eq!(
+        ByteArray::try_from(nbt!([B; 1, 2])),
+        Ok(ByteArray::new(vec![1, 2]))
+    );
+    assert_eq!(
+        Vec::<Value>::try_from(nbt!([1, 2])),
+        Ok(vec![Value::Int(1), Value::Int(2)])
+    );
+    assert_eq!(
+        Vec::<Value>::try_from(Value::EmptyList(Tag::Int)),
+        Ok(vec![])
+    );
+
+    let map = Map::<String, Value>::try_from(nbt!({ "a": 1 })).unwrap();
+    assert_eq!(map["a"], Value::Int(1));
+
+    let err: TryFromValueError = String::try_from(nbt!([1])).unwrap_err();
+    assert_eq!(err.to_string(), "cannot convert list to String");
+    assert_eq!(err.into_value(), nbt!([1]));
+}
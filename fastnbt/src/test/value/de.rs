@@ -1,8 +1,8 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
 use serde::Deserialize;
 
-use crate::{value::from_value, ByteArray, IntArray, LongArray};
+use crate::{from_bytes, to_bytes, value::from_value, ByteArray, IntArray, LongArray, Value};
 
 #[test]
 fn simple_types() {
@@ -144,3 +144,92 @@ fn no_root_compound() {
     );
     assert_eq!(Ok(vec![1, 2, 3, 4]), from_value(&nbt!([1, 2, 3, 4])));
 }
+
+fn with_arrays() -> Value {
+    nbt!({
+        "known": 1,
+        "bytes": [B; 1, -2, 3],
+        "ints": [I; 1, -2, 0x01020304],
+        "longs": [L; 1, -2, 0x0102030405060708],
+        "compound": {
+            "ints": [I; 5],
+        },
+        "list": [{ "longs": [L; 6] }],
+    })
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+struct Nested {
+    bytes: Value,
+    ints: Value,
+    longs: Value,
+    compound: Value,
+    list: Value,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+struct Flattened {
+    known: i32,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+fn assert_same_as_root(root: &Value, nested: Nested, flattened: Flattened) {
+    let root = match root {
+        Value::Compound(map) => map,
+        _ => panic!("not a compound"),
+    };
+
+    assert_eq!(root["bytes"], nested.bytes);
+    assert_eq!(root["ints"], nested.ints);
+    assert_eq!(root["longs"], nested.longs);
+    assert_eq!(root["compound"], nested.compound);
+    assert_eq!(root["list"], nested.list);
+
+    assert_eq!(flattened.known, 1);
+    assert_eq!(flattened.extra.len(), root.len() - 1);
+    for (key, value) in &flattened.extra {
+        assert_eq!(&root[key], value, "{key}");
+    }
+}
+
+#[test]
+fn arrays_in_nested_values_from_bytes() {
+    let data = to_bytes(&with_arrays()).unwrap();
+    let root: Value = from_bytes(&data).unwrap();
+    assert_eq!(root, with_arrays());
+
+    assert_same_as_root(
+        &root,
+        from_bytes(&data).unwrap(),
+        from_bytes(&data).unwrap(),
+    );
+}
+
+#[test]
+fn arrays_in_nested_values_from_value() {
+    let value = with_arrays();
+    let root: Value = from_value(&value).unwrap();
+    assert_eq!(root, value);
+
+    assert_same_as_root(
+        &root,
+        from_value(&value).unwrap(),
+        from_value(&value).unwrap(),
+    );
+}
+
+#[test]
+fn arrays_in_untagged_enum() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(untagged)]
+    enum Either {
+        Number(i32),
+        Other(Value),
+    }
+
+    for value in [nbt!([B; 1, 2]), nbt!([I; 1, 2]), nbt!([L; 1, 2])] {
+        let either: Either = from_value(&value).unwrap();
+        assert_eq!(either, Either::Other(value));
+    }
+}
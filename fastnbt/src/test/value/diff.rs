@@ -0,0 +1,111 @@
+use crate::{
+    value::{DifferenceKind, PathSegment},
+    IntArray, Tag, Value,
+};
+
+#[test]
+fn diff_of_equal_values_is_empty() {
+    let v = nbt!({ "a": [1, 2], "b": { "c": 1.5_f32 } });
+    assert!(v.diff(&v.clone()).is_empty());
+    assert_eq!(v.diff(&v).to_string(), "");
+}
+
+#[test]
+fn diff_gives_path_to_difference() {
+    let old = nbt!({
+        "Level": {
+            "Sections": [
+                { "Y": 0_i8, "Palette": [{ "Name": "minecraft:air" }] },
+                { "Y": 1_i8, "Palette": [{ "Name": "minecraft:air" }, { "Name": "minecraft:stone" }] },
+            ],
+        },
+    });
+    let mut new = old.clone();
+    let Value::Compound(level) = &mut new else {
+        unreachable!()
+    };
+    let Some(Value::Compound(level)) = level.get_mut("Level") else {
+        unreachable!()
+    };
+    level.insert(
+        "Sections".to_owned(),
+        nbt!([
+            { "Y": 0_i8, "Palette": [{ "Name": "minecraft:air" }] },
+            { "Y": 1_i8, "Palette": [{ "Name": "minecraft:air" }, { "Name": "minecraft:dirt" }] },
+        ]),
+    );
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.len(), 1);
+
+    let difference = &diff.differences()[0];
+    assert_eq!(
+        difference.path,
+        vec![
+            PathSegment::Key("Level".to_owned()),
+            PathSegment::Key("Sections".to_owned()),
+            PathSegment::Index(1),
+            PathSegment::Key("Palette".to_owned()),
+            PathSegment::Index(1),
+            PathSegment::Key("Name".to_owned()),
+        ]
+    );
+    assert_eq!(
+        difference.kind,
+        DifferenceKind::Changed {
+            from: nbt!("minecraft:stone"),
+            to: nbt!("minecraft:dirt"),
+        }
+    );
+    assert_eq!(
+        diff.to_string(),
+        "Level.Sections[1].Palette[1].Name: changed from String(\"minecraft:stone\") to String(\"minecraft:dirt\")\n"
+    );
+}
+
+#[test]
+fn diff_added_and_removed() {
+    let diff = nbt!({ "a": 1, "b": [1, 2, 3] }).diff(&nbt!({ "b": [1, 2], "c": 2 }));
+    assert_eq!(
+        diff.to_string(),
+        "a: removed Int(1)\n\
+         b[2]: removed Int(3)\n\
+         c: added Int(2)\n"
+    );
+}
+
+#[test]
+fn diff_arrays() {
+    let a = Value::IntArray(IntArray::new(vec![1, 2, 3]));
+    let b = Value::IntArray(IntArray::new(vec![1, 5, 3]));
+    assert_eq!(
+        a.diff(&b).to_string(),
+        "[1]: changed from Int(2) to Int(5)\n"
+    );
+
+    // Arrays of different lengths differ as a whole.
+    let c = Value::IntArray(IntArray::new(vec![1]));
+    let diff = a.diff(&c);
+    assert_eq!(diff.len(), 1);
+    assert!(diff.differences()[0].path.is_empty());
+    assert!(diff
+        .to_string()
+        .starts_with("(root): changed from IntArray"));
+}
+
+#[test]
+fn diff_compares_floats_exactly() {
+    assert!(nbt!(f32::NAN).diff(&nbt!(f32::NAN)).is_empty());
+    assert!(nbt!(f64::NAN).diff(&nbt!(f64::NAN)).is_empty());
+    assert_eq!(nbt!(0.0_f64).diff(&nbt!(-0.0_f64)).len(), 1);
+    assert_eq!(nbt!(0.1_f32).diff(&nbt!(0.1_f64)).len(), 1);
+}
+
+#[test]
+fn diff_empty_lists() {
+    let empty = Value::EmptyList(Tag::Int);
+    assert!(empty.diff(&Value::EmptyList(Tag::Int)).is_empty());
+    assert_eq!(empty.diff(&Value::EmptyList(Tag::Long)).len(), 1);
+    assert_eq!(empty.diff(&nbt!([1])).len(), 1);
+    assert_eq!(Value::List(vec![]).diff(&nbt!([1])).len(), 1);
+}
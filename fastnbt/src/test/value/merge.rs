@@ -0,0 +1,112 @@
+use crate::{
+    value::{CompoundMerge, ListMerge, MergeStrategy},
+    LongArray, Tag, Value,
+};
+
+fn append() -> MergeStrategy {
+    MergeStrategy::new().lists(ListMerge::Append)
+}
+
+#[test]
+fn merge_recurses_into_compounds() {
+    let mut v = nbt!({
+        "a": 1,
+        "nested": { "keep": "x", "change": 1_i8 },
+    });
+    v.merge(
+        &nbt!({ "b": 2, "nested": { "change": 2_i8, "add": [1, 2] } }),
+        MergeStrategy::default(),
+    );
+
+    assert_eq!(
+        v,
+        nbt!({
+            "a": 1,
+            "b": 2,
+            "nested": { "keep": "x", "change": 2_i8, "add": [1, 2] },
+        })
+    );
+}
+
+#[test]
+fn merge_can_replace_compounds() {
+    let mut v = nbt!({ "nested": { "keep": "x" } });
+    v.merge(
+        &nbt!({ "nested": { "other": 1 } }),
+        MergeStrategy::new().compounds(CompoundMerge::Replace),
+    );
+    assert_eq!(v, nbt!({ "nested": { "other": 1 } }));
+}
+
+#[test]
+fn merge_replaces_different_tags() {
+    let mut v = nbt!({ "a": 1, "b": { "c": 1 } });
+    v.merge(&nbt!({ "a": 1_i64, "b": "text" }), MergeStrategy::default());
+    assert_eq!(v, nbt!({ "a": 1_i64, "b": "text" }));
+}
+
+#[test]
+fn merge_lists() {
+    let mut v = nbt!([1, 2]);
+    v.merge(&nbt!([3]), MergeStrategy::default());
+    assert_eq!(v, nbt!([3]));
+
+    let mut v = nbt!([1, 2]);
+    v.merge(&nbt!([3]), append());
+    assert_eq!(v, nbt!([1, 2, 3]));
+
+    // Elements of a different tag cannot be appended.
+    let mut v = nbt!([1, 2]);
+    v.merge(&nbt!(["a"]), append());
+    assert_eq!(v, nbt!(["a"]));
+}
+
+#[test]
+fn merge_lists_by_index() {
+    let by_index = MergeStrategy::new().lists(ListMerge::ByIndex);
+
+    let mut v = nbt!([{ "a": 1, "b": 2 }, { "a": 3 }]);
+    v.merge(&nbt!([{ "a": 10 }, { "c": 4 }]), by_index);
+    assert_eq!(v, nbt!([{ "a": 10, "b": 2 }, { "a": 3, "c": 4 }]));
+
+    // Nested lists are merged by index too.
+    let mut v = nbt!({ "l": [[{ "a": 1, "b": 2 }]] });
+    v.merge(&nbt!({ "l": [[{ "a": 5 }]] }), by_index);
+    assert_eq!(v, nbt!({ "l": [[{ "a": 5, "b": 2 }]] }));
+
+    // Lists of different lengths are replaced.
+    let mut v = nbt!([{ "a": 1, "b": 2 }]);
+    v.merge(&nbt!([{ "a": 1 }, { "a": 2 }]), by_index);
+    assert_eq!(v, nbt!([{ "a": 1 }, { "a": 2 }]));
+}
+
+#[test]
+fn merge_appends_empty_lists() {
+    let mut v = Value::EmptyList(Tag::Int);
+    v.merge(&nbt!([3]), append());
+    assert_eq!(v, nbt!([3]));
+
+    let mut v = nbt!([1]);
+    v.merge(&Value::EmptyList(Tag::Int), append());
+    assert_eq!(v, nbt!([1]));
+
+    let mut v = nbt!([1]);
+    v.merge(&Value::EmptyList(Tag::String), append());
+    assert_eq!(v, Value::EmptyList(Tag::String));
+
+    // An untyped empty list takes the tag of what is appended.
+    let mut v = Value::List(vec![]);
+    v.merge(&Value::EmptyList(Tag::Long), append());
+    assert_eq!(v, Value::EmptyList(Tag::Long));
+
+    let mut v = Value::EmptyList(Tag::Long);
+    v.merge(&Value::List(vec![]), append());
+    assert_eq!(v, Value::EmptyList(Tag::Long));
+}
+
+#[test]
+fn merge_replaces_arrays() {
+    let mut v = Value::LongArray(LongArray::new(vec![1, 2]));
+    v.merge(&Value::LongArray(LongArray::new(vec![3])), append());
+    assert_eq!(v, Value::LongArray(LongArray::new(vec![3])));
+}
@@ -1,9 +1,15 @@
-mod ser;
+mod borrowed;
+mod cmp;
+mod convert;
 mod de;
+mod diff;
+mod merge;
+mod pointer;
+mod ser;
 
 use std::collections::HashMap;
 
-use crate::{from_bytes, to_bytes, Tag, Value};
+use crate::{from_bytes, from_value, to_bytes, Map, Tag, Value};
 
 use super::builder::Builder;
 
@@ -118,7 +124,7 @@ fn distinguish_floats() {
 #[test]
 fn fuzz_float() {
     let v = Value::Float(1.4e-44);
-    let mut inner = HashMap::new();
+    let mut inner = Map::default();
     inner.insert("".to_string(), v);
 
     let v = Value::Compound(inner);
@@ -216,3 +222,117 @@ fn distinguish_compound() {
     let v: Value = from_bytes(&input).unwrap();
     assert_contains!(v, "a", Value::Compound(_));
 }
+
+#[test]
+fn compare_with_primitives() {
+    let v = crate::nbt!({
+        "Name": "minecraft:stone",
+        "Count": 1_i8,
+        "Tags": ["a", "b"],
+        "Lit": true,
+        "Weight": 2.5,
+    });
+
+    let Value::Compound(v) = v else {
+        panic!("expected compound");
+    };
+
+    assert_eq!(v["Name"], "minecraft:stone");
+    assert_eq!(v["Name"], "minecraft:stone".to_string());
+    assert_eq!("minecraft:stone", v["Name"]);
+    assert_eq!(v["Count"], 1i8);
+    assert_eq!(v["Count"], 1i64);
+    assert_eq!(1u8, v["Count"]);
+    assert_eq!(v["Lit"], true);
+    assert_ne!(v["Lit"], false);
+    assert_eq!(v["Weight"], 2.5);
+    assert_ne!(v["Name"], 1);
+    assert_ne!(v["Count"], "1");
+    assert_ne!(v["Tags"], true);
+    assert_eq!(v["Tags"], crate::nbt!(["a", "b"]));
+}
+
+#[test]
+fn empty_lists_keep_element_tag() {
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("compounds", Tag::Compound, 0)
+        .start_list("ints", Tag::Int, 0)
+        .start_list("end", Tag::End, 0)
+        .start_list("nested", Tag::List, 1)
+        .tag(Tag::String)
+        .int_payload(0)
+        .end_compound()
+        .build();
+
+    let v: Value = from_bytes(&input).unwrap();
+    assert_contains!(v, "compounds", Value::EmptyList(Tag::Compound));
+    assert_contains!(v, "ints", Value::EmptyList(Tag::Int));
+    assert_contains!(v, "end", Value::List(ref data), data.is_empty());
+    assert_contains!(
+        v,
+        "nested",
+        Value::List(ref data),
+        data == &[Value::EmptyList(Tag::String)]
+    );
+}
+
+#[test]
+fn empty_list_round_trips_exactly() {
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("a", Tag::Compound, 0)
+        .end_compound()
+        .build();
+
+    let v: Value = from_bytes(&input).unwrap();
+    assert_eq!(to_bytes(&v).unwrap(), input);
+
+    let v: HashMap<String, Value> = from_bytes(&input).unwrap();
+    assert_eq!(v["a"], Value::EmptyList(Tag::Compound));
+    assert_eq!(to_bytes(&v).unwrap(), input);
+}
+
+#[test]
+fn empty_list_of_end_written_as_plain_empty_list() {
+    let mut v = HashMap::new();
+    v.insert("a".to_string(), Value::EmptyList(Tag::End));
+    let bytes = to_bytes(&v).unwrap();
+
+    let expected = Builder::new()
+        .start_compound("")
+        .start_list("a", Tag::End, 0)
+        .end_compound()
+        .build();
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn typed_empty_list_into_other_types() {
+    #[derive(serde::Deserialize)]
+    struct V {
+        a: Vec<i32>,
+        b: Value,
+    }
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("a", Tag::Int, 0)
+        .start_list("b", Tag::Int, 0)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&input).unwrap();
+    assert!(v.a.is_empty());
+    assert_eq!(v.b, Value::EmptyList(Tag::Int));
+
+    let a: Vec<i32> = from_value(&Value::EmptyList(Tag::Int)).unwrap();
+    assert!(a.is_empty());
+}
+
+#[test]
+fn empty_list_equality() {
+    assert_eq!(Value::EmptyList(Tag::Int), Value::EmptyList(Tag::Int));
+    assert_ne!(Value::EmptyList(Tag::Int), Value::EmptyList(Tag::Compound));
+    assert_ne!(Value::EmptyList(Tag::Int), Value::List(vec![]));
+}
@@ -0,0 +1,85 @@
+use std::borrow::Cow;
+
+use crate::{ByteArray, IntArray, LongArray, Value};
+
+fn chunk() -> Value {
+    nbt!({
+        "Level": {
+            "Sections": [
+                { "Y": 0_i8, "Palette": [{ "Name": "minecraft:air" }] },
+                { "Y": 1_i8, "BlockStates": LongArray::new(vec![1, 2, 3]) },
+            ],
+            "Biomes": IntArray::new(vec![4, 5]),
+            "Light": ByteArray::new(vec![-1]),
+        },
+        "a/b": { "c~d": 1, "": 2 },
+    })
+}
+
+#[test]
+fn pointer_finds_values() {
+    let v = chunk();
+    assert_eq!(v.pointer(""), Some(&v));
+    assert_eq!(
+        v.pointer("/Level/Sections/0/Palette/0/Name").unwrap(),
+        "minecraft:air"
+    );
+    assert_eq!(v.pointer("/Level/Sections/1/Y"), Some(&Value::Byte(1)));
+    assert_eq!(v.pointer("/a~1b/c~0d"), Some(&Value::Int(1)));
+    assert_eq!(v.pointer("/a~1b/"), Some(&Value::Int(2)));
+}
+
+#[test]
+fn pointer_misses() {
+    let v = chunk();
+    assert_eq!(v.pointer("Level"), None);
+    assert_eq!(v.pointer("/Missing"), None);
+    assert_eq!(v.pointer("/Level/Sections/2"), None);
+    assert_eq!(v.pointer("/Level/Sections/00"), None);
+    assert_eq!(v.pointer("/Level/Sections/+1"), None);
+    assert_eq!(v.pointer("/Level/Sections/-1"), None);
+    assert_eq!(v.pointer("/Level/Sections/Y"), None);
+    assert_eq!(v.pointer("/Level/Sections/0/Y/0"), None);
+    assert_eq!(v.pointer("/a/b"), None);
+
+    // Array elements cannot be borrowed.
+    assert_eq!(v.pointer("/Level/Biomes/0"), None);
+}
+
+#[test]
+fn pointer_mut_changes_values() {
+    let mut v = chunk();
+    *v.pointer_mut("/Level/Sections/0/Palette/0/Name").unwrap() = nbt!("minecraft:stone");
+    assert_eq!(
+        v.pointer("/Level/Sections/0/Palette/0/Name").unwrap(),
+        "minecraft:stone"
+    );
+
+    *v.pointer_mut("").unwrap() = nbt!(1);
+    assert_eq!(v, nbt!(1));
+}
+
+#[test]
+fn pointer_cow_finds_array_elements() {
+    let v = chunk();
+    assert_eq!(
+        v.pointer_cow("/Level/Sections/1/BlockStates/2"),
+        Some(Cow::Owned(Value::Long(3)))
+    );
+    assert_eq!(
+        v.pointer_cow("/Level/Biomes/1"),
+        Some(Cow::Owned(Value::Int(5)))
+    );
+    assert_eq!(
+        v.pointer_cow("/Level/Light/0"),
+        Some(Cow::Owned(Value::Byte(-1)))
+    );
+    assert_eq!(v.pointer_cow("/Level/Biomes/2"), None);
+    assert_eq!(v.pointer_cow("/Level/Biomes/01"), None);
+
+    assert!(matches!(
+        v.pointer_cow("/Level/Sections/1/Y"),
+        Some(Cow::Borrowed(Value::Byte(1)))
+    ));
+    assert!(matches!(v.pointer_cow(""), Some(Cow::Borrowed(_))));
+}
@@ -1,8 +1,6 @@
-use std::collections::HashMap;
-
 use serde::Serialize;
 
-use crate::{to_value, ByteArray, IntArray, LongArray, Value};
+use crate::{from_value, to_value, ByteArray, IntArray, LongArray, Map, Tag, Value};
 
 #[test]
 fn simple_types() {
@@ -43,7 +41,7 @@ fn simple_types() {
 
     let val = to_value(&v).unwrap();
     // Note: we cannot use the nbt! macro here as that uses the `to_value` function
-    let expected = Value::Compound(HashMap::from([
+    let expected = Value::Compound(Map::from_iter([
         ("bool".to_string(), Value::Byte(1)),
         ("i8".to_string(), Value::Byte(i8::MAX)),
         ("i16".to_string(), Value::Short(i16::MAX)),
@@ -83,7 +81,7 @@ fn int_array_types() {
     };
 
     let val = to_value(&v).unwrap();
-    let expected = Value::Compound(HashMap::from([
+    let expected = Value::Compound(Map::from_iter([
         (
             "i128".to_string(),
             // Only left most bit is 0
@@ -135,14 +133,14 @@ fn nested() {
     };
 
     let val = to_value(&v).unwrap();
-    let expected = Value::Compound(HashMap::from([
+    let expected = Value::Compound(Map::from_iter([
         (
             "list".to_string(),
             Value::List(vec![Value::Short(1), Value::Short(2)]),
         ),
         (
             "nested".to_string(),
-            Value::Compound(HashMap::from([("key".to_string(), Value::Byte(42))])),
+            Value::Compound(Map::from_iter([("key".to_string(), Value::Byte(42))])),
         ),
     ]));
 
@@ -185,3 +183,32 @@ fn no_root_compound() {
         ])
     );
 }
+
+#[test]
+fn empty_list_through_value() {
+    let v = to_value(Value::EmptyList(Tag::Compound)).unwrap();
+    assert_eq!(v, Value::EmptyList(Tag::Compound));
+
+    let v: Value = from_value(&Value::EmptyList(Tag::Compound)).unwrap();
+    assert_eq!(v, Value::EmptyList(Tag::Compound));
+
+    let v = to_value(Value::EmptyList(Tag::End)).unwrap();
+    assert_eq!(v, Value::List(vec![]));
+}
+
+#[test]
+fn empty_list_to_other_formats_is_an_empty_seq() {
+    let mut map = Map::default();
+    map.insert("list".to_owned(), Value::EmptyList(Tag::Int));
+    let v = Value::Compound(map);
+
+    assert_eq!(serde_json::to_string(&v).unwrap(), r#"{"list":[]}"#);
+    assert_eq!(
+        serde_json::to_string(&Value::EmptyList(Tag::Compound)).unwrap(),
+        "[]"
+    );
+
+    // NBT still keeps the element tag.
+    let bytes = crate::to_bytes(&v).unwrap();
+    assert_eq!(crate::from_bytes::<Value>(&bytes).unwrap(), v);
+}
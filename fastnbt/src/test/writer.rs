@@ -0,0 +1,249 @@
+use std::{cell::Cell, io::Write};
+
+use crate::{error::Result, ser::NbtWriter, test::builder::Builder, to_bytes, Tag};
+
+#[test]
+fn writes_same_bytes_as_builder() -> Result<()> {
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.start_compound("root")?;
+    writer.byte("b", 1)?;
+    writer.short("s", 2)?;
+    writer.int("i", 3)?;
+    writer.long("l", 4)?;
+    writer.float("f", 5.0)?;
+    writer.double("d", 6.0)?;
+    writer.string("str", "hello")?;
+    writer.byte_array("ba", &[1, 2])?;
+    writer.int_array("ia", &[3])?;
+    writer.long_array("la", &[4, 5])?;
+    writer.start_compound("inner")?;
+    writer.int("a", 1)?;
+    writer.end_compound()?;
+    writer.end_compound()?;
+
+    let expected = Builder::new()
+        .start_compound("root")
+        .byte("b", 1)
+        .short("s", 2)
+        .int("i", 3)
+        .long("l", 4)
+        .float("f", 5.0)
+        .double("d", 6.0)
+        .string("str", "hello")
+        .byte_array("ba", &[1, 2])
+        .int_array("ia", &[3])
+        .long_array("la", &[4, 5])
+        .start_compound("inner")
+        .int("a", 1)
+        .end_compound()
+        .end_compound()
+        .build();
+
+    assert_eq!(writer.finish()?, expected);
+    Ok(())
+}
+
+#[test]
+fn lists_of_values_compounds_and_lists() -> Result<()> {
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.start_compound("")?;
+    writer.start_list("ints", Tag::Int, 2)?;
+    writer.int("", 1)?;
+    writer.int("ignored", 2)?;
+    writer.end_list()?;
+    writer.start_list("compounds", Tag::Compound, 1)?;
+    writer.start_compound("")?;
+    writer.string("id", "a")?;
+    writer.end_compound()?;
+    writer.end_list()?;
+    writer.start_list("lists", Tag::List, 2)?;
+    writer.start_list("", Tag::Short, 1)?;
+    writer.short("", 3)?;
+    writer.end_list()?;
+    writer.start_list("", Tag::End, 0)?;
+    writer.end_list()?;
+    writer.end_list()?;
+    writer.end_compound()?;
+
+    let expected = nbt!({
+        "ints": [1, 2],
+        "compounds": [{"id": "a"}],
+        "lists": [[3_i16], []],
+    });
+    let value: crate::Value = crate::from_bytes(&writer.finish()?)?;
+    assert_eq!(value, expected);
+    Ok(())
+}
+
+#[test]
+fn matches_serializer_output() -> Result<()> {
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.start_compound("")?;
+    writer.start_list("val", Tag::Long, 3)?;
+    for i in 0..3 {
+        writer.long("", i)?;
+    }
+    writer.end_list()?;
+    writer.end_compound()?;
+
+    assert_eq!(
+        writer.finish()?,
+        to_bytes(&nbt!({"val": [0_i64, 1_i64, 2_i64]}))?
+    );
+    Ok(())
+}
+
+#[test]
+fn too_many_list_elements_error() -> Result<()> {
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.start_compound("")?;
+    writer.start_list("val", Tag::Int, 2)?;
+    writer.int("", 1)?;
+    writer.int("", 2)?;
+
+    let err = writer.int("", 3).unwrap_err();
+    assert_eq!(err.to_string(), "cannot write more than 2 elements to list");
+    Ok(())
+}
+
+#[test]
+fn too_few_list_elements_error() -> Result<()> {
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.start_compound("")?;
+    writer.start_list("val", Tag::Int, 3)?;
+    writer.int("", 1)?;
+
+    let err = writer.end_list().unwrap_err();
+    assert_eq!(err.to_string(), "cannot end list: 1 of 3 elements written");
+
+    // Finishing the list properly carries on as normal.
+    writer.int("", 2)?;
+    writer.int("", 3)?;
+    writer.end_list()?;
+    writer.end_compound()?;
+    writer.finish()?;
+    Ok(())
+}
+
+#[test]
+fn list_elements_must_have_the_list_tag() -> Result<()> {
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.start_compound("")?;
+    writer.start_list("val", Tag::Int, 1)?;
+
+    let err = writer.long("", 1).unwrap_err();
+    assert_eq!(err.to_string(), "cannot write long to list of int");
+    assert!(writer.start_list("", Tag::End, 1).is_err());
+    Ok(())
+}
+
+#[test]
+fn ending_the_wrong_scope_errors() -> Result<()> {
+    let mut writer = NbtWriter::new(Vec::new());
+    assert!(writer.end_compound().is_err());
+    assert!(writer.end_list().is_err());
+
+    writer.start_compound("")?;
+    assert!(writer.end_list().is_err());
+    writer.start_list("val", Tag::Compound, 0)?;
+    assert!(writer.end_compound().is_err());
+    Ok(())
+}
+
+#[test]
+fn root_must_be_a_single_compound() -> Result<()> {
+    let mut writer = NbtWriter::new(Vec::new());
+    assert!(writer.int("", 1).is_err());
+    assert!(writer.start_list("", Tag::Int, 0).is_err());
+
+    writer.start_compound("")?;
+    writer.end_compound()?;
+    assert!(writer.start_compound("").is_err());
+    writer.finish()?;
+    Ok(())
+}
+
+#[test]
+fn finish_checks_scopes_are_closed() -> Result<()> {
+    let writer = NbtWriter::new(Vec::new());
+    let err = writer.finish().unwrap_err();
+    assert_eq!(err.to_string(), "cannot finish: no root compound written");
+
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.start_compound("")?;
+    writer.start_list("val", Tag::Int, 0)?;
+    let err = writer.finish().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "cannot finish: 2 scopes still open, innermost is a list"
+    );
+    Ok(())
+}
+
+#[test]
+fn writes_through_without_buffering() -> Result<()> {
+    // Counts bytes as they reach the sink.
+    struct Counter<'a>(&'a Cell<usize>);
+
+    impl Write for Counter<'_> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.set(self.0.get() + buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let written = Cell::new(0);
+    let mut writer = NbtWriter::new(Counter(&written));
+    writer.start_compound("")?;
+    writer.start_list("val", Tag::Long, 1000)?;
+    for i in 0..1000 {
+        writer.long("", i)?;
+    }
+
+    // Root header, list header and the longs, before the list is ended.
+    assert_eq!(written.get(), 3 + 11 + 8000);
+
+    writer.end_list()?;
+    writer.end_compound()?;
+    writer.finish()?;
+    assert_eq!(written.get(), 3 + 11 + 8000 + 1);
+    Ok(())
+}
+
+#[test]
+fn arrays_are_written_in_blocks() -> Result<()> {
+    // Counts calls to write, and keeps what was written.
+    struct Calls<'a>(&'a Cell<usize>, Vec<u8>);
+
+    impl Write for Calls<'_> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.set(self.0.get() + 1);
+            self.1.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let longs: Vec<i64> = (0..10_000).collect();
+    let calls = Cell::new(0);
+    let mut writer = NbtWriter::new(Calls(&calls, vec![]));
+    writer.start_compound("")?;
+    let before = calls.get();
+    writer.long_array("val", &longs)?;
+
+    // 80000 bytes of longs in 8192 byte blocks, after the header.
+    assert!(calls.get() - before < 20, "{} writes", calls.get() - before);
+
+    writer.end_compound()?;
+    let Calls(_, bytes) = writer.finish()?;
+    let value: crate::Value = crate::from_bytes(&bytes)?;
+    assert_eq!(value, crate::nbt!({"val": crate::LongArray::new(longs)}));
+    Ok(())
+}
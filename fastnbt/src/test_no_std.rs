@@ -0,0 +1,92 @@
+use alloc::{string::String, vec, vec::Vec};
+
+use serde::Deserialize;
+
+use crate::{borrow, error::Result, from_bytes, LongArray, Map, Value};
+
+// {"": {"name": "Steve", "pos": [1.5d, 64d], "heights": [L; 1, -1],
+// "nested": {"byte": 3b}}}
+const PLAYER: &[u8] = &[
+    10, 0, 0, //
+    8, 0, 4, b'n', b'a', b'm', b'e', 0, 5, b'S', b't', b'e', b'v', b'e', //
+    9, 0, 3, b'p', b'o', b's', 6, 0, 0, 0, 2, //
+    0x3f, 0xf8, 0, 0, 0, 0, 0, 0, //
+    0x40, 0x50, 0, 0, 0, 0, 0, 0, //
+    12, 0, 7, b'h', b'e', b'i', b'g', b'h', b't', b's', 0, 0, 0, 2, //
+    0, 0, 0, 0, 0, 0, 0, 1, //
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, //
+    10, 0, 6, b'n', b'e', b's', b't', b'e', b'd', //
+    1, 0, 4, b'b', b'y', b't', b'e', 3, //
+    0, //
+    0,
+];
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Player {
+    name: String,
+    pos: Vec<f64>,
+    heights: LongArray,
+    nested: Nested,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Nested {
+    byte: i8,
+}
+
+#[test]
+fn struct_from_bytes() {
+    let player: Player = from_bytes(PLAYER).unwrap();
+    assert_eq!(
+        player,
+        Player {
+            name: "Steve".into(),
+            pos: vec![1.5, 64.0],
+            heights: LongArray::new(vec![1, -1]),
+            nested: Nested { byte: 3 },
+        }
+    );
+}
+
+#[test]
+fn borrowed_fields_from_bytes() {
+    #[derive(Deserialize)]
+    struct Partial<'a> {
+        name: &'a str,
+        #[serde(borrow)]
+        heights: borrow::LongArray<'a>,
+    }
+
+    let player: Partial = from_bytes(PLAYER).unwrap();
+    assert_eq!(player.name, "Steve");
+    assert_eq!(player.heights.iter().collect::<Vec<_>>(), [1, -1]);
+}
+
+#[test]
+fn value_from_bytes() {
+    let value: Value = from_bytes(PLAYER).unwrap();
+
+    let mut nested = Map::new();
+    nested.insert("byte".into(), Value::Byte(3));
+    let mut expected = Map::new();
+    expected.insert("name".into(), Value::String("Steve".into()));
+    expected.insert(
+        "pos".into(),
+        Value::List(vec![Value::Double(1.5), Value::Double(64.0)]),
+    );
+    expected.insert(
+        "heights".into(),
+        Value::LongArray(LongArray::new(vec![1, -1])),
+    );
+    expected.insert("nested".into(), Value::Compound(nested));
+
+    assert_eq!(value, Value::Compound(expected));
+}
+
+#[test]
+fn truncated_input_errors() {
+    for len in 0..PLAYER.len() {
+        let res: Result<Player> = from_bytes(&PLAYER[..len]);
+        assert!(res.is_err(), "{} bytes", len);
+    }
+}
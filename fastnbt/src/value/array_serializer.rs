@@ -1,18 +1,15 @@
-use byteorder::{NativeEndian, ReadBytesExt};
+use byteorder::NativeEndian;
 use serde::ser::Impossible;
 
 use crate::{error::Error, ByteArray, IntArray, LongArray, Tag, Value};
 
-use super::ser::Serializer;
-
 /// ArraySerializer is for serializing the NBT Arrays ie ByteArray, IntArray and
 /// LongArray.
-pub struct ArraySerializer<'a> {
-    pub ser: &'a mut Serializer,
+pub struct ArraySerializer {
     pub tag: Tag,
 }
 
-impl<'a> serde::Serializer for ArraySerializer<'a> {
+impl serde::Serializer for ArraySerializer {
     type Ok = Value;
     type Error = Error;
     type SerializeSeq = Impossible<Self::Ok, Self::Error>;
@@ -78,16 +75,8 @@ impl<'a> serde::Serializer for ArraySerializer<'a> {
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
         match self.tag {
             Tag::ByteArray => Ok(Value::ByteArray(ByteArray::from_bytes(v))),
-            Tag::IntArray => Ok(Value::IntArray(IntArray::new(
-                v.chunks_exact(4)
-                    .map(|mut bs| bs.read_i32::<NativeEndian>())
-                    .collect::<std::io::Result<Vec<i32>>>()?,
-            ))),
-            Tag::LongArray => Ok(Value::LongArray(LongArray::new(
-                v.chunks_exact(8)
-                    .map(|mut bs| bs.read_i64::<NativeEndian>())
-                    .collect::<std::io::Result<Vec<i64>>>()?,
-            ))),
+            Tag::IntArray => Ok(Value::IntArray(IntArray::from_bytes::<NativeEndian>(v))),
+            Tag::LongArray => Ok(Value::LongArray(LongArray::from_bytes::<NativeEndian>(v))),
             _ => unreachable!(),
         }
     }
@@ -0,0 +1,197 @@
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+use core::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::{borrow, ByteArray, IntArray, LongArray, Map, Tag, Value};
+
+use super::{
+    de::{KeyClass, KeyClassifier},
+    VALUE_TOKEN,
+};
+
+/// Like [`Value`], but borrowing from the input where it can rather than
+/// owning its data. This makes parsing NBT that is only looked at and thrown
+/// away much cheaper, eg when filtering chunks by criteria only known at run
+/// time.
+///
+/// Arrays are views of the input, see [`borrow`][crate::borrow]. Strings and
+/// compound keys borrow when Java's encoding of them is the same as Rust's,
+/// which is the case for nearly all strings, see [`CowStr`][borrow::CowStr].
+///
+/// ```
+/// # use fastnbt::{nbt, borrow::BorrowedValue};
+/// let nbt = fastnbt::to_bytes(&nbt!({
+///     "Status": "minecraft:full",
+///     "Heightmaps": { "WORLD_SURFACE": [L; 1, 2, 3] },
+/// }))
+/// .unwrap();
+///
+/// let chunk: BorrowedValue = fastnbt::from_bytes(&nbt).unwrap();
+/// let BorrowedValue::Compound(chunk) = &chunk else { panic!() };
+/// assert!(matches!(&chunk["Status"], BorrowedValue::String(s) if s == "minecraft:full"));
+/// ```
+///
+/// Only NBT being deserialized can be borrowed from. Deserializing from a
+/// [`Value`] fails for arrays, which a `Value` stores in a different form.
+#[derive(Debug, Clone)]
+pub enum BorrowedValue<'a> {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(Cow<'a, str>),
+    ByteArray(borrow::ByteArray<'a>),
+    IntArray(borrow::IntArray<'a>),
+    LongArray(borrow::LongArray<'a>),
+    List(Vec<BorrowedValue<'a>>),
+    /// An empty list whose elements would have the given tag, see
+    /// [`Value::EmptyList`].
+    EmptyList(Tag),
+    Compound(Map<Cow<'a, str>, BorrowedValue<'a>>),
+}
+
+impl BorrowedValue<'_> {
+    /// Copy the value into a [`Value`] that owns its data.
+    pub fn to_owned(&self) -> Value {
+        match self {
+            BorrowedValue::Byte(v) => Value::Byte(*v),
+            BorrowedValue::Short(v) => Value::Short(*v),
+            BorrowedValue::Int(v) => Value::Int(*v),
+            BorrowedValue::Long(v) => Value::Long(*v),
+            BorrowedValue::Float(v) => Value::Float(*v),
+            BorrowedValue::Double(v) => Value::Double(*v),
+            BorrowedValue::String(v) => Value::String(String::from(v.as_ref())),
+            BorrowedValue::ByteArray(v) => Value::ByteArray(ByteArray::new(v.iter().collect())),
+            BorrowedValue::IntArray(v) => Value::IntArray(IntArray::new(v.iter().collect())),
+            BorrowedValue::LongArray(v) => Value::LongArray(LongArray::new(v.iter().collect())),
+            BorrowedValue::List(v) => Value::List(v.iter().map(BorrowedValue::to_owned).collect()),
+            BorrowedValue::EmptyList(tag) => Value::EmptyList(*tag),
+            BorrowedValue::Compound(v) => Value::Compound(
+                v.iter()
+                    .map(|(k, v)| (String::from(k.as_ref()), v.to_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a, 'de: 'a> Deserialize<'de> for BorrowedValue<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // The token gets the element tag of empty lists, as for `Value`.
+        deserializer.deserialize_newtype_struct(VALUE_TOKEN, BorrowedValueVisitor(PhantomData))
+    }
+}
+
+struct BorrowedValueVisitor<'a>(PhantomData<&'a ()>);
+
+impl<'a, 'de: 'a> Visitor<'de> for BorrowedValueVisitor<'a> {
+    type Value = BorrowedValue<'a>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("valid NBT")
+    }
+
+    fn visit_i8<E: de::Error>(self, v: i8) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Byte(v))
+    }
+
+    fn visit_i16<E: de::Error>(self, v: i16) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Short(v))
+    }
+
+    fn visit_i32<E: de::Error>(self, v: i32) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Int(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Long(v))
+    }
+
+    fn visit_f32<E: de::Error>(self, v: f32) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Float(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Double(v))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::String(Cow::Borrowed(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::String(Cow::Owned(v.into())))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::String(Cow::Owned(v)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut list = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            list.push(element);
+        }
+        Ok(BorrowedValue::List(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        match map.next_key_seed(KeyClassifier)? {
+            Some(KeyClass::Compound(first_key)) => {
+                let mut compound = Map::default();
+
+                compound.insert(first_key, map.next_value()?);
+                while let Some(key) = map.next_key::<borrow::CowStr>()? {
+                    compound.insert(key.into_inner(), map.next_value()?);
+                }
+
+                Ok(BorrowedValue::Compound(compound))
+            }
+            Some(KeyClass::ByteArray) => {
+                let data = map.next_value::<&[u8]>()?;
+                Ok(BorrowedValue::ByteArray(borrow::ByteArray::from_bytes(
+                    data,
+                )))
+            }
+            Some(KeyClass::IntArray) => {
+                let data = map.next_value::<&[u8]>()?;
+                Ok(BorrowedValue::IntArray(borrow::IntArray::from_bytes(data)))
+            }
+            Some(KeyClass::LongArray) => {
+                let data = map.next_value::<&[u8]>()?;
+                Ok(BorrowedValue::LongArray(borrow::LongArray::from_bytes(
+                    data,
+                )))
+            }
+            Some(KeyClass::IntArrayValue | KeyClass::LongArrayValue) => Err(de::Error::custom(
+                "arrays in a Value cannot be borrowed, deserialize a Value instead",
+            )),
+            Some(KeyClass::EmptyList) => {
+                let tag = map.next_value::<u8>()?;
+                match Tag::try_from(tag) {
+                    Ok(Tag::End) => Ok(BorrowedValue::List(vec![])),
+                    Ok(tag) => Ok(BorrowedValue::EmptyList(tag)),
+                    Err(_) => Err(de::Error::custom("invalid list element tag")),
+                }
+            }
+            // No keys just means an empty compound.
+            None => Ok(BorrowedValue::Compound(Map::default())),
+        }
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
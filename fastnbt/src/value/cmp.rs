@@ -0,0 +1,175 @@
+use alloc::{string::String, vec::Vec};
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    mem,
+};
+
+use crate::Map;
+
+use super::Value;
+
+impl Value {
+    /// A total order of values, consistent with `==` on `Value`.
+    ///
+    /// Values of different types are ordered by their tag, in the order of
+    /// [`Tag`]'s ids, eg every `Byte` is before every `Short`. Values of the
+    /// same type are ordered as follows:
+    ///
+    /// * Integers and strings by value.
+    /// * Floats by [`f32::total_cmp`] and [`f64::total_cmp`]. This orders
+    ///   numbers as expected, but puts `-0.0` before `0.0` and orders NaNs by
+    ///   their bits, so only floats with identical bits are equal.
+    /// * Arrays and lists lexicographically by their elements. An
+    ///   [`EmptyList`][Value::EmptyList] is after `List(vec![])`, and empty
+    ///   lists with different element tags are ordered by the tag.
+    /// * Compounds lexicographically by their entries, sorted by key. Two
+    ///   compounds are equal if they have the same keys with equal values,
+    ///   whatever order they are stored in.
+    ///
+    /// ```
+    /// # use fastnbt::Value;
+    /// let mut values = vec![Value::Double(1.5), Value::Int(3), Value::Double(f64::NAN)];
+    /// values.sort_by(Value::total_cmp);
+    /// assert_eq!(values[0], Value::Int(3));
+    /// assert_eq!(values[2], Value::Double(f64::NAN));
+    /// ```
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        use Value::*;
+
+        match (self, other) {
+            (Byte(a), Byte(b)) => a.cmp(b),
+            (Short(a), Short(b)) => a.cmp(b),
+            (Int(a), Int(b)) => a.cmp(b),
+            (Long(a), Long(b)) => a.cmp(b),
+            (Float(a), Float(b)) => a.total_cmp(b),
+            (Double(a), Double(b)) => a.total_cmp(b),
+            (String(a), String(b)) => a.cmp(b),
+            (ByteArray(a), ByteArray(b)) => a[..].cmp(&b[..]),
+            (IntArray(a), IntArray(b)) => a[..].cmp(&b[..]),
+            (LongArray(a), LongArray(b)) => a[..].cmp(&b[..]),
+            (List(_) | EmptyList(_), List(_) | EmptyList(_)) => {
+                let (a, a_tag) = list_key(self);
+                let (b, b_tag) = list_key(other);
+                a.iter()
+                    .map(ValueOrd)
+                    .cmp(b.iter().map(ValueOrd))
+                    .then(a_tag.cmp(&b_tag))
+            }
+            (Compound(a), Compound(b)) => {
+                let a = sorted_entries(a);
+                let b = sorted_entries(b);
+                a.iter()
+                    .map(|&(k, v)| (k, ValueOrd(v)))
+                    .cmp(b.iter().map(|&(k, v)| (k, ValueOrd(v))))
+            }
+            _ => (self.tag() as u8).cmp(&(other.tag() as u8)),
+        }
+    }
+}
+
+/// Lets iterators of values be compared with [`Value::total_cmp`].
+struct ValueOrd<'a>(&'a Value);
+
+impl PartialEq for ValueOrd<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for ValueOrd<'_> {}
+
+impl PartialOrd for ValueOrd<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ValueOrd<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(other.0)
+    }
+}
+
+/// The elements of a list, and the element tag if it is an
+/// [`EmptyList`][Value::EmptyList].
+fn list_key(value: &Value) -> (&[Value], Option<u8>) {
+    match value {
+        Value::List(v) => (v, None),
+        Value::EmptyList(tag) => (&[], Some(*tag as u8)),
+        _ => unreachable!("not a list"),
+    }
+}
+
+fn sorted_entries(map: &Map<String, Value>) -> Vec<(&String, &Value)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by_key(|&(k, _)| k);
+    entries
+}
+
+/// Values are equal if [`Value::total_cmp`] says so. This compares what would
+/// be stored rather than numbers, so floats are only equal if they have the
+/// same bits: `NaN` equals a `NaN` with the same bits, and `0.0` does not
+/// equal `-0.0`.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        use Value::*;
+
+        match (self, other) {
+            (Byte(a), Byte(b)) => a == b,
+            (Short(a), Short(b)) => a == b,
+            (Int(a), Int(b)) => a == b,
+            (Long(a), Long(b)) => a == b,
+            (Float(a), Float(b)) => a.to_bits() == b.to_bits(),
+            (Double(a), Double(b)) => a.to_bits() == b.to_bits(),
+            (String(a), String(b)) => a == b,
+            (ByteArray(a), ByteArray(b)) => a == b,
+            (IntArray(a), IntArray(b)) => a == b,
+            (LongArray(a), LongArray(b)) => a == b,
+            (List(a), List(b)) => a == b,
+            (EmptyList(a), EmptyList(b)) => a == b,
+            (Compound(a), Compound(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// See [`Value::total_cmp`].
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.total_cmp(other)
+    }
+}
+
+/// Hashes floats by their bits, consistent with `==`. Compounds are hashed in
+/// the order of their keys, so equal compounds hash the same whatever order
+/// their entries are stored in.
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            Value::Byte(v) => v.hash(state),
+            Value::Short(v) => v.hash(state),
+            Value::Int(v) => v.hash(state),
+            Value::Long(v) => v.hash(state),
+            Value::Float(v) => v.to_bits().hash(state),
+            Value::Double(v) => v.to_bits().hash(state),
+            Value::String(v) => v.hash(state),
+            Value::ByteArray(v) => v[..].hash(state),
+            Value::IntArray(v) => v[..].hash(state),
+            Value::LongArray(v) => v[..].hash(state),
+            Value::List(v) => v.hash(state),
+            Value::EmptyList(tag) => (*tag as u8).hash(state),
+            Value::Compound(v) => sorted_entries(v).hash(state),
+        }
+    }
+}
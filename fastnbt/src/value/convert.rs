@@ -0,0 +1,146 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::convert::TryFrom;
+use core::fmt::{self, Display};
+
+use crate::{ByteArray, IntArray, LongArray, Map, Tag};
+
+use super::Value;
+
+/// Error from converting a [`Value`] into a Rust type with `TryFrom`, when
+/// the value has a different tag or does not fit in the type. The value is
+/// kept, so nothing is lost when a conversion fails.
+///
+/// ```
+/// # use fastnbt::Value;
+/// let err = i8::try_from(Value::Int(300)).unwrap_err();
+/// assert_eq!(err.to_string(), "cannot convert int to i8");
+/// assert_eq!(err.into_value(), Value::Int(300));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TryFromValueError {
+    value: Value,
+    target: &'static str,
+}
+
+impl TryFromValueError {
+    fn new(value: Value, target: &'static str) -> Self {
+        Self { value, target }
+    }
+
+    /// The value that could not be converted.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Take back the value that could not be converted.
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+}
+
+impl Display for TryFromValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot convert {} to {}", self.value.tag(), self.target)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryFromValueError {}
+
+// Integers convert from any integer tag, as long as the number fits. This
+// matches deserializing a value into an integer.
+macro_rules! try_from_int {
+    ($($type:ty),+) => {
+        $(
+            impl TryFrom<Value> for $type {
+                type Error = TryFromValueError;
+
+                fn try_from(value: Value) -> Result<Self, Self::Error> {
+                    let n = match value {
+                        Value::Byte(n) => <$type>::try_from(n).ok(),
+                        Value::Short(n) => <$type>::try_from(n).ok(),
+                        Value::Int(n) => <$type>::try_from(n).ok(),
+                        Value::Long(n) => <$type>::try_from(n).ok(),
+                        _ => None,
+                    };
+                    n.ok_or_else(|| TryFromValueError::new(value, stringify!($type)))
+                }
+            }
+        )+
+    };
+}
+try_from_int!(i8, i16, i32, i64);
+
+// Anything else only converts from its own variant.
+macro_rules! try_from_variant {
+    ($($type:ty => $($variant:ident)|+),+ $(,)?) => {
+        $(
+            impl TryFrom<Value> for $type {
+                type Error = TryFromValueError;
+
+                fn try_from(value: Value) -> Result<Self, Self::Error> {
+                    match value {
+                        $(Value::$variant(v) => Ok(v.into()),)+
+                        _ => Err(TryFromValueError::new(value, stringify!($type))),
+                    }
+                }
+            }
+        )+
+    };
+}
+try_from_variant! {
+    f32 => Float,
+    f64 => Float | Double,
+    String => String,
+    ByteArray => ByteArray,
+    IntArray => IntArray,
+    LongArray => LongArray,
+    Map<String, Value> => Compound,
+}
+
+/// A byte of 0 or 1, which is how NBT stores booleans.
+impl TryFrom<Value> for bool {
+    type Error = TryFromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Byte(0) => Ok(false),
+            Value::Byte(1) => Ok(true),
+            _ => Err(TryFromValueError::new(value, "bool")),
+        }
+    }
+}
+
+/// The elements of a list. An empty list of any element tag converts to an
+/// empty vec.
+impl TryFrom<Value> for Vec<Value> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::List(v) => Ok(v),
+            Value::EmptyList(_) => Ok(vec![]),
+            _ => Err(TryFromValueError::new(value, "Vec<Value>")),
+        }
+    }
+}
+
+impl Value {
+    /// The tag the value is written with.
+    pub(crate) fn tag(&self) -> Tag {
+        match self {
+            Value::Byte(_) => Tag::Byte,
+            Value::Short(_) => Tag::Short,
+            Value::Int(_) => Tag::Int,
+            Value::Long(_) => Tag::Long,
+            Value::Float(_) => Tag::Float,
+            Value::Double(_) => Tag::Double,
+            Value::String(_) => Tag::String,
+            Value::ByteArray(_) => Tag::ByteArray,
+            Value::IntArray(_) => Tag::IntArray,
+            Value::LongArray(_) => Tag::LongArray,
+            Value::List(_) | Value::EmptyList(_) => Tag::List,
+            Value::Compound(_) => Tag::Compound,
+        }
+    }
+}
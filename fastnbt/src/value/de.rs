@@ -1,6 +1,12 @@
-use std::{borrow::Cow, collections::HashMap};
+use alloc::{
+    borrow::{Cow, ToOwned},
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 
-use byteorder::BigEndian;
+use byteorder::{BigEndian, NativeEndian};
 use serde::{
     de::{
         value::{BorrowedBytesDeserializer, BorrowedStrDeserializer},
@@ -10,9 +16,9 @@ use serde::{
     forward_to_deserialize_any, serde_if_integer128, Deserialize, Deserializer,
 };
 
-use crate::{error::Error, ByteArray, IntArray, LongArray, Value};
+use crate::{error::Error, ByteArray, IntArray, LongArray, Map, Tag, Value};
 
-use super::{INT_ARRAY_VALUE_TOKEN, LONG_ARRAY_VALUE_TOKEN};
+use super::{EMPTY_LIST_TOKEN, INT_ARRAY_VALUE_TOKEN, LONG_ARRAY_VALUE_TOKEN, VALUE_TOKEN};
 
 impl<'de> Deserialize<'de> for Value {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -23,7 +29,7 @@ impl<'de> Deserialize<'de> for Value {
         impl<'de> serde::de::Visitor<'de> for ValueVisitor {
             type Value = Value;
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("valid NBT")
             }
 
@@ -102,9 +108,9 @@ impl<'de> Deserialize<'de> for Value {
             {
                 match map.next_key_seed(KeyClassifier)? {
                     Some(KeyClass::Compound(first_key)) => {
-                        let mut compound = HashMap::new();
+                        let mut compound = Map::default();
 
-                        compound.insert(first_key, map.next_value()?);
+                        compound.insert(first_key.into_owned(), map.next_value()?);
                         while let Some((key, value)) = map.next_entry()? {
                             compound.insert(key, value);
                         }
@@ -117,39 +123,84 @@ impl<'de> Deserialize<'de> for Value {
                     }
                     Some(KeyClass::IntArray) => {
                         let data = map.next_value::<&[u8]>()?;
-                        IntArray::from_bytes::<BigEndian>(data)
-                            .map(Value::IntArray)
-                            .map_err(|_| serde::de::Error::custom("could not read int array"))
+                        Ok(Value::IntArray(IntArray::from_bytes::<BigEndian>(data)))
                     }
                     Some(KeyClass::LongArray) => {
                         let data = map.next_value::<&[u8]>()?;
-                        LongArray::from_bytes::<BigEndian>(data)
-                            .map(Value::LongArray)
-                            .map_err(|_| serde::de::Error::custom("could not read long array"))
+                        Ok(Value::LongArray(LongArray::from_bytes::<BigEndian>(data)))
+                    }
+                    Some(KeyClass::IntArrayValue) => {
+                        let data = map.next_value::<&[u8]>()?;
+                        Ok(Value::IntArray(IntArray::from_bytes::<NativeEndian>(data)))
+                    }
+                    Some(KeyClass::LongArrayValue) => {
+                        let data = map.next_value::<&[u8]>()?;
+                        Ok(Value::LongArray(LongArray::from_bytes::<NativeEndian>(
+                            data,
+                        )))
+                    }
+                    Some(KeyClass::EmptyList) => {
+                        let tag = map.next_value::<u8>()?;
+                        match Tag::try_from(tag) {
+                            Ok(Tag::End) => Ok(Value::List(vec![])),
+                            Ok(tag) => Ok(Value::EmptyList(tag)),
+                            Err(_) => Err(serde::de::Error::custom("invalid list element tag")),
+                        }
                     }
                     // No keys just means an empty compound.
                     None => Ok(Value::Compound(Default::default())),
                 }
             }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_any(self)
+            }
         }
 
-        deserializer.deserialize_any(ValueVisitor)
+        // Deserializers that don't know about the token just visit the newtype,
+        // which is the same as deserialize_any.
+        deserializer.deserialize_newtype_struct(VALUE_TOKEN, ValueVisitor)
     }
 }
 
-struct KeyClassifier;
+/// Tells the first key of a map apart from the keys that stand for the NBT
+/// types serde has no equivalent of. Shared with
+/// [`BorrowedValue`][super::BorrowedValue].
+pub(super) struct KeyClassifier;
 
-enum KeyClass {
-    Compound(String),
+pub(super) enum KeyClass<'de> {
+    Compound(Cow<'de, str>),
     ByteArray,
     IntArray,
     LongArray,
+    /// An int array from a `Value`, in native byte order.
+    IntArrayValue,
+    /// A long array from a `Value`, in native byte order.
+    LongArrayValue,
+    EmptyList,
+}
+
+impl<'de> KeyClass<'de> {
+    fn classify(s: Cow<'de, str>) -> Self {
+        match s.as_ref() {
+            crate::BYTE_ARRAY_TOKEN => KeyClass::ByteArray,
+            crate::INT_ARRAY_TOKEN => KeyClass::IntArray,
+            crate::LONG_ARRAY_TOKEN => KeyClass::LongArray,
+            INT_ARRAY_VALUE_TOKEN => KeyClass::IntArrayValue,
+            LONG_ARRAY_VALUE_TOKEN => KeyClass::LongArrayValue,
+            EMPTY_LIST_TOKEN => KeyClass::EmptyList,
+            _ => KeyClass::Compound(s),
+        }
+    }
 }
 
 impl<'de> DeserializeSeed<'de> for KeyClassifier {
-    type Value = KeyClass;
+    type Value = KeyClass<'de>;
 
-    fn deserialize<D>(self, deserializer: D) -> Result<KeyClass, D::Error>
+    fn deserialize<D>(self, deserializer: D) -> Result<KeyClass<'de>, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
@@ -158,34 +209,31 @@ impl<'de> DeserializeSeed<'de> for KeyClassifier {
 }
 
 impl<'de> Visitor<'de> for KeyClassifier {
-    type Value = KeyClass;
+    type Value = KeyClass<'de>;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("an nbt field string")
     }
 
+    fn visit_borrowed_str<E>(self, s: &'de str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(KeyClass::classify(Cow::Borrowed(s)))
+    }
+
     fn visit_string<E>(self, s: String) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
     {
-        match s.as_str() {
-            crate::BYTE_ARRAY_TOKEN => Ok(KeyClass::ByteArray),
-            crate::INT_ARRAY_TOKEN => Ok(KeyClass::IntArray),
-            crate::LONG_ARRAY_TOKEN => Ok(KeyClass::LongArray),
-            _ => Ok(KeyClass::Compound(s)),
-        }
+        Ok(KeyClass::classify(Cow::Owned(s)))
     }
 
     fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
     {
-        match s {
-            crate::BYTE_ARRAY_TOKEN => Ok(KeyClass::ByteArray),
-            crate::INT_ARRAY_TOKEN => Ok(KeyClass::IntArray),
-            crate::LONG_ARRAY_TOKEN => Ok(KeyClass::LongArray),
-            _ => Ok(KeyClass::Compound(s.to_string())),
-        }
+        Ok(KeyClass::classify(Cow::Owned(s.to_owned())))
     }
 }
 
@@ -236,6 +284,9 @@ macro_rules! deserialize_number {
     };
 }
 
+// Used to visit a Value::EmptyList as a sequence.
+static EMPTY_LIST: Vec<Value> = Vec::new();
+
 fn visit_list<'de, V>(list: &'de Vec<Value>, visitor: V) -> Result<V::Value, Error>
 where
     V: Visitor<'de>,
@@ -254,10 +305,7 @@ where
     }
 }
 
-fn visit_compound<'de, V>(
-    compound: &'de HashMap<String, Value>,
-    visitor: V,
-) -> Result<V::Value, Error>
+fn visit_compound<'de, V>(compound: &'de Map<String, Value>, visitor: V) -> Result<V::Value, Error>
 where
     V: Visitor<'de>,
 {
@@ -275,6 +323,16 @@ where
     }
 }
 
+/// Integers give the index of a unit variant, like they do when deserializing
+/// from bytes.
+fn visit_variant_index<'de, V>(visitor: V, tag: Tag, value: i64) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let index = crate::de::variant_index(tag, value)?;
+    visitor.visit_enum(index.into_deserializer())
+}
+
 fn get_i128_value<'de>(de: &'de Value) -> Result<i128, Error> {
     match de {
         Value::IntArray(v) => {
@@ -314,19 +372,15 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
             Value::Float(val) => visitor.visit_f32(val),
             Value::Double(val) => visitor.visit_f64(val),
             Value::String(ref val) => visitor.visit_borrowed_str(val),
-            Value::ByteArray(_) => visitor.visit_map(ArrayAccess {
-                token: crate::BYTE_ARRAY_TOKEN,
-                value: self,
-            }),
-            Value::IntArray(_) => visitor.visit_map(ArrayAccess {
-                token: crate::INT_ARRAY_TOKEN,
-                value: self,
-            }),
-            Value::LongArray(_) => visitor.visit_map(ArrayAccess {
-                token: crate::LONG_ARRAY_TOKEN,
-                value: self,
-            }),
+            Value::ByteArray(_) => {
+                visitor.visit_map(ArrayAccess::new(crate::BYTE_ARRAY_TOKEN, self))
+            }
+            Value::IntArray(_) => visitor.visit_map(ArrayAccess::new(INT_ARRAY_VALUE_TOKEN, self)),
+            Value::LongArray(_) => {
+                visitor.visit_map(ArrayAccess::new(LONG_ARRAY_VALUE_TOKEN, self))
+            }
             Value::List(ref val) => visit_list(val, visitor),
+            Value::EmptyList(_) => visit_list(&EMPTY_LIST, visitor),
             Value::Compound(ref val) => visit_compound(val, visitor),
         }
     }
@@ -398,10 +452,14 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
                 (variant, Some(value))
             }
             Value::String(variant) => (variant, None),
+            Value::Byte(v) => return visit_variant_index(visitor, Tag::Byte, *v as i64),
+            Value::Short(v) => return visit_variant_index(visitor, Tag::Short, *v as i64),
+            Value::Int(v) => return visit_variant_index(visitor, Tag::Int, *v as i64),
+            Value::Long(v) => return visit_variant_index(visitor, Tag::Long, *v),
             other => {
                 return Err(serde::de::Error::invalid_type(
                     other.unexpected(),
-                    &"string or map",
+                    &"string, integer or map",
                 ));
             }
         };
@@ -419,18 +477,19 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
         V: Visitor<'de>,
     {
         match name {
-            crate::BYTE_ARRAY_TOKEN => visitor.visit_map(ArrayAccess {
-                token: name,
-                value: self,
-            }),
-            crate::INT_ARRAY_TOKEN => visitor.visit_map(ArrayAccess {
-                token: INT_ARRAY_VALUE_TOKEN,
-                value: self,
-            }),
-            crate::LONG_ARRAY_TOKEN => visitor.visit_map(ArrayAccess {
-                token: LONG_ARRAY_VALUE_TOKEN,
-                value: self,
-            }),
+            crate::BYTE_ARRAY_TOKEN => visitor.visit_map(ArrayAccess::new(name, self)),
+            crate::INT_ARRAY_TOKEN => {
+                visitor.visit_map(ArrayAccess::new(INT_ARRAY_VALUE_TOKEN, self))
+            }
+            crate::LONG_ARRAY_TOKEN => {
+                visitor.visit_map(ArrayAccess::new(LONG_ARRAY_VALUE_TOKEN, self))
+            }
+            VALUE_TOKEN => match self {
+                Value::EmptyList(tag) => visitor.visit_map(serde::de::value::MapDeserializer::new(
+                    core::iter::once((EMPTY_LIST_TOKEN, *tag as u8)),
+                )),
+                _ => visitor.visit_newtype_struct(self),
+            },
             _ => return visitor.visit_newtype_struct(self),
         }
     }
@@ -502,6 +561,7 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
         match self {
             Value::String(v) => visitor.visit_borrowed_str(v),
             Value::List(v) => visit_list(v, visitor),
+            Value::EmptyList(_) => visit_list(&EMPTY_LIST, visitor),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -512,6 +572,7 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     {
         match self {
             Value::List(v) => visit_list(v, visitor),
+            Value::EmptyList(_) => visit_list(&EMPTY_LIST, visitor),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -556,6 +617,7 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     {
         match self {
             Value::List(v) => visit_list(v, visitor),
+            Value::EmptyList(_) => visit_list(&EMPTY_LIST, visitor),
             Value::Compound(v) => visit_compound(v, visitor),
             _ => Err(self.invalid_type(&visitor)),
         }
@@ -594,10 +656,23 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     }
 }
 
+/// An array as a map with a single entry, from a token naming the array type
+/// to the array's bytes. Int and long arrays are given in native byte order,
+/// with the `*_VALUE_TOKEN`s to say so.
 pub struct ArrayAccess<'de> {
-    pub token: &'static str,
-    pub value: &'de Value,
+    token: Option<&'static str>,
+    value: &'de Value,
+}
+
+impl<'de> ArrayAccess<'de> {
+    fn new(token: &'static str, value: &'de Value) -> Self {
+        Self {
+            token: Some(token),
+            value,
+        }
+    }
 }
+
 impl<'de> MapAccess<'de> for ArrayAccess<'de> {
     type Error = Error;
 
@@ -605,8 +680,12 @@ impl<'de> MapAccess<'de> for ArrayAccess<'de> {
     where
         K: DeserializeSeed<'de>,
     {
-        seed.deserialize(BorrowedStrDeserializer::new(self.token))
-            .map(Some)
+        match self.token.take() {
+            Some(token) => seed
+                .deserialize(BorrowedStrDeserializer::new(token))
+                .map(Some),
+            None => Ok(None),
+        }
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
@@ -690,6 +769,7 @@ impl<'de> VariantAccess<'de> for VariantDeserializer<'de> {
                     visit_list(v, visitor)
                 }
             }
+            Some(Value::EmptyList(_)) => visitor.visit_unit(),
             Some(other) => Err(serde::de::Error::invalid_type(
                 other.unexpected(),
                 &"tuple variant",
@@ -724,7 +804,7 @@ impl<'de> VariantAccess<'de> for VariantDeserializer<'de> {
 }
 
 struct SeqDeserializer<'de> {
-    iter: std::slice::Iter<'de, Value>,
+    iter: core::slice::Iter<'de, Value>,
 }
 
 impl<'de> SeqDeserializer<'de> {
@@ -755,12 +835,12 @@ impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
 }
 
 struct MapDeserializer<'de> {
-    iter: <&'de HashMap<String, Value> as IntoIterator>::IntoIter,
+    iter: <&'de Map<String, Value> as IntoIterator>::IntoIter,
     value: Option<&'de Value>,
 }
 
 impl<'de> MapDeserializer<'de> {
-    fn new(map: &'de HashMap<String, Value>) -> Self {
+    fn new(map: &'de Map<String, Value>) -> Self {
         MapDeserializer {
             iter: map.into_iter(),
             value: None,
@@ -899,7 +979,7 @@ impl Value {
     }
 
     #[cold]
-    fn unexpected(&self) -> Unexpected {
+    fn unexpected(&self) -> Unexpected<'_> {
         match self {
             Value::Byte(v) => Unexpected::Signed(*v as i64),
             Value::Short(v) => Unexpected::Signed(*v as i64),
@@ -911,7 +991,7 @@ impl Value {
             Value::ByteArray(_) => Unexpected::Seq,
             Value::IntArray(_) => Unexpected::Seq,
             Value::LongArray(_) => Unexpected::Seq,
-            Value::List(_) => Unexpected::Seq,
+            Value::List(_) | Value::EmptyList(_) => Unexpected::Seq,
             Value::Compound(_) => Unexpected::Map,
         }
     }
@@ -0,0 +1,211 @@
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+use core::fmt::{self, Display};
+
+use super::Value;
+
+/// The differences between two values, from [`Value::diff`].
+///
+/// Displaying it gives one line per difference, with the path to where the
+/// values differ:
+///
+/// ```
+/// # use fastnbt::nbt;
+/// let old = nbt!({ "Palette": [{ "Name": "minecraft:stone" }] });
+/// let new = nbt!({ "Palette": [{ "Name": "minecraft:dirt" }] });
+///
+/// assert_eq!(
+///     old.diff(&new).to_string(),
+///     "Palette[0].Name: changed from String(\"minecraft:stone\") to String(\"minecraft:dirt\")\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValueDiff {
+    differences: Vec<Difference>,
+}
+
+/// A single place where two values differ. See [`ValueDiff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// Where the values differ. Empty if the values differ as a whole.
+    pub path: Vec<PathSegment>,
+    pub kind: DifferenceKind,
+}
+
+/// A step into a compound or list, making up the path of a [`Difference`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// A key of a compound.
+    Key(String),
+    /// An index into a list or array.
+    Index(usize),
+}
+
+/// How the values at the path of a [`Difference`] differ.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DifferenceKind {
+    /// Only the second value has something here.
+    Added(Value),
+    /// Only the first value has something here.
+    Removed(Value),
+    /// The values here are different.
+    Changed { from: Value, to: Value },
+}
+
+impl ValueDiff {
+    /// Whether the values are the same.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// The number of differences.
+    pub fn len(&self) -> usize {
+        self.differences.len()
+    }
+
+    /// The differences, in the order they are displayed.
+    pub fn differences(&self) -> &[Difference] {
+        &self.differences
+    }
+}
+
+impl IntoIterator for ValueDiff {
+    type Item = Difference;
+    type IntoIter = alloc::vec::IntoIter<Difference>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.differences.into_iter()
+    }
+}
+
+impl Value {
+    /// Find where this value and `other` differ, eg to see what an edit to a
+    /// chunk changed, or to say where two chunks differ in a failing test.
+    ///
+    /// Compounds are compared key by key, in key order. Lists and arrays are
+    /// compared element by element, with any extra elements at the end added
+    /// or removed. Values with different tags, including arrays of different
+    /// lengths and an empty list compared to one with elements, are changed
+    /// as a whole.
+    ///
+    /// Floats and doubles are compared exactly by their bits, as they are
+    /// stored, so `0.0` and `-0.0` differ but a NaN is the same as itself.
+    /// This makes an empty diff slightly different to `==`.
+    pub fn diff(&self, other: &Value) -> ValueDiff {
+        let mut diff = ValueDiff::default();
+        diff_into(self, other, &mut vec![], &mut diff.differences);
+        diff
+    }
+}
+
+fn diff_into(a: &Value, b: &Value, path: &mut Vec<PathSegment>, out: &mut Vec<Difference>) {
+    match (a, b) {
+        (Value::Compound(a), Value::Compound(b)) => {
+            let mut keys: Vec<_> = a
+                .keys()
+                .chain(b.keys().filter(|k| !a.contains_key(*k)))
+                .collect();
+            keys.sort_unstable();
+
+            for key in keys {
+                path.push(PathSegment::Key(key.clone()));
+                match (a.get(key), b.get(key)) {
+                    (Some(a), Some(b)) => diff_into(a, b, path, out),
+                    (Some(a), None) => push(path, DifferenceKind::Removed(a.clone()), out),
+                    (None, Some(b)) => push(path, DifferenceKind::Added(b.clone()), out),
+                    (None, None) => unreachable!(),
+                }
+                path.pop();
+            }
+        }
+        (Value::List(a), Value::List(b)) if !a.is_empty() && !b.is_empty() => {
+            diff_seq(a, b, Cow::Borrowed, path, out)
+        }
+        (Value::ByteArray(a), Value::ByteArray(b)) if a.len() == b.len() => {
+            diff_seq(a, b, |v| Cow::Owned(Value::Byte(*v)), path, out)
+        }
+        (Value::IntArray(a), Value::IntArray(b)) if a.len() == b.len() => {
+            diff_seq(a, b, |v| Cow::Owned(Value::Int(*v)), path, out)
+        }
+        (Value::LongArray(a), Value::LongArray(b)) if a.len() == b.len() => {
+            diff_seq(a, b, |v| Cow::Owned(Value::Long(*v)), path, out)
+        }
+        (Value::Float(x), Value::Float(y)) if x.to_bits() == y.to_bits() => {}
+        (Value::Double(x), Value::Double(y)) if x.to_bits() == y.to_bits() => {}
+        (Value::Float(_), Value::Float(_)) | (Value::Double(_), Value::Double(_)) => {
+            push(path, changed(a, b), out)
+        }
+        _ if a == b => {}
+        _ => push(path, changed(a, b), out),
+    }
+}
+
+fn diff_seq<'a, T>(
+    a: &'a [T],
+    b: &'a [T],
+    as_value: impl Fn(&'a T) -> Cow<'a, Value>,
+    path: &mut Vec<PathSegment>,
+    out: &mut Vec<Difference>,
+) {
+    for i in 0..a.len().max(b.len()) {
+        path.push(PathSegment::Index(i));
+        match (a.get(i), b.get(i)) {
+            (Some(a), Some(b)) => diff_into(&as_value(a), &as_value(b), path, out),
+            (Some(a), None) => push(path, DifferenceKind::Removed(as_value(a).into_owned()), out),
+            (None, Some(b)) => push(path, DifferenceKind::Added(as_value(b).into_owned()), out),
+            (None, None) => unreachable!(),
+        }
+        path.pop();
+    }
+}
+
+fn changed(from: &Value, to: &Value) -> DifferenceKind {
+    DifferenceKind::Changed {
+        from: from.clone(),
+        to: to.clone(),
+    }
+}
+
+fn push(path: &[PathSegment], kind: DifferenceKind, out: &mut Vec<Difference>) {
+    out.push(Difference {
+        path: path.to_vec(),
+        kind,
+    })
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Key(key) => f.write_str(key),
+            PathSegment::Index(i) => write!(f, "[{i}]"),
+        }
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str("(root)")?;
+        }
+        for (i, segment) in self.path.iter().enumerate() {
+            if i > 0 && matches!(segment, PathSegment::Key(_)) {
+                f.write_str(".")?;
+            }
+            write!(f, "{segment}")?;
+        }
+
+        match &self.kind {
+            DifferenceKind::Added(v) => write!(f, ": added {v:?}"),
+            DifferenceKind::Removed(v) => write!(f, ": removed {v:?}"),
+            DifferenceKind::Changed { from, to } => write!(f, ": changed from {from:?} to {to:?}"),
+        }
+    }
+}
+
+impl Display for ValueDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for difference in &self.differences {
+            writeln!(f, "{difference}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use crate::Tag;
+
+use super::Value;
+
+/// How [`Value::merge`] combines two values. The default merges compounds
+/// key by key and replaces lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeStrategy {
+    pub compounds: CompoundMerge,
+    pub lists: ListMerge,
+}
+
+impl MergeStrategy {
+    /// Merge compounds and replace lists, the default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how compounds are merged.
+    pub fn compounds(mut self, compounds: CompoundMerge) -> Self {
+        self.compounds = compounds;
+        self
+    }
+
+    /// Set how lists are merged.
+    pub fn lists(mut self, lists: ListMerge) -> Self {
+        self.lists = lists;
+        self
+    }
+}
+
+/// How [`Value::merge`] combines two compounds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompoundMerge {
+    /// Keep the keys only in the first compound, add the keys only in the
+    /// second, and merge the values of keys in both.
+    #[default]
+    Recurse,
+    /// Replace the first compound with the second.
+    Replace,
+}
+
+/// How [`Value::merge`] combines two lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListMerge {
+    /// Replace the first list with the second.
+    #[default]
+    Replace,
+    /// Add the elements of the second list to the end of the first. Lists
+    /// whose elements have different tags cannot be combined, so the second
+    /// list replaces the first instead.
+    Append,
+    /// Merge the elements at the same index, if both lists are the same
+    /// length, so compounds in the first list keep the keys that those in
+    /// the second lack. Lists of different lengths are replaced.
+    ByIndex,
+}
+
+impl Value {
+    /// Merge `other` into this value, eg to apply a patch to a chunk.
+    ///
+    /// Compounds and lists are combined according to `strategy`. Anything
+    /// else, including two values with different tags, is replaced by
+    /// `other`. Byte, int and long arrays are always replaced rather than
+    /// appended, as they usually hold packed data such as block states.
+    ///
+    /// ```
+    /// # use fastnbt::{nbt, value::MergeStrategy};
+    /// let mut chunk = nbt!({
+    ///     "Status": "minecraft:features",
+    ///     "Level": { "xPos": 1, "zPos": 2 },
+    /// });
+    ///
+    /// chunk.merge(
+    ///     &nbt!({ "Status": "minecraft:full", "Level": { "zPos": 3 } }),
+    ///     MergeStrategy::default(),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     chunk,
+    ///     nbt!({
+    ///         "Status": "minecraft:full",
+    ///         "Level": { "xPos": 1, "zPos": 3 },
+    ///     })
+    /// );
+    /// ```
+    pub fn merge(&mut self, other: &Value, strategy: MergeStrategy) {
+        match (self, other) {
+            (Value::Compound(this), Value::Compound(other))
+                if strategy.compounds == CompoundMerge::Recurse =>
+            {
+                for (key, value) in other {
+                    match this.get_mut(key) {
+                        Some(existing) => existing.merge(value, strategy),
+                        None => {
+                            this.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+            (Value::List(this), Value::List(other))
+                if strategy.lists == ListMerge::ByIndex && this.len() == other.len() =>
+            {
+                for (this, other) in this.iter_mut().zip(other) {
+                    this.merge(other, strategy);
+                }
+            }
+            (this, other)
+                if strategy.lists == ListMerge::Append && is_list(this) && is_list(other) =>
+            {
+                append(this, other)
+            }
+            (this, other) => *this = other.clone(),
+        }
+    }
+}
+
+fn is_list(value: &Value) -> bool {
+    matches!(value, Value::List(_) | Value::EmptyList(_))
+}
+
+/// The tag of a list's elements, if it is known.
+fn element_tag(list: &Value) -> Option<Tag> {
+    match list {
+        Value::List(v) => v.first().map(Value::tag),
+        Value::EmptyList(Tag::End) => None,
+        Value::EmptyList(t) => Some(*t),
+        _ => None,
+    }
+}
+
+fn append(this: &mut Value, other: &Value) {
+    let this_tag = element_tag(this);
+    if this_tag.is_some_and(|t| element_tag(other).is_some_and(|o| o != t)) {
+        *this = other.clone();
+        return;
+    }
+
+    match (this, other) {
+        (Value::List(this), Value::List(other)) => this.extend(other.iter().cloned()),
+        // Appending to an empty list, or appending nothing to a list that
+        // does not know its element tag yet.
+        (this, Value::List(v)) if !v.is_empty() => *this = other.clone(),
+        (this, Value::EmptyList(_)) if this_tag.is_none() => *this = other.clone(),
+        _ => {}
+    }
+}
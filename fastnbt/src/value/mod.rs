@@ -1,18 +1,33 @@
 mod array_serializer;
+mod borrowed;
+mod cmp;
+mod convert;
 mod de;
+mod diff;
+mod merge;
+mod pointer;
 mod ser;
 
-use std::collections::HashMap;
+use alloc::{borrow::ToOwned, string::String, vec, vec::Vec};
 
 use serde::{serde_if_integer128, Deserialize, Serialize};
 
-use crate::{error::Error, ByteArray, IntArray, LongArray};
+use crate::{error::Error, ByteArray, IntArray, LongArray, Map, Tag};
 
+pub use self::borrowed::BorrowedValue;
+pub use self::convert::TryFromValueError;
+pub use self::diff::{Difference, DifferenceKind, PathSegment, ValueDiff};
+pub use self::merge::{CompoundMerge, ListMerge, MergeStrategy};
 pub use self::ser::Serializer;
 
 pub(crate) const INT_ARRAY_VALUE_TOKEN: &str = "__fastnbt_int_array_from_value";
 pub(crate) const LONG_ARRAY_VALUE_TOKEN: &str = "__fastnbt_long_array_from_value";
 
+// Lets the deserializer know a Value is being deserialized, so that it can
+// tell it the element tag of empty lists via a map with EMPTY_LIST_TOKEN.
+pub(crate) const VALUE_TOKEN: &str = "__fastnbt_value";
+pub(crate) const EMPTY_LIST_TOKEN: &str = "__fastnbt_empty_list";
+
 /// Value is a complete NBT value. It owns its data. Compounds and Lists are
 /// resursively deserialized. This type takes care to preserve all the
 /// information from the original NBT, with the exception of the name of the
@@ -34,7 +49,11 @@ pub(crate) const LONG_ARRAY_VALUE_TOKEN: &str = "__fastnbt_long_array_from_value
 /// #   Ok(())
 /// # }
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Values can be sorted, hashed and used as keys, see
+/// [`total_cmp`][Value::total_cmp] for how they compare. Floats compare by
+/// their bits rather than numerically.
+#[derive(Debug, Clone)]
 pub enum Value {
     Byte(i8),
     Short(i16),
@@ -47,7 +66,19 @@ pub enum Value {
     IntArray(IntArray),
     LongArray(LongArray),
     List(Vec<Value>),
-    Compound(HashMap<String, Value>),
+    /// An empty list whose elements would have the given tag. NBT records
+    /// the element tag of a list even if it is empty, and this keeps it so
+    /// that the list can be written back out exactly.
+    ///
+    /// Empty lists with an element tag of [`Tag::End`] are read as
+    /// `List(vec![])`, and `EmptyList(Tag::End)` is written the same way.
+    /// Empty lists with different element tags are not equal, and an
+    /// `EmptyList` is not equal to `List(vec![])`.
+    ///
+    /// Only NBT keeps the element tag. Serializing to another format, eg
+    /// JSON, writes an empty sequence.
+    EmptyList(Tag),
+    Compound(Map<String, Value>),
 }
 
 #[cfg(feature = "arbitrary1")]
@@ -196,6 +227,21 @@ from!(ByteArray, ByteArray);
 from!(IntArray, IntArray);
 from!(LongArray, LongArray);
 
+/// A list of the values. The values should all have the same tag.
+impl From<Vec<Value>> for Value {
+    fn from(val: Vec<Value>) -> Self {
+        Self::List(val)
+    }
+}
+
+/// A compound of the values. This is a `HashMap<String, Value>` unless the
+/// `fast-hash` feature is enabled, see [`Map`].
+impl From<Map<String, Value>> for Value {
+    fn from(val: Map<String, Value>) -> Self {
+        Self::Compound(val)
+    }
+}
+
 impl From<bool> for Value {
     fn from(val: bool) -> Self {
         Self::Byte(if val { 1 } else { 0 })
@@ -254,6 +300,11 @@ fn eq_str(value: &Value, other: &str) -> bool {
     value.as_str().map_or(false, |i| i == other)
 }
 
+// NBT has no boolean type, booleans are stored as a byte of 0 or 1.
+fn eq_bool(value: &Value, other: bool) -> bool {
+    matches!(value, Value::Byte(b) if *b == other as i8)
+}
+
 impl PartialEq<str> for Value {
     fn eq(&self, other: &str) -> bool {
         eq_str(self, other)
@@ -324,6 +375,7 @@ partialeq_numeric! {
     eq_i64[i8 i16 i32 i64 isize]
     eq_u64[u8 u16 u32 u64 usize]
     eq_f64[f32 f64]
+    eq_bool[bool]
 }
 
 macro_rules! from_128bit {
@@ -0,0 +1,105 @@
+use alloc::borrow::Cow;
+
+use super::Value;
+
+impl Value {
+    /// Look up a value by a [JSON Pointer](https://tools.ietf.org/html/rfc6901),
+    /// the same as serde_json's `Value::pointer`.
+    ///
+    /// The pointer is a list of segments each starting with `/`, which are
+    /// keys of compounds or indices into lists. `~1` in a key stands for `/`
+    /// and `~0` for `~`. An empty pointer refers to the value itself.
+    ///
+    /// The elements of byte, int and long arrays are not stored as values, so
+    /// cannot be borrowed. Pointers into arrays give `None`, use
+    /// [`pointer_cow`][Self::pointer_cow] to look those up.
+    ///
+    /// ```
+    /// # use fastnbt::nbt;
+    /// let chunk = nbt!({
+    ///     "Level": {
+    ///         "Sections": [{ "Palette": [{ "Name": "minecraft:stone" }] }],
+    ///     },
+    /// });
+    ///
+    /// assert_eq!(
+    ///     chunk.pointer("/Level/Sections/0/Palette/0/Name").unwrap(),
+    ///     "minecraft:stone"
+    /// );
+    /// assert_eq!(chunk.pointer("/Level/Sections/1"), None);
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        segments(pointer)?.try_fold(self, |target, segment| match target {
+            Value::Compound(map) => map.get(segment.as_ref()),
+            Value::List(list) => list.get(parse_index(&segment)?),
+            _ => None,
+        })
+    }
+
+    /// Look up a value by a JSON Pointer, like [`pointer`][Self::pointer],
+    /// to change it.
+    ///
+    /// ```
+    /// # use fastnbt::nbt;
+    /// let mut v = nbt!({ "a/b": [1, 2] });
+    /// *v.pointer_mut("/a~1b/1").unwrap() = nbt!(3);
+    /// assert_eq!(v, nbt!({ "a/b": [1, 3] }));
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        segments(pointer)?.try_fold(self, |target, segment| match target {
+            Value::Compound(map) => map.get_mut(segment.as_ref()),
+            Value::List(list) => list.get_mut(parse_index(&segment)?),
+            _ => None,
+        })
+    }
+
+    /// Look up a value by a JSON Pointer, like [`pointer`][Self::pointer],
+    /// but also resolving an index into a byte, int or long array. Array
+    /// elements are given as a [`Value::Byte`], [`Value::Int`] or
+    /// [`Value::Long`], everything else is borrowed.
+    ///
+    /// ```
+    /// # use fastnbt::{nbt, LongArray, Value};
+    /// let v = nbt!({ "BlockStates": LongArray::new(vec![7, 8]) });
+    /// assert_eq!(*v.pointer_cow("/BlockStates/1").unwrap(), Value::Long(8));
+    /// ```
+    pub fn pointer_cow(&self, pointer: &str) -> Option<Cow<'_, Value>> {
+        let (parent, last) = match pointer.rfind('/') {
+            Some(i) => (&pointer[..i], &pointer[i + 1..]),
+            None => return self.pointer(pointer).map(Cow::Borrowed),
+        };
+
+        let element = match self.pointer(parent)? {
+            Value::ByteArray(a) => Value::Byte(*a.get(parse_index(last)?)?),
+            Value::IntArray(a) => Value::Int(*a.get(parse_index(last)?)?),
+            Value::LongArray(a) => Value::Long(*a.get(parse_index(last)?)?),
+            _ => return self.pointer(pointer).map(Cow::Borrowed),
+        };
+        Some(Cow::Owned(element))
+    }
+}
+
+/// The unescaped segments of a pointer, or `None` if it is not a valid
+/// pointer.
+fn segments(pointer: &str) -> Option<impl Iterator<Item = Cow<'_, str>>> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return None;
+    }
+
+    Some(pointer.split('/').skip(1).map(|segment| {
+        if segment.contains('~') {
+            Cow::Owned(segment.replace("~1", "/").replace("~0", "~"))
+        } else {
+            Cow::Borrowed(segment)
+        }
+    }))
+}
+
+/// Parse a list index. Like JSON Pointer, leading zeros and signs are not
+/// allowed.
+fn parse_index(segment: &str) -> Option<usize> {
+    if segment.starts_with('+') || (segment.starts_with('0') && segment.len() > 1) {
+        return None;
+    }
+    segment.parse().ok()
+}
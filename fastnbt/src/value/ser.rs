@@ -1,14 +1,22 @@
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 use core::result;
-use std::collections::HashMap;
 
-use serde::{ser::Impossible, serde_if_integer128, Serialize};
+use serde::{
+    ser::{Impossible, SerializeSeq},
+    serde_if_integer128, Serialize,
+};
 
 use crate::{
     error::{Error, Result},
-    IntArray, Tag, Value,
+    IntArray, Map, Tag, Value,
 };
 
-use super::array_serializer::ArraySerializer;
+use super::{array_serializer::ArraySerializer, EMPTY_LIST_TOKEN};
 
 impl Serialize for Value {
     fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
@@ -27,11 +35,25 @@ impl Serialize for Value {
             Value::IntArray(v) => v.serialize(serializer),
             Value::LongArray(v) => v.serialize(serializer),
             Value::List(v) => v.serialize(serializer),
+            Value::EmptyList(Tag::End) => serializer.serialize_seq(Some(0))?.end(),
+            Value::EmptyList(tag) if is_nbt_serializer::<S>() => {
+                serializer.serialize_newtype_variant("Value", 0, EMPTY_LIST_TOKEN, &(*tag as u8))
+            }
+            // Other formats have nowhere to keep the element tag.
+            Value::EmptyList(_) => serializer.serialize_seq(Some(0))?.end(),
             Value::Compound(v) => v.serialize(serializer),
         }
     }
 }
 
+/// Whether `S` is one of the serializers in this crate, which understand
+/// [`EMPTY_LIST_TOKEN`]. Other serializers would write the token out as data.
+fn is_nbt_serializer<S>() -> bool {
+    let name = core::any::type_name::<S>();
+    let name = name.strip_prefix("&mut ").unwrap_or(name);
+    name.starts_with("fastnbt::ser::") || name.starts_with("fastnbt::value::ser::")
+}
+
 //
 // Everything below is copied and modified from serde_json:
 // https://github.com/serde-rs/json/blob/52a9c050f5dcc0dc3de4825b131b8ff05219cc82/src/value/ser.rs
@@ -212,17 +234,22 @@ impl<'a> serde::Serializer for &'a mut Serializer {
     {
         match variant {
             crate::BYTE_ARRAY_TOKEN => value.serialize(ArraySerializer {
-                ser: self,
                 tag: Tag::ByteArray,
             }),
             crate::INT_ARRAY_TOKEN => value.serialize(ArraySerializer {
-                ser: self,
                 tag: Tag::IntArray,
             }),
             crate::LONG_ARRAY_TOKEN => value.serialize(ArraySerializer {
-                ser: self,
                 tag: Tag::LongArray,
             }),
+            EMPTY_LIST_TOKEN => match value.serialize(self)? {
+                Value::Byte(tag) => match Tag::try_from(tag as u8) {
+                    Ok(Tag::End) => Ok(Value::List(vec![])),
+                    Ok(tag) => Ok(Value::EmptyList(tag)),
+                    Err(_) => Err(Error::bespoke("invalid list element tag".to_string())),
+                },
+                _ => Err(Error::bespoke("expected list element tag".to_string())),
+            },
             _ => todo!("newtype variants that are not nbt arrays"),
         }
     }
@@ -268,7 +295,7 @@ impl<'a> serde::Serializer for &'a mut Serializer {
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
         Ok(SerializeMap {
-            map: HashMap::new(),
+            map: Map::default(),
             next_key: None,
         })
     }
@@ -286,13 +313,13 @@ impl<'a> serde::Serializer for &'a mut Serializer {
     ) -> Result<Self::SerializeStructVariant> {
         Ok(SerializeStructVariant {
             name: variant.into(),
-            map: HashMap::new(),
+            map: Map::default(),
         })
     }
 
     fn collect_str<T: ?Sized>(self, value: &T) -> Result<Value>
     where
-        T: std::fmt::Display,
+        T: core::fmt::Display,
     {
         Ok(Value::String(value.to_string()))
     }
@@ -320,13 +347,13 @@ pub struct SerializeTupleVariant {
 }
 
 pub struct SerializeMap {
-    map: HashMap<String, Value>,
+    map: Map<String, Value>,
     next_key: Option<String>,
 }
 
 pub struct SerializeStructVariant {
     name: String,
-    map: HashMap<String, Value>,
+    map: Map<String, Value>,
 }
 
 impl serde::ser::SerializeSeq for SerializeVec {
@@ -391,7 +418,7 @@ impl serde::ser::SerializeTupleVariant for SerializeTupleVariant {
     }
 
     fn end(self) -> Result<Value> {
-        let mut object = HashMap::new();
+        let mut object = Map::default();
 
         object.insert(self.name, Value::List(self.vec));
 
@@ -600,7 +627,7 @@ impl serde::Serializer for MapKeySerializer {
 
     fn collect_str<T: ?Sized>(self, value: &T) -> Result<String>
     where
-        T: std::fmt::Display,
+        T: core::fmt::Display,
     {
         Ok(value.to_string())
     }
@@ -635,7 +662,7 @@ impl serde::ser::SerializeStructVariant for SerializeStructVariant {
     }
 
     fn end(self) -> Result<Value> {
-        let mut object = HashMap::new();
+        let mut object = Map::default();
 
         object.insert(self.name, Value::Compound(self.map));
 
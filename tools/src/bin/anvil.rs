@@ -1,7 +1,10 @@
 use clap::{App, Arg, ArgMatches, SubCommand};
 use env_logger::Env;
 use fastanvil::RenderedPalette;
-use fastanvil::{render_region, CCoord, HeightMode, RCoord, RegionLoader, Rgba, TopShadeRenderer};
+use fastanvil::{
+    render_region, CCoord, DepthRamp, DimensionOpts, HeightMode, RCoord, RegionLoader, RenderMode,
+    Rgba, TopShadeRenderer, WaterDepthShading,
+};
 
 use fastanvil::RegionFileLoader;
 use flate2::read::GzDecoder;
@@ -115,11 +118,18 @@ fn render(args: &ArgMatches) -> Result<()> {
         true => HeightMode::Calculate,
         false => HeightMode::Trust,
     };
-
-    let subpath = match dim {
-        "end" => "DIM1/region",
-        "nether" => "DIM-1/region",
-        _ => "region",
+    let mode = match args.is_present("caves") {
+        true => RenderMode::Caves(DepthRamp::default()),
+        false => RenderMode::Surface,
+    };
+    let water_shading = args
+        .is_present("water-depth-shading")
+        .then(WaterDepthShading::default);
+
+    let (subpath, dimension) = match dim {
+        "end" => ("DIM1/region", DimensionOpts::default()),
+        "nether" => ("DIM-1/region", DimensionOpts::nether()),
+        _ => ("region", DimensionOpts::default()),
     };
 
     let loader = RegionFileLoader::new(world.join(subpath));
@@ -151,7 +161,10 @@ fn render(args: &ArgMatches) -> Result<()> {
             let (x, z) = coord;
 
             if x < x_range.end && x >= x_range.start && z < z_range.end && z >= z_range.start {
-                let drawer = TopShadeRenderer::new(&pal, height_mode);
+                let drawer = TopShadeRenderer::new(&pal, height_mode)
+                    .with_dimension(dimension)
+                    .with_mode(mode)
+                    .with_water_depth_shading(water_shading);
                 let map = render_region(x, z, &loader, drawer);
                 info!("processed r.{}.{}.mca", x.0, z.0);
                 Some(map)
@@ -202,11 +215,18 @@ fn tiles(args: &ArgMatches) -> Result<()> {
         true => HeightMode::Calculate,
         false => HeightMode::Trust,
     };
-
-    let subpath = match dim {
-        "end" => "DIM1/region",
-        "nether" => "DIM-1/region",
-        _ => "region",
+    let mode = match args.is_present("caves") {
+        true => RenderMode::Caves(DepthRamp::default()),
+        false => RenderMode::Surface,
+    };
+    let water_shading = args
+        .is_present("water-depth-shading")
+        .then(WaterDepthShading::default);
+
+    let (subpath, dimension) = match dim {
+        "end" => ("DIM1/region", DimensionOpts::default()),
+        "nether" => ("DIM-1/region", DimensionOpts::nether()),
+        _ => ("region", DimensionOpts::default()),
     };
 
     // don't care if dir already exists.
@@ -241,7 +261,10 @@ fn tiles(args: &ArgMatches) -> Result<()> {
             let (x, z) = coord;
 
             if x < x_range.end && x >= x_range.start && z < z_range.end && z >= z_range.start {
-                let drawer = TopShadeRenderer::new(&pal, height_mode);
+                let drawer = TopShadeRenderer::new(&pal, height_mode)
+                    .with_dimension(dimension)
+                    .with_mode(mode)
+                    .with_water_depth_shading(water_shading);
                 let map = render_region(x, z, &loader, drawer);
                 info!("processed r.{}.{}.mca", x.0, z.0);
                 Some(map)
@@ -325,6 +348,18 @@ fn main() -> Result<()> {
                         .long("calculate-heights")
                         .takes_value(false)
                         .required(false),
+                )
+                .arg(
+                    Arg::with_name("caves")
+                        .long("caves")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("water-depth-shading")
+                        .long("water-depth-shading")
+                        .takes_value(false)
+                        .required(false),
                 ),
         )
         .subcommand(
@@ -368,6 +403,18 @@ fn main() -> Result<()> {
                         .long("calculate-heights")
                         .takes_value(false)
                         .required(false),
+                )
+                .arg(
+                    Arg::with_name("caves")
+                        .long("caves")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("water-depth-shading")
+                        .long("water-depth-shading")
+                        .takes_value(false)
+                        .required(false),
                 ),
         )
         .get_matches();
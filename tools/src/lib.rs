@@ -1,5 +1,5 @@
 use fastanvil::{
-    tex::{Blockstate, Model, Render, Renderer, Texture},
+    tex::{Blockstate, Model, Renderer, Texture},
     Rgba,
 };
 use flate2::write::GzEncoder;
@@ -105,27 +105,30 @@ fn load_models(path: &Path) -> Result<HashMap<String, Model>> {
     Ok(models)
 }
 
-fn load_textures(path: &Path) -> Result<HashMap<String, Texture>> {
+fn load_textures(path: &Path, prefix: &str) -> Result<HashMap<String, Texture>> {
     let mut tex = HashMap::new();
 
     for entry in std::fs::read_dir(path)? {
         let entry = entry?;
         let path = entry.path();
 
-        if path.is_file() && path.extension().ok_or("invalid ext")?.to_string_lossy() == "png" {
+        let stem = path
+            .file_stem()
+            .ok_or(format!("invalid file name: {}", path.display()))?
+            .to_str()
+            .ok_or(format!("nonunicode file name: {}", path.display()))?;
+
+        if path.is_dir() {
+            // Entity textures are nested, eg entity/chest/normal.png.
+            tex.extend(load_textures(&path, &format!("{}{}/", prefix, stem))?);
+        } else if path.is_file()
+            && path.extension().ok_or("invalid ext")?.to_string_lossy() == "png"
+        {
             let texture = load_texture(&path);
 
             match texture {
                 Err(_) => continue,
-                Ok(texture) => tex.insert(
-                    "minecraft:block/".to_owned()
-                        + path
-                            .file_stem()
-                            .ok_or(format!("invalid file name: {}", path.display()))?
-                            .to_str()
-                            .ok_or(format!("nonunicode file name: {}", path.display()))?,
-                    texture,
-                ),
+                Ok(texture) => tex.insert(prefix.to_owned() + stem, texture),
             };
         }
     }
@@ -163,7 +166,11 @@ impl RegexMapping {
 pub fn make_palette(mc_jar_path: &Path) -> Result<()> {
     let assets = mc_jar_path.to_owned().join("assets").join("minecraft");
 
-    let textures = load_textures(&assets.join("textures").join("block"))?;
+    let mut textures = load_textures(&assets.join("textures").join("block"), "minecraft:block/")?;
+    textures.extend(load_textures(
+        &assets.join("textures").join("entity"),
+        "minecraft:entity/",
+    )?);
     let blockstates = load_blockstates(&assets.join("blockstates"))?;
     let models = load_models(&assets.join("models").join("block"))?;
 
@@ -341,7 +348,7 @@ pub fn make_palette(mc_jar_path: &Path) -> Result<()> {
         match bs {
             Blockstate::Variants(vars) => {
                 for props in vars.keys() {
-                    let res = renderer.get_top(name, props);
+                    let res = renderer.top(name, props);
                     match res {
                         Ok(texture) => {
                             let col = avg_colour(texture.as_slice());